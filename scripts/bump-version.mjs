@@ -28,13 +28,18 @@ updateJson(path.join(root, 'package.json'), (data) => ({
   version,
 }));
 
-updateText(path.join(root, 'src-tauri', 'Cargo.toml'), (raw) => {
-  const next = raw.replace(/^(version\s*=\s*")([^"]+)(")/m, `$1${version}$3`);
-  if (next === raw) {
-    throw new Error('Cargo.toml version not found');
-  }
-  return next;
-});
+for (const manifest of [
+  path.join(root, 'src-tauri', 'Cargo.toml'),
+  path.join(root, 'src-tauri', 'ralph-core', 'Cargo.toml'),
+]) {
+  updateText(manifest, (raw) => {
+    const next = raw.replace(/^(version\s*=\s*")([^"]+)(")/m, `$1${version}$3`);
+    if (next === raw) {
+      throw new Error(`${path.relative(root, manifest)} version not found`);
+    }
+    return next;
+  });
+}
 
 updateJson(path.join(root, 'src-tauri', 'tauri.conf.json'), (data) => ({
   ...data,
@@ -0,0 +1,271 @@
+use super::{
+    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, portable,
+    resolve_cli_path, CliAdapter, CommandOptions, LineType, ParsedLine, PortableCommand,
+};
+use crate::storage::models::CliType;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Sourcegraph's `amp` CLI. Every `-x` run starts a new thread, which matches
+/// Ralph's fresh-context iterations; the thread ID is logged so it can be
+/// reopened with `amp threads continue`.
+pub struct AmpAdapter {
+    path: Option<String>,
+}
+
+impl Default for AmpAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AmpAdapter {
+    pub fn new() -> Self {
+        Self::with_path(resolve_cli_path("amp"))
+    }
+
+    pub fn with_path(path: Option<String>) -> Self {
+        Self { path }
+    }
+
+    fn exec_args(prompt: &str) -> Vec<String> {
+        vec![
+            "--dangerously-allow-all".to_string(),
+            "-x".to_string(),
+            prompt.to_string(),
+            "--stream-json".to_string(),
+        ]
+    }
+
+    /// Without `--dangerously-allow-all`, execute mode rejects tools that need approval
+    fn readonly_args(prompt: &str) -> Vec<String> {
+        vec![
+            "-x".to_string(),
+            prompt.to_string(),
+            "--stream-json".to_string(),
+        ]
+    }
+
+    fn build_run_command(&self, prompt: &str, working_dir: &Path, readonly: bool) -> Command {
+        let exe = self.path.as_deref().unwrap_or("amp");
+        let args = if readonly {
+            Self::readonly_args(prompt)
+        } else {
+            Self::exec_args(prompt)
+        };
+        let mut cmd = command_for_cli(exe, &args, working_dir);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    /// Text blocks of an assistant message, with tool calls summarized
+    fn message_text(value: &Value) -> Option<String> {
+        let blocks = value.pointer("/message/content")?.as_array()?;
+        let parts: Vec<String> = blocks
+            .iter()
+            .filter_map(|block| match block.get("type").and_then(|t| t.as_str()) {
+                Some("text") => block.get("text").and_then(|t| t.as_str()).map(String::from),
+                Some("tool_use") => block
+                    .get("name")
+                    .and_then(|n| n.as_str())
+                    .map(|name| format!("[tool] {}", name)),
+                _ => None,
+            })
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    }
+}
+
+#[async_trait]
+impl CliAdapter for AmpAdapter {
+    fn name(&self) -> &str {
+        "Amp"
+    }
+
+    fn cli_type(&self) -> CliType {
+        CliType::Amp
+    }
+
+    fn is_installed(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn get_path(&self) -> Option<String> {
+        self.path.clone()
+    }
+
+    async fn version(&self) -> Option<String> {
+        let exe = self.path.as_deref().unwrap_or("amp");
+        let mut cmd = Command::new(exe);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        hide_console_window(&mut cmd);
+        let output = cmd.arg("--version").output().await.ok()?;
+
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            None
+        }
+    }
+
+    fn portable_command(&self, prompt: &str, _options: CommandOptions) -> Option<PortableCommand> {
+        Some(portable("amp", Self::exec_args(prompt)))
+    }
+
+    fn build_command(&self, prompt: &str, working_dir: &Path, _options: CommandOptions) -> Command {
+        self.build_run_command(prompt, working_dir, false)
+    }
+
+    fn build_readonly_command(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        _options: CommandOptions,
+    ) -> Command {
+        self.build_run_command(prompt, working_dir, true)
+    }
+
+    fn detect_completion(&self, output: &str, signal: &str) -> bool {
+        output.lines().any(|line| {
+            let parsed = self.parse_output_line(line);
+            parsed.is_assistant && parsed.content.contains(signal)
+        })
+    }
+
+    fn supports_image_input(&self) -> bool {
+        // Amp's Read tool renders image files
+        true
+    }
+
+    fn parse_output_line(&self, line: &str) -> ParsedLine {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            return ParsedLine {
+                content: line.to_string(),
+                line_type: LineType::Text,
+                is_assistant: false,
+                anomaly: None,
+            };
+        };
+
+        let event_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let session_id = value.get("session_id").and_then(|v| v.as_str());
+        match event_type {
+            "system" => ParsedLine {
+                content: session_id
+                    .map(|id| format!("Amp thread {}", id))
+                    .unwrap_or_default(),
+                line_type: LineType::Json,
+                is_assistant: false,
+                anomaly: None,
+            },
+            "assistant" => {
+                let content = Self::message_text(&value);
+                let anomaly = content
+                    .is_none()
+                    .then(|| "no text extracted from assistant message".to_string());
+                ParsedLine {
+                    content: content.unwrap_or_else(|| line.to_string()),
+                    line_type: LineType::Json,
+                    is_assistant: true,
+                    anomaly,
+                }
+            }
+            // Tool results echoed back to the model
+            "user" => ParsedLine {
+                content: String::new(),
+                line_type: LineType::Json,
+                is_assistant: false,
+                anomaly: None,
+            },
+            "result" => {
+                let is_error = value
+                    .get("is_error")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let content = value
+                    .get("result")
+                    .or_else(|| value.get("error"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                ParsedLine {
+                    content,
+                    line_type: if is_error {
+                        LineType::Error
+                    } else {
+                        LineType::Json
+                    },
+                    is_assistant: false,
+                    anomaly: None,
+                }
+            }
+            _ => ParsedLine {
+                content: line.to_string(),
+                line_type: LineType::Json,
+                is_assistant: false,
+                anomaly: Some(format!("unknown event type: {}", event_type)),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exec_args_allow_all_tools() {
+        assert_eq!(
+            AmpAdapter::exec_args("hello"),
+            vec!["--dangerously-allow-all", "-x", "hello", "--stream-json"]
+        );
+        assert_eq!(
+            AmpAdapter::readonly_args("hello"),
+            vec!["-x", "hello", "--stream-json"]
+        );
+    }
+
+    #[test]
+    fn parses_stream_json_events() {
+        let adapter = AmpAdapter::new();
+
+        let init = r#"{"type":"system","subtype":"init","session_id":"T-1234","tools":[]}"#;
+        let parsed = adapter.parse_output_line(init);
+        assert_eq!(parsed.content, "Amp thread T-1234");
+        assert!(!parsed.is_assistant);
+
+        let message = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Editing"},{"type":"tool_use","name":"edit_file","input":{}}]},"session_id":"T-1234"}"#;
+        let parsed = adapter.parse_output_line(message);
+        assert_eq!(parsed.content, "Editing\n[tool] edit_file");
+        assert!(parsed.is_assistant);
+        assert!(parsed.anomaly.is_none());
+
+        let failed = r#"{"type":"result","subtype":"error_during_execution","is_error":true,"error":"out of credits","session_id":"T-1234"}"#;
+        let parsed = adapter.parse_output_line(failed);
+        assert_eq!(parsed.content, "out of credits");
+        assert_eq!(parsed.line_type, LineType::Error);
+    }
+
+    #[test]
+    fn detects_completion_in_assistant_text() {
+        let adapter = AmpAdapter::new();
+        let output = [
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"<done>COMPLETE</done>"}]}}"#,
+            r#"{"type":"result","subtype":"success","result":"<done>COMPLETE</done>","is_error":false}"#,
+        ]
+        .join("\n");
+        assert!(adapter.detect_completion(&output, "<done>COMPLETE</done>"));
+        assert!(!adapter.detect_completion(
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","content":"<done>COMPLETE</done>"}]}}"#,
+            "<done>COMPLETE</done>"
+        ));
+    }
+}
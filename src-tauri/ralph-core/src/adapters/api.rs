@@ -0,0 +1,211 @@
+use super::{
+    apply_extended_path, apply_shell_env, hide_console_window, shell_env_value, CliAdapter,
+    CommandOptions, LineType, ParsedLine,
+};
+use crate::api_agent::{AGENT_FLAG, CONFIG_ENV, READONLY_FLAG};
+use crate::storage;
+use crate::storage::models::{ApiBackendConfig, ApiProvider, CliType};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::env;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Runs the built-in API agent (see `crate::api_agent`) as a child process of this app
+pub struct ApiAdapter {
+    config: ApiBackendConfig,
+}
+
+/// Configured key, else the provider's conventional environment variable
+pub fn resolve_api_key(config: &ApiBackendConfig) -> Option<String> {
+    if let Some(key) = config.api_key.as_deref().filter(|k| !k.trim().is_empty()) {
+        return Some(key.trim().to_string());
+    }
+    let var = match config.provider {
+        ApiProvider::Anthropic => "ANTHROPIC_API_KEY",
+        ApiProvider::OpenAiCompatible => "OPENAI_API_KEY",
+    };
+    env::var(var)
+        .ok()
+        .or_else(|| shell_env_value(var))
+        .filter(|k| !k.trim().is_empty())
+}
+
+impl Default for ApiAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ApiAdapter {
+    pub fn new() -> Self {
+        let config = storage::load_config()
+            .map(|c| c.api_backend)
+            .unwrap_or_default();
+        Self { config }
+    }
+
+    fn build_agent_command(&self, prompt: &str, working_dir: &Path, readonly: bool) -> Command {
+        let exe = env::current_exe().unwrap_or_else(|_| "ralph-desktop".into());
+        let mut cmd = Command::new(exe);
+        cmd.arg(AGENT_FLAG);
+        if readonly {
+            cmd.arg(READONLY_FLAG);
+        }
+        cmd.arg(prompt).current_dir(working_dir);
+
+        let mut config = self.config.clone();
+        config.api_key = resolve_api_key(&self.config);
+        if let Ok(serialized) = serde_json::to_string(&config) {
+            cmd.env(CONFIG_ENV, serialized);
+        }
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        hide_console_window(&mut cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+}
+
+#[async_trait]
+impl CliAdapter for ApiAdapter {
+    fn name(&self) -> &str {
+        "API (direct)"
+    }
+
+    fn cli_type(&self) -> CliType {
+        CliType::Api
+    }
+
+    /// Available whenever an API key can be found
+    fn is_installed(&self) -> bool {
+        resolve_api_key(&self.config).is_some()
+    }
+
+    fn get_path(&self) -> Option<String> {
+        env::current_exe()
+            .ok()
+            .map(|p| p.to_string_lossy().to_string())
+    }
+
+    async fn version(&self) -> Option<String> {
+        Some(self.config.model.clone())
+    }
+
+    fn build_command(&self, prompt: &str, working_dir: &Path, _options: CommandOptions) -> Command {
+        self.build_agent_command(prompt, working_dir, false)
+    }
+
+    fn build_readonly_command(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        _options: CommandOptions,
+    ) -> Command {
+        self.build_agent_command(prompt, working_dir, true)
+    }
+
+    fn detect_completion(&self, output: &str, signal: &str) -> bool {
+        output.lines().any(|line| {
+            let parsed = self.parse_output_line(line);
+            parsed.is_assistant && parsed.content.contains(signal)
+        })
+    }
+
+    fn parse_output_line(&self, line: &str) -> ParsedLine {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            return ParsedLine {
+                content: line.to_string(),
+                line_type: LineType::Text,
+                is_assistant: false,
+                anomaly: None,
+            };
+        };
+        let field = |key: &str| {
+            value
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let (content, line_type, is_assistant) =
+            match value.get("type").and_then(|v| v.as_str()).unwrap_or("") {
+                "text" => (field("text"), LineType::Json, true),
+                "tool_use" => (
+                    format!("[tool] {}", field("summary")),
+                    LineType::Json,
+                    false,
+                ),
+                "tool_result" => {
+                    let is_error = value
+                        .get("isError")
+                        .and_then(|v| v.as_bool())
+                        .unwrap_or(false);
+                    let line_type = if is_error {
+                        LineType::Error
+                    } else {
+                        LineType::Json
+                    };
+                    (field("output"), line_type, false)
+                }
+                "error" => (field("message"), LineType::Error, false),
+                "done" => (String::new(), LineType::Json, false),
+                other => {
+                    return ParsedLine {
+                        content: line.to_string(),
+                        line_type: LineType::Json,
+                        is_assistant: false,
+                        anomaly: Some(format!("unknown event type: {}", other)),
+                    }
+                }
+            };
+        ParsedLine {
+            content,
+            line_type,
+            is_assistant,
+            anomaly: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_agent_events() {
+        let adapter = ApiAdapter {
+            config: ApiBackendConfig::default(),
+        };
+
+        let parsed =
+            adapter.parse_output_line(r#"{"type":"text","text":"All done <done>COMPLETE</done>"}"#);
+        assert!(parsed.is_assistant);
+        assert!(adapter.detect_completion(
+            r#"{"type":"text","text":"All done <done>COMPLETE</done>"}"#,
+            "<done>COMPLETE</done>"
+        ));
+
+        let parsed =
+            adapter.parse_output_line(r#"{"type":"tool_use","summary":"bash cargo test"}"#);
+        assert_eq!(parsed.content, "[tool] bash cargo test");
+        assert!(!parsed.is_assistant);
+
+        let parsed =
+            adapter.parse_output_line(r#"{"type":"tool_result","output":"boom","isError":true}"#);
+        assert_eq!(parsed.line_type, LineType::Error);
+    }
+
+    #[test]
+    fn configured_key_wins_over_environment() {
+        let config = ApiBackendConfig {
+            api_key: Some(" sk-test ".to_string()),
+            ..ApiBackendConfig::default()
+        };
+        assert_eq!(resolve_api_key(&config).as_deref(), Some("sk-test"));
+    }
+}
@@ -1,6 +1,7 @@
 use super::{
-    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, resolve_cli_path,
-    CliAdapter, CommandOptions, LineType, ParsedLine,
+    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, portable,
+    prompt_via_stdin_with, resolve_cli_path, CliAdapter, CommandOptions, LineType, ParsedLine,
+    PortableCommand, TokenUsage,
 };
 use crate::storage::models::CliType;
 use async_trait::async_trait;
@@ -13,9 +14,18 @@ pub struct ClaudeCodeAdapter {
     path: Option<String>,
 }
 
+impl Default for ClaudeCodeAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ClaudeCodeAdapter {
     pub fn new() -> Self {
-        let path = resolve_cli_path("claude");
+        Self::with_path(resolve_cli_path("claude"))
+    }
+
+    pub fn with_path(path: Option<String>) -> Self {
         Self { path }
     }
 }
@@ -53,8 +63,27 @@ impl CliAdapter for ClaudeCodeAdapter {
         }
     }
 
-    fn build_command(&self, prompt: &str, working_dir: &Path, _options: CommandOptions) -> Command {
+    fn portable_command(&self, prompt: &str, _options: CommandOptions) -> Option<PortableCommand> {
+        let args = [
+            "--print",
+            "--dangerously-skip-permissions",
+            "--permission-mode",
+            "bypassPermissions",
+            "--verbose",
+            prompt,
+            "--output-format",
+            "stream-json",
+            "--include-partial-messages",
+        ];
+        Some(portable(
+            "claude",
+            args.iter().map(|arg| arg.to_string()).collect(),
+        ))
+    }
+
+    fn build_command(&self, prompt: &str, working_dir: &Path, options: CommandOptions) -> Command {
         let exe = self.path.as_deref().unwrap_or("claude");
+        let via_stdin = prompt_via_stdin_with(self, options);
         let mut args = vec![
             "--print".to_string(),
             "--dangerously-skip-permissions".to_string(),
@@ -62,14 +91,10 @@ impl CliAdapter for ClaudeCodeAdapter {
             "bypassPermissions".to_string(),
             "--verbose".to_string(),
         ];
-        #[cfg(target_os = "windows")]
-        {
-            let _ = prompt;
+        if via_stdin {
             args.push("--input-format".to_string());
             args.push("text".to_string());
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
+        } else {
             args.push(prompt.to_string());
         }
         args.push("--output-format".to_string());
@@ -78,14 +103,11 @@ impl CliAdapter for ClaudeCodeAdapter {
         let mut cmd = command_for_cli(exe, &args, working_dir);
         apply_extended_path(&mut cmd);
         apply_shell_env(&mut cmd);
-        #[cfg(target_os = "windows")]
-        {
-            cmd.stdin(Stdio::piped());
-        }
-        #[cfg(not(target_os = "windows"))]
-        {
-            cmd.stdin(Stdio::null());
-        }
+        cmd.stdin(if via_stdin {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        });
         cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
         cmd
     }
@@ -142,6 +164,68 @@ impl CliAdapter for ClaudeCodeAdapter {
         false
     }
 
+    fn supports_image_input(&self) -> bool {
+        // Claude Code's Read tool renders image files
+        true
+    }
+
+    fn prompt_via_stdin(&self) -> bool {
+        // Long prompts get mangled by `cmd /C` argument handling on Windows
+        cfg!(target_os = "windows")
+    }
+
+    fn has_alternate_prompt_delivery(&self) -> bool {
+        true
+    }
+
+    fn cost_usd(&self, line: &str) -> Option<f64> {
+        let value = serde_json::from_str::<Value>(line).ok()?;
+        if value.get("type").and_then(|v| v.as_str()) != Some("result") {
+            return None;
+        }
+        value
+            .get("total_cost_usd")
+            .or_else(|| value.get("cost_usd"))
+            .and_then(|v| v.as_f64())
+    }
+
+    fn token_usage(&self, line: &str) -> Option<TokenUsage> {
+        let value = serde_json::from_str::<Value>(line).ok()?;
+        if value.get("type").and_then(|v| v.as_str()) != Some("result") {
+            return None;
+        }
+        let usage = value.get("usage")?;
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        Some(TokenUsage {
+            input_tokens: count("input_tokens")
+                + count("cache_creation_input_tokens")
+                + count("cache_read_input_tokens"),
+            output_tokens: count("output_tokens"),
+            cache_read_tokens: count("cache_read_input_tokens"),
+        })
+    }
+
+    fn shell_commands(&self, line: &str) -> Vec<String> {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            return Vec::new();
+        };
+        if value.get("type").and_then(|v| v.as_str()) != Some("assistant") {
+            return Vec::new();
+        }
+        let Some(items) = value.pointer("/message/content").and_then(|v| v.as_array()) else {
+            return Vec::new();
+        };
+        items
+            .iter()
+            .filter(|item| {
+                item.get("type").and_then(|v| v.as_str()) == Some("tool_use")
+                    && item.get("name").and_then(|v| v.as_str()) == Some("Bash")
+            })
+            .filter_map(|item| item.pointer("/input/command").and_then(|v| v.as_str()))
+            .map(str::to_string)
+            .collect()
+    }
+
     fn parse_output_line(&self, line: &str) -> ParsedLine {
         // Try to parse as JSON first
         if let Ok(value) = serde_json::from_str::<Value>(line) {
@@ -157,12 +241,14 @@ impl CliAdapter for ClaudeCodeAdapter {
                     is_assistant = true;
                 }
             }
+            let mut anomaly = None;
             if content.trim().is_empty() {
                 // If content extraction failed but it's a valid JSON, use the raw line
                 // unless it's a known non-content message type
                 let event_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
                 if event_type != "ping" && event_type != "progress" {
                     content = line.to_string();
+                    anomaly = Some(format!("no text extracted from event type: {}", event_type));
                 }
             }
 
@@ -170,6 +256,7 @@ impl CliAdapter for ClaudeCodeAdapter {
                 content,
                 line_type: LineType::Json,
                 is_assistant,
+                anomaly,
             }
         } else {
             // Fallback for non-JSON lines
@@ -178,6 +265,7 @@ impl CliAdapter for ClaudeCodeAdapter {
                 content: line.to_string(),
                 line_type: LineType::Text,
                 is_assistant: false,
+                anomaly: None,
             }
         }
     }
@@ -246,7 +334,7 @@ fn join_text_array(value: &Value) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::{ClaudeCodeAdapter, LineType};
-    use crate::adapters::CliAdapter;
+    use crate::adapters::{CliAdapter, TokenUsage};
 
     #[test]
     fn parse_assistant_json_line() {
@@ -258,6 +346,44 @@ mod tests {
         assert!(parsed.is_assistant);
     }
 
+    #[test]
+    fn reads_cost_from_result_event() {
+        let adapter = ClaudeCodeAdapter::new();
+        let result =
+            r#"{"type":"result","subtype":"success","total_cost_usd":0.0421,"num_turns":6}"#;
+        assert_eq!(adapter.cost_usd(result), Some(0.0421));
+        assert_eq!(
+            adapter.cost_usd(r#"{"type":"assistant","message":{"content":[]}}"#),
+            None
+        );
+    }
+
+    #[test]
+    fn reads_usage_including_cache_tokens() {
+        let adapter = ClaudeCodeAdapter::new();
+        let result = r#"{"type":"result","usage":{"input_tokens":12,"cache_creation_input_tokens":3000,"cache_read_input_tokens":40000,"output_tokens":850}}"#;
+        assert_eq!(
+            adapter.token_usage(result),
+            Some(TokenUsage {
+                input_tokens: 43012,
+                output_tokens: 850,
+                cache_read_tokens: 40000,
+            })
+        );
+        assert_eq!(adapter.token_usage(r#"{"type":"result"}"#), None);
+    }
+
+    #[test]
+    fn reads_bash_tool_commands() {
+        let adapter = ClaudeCodeAdapter::new();
+        let line = r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Fetching"},{"type":"tool_use","name":"Bash","input":{"command":"curl -s https://example.com"}},{"type":"tool_use","name":"Read","input":{"file_path":"a.rs"}}]}}"#;
+        assert_eq!(
+            adapter.shell_commands(line),
+            vec!["curl -s https://example.com".to_string()]
+        );
+        assert!(adapter.shell_commands(r#"{"type":"result"}"#).is_empty());
+    }
+
     #[test]
     fn parse_non_json_line() {
         let adapter = ClaudeCodeAdapter::new();
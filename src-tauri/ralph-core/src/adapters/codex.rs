@@ -0,0 +1,511 @@
+use super::{
+    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, portable,
+    resolve_cli_path, CliAdapter, CommandOptions, LineType, ParsedLine, PortableCommand,
+    TokenUsage,
+};
+use crate::storage::models::CliType;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+pub struct CodexAdapter {
+    path: Option<String>,
+}
+
+impl Default for CodexAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CodexAdapter {
+    pub fn new() -> Self {
+        Self::with_path(resolve_cli_path("codex"))
+    }
+
+    pub fn with_path(path: Option<String>) -> Self {
+        Self { path }
+    }
+
+    fn exec_args(prompt: &str, options: CommandOptions) -> Vec<String> {
+        let mut args = vec![
+            "exec".to_string(),
+            "--dangerously-bypass-approvals-and-sandbox".to_string(),
+            // Structured events keep command output apart from agent messages
+            "--json".to_string(),
+        ];
+        if options.skip_git_repo_check {
+            args.push("--skip-git-repo-check".to_string());
+        }
+        args.push(prompt.to_string());
+        args
+    }
+
+    fn readonly_args(prompt: &str, options: CommandOptions) -> Vec<String> {
+        let mut args = vec![
+            "exec".to_string(),
+            "--dangerously-bypass-approvals-and-sandbox".to_string(),
+            "--json".to_string(), // Output JSONL for parsing
+        ];
+        if options.skip_git_repo_check {
+            args.push("--skip-git-repo-check".to_string());
+        }
+        args.push(prompt.to_string());
+        args
+    }
+
+    fn build_exec_command(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        readonly: bool,
+        options: CommandOptions,
+    ) -> Command {
+        let exe = self.path.as_deref().unwrap_or("codex");
+        let args = if readonly {
+            Self::readonly_args(prompt, options)
+        } else {
+            Self::exec_args(prompt, options)
+        };
+        let mut cmd = command_for_cli(exe, &args, working_dir);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+}
+
+#[async_trait]
+impl CliAdapter for CodexAdapter {
+    fn name(&self) -> &str {
+        "Codex CLI"
+    }
+
+    fn cli_type(&self) -> CliType {
+        CliType::Codex
+    }
+
+    fn is_installed(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn get_path(&self) -> Option<String> {
+        self.path.clone()
+    }
+
+    async fn version(&self) -> Option<String> {
+        let exe = self.path.as_deref().unwrap_or("codex");
+        let mut cmd = Command::new(exe);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        hide_console_window(&mut cmd);
+        let output = cmd.arg("--version").output().await.ok()?;
+
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            None
+        }
+    }
+
+    fn portable_command(&self, prompt: &str, options: CommandOptions) -> Option<PortableCommand> {
+        Some(portable("codex", Self::exec_args(prompt, options)))
+    }
+
+    fn build_command(&self, prompt: &str, working_dir: &Path, options: CommandOptions) -> Command {
+        self.build_exec_command(prompt, working_dir, false, options)
+    }
+
+    fn build_readonly_command(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        options: CommandOptions,
+    ) -> Command {
+        self.build_exec_command(prompt, working_dir, true, options)
+    }
+
+    fn detect_completion(&self, output: &str, signal: &str) -> bool {
+        // Parsed agent messages are plain text, direct detection
+        output.contains(signal)
+    }
+
+    fn token_usage(&self, line: &str) -> Option<TokenUsage> {
+        // Each turn.completed event carries that turn's usage; cached tokens are part of input_tokens
+        let json = serde_json::from_str::<Value>(line).ok()?;
+        if json.get("type").and_then(|t| t.as_str()) != Some("turn.completed") {
+            return None;
+        }
+        let usage = json.get("usage")?;
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+        Some(TokenUsage {
+            input_tokens: count("input_tokens"),
+            output_tokens: count("output_tokens"),
+            cache_read_tokens: count("cached_input_tokens"),
+        })
+    }
+
+    fn shell_commands(&self, line: &str) -> Vec<String> {
+        // Commands are reported when they start and again when they complete
+        let Ok(json) = serde_json::from_str::<Value>(line) else {
+            return Vec::new();
+        };
+        if json.get("type").and_then(|t| t.as_str()) != Some("item.started")
+            || json.pointer("/item/type").and_then(|t| t.as_str()) != Some("command_execution")
+        {
+            return Vec::new();
+        }
+        json.pointer("/item/command")
+            .and_then(|c| c.as_str())
+            .map(|c| vec![c.to_string()])
+            .unwrap_or_default()
+    }
+
+    fn parse_output_line(&self, line: &str) -> ParsedLine {
+        // Codex --json outputs JSONL: agent messages are the answer, command
+        // executions and edits are activity, failures are errors
+        let Ok(json) = serde_json::from_str::<Value>(line) else {
+            // Not JSON, treat as plain text (fallback for non --json mode)
+            return parsed(line.to_string(), LineType::Text, true);
+        };
+        let event_type = json.get("type").and_then(|t| t.as_str()).unwrap_or("");
+
+        match event_type {
+            "item.completed" => {
+                let item = json.get("item").unwrap_or(&Value::Null);
+                let item_type = item.get("type").and_then(|t| t.as_str()).unwrap_or("");
+                match item_type {
+                    "agent_message" => match item.get("text").and_then(|t| t.as_str()) {
+                        Some(text) => parsed(text.to_string(), LineType::Json, true),
+                        None => ParsedLine {
+                            anomaly: Some("item.completed without extractable text".to_string()),
+                            ..parsed(String::new(), LineType::Json, false)
+                        },
+                    },
+                    "command_execution" | "file_change" | "mcp_tool_call" | "web_search" => {
+                        parsed(describe_activity(item_type, item), LineType::Tool, false)
+                    }
+                    "error" => parsed(
+                        error_message(item).unwrap_or("Unknown error").to_string(),
+                        LineType::Error,
+                        false,
+                    ),
+                    // Reasoning summaries and plan updates
+                    "reasoning" | "todo_list" => parsed(String::new(), LineType::Json, false),
+                    "" => ParsedLine {
+                        anomaly: Some("item.completed without extractable text".to_string()),
+                        ..parsed(String::new(), LineType::Json, false)
+                    },
+                    other => ParsedLine {
+                        anomaly: Some(format!("unknown item type: {}", other)),
+                        ..parsed(String::new(), LineType::Json, false)
+                    },
+                }
+            }
+            // Control and lifecycle events; completed items carry the content
+            "thread.started" | "turn.started" | "turn.completed" | "item.delta"
+            | "item.started" | "item.updated" | "session.started" | "session.completed" => {
+                parsed(String::new(), LineType::Json, false)
+            }
+            "turn.failed" | "error" => parsed(
+                error_message(&json).unwrap_or("Unknown error").to_string(),
+                LineType::Error,
+                false,
+            ),
+            // No type field - this might be direct JSON response (Loop mode or mock)
+            "" => parsed(line.to_string(), LineType::Text, true),
+            // Unknown event type - skip to avoid polluting output
+            _ => ParsedLine {
+                anomaly: Some(format!("unknown event type: {}", event_type)),
+                ..parsed(String::new(), LineType::Json, false)
+            },
+        }
+    }
+}
+
+fn parsed(content: String, line_type: LineType, is_assistant: bool) -> ParsedLine {
+    ParsedLine {
+        content,
+        line_type,
+        is_assistant,
+        anomaly: None,
+    }
+}
+
+/// `message` of an error event or item, directly or under `error`
+fn error_message(value: &Value) -> Option<&str> {
+    value
+        .get("message")
+        .or_else(|| value.pointer("/error/message"))
+        .and_then(|m| m.as_str())
+}
+
+/// One-line summary of a completed tool item, with the output of failed commands
+fn describe_activity(item_type: &str, item: &Value) -> String {
+    let field = |key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    match item_type {
+        "command_execution" => {
+            let mut text = format!("$ {}", field("command"));
+            let exit_code = item.get("exit_code").and_then(|c| c.as_i64());
+            if let Some(code) = exit_code.filter(|code| *code != 0) {
+                text.push_str(&format!(" (exit code {})", code));
+                let output = field("aggregated_output").trim_end();
+                if !output.is_empty() {
+                    text.push('\n');
+                    text.push_str(output);
+                }
+            }
+            text
+        }
+        "file_change" => {
+            let changes: Vec<String> = item
+                .get("changes")
+                .and_then(|c| c.as_array())
+                .map(|changes| {
+                    changes
+                        .iter()
+                        .map(|change| {
+                            let value = |key: &str| change.get(key).and_then(|v| v.as_str());
+                            let kind = value("kind").unwrap_or("update");
+                            format!("{} {}", kind, value("path").unwrap_or(""))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            format!("[edit] {}", changes.join(", "))
+        }
+        "mcp_tool_call" => format!("[tool] {}.{}", field("server"), field("tool")),
+        _ => format!("[search] {}", field("query")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CodexAdapter;
+    use super::CommandOptions;
+    use crate::adapters::CliAdapter;
+
+    #[test]
+    fn exec_args_include_exec_and_full_auto() {
+        let args = CodexAdapter::exec_args("hello", CommandOptions::default());
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "--dangerously-bypass-approvals-and-sandbox",
+                "--json",
+                "hello"
+            ]
+        );
+    }
+
+    #[test]
+    fn readonly_args_use_read_only_sandbox() {
+        let args = CodexAdapter::readonly_args("hello", CommandOptions::default());
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "--dangerously-bypass-approvals-and-sandbox",
+                "--json",
+                "hello"
+            ]
+        );
+    }
+
+    #[test]
+    fn exec_args_include_skip_git_repo_check() {
+        let args = CodexAdapter::exec_args(
+            "hello",
+            CommandOptions {
+                skip_git_repo_check: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "--dangerously-bypass-approvals-and-sandbox",
+                "--json",
+                "--skip-git-repo-check",
+                "hello"
+            ]
+        );
+    }
+
+    #[test]
+    fn readonly_args_include_skip_git_repo_check() {
+        let args = CodexAdapter::readonly_args(
+            "hello",
+            CommandOptions {
+                skip_git_repo_check: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "--dangerously-bypass-approvals-and-sandbox",
+                "--json",
+                "--skip-git-repo-check",
+                "hello"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_output_line_extracts_agent_message_text() {
+        let adapter = CodexAdapter::new();
+        let line =
+            r#"{"type":"item.completed","item":{"type":"agent_message","text":"Hello world"}}"#;
+        let parsed = adapter.parse_output_line(line);
+        assert_eq!(parsed.content, "Hello world");
+        assert_eq!(parsed.line_type, super::LineType::Json);
+        assert!(parsed.is_assistant);
+    }
+
+    #[test]
+    fn parse_output_line_skips_control_events() {
+        let adapter = CodexAdapter::new();
+        // All known control/lifecycle events should be skipped
+        for event in [
+            "thread.started",
+            "turn.started",
+            "turn.completed",
+            "item.delta",
+            "item.started",
+            "item.updated",
+            "session.started",
+            "session.completed",
+        ] {
+            let line = format!(r#"{{"type":"{}"}}"#, event);
+            let parsed = adapter.parse_output_line(&line);
+            assert!(parsed.content.is_empty(), "should skip {}", event);
+            assert!(
+                !parsed.is_assistant,
+                "should not mark {} as assistant",
+                event
+            );
+        }
+    }
+
+    #[test]
+    fn parse_output_line_reports_error_events_as_errors() {
+        let adapter = CodexAdapter::new();
+        let turn_failed = r#"{"type":"turn.failed","error":{"message":"Rate limit exceeded"}}"#;
+        let parsed = adapter.parse_output_line(turn_failed);
+        assert_eq!(parsed.content, "Rate limit exceeded");
+        assert_eq!(parsed.line_type, super::LineType::Error);
+        assert!(!parsed.is_assistant);
+
+        let error_event = r#"{"type":"error","message":"Connection failed"}"#;
+        let parsed = adapter.parse_output_line(error_event);
+        assert_eq!(parsed.content, "Connection failed");
+        assert_eq!(parsed.line_type, super::LineType::Error);
+    }
+
+    #[test]
+    fn parse_output_line_keeps_command_output_out_of_assistant_text() {
+        let adapter = CodexAdapter::new();
+        // A command echoing the completion signal must not complete the loop
+        let line = r#"{"type":"item.completed","item":{"id":"item_2","type":"command_execution","command":"bash -lc 'grep -r COMPLETE .'","aggregated_output":"prompt.md:<done>COMPLETE</done>\n","exit_code":0,"status":"completed"}}"#;
+        let parsed = adapter.parse_output_line(line);
+        assert_eq!(parsed.content, "$ bash -lc 'grep -r COMPLETE .'");
+        assert_eq!(parsed.line_type, super::LineType::Tool);
+        assert!(!parsed.is_assistant);
+
+        let failed = r#"{"type":"item.completed","item":{"type":"command_execution","command":"npm test","aggregated_output":"1 failing\n","exit_code":1}}"#;
+        assert_eq!(
+            adapter.parse_output_line(failed).content,
+            "$ npm test (exit code 1)\n1 failing"
+        );
+
+        let edit = r#"{"type":"item.completed","item":{"type":"file_change","changes":[{"path":"src/main.rs","kind":"update"},{"path":"README.md","kind":"add"}]}}"#;
+        let parsed = adapter.parse_output_line(edit);
+        assert_eq!(parsed.content, "[edit] update src/main.rs, add README.md");
+        assert!(!parsed.is_assistant);
+    }
+
+    #[test]
+    fn parse_output_line_skips_unknown_typed_events() {
+        let adapter = CodexAdapter::new();
+        // Unknown events WITH a type field should be skipped
+        let line = r#"{"type":"future.new.event","data":"something"}"#;
+        let parsed = adapter.parse_output_line(line);
+        assert!(
+            parsed.content.is_empty(),
+            "unknown typed event should be skipped"
+        );
+        assert!(!parsed.is_assistant);
+    }
+
+    #[test]
+    fn parse_output_line_flags_unknown_events_as_anomalies() {
+        let adapter = CodexAdapter::new();
+        let parsed = adapter.parse_output_line(r#"{"type":"future.new.event"}"#);
+        assert_eq!(
+            parsed.anomaly.as_deref(),
+            Some("unknown event type: future.new.event")
+        );
+
+        let parsed = adapter.parse_output_line(r#"{"type":"turn.started"}"#);
+        assert!(parsed.anomaly.is_none());
+    }
+
+    #[test]
+    fn parse_output_line_passes_through_typeless_json() {
+        let adapter = CodexAdapter::new();
+        // JSON without "type" field (like mock data or Loop mode) should pass through
+        let line = r#"{"question":"Hi","options":[]}"#;
+        let parsed = adapter.parse_output_line(line);
+        assert_eq!(parsed.content, line);
+        assert_eq!(parsed.line_type, super::LineType::Text);
+        assert!(parsed.is_assistant);
+    }
+
+    #[test]
+    fn parse_output_line_handles_plain_text() {
+        let adapter = CodexAdapter::new();
+        let line = "Hello world";
+        let parsed = adapter.parse_output_line(line);
+        assert_eq!(parsed.content, "Hello world");
+        assert_eq!(parsed.line_type, super::LineType::Text);
+    }
+
+    #[test]
+    fn parse_output_line_preserves_completion_signal() {
+        let adapter = CodexAdapter::new();
+        let line = "<done>COMPLETE</done>";
+        let parsed = adapter.parse_output_line(line);
+        assert_eq!(parsed.content, "<done>COMPLETE</done>");
+        assert!(parsed.is_assistant);
+    }
+
+    #[test]
+    fn token_usage_reads_turn_completed() {
+        let adapter = CodexAdapter::new();
+        let line = r#"{"type":"turn.completed","usage":{"input_tokens":24763,"cached_input_tokens":24448,"output_tokens":122}}"#;
+        let usage = adapter.token_usage(line).unwrap();
+        assert_eq!(usage.input_tokens, 24763);
+        assert_eq!(usage.output_tokens, 122);
+        assert!(adapter.token_usage(r#"{"type":"turn.started"}"#).is_none());
+    }
+
+    #[test]
+    fn shell_commands_read_started_command_executions() {
+        let adapter = CodexAdapter::new();
+        let started = r#"{"type":"item.started","item":{"id":"item_1","type":"command_execution","command":"bash -lc 'npm install left-pad'","status":"in_progress"}}"#;
+        assert_eq!(
+            adapter.shell_commands(started),
+            vec!["bash -lc 'npm install left-pad'".to_string()]
+        );
+        let completed = started.replace("item.started", "item.completed");
+        assert!(adapter.shell_commands(&completed).is_empty());
+    }
+}
@@ -0,0 +1,251 @@
+use super::{
+    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, portable,
+    resolve_cli_path, CliAdapter, CommandOptions, LineType, ParsedLine, PortableCommand,
+};
+use crate::storage;
+use crate::storage::models::{CliType, CustomCliConfig, CustomOutputFormat};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Agent CLI described entirely by `GlobalConfig::custom_cli`, for tools
+/// without a dedicated adapter
+pub struct CustomAdapter {
+    config: CustomCliConfig,
+    path: Option<String>,
+}
+
+impl Default for CustomAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CustomAdapter {
+    pub fn new() -> Self {
+        let config = storage::load_config()
+            .map(|c| c.custom_cli)
+            .unwrap_or_default();
+        Self::with_config(config)
+    }
+
+    /// Configured CLI run from `path` instead of its configured executable
+    pub fn with_path(path: String) -> Self {
+        let mut adapter = Self::new();
+        adapter.path = Some(path);
+        adapter
+    }
+
+    fn with_config(config: CustomCliConfig) -> Self {
+        let executable = config.executable.trim();
+        let path = (!executable.is_empty())
+            .then(|| resolve_cli_path(executable))
+            .flatten();
+        Self { config, path }
+    }
+
+    /// Arguments with `{prompt}` and `{cwd}` filled in
+    fn expand_args(args: &[String], prompt: &str, working_dir: &Path) -> Vec<String> {
+        let cwd = working_dir.to_string_lossy();
+        args.iter()
+            .map(|arg| arg.replace("{cwd}", &cwd).replace("{prompt}", prompt))
+            .collect()
+    }
+
+    fn build_run_command(&self, prompt: &str, working_dir: &Path, readonly: bool) -> Command {
+        let exe = self
+            .path
+            .as_deref()
+            .unwrap_or_else(|| self.config.executable.trim());
+        let args = match (&self.config.readonly_args, readonly) {
+            (Some(readonly_args), true) => readonly_args,
+            _ => &self.config.args,
+        };
+        let mut cmd = command_for_cli(
+            exe,
+            &Self::expand_args(args, prompt, working_dir),
+            working_dir,
+        );
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        if self.config.prompt_via_stdin {
+            cmd.stdin(Stdio::piped());
+        } else {
+            cmd.stdin(Stdio::null());
+        }
+        cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+        cmd
+    }
+
+    /// First configured pointer that resolves to text
+    fn pointer_text(&self, value: &Value) -> Option<String> {
+        self.config.text_pointers.iter().find_map(|pointer| {
+            match value.pointer(pointer.trim())? {
+                Value::String(text) => Some(text.clone()),
+                Value::Null => None,
+                other => Some(other.to_string()),
+            }
+        })
+    }
+}
+
+#[async_trait]
+impl CliAdapter for CustomAdapter {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    fn cli_type(&self) -> CliType {
+        CliType::Custom
+    }
+
+    fn is_installed(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn get_path(&self) -> Option<String> {
+        self.path.clone()
+    }
+
+    async fn version(&self) -> Option<String> {
+        let exe = self.path.as_deref()?;
+        let mut cmd = Command::new(exe);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        hide_console_window(&mut cmd);
+        let output = cmd.args(&self.config.version_args).output().await.ok()?;
+
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        (output.status.success() && !version.is_empty()).then_some(version)
+    }
+
+    /// Only plain argument templates can run elsewhere; stdin and `{cwd}` depend on this host
+    fn portable_command(&self, prompt: &str, _options: CommandOptions) -> Option<PortableCommand> {
+        if self.config.prompt_via_stdin || self.config.args.iter().any(|a| a.contains("{cwd}")) {
+            return None;
+        }
+        let args = Self::expand_args(&self.config.args, prompt, Path::new("."));
+        Some(portable(self.config.executable.trim(), args))
+    }
+
+    fn build_command(&self, prompt: &str, working_dir: &Path, _options: CommandOptions) -> Command {
+        self.build_run_command(prompt, working_dir, false)
+    }
+
+    fn build_readonly_command(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        _options: CommandOptions,
+    ) -> Command {
+        self.build_run_command(prompt, working_dir, true)
+    }
+
+    fn detect_completion(&self, output: &str, signal: &str) -> bool {
+        output.lines().any(|line| {
+            let parsed = self.parse_output_line(line);
+            parsed.is_assistant && parsed.content.contains(signal)
+        })
+    }
+
+    fn prompt_via_stdin(&self) -> bool {
+        self.config.prompt_via_stdin
+    }
+
+    fn parse_output_line(&self, line: &str) -> ParsedLine {
+        if self.config.output_format == CustomOutputFormat::Text {
+            return ParsedLine {
+                content: line.to_string(),
+                line_type: LineType::Text,
+                is_assistant: true,
+                anomaly: None,
+            };
+        }
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            return ParsedLine {
+                content: line.to_string(),
+                line_type: LineType::Text,
+                is_assistant: false,
+                anomaly: None,
+            };
+        };
+        match self.pointer_text(&value) {
+            Some(content) => ParsedLine {
+                content,
+                line_type: LineType::Json,
+                is_assistant: true,
+                anomaly: None,
+            },
+            None => ParsedLine {
+                content: String::new(),
+                line_type: LineType::Json,
+                is_assistant: false,
+                anomaly: Some("no text at the configured JSON pointers".to_string()),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adapter(output_format: CustomOutputFormat, text_pointers: &[&str]) -> CustomAdapter {
+        CustomAdapter::with_config(CustomCliConfig {
+            executable: "my-agent".to_string(),
+            args: vec![
+                "run".to_string(),
+                "--dir={cwd}".to_string(),
+                "{prompt}".to_string(),
+            ],
+            output_format,
+            text_pointers: text_pointers.iter().map(|p| p.to_string()).collect(),
+            ..CustomCliConfig::default()
+        })
+    }
+
+    #[test]
+    fn fills_placeholders_in_argument_template() {
+        let adapter = adapter(CustomOutputFormat::Text, &[]);
+        assert_eq!(
+            CustomAdapter::expand_args(&adapter.config.args, "fix it", Path::new("/repo")),
+            vec!["run", "--dir=/repo", "fix it"]
+        );
+        // `{cwd}` only means something on this host
+        assert!(adapter
+            .portable_command("fix it", CommandOptions::default())
+            .is_none());
+    }
+
+    #[test]
+    fn text_output_is_all_agent_text() {
+        let adapter = adapter(CustomOutputFormat::Text, &[]);
+        let parsed = adapter.parse_output_line("Working on it");
+        assert_eq!(parsed.content, "Working on it");
+        assert!(parsed.is_assistant);
+        assert!(adapter.detect_completion("a\n<done>COMPLETE</done>\n", "<done>COMPLETE</done>"));
+    }
+
+    #[test]
+    fn jsonl_output_reads_text_through_pointers() {
+        let adapter = adapter(CustomOutputFormat::Jsonl, &["/delta/text", "/message"]);
+
+        let parsed = adapter.parse_output_line(r#"{"delta":{"text":"Editing"}}"#);
+        assert_eq!(parsed.content, "Editing");
+        assert!(parsed.is_assistant);
+
+        let parsed = adapter.parse_output_line(r#"{"message":"Done"}"#);
+        assert_eq!(parsed.content, "Done");
+
+        let parsed = adapter.parse_output_line(r#"{"type":"tool","name":"bash"}"#);
+        assert!(parsed.content.is_empty());
+        assert!(!parsed.is_assistant);
+        assert!(parsed.anomaly.is_some());
+
+        let parsed = adapter.parse_output_line("plain stderr-ish text");
+        assert_eq!(parsed.line_type, LineType::Text);
+        assert!(!parsed.is_assistant);
+    }
+}
@@ -0,0 +1,318 @@
+//! Gemini CLI and the CLIs forked from it share one interface (`-p` prompt,
+//! `--yolo`), so one adapter drives all of them from a table of per-fork differences.
+
+use super::{
+    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, portable,
+    resolve_cli_path, CliAdapter, CommandOptions, LineType, ParsedLine, PortableCommand,
+};
+use crate::storage::models::CliType;
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// How one Gemini CLI fork differs from the others
+#[derive(Debug)]
+pub struct ForkSpec {
+    pub cli_type: CliType,
+    pub name: &'static str,
+    pub binary: &'static str,
+    /// Flag that takes the prompt in non-interactive mode
+    pub prompt_flag: &'static str,
+    /// Flags granting full tool permissions for loop iterations
+    pub full_access_args: &'static [&'static str],
+    /// Flags for brainstorm runs that must not modify the project
+    pub readonly_args: &'static [&'static str],
+    /// Flags selecting line-delimited JSON output, if the fork supports it
+    pub stream_json_args: &'static [&'static str],
+}
+
+/// Supported forks; adding another is a new entry here plus a `CliType` variant
+pub const FORKS: &[ForkSpec] = &[
+    ForkSpec {
+        cli_type: CliType::Gemini,
+        name: "Gemini CLI",
+        binary: "gemini",
+        prompt_flag: "-p",
+        full_access_args: &["--yolo"],
+        // Without `--yolo`, tool calls needing approval are declined in `-p` mode
+        readonly_args: &["--approval-mode", "default"],
+        stream_json_args: &["--output-format", "stream-json"],
+    },
+    ForkSpec {
+        cli_type: CliType::QwenCode,
+        name: "Qwen Code",
+        binary: "qwen",
+        prompt_flag: "-p",
+        full_access_args: &["--yolo"],
+        readonly_args: &["--approval-mode", "plan"],
+        stream_json_args: &["--output-format", "stream-json"],
+    },
+    ForkSpec {
+        cli_type: CliType::Iflow,
+        name: "iFlow CLI",
+        binary: "iflow",
+        prompt_flag: "-p",
+        full_access_args: &["--yolo"],
+        readonly_args: &[],
+        stream_json_args: &[],
+    },
+];
+
+/// Fork spec for a CLI type, if it belongs to the Gemini family
+pub fn fork_spec(cli_type: CliType) -> Option<&'static ForkSpec> {
+    FORKS.iter().find(|spec| spec.cli_type == cli_type)
+}
+
+pub struct GeminiFamilyAdapter {
+    spec: &'static ForkSpec,
+    path: Option<String>,
+}
+
+impl GeminiFamilyAdapter {
+    pub fn new(spec: &'static ForkSpec) -> Self {
+        Self::with_path(spec, resolve_cli_path(spec.binary))
+    }
+
+    pub fn with_path(spec: &'static ForkSpec, path: Option<String>) -> Self {
+        Self { spec, path }
+    }
+
+    fn args(spec: &ForkSpec, prompt: &str, readonly: bool) -> Vec<String> {
+        let mode_args = if readonly {
+            spec.readonly_args
+        } else {
+            spec.full_access_args
+        };
+        let mut args: Vec<String> = mode_args
+            .iter()
+            .chain(spec.stream_json_args)
+            .map(|arg| arg.to_string())
+            .collect();
+        args.push(spec.prompt_flag.to_string());
+        args.push(prompt.to_string());
+        args
+    }
+
+    fn build_run_command(&self, prompt: &str, working_dir: &Path, readonly: bool) -> Command {
+        let exe = self.path.as_deref().unwrap_or(self.spec.binary);
+        let args = Self::args(self.spec, prompt, readonly);
+        let mut cmd = command_for_cli(exe, &args, working_dir);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        cmd
+    }
+
+    /// Assistant text from either the Gemini-style `message` event or the
+    /// Claude-style `assistant` event some forks emit instead
+    fn extract_text(value: &Value) -> Option<String> {
+        if let Some(text) = value.get("content").and_then(|v| v.as_str()) {
+            return Some(text.to_string());
+        }
+        if let Some(blocks) = value.pointer("/message/content").and_then(|v| v.as_array()) {
+            let text: Vec<&str> = blocks
+                .iter()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+                .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+                .collect();
+            if !text.is_empty() {
+                return Some(text.join(""));
+            }
+        }
+        if let Some(text) = value.get("result").and_then(|v| v.as_str()) {
+            return Some(text.to_string());
+        }
+        if let Some(text) = value.pointer("/error/message").and_then(|v| v.as_str()) {
+            return Some(text.to_string());
+        }
+        value
+            .get("message")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    }
+}
+
+#[async_trait]
+impl CliAdapter for GeminiFamilyAdapter {
+    fn name(&self) -> &str {
+        self.spec.name
+    }
+
+    fn cli_type(&self) -> CliType {
+        self.spec.cli_type
+    }
+
+    fn is_installed(&self) -> bool {
+        self.path.is_some()
+    }
+
+    fn get_path(&self) -> Option<String> {
+        self.path.clone()
+    }
+
+    async fn version(&self) -> Option<String> {
+        let exe = self.path.as_deref().unwrap_or(self.spec.binary);
+        let mut cmd = Command::new(exe);
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        hide_console_window(&mut cmd);
+        let output = cmd.arg("--version").output().await.ok()?;
+
+        if output.status.success() {
+            Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            None
+        }
+    }
+
+    fn portable_command(&self, prompt: &str, _options: CommandOptions) -> Option<PortableCommand> {
+        Some(portable(
+            self.spec.binary,
+            Self::args(self.spec, prompt, false),
+        ))
+    }
+
+    fn build_command(&self, prompt: &str, working_dir: &Path, _options: CommandOptions) -> Command {
+        self.build_run_command(prompt, working_dir, false)
+    }
+
+    fn build_readonly_command(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        _options: CommandOptions,
+    ) -> Command {
+        self.build_run_command(prompt, working_dir, true)
+    }
+
+    fn detect_completion(&self, output: &str, signal: &str) -> bool {
+        output.contains(signal)
+    }
+
+    fn parse_output_line(&self, line: &str) -> ParsedLine {
+        let Ok(value) = serde_json::from_str::<Value>(line) else {
+            return ParsedLine {
+                content: line.to_string(),
+                line_type: LineType::Text,
+                is_assistant: true,
+                anomaly: None,
+            };
+        };
+
+        let event_type = value.get("type").and_then(|v| v.as_str()).unwrap_or("");
+        let role = value.get("role").and_then(|v| v.as_str());
+        match event_type {
+            "message" if role == Some("assistant") => ParsedLine {
+                content: Self::extract_text(&value).unwrap_or_default(),
+                line_type: LineType::Json,
+                is_assistant: true,
+                anomaly: None,
+            },
+            "assistant" => ParsedLine {
+                content: Self::extract_text(&value).unwrap_or_default(),
+                line_type: LineType::Json,
+                is_assistant: true,
+                anomaly: None,
+            },
+            "error" => ParsedLine {
+                content: Self::extract_text(&value).unwrap_or_else(|| line.to_string()),
+                line_type: LineType::Error,
+                is_assistant: false,
+                anomaly: None,
+            },
+            _ => {
+                let extracted = Self::extract_text(&value);
+                let anomaly = (extracted.is_none() && !is_known_event(event_type))
+                    .then(|| format!("no text extracted from event type: {}", event_type));
+                ParsedLine {
+                    content: extracted.unwrap_or_else(|| line.to_string()),
+                    line_type: LineType::Json,
+                    is_assistant: false,
+                    anomaly,
+                }
+            }
+        }
+    }
+}
+
+/// Bookkeeping events that carry no text by design
+fn is_known_event(event_type: &str) -> bool {
+    matches!(
+        event_type,
+        "init" | "system" | "tool_use" | "tool_result" | "user" | "result"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_come_from_fork_spec() {
+        let qwen = fork_spec(CliType::QwenCode).unwrap();
+        assert_eq!(
+            GeminiFamilyAdapter::args(qwen, "hello", false),
+            vec!["--yolo", "--output-format", "stream-json", "-p", "hello"]
+        );
+        assert_eq!(
+            GeminiFamilyAdapter::args(qwen, "hello", true),
+            vec![
+                "--approval-mode",
+                "plan",
+                "--output-format",
+                "stream-json",
+                "-p",
+                "hello"
+            ]
+        );
+
+        let iflow = fork_spec(CliType::Iflow).unwrap();
+        assert_eq!(
+            GeminiFamilyAdapter::args(iflow, "hello", false),
+            vec!["--yolo", "-p", "hello"]
+        );
+        let gemini = fork_spec(CliType::Gemini).unwrap();
+        assert_eq!(gemini.binary, "gemini");
+        assert_eq!(
+            GeminiFamilyAdapter::args(gemini, "hello", true),
+            vec![
+                "--approval-mode",
+                "default",
+                "--output-format",
+                "stream-json",
+                "-p",
+                "hello"
+            ]
+        );
+        assert!(fork_spec(CliType::Claude).is_none());
+    }
+
+    #[test]
+    fn parses_both_stream_json_styles() {
+        let adapter = GeminiFamilyAdapter::new(fork_spec(CliType::QwenCode).unwrap());
+
+        let gemini_style = r#"{"type":"message","role":"assistant","content":"Hi","delta":true}"#;
+        let parsed = adapter.parse_output_line(gemini_style);
+        assert_eq!(parsed.content, "Hi");
+        assert!(parsed.is_assistant);
+
+        let claude_style =
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Done"}]}}"#;
+        let parsed = adapter.parse_output_line(claude_style);
+        assert_eq!(parsed.content, "Done");
+        assert!(parsed.is_assistant);
+
+        let error = r#"{"type":"error","error":{"message":"quota exceeded"}}"#;
+        let parsed = adapter.parse_output_line(error);
+        assert_eq!(parsed.content, "quota exceeded");
+        assert_eq!(parsed.line_type, LineType::Error);
+
+        let parsed = adapter.parse_output_line("plain text reply");
+        assert_eq!(parsed.line_type, LineType::Text);
+        assert!(parsed.is_assistant);
+    }
+}
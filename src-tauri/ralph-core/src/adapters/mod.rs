@@ -7,12 +7,12 @@ use std::fs;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::OnceLock;
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 
-
 /// Windows flag to prevent console window from appearing
 #[cfg(target_os = "windows")]
-const CREATE_NO_WINDOW: u32 = 0x08000000;
+pub(crate) const CREATE_NO_WINDOW: u32 = 0x08000000;
 
 /// Apply CREATE_NO_WINDOW flag to hide console window on Windows
 #[cfg(target_os = "windows")]
@@ -26,8 +26,12 @@ pub fn hide_console_window(_cmd: &mut Command) {
     // No-op
 }
 
+pub mod amp;
+pub mod api;
 pub mod claude;
 pub mod codex;
+pub mod custom;
+pub mod gemini_family;
 pub mod opencode;
 
 /// Parsed output line from CLI
@@ -37,6 +41,8 @@ pub struct ParsedLine {
     pub content: String,
     pub line_type: LineType,
     pub is_assistant: bool,
+    /// Why parsing fell short (unknown event, nothing extracted), for local diagnostics
+    pub anomaly: Option<String>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -44,11 +50,35 @@ pub enum LineType {
     Text,
     Json,
     Error,
+    /// Agent activity such as commands run or files edited: shown in the loop
+    /// output but never part of the answer
+    Tool,
+}
+
+/// A loop command independent of this host's paths and shell, for running the CLI elsewhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortableCommand {
+    /// Binary name (resolved on the target's PATH) followed by its arguments
+    pub argv: Vec<String>,
+    /// Environment the CLI needs beyond forwarded credentials
+    pub env: Vec<(String, String)>,
+}
+
+/// Tokens a CLI reported using; input includes cached prompt tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Part of `input_tokens` read from the prompt cache
+    pub cache_read_tokens: u64,
 }
 
 #[derive(Debug, Clone, Copy, Default)]
 pub struct CommandOptions {
     pub skip_git_repo_check: bool,
+    /// Deliver the prompt the other way round (argument or stdin) for adapters
+    /// that support both, after a spawn hung with the usual one
+    pub alternate_prompt_delivery: bool,
 }
 
 /// CLI adapter trait for different CLI implementations
@@ -86,6 +116,85 @@ pub trait CliAdapter: Send + Sync {
 
     /// Parse a single output line
     fn parse_output_line(&self, line: &str) -> ParsedLine;
+
+    /// Whether the CLI can open image files referenced in the prompt
+    fn supports_image_input(&self) -> bool {
+        false
+    }
+
+    /// Whether the prompt is written to the CLI's stdin instead of passed as an argument
+    fn prompt_via_stdin(&self) -> bool {
+        false
+    }
+
+    /// Whether the CLI also takes the prompt the other way than `prompt_via_stdin` says
+    fn has_alternate_prompt_delivery(&self) -> bool {
+        false
+    }
+
+    /// Loop command for running the CLI off-host (e.g. in a container); `None` if unsupported
+    fn portable_command(&self, _prompt: &str, _options: CommandOptions) -> Option<PortableCommand> {
+        None
+    }
+
+    /// Spend reported by an output line, for CLIs that report it
+    fn cost_usd(&self, _line: &str) -> Option<f64> {
+        None
+    }
+
+    /// Token usage reported by an output line, for CLIs that report it
+    fn token_usage(&self, _line: &str) -> Option<TokenUsage> {
+        None
+    }
+
+    /// Shell commands the agent starts on an output line, for CLIs that report them
+    fn shell_commands(&self, _line: &str) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Whether the prompt goes to stdin for a command built with `options`
+pub fn prompt_via_stdin_with(adapter: &dyn CliAdapter, options: CommandOptions) -> bool {
+    adapter.prompt_via_stdin()
+        != (options.alternate_prompt_delivery && adapter.has_alternate_prompt_delivery())
+}
+
+/// Run a one-shot command to completion, writing the prompt to stdin for CLIs that read it there
+pub async fn output_with_prompt(
+    adapter: &dyn CliAdapter,
+    mut cmd: Command,
+    prompt: &str,
+) -> Result<std::process::Output, String> {
+    if !adapter.prompt_via_stdin() {
+        return cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run CLI: {}", e));
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run CLI: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let written = async {
+            stdin.write_all(prompt.as_bytes()).await?;
+            stdin.write_all(b"\n").await
+        }
+        .await;
+        written.map_err(|e| format!("Failed to write {} prompt: {}", adapter.name(), e))?;
+    }
+    child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("Failed to run CLI: {}", e))
+}
+
+fn portable(binary: &str, args: Vec<String>) -> PortableCommand {
+    let mut argv = vec![binary.to_string()];
+    argv.extend(args);
+    PortableCommand {
+        argv,
+        env: Vec::new(),
+    }
 }
 
 fn push_path(paths: &mut Vec<PathBuf>, path: PathBuf) {
@@ -301,6 +410,17 @@ pub fn shell_env_value(key: &str) -> Option<String> {
     shell_env().get(key).cloned()
 }
 
+/// Names of variables set in this process or the login shell environment
+pub fn env_keys() -> Vec<String> {
+    let mut keys: Vec<String> = env::vars_os()
+        .filter_map(|(key, _)| key.into_string().ok())
+        .chain(shell_env().keys().cloned())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
 fn env_key_is_set(key: &str, shell_envs: &HashMap<String, String>) -> bool {
     shell_envs.get(key).is_some() || env::var(key).is_ok()
 }
@@ -405,6 +525,7 @@ pub fn apply_shell_env(cmd: &mut Command) {
             || key.starts_with("CODEX_")
             || key.starts_with("CLAUDE_")
             || key.starts_with("ANTHROPIC_")
+            || key.starts_with("AMP_")
         {
             cmd.env(key, value);
         }
@@ -446,11 +567,18 @@ pub fn resolve_cli_path(binary: &str) -> Option<String> {
 
 /// Get all available CLI adapters
 pub fn get_adapters() -> Vec<Box<dyn CliAdapter>> {
-    vec![
+    let mut adapters: Vec<Box<dyn CliAdapter>> = vec![
         Box::new(claude::ClaudeCodeAdapter::new()),
         Box::new(codex::CodexAdapter::new()),
         Box::new(opencode::OpenCodeAdapter::new()),
-    ]
+        Box::new(amp::AmpAdapter::new()),
+        Box::new(api::ApiAdapter::new()),
+        Box::new(custom::CustomAdapter::new()),
+    ];
+    adapters.extend(gemini_family::FORKS.iter().map(|spec| {
+        Box::new(gemini_family::GeminiFamilyAdapter::new(spec)) as Box<dyn CliAdapter>
+    }));
+    adapters
 }
 
 /// Detect all installed CLIs
@@ -485,5 +613,39 @@ pub fn get_adapter(cli_type: CliType) -> Box<dyn CliAdapter> {
         CliType::Claude => Box::new(claude::ClaudeCodeAdapter::new()),
         CliType::Codex => Box::new(codex::CodexAdapter::new()),
         CliType::OpenCode => Box::new(opencode::OpenCodeAdapter::new()),
+        CliType::Amp => Box::new(amp::AmpAdapter::new()),
+        CliType::Api => Box::new(api::ApiAdapter::new()),
+        CliType::Custom => Box::new(custom::CustomAdapter::new()),
+        CliType::QwenCode | CliType::Iflow | CliType::Gemini => {
+            let spec =
+                gemini_family::fork_spec(cli_type).expect("Gemini-family CLI has a fork spec");
+            Box::new(gemini_family::GeminiFamilyAdapter::new(spec))
+        }
+    }
+}
+
+/// Adapter running `path_override` instead of the executable found on PATH,
+/// for projects where version-manager shims resolve to the wrong binary
+pub fn get_adapter_at(cli_type: CliType, path_override: Option<&str>) -> Box<dyn CliAdapter> {
+    let Some(path) = path_override.map(str::trim).filter(|p| !p.is_empty()) else {
+        return get_adapter(cli_type);
+    };
+    let path = path.to_string();
+    match cli_type {
+        CliType::Claude => Box::new(claude::ClaudeCodeAdapter::with_path(Some(path))),
+        CliType::Codex => Box::new(codex::CodexAdapter::with_path(Some(path))),
+        CliType::OpenCode => Box::new(opencode::OpenCodeAdapter::with_path(Some(path))),
+        CliType::Amp => Box::new(amp::AmpAdapter::with_path(Some(path))),
+        // Talks to the model endpoint directly; there is no executable to override
+        CliType::Api => get_adapter(cli_type),
+        CliType::Custom => Box::new(custom::CustomAdapter::with_path(path)),
+        CliType::QwenCode | CliType::Iflow | CliType::Gemini => {
+            let spec =
+                gemini_family::fork_spec(cli_type).expect("Gemini-family CLI has a fork spec");
+            Box::new(gemini_family::GeminiFamilyAdapter::with_path(
+                spec,
+                Some(path),
+            ))
+        }
     }
 }
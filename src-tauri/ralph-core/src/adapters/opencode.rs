@@ -1,6 +1,7 @@
 use super::{
-    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, resolve_cli_path,
-    shell_env_has, shell_env_value, CliAdapter, CommandOptions, LineType, ParsedLine,
+    apply_extended_path, apply_shell_env, command_for_cli, hide_console_window, portable,
+    resolve_cli_path, shell_env_has, shell_env_value, CliAdapter, CommandOptions, LineType,
+    ParsedLine, PortableCommand,
 };
 use crate::storage::models::CliType;
 use async_trait::async_trait;
@@ -15,9 +16,18 @@ pub struct OpenCodeAdapter {
     path: Option<String>,
 }
 
+impl Default for OpenCodeAdapter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl OpenCodeAdapter {
     pub fn new() -> Self {
-        let path = resolve_cli_path("opencode");
+        Self::with_path(resolve_cli_path("opencode"))
+    }
+
+    pub fn with_path(path: Option<String>) -> Self {
         Self { path }
     }
 
@@ -108,8 +118,7 @@ impl OpenCodeAdapter {
 }
 
 fn has_env_key(key: &str) -> bool {
-    env::var_os(key).is_some()
-        || env::vars_os().any(|(k, _)| k == OsStr::new(key))
+    env::var_os(key).is_some() || env::vars_os().any(|(k, _)| k == OsStr::new(key))
 }
 
 fn env_or_shell(key: &str) -> Option<String> {
@@ -168,7 +177,12 @@ fn merge_permissions(config: Value) -> Value {
     };
 
     let permission = full_access_permissions();
-    apply_permissions(&mut config, "agent", &["general", "build", "plan", "explore"], &permission);
+    apply_permissions(
+        &mut config,
+        "agent",
+        &["general", "build", "plan", "explore"],
+        &permission,
+    );
     apply_permissions(&mut config, "mode", &["build", "plan"], &permission);
     config
 }
@@ -252,6 +266,16 @@ impl CliAdapter for OpenCodeAdapter {
         }
     }
 
+    /// The container has no OpenCode config of its own, so grant full access outright
+    fn portable_command(&self, prompt: &str, _options: CommandOptions) -> Option<PortableCommand> {
+        let mut command = portable("opencode", Self::exec_args(prompt));
+        command.env.push((
+            "OPENCODE_CONFIG_CONTENT".to_string(),
+            full_access_template().to_string(),
+        ));
+        Some(command)
+    }
+
     fn build_command(&self, prompt: &str, working_dir: &Path, options: CommandOptions) -> Command {
         self.build_run_command(prompt, working_dir, false, options)
     }
@@ -277,6 +301,7 @@ impl CliAdapter for OpenCodeAdapter {
                     content: Self::extract_text(&value).unwrap_or_default(),
                     line_type: LineType::Json,
                     is_assistant: true,
+                    anomaly: None,
                 };
             }
 
@@ -285,13 +310,19 @@ impl CliAdapter for OpenCodeAdapter {
                     content: Self::extract_text(&value).unwrap_or_else(|| line.to_string()),
                     line_type: LineType::Error,
                     is_assistant: false,
+                    anomaly: None,
                 };
             }
 
+            let extracted = Self::extract_text(&value);
+            let anomaly = extracted
+                .is_none()
+                .then(|| format!("no text extracted from event type: {}", event_type));
             return ParsedLine {
-                content: Self::extract_text(&value).unwrap_or_else(|| line.to_string()),
+                content: extracted.unwrap_or_else(|| line.to_string()),
                 line_type: LineType::Json,
                 is_assistant: false,
+                anomaly,
             };
         }
 
@@ -299,6 +330,7 @@ impl CliAdapter for OpenCodeAdapter {
             content: line.to_string(),
             line_type: LineType::Text,
             is_assistant: true,
+            anomaly: None,
         }
     }
 }
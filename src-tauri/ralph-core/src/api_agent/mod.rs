@@ -0,0 +1,158 @@
+//! Minimal coding agent that talks to a model API directly, for users with an
+//! API key but no agent CLI. The `api` adapter launches the app binary with
+//! `AGENT_FLAG`; the agent then prints JSONL events on stdout like a CLI would,
+//! so the loop engine drives it unchanged.
+
+mod provider;
+mod tools;
+
+use crate::storage::models::ApiBackendConfig;
+use provider::{Client, Conversation};
+use serde_json::{json, Value};
+use std::io::Write;
+use std::path::Path;
+
+/// First argument that switches the binary into agent mode
+pub const AGENT_FLAG: &str = "--ralph-api-agent";
+/// Restricts the agent to read-only tools (brainstorm runs)
+pub const READONLY_FLAG: &str = "--readonly";
+/// Environment variable carrying the serialized `ApiBackendConfig`, key included
+pub const CONFIG_ENV: &str = "RALPH_API_BACKEND";
+
+/// Characters of a tool result echoed to the log
+const RESULT_PREVIEW_CHARS: usize = 2000;
+
+fn emit(event: Value) {
+    let mut stdout = std::io::stdout().lock();
+    let _ = writeln!(stdout, "{}", event);
+    let _ = stdout.flush();
+}
+
+fn system_prompt(working_dir: &Path, readonly: bool) -> String {
+    let mut prompt = format!(
+        "You are an autonomous coding agent working in {}. Use the tools to inspect the \
+         project, make changes and run commands. Work until the task is done, then reply \
+         with a short summary.",
+        working_dir.display()
+    );
+    if readonly {
+        prompt.push_str(" You may only read files; do not attempt to modify the project.");
+    }
+    prompt
+}
+
+/// Run as the agent when the process was launched with `AGENT_FLAG`; returns the exit code
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (flag, rest) = args.split_first()?;
+    if flag != AGENT_FLAG {
+        return None;
+    }
+
+    let (readonly, prompt) = match rest {
+        [flag, prompt] if flag == READONLY_FLAG => (true, prompt),
+        [prompt] => (false, prompt),
+        _ => {
+            emit(
+                json!({ "type": "error", "message": "Usage: --ralph-api-agent [--readonly] <prompt>" }),
+            );
+            return Some(2);
+        }
+    };
+    let config = match std::env::var(CONFIG_ENV)
+        .map_err(|e| e.to_string())
+        .and_then(|raw| serde_json::from_str::<ApiBackendConfig>(&raw).map_err(|e| e.to_string()))
+    {
+        Ok(config) => config,
+        Err(e) => {
+            emit(json!({ "type": "error", "message": format!("Invalid {}: {}", CONFIG_ENV, e) }));
+            return Some(2);
+        }
+    };
+    let Some(api_key) = config.api_key.clone().filter(|key| !key.is_empty()) else {
+        emit(json!({ "type": "error", "message": "No API key configured" }));
+        return Some(2);
+    };
+    let working_dir = std::env::current_dir().unwrap_or_else(|_| ".".into());
+
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            emit(json!({ "type": "error", "message": e.to_string() }));
+            return Some(1);
+        }
+    };
+    let result = runtime.block_on(run(config, api_key, prompt, &working_dir, readonly));
+    match result {
+        Ok(()) => Some(0),
+        Err(e) => {
+            emit(json!({ "type": "error", "message": e }));
+            Some(1)
+        }
+    }
+}
+
+async fn run(
+    config: ApiBackendConfig,
+    api_key: String,
+    prompt: &str,
+    working_dir: &Path,
+    readonly: bool,
+) -> Result<(), String> {
+    let max_turns = config.max_turns.max(1);
+    let tool_specs = tools::specs(readonly);
+    let mut conversation = Conversation::new(
+        config.provider,
+        system_prompt(working_dir, readonly),
+        prompt,
+    );
+    let client = Client::new(config, api_key)?;
+
+    for turn in 1..=max_turns {
+        // Streamed text goes out line by line, as a CLI would print it
+        let mut pending = String::new();
+        let mut streamed = false;
+        let reply = client
+            .send(&mut conversation, &tool_specs, |delta| {
+                streamed = true;
+                pending.push_str(delta);
+                while let Some(end) = pending.find('\n') {
+                    let line: String = pending.drain(..=end).collect();
+                    emit(json!({ "type": "text", "text": line.trim_end_matches('\n') }));
+                }
+            })
+            .await?;
+        if !pending.is_empty() {
+            emit(json!({ "type": "text", "text": pending }));
+        }
+        if !streamed {
+            if let Some(text) = reply.text {
+                emit(json!({ "type": "text", "text": text }));
+            }
+        }
+        if reply.tool_calls.is_empty() {
+            emit(json!({ "type": "done", "turns": turn }));
+            return Ok(());
+        }
+
+        let mut results = Vec::new();
+        for call in reply.tool_calls {
+            emit(
+                json!({ "type": "tool_use", "summary": tools::summarize(&call.name, &call.input) }),
+            );
+            let output = tools::execute(&call.name, &call.input, working_dir, readonly).await;
+            let preview: String = output.content.chars().take(RESULT_PREVIEW_CHARS).collect();
+            emit(json!({ "type": "tool_result", "output": preview, "isError": output.is_error }));
+            results.push((call.id, output.content, output.is_error));
+        }
+        conversation.push_tool_results(results);
+    }
+
+    Err(format!(
+        "Stopped after {} turns without finishing",
+        max_turns
+    ))
+}
@@ -0,0 +1,546 @@
+use super::tools::ToolSpec;
+use crate::storage::models::{ApiBackendConfig, ApiProvider};
+use futures_util::StreamExt;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com";
+const OPENAI_BASE_URL: &str = "https://api.openai.com/v1";
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub input: Value,
+}
+
+/// A model response in provider-neutral form
+pub struct Reply {
+    pub text: Option<String>,
+    pub tool_calls: Vec<ToolCall>,
+}
+
+/// Message history in the provider's own wire format
+pub struct Conversation {
+    provider: ApiProvider,
+    system: String,
+    messages: Vec<Value>,
+}
+
+impl Conversation {
+    pub fn new(provider: ApiProvider, system: String, prompt: &str) -> Self {
+        let mut messages = Vec::new();
+        if provider == ApiProvider::OpenAiCompatible {
+            messages.push(json!({ "role": "system", "content": system }));
+        }
+        messages.push(json!({ "role": "user", "content": prompt }));
+        Self {
+            provider,
+            system,
+            messages,
+        }
+    }
+
+    fn request_body(&self, config: &ApiBackendConfig, tools: &[ToolSpec]) -> Value {
+        match self.provider {
+            ApiProvider::Anthropic => json!({
+                "model": config.model,
+                "max_tokens": config.max_tokens,
+                "system": self.system,
+                "messages": self.messages,
+                "tools": tools.iter().map(|tool| json!({
+                    "name": tool.name,
+                    "description": tool.description,
+                    "input_schema": tool.input_schema,
+                })).collect::<Vec<_>>(),
+            }),
+            ApiProvider::OpenAiCompatible => json!({
+                "model": config.model,
+                "max_tokens": config.max_tokens,
+                "messages": self.messages,
+                "tools": tools.iter().map(|tool| json!({
+                    "type": "function",
+                    "function": {
+                        "name": tool.name,
+                        "description": tool.description,
+                        "parameters": tool.input_schema,
+                    },
+                })).collect::<Vec<_>>(),
+            }),
+        }
+    }
+
+    /// Record the model's response and return it in neutral form
+    fn accept_response(&mut self, response: &Value) -> Result<Reply, String> {
+        match self.provider {
+            ApiProvider::Anthropic => {
+                let content = response
+                    .get("content")
+                    .and_then(|v| v.as_array())
+                    .ok_or("Response has no content")?;
+                let mut text = Vec::new();
+                let mut tool_calls = Vec::new();
+                for block in content {
+                    match block.get("type").and_then(|v| v.as_str()) {
+                        Some("text") => {
+                            text.extend(block.get("text").and_then(|v| v.as_str()));
+                        }
+                        Some("tool_use") => tool_calls.push(ToolCall {
+                            id: str_field(block, "id"),
+                            name: str_field(block, "name"),
+                            input: block.get("input").cloned().unwrap_or(json!({})),
+                        }),
+                        _ => {}
+                    }
+                }
+                self.messages
+                    .push(json!({ "role": "assistant", "content": content }));
+                Ok(Reply {
+                    text: (!text.is_empty()).then(|| text.join("\n")),
+                    tool_calls,
+                })
+            }
+            ApiProvider::OpenAiCompatible => {
+                let message = response
+                    .pointer("/choices/0/message")
+                    .ok_or("Response has no message")?;
+                let tool_calls = message
+                    .get("tool_calls")
+                    .and_then(|v| v.as_array())
+                    .map(|calls| {
+                        calls
+                            .iter()
+                            .map(|call| ToolCall {
+                                id: str_field(call, "id"),
+                                name: call
+                                    .pointer("/function/name")
+                                    .and_then(|v| v.as_str())
+                                    .unwrap_or_default()
+                                    .to_string(),
+                                // Arguments arrive as a JSON-encoded string
+                                input: call
+                                    .pointer("/function/arguments")
+                                    .and_then(|v| v.as_str())
+                                    .and_then(|args| serde_json::from_str(args).ok())
+                                    .unwrap_or(json!({})),
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let text = message
+                    .get("content")
+                    .and_then(|v| v.as_str())
+                    .filter(|s| !s.trim().is_empty())
+                    .map(String::from);
+                self.messages.push(message.clone());
+                Ok(Reply { text, tool_calls })
+            }
+        }
+    }
+
+    pub fn push_tool_results(&mut self, results: Vec<(String, String, bool)>) {
+        match self.provider {
+            ApiProvider::Anthropic => {
+                let blocks: Vec<Value> = results
+                    .into_iter()
+                    .map(|(id, content, is_error)| {
+                        json!({
+                            "type": "tool_result",
+                            "tool_use_id": id,
+                            "content": content,
+                            "is_error": is_error,
+                        })
+                    })
+                    .collect();
+                self.messages
+                    .push(json!({ "role": "user", "content": blocks }));
+            }
+            ApiProvider::OpenAiCompatible => {
+                for (id, content, _) in results {
+                    self.messages.push(json!({
+                        "role": "tool",
+                        "tool_call_id": id,
+                        "content": content,
+                    }));
+                }
+            }
+        }
+    }
+}
+
+/// Rebuilds a streamed (SSE) response into the shape of a non-streamed one,
+/// so `Conversation::accept_response` handles both
+struct StreamAssembler {
+    provider: ApiProvider,
+    /// Anthropic content blocks, by index
+    blocks: Vec<Value>,
+    /// Anthropic tool inputs arrive as JSON fragments, by block index
+    partial_inputs: Vec<String>,
+    /// OpenAI-compatible message text
+    text: String,
+    /// OpenAI-compatible tool calls, by index
+    tool_calls: Vec<Value>,
+    error: Option<String>,
+}
+
+impl StreamAssembler {
+    fn new(provider: ApiProvider) -> Self {
+        Self {
+            provider,
+            blocks: Vec::new(),
+            partial_inputs: Vec::new(),
+            text: String::new(),
+            tool_calls: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Apply one `data:` payload, returning the text it adds
+    fn push(&mut self, data: &str) -> Option<String> {
+        if data == "[DONE]" {
+            return None;
+        }
+        let event: Value = serde_json::from_str(data).ok()?;
+        if let Some(message) = event.pointer("/error/message").and_then(|v| v.as_str()) {
+            self.error = Some(message.to_string());
+            return None;
+        }
+        match self.provider {
+            ApiProvider::Anthropic => self.push_anthropic(&event),
+            ApiProvider::OpenAiCompatible => self.push_openai(&event),
+        }
+    }
+
+    fn push_anthropic(&mut self, event: &Value) -> Option<String> {
+        let index = event.get("index").and_then(|v| v.as_u64())? as usize;
+        if self.blocks.len() <= index {
+            self.blocks.resize(index + 1, Value::Null);
+            self.partial_inputs.resize(index + 1, String::new());
+        }
+        match event.get("type").and_then(|v| v.as_str())? {
+            "content_block_start" => {
+                self.blocks[index] = event.get("content_block").cloned().unwrap_or(json!({}));
+                None
+            }
+            "content_block_delta" => {
+                let delta = event.get("delta")?;
+                match delta.get("type").and_then(|v| v.as_str())? {
+                    "text_delta" => {
+                        let text = delta.get("text").and_then(|v| v.as_str())?;
+                        let existing = self.blocks[index]
+                            .get("text")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or_default();
+                        self.blocks[index]["text"] = json!(format!("{}{}", existing, text));
+                        Some(text.to_string())
+                    }
+                    "input_json_delta" => {
+                        let fragment = delta.get("partial_json").and_then(|v| v.as_str())?;
+                        self.partial_inputs[index].push_str(fragment);
+                        None
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn push_openai(&mut self, event: &Value) -> Option<String> {
+        let delta = event.pointer("/choices/0/delta")?;
+        for (position, call) in delta
+            .get("tool_calls")
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .enumerate()
+        {
+            let index = call
+                .get("index")
+                .and_then(|v| v.as_u64())
+                .map_or(position, |i| i as usize);
+            if self.tool_calls.len() <= index {
+                self.tool_calls.resize(
+                    index + 1,
+                    json!({ "id": "", "type": "function", "function": { "name": "", "arguments": "" } }),
+                );
+            }
+            let entry = &mut self.tool_calls[index];
+            if let Some(id) = call.get("id").and_then(|v| v.as_str()) {
+                entry["id"] = json!(id);
+            }
+            for key in ["name", "arguments"] {
+                if let Some(part) = call
+                    .pointer(&format!("/function/{}", key))
+                    .and_then(|v| v.as_str())
+                {
+                    let existing = entry["function"][key].as_str().unwrap_or_default();
+                    entry["function"][key] = json!(format!("{}{}", existing, part));
+                }
+            }
+        }
+        let text = delta.get("content").and_then(|v| v.as_str())?;
+        self.text.push_str(text);
+        Some(text.to_string())
+    }
+
+    fn finish(mut self) -> Result<Value, String> {
+        if let Some(error) = self.error {
+            return Err(format!("API stream failed: {}", error));
+        }
+        match self.provider {
+            ApiProvider::Anthropic => {
+                for (block, input) in self.blocks.iter_mut().zip(&self.partial_inputs) {
+                    if block.get("type").and_then(|v| v.as_str()) == Some("tool_use") {
+                        block["input"] = serde_json::from_str(input).unwrap_or(json!({}));
+                    }
+                }
+                let content: Vec<Value> =
+                    self.blocks.into_iter().filter(|b| !b.is_null()).collect();
+                Ok(json!({ "content": content }))
+            }
+            ApiProvider::OpenAiCompatible => {
+                let mut message = json!({
+                    "role": "assistant",
+                    "content": (!self.text.is_empty()).then_some(self.text),
+                });
+                if !self.tool_calls.is_empty() {
+                    message["tool_calls"] = json!(self.tool_calls);
+                }
+                Ok(json!({ "choices": [{ "message": message }] }))
+            }
+        }
+    }
+}
+
+fn str_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    config: ApiBackendConfig,
+    api_key: String,
+}
+
+impl Client {
+    pub fn new(config: ApiBackendConfig, api_key: String) -> Result<Self, String> {
+        let http = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            http,
+            config,
+            api_key,
+        })
+    }
+
+    /// Send the conversation, passing text to `on_text` as it streams in
+    pub async fn send(
+        &self,
+        conversation: &mut Conversation,
+        tools: &[ToolSpec],
+        mut on_text: impl FnMut(&str),
+    ) -> Result<Reply, String> {
+        let mut body = conversation.request_body(&self.config, tools);
+        body["stream"] = json!(true);
+        let request = match self.config.provider {
+            ApiProvider::Anthropic => {
+                let base = self
+                    .config
+                    .base_url
+                    .as_deref()
+                    .unwrap_or(ANTHROPIC_BASE_URL);
+                self.http
+                    .post(format!("{}/v1/messages", base.trim_end_matches('/')))
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", ANTHROPIC_VERSION)
+            }
+            ApiProvider::OpenAiCompatible => {
+                let base = self.config.base_url.as_deref().unwrap_or(OPENAI_BASE_URL);
+                self.http
+                    .post(format!("{}/chat/completions", base.trim_end_matches('/')))
+                    .bearer_auth(&self.api_key)
+            }
+        };
+        let response = request
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("API request failed: {}", e))?;
+        let status = response.status();
+        let is_stream = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        // Errors, and endpoints that ignore `stream`, answer with plain JSON
+        if !status.is_success() || !is_stream {
+            let value: Value = response
+                .json()
+                .await
+                .map_err(|e| format!("Invalid API response: {}", e))?;
+            if !status.is_success() {
+                let message = value
+                    .pointer("/error/message")
+                    .and_then(|v| v.as_str())
+                    .map(String::from)
+                    .unwrap_or_else(|| value.to_string());
+                return Err(format!("API returned {}: {}", status, message));
+            }
+            return conversation.accept_response(&value);
+        }
+
+        let mut assembler = StreamAssembler::new(self.config.provider);
+        let mut stream = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| format!("API stream failed: {}", e))?;
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                if let Some(data) = line.trim_end().strip_prefix("data:") {
+                    if let Some(text) = assembler.push(data.trim_start()) {
+                        on_text(&text);
+                    }
+                }
+            }
+        }
+        conversation.accept_response(&assembler.finish()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api_agent::tools;
+
+    #[test]
+    fn anthropic_round_trip() {
+        let config = ApiBackendConfig::default();
+        let mut conversation =
+            Conversation::new(ApiProvider::Anthropic, "system".to_string(), "fix it");
+        let body = conversation.request_body(&config, &tools::specs(false));
+        assert_eq!(body["system"], "system");
+        assert_eq!(body["tools"][0]["name"], "bash");
+
+        let reply = conversation
+            .accept_response(&json!({
+                "content": [
+                    { "type": "text", "text": "Running tests" },
+                    { "type": "tool_use", "id": "tu_1", "name": "bash", "input": { "command": "cargo test" } }
+                ],
+                "stop_reason": "tool_use"
+            }))
+            .unwrap();
+        assert_eq!(reply.text.as_deref(), Some("Running tests"));
+        assert_eq!(reply.tool_calls[0].input["command"], "cargo test");
+
+        conversation.push_tool_results(vec![("tu_1".to_string(), "ok".to_string(), false)]);
+        assert_eq!(conversation.messages.len(), 3);
+        assert_eq!(
+            conversation.messages[2]["content"][0]["tool_use_id"],
+            "tu_1"
+        );
+    }
+
+    #[test]
+    fn openai_compatible_round_trip() {
+        let config = ApiBackendConfig {
+            provider: ApiProvider::OpenAiCompatible,
+            model: "glm-4.6".to_string(),
+            ..ApiBackendConfig::default()
+        };
+        let mut conversation = Conversation::new(
+            ApiProvider::OpenAiCompatible,
+            "system".to_string(),
+            "fix it",
+        );
+        let body = conversation.request_body(&config, &tools::specs(true));
+        assert_eq!(body["messages"][0]["role"], "system");
+        assert_eq!(body["tools"][0]["function"]["name"], "read_file");
+
+        let reply = conversation
+            .accept_response(&json!({
+                "choices": [{
+                    "message": {
+                        "role": "assistant",
+                        "content": null,
+                        "tool_calls": [{
+                            "id": "call_1",
+                            "type": "function",
+                            "function": { "name": "read_file", "arguments": "{\"path\":\"README.md\"}" }
+                        }]
+                    }
+                }]
+            }))
+            .unwrap();
+        assert!(reply.text.is_none());
+        assert_eq!(reply.tool_calls[0].input["path"], "README.md");
+
+        conversation.push_tool_results(vec![("call_1".to_string(), "# Readme".to_string(), false)]);
+        assert_eq!(conversation.messages.last().unwrap()["role"], "tool");
+    }
+
+    #[test]
+    fn assembles_streamed_anthropic_response() {
+        let mut assembler = StreamAssembler::new(ApiProvider::Anthropic);
+        let events = [
+            r#"{"type":"message_start","message":{"id":"m1"}}"#,
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"text","text":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Running "}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"tests"}}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"tool_use","id":"tu_1","name":"bash","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"{\"command\":"}}"#,
+            r#"{"type":"content_block_delta","index":1,"delta":{"type":"input_json_delta","partial_json":"\"cargo test\"}"}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+        let streamed: Vec<String> = events.iter().filter_map(|e| assembler.push(e)).collect();
+        assert_eq!(streamed, vec!["Running ", "tests"]);
+
+        let mut conversation =
+            Conversation::new(ApiProvider::Anthropic, "system".to_string(), "fix it");
+        let reply = conversation
+            .accept_response(&assembler.finish().unwrap())
+            .unwrap();
+        assert_eq!(reply.text.as_deref(), Some("Running tests"));
+        assert_eq!(reply.tool_calls[0].id, "tu_1");
+        assert_eq!(reply.tool_calls[0].input["command"], "cargo test");
+    }
+
+    #[test]
+    fn assembles_streamed_openai_response() {
+        let mut assembler = StreamAssembler::new(ApiProvider::OpenAiCompatible);
+        let events = [
+            r#"{"choices":[{"delta":{"role":"assistant","content":"Reading"}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"read_file","arguments":"{\"path\":"}}]}}]}"#,
+            r#"{"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"README.md\"}"}}]}}]}"#,
+            "[DONE]",
+        ];
+        let streamed: Vec<String> = events.iter().filter_map(|e| assembler.push(e)).collect();
+        assert_eq!(streamed, vec!["Reading"]);
+
+        let mut conversation = Conversation::new(
+            ApiProvider::OpenAiCompatible,
+            "system".to_string(),
+            "fix it",
+        );
+        let reply = conversation
+            .accept_response(&assembler.finish().unwrap())
+            .unwrap();
+        assert_eq!(reply.text.as_deref(), Some("Reading"));
+        assert_eq!(reply.tool_calls[0].name, "read_file");
+        assert_eq!(reply.tool_calls[0].input["path"], "README.md");
+
+        let mut failed = StreamAssembler::new(ApiProvider::OpenAiCompatible);
+        failed.push(r#"{"error":{"message":"rate limited"}}"#);
+        assert!(failed.finish().is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Longest tool output returned to the model
+const MAX_OUTPUT_CHARS: usize = 30_000;
+const BASH_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// A tool the model may call, described the same way to every provider
+pub struct ToolSpec {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub input_schema: Value,
+    /// Whether the tool is offered to read-only (brainstorm) runs
+    pub readonly: bool,
+}
+
+pub struct ToolOutput {
+    pub content: String,
+    pub is_error: bool,
+}
+
+impl ToolOutput {
+    fn ok(content: impl Into<String>) -> Self {
+        Self {
+            content: truncate(content.into()),
+            is_error: false,
+        }
+    }
+
+    fn error(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            is_error: true,
+        }
+    }
+}
+
+pub fn specs(readonly: bool) -> Vec<ToolSpec> {
+    let all = vec![
+        ToolSpec {
+            name: "bash",
+            description: "Run a shell command in the project directory and return its combined stdout and stderr.",
+            input_schema: json!({
+                "type": "object",
+                "properties": { "command": { "type": "string" } },
+                "required": ["command"]
+            }),
+            readonly: false,
+        },
+        ToolSpec {
+            name: "read_file",
+            description: "Read a UTF-8 text file. Paths are relative to the project directory.",
+            input_schema: json!({
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }),
+            readonly: true,
+        },
+        ToolSpec {
+            name: "list_dir",
+            description: "List the entries of a directory. Paths are relative to the project directory.",
+            input_schema: json!({
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }),
+            readonly: true,
+        },
+        ToolSpec {
+            name: "write_file",
+            description: "Create or overwrite a file with the given content, creating parent directories.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "content": { "type": "string" }
+                },
+                "required": ["path", "content"]
+            }),
+            readonly: false,
+        },
+        ToolSpec {
+            name: "edit_file",
+            description: "Replace old_string with new_string in a file. old_string must occur exactly once.",
+            input_schema: json!({
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "old_string": { "type": "string" },
+                    "new_string": { "type": "string" }
+                },
+                "required": ["path", "old_string", "new_string"]
+            }),
+            readonly: false,
+        },
+    ];
+    all.into_iter()
+        .filter(|spec| !readonly || spec.readonly)
+        .collect()
+}
+
+/// One-line description of a call for the loop log
+pub fn summarize(name: &str, input: &Value) -> String {
+    let field = match name {
+        "bash" => "command",
+        _ => "path",
+    };
+    let value = input.get(field).and_then(|v| v.as_str()).unwrap_or("");
+    format!("{} {}", name, value.lines().next().unwrap_or(""))
+        .trim()
+        .to_string()
+}
+
+pub async fn execute(name: &str, input: &Value, working_dir: &Path, readonly: bool) -> ToolOutput {
+    if !specs(readonly).iter().any(|spec| spec.name == name) {
+        return ToolOutput::error(format!("Tool {} is not available", name));
+    }
+    let arg = |key: &str| input.get(key).and_then(|v| v.as_str());
+    let result = match name {
+        "bash" => match arg("command") {
+            Some(command) => run_bash(command, working_dir).await,
+            None => Err("Missing command".to_string()),
+        },
+        "read_file" => arg("path")
+            .ok_or_else(|| "Missing path".to_string())
+            .and_then(|path| {
+                std::fs::read_to_string(resolve(working_dir, path)).map_err(|e| e.to_string())
+            }),
+        "list_dir" => list_dir(&resolve(working_dir, arg("path").unwrap_or("."))),
+        "write_file" => match (arg("path"), arg("content")) {
+            (Some(path), Some(content)) => write_file(&resolve(working_dir, path), content),
+            _ => Err("Missing path or content".to_string()),
+        },
+        "edit_file" => match (arg("path"), arg("old_string"), arg("new_string")) {
+            (Some(path), Some(old), Some(new)) => edit_file(&resolve(working_dir, path), old, new),
+            _ => Err("Missing path, old_string or new_string".to_string()),
+        },
+        _ => Err(format!("Unknown tool {}", name)),
+    };
+    match result {
+        Ok(content) => ToolOutput::ok(content),
+        Err(e) => ToolOutput::error(e),
+    }
+}
+
+fn resolve(working_dir: &Path, path: &str) -> PathBuf {
+    working_dir.join(path)
+}
+
+async fn run_bash(command: &str, working_dir: &Path) -> Result<String, String> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.current_dir(working_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    crate::adapters::hide_console_window(&mut cmd);
+
+    let output = tokio::time::timeout(BASH_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("Command timed out after {}s", BASH_TIMEOUT.as_secs()))?
+        .map_err(|e| e.to_string())?;
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    if !output.status.success() {
+        text.push_str(&format!(
+            "\n[exit code {}]",
+            output.status.code().unwrap_or(-1)
+        ));
+    }
+    Ok(text)
+}
+
+fn list_dir(path: &Path) -> Result<String, String> {
+    let mut entries: Vec<String> = std::fs::read_dir(path)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() {
+                format!("{}/", name)
+            } else {
+                name
+            }
+        })
+        .collect();
+    entries.sort();
+    Ok(entries.join("\n"))
+}
+
+fn write_file(path: &Path, content: &str) -> Result<String, String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    std::fs::write(path, content).map_err(|e| e.to_string())?;
+    Ok(format!("Wrote {} bytes", content.len()))
+}
+
+fn edit_file(path: &Path, old: &str, new: &str) -> Result<String, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    match content.matches(old).count() {
+        0 => Err("old_string not found".to_string()),
+        1 => {
+            std::fs::write(path, content.replacen(old, new, 1)).map_err(|e| e.to_string())?;
+            Ok("Edited file".to_string())
+        }
+        n => Err(format!(
+            "old_string occurs {} times; include more context",
+            n
+        )),
+    }
+}
+
+fn truncate(mut text: String) -> String {
+    if text.len() > MAX_OUTPUT_CHARS {
+        let mut end = MAX_OUTPUT_CHARS;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push_str("\n[output truncated]");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn file_tools_edit_within_working_dir() {
+        let dir = tempdir().unwrap();
+        let run = |name: &'static str, input: Value| {
+            let dir = dir.path().to_path_buf();
+            async move { execute(name, &input, &dir, false).await }
+        };
+
+        let out = run(
+            "write_file",
+            json!({"path": "src/lib.rs", "content": "fn a() {}\n"}),
+        )
+        .await;
+        assert!(!out.is_error, "{}", out.content);
+
+        let out = run(
+            "edit_file",
+            json!({"path": "src/lib.rs", "old_string": "a()", "new_string": "b()"}),
+        )
+        .await;
+        assert!(!out.is_error, "{}", out.content);
+
+        let out = run("read_file", json!({"path": "src/lib.rs"})).await;
+        assert_eq!(out.content, "fn b() {}\n");
+
+        let out = run(
+            "edit_file",
+            json!({"path": "src/lib.rs", "old_string": "missing", "new_string": "x"}),
+        )
+        .await;
+        assert!(out.is_error);
+
+        let out = run("list_dir", json!({"path": "."})).await;
+        assert_eq!(out.content, "src/");
+    }
+
+    #[tokio::test]
+    async fn readonly_runs_cannot_modify_files() {
+        let dir = tempdir().unwrap();
+        let out = execute(
+            "write_file",
+            &json!({"path": "a.txt", "content": "x"}),
+            dir.path(),
+            true,
+        )
+        .await;
+        assert!(out.is_error);
+        assert!(!dir.path().join("a.txt").exists());
+        assert!(specs(true).iter().all(|spec| spec.readonly));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn bash_reports_output_and_exit_code() {
+        let dir = tempdir().unwrap();
+        let out = execute(
+            "bash",
+            &json!({"command": "echo hi; exit 3"}),
+            dir.path(),
+            false,
+        )
+        .await;
+        assert!(!out.is_error);
+        assert_eq!(out.content, "hi\n\n[exit code 3]");
+    }
+}
@@ -1,4 +1,6 @@
 use crate::adapters::{get_adapter, CommandOptions, LineType};
+use crate::engine::{prompt, repo_summary};
+use crate::storage;
 use crate::storage::models::CliType;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
@@ -36,6 +38,14 @@ pub struct QuestionOption {
 }
 
 /// Conversation message
+/// Transcript of a voice note and the brainstorm reply to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceBrainstormResponse {
+    pub transcript: String,
+    pub response: AiBrainstormResponse,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConversationMessage {
@@ -43,10 +53,11 @@ pub struct ConversationMessage {
     pub content: String,
 }
 
-const BRAINSTORM_SYSTEM_PROMPT: &str = r#"You are a thought partner for programming tasks, helping users explore and clarify what they want to accomplish.
+/// Built-in brainstorm system prompt, used until the user saves their own
+pub const BRAINSTORM_SYSTEM_PROMPT: &str = r#"You are a thought partner for programming tasks, helping users explore and clarify what they want to accomplish.
 
 ## Language Rule
-IMPORTANT: Detect and match the user's language automatically. If the user writes in Chinese, respond in Chinese. If in English, respond in English. If in Japanese, respond in Japanese. Always mirror the user's language.
+{language_rule}
 
 ## Core Principles
 
@@ -193,7 +204,70 @@ Before completing, you MUST ask about testing/validation. If the user is unsure,
 - At minimum: unit tests covering key logic
 - If there is UI or end-to-end flow: add a minimal E2E smoke test
 
-Remember: Match the user's language in all your responses!"#;
+Remember: Follow the Language Rule in all your responses!"#;
+
+const AUTO_LANGUAGE_RULE: &str = "IMPORTANT: Detect and match the user's language automatically. If the user writes in Chinese, respond in Chinese. If in English, respond in English. If in Japanese, respond in Japanese. Always mirror the user's language.";
+
+/// Placeholders a brainstorm prompt template may use
+const BRAINSTORM_PLACEHOLDERS: &[&str] = &["language_rule"];
+
+/// Names written as `{name}` in a template (JSON braces are not matched)
+fn template_placeholders(template: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rest = &rest[start + 1..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let name = &rest[..end];
+        if !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        {
+            found.push(name);
+        }
+    }
+    found
+}
+
+/// Check a user-supplied brainstorm prompt before it is saved
+pub fn validate_brainstorm_prompt(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Brainstorm prompt cannot be empty".to_string());
+    }
+    let placeholders = template_placeholders(template);
+    if let Some(unknown) = placeholders
+        .iter()
+        .find(|name| !BRAINSTORM_PLACEHOLDERS.contains(name))
+    {
+        return Err(format!(
+            "Unknown placeholder {{{}}}; supported: {}",
+            unknown,
+            BRAINSTORM_PLACEHOLDERS
+                .iter()
+                .map(|p| format!("{{{}}}", p))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+    }
+    for required in BRAINSTORM_PLACEHOLDERS {
+        if !placeholders.contains(required) {
+            return Err(format!("Missing required placeholder {{{}}}", required));
+        }
+    }
+    Ok(())
+}
+
+/// Fill the language rule for the configured agent language into a template
+fn brainstorm_system_prompt(template: &str, language: Option<&str>) -> String {
+    let rule = match prompt::language_instruction(language) {
+        Some(instruction) => format!("IMPORTANT: {instruction}"),
+        None => AUTO_LANGUAGE_RULE.to_string(),
+    };
+    template.replace("{language_rule}", &rule)
+}
 
 /// Run AI brainstorm with Claude Code
 pub async fn run_ai_brainstorm(
@@ -201,10 +275,25 @@ pub async fn run_ai_brainstorm(
     conversation: &[ConversationMessage],
     cli_type: CliType,
     skip_git_repo_check: bool,
+    repo_context: Option<&str>,
+    language: Option<&str>,
+    persona: Option<&str>,
 ) -> Result<AiBrainstormResponse, String> {
     // Build the conversation context
     let mut context = String::new();
 
+    // Ground the questions in the existing code, if there is any
+    if let Some(summary) = repo_summary::summarize_repo(working_dir).await {
+        context.push_str(&format!(
+            "## Existing Codebase\n\nThe project directory already contains code. Ask informed questions about extending or changing it rather than greenfield questions.\n\n{}\n",
+            summary
+        ));
+    }
+
+    if let Some(repo) = repo_context.filter(|r| !r.trim().is_empty()) {
+        context.push_str(&format!("## Repository Context\n\n{}\n\n", repo.trim()));
+    }
+
     for msg in conversation {
         if msg.role == "user" {
             context.push_str(&format!("User: {}\n\n", msg.content));
@@ -213,10 +302,21 @@ pub async fn run_ai_brainstorm(
         }
     }
 
+    // Prefer the user's saved template, falling back to the built-in one
+    let template = storage::load_brainstorm_prompt()
+        .ok()
+        .flatten()
+        .unwrap_or_else(|| BRAINSTORM_SYSTEM_PROMPT.to_string());
+
+    let mut system_prompt = brainstorm_system_prompt(&template, language);
+    if let Some(persona) = persona.map(str::trim).filter(|p| !p.is_empty()) {
+        system_prompt.push_str(&format!("\n\n## Persona\n{}", persona));
+    }
+
     // Create the prompt for Claude
     let prompt = format!(
         "{}\n\n## Conversation\n\n{}\n\nBased on the conversation above, output the next question JSON (or the final prompt). Output JSON only.",
-        BRAINSTORM_SYSTEM_PROMPT,
+        system_prompt,
         context
     );
 
@@ -224,11 +324,11 @@ pub async fn run_ai_brainstorm(
     let output = call_brainstorm_cli(cli_type, working_dir, &prompt, skip_git_repo_check).await?;
 
     // Parse JSON response
-    parse_ai_response(&output)
+    parse_ai_response(&output, language)
 }
 
 /// Parse AI response JSON
-fn parse_ai_response(output: &str) -> Result<AiBrainstormResponse, String> {
+fn parse_ai_response(output: &str, language: Option<&str>) -> Result<AiBrainstormResponse, String> {
     // Try to extract JSON from the output
     match extract_json(output) {
         Ok(json_str) => {
@@ -258,7 +358,7 @@ fn parse_ai_response(output: &str) -> Result<AiBrainstormResponse, String> {
 
             // Check if it looks like a completion
             if trimmed.contains("<done>COMPLETE</done>") {
-                let (question, description) = match detect_language(trimmed) {
+                let (question, description) = match resolve_language(language, trimmed) {
                     DetectedLanguage::Zh => {
                         ("需求收集完成".to_string(), "已生成任务 prompt".to_string())
                     }
@@ -337,7 +437,10 @@ fn extract_json(output: &str) -> Result<String, String> {
         }
     }
 
-    Err(format!("No JSON found in output: {}", truncate_for_error(output, 500)))
+    Err(format!(
+        "No JSON found in output: {}",
+        truncate_for_error(output, 500)
+    ))
 }
 
 /// Extract a balanced JSON object using bracket matching
@@ -346,13 +449,13 @@ fn extract_balanced_json(input: &str) -> Result<String, String> {
     let mut in_string = false;
     let mut escape_next = false;
     let chars: Vec<char> = input.chars().collect();
-    
+
     for (i, &ch) in chars.iter().enumerate() {
         if escape_next {
             escape_next = false;
             continue;
         }
-        
+
         match ch {
             '\\' if in_string => escape_next = true,
             '"' if !escape_next => in_string = !in_string,
@@ -368,7 +471,7 @@ fn extract_balanced_json(input: &str) -> Result<String, String> {
             _ => {}
         }
     }
-    
+
     // JSON is incomplete
     if depth > 0 {
         Err(format!(
@@ -382,7 +485,10 @@ fn extract_balanced_json(input: &str) -> Result<String, String> {
             truncate_for_error(input, 300)
         ))
     } else {
-        Err(format!("Invalid JSON structure in: {}", truncate_for_error(input, 300)))
+        Err(format!(
+            "Invalid JSON structure in: {}",
+            truncate_for_error(input, 300)
+        ))
     }
 }
 
@@ -392,18 +498,18 @@ fn validate_json_structure(json_str: &str) -> Result<String, String> {
     if trimmed.is_empty() {
         return Err("Empty JSON content".to_string());
     }
-    
+
     // Quick validation using bracket matching
     let mut depth = 0;
     let mut in_string = false;
     let mut escape_next = false;
-    
+
     for ch in trimmed.chars() {
         if escape_next {
             escape_next = false;
             continue;
         }
-        
+
         match ch {
             '\\' if in_string => escape_next = true,
             '"' if !escape_next => in_string = !in_string,
@@ -412,7 +518,7 @@ fn validate_json_structure(json_str: &str) -> Result<String, String> {
             _ => {}
         }
     }
-    
+
     if depth != 0 {
         Err(format!(
             "Unbalanced JSON structure (depth={}). Content may be truncated: {}",
@@ -446,6 +552,17 @@ enum DetectedLanguage {
     Other,
 }
 
+/// Use the configured language when set, otherwise detect it from the text
+fn resolve_language(configured: Option<&str>, input: &str) -> DetectedLanguage {
+    match configured.map(|c| c.trim().to_ascii_lowercase()).as_deref() {
+        Some("zh") => DetectedLanguage::Zh,
+        Some("ja") => DetectedLanguage::Ja,
+        Some("ko") => DetectedLanguage::Ko,
+        Some("en") => DetectedLanguage::Other,
+        _ => detect_language(input),
+    }
+}
+
 fn detect_language(input: &str) -> DetectedLanguage {
     if contains_hangul(input) {
         return DetectedLanguage::Ko;
@@ -559,7 +676,7 @@ async fn call_claude_cli(working_dir: &Path, prompt: &str) -> Result<String, Str
     Ok(stdout)
 }
 
-async fn call_brainstorm_cli(
+pub(crate) async fn call_brainstorm_cli(
     cli_type: CliType,
     working_dir: &Path,
     prompt: &str,
@@ -567,7 +684,14 @@ async fn call_brainstorm_cli(
 ) -> Result<String, String> {
     match cli_type {
         CliType::Claude => call_claude_cli(working_dir, prompt).await,
-        CliType::Codex | CliType::OpenCode => {
+        CliType::Codex
+        | CliType::OpenCode
+        | CliType::Amp
+        | CliType::QwenCode
+        | CliType::Iflow
+        | CliType::Gemini
+        | CliType::Api
+        | CliType::Custom => {
             call_other_cli(cli_type, working_dir, prompt, skip_git_repo_check).await
         }
     }
@@ -582,12 +706,10 @@ async fn call_other_cli(
     let adapter = get_adapter(cli_type);
     let options = CommandOptions {
         skip_git_repo_check,
+        ..Default::default()
     };
-    let mut cmd = adapter.build_readonly_command(prompt, working_dir, options);
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run CLI: {}", e))?;
+    let cmd = adapter.build_readonly_command(prompt, working_dir, options);
+    let output = crate::adapters::output_with_prompt(adapter.as_ref(), cmd, prompt).await?;
 
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
@@ -635,7 +757,9 @@ fn collect_brainstorm_output(cli_type: CliType, stdout: &str) -> (String, Option
             continue;
         }
 
-        if parsed.line_type == LineType::Json && parsed.content == line {
+        if parsed.line_type == LineType::Tool
+            || (parsed.line_type == LineType::Json && parsed.content == line)
+        {
             continue;
         }
 
@@ -661,13 +785,9 @@ pub async fn generate_project_title(
     cli_type: CliType,
     skip_git_repo_check: bool,
 ) -> Result<String, String> {
-    let prompt = format!(
-        "{}\n\nUser request: {}",
-        TITLE_SYSTEM_PROMPT, first_message
-    );
+    let prompt = format!("{}\n\nUser request: {}", TITLE_SYSTEM_PROMPT, first_message);
 
-    let raw =
-        call_brainstorm_cli(cli_type, working_dir, &prompt, skip_git_repo_check).await?;
+    let raw = call_brainstorm_cli(cli_type, working_dir, &prompt, skip_git_repo_check).await?;
 
     // Take first non-empty line, strip common quote/backtick wrapping
     let title: String = raw
@@ -706,119 +826,36 @@ pub fn truncate_to_title(text: &str, max_chars: usize) -> String {
 
 #[cfg(test)]
 mod tests {
-    use super::ConversationMessage;
-    use crate::storage;
-    use crate::storage::models::{
-        BrainstormState, CliType, GlobalConfig, ProjectState, ProjectStatus,
-    };
-    use chrono::Utc;
-    use std::env;
-    use std::ffi::{OsStr, OsString};
-    use std::fs;
-    use std::path::Path;
-    use tempfile::TempDir;
-    use uuid::Uuid;
-
-    #[cfg(unix)]
-    use std::os::unix::fs::PermissionsExt;
-
-    struct EnvVarGuard {
-        key: &'static str,
-        prev: Option<OsString>,
-    }
-
-    impl EnvVarGuard {
-        fn set(key: &'static str, value: impl AsRef<OsStr>) -> Self {
-            let prev = env::var_os(key);
-            env::set_var(key, value);
-            Self { key, prev }
-        }
-    }
-
-    impl Drop for EnvVarGuard {
-        fn drop(&mut self) {
-            if let Some(prev) = self.prev.take() {
-                env::set_var(self.key, prev);
-            } else {
-                env::remove_var(self.key);
-            }
-        }
-    }
+    // --- Unit tests for truncate_to_title ---
 
-    #[cfg(unix)]
-    fn write_executable(path: &Path, contents: &str) {
-        fs::write(path, contents).unwrap();
-        let mut perms = fs::metadata(path).unwrap().permissions();
-        perms.set_mode(0o755);
-        fs::set_permissions(path, perms).unwrap();
-    }
-
-    #[cfg(unix)]
-    #[tokio::test]
-    async fn brainstorm_uses_configured_cli() {
-        let _env_lock = crate::test_support::lock_env();
-        let temp_dir = TempDir::new().unwrap();
-        let home_dir = temp_dir.path();
-        let _home_guard = EnvVarGuard::set("HOME", home_dir);
-        let _shell_guard = EnvVarGuard::set("SHELL", "/bin/bash");
-
-        let bin_dir = home_dir.join("bin");
-        fs::create_dir_all(&bin_dir).unwrap();
-
-        let codex_script = r#"#!/usr/bin/env bash
-echo '{"question":"Hi","description":null,"options":[],"multiSelect":false,"allowOther":false,"isComplete":false}'
-"#;
-        write_executable(&bin_dir.join("codex"), codex_script);
-
-        let claude_script = r#"#!/usr/bin/env bash
-echo 'not-json'
-"#;
-        write_executable(&bin_dir.join("claude"), claude_script);
-
-        let current_path = env::var_os("PATH").unwrap_or_default();
-        let new_path = format!("{}:{}", bin_dir.display(), current_path.to_string_lossy());
-        let _path_guard = EnvVarGuard::set("PATH", new_path);
-
-        let project_path = home_dir.join("project");
-        fs::create_dir_all(&project_path).unwrap();
-
-        let mut config = GlobalConfig::default();
-        config.default_cli = CliType::Codex;
-        storage::save_config(&config).unwrap();
-
-        let now = Utc::now();
-        let project_id = Uuid::new_v4();
-        let project_state = ProjectState {
-            id: project_id,
-            name: "Test".to_string(),
-            path: project_path.to_string_lossy().to_string(),
-            status: ProjectStatus::Brainstorming,
-            skip_git_repo_check: false,
-            brainstorm: Some(BrainstormState {
-                answers: vec![],
-                completed_at: None,
-            }),
-            task: None,
-            execution: None,
-            created_at: now,
-            updated_at: now,
-        };
-        storage::save_project_state(&project_state).unwrap();
-
-        let response = crate::commands::ai_brainstorm_chat(
-            project_id.to_string(),
-            vec![ConversationMessage {
-                role: "user".to_string(),
-                content: "Test".to_string(),
-            }],
-        )
-        .await
-        .unwrap();
+    #[test]
+    fn configured_language_overrides_detection() {
+        let auto = super::brainstorm_system_prompt(super::BRAINSTORM_SYSTEM_PROMPT, Some("auto"));
+        assert!(auto.contains("Detect and match the user's language"));
+        let pinned = super::brainstorm_system_prompt(super::BRAINSTORM_SYSTEM_PROMPT, Some("ko"));
+        assert!(pinned.contains("in Korean"));
+        assert!(!pinned.contains("{language_rule}"));
 
-        assert_eq!(response.question, "Hi");
+        let response =
+            super::parse_ai_response("Plan ready\n<done>COMPLETE</done>", Some("zh")).unwrap();
+        assert_eq!(response.question, "需求收集完成");
+        let response = super::parse_ai_response("计划\n<done>COMPLETE</done>", Some("en")).unwrap();
+        assert_eq!(response.question, "Requirements complete");
     }
 
-    // --- Unit tests for truncate_to_title ---
+    #[test]
+    fn brainstorm_prompt_validation_checks_placeholders() {
+        assert!(super::validate_brainstorm_prompt(super::BRAINSTORM_SYSTEM_PROMPT).is_ok());
+        assert!(super::validate_brainstorm_prompt("  ").is_err());
+        assert!(super::validate_brainstorm_prompt("Ask short questions.")
+            .unwrap_err()
+            .contains("{language_rule}"));
+        assert!(
+            super::validate_brainstorm_prompt("{language_rule}\n{persona_name}")
+                .unwrap_err()
+                .contains("Unknown placeholder {persona_name}")
+        );
+    }
 
     #[test]
     fn truncate_to_title_short_message_unchanged() {
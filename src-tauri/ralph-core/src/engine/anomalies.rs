@@ -0,0 +1,87 @@
+use crate::security::sanitize_log;
+use crate::storage;
+use crate::storage::models::{CliType, ParseAnomaly};
+use chrono::Utc;
+
+/// Longest sample kept per anomaly kind, in characters
+const MAX_SAMPLE_CHARS: usize = 500;
+
+/// Collects adapter parse anomalies during a loop and persists them per CLI
+pub struct AnomalyRecorder {
+    cli: CliType,
+    pending: Vec<ParseAnomaly>,
+}
+
+impl AnomalyRecorder {
+    pub fn new(cli: CliType) -> Self {
+        Self {
+            cli,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Count an anomaly of `kind`, keeping a redacted sample of `line`
+    pub fn record(&mut self, kind: &str, line: &str) {
+        let now = Utc::now();
+        let sample: String = sanitize_log(line).chars().take(MAX_SAMPLE_CHARS).collect();
+        match self.pending.iter_mut().find(|a| a.kind == kind) {
+            Some(existing) => {
+                existing.count += 1;
+                existing.sample = sample;
+                existing.last_seen = now;
+            }
+            None => self.pending.push(ParseAnomaly {
+                cli: self.cli,
+                kind: kind.to_string(),
+                count: 1,
+                sample,
+                first_seen: now,
+                last_seen: now,
+            }),
+        }
+    }
+
+    /// Write pending anomalies to disk and reset the counters
+    pub fn flush(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        if let Err(err) = storage::record_parse_anomalies(self.cli, &self.pending) {
+            eprintln!("[Anomalies] Failed to record parse anomalies: {}", err);
+        }
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[test]
+    fn flush_merges_counts_and_redacts_samples() {
+        let _lock = lock_env();
+        let home = tempdir().unwrap();
+        let _home = EnvVarGuard::set("HOME", home.path());
+
+        let mut recorder = AnomalyRecorder::new(CliType::Codex);
+        recorder.record("unknown event type: x", r#"{"type":"x"}"#);
+        recorder.record("unknown event type: x", r#"{"type":"x"}"#);
+        recorder.flush();
+        recorder.record(
+            "unknown event type: x",
+            r#"{"type":"x","msg":"password=hunter2"}"#,
+        );
+        recorder.flush();
+
+        let stored = storage::load_parse_anomalies(CliType::Codex).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].count, 3);
+        assert!(stored[0].sample.contains("[REDACTED]"));
+        assert!(!stored[0].sample.contains("hunter2"));
+        assert!(storage::load_parse_anomalies(CliType::Claude)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+//! Copies files matching a task's artifact globs into the session after each
+//! iteration, so outputs like builds or screenshots can be compared per iteration.
+
+use crate::storage::models::IterationArtifact;
+use glob::{MatchOptions, Pattern};
+use std::fs;
+use std::path::{Component, Path};
+
+/// Cap per iteration so a broad glob like `**` cannot copy a whole tree
+pub const MAX_FILES_PER_ITERATION: usize = 200;
+/// Files larger than this are skipped
+pub const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Check that artifact globs are valid and stay inside the working directory
+pub fn validate_patterns(patterns: &[String]) -> Result<(), String> {
+    for pattern in patterns {
+        let trimmed = pattern.trim();
+        if trimmed.is_empty() {
+            return Err("Artifact pattern cannot be empty".to_string());
+        }
+        let escapes = Path::new(trimmed)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if escapes {
+            return Err(format!(
+                "Artifact pattern must be relative and stay inside the project: {}",
+                trimmed
+            ));
+        }
+        Pattern::new(trimmed)
+            .map_err(|e| format!("Invalid artifact pattern '{}': {}", trimmed, e))?;
+    }
+    Ok(())
+}
+
+/// Copy regular files under `root` matching `patterns` into `dest`, keeping
+/// their relative paths. Unreadable matches are skipped rather than failing the loop.
+pub fn collect(
+    root: &Path,
+    patterns: &[String],
+    dest: &Path,
+    iteration: u32,
+) -> Vec<IterationArtifact> {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    let escaped_root = Pattern::escape(&root.to_string_lossy());
+    let mut artifacts: Vec<IterationArtifact> = Vec::new();
+
+    for pattern in patterns {
+        let pattern = pattern.trim().trim_end_matches('/');
+        // A trailing `**` only matches directories; `dist/**` should mean everything under `dist`
+        let full = if pattern == "**" || pattern.ends_with("/**") {
+            format!("{}/{}/*", escaped_root, pattern)
+        } else {
+            format!("{}/{}", escaped_root, pattern)
+        };
+        let Ok(paths) = glob::glob_with(&full, options) else {
+            continue;
+        };
+        for path in paths.flatten() {
+            if artifacts.len() >= MAX_FILES_PER_ITERATION {
+                return artifacts;
+            }
+            // Symlinks could point outside the project
+            let Ok(metadata) = fs::symlink_metadata(&path) else {
+                continue;
+            };
+            if !metadata.is_file() || metadata.len() > MAX_FILE_BYTES {
+                continue;
+            }
+            let Ok(relative) = path.strip_prefix(root) else {
+                continue;
+            };
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            if artifacts.iter().any(|a| a.path == relative) {
+                continue;
+            }
+            let stored = dest.join(&relative);
+            let copied = stored
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::copy(&path, &stored));
+            if copied.is_ok() {
+                artifacts.push(IterationArtifact {
+                    iteration,
+                    path: relative,
+                    stored_path: stored.to_string_lossy().to_string(),
+                    size_bytes: metadata.len(),
+                });
+            }
+        }
+    }
+    artifacts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn validates_patterns() {
+        assert!(validate_patterns(&["dist/**".to_string(), "shots/*.png".to_string()]).is_ok());
+        assert!(validate_patterns(&["../secrets/*".to_string()]).is_err());
+        assert!(validate_patterns(&["/etc/*".to_string()]).is_err());
+        assert!(validate_patterns(&["  ".to_string()]).is_err());
+        assert!(validate_patterns(&["shots/[.png".to_string()]).is_err());
+    }
+
+    #[test]
+    fn copies_matching_files_with_relative_paths() {
+        let project = tempdir().unwrap();
+        let dest = tempdir().unwrap();
+        fs::create_dir_all(project.path().join("dist/assets")).unwrap();
+        fs::create_dir_all(project.path().join("shots")).unwrap();
+        fs::write(project.path().join("dist/index.html"), "<html>").unwrap();
+        fs::write(project.path().join("dist/assets/app.js"), "js").unwrap();
+        fs::write(project.path().join("shots/home.png"), "png").unwrap();
+        fs::write(project.path().join("shots/notes.txt"), "txt").unwrap();
+
+        let mut artifacts = collect(
+            project.path(),
+            &[
+                "dist/**".to_string(),
+                "shots/*.png".to_string(),
+                "dist/index.html".to_string(),
+            ],
+            dest.path(),
+            3,
+        );
+        artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let paths: Vec<&str> = artifacts.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec!["dist/assets/app.js", "dist/index.html", "shots/home.png"]
+        );
+        assert!(artifacts.iter().all(|a| a.iteration == 3));
+        assert_eq!(
+            fs::read_to_string(dest.path().join("dist/assets/app.js")).unwrap(),
+            "js"
+        );
+    }
+}
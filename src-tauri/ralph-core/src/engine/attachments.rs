@@ -0,0 +1,86 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Attachment directory, relative to the project root
+pub const ATTACHMENTS_DIR: &str = ".ralph/attachments";
+
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp"];
+
+pub fn attachments_dir(project_path: &Path) -> PathBuf {
+    project_path.join(ATTACHMENTS_DIR)
+}
+
+/// Copy an image into the project's attachment directory, returning its stored file name
+pub fn copy_attachment(project_path: &Path, source: &Path) -> Result<String, String> {
+    if !source.is_file() {
+        return Err(format!("Attachment not found: {}", source.display()));
+    }
+    let ext = source
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase())
+        .unwrap_or_default();
+    if !IMAGE_EXTENSIONS.contains(&ext.as_str()) {
+        return Err(format!("Unsupported attachment type: .{}", ext));
+    }
+    let stem = source
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("attachment");
+
+    let dir = attachments_dir(project_path);
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let mut name = format!("{}.{}", stem, ext);
+    let mut suffix = 1;
+    while dir.join(&name).exists() {
+        suffix += 1;
+        name = format!("{}-{}.{}", stem, suffix, ext);
+    }
+    fs::copy(source, dir.join(&name)).map_err(|e| e.to_string())?;
+    Ok(name)
+}
+
+/// Delete a stored attachment; missing files are ignored
+pub fn remove_attachment(project_path: &Path, name: &str) -> Result<(), String> {
+    let path = attachments_dir(project_path).join(name);
+    if path.parent() != Some(attachments_dir(project_path).as_path()) {
+        return Err(format!("Invalid attachment name: {}", name));
+    }
+    if path.exists() {
+        fs::remove_file(path).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn copy_keeps_existing_attachments() {
+        let project = tempdir().unwrap();
+        let source = tempdir().unwrap();
+        let image = source.path().join("Mockup.PNG");
+        fs::write(&image, b"png").unwrap();
+
+        assert_eq!(
+            copy_attachment(project.path(), &image).unwrap(),
+            "Mockup.png"
+        );
+        assert_eq!(
+            copy_attachment(project.path(), &image).unwrap(),
+            "Mockup-2.png"
+        );
+        assert!(attachments_dir(project.path())
+            .join("Mockup-2.png")
+            .exists());
+
+        let text = source.path().join("notes.txt");
+        fs::write(&text, b"x").unwrap();
+        assert!(copy_attachment(project.path(), &text).is_err());
+        assert!(remove_attachment(project.path(), "../state.json").is_err());
+        remove_attachment(project.path(), "Mockup.png").unwrap();
+        assert!(!attachments_dir(project.path()).join("Mockup.png").exists());
+    }
+}
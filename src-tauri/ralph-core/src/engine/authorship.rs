@@ -0,0 +1,105 @@
+//! Author identity of auto-commits, so agent commits stand apart from human
+//! ones in `git log` and blame.
+
+use crate::storage::models::{CliType, CommitIdentity};
+
+/// Reject values git would mangle: line breaks, or angle brackets that end the name
+pub fn validate(identity: &CommitIdentity) -> Result<(), String> {
+    let fields = [("name", &identity.name), ("email", &identity.email)];
+    for (field, value) in fields {
+        let Some(value) = value else {
+            continue;
+        };
+        if value.trim().is_empty() {
+            return Err(format!("Commit author {} cannot be empty", field));
+        }
+        if value.contains(['<', '>', '\n', '\r']) {
+            return Err(format!(
+                "Commit author {} contains invalid characters",
+                field
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn cli_slug(cli_type: CliType) -> String {
+    format!("{:?}", cli_type).to_ascii_lowercase()
+}
+
+/// `git` arguments for an auto-commit: `-c` overrides for the configured
+/// author, then `commit` with the message and the optional co-author trailer
+pub fn commit_args(
+    identity: &CommitIdentity,
+    cli_type: CliType,
+    cli_name: &str,
+    message: &str,
+) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(name) = &identity.name {
+        args.push("-c".to_string());
+        args.push(format!("user.name={}", name.trim()));
+    }
+    if let Some(email) = &identity.email {
+        args.push("-c".to_string());
+        args.push(format!("user.email={}", email.trim()));
+    }
+    let mut message = message.to_string();
+    // Appended by hand since `git commit --trailer` needs git 2.32
+    if identity.co_author_trailer {
+        message.push_str(&format!(
+            "\n\nCo-authored-by: {} <{}@ralph.local>",
+            cli_name,
+            cli_slug(cli_type)
+        ));
+    }
+    args.extend(["commit".to_string(), "-m".to_string(), message]);
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overrides_author_and_adds_trailer() {
+        let identity = CommitIdentity {
+            name: Some("Ralph".to_string()),
+            email: Some("ralph@local".to_string()),
+            co_author_trailer: true,
+        };
+        assert_eq!(
+            commit_args(&identity, CliType::Codex, "Codex CLI", "Add parser"),
+            vec![
+                "-c",
+                "user.name=Ralph",
+                "-c",
+                "user.email=ralph@local",
+                "commit",
+                "-m",
+                "Add parser\n\nCo-authored-by: Codex CLI <codex@ralph.local>",
+            ]
+        );
+        assert_eq!(
+            commit_args(
+                &CommitIdentity::default(),
+                CliType::Claude,
+                "Claude Code",
+                "Fix"
+            ),
+            vec!["commit", "-m", "Fix"]
+        );
+    }
+
+    #[test]
+    fn rejects_names_git_would_mangle() {
+        let identity = |name: &str| CommitIdentity {
+            name: Some(name.to_string()),
+            ..CommitIdentity::default()
+        };
+        assert!(validate(&identity("Ralph Bot")).is_ok());
+        assert!(validate(&identity("Ralph <bot>")).is_err());
+        assert!(validate(&identity(" ")).is_err());
+        assert!(validate(&CommitIdentity::default()).is_ok());
+    }
+}
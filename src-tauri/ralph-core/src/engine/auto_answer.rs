@@ -0,0 +1,133 @@
+//! Answers the questions CLIs occasionally stop on, such as "Do you want to
+//! proceed? [y/N]", which would otherwise sit until the idle timeout kills
+//! the iteration.
+
+use crate::storage::models::PromptAnswer;
+use regex::Regex;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Label of the highlight recorded for every answer
+pub const ANSWER_LABEL: &str = "auto-answer";
+/// Quiet time after which an unfinished output line is taken for a prompt
+pub const PROMPT_QUIET: Duration = Duration::from_secs(2);
+
+/// Default answers. Patterns are anchored at the end of the line so the
+/// echoed reply does not match again.
+pub fn builtin_answers() -> Vec<PromptAnswer> {
+    let answer = |pattern: &str, response: &str| PromptAnswer {
+        pattern: pattern.to_string(),
+        response: response.to_string(),
+    };
+    vec![
+        answer(r"(?i)[\[(]y/n[\])]\s*[?:]?\s*$", "y"),
+        answer(r"(?i)\(yes/no\)\s*[?:]?\s*$", "yes"),
+        answer(
+            r"(?i)\b(do you want|would you like) to (proceed|continue)\?\s*$",
+            "y",
+        ),
+        answer(r"(?i)\bpress (enter|return) to continue\W*$", ""),
+    ]
+}
+
+/// Reject answers with invalid regexes
+pub fn validate_answers(answers: &[PromptAnswer]) -> Result<(), String> {
+    PromptAnswerer::new(answers).map(|_| ())
+}
+
+/// Colors and cursor movement, which would keep patterns from matching
+fn strip_escapes(line: &str) -> std::borrow::Cow<'_, str> {
+    static ESCAPES: OnceLock<Regex> = OnceLock::new();
+    ESCAPES
+        .get_or_init(|| {
+            Regex::new(r"\x1b\[[0-9;?]*[ -/]*[@-~]|\x1b\][^\x07\x1b]*(\x07|\x1b\\)")
+                .expect("valid escape pattern")
+        })
+        .replace_all(line, "")
+}
+
+/// Picks the reply for a prompt in the output, answering each prompt once
+#[derive(Debug, Default)]
+pub struct PromptAnswerer {
+    answers: Vec<(Regex, String)>,
+    /// Prompt last answered, so a prompt still on screen is not answered twice
+    answered: Option<String>,
+}
+
+impl PromptAnswerer {
+    pub fn new(answers: &[PromptAnswer]) -> Result<Self, String> {
+        let answers = answers
+            .iter()
+            .map(|answer| {
+                let re = Regex::new(&answer.pattern)
+                    .map_err(|e| format!("Invalid prompt answer {}: {}", answer.pattern, e))?;
+                Ok((re, answer.response.clone()))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            answers,
+            answered: None,
+        })
+    }
+
+    /// The prompt and its reply when `line` asks a question with a configured answer
+    pub fn answer(&mut self, line: &str) -> Option<(String, String)> {
+        let prompt = strip_escapes(line).trim().to_string();
+        if prompt.is_empty() || self.answered.as_deref() == Some(prompt.as_str()) {
+            return None;
+        }
+        let reply = self
+            .answers
+            .iter()
+            .find(|(re, _)| re.is_match(&prompt))
+            .map(|(_, reply)| reply.clone());
+        self.answered = reply.as_ref().map(|_| prompt.clone());
+        reply.map(|reply| (prompt, reply))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_answers_reply_once_per_prompt() {
+        let mut answerer = PromptAnswerer::new(&builtin_answers()).unwrap();
+        let reply = |answerer: &mut PromptAnswerer, line: &str| {
+            answerer.answer(line).map(|(_, reply)| reply)
+        };
+
+        assert_eq!(
+            reply(&mut answerer, "Do you want to proceed? [y/N] ").as_deref(),
+            Some("y")
+        );
+        // Still on screen at the next check
+        assert_eq!(reply(&mut answerer, "Do you want to proceed? [y/N] "), None);
+        assert_eq!(
+            reply(&mut answerer, "Do you want to proceed? [y/N] y"),
+            None
+        );
+        assert_eq!(
+            reply(&mut answerer, "\x1b[1mOverwrite config? (yes/no)\x1b[0m").as_deref(),
+            Some("yes")
+        );
+        assert_eq!(
+            reply(&mut answerer, "Press Enter to continue...").as_deref(),
+            Some("")
+        );
+        assert_eq!(reply(&mut answerer, "Compiling ralph-desktop v0.1.0"), None);
+        assert_eq!(
+            reply(&mut answerer, "Do you want to proceed? [y/N] ").as_deref(),
+            Some("y")
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        let answers = vec![PromptAnswer {
+            pattern: "(unclosed".to_string(),
+            response: "y".to_string(),
+        }];
+        assert!(validate_answers(&answers).is_err());
+    }
+}
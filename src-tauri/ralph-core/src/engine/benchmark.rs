@@ -0,0 +1,477 @@
+//! Runs the same task with several CLIs, or several prompts with one CLI, each
+//! in a detached git worktree of one commit, and renders the collected metrics
+//! as a comparison table.
+
+use super::screenshot::shell_command;
+use super::LoopState;
+use crate::adapters::{get_adapter, hide_console_window};
+use crate::storage::models::{
+    BenchmarkOutcome, BenchmarkReport, BenchmarkResult, CliFiles, DiffStats, Divergence,
+    PromptExperiment,
+};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Test commands that run longer than this count as failing
+const TEST_TIMEOUT: Duration = Duration::from_secs(600);
+
+const TABLE_HEADER: &str = "| Outcome | Iterations | Time | Tests | Cost |";
+
+/// Commit the project's HEAD points at, so every run starts from the same code
+pub async fn head_commit(project_path: &Path) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(project_path).args(["rev-parse", "HEAD"]);
+    hide_console_window(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err("The project has no commit to start from".to_string())
+    }
+}
+
+/// Check out `rev` into `path` without a branch
+pub async fn add_worktree(project_path: &Path, path: &Path, rev: &str) -> Result<(), String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(project_path)
+        .args(["worktree", "add", "--detach"])
+        .arg(path)
+        .arg(rev);
+    hide_console_window(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(format!("git worktree add failed: {}", stderr.trim()))
+    }
+}
+
+/// Remove a benchmark worktree, discarding whatever the CLI left in it
+pub async fn remove_worktree(project_path: &Path, path: &Path) {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(project_path)
+        .args(["worktree", "remove", "--force"])
+        .arg(path);
+    hide_console_window(&mut cmd);
+    let _ = cmd.output().await;
+}
+
+/// Run the benchmark's test command in a worktree; `true` when it exits zero
+pub async fn run_tests(command: &str, dir: &Path) -> bool {
+    let mut cmd = shell_command(command, dir);
+    cmd.stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    matches!(
+        tokio::time::timeout(TEST_TIMEOUT, cmd.status()).await,
+        Ok(Ok(status)) if status.success()
+    )
+}
+
+/// Totals and paths of `git diff --numstat` output; binary files count no lines
+fn parse_numstat(numstat: &str) -> DiffStats {
+    let mut stats = DiffStats::default();
+    for line in numstat.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(insertions), Some(deletions), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        stats.insertions += insertions.parse::<u64>().unwrap_or(0);
+        stats.deletions += deletions.parse::<u64>().unwrap_or(0);
+        stats.files.push(path.to_string());
+    }
+    stats.files.sort();
+    stats
+}
+
+/// What a worktree changed from `base_commit`, including uncommitted and new
+/// files; marks new files in the worktree's index to see them
+pub async fn diff_stats(worktree: &Path, base_commit: &str) -> Option<DiffStats> {
+    let git = |args: &[&str]| {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(worktree).args(args);
+        hide_console_window(&mut cmd);
+        cmd
+    };
+    let added = git(&["add", "-A", "--intent-to-add"]).output().await.ok()?;
+    if !added.status.success() {
+        return None;
+    }
+    let output = git(&["diff", "--numstat", "--no-renames", base_commit])
+        .output()
+        .await
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| parse_numstat(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Files every run changed, files only one run changed and whether the test
+/// results differ; `None` until two runs have diff stats
+pub fn divergence(results: &[BenchmarkResult]) -> Option<Divergence> {
+    let diffs: Vec<(&BenchmarkResult, BTreeSet<&str>)> = results
+        .iter()
+        .filter_map(|result| {
+            let diff = result.diff.as_ref()?;
+            Some((result, diff.files.iter().map(String::as_str).collect()))
+        })
+        .collect();
+    if diffs.len() < 2 {
+        return None;
+    }
+    let shared_files = diffs[0]
+        .1
+        .iter()
+        .filter(|file| diffs.iter().all(|(_, files)| files.contains(*file)))
+        .map(|file| file.to_string())
+        .collect();
+    let unique_files = diffs
+        .iter()
+        .enumerate()
+        .map(|(i, (result, files))| CliFiles {
+            cli: result.cli,
+            files: files
+                .iter()
+                .filter(|file| {
+                    diffs
+                        .iter()
+                        .enumerate()
+                        .all(|(j, (_, other))| i == j || !other.contains(*file))
+                })
+                .map(|file| file.to_string())
+                .collect(),
+        })
+        .collect();
+    let tests: Option<Vec<bool>> = results.iter().map(|r| r.tests_passed).collect();
+    let tests_differ = tests.map(|tests| tests.iter().any(|passed| *passed != tests[0]));
+    Some(Divergence {
+        shared_files,
+        unique_files,
+        tests_differ,
+    })
+}
+
+/// Outcome and iteration count of a finished loop
+pub fn outcome_of(result: &Result<LoopState, String>) -> (BenchmarkOutcome, u32) {
+    match result {
+        Ok(LoopState::Completed { iteration }) => (BenchmarkOutcome::Completed, *iteration),
+        Ok(LoopState::MaxIterationsReached { iteration }) => {
+            (BenchmarkOutcome::MaxIterationsReached, *iteration)
+        }
+        Ok(LoopState::Failed { iteration }) | Ok(LoopState::PathMissing { iteration }) => {
+            (BenchmarkOutcome::Failed, *iteration)
+        }
+        Ok(_) => (BenchmarkOutcome::Stopped, 0),
+        Err(_) => (BenchmarkOutcome::Error, 0),
+    }
+}
+
+fn settings_line(max_iterations: u32, test_command: Option<&str>) -> String {
+    format!(
+        "Max iterations: {}. Test command: {}.",
+        max_iterations,
+        test_command.map_or("none".to_string(), |c| format!("`{}`", c))
+    )
+}
+
+/// Table row for one run, led by `name`
+fn result_row(name: &str, result: &BenchmarkResult) -> String {
+    let outcome = match result.outcome {
+        BenchmarkOutcome::Completed => "completed",
+        BenchmarkOutcome::MaxIterationsReached => "max iterations",
+        BenchmarkOutcome::Failed => "failed",
+        BenchmarkOutcome::Stopped => "stopped",
+        BenchmarkOutcome::Error => "error",
+    };
+    let tests = match result.tests_passed {
+        Some(true) => "pass",
+        Some(false) => "fail",
+        None => "-",
+    };
+    let cost = result
+        .cost_usd
+        .map_or("-".to_string(), |cost| format!("${:.2}", cost));
+    format!(
+        "| {} | {} | {} | {:.1}s | {} | {} |",
+        name,
+        outcome,
+        result.iterations,
+        result.duration_ms as f64 / 1000.0,
+        tests,
+        cost
+    )
+}
+
+/// Comparison table of a benchmark's results
+pub fn render_markdown(report: &BenchmarkReport) -> String {
+    let mut lines = vec![
+        format!("# Benchmark {}", report.id),
+        String::new(),
+        settings_line(report.max_iterations, report.test_command.as_deref()),
+        String::new(),
+        format!("| CLI {}", TABLE_HEADER),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for result in &report.results {
+        lines.push(result_row(get_adapter(result.cli).name(), result));
+    }
+    if let Some(divergence) = divergence(&report.results) {
+        lines.push(String::new());
+        lines.push("## Divergence".to_string());
+        lines.push(String::new());
+        for result in &report.results {
+            if let Some(diff) = &result.diff {
+                lines.push(format!(
+                    "- {}: {} files, +{} -{}",
+                    get_adapter(result.cli).name(),
+                    diff.files.len(),
+                    diff.insertions,
+                    diff.deletions
+                ));
+            }
+        }
+        lines.push(format!(
+            "- Changed by every CLI: {}",
+            file_list(&divergence.shared_files)
+        ));
+        for unique in &divergence.unique_files {
+            lines.push(format!(
+                "- Only {}: {}",
+                get_adapter(unique.cli).name(),
+                file_list(&unique.files)
+            ));
+        }
+        if let Some(differ) = divergence.tests_differ {
+            lines.push(format!(
+                "- Tests: {}",
+                if differ {
+                    "results differ"
+                } else {
+                    "same result"
+                }
+            ));
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+fn file_list(files: &[String]) -> String {
+    if files.is_empty() {
+        return "none".to_string();
+    }
+    files
+        .iter()
+        .map(|file| format!("`{}`", file))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Comparison table of a prompt experiment, followed by the prompts themselves
+pub fn render_experiment_markdown(experiment: &PromptExperiment) -> String {
+    let mut lines = vec![
+        format!("# Prompt experiment {}", experiment.id),
+        String::new(),
+        format!(
+            "{} from commit `{}`. {}",
+            get_adapter(experiment.cli).name(),
+            experiment.base_commit,
+            settings_line(
+                experiment.max_iterations,
+                experiment.test_command.as_deref()
+            )
+        ),
+        String::new(),
+        format!("| Variant {}", TABLE_HEADER),
+        "| --- | --- | --- | --- | --- | --- |".to_string(),
+    ];
+    for (variant, result) in experiment.variants.iter().zip(&experiment.results) {
+        lines.push(result_row(&variant.label, result));
+    }
+    for variant in &experiment.variants {
+        lines.push(String::new());
+        lines.push(format!("## Variant {}", variant.label));
+        lines.push(String::new());
+        lines.push(variant.prompt.trim().to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::{CliType, PromptVariant};
+    use chrono::Utc;
+
+    #[test]
+    fn maps_loop_results_to_outcomes() {
+        assert_eq!(
+            outcome_of(&Ok(LoopState::Completed { iteration: 3 })),
+            (BenchmarkOutcome::Completed, 3)
+        );
+        assert_eq!(
+            outcome_of(&Ok(LoopState::Idle)),
+            (BenchmarkOutcome::Stopped, 0)
+        );
+        assert_eq!(
+            outcome_of(&Err("spawn failed".to_string())),
+            (BenchmarkOutcome::Error, 0)
+        );
+    }
+
+    fn result(
+        cli: CliType,
+        outcome: BenchmarkOutcome,
+        tests_passed: Option<bool>,
+        cost_usd: Option<f64>,
+    ) -> BenchmarkResult {
+        BenchmarkResult {
+            cli,
+            outcome,
+            iterations: 4,
+            duration_ms: 95_500,
+            tests_passed,
+            cost_usd,
+            session_id: None,
+            worktree_path: String::new(),
+            diff: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn renders_comparison_table() {
+        let report = BenchmarkReport {
+            id: "2026-01-02T03-04-05".to_string(),
+            project_id: uuid::Uuid::nil(),
+            started_at: Utc::now(),
+            finished_at: None,
+            clis: vec![CliType::Claude, CliType::Codex],
+            max_iterations: 10,
+            test_command: Some("npm test".to_string()),
+            results: vec![
+                result(
+                    CliType::Claude,
+                    BenchmarkOutcome::Completed,
+                    Some(true),
+                    Some(1.234),
+                ),
+                result(
+                    CliType::Codex,
+                    BenchmarkOutcome::MaxIterationsReached,
+                    Some(false),
+                    None,
+                ),
+            ],
+            divergence: None,
+        };
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("Max iterations: 10. Test command: `npm test`."));
+        assert!(markdown.contains("| Claude Code | completed | 4 | 95.5s | pass | $1.23 |"));
+        assert!(markdown.contains("| Codex CLI | max iterations | 4 | 95.5s | fail | - |"));
+        assert!(!markdown.contains("## Divergence"));
+    }
+
+    #[test]
+    fn reports_divergence_between_runs() {
+        assert_eq!(
+            parse_numstat("3\t1\tsrc/b.rs\n-\t-\tlogo.png\n10\t0\tsrc/a.rs\n"),
+            DiffStats {
+                insertions: 13,
+                deletions: 1,
+                files: vec![
+                    "logo.png".to_string(),
+                    "src/a.rs".to_string(),
+                    "src/b.rs".to_string()
+                ],
+            }
+        );
+        let with_diff = |cli, tests_passed, numstat: &str| BenchmarkResult {
+            diff: Some(parse_numstat(numstat)),
+            ..result(cli, BenchmarkOutcome::Completed, tests_passed, None)
+        };
+        let results = vec![
+            with_diff(
+                CliType::Claude,
+                Some(true),
+                "3\t1\tsrc/a.rs\n2\t0\tsrc/b.rs\n",
+            ),
+            with_diff(
+                CliType::Codex,
+                Some(false),
+                "5\t2\tsrc/a.rs\n1\t0\tsrc/c.rs\n",
+            ),
+        ];
+        let found = divergence(&results).unwrap();
+        assert_eq!(found.shared_files, vec!["src/a.rs".to_string()]);
+        assert_eq!(found.unique_files[0].files, vec!["src/b.rs".to_string()]);
+        assert_eq!(found.unique_files[1].files, vec!["src/c.rs".to_string()]);
+        assert_eq!(found.tests_differ, Some(true));
+        assert!(divergence(&results[..1]).is_none());
+
+        let report = BenchmarkReport {
+            id: "2026-01-02T03-04-05".to_string(),
+            project_id: uuid::Uuid::nil(),
+            started_at: Utc::now(),
+            finished_at: None,
+            clis: vec![CliType::Claude, CliType::Codex],
+            max_iterations: 10,
+            test_command: None,
+            results,
+            divergence: None,
+        };
+        let markdown = render_markdown(&report);
+        assert!(markdown.contains("- Claude Code: 2 files, +5 -1"));
+        assert!(markdown.contains("- Changed by every CLI: `src/a.rs`"));
+        assert!(markdown.contains("- Only Codex CLI: `src/c.rs`"));
+        assert!(markdown.contains("- Tests: results differ"));
+    }
+
+    #[test]
+    fn renders_experiment_with_prompts() {
+        let variant = |label: &str, prompt: &str| PromptVariant {
+            label: label.to_string(),
+            prompt: prompt.to_string(),
+        };
+        let experiment = PromptExperiment {
+            id: "2026-01-02T03-04-05".to_string(),
+            project_id: uuid::Uuid::nil(),
+            started_at: Utc::now(),
+            finished_at: None,
+            cli: CliType::Claude,
+            base_commit: "abc1234".to_string(),
+            max_iterations: 5,
+            test_command: None,
+            variants: vec![
+                variant("A", "Fix the bug."),
+                variant("B", "Write a failing test, then fix the bug."),
+            ],
+            results: vec![result(
+                CliType::Claude,
+                BenchmarkOutcome::Completed,
+                None,
+                None,
+            )],
+        };
+        let markdown = render_experiment_markdown(&experiment);
+        assert!(markdown
+            .contains("Claude Code from commit `abc1234`. Max iterations: 5. Test command: none."));
+        assert!(markdown.contains("| A | completed | 4 | 95.5s | - | - |"));
+        assert!(!markdown.contains("| B |"));
+        assert!(markdown.contains("## Variant B\n\nWrite a failing test, then fix the bug.\n"));
+    }
+}
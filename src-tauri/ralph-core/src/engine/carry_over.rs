@@ -0,0 +1,80 @@
+//! Progress carried from one iteration to the next. Every iteration starts a
+//! fresh CLI process, so the previous one's assistant output is summarized by
+//! a readonly CLI call and put in front of the next prompt.
+
+use std::time::Duration;
+
+/// Longest the summarizing CLI call may take
+pub const SUMMARY_TIMEOUT: Duration = Duration::from_secs(120);
+/// End of the assistant output handed to the summarizer
+const MAX_TRANSCRIPT_CHARS: usize = 12_000;
+/// Longest summary added to the next prompt
+const MAX_SUMMARY_CHARS: usize = 1_500;
+
+/// Prompt asking the CLI to summarize iteration `iteration` from its output
+pub fn summary_prompt(iteration: u32, assistant_text: &str) -> Option<String> {
+    let text = assistant_text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let count = text.chars().count();
+    let text: String = text
+        .chars()
+        .skip(count.saturating_sub(MAX_TRANSCRIPT_CHARS))
+        .collect();
+    Some(format!(
+        "Below is what a coding agent said during iteration {iteration} of a task. Summarize \
+         its progress for the agent that continues the task in a fresh session.\n\
+         Rules:\n\
+         - Output only the summary, as at most 8 short bullet points.\n\
+         - Cover what was done, what was found out and what is left or failing.\n\
+         - Name files, commands and errors exactly; leave out pleasantries.\n\n\
+         Agent output:\n{text}\n"
+    ))
+}
+
+/// The summarizer's output, trimmed and cut to a size the prompt can afford;
+/// `None` when it is empty
+pub fn bound_summary(output: &str) -> Option<String> {
+    let summary = output.trim();
+    if summary.is_empty() {
+        return None;
+    }
+    if summary.chars().count() <= MAX_SUMMARY_CHARS {
+        return Some(summary.to_string());
+    }
+    let mut cut: String = summary.chars().take(MAX_SUMMARY_CHARS).collect();
+    if let Some(end) = cut.rfind('\n') {
+        cut.truncate(end);
+    }
+    cut.push_str("\n...");
+    Some(cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_keeps_the_end_of_long_output() {
+        assert!(summary_prompt(1, "  \n").is_none());
+        let output = format!("{}LAST", "x".repeat(MAX_TRANSCRIPT_CHARS));
+        let prompt = summary_prompt(3, &output).unwrap();
+        assert!(prompt.contains("iteration 3"));
+        assert!(prompt.ends_with("LAST\n"));
+        assert!(!prompt.contains(&"x".repeat(MAX_TRANSCRIPT_CHARS)));
+    }
+
+    #[test]
+    fn summaries_are_bounded_at_a_line_break() {
+        assert_eq!(
+            bound_summary(" - Added login\n"),
+            Some("- Added login".to_string())
+        );
+        assert_eq!(bound_summary(""), None);
+        let long = "- step\n".repeat(MAX_SUMMARY_CHARS);
+        let bounded = bound_summary(&long).unwrap();
+        assert!(bounded.chars().count() <= MAX_SUMMARY_CHARS + 4);
+        assert!(bounded.ends_with("- step\n..."));
+    }
+}
@@ -0,0 +1,193 @@
+//! CHANGELOG entries for loop sessions, in Keep a Changelog format: the
+//! session's commit subjects sorted into Added/Changed/Fixed/Removed under a
+//! dated heading, led by the summary the agent completed with.
+
+use crate::adapters::hide_console_window;
+use chrono::NaiveDate;
+use std::path::Path;
+use tokio::process::Command;
+
+pub const CHANGELOG_FILE: &str = "CHANGELOG.md";
+
+const HEADER: &str = "# Changelog\n\n\
+All notable changes to this project will be documented in this file.\n\n\
+The format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/).\n";
+
+/// Sections in the order Keep a Changelog lists them
+const SECTIONS: &[&str] = &["Added", "Changed", "Removed", "Fixed"];
+
+/// Prefix of Ralph's own fallback and housekeeping commits, left out of entries
+const HOUSEKEEPING_PREFIX: &str = "ralph: ";
+
+/// Section of a commit and its subject without a conventional-commit prefix
+fn classify(subject: &str) -> (&'static str, String) {
+    let (kind, rest) = match subject.split_once(':') {
+        Some((kind, rest))
+            if !kind.contains(' ') && !kind.is_empty() && !rest.trim().is_empty() =>
+        {
+            (
+                kind.trim_end_matches('!')
+                    .split('(')
+                    .next()
+                    .unwrap_or(kind)
+                    .to_lowercase(),
+                rest.trim(),
+            )
+        }
+        _ => (String::new(), subject.trim()),
+    };
+    let first_word = rest.split_whitespace().next().unwrap_or("").to_lowercase();
+    let section = match (kind.as_str(), first_word.as_str()) {
+        ("feat" | "feature", _) | (_, "add" | "adds" | "added" | "implement" | "create") => "Added",
+        ("fix" | "bugfix", _) | (_, "fix" | "fixes" | "fixed") => "Fixed",
+        (_, "remove" | "removes" | "removed" | "delete" | "deletes" | "drop") => "Removed",
+        _ => "Changed",
+    };
+    let mut text = rest.to_string();
+    if let Some(first) = text.get(..1) {
+        text.replace_range(..1, &first.to_uppercase());
+    }
+    (section, text)
+}
+
+/// Whether a commit subject describes a fix, the way entries sort it
+pub fn is_fix(subject: &str) -> bool {
+    !subject.starts_with(HOUSEKEEPING_PREFIX) && classify(subject).0 == "Fixed"
+}
+
+/// Markdown entry for a session from its date, completion summary and commit subjects
+pub fn render_entry(date: NaiveDate, summary: Option<&str>, subjects: &[String]) -> String {
+    let mut entry = format!("## [{}]\n", date.format("%Y-%m-%d"));
+    if let Some(summary) = summary.map(str::trim).filter(|s| !s.is_empty()) {
+        entry.push_str(&format!("\n{}\n", summary));
+    }
+    let classified: Vec<(&str, String)> = subjects
+        .iter()
+        .filter(|s| !s.starts_with(HOUSEKEEPING_PREFIX))
+        .map(|s| classify(s))
+        .collect();
+    for section in SECTIONS {
+        let items: Vec<&String> = classified
+            .iter()
+            .filter(|(s, _)| s == section)
+            .map(|(_, text)| text)
+            .collect();
+        if items.is_empty() {
+            continue;
+        }
+        entry.push_str(&format!("\n### {}\n\n", section));
+        for item in items {
+            entry.push_str(&format!("- {}\n", item));
+        }
+    }
+    entry
+}
+
+/// `existing` with `entry` as its newest release, or a new changelog holding it
+pub fn insert_entry(existing: Option<&str>, entry: &str) -> String {
+    let Some(existing) = existing.filter(|text| !text.trim().is_empty()) else {
+        return format!("{}\n{}", HEADER, entry);
+    };
+    // Newest entries go first, below an Unreleased section when there is one
+    let position = existing
+        .match_indices("\n## ")
+        .map(|(i, _)| i + 1)
+        .find(|i| !existing[*i..].starts_with("## [Unreleased]"));
+    match position {
+        Some(i) => format!("{}{}\n{}", &existing[..i], entry, &existing[i..]),
+        None => format!("{}\n\n{}", existing.trim_end(), entry),
+    }
+}
+
+pub(crate) async fn run_git(project_path: &Path, args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(project_path).args(args);
+    hide_console_window(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git failed: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Subjects of the commits after `from` up to `to`, oldest first; the whole
+/// history up to `to` when the session started before the first commit
+pub async fn commit_subjects(
+    project_path: &Path,
+    from: Option<&str>,
+    to: &str,
+) -> Result<Vec<String>, String> {
+    let range = match from {
+        Some(from) => format!("{}..{}", from, to),
+        None => to.to_string(),
+    };
+    let log = run_git(
+        project_path,
+        &["log", "--reverse", "--no-merges", "--format=%s", &range],
+    )
+    .await?;
+    Ok(log
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Commit the changelog alone with `commit_args` from `authorship::commit_args`;
+/// returns the short sha
+pub async fn commit(project_path: &Path, commit_args: Vec<String>) -> Result<String, String> {
+    run_git(project_path, &["add", "--", CHANGELOG_FILE]).await?;
+    let mut args = commit_args;
+    args.extend(["--".to_string(), CHANGELOG_FILE.to_string()]);
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    run_git(project_path, &args).await?;
+    let sha = run_git(project_path, &["rev-parse", "--short", "HEAD"]).await?;
+    Ok(sha.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorts_commits_into_sections() {
+        let subjects = vec![
+            "feat(api): add login endpoint".to_string(),
+            "Fix crash on empty input".to_string(),
+            "ralph: iteration 3".to_string(),
+            "refactor: split the parser".to_string(),
+            "Remove the legacy importer".to_string(),
+        ];
+        let date = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        assert_eq!(
+            render_entry(date, Some("Login works end to end."), &subjects),
+            "## [2026-10-16]\n\nLogin works end to end.\n\n\
+             ### Added\n\n- Add login endpoint\n\n\
+             ### Changed\n\n- Split the parser\n\n\
+             ### Removed\n\n- Remove the legacy importer\n\n\
+             ### Fixed\n\n- Fix crash on empty input\n"
+        );
+    }
+
+    #[test]
+    fn inserts_new_entries_below_unreleased() {
+        let entry = "## [2026-10-16]\n\n### Added\n\n- Login\n";
+        let created = insert_entry(None, entry);
+        assert!(created.starts_with("# Changelog\n"));
+        assert!(created.ends_with(entry));
+
+        let existing =
+            "# Changelog\n\n## [Unreleased]\n\n- WIP\n\n## [1.0.0] - 2026-01-01\n\n- First\n";
+        assert_eq!(
+            insert_entry(Some(existing), entry),
+            "# Changelog\n\n## [Unreleased]\n\n- WIP\n\n\
+             ## [2026-10-16]\n\n### Added\n\n- Login\n\n\
+             ## [1.0.0] - 2026-01-01\n\n- First\n"
+        );
+    }
+}
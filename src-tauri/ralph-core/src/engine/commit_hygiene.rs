@@ -0,0 +1,237 @@
+//! Keeps secrets and large binaries out of auto-commits: changed files matching
+//! a deny glob or above the size limit are left unstaged and reported.
+
+use crate::storage::models::CommitHygiene;
+use glob::{MatchOptions, Pattern};
+
+/// How many skipped paths the iteration report lists by name
+const REPORTED_PATHS: usize = 5;
+
+/// A changed file left out of the commit
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkippedFile {
+    pub path: String,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    Denied(String),
+    TooLarge(u64),
+}
+
+/// A path reported by `git status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChangedFile {
+    pub path: String,
+    pub deleted: bool,
+}
+
+/// Check that deny globs are valid
+pub fn validate(hygiene: &CommitHygiene) -> Result<(), String> {
+    for glob in &hygiene.deny_globs {
+        let trimmed = glob.trim();
+        if trimmed.is_empty() {
+            return Err("Commit deny pattern cannot be empty".to_string());
+        }
+        Pattern::new(trimmed)
+            .map_err(|e| format!("Invalid commit deny pattern '{}': {}", trimmed, e))?;
+    }
+    Ok(())
+}
+
+/// Changed paths from `git status --porcelain -z --untracked-files=all`
+pub fn parse_status(status: &str) -> Vec<ChangedFile> {
+    let mut files = Vec::new();
+    let mut entries = status.split('\0').filter(|e| !e.is_empty());
+    while let Some(entry) = entries.next() {
+        if entry.len() < 4 {
+            continue;
+        }
+        let (code, path) = entry.split_at(3);
+        // Renames and copies are followed by their original path
+        if code.starts_with('R') || code.starts_with('C') {
+            entries.next();
+        }
+        files.push(ChangedFile {
+            path: path.to_string(),
+            deleted: code.contains('D'),
+        });
+    }
+    files
+}
+
+/// Deny glob matching `path` or one of its trailing parts
+fn denied_by<'a>(patterns: &'a [(String, Pattern)], path: &str) -> Option<&'a str> {
+    let options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    let suffixes =
+        std::iter::once(path).chain(path.match_indices('/').map(|(i, _)| &path[i + 1..]));
+    for suffix in suffixes {
+        if let Some((glob, _)) = patterns
+            .iter()
+            .find(|(_, pattern)| pattern.matches_with(suffix, options))
+        {
+            return Some(glob);
+        }
+    }
+    None
+}
+
+/// Changed files the commit must leave out. `size_of` returns a file's size
+/// when it can be read; deletions are always committed.
+pub fn check(
+    hygiene: &CommitHygiene,
+    files: &[ChangedFile],
+    size_of: impl Fn(&str) -> Option<u64>,
+) -> Vec<SkippedFile> {
+    let patterns: Vec<(String, Pattern)> = hygiene
+        .deny_globs
+        .iter()
+        .filter_map(|glob| {
+            let glob = glob.trim();
+            Pattern::new(glob).ok().map(|p| (glob.to_string(), p))
+        })
+        .collect();
+
+    files
+        .iter()
+        .filter(|file| !file.deleted)
+        .filter_map(|file| {
+            let reason = if let Some(glob) = denied_by(&patterns, &file.path) {
+                SkipReason::Denied(glob.to_string())
+            } else {
+                let size = size_of(&file.path)
+                    .filter(|size| hygiene.max_file_bytes > 0 && *size > hygiene.max_file_bytes)?;
+                SkipReason::TooLarge(size)
+            };
+            Some(SkippedFile {
+                path: file.path.clone(),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Pathspecs for `git add -A` that stage the whole tree except `skipped`
+pub fn add_pathspecs(skipped: &[SkippedFile]) -> Vec<String> {
+    let mut specs = vec![":/".to_string()];
+    specs.extend(
+        skipped
+            .iter()
+            .map(|file| format!(":(exclude,literal){}", file.path)),
+    );
+    specs
+}
+
+/// One-line report of the skipped files, naming the first few
+pub fn summary(skipped: &[SkippedFile]) -> String {
+    let mut listed: Vec<String> = skipped
+        .iter()
+        .take(REPORTED_PATHS)
+        .map(|file| match &file.reason {
+            SkipReason::Denied(glob) => format!("{} (matches {})", file.path, glob),
+            SkipReason::TooLarge(size) => {
+                format!("{} ({:.1} MB)", file.path, *size as f64 / (1024.0 * 1024.0))
+            }
+        })
+        .collect();
+    if skipped.len() > REPORTED_PATHS {
+        listed.push(format!("and {} more", skipped.len() - REPORTED_PATHS));
+    }
+    format!(
+        "Left {} files out of the commit: {}",
+        skipped.len(),
+        listed.join(", ")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed(path: &str) -> ChangedFile {
+        ChangedFile {
+            path: path.to_string(),
+            deleted: false,
+        }
+    }
+
+    #[test]
+    fn parses_porcelain_status_with_renames() {
+        let status = " M src/main.rs\0?? certs/dev.pem\0R  new.rs\0old.rs\0 D gone.db\0";
+        assert_eq!(
+            parse_status(status),
+            vec![
+                changed("src/main.rs"),
+                changed("certs/dev.pem"),
+                changed("new.rs"),
+                ChangedFile {
+                    path: "gone.db".to_string(),
+                    deleted: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_denied_and_oversized_files_but_not_deletions() {
+        let hygiene = CommitHygiene {
+            max_file_bytes: 1000,
+            ..CommitHygiene::default()
+        };
+        let files = vec![
+            changed("src/main.rs"),
+            changed("certs/dev.pem"),
+            changed("web/node_modules/react/index.js"),
+            changed("assets/video.mp4"),
+            changed("docs/env.md"),
+            ChangedFile {
+                path: "data/app.sqlite".to_string(),
+                deleted: true,
+            },
+        ];
+        let skipped = check(&hygiene, &files, |path| {
+            Some(if path.ends_with(".mp4") { 5000 } else { 10 })
+        });
+
+        assert_eq!(
+            skipped,
+            vec![
+                SkippedFile {
+                    path: "certs/dev.pem".to_string(),
+                    reason: SkipReason::Denied("*.pem".to_string()),
+                },
+                SkippedFile {
+                    path: "web/node_modules/react/index.js".to_string(),
+                    reason: SkipReason::Denied("node_modules/**".to_string()),
+                },
+                SkippedFile {
+                    path: "assets/video.mp4".to_string(),
+                    reason: SkipReason::TooLarge(5000),
+                },
+            ]
+        );
+        assert_eq!(
+            add_pathspecs(&skipped[..1]),
+            vec![":/", ":(exclude,literal)certs/dev.pem"]
+        );
+    }
+
+    #[test]
+    fn size_limit_can_be_disabled() {
+        let hygiene = CommitHygiene {
+            max_file_bytes: 0,
+            deny_globs: Vec::new(),
+            scan_secrets: true,
+        };
+        assert!(check(&hygiene, &[changed("big.bin")], |_| Some(u64::MAX)).is_empty());
+        assert!(validate(&CommitHygiene {
+            deny_globs: vec!["[".to_string()],
+            ..hygiene
+        })
+        .is_err());
+    }
+}
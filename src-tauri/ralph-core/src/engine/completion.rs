@@ -0,0 +1,264 @@
+//! Structured completion: instead of the bare signal an agent may finish with
+//! `<done>{json}</done>`, reporting what it did and what could come next.
+
+use crate::storage::models::{CompletionMode, CompletionPayload, CompletionStrategy};
+use regex::{Regex, RegexBuilder};
+
+const OPEN: &str = "<done>";
+pub const CLOSE: &str = "</done>";
+/// Assistant text kept per iteration while looking for a payload
+const MAX_BUFFER_BYTES: usize = 64 * 1024;
+
+/// How an iteration's output is checked for the task's completion signal
+#[derive(Debug, Clone)]
+pub enum Signal {
+    /// Anywhere in the assistant's output, as soon as it streams by
+    Text(String),
+    /// Against the assistant's final message once the CLI exits; `^` and `$` match at lines
+    Pattern(Regex),
+}
+
+impl Signal {
+    pub fn new(signal: &str, is_regex: bool) -> Result<Self, String> {
+        if signal.trim().is_empty() {
+            return Err("Completion signal cannot be empty".to_string());
+        }
+        if !is_regex {
+            return Ok(Signal::Text(signal.to_string()));
+        }
+        RegexBuilder::new(signal)
+            .multi_line(true)
+            .build()
+            .map(Signal::Pattern)
+            .map_err(|e| format!("Invalid completion signal {}: {}", signal, e))
+    }
+
+    /// Whether a streamed assistant line completes the iteration
+    pub fn in_line(&self, line: &str) -> bool {
+        match self {
+            Signal::Text(signal) => line.contains(signal.as_str()),
+            Signal::Pattern(_) => false,
+        }
+    }
+
+    /// Whether the assistant's final message completes the iteration
+    pub fn in_final_message(&self, message: &str) -> bool {
+        match self {
+            Signal::Text(_) => false,
+            Signal::Pattern(re) => re.is_match(message.trim()),
+        }
+    }
+}
+
+/// The task's completion signals and which of them an iteration has shown
+#[derive(Debug, Clone, Default)]
+pub struct Signals {
+    signals: Vec<Signal>,
+    mode: CompletionMode,
+    seen: Vec<bool>,
+}
+
+impl Signals {
+    pub fn new(signals: &[String], is_regex: bool, mode: CompletionMode) -> Result<Self, String> {
+        let signals = signals
+            .iter()
+            .map(|signal| Signal::new(signal, is_regex))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            seen: vec![false; signals.len()],
+            signals,
+            mode,
+        })
+    }
+
+    /// Forget what the previous iteration showed
+    pub fn reset(&mut self) {
+        self.seen.fill(false);
+    }
+
+    /// Note a streamed assistant line; whether the iteration is now complete
+    pub fn observe_line(&mut self, line: &str) -> bool {
+        for (signal, seen) in self.signals.iter().zip(&mut self.seen) {
+            *seen |= signal.in_line(line);
+        }
+        self.is_complete()
+    }
+
+    /// Note the assistant's final message; whether the iteration is now complete
+    pub fn observe_final_message(&mut self, message: &str) -> bool {
+        for (signal, seen) in self.signals.iter().zip(&mut self.seen) {
+            *seen |= signal.in_final_message(message);
+        }
+        self.is_complete()
+    }
+
+    /// Whether a structured `<done>{json}</done>` completes the iteration by itself;
+    /// when every signal is required it only adds its report
+    pub fn accepts_payload(&self) -> bool {
+        self.mode == CompletionMode::Any
+    }
+
+    fn is_complete(&self) -> bool {
+        match self.mode {
+            CompletionMode::Any => self.seen.contains(&true),
+            CompletionMode::All => !self.seen.is_empty() && !self.seen.contains(&false),
+        }
+    }
+}
+
+/// Progress toward a strategy that completes without a signal
+#[derive(Debug, Default)]
+pub struct StrategyTracker {
+    no_op_streak: u32,
+}
+
+impl StrategyTracker {
+    /// Note a finished iteration; whether the loop is now complete
+    pub fn observe(
+        &mut self,
+        strategy: &CompletionStrategy,
+        exit_code: Option<i32>,
+        changed: bool,
+        tree_clean: bool,
+    ) -> bool {
+        let succeeded = exit_code == Some(0);
+        match strategy {
+            CompletionStrategy::Signal => false,
+            CompletionStrategy::CleanExit => succeeded && tree_clean,
+            CompletionStrategy::NoOpStreak { iterations } => {
+                self.no_op_streak = if succeeded && !changed {
+                    self.no_op_streak + 1
+                } else {
+                    0
+                };
+                self.no_op_streak >= (*iterations).max(1)
+            }
+        }
+    }
+}
+
+/// Append assistant output to the iteration's buffer, keeping only its tail
+pub fn push_output(buffer: &mut String, content: &str) {
+    buffer.push_str(content);
+    buffer.push('\n');
+    if buffer.len() > MAX_BUFFER_BYTES {
+        let mut cut = buffer.len() - MAX_BUFFER_BYTES;
+        while !buffer.is_char_boundary(cut) {
+            cut += 1;
+        }
+        buffer.drain(..cut);
+    }
+}
+
+/// The last `<done>{...}</done>` block in `text`, if its body is a JSON object
+pub fn parse_payload(text: &str) -> Option<CompletionPayload> {
+    let end = text.rfind(CLOSE)?;
+    let start = text[..end].rfind(OPEN)? + OPEN.len();
+    let body = text[start..end].trim();
+    if !body.starts_with('{') {
+        return None;
+    }
+    // Streamed deltas are joined with newlines, which may land inside a string
+    let mut payload: CompletionPayload = serde_json::from_str(body)
+        .or_else(|_| serde_json::from_str(&body.replace('\n', "")))
+        .ok()?;
+    // Agents sometimes answer in percent
+    payload.confidence = payload
+        .confidence
+        .map(|c| if c > 1.0 { c / 100.0 } else { c })
+        .map(|c| c.clamp(0.0, 1.0));
+    Some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_last_structured_block() {
+        let text = "Working...\n<done>{\"summary\": \"draft\"}</done>\nActually:\n<done>{\n  \"summary\": \"Added login\",\n  \"filesOfInterest\": [\"src/auth.rs\"],\n  \"follow_ups\": [\"Add logout\"],\n  \"confidence\": 85\n}</done>\n";
+        let payload = parse_payload(text).unwrap();
+        assert_eq!(payload.summary.as_deref(), Some("Added login"));
+        assert_eq!(payload.files, vec!["src/auth.rs"]);
+        assert_eq!(payload.follow_ups, vec!["Add logout"]);
+        assert_eq!(payload.confidence, Some(0.85));
+    }
+
+    #[test]
+    fn falls_back_for_plain_or_broken_blocks() {
+        assert_eq!(parse_payload("<done>COMPLETE</done>"), None);
+        assert_eq!(parse_payload("<done>{\"summary\": </done>"), None);
+        assert_eq!(parse_payload("no marker"), None);
+
+        let split = "<done>{\"summary\": \"Fixed the\n bug\"}</done>";
+        assert_eq!(
+            parse_payload(split).unwrap().summary.as_deref(),
+            Some("Fixed the bug")
+        );
+    }
+
+    #[test]
+    fn pattern_signals_match_only_whole_lines_of_the_final_message() {
+        let signal = Signal::new("^<done>COMPLETE</done>$", true).unwrap();
+        assert!(!signal.in_line("<done>COMPLETE</done>"));
+        assert!(signal.in_final_message("All tests pass.\n<done>COMPLETE</done>\n"));
+        assert!(!signal.in_final_message("I will print <done>COMPLETE</done> once done."));
+
+        let text = Signal::new("<done>COMPLETE</done>", false).unwrap();
+        assert!(text.in_line("I will print <done>COMPLETE</done> once done."));
+        assert!(Signal::new("^<done>(", true).is_err());
+        assert!(Signal::new(" ", false).is_err());
+    }
+
+    #[test]
+    fn all_mode_waits_for_every_signal() {
+        let list = vec![
+            "<tests>PASS</tests>".to_string(),
+            "<done>COMPLETE</done>".to_string(),
+        ];
+        let mut all = Signals::new(&list, false, CompletionMode::All).unwrap();
+        assert!(!all.observe_line("<done>COMPLETE</done>"));
+        assert!(all.observe_line("<tests>PASS</tests>"));
+        all.reset();
+        assert!(!all.observe_line("<tests>PASS</tests>"));
+        assert!(!all.accepts_payload());
+
+        let mut any = Signals::new(&list, false, CompletionMode::Any).unwrap();
+        assert!(any.observe_line("<tests>PASS</tests>"));
+
+        let mut patterns = Signals::new(&list, true, CompletionMode::All).unwrap();
+        assert!(!patterns.observe_line("<tests>PASS</tests>"));
+        assert!(patterns.observe_final_message("<tests>PASS</tests>\n<done>COMPLETE</done>"));
+    }
+
+    #[test]
+    fn strategies_complete_on_clean_exits_and_no_op_streaks() {
+        let mut tracker = StrategyTracker::default();
+        let clean = CompletionStrategy::CleanExit;
+        assert!(tracker.observe(&clean, Some(0), true, true));
+        assert!(!tracker.observe(&clean, Some(1), false, true));
+        assert!(!tracker.observe(&clean, Some(0), true, false));
+
+        let streak = CompletionStrategy::NoOpStreak { iterations: 2 };
+        assert!(!tracker.observe(&streak, Some(0), false, false));
+        assert!(!tracker.observe(&streak, Some(0), true, false));
+        assert!(!tracker.observe(&streak, Some(0), false, false));
+        assert!(tracker.observe(&streak, Some(0), false, false));
+        assert!(!StrategyTracker::default().observe(
+            &CompletionStrategy::Signal,
+            Some(0),
+            false,
+            true
+        ));
+    }
+
+    #[test]
+    fn keeps_only_the_buffer_tail() {
+        let mut buffer = String::new();
+        for _ in 0..20 {
+            push_output(&mut buffer, &"é".repeat(2048));
+        }
+        assert!(buffer.len() <= MAX_BUFFER_BYTES);
+        assert!(buffer.ends_with("é\n"));
+    }
+}
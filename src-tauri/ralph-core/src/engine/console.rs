@@ -0,0 +1,149 @@
+//! Splits CLI output into lines the way a terminal shows them: `\r` redraws
+//! the current line (progress bars, spinners) instead of ending it.
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const READ_CHUNK: usize = 8 * 1024;
+
+/// A piece of console output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Segment {
+    /// A finished line
+    Line(String),
+    /// An in-place update of the current line, overwritten by what follows
+    Progress(String),
+}
+
+/// Line reader that also splits on bare `\r`; `\r\n` still ends a single line
+pub struct ConsoleLines<R> {
+    reader: R,
+    buffer: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> ConsoleLines<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: Vec::new(),
+            eof: false,
+        }
+    }
+
+    /// Next segment, or `None` at end of output. Cancel-safe: all state lives
+    /// in `self` and a single `read` either completes or reads nothing.
+    pub async fn next_segment(&mut self) -> std::io::Result<Option<Segment>> {
+        loop {
+            if let Some(segment) = self.take_segment() {
+                return Ok(Some(segment));
+            }
+            if self.eof {
+                if self.buffer.is_empty() {
+                    return Ok(None);
+                }
+                let rest = std::mem::take(&mut self.buffer);
+                return Ok(Some(Segment::Line(text(&rest))));
+            }
+            let mut chunk = [0u8; READ_CHUNK];
+            let read = self.reader.read(&mut chunk).await?;
+            if read == 0 {
+                self.eof = true;
+            } else {
+                self.buffer.extend_from_slice(&chunk[..read]);
+            }
+        }
+    }
+
+    /// Output after the last line break, e.g. a prompt waiting on the same line for input
+    pub fn pending(&self) -> Option<String> {
+        let start = self
+            .buffer
+            .iter()
+            .rposition(|b| *b == b'\n' || *b == b'\r')
+            .map_or(0, |i| i + 1);
+        let pending = &self.buffer[start..];
+        (!pending.is_empty()).then(|| text(pending))
+    }
+
+    fn take_segment(&mut self) -> Option<Segment> {
+        loop {
+            let end = self
+                .buffer
+                .iter()
+                .position(|b| *b == b'\n' || *b == b'\r')?;
+            if self.buffer[end] == b'\n' {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return Some(Segment::Line(text(&line[..end])));
+            }
+            // `\r` at the end of what we have may be the start of `\r\n`
+            let next = self.buffer.get(end + 1).copied();
+            if next.is_none() {
+                if !self.eof {
+                    return None;
+                }
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                return Some(Segment::Line(text(&line[..end])));
+            }
+            if next == Some(b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end + 1).collect();
+                return Some(Segment::Line(text(&line[..end])));
+            }
+            let update: Vec<u8> = self.buffer.drain(..=end).collect();
+            // Redraws often start with `\r`, leaving nothing before it
+            if end > 0 {
+                return Some(Segment::Progress(text(&update[..end])));
+            }
+        }
+    }
+}
+
+fn text(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn segments(input: &[u8]) -> Vec<Segment> {
+        let mut lines = ConsoleLines::new(input);
+        let mut segments = Vec::new();
+        while let Some(segment) = lines.next_segment().await.unwrap() {
+            segments.push(segment);
+        }
+        segments
+    }
+
+    #[tokio::test]
+    async fn splits_progress_updates_from_lines() {
+        assert_eq!(
+            segments(b"start\r\n 10%\r 50%\r100%\ndone").await,
+            vec![
+                Segment::Line("start".to_string()),
+                Segment::Progress(" 10%".to_string()),
+                Segment::Progress(" 50%".to_string()),
+                Segment::Line("100%".to_string()),
+                Segment::Line("done".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_empty_redraws_and_trailing_carriage_return() {
+        assert_eq!(
+            segments(b"\r\rloading\r").await,
+            vec![Segment::Line("loading".to_string())]
+        );
+        assert!(segments(b"").await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn keeps_an_unfinished_line_pending() {
+        let mut lines = ConsoleLines::new(&b"Building\nProceed? [y/N] "[..]);
+        assert_eq!(
+            lines.next_segment().await.unwrap(),
+            Some(Segment::Line("Building".to_string()))
+        );
+        assert_eq!(lines.pending().as_deref(), Some("Proceed? [y/N] "));
+    }
+}
@@ -0,0 +1,260 @@
+//! Runs the agent CLI inside a Docker container with only the project mounted,
+//! so "skip all permissions" flags cannot touch the rest of the host.
+
+use super::executor::{
+    map_working_dir, Execution, Executor, IterationRequest, OutputLine, ProcessExecution,
+};
+use crate::adapters::{
+    apply_extended_path, apply_shell_env, env_keys, hide_console_window, resolve_cli_path,
+    CliAdapter, PortableCommand,
+};
+use crate::storage::models::{ContainerConfig, ContainerNetwork};
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Where the project directory is mounted inside the container
+pub const WORKSPACE: &str = "/workspace";
+
+/// Credential and endpoint variables forwarded by name, so values stay off the command line
+fn is_forwarded_key(key: &str) -> bool {
+    const PREFIXES: &[&str] = &[
+        "ANTHROPIC_",
+        "OPENAI_",
+        "CLAUDE_",
+        "CODEX_",
+        "AMP_",
+        "DASHSCOPE_",
+    ];
+    const SUFFIXES: &[&str] = &[
+        "_API_KEY",
+        "_AUTH_TOKEN",
+        "_BASE_URL",
+        "_API_BASE",
+        "_API_URL",
+        "_MODEL",
+    ];
+    PREFIXES.iter().any(|p| key.starts_with(p)) && SUFFIXES.iter().any(|s| key.ends_with(s))
+}
+
+/// Arguments for `docker run`
+fn run_args(
+    config: &ContainerConfig,
+    name: &str,
+    project_path: &Path,
+    workdir: &str,
+    user: Option<&str>,
+    forwarded: &[String],
+    command: &PortableCommand,
+) -> Vec<String> {
+    let network = match config.network {
+        ContainerNetwork::Bridge => "bridge",
+        ContainerNetwork::None => "none",
+        ContainerNetwork::Host => "host",
+    };
+    let mut args: Vec<String> = vec![
+        "run".into(),
+        "--rm".into(),
+        "--init".into(),
+        "--name".into(),
+        name.into(),
+        "--network".into(),
+        network.into(),
+        "-v".into(),
+        format!("{}:{}", project_path.display(), WORKSPACE),
+        "-w".into(),
+        workdir.into(),
+    ];
+    if let Some(user) = user {
+        args.push("--user".into());
+        args.push(user.into());
+    }
+    for key in forwarded {
+        args.push("-e".into());
+        args.push(key.clone());
+    }
+    for (key, value) in &command.env {
+        args.push("-e".into());
+        args.push(format!("{}={}", key, value));
+    }
+    args.extend(config.extra_args.iter().cloned());
+    args.push(config.image.clone());
+    args.extend(command.argv.iter().cloned());
+    args
+}
+
+/// Run as the project owner so files the agent creates stay editable on the host
+#[cfg(unix)]
+fn owner_of(path: &Path) -> Option<String> {
+    use std::os::unix::fs::MetadataExt;
+    let metadata = std::fs::metadata(path).ok()?;
+    Some(format!("{}:{}", metadata.uid(), metadata.gid()))
+}
+
+#[cfg(not(unix))]
+fn owner_of(_path: &Path) -> Option<String> {
+    None
+}
+
+pub struct ContainerExecutor {
+    config: ContainerConfig,
+    project_path: PathBuf,
+    adapter: Box<dyn CliAdapter>,
+}
+
+impl ContainerExecutor {
+    pub fn new(
+        config: ContainerConfig,
+        project_path: PathBuf,
+        adapter: Box<dyn CliAdapter>,
+    ) -> Self {
+        Self {
+            config,
+            project_path,
+            adapter,
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for ContainerExecutor {
+    async fn start(&self, request: IterationRequest<'_>) -> Result<Box<dyn Execution>, String> {
+        if self.config.image.trim().is_empty() {
+            return Err("No container image configured".to_string());
+        }
+        let mut command = self
+            .adapter
+            .portable_command(request.prompt, request.options)
+            .ok_or_else(|| format!("{} cannot run in a container", self.adapter.name()))?;
+        command.env.extend(request.env.iter().cloned());
+        if request.network_blocked {
+            command
+                .env
+                .extend(super::network::offline_env(request.api_hosts));
+        }
+        let docker = resolve_cli_path("docker").ok_or("Docker is not installed")?;
+
+        let name = format!("ralph-{}", uuid::Uuid::new_v4().simple());
+        let workdir = map_working_dir(&self.project_path, request.working_dir, WORKSPACE)?;
+        let forwarded: Vec<String> = env_keys()
+            .into_iter()
+            .filter(|key| is_forwarded_key(key))
+            .collect();
+        let args = run_args(
+            &self.config,
+            &name,
+            &self.project_path,
+            &workdir,
+            owner_of(&self.project_path).as_deref(),
+            &forwarded,
+            &command,
+        );
+
+        let mut cmd = Command::new(&docker);
+        cmd.args(&args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        apply_extended_path(&mut cmd);
+        apply_shell_env(&mut cmd);
+        hide_console_window(&mut cmd);
+
+        let process = ProcessExecution::spawn(cmd, request.project_id)?;
+        Ok(Box::new(ContainerExecution {
+            process,
+            docker,
+            name,
+            exited: false,
+        }))
+    }
+}
+
+struct ContainerExecution {
+    process: ProcessExecution,
+    docker: String,
+    name: String,
+    /// The container exited on its own and `--rm` removed it
+    exited: bool,
+}
+
+#[async_trait]
+impl Execution for ContainerExecution {
+    async fn next_line(&mut self) -> Option<OutputLine> {
+        self.process.next_line().await
+    }
+
+    /// Killing the docker client alone leaves the container running
+    async fn kill(&mut self) {
+        let mut cmd = Command::new(&self.docker);
+        cmd.args(["rm", "-f", &self.name])
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        hide_console_window(&mut cmd);
+        let _ = cmd.status().await;
+        self.process.kill().await;
+    }
+
+    async fn wait(&mut self) -> Option<i32> {
+        let code = self.process.wait().await;
+        self.exited = code.is_some();
+        code
+    }
+}
+
+impl Drop for ContainerExecution {
+    fn drop(&mut self) {
+        if !self.exited {
+            let _ = std::process::Command::new(&self.docker)
+                .args(["rm", "-f", &self.name])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_docker_run_arguments() {
+        let config = ContainerConfig {
+            image: "ghcr.io/acme/agents:latest".to_string(),
+            network: ContainerNetwork::None,
+            extra_args: vec!["--memory".to_string(), "4g".to_string()],
+        };
+        let command = PortableCommand {
+            argv: vec![
+                "codex".to_string(),
+                "exec".to_string(),
+                "fix it".to_string(),
+            ],
+            env: vec![("OPENCODE_CONFIG_CONTENT".to_string(), "{}".to_string())],
+        };
+        let args = run_args(
+            &config,
+            "ralph-1",
+            Path::new("/home/me/repo"),
+            "/workspace",
+            Some("1000:1000"),
+            &["OPENAI_API_KEY".to_string()],
+            &command,
+        );
+        assert_eq!(
+            args.join(" "),
+            "run --rm --init --name ralph-1 --network none -v /home/me/repo:/workspace -w /workspace \
+             --user 1000:1000 -e OPENAI_API_KEY -e OPENCODE_CONFIG_CONTENT={} --memory 4g \
+             ghcr.io/acme/agents:latest codex exec fix it"
+        );
+    }
+
+    #[test]
+    fn forwards_only_credential_variables() {
+        assert!(is_forwarded_key("ANTHROPIC_API_KEY"));
+        assert!(is_forwarded_key("OPENAI_BASE_URL"));
+        assert!(!is_forwarded_key("CODEX_HOME"));
+        assert!(!is_forwarded_key("AWS_SECRET_ACCESS_KEY"));
+    }
+}
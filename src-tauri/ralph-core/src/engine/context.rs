@@ -0,0 +1,280 @@
+use crate::adapters::hide_console_window;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Directories never worth showing to an agent
+const SKIP_DIRS: &[&str] = &[
+    ".git",
+    ".ralph",
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "vendor",
+    "__pycache__",
+    ".venv",
+];
+
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "that", "this", "from", "into", "should", "must", "will", "make",
+    "add", "use", "are", "not", "all", "any", "can", "you", "your", "task",
+];
+
+const MAX_FILE_BYTES: u64 = 200 * 1024;
+const EXCERPT_RADIUS: usize = 15;
+
+/// Rough token estimate (4 chars per token)
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// List project files relative to `root`, honoring .gitignore
+pub async fn list_project_files(root: &Path) -> Vec<PathBuf> {
+    if let Some(files) = git_ls_files(root).await {
+        return files;
+    }
+    let ignored = read_gitignore_names(root);
+    let mut files = Vec::new();
+    walk(root, root, &ignored, &mut files);
+    files.sort();
+    files
+}
+
+async fn git_ls_files(root: &Path) -> Option<Vec<PathBuf>> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C")
+        .arg(root)
+        .args(["ls-files", "--cached", "--others", "--exclude-standard"]);
+    hide_console_window(&mut cmd);
+    let output = cmd.output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let mut files: Vec<PathBuf> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(PathBuf::from)
+        .filter(|p| {
+            !p.components()
+                .any(|c| SKIP_DIRS.contains(&c.as_os_str().to_string_lossy().as_ref()))
+        })
+        .collect();
+    files.sort();
+    Some(files)
+}
+
+/// Plain names and `*.ext` patterns from the root .gitignore
+fn read_gitignore_names(root: &Path) -> Vec<String> {
+    fs::read_to_string(root.join(".gitignore"))
+        .unwrap_or_default()
+        .lines()
+        .map(|l| l.trim().trim_matches('/'))
+        .filter(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with('!'))
+        .map(|l| l.to_string())
+        .collect()
+}
+
+fn is_ignored(name: &str, ignored: &[String]) -> bool {
+    if SKIP_DIRS.contains(&name) {
+        return true;
+    }
+    ignored
+        .iter()
+        .any(|pattern| match pattern.strip_prefix('*') {
+            Some(suffix) => name.ends_with(suffix),
+            None => pattern == name,
+        })
+}
+
+fn walk(root: &Path, dir: &Path, ignored: &[String], files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if is_ignored(&name, ignored) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            walk(root, &path, ignored, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            files.push(relative.to_path_buf());
+        }
+    }
+}
+
+fn keywords(query: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    query
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .map(|w| w.to_lowercase())
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(&w.as_str()))
+        .filter(|w| seen.insert(w.clone()))
+        .collect()
+}
+
+pub(crate) fn read_text_file(path: &Path) -> Option<String> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = fs::read(path).ok()?;
+    if bytes.iter().take(1024).any(|b| *b == 0) {
+        return None;
+    }
+    String::from_utf8(bytes).ok()
+}
+
+fn score_file(path: &Path, content: &str, keywords: &[String]) -> usize {
+    let path_text = path.to_string_lossy().to_lowercase();
+    let content_lower = content.to_lowercase();
+    keywords
+        .iter()
+        .map(|k| {
+            let path_hits = if path_text.contains(k.as_str()) { 3 } else { 0 };
+            path_hits + content_lower.matches(k.as_str()).count().min(10)
+        })
+        .sum()
+}
+
+/// Lines around the first keyword hit, or the head of the file
+fn excerpt(content: &str, keywords: &[String]) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let hit = lines.iter().position(|l| {
+        let lower = l.to_lowercase();
+        keywords.iter().any(|k| lower.contains(k.as_str()))
+    });
+    let center = hit.unwrap_or(0);
+    let start = center.saturating_sub(EXCERPT_RADIUS);
+    let end = (center + EXCERPT_RADIUS + 1).min(lines.len());
+    lines[start..end].join("\n")
+}
+
+/// Build a token-budgeted file tree plus excerpts of files relevant to `query`
+pub async fn build_repo_context(root: &Path, query: &str, budget_tokens: usize) -> String {
+    if budget_tokens == 0 {
+        return String::new();
+    }
+    let files = list_project_files(root).await;
+    if files.is_empty() {
+        return String::new();
+    }
+
+    // Spend at most ~40% of the budget on the tree
+    let tree_budget = budget_tokens * 2 / 5;
+    let mut tree = String::from("### File Tree\n");
+    let mut listed = 0;
+    for file in &files {
+        let line = format!("- {}\n", file.to_string_lossy().replace('\\', "/"));
+        if estimate_tokens(&tree) + estimate_tokens(&line) > tree_budget {
+            break;
+        }
+        tree.push_str(&line);
+        listed += 1;
+    }
+    if listed < files.len() {
+        tree.push_str(&format!("- ... ({} more files)\n", files.len() - listed));
+    }
+
+    let mut output = tree;
+    let keywords = keywords(query);
+    if keywords.is_empty() {
+        return output;
+    }
+
+    let mut scored: Vec<(usize, &PathBuf, String)> = files
+        .iter()
+        .filter_map(|file| {
+            let content = read_text_file(&root.join(file))?;
+            let score = score_file(file, &content, &keywords);
+            (score > 0).then_some((score, file, content))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    let mut excerpts = String::new();
+    for (_, file, content) in scored {
+        let block = format!(
+            "\n#### {}\n```\n{}\n```\n",
+            file.to_string_lossy().replace('\\', "/"),
+            excerpt(&content, &keywords)
+        );
+        if estimate_tokens(&output) + estimate_tokens(&excerpts) + estimate_tokens(&block)
+            > budget_tokens
+        {
+            break;
+        }
+        excerpts.push_str(&block);
+    }
+    if !excerpts.is_empty() {
+        output.push_str("\n### Relevant Files\n");
+        output.push_str(&excerpts);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn walker_respects_gitignore_and_skip_dirs() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), ".gitignore", "secret.txt\n*.log\n");
+        write(dir.path(), "src/main.rs", "fn main() {}");
+        write(dir.path(), "secret.txt", "x");
+        write(dir.path(), "debug.log", "x");
+        write(dir.path(), "node_modules/pkg/index.js", "x");
+
+        let files = list_project_files(dir.path()).await;
+        let names: Vec<String> = files
+            .iter()
+            .map(|f| f.to_string_lossy().replace('\\', "/"))
+            .collect();
+        assert!(names.contains(&"src/main.rs".to_string()));
+        assert!(!names
+            .iter()
+            .any(|n| n.contains("secret") || n.ends_with(".log")));
+        assert!(!names.iter().any(|n| n.contains("node_modules")));
+    }
+
+    #[tokio::test]
+    async fn context_prefers_files_matching_prompt() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            "src/snake.rs",
+            "struct Snake;\nimpl Snake { fn grow() {} }",
+        );
+        write(dir.path(), "src/other.rs", "fn unrelated() {}");
+
+        let context = build_repo_context(dir.path(), "Make the snake grow faster", 2000).await;
+        assert!(context.contains("### File Tree"));
+        assert!(context.contains("#### src/snake.rs"));
+        assert!(!context.contains("#### src/other.rs"));
+    }
+
+    #[tokio::test]
+    async fn context_respects_budget() {
+        let dir = tempdir().unwrap();
+        for i in 0..200 {
+            write(dir.path(), &format!("src/module_{i}.rs"), "fn f() {}");
+        }
+        let context = build_repo_context(dir.path(), "", 100).await;
+        assert!(estimate_tokens(&context) <= 100);
+        assert!(context.contains("more files"));
+        assert!(build_repo_context(dir.path(), "anything", 0)
+            .await
+            .is_empty());
+    }
+}
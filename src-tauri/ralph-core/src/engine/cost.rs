@@ -0,0 +1,170 @@
+//! Estimates what a loop will cost before it starts, from the tokens and spend
+//! past iterations of the project reported, or list prices when there is no history.
+
+use crate::storage::models::{CliType, CostEstimate, IterationUsage};
+
+/// Assumed per-iteration usage without history: agent system prompts, tool
+/// definitions and file reads dwarf the task prompt itself
+const FALLBACK_CONTEXT_TOKENS: u64 = 30_000;
+const FALLBACK_OUTPUT_TOKENS: u64 = 3_000;
+
+/// USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TokenRates {
+    pub input: f64,
+    pub output: f64,
+}
+
+impl TokenRates {
+    pub fn cost(&self, input_tokens: u64, output_tokens: u64) -> f64 {
+        (input_tokens as f64 * self.input + output_tokens as f64 * self.output) / 1_000_000.0
+    }
+}
+
+/// List prices of a CLI's default model; `None` where the model is up to the user
+pub fn default_rates(cli: CliType) -> Option<TokenRates> {
+    match cli {
+        CliType::Claude => Some(TokenRates {
+            input: 3.0,
+            output: 15.0,
+        }),
+        CliType::Codex => Some(TokenRates {
+            input: 1.25,
+            output: 10.0,
+        }),
+        CliType::OpenCode
+        | CliType::Amp
+        | CliType::QwenCode
+        | CliType::Iflow
+        | CliType::Gemini
+        | CliType::Api
+        | CliType::Custom => None,
+    }
+}
+
+/// Value below which 90% of `values` fall
+fn p90(values: &mut [f64]) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let index = (values.len() * 9).div_ceil(10).saturating_sub(1);
+    values[index]
+}
+
+/// Cost range of a loop with `cli` running up to `max_iterations`. The low end
+/// assumes a typical run at average cost; the high end every iteration at the
+/// 90th-percentile cost.
+pub fn estimate(
+    cli: CliType,
+    prompt_tokens: u64,
+    max_iterations: u32,
+    sessions: &[Vec<IterationUsage>],
+) -> CostEstimate {
+    let rates = default_rates(cli);
+    let runs: Vec<Vec<&IterationUsage>> = sessions
+        .iter()
+        .map(|session| session.iter().filter(|u| u.cli == cli).collect::<Vec<_>>())
+        .filter(|run| !run.is_empty())
+        .collect();
+    let samples: Vec<&IterationUsage> = runs.iter().flatten().copied().collect();
+    let max_iterations = max_iterations.max(1);
+
+    if samples.is_empty() {
+        let input = FALLBACK_CONTEXT_TOKENS + prompt_tokens;
+        let per_iteration = rates.map(|rates| rates.cost(input, FALLBACK_OUTPUT_TOKENS));
+        return CostEstimate {
+            cli,
+            max_iterations,
+            prompt_tokens,
+            sampled_iterations: 0,
+            typical_iterations: None,
+            input_tokens_per_iteration: input,
+            output_tokens_per_iteration: FALLBACK_OUTPUT_TOKENS,
+            low_usd: per_iteration,
+            high_usd: per_iteration.map(|cost| cost * max_iterations as f64),
+        };
+    }
+
+    let count = samples.len() as u64;
+    let input = (samples.iter().map(|u| u.input_tokens).sum::<u64>() / count).max(prompt_tokens);
+    let output = samples.iter().map(|u| u.output_tokens).sum::<u64>() / count;
+    let typical = (samples.len() as f64 / runs.len() as f64).round() as u32;
+    let typical = typical.clamp(1, max_iterations);
+    // Reported spend beats list prices: it reflects the actual model and cache discounts
+    let mut costs: Vec<f64> = samples
+        .iter()
+        .filter_map(|u| {
+            u.cost_usd
+                .or_else(|| rates.map(|r| r.cost(u.input_tokens, u.output_tokens)))
+        })
+        .collect();
+    let (low_usd, high_usd) = if costs.is_empty() {
+        (None, None)
+    } else {
+        let mean = costs.iter().sum::<f64>() / costs.len() as f64;
+        (
+            Some(mean * typical as f64),
+            Some(p90(&mut costs) * max_iterations as f64),
+        )
+    };
+
+    CostEstimate {
+        cli,
+        max_iterations,
+        prompt_tokens,
+        sampled_iterations: samples.len() as u32,
+        typical_iterations: Some(typical),
+        input_tokens_per_iteration: input,
+        output_tokens_per_iteration: output,
+        low_usd,
+        high_usd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(iteration: u32, cli: CliType, cost_usd: Option<f64>) -> IterationUsage {
+        IterationUsage {
+            iteration,
+            cli,
+            input_tokens: 40_000,
+            output_tokens: 2_000,
+            cache_read_tokens: 0,
+            cost_usd,
+        }
+    }
+
+    #[test]
+    fn falls_back_to_list_prices_without_history() {
+        let estimate = estimate(CliType::Codex, 1_000, 10, &[]);
+        assert_eq!(estimate.input_tokens_per_iteration, 31_000);
+        assert_eq!(estimate.typical_iterations, None);
+        let low = estimate.low_usd.unwrap();
+        assert!((low - 0.06875).abs() < 1e-9);
+        assert!((estimate.high_usd.unwrap() - low * 10.0).abs() < 1e-9);
+
+        let unpriced = super::estimate(CliType::OpenCode, 1_000, 10, &[]);
+        assert_eq!((unpriced.low_usd, unpriced.high_usd), (None, None));
+    }
+
+    #[test]
+    fn uses_reported_costs_of_matching_sessions() {
+        let sessions = vec![
+            vec![
+                usage(1, CliType::Claude, Some(0.10)),
+                usage(2, CliType::Claude, Some(0.30)),
+            ],
+            vec![
+                usage(1, CliType::Claude, Some(0.20)),
+                usage(2, CliType::Claude, Some(0.20)),
+            ],
+            vec![usage(1, CliType::Codex, None)],
+        ];
+        let estimate = estimate(CliType::Claude, 500, 20, &sessions);
+        assert_eq!(estimate.sampled_iterations, 4);
+        assert_eq!(estimate.typical_iterations, Some(2));
+        assert_eq!(estimate.input_tokens_per_iteration, 40_000);
+        assert!((estimate.low_usd.unwrap() - 0.40).abs() < 1e-9);
+        assert!((estimate.high_usd.unwrap() - 6.0).abs() < 1e-9);
+    }
+}
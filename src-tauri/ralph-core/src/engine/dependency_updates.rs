@@ -0,0 +1,185 @@
+//! Built-in recipe for scheduled dependency updates: the package manager lists
+//! what is outdated, then a loop has the agent upgrade it and fix what breaks,
+//! with the tests as the gate for completing.
+
+use crate::storage::models::DependencyUpdateSchedule;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Interval a newly enabled schedule starts with
+pub const DEFAULT_INTERVAL_DAYS: u32 = 7;
+/// Longest an outdated check may take, index updates included
+const CHECK_TIMEOUT: Duration = Duration::from_secs(300);
+/// Test script `npm init` writes, which always fails
+const NPM_PLACEHOLDER_TEST: &str = "no test specified";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ecosystem {
+    Npm,
+    Cargo,
+}
+
+impl Ecosystem {
+    fn outdated_command(self) -> &'static str {
+        match self {
+            Ecosystem::Npm => "npm outdated",
+            Ecosystem::Cargo => "cargo update --dry-run",
+        }
+    }
+
+    /// The part of the check's output naming updates; `None` when there are none
+    fn outdated_entries(self, stdout: &str, stderr: &str) -> Option<String> {
+        let entries = match self {
+            // A table of outdated packages, empty when everything is current
+            Ecosystem::Npm => stdout.trim().to_string(),
+            // `Updating serde v1.0.200 -> v1.0.210` per crate the lockfile would change
+            Ecosystem::Cargo => stderr
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with("Updating ") && line.contains(" -> "))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        (!entries.is_empty()).then_some(entries)
+    }
+}
+
+/// Whether the schedule's next check is due at `now`
+pub fn is_due(schedule: &DependencyUpdateSchedule, now: DateTime<Utc>) -> bool {
+    schedule
+        .last_run_at
+        .is_none_or(|last| now - last >= chrono::Duration::days(i64::from(schedule.interval_days)))
+}
+
+/// Package managers whose manifest is in `dir`
+pub fn detect(dir: &Path) -> Vec<Ecosystem> {
+    [
+        ("package.json", Ecosystem::Npm),
+        ("Cargo.toml", Ecosystem::Cargo),
+    ]
+    .into_iter()
+    .filter(|(manifest, _)| dir.join(manifest).is_file())
+    .map(|(_, ecosystem)| ecosystem)
+    .collect()
+}
+
+fn has_npm_test_script(package_json: &str) -> bool {
+    serde_json::from_str::<Value>(package_json)
+        .ok()
+        .and_then(|package| package["scripts"]["test"].as_str().map(str::to_string))
+        .is_some_and(|script| !script.contains(NPM_PLACEHOLDER_TEST))
+}
+
+/// Command that must pass before the update loop may complete; `None` without tests
+pub fn test_command(dir: &Path, ecosystems: &[Ecosystem]) -> Option<String> {
+    let commands: Vec<&str> = ecosystems
+        .iter()
+        .filter_map(|ecosystem| match ecosystem {
+            Ecosystem::Npm => std::fs::read_to_string(dir.join("package.json"))
+                .ok()
+                .filter(|package| has_npm_test_script(package))
+                .map(|_| "npm test"),
+            Ecosystem::Cargo => Some("cargo test"),
+        })
+        .collect();
+    (!commands.is_empty()).then(|| commands.join(" && "))
+}
+
+/// What each package manager would update, as Markdown; `None` when all is current
+pub async fn outdated_report(
+    dir: &Path,
+    ecosystems: &[Ecosystem],
+) -> Result<Option<String>, String> {
+    let mut sections = Vec::new();
+    for ecosystem in ecosystems {
+        let command = ecosystem.outdated_command();
+        let mut cmd = super::screenshot::shell_command(command, dir);
+        cmd.stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let output = tokio::time::timeout(CHECK_TIMEOUT, cmd.output())
+            .await
+            .map_err(|_| format!("`{}` timed out", command))?
+            .map_err(|e| format!("Failed to run `{}`: {}", command, e))?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        match ecosystem.outdated_entries(&stdout, &stderr) {
+            Some(entries) => sections.push(format!("`{}`:\n```\n{}\n```", command, entries)),
+            // `npm outdated` exits with 1 exactly when it lists something
+            None if !output.status.success() => {
+                return Err(format!("`{}` failed: {}", command, stderr.trim()));
+            }
+            None => {}
+        }
+    }
+    Ok((!sections.is_empty()).then(|| sections.join("\n\n")))
+}
+
+/// Task prompt of an update loop
+pub fn update_prompt(report: &str, test_command: Option<&str>) -> String {
+    let tests = match test_command {
+        Some(command) => format!(
+            "Run `{command}` after the upgrades and fix whatever they broke. The loop only \
+             completes once `{command}` passes, so keep going until it does."
+        ),
+        None => "The project has no test command; build it after the upgrades and fix whatever \
+                 they broke."
+            .to_string(),
+    };
+    format!(
+        "Update this project's dependencies. The package manager reports these as outdated:\n\n\
+         {report}\n\n\
+         Upgrade them, including major versions when the migration is manageable; for a major \
+         upgrade that needs a large migration, stay on the newest compatible version and note \
+         why. Update lockfiles with the package manager rather than by hand, and adjust the code \
+         to API changes. {tests} Do not add, remove or replace dependencies beyond what the \
+         upgrades require."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_updates_from_cargo_dry_run() {
+        let stderr = "    Updating crates.io index\n     Locking 2 packages to latest compatible versions\n    \
+                      Updating serde v1.0.200 -> v1.0.210\n    Updating tokio v1.37.0 -> v1.40.0\n\
+                      warning: not updating lockfile due to dry run\n";
+        assert_eq!(
+            Ecosystem::Cargo.outdated_entries("", stderr).as_deref(),
+            Some("Updating serde v1.0.200 -> v1.0.210\nUpdating tokio v1.37.0 -> v1.40.0")
+        );
+        assert_eq!(
+            Ecosystem::Cargo.outdated_entries("", "    Updating crates.io index\n"),
+            None
+        );
+        assert_eq!(Ecosystem::Npm.outdated_entries("\n", ""), None);
+    }
+
+    #[test]
+    fn schedules_are_due_once_their_interval_passed() {
+        let now = Utc::now();
+        let schedule = |days_ago: Option<i64>| DependencyUpdateSchedule {
+            interval_days: 7,
+            last_run_at: days_ago.map(|days| now - chrono::Duration::days(days)),
+        };
+        assert!(is_due(&schedule(None), now));
+        assert!(!is_due(&schedule(Some(3)), now));
+        assert!(is_due(&schedule(Some(7)), now));
+    }
+
+    #[test]
+    fn ignores_the_placeholder_npm_test_script() {
+        assert!(has_npm_test_script(
+            r#"{"scripts": {"test": "vitest run"}}"#
+        ));
+        assert!(!has_npm_test_script(
+            r#"{"scripts": {"test": "echo \"Error: no test specified\" && exit 1"}}"#
+        ));
+        assert!(!has_npm_test_script(r#"{"name": "app"}"#));
+    }
+}
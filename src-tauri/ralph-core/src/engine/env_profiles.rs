@@ -0,0 +1,94 @@
+use crate::storage::models::{EnvProfile, GlobalConfig};
+use std::collections::BTreeMap;
+
+/// Whether `key` is a portable shell variable name (`[A-Za-z_][A-Za-z0-9_]*`)
+pub fn is_valid_env_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Reject unnamed or duplicate profiles and variable names a shell can't carry
+pub fn validate_profiles(profiles: &[EnvProfile]) -> Result<(), String> {
+    for (index, profile) in profiles.iter().enumerate() {
+        let name = profile.name.trim();
+        if name.is_empty() {
+            return Err("Environment profile name cannot be empty".to_string());
+        }
+        if profiles[..index].iter().any(|p| p.name.trim() == name) {
+            return Err(format!("Duplicate environment profile: {}", name));
+        }
+        if let Some(key) = profile.env.keys().find(|key| !is_valid_env_name(key)) {
+            return Err(format!(
+                "Invalid variable name {:?} in environment profile {}",
+                key, name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Environment for a task: the global (and workspace) variables, then the
+/// selected profile's on top
+pub fn task_env(
+    config: &GlobalConfig,
+    profile: Option<&str>,
+) -> Result<BTreeMap<String, String>, String> {
+    let mut env = config.env.clone();
+    let Some(name) = profile.map(str::trim).filter(|n| !n.is_empty()) else {
+        return Ok(env);
+    };
+    let profile = config
+        .env_profiles
+        .iter()
+        .find(|p| p.name.trim() == name)
+        .ok_or_else(|| format!("Environment profile not found: {}", name))?;
+    env.extend(profile.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str, vars: &[(&str, &str)]) -> EnvProfile {
+        EnvProfile {
+            name: name.to_string(),
+            env: vars
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn profile_overrides_global_variables() {
+        let config = GlobalConfig {
+            env: BTreeMap::from([
+                ("HTTPS_PROXY".to_string(), "http://proxy".to_string()),
+                ("ANTHROPIC_BASE_URL".to_string(), "https://a".to_string()),
+            ]),
+            env_profiles: vec![profile("glm", &[("ANTHROPIC_BASE_URL", "https://glm")])],
+            ..GlobalConfig::default()
+        };
+
+        let env = task_env(&config, Some("glm")).unwrap();
+        assert_eq!(env["ANTHROPIC_BASE_URL"], "https://glm");
+        assert_eq!(env["HTTPS_PROXY"], "http://proxy");
+        assert_eq!(task_env(&config, None).unwrap(), config.env);
+        assert!(task_env(&config, Some("deepseek")).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_profiles() {
+        assert!(validate_profiles(&[profile("glm", &[("OPENAI_API_KEY", "k")])]).is_ok());
+        assert!(validate_profiles(&[profile(" ", &[])]).is_err());
+        assert!(validate_profiles(&[profile("glm", &[]), profile("glm ", &[])]).is_err());
+        assert!(validate_profiles(&[profile("glm", &[("A=B", "k")])]).is_err());
+        assert!(validate_profiles(&[profile("glm", &[("X;rm -rf ~", "k")])]).is_err());
+        assert!(validate_profiles(&[profile("glm", &[("1ST", "k")])]).is_err());
+        assert!(validate_profiles(&[profile("glm", &[("_PRIVATE_2", "k")])]).is_ok());
+    }
+}
@@ -0,0 +1,322 @@
+//! Where an iteration's agent runs. The engine hands an executor the prompt and
+//! reads output lines back; adapters still decide the command line and how to
+//! parse what comes out.
+
+use super::console::{ConsoleLines, Segment};
+use super::container::ContainerExecutor;
+use super::processes::{self, TrackedProcess};
+use super::remote::SshExecutor;
+use super::terminal::PtyExecutor;
+use super::LoopConfig;
+use crate::adapters::{get_adapter_at, prompt_via_stdin_with, CliAdapter, CommandOptions};
+use crate::storage::models::ProcessPriority;
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
+
+/// A line of agent output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OutputLine {
+    Stdout(String),
+    Stderr(String),
+    /// A `\r` redraw of the current line on either stream, e.g. a progress bar
+    Progress(String),
+}
+
+/// Inputs for one iteration
+pub struct IterationRequest<'a> {
+    pub project_id: &'a str,
+    pub prompt: &'a str,
+    pub working_dir: &'a Path,
+    pub options: CommandOptions,
+    /// Run the agent with `network::offline_env` applied
+    pub network_blocked: bool,
+    /// Configured model API hosts kept reachable when the network is blocked
+    pub api_hosts: &'a [String],
+    /// Extra environment for the agent, applied before the offline overrides
+    pub env: &'a [(String, String)],
+}
+
+/// Starts iterations of an agent
+#[async_trait]
+pub trait Executor: Send + Sync {
+    async fn start(&self, request: IterationRequest<'_>) -> Result<Box<dyn Execution>, String>;
+}
+
+/// Error when the agent's input cannot be written to
+pub const INPUT_CLOSED: &str =
+    "The CLI does not accept input; enable the interactive terminal to answer it";
+
+/// A running iteration
+#[async_trait]
+pub trait Execution: Send {
+    /// Next output line, or `None` once the agent closed its output.
+    /// Must be cancel-safe: the engine races it against its timeout checks.
+    async fn next_line(&mut self) -> Option<OutputLine>;
+
+    async fn kill(&mut self);
+
+    /// Wait for the agent to exit; `None` when it was killed or the status is unknown
+    async fn wait(&mut self) -> Option<i32>;
+
+    /// Unfinished output line, such as a prompt waiting for an answer
+    fn pending_line(&self) -> Option<String> {
+        None
+    }
+
+    /// Type `reply` followed by Enter into the agent's input
+    async fn answer(&mut self, _reply: &str) -> Result<(), String> {
+        Err(INPUT_CLOSED.to_string())
+    }
+}
+
+/// Path of `working_dir` under `root` on a target where the project lives at `root`
+pub(super) fn map_working_dir(
+    project_path: &Path,
+    working_dir: &Path,
+    root: &str,
+) -> Result<String, String> {
+    let relative = working_dir
+        .strip_prefix(project_path)
+        .map_err(|_| "Working directory is outside the project".to_string())?;
+    let mut mapped = root.trim_end_matches('/').to_string();
+    for component in relative.components() {
+        match component {
+            Component::Normal(part) => {
+                mapped.push('/');
+                mapped.push_str(&part.to_string_lossy());
+            }
+            Component::CurDir => {}
+            _ => return Err("Working directory is outside the project".to_string()),
+        }
+    }
+    Ok(mapped)
+}
+
+/// Executor for a loop: on its SSH host or in its container when configured, else a local
+/// subprocess, in a terminal the app can attach to for interactive tasks
+pub fn for_config(config: &LoopConfig, project_path: &Path) -> Box<dyn Executor> {
+    let adapter = get_adapter_at(config.cli_type, config.cli_path.as_deref());
+    match (&config.remote, &config.container) {
+        (Some(target), _) => Box::new(SshExecutor::new(target.clone(), project_path, adapter)),
+        (None, Some(container)) => Box::new(ContainerExecutor::new(
+            container.clone(),
+            project_path.to_path_buf(),
+            adapter,
+        )),
+        // Prompts written to a terminal would be echoed back as output
+        (None, None) if config.interactive_terminal && !adapter.prompt_via_stdin() => {
+            Box::new(PtyExecutor {
+                adapter,
+                priority: config.priority,
+                memory_limit_mb: config.memory_limit_mb,
+            })
+        }
+        (None, None) => Box::new(ProcessExecutor {
+            adapter,
+            priority: config.priority,
+            memory_limit_mb: config.memory_limit_mb,
+        }),
+    }
+}
+
+/// Runs the adapter's command as a child process of the app
+pub struct ProcessExecutor {
+    adapter: Box<dyn CliAdapter>,
+    priority: ProcessPriority,
+    memory_limit_mb: Option<u64>,
+}
+
+#[async_trait]
+impl Executor for ProcessExecutor {
+    async fn start(&self, request: IterationRequest<'_>) -> Result<Box<dyn Execution>, String> {
+        let mut cmd =
+            self.adapter
+                .build_command(request.prompt, request.working_dir, request.options);
+        cmd.envs(request.env.iter().cloned());
+        if request.network_blocked {
+            cmd.envs(super::network::offline_env(request.api_hosts));
+        }
+        super::priority::apply_to_command(&mut cmd, self.priority);
+        #[cfg(unix)]
+        super::resources::apply_to_command(&mut cmd, self.memory_limit_mb);
+        let mut execution = ProcessExecution::spawn(cmd, request.project_id)?;
+        #[cfg(target_os = "windows")]
+        if let Some(pid) = execution.child.id() {
+            if let Err(e) = super::resources::apply_to_process(pid, self.memory_limit_mb) {
+                execution.kill().await;
+                return Err(format!(
+                    "Failed to limit {} memory: {}",
+                    self.adapter.name(),
+                    e
+                ));
+            }
+        }
+
+        let stdin = execution.child.stdin.take();
+        if prompt_via_stdin_with(self.adapter.as_ref(), request.options) {
+            // Closed once written, so the CLI knows the prompt is complete
+            if let Some(mut stdin) = stdin {
+                let written = async {
+                    stdin.write_all(request.prompt.as_bytes()).await?;
+                    stdin.write_all(b"\n").await
+                }
+                .await;
+                if let Err(e) = written {
+                    execution.kill().await;
+                    return Err(format!(
+                        "Failed to write {} prompt: {}",
+                        self.adapter.name(),
+                        e
+                    ));
+                }
+            }
+        } else {
+            // Kept open when the adapter pipes it, for answering prompts
+            execution.stdin = stdin;
+        }
+
+        Ok(Box::new(execution))
+    }
+}
+
+async fn read_segment<R>(reader: &mut Option<ConsoleLines<R>>) -> Option<Segment>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    match reader {
+        Some(reader) => reader.next_segment().await.ok().flatten(),
+        None => None,
+    }
+}
+
+/// A spawned child process with its output streams
+pub(super) struct ProcessExecution {
+    child: Child,
+    /// Agent input prompts are answered on; only set for local CLIs
+    stdin: Option<ChildStdin>,
+    stdout: Option<ConsoleLines<ChildStdout>>,
+    stderr: Option<ConsoleLines<ChildStderr>>,
+    _tracked: TrackedProcess,
+}
+
+impl ProcessExecution {
+    /// Spawn `cmd` and record it in the process registry until dropped
+    pub(super) fn spawn(mut cmd: Command, project_id: &str) -> Result<Self, String> {
+        let program = PathBuf::from(cmd.as_std().get_program());
+        let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+        let tracked = processes::track(project_id, child.id(), &program);
+        Ok(Self {
+            stdin: None,
+            stdout: child.stdout.take().map(ConsoleLines::new),
+            stderr: child.stderr.take().map(ConsoleLines::new),
+            child,
+            _tracked: tracked,
+        })
+    }
+}
+
+#[async_trait]
+impl Execution for ProcessExecution {
+    async fn next_line(&mut self) -> Option<OutputLine> {
+        while self.stdout.is_some() || self.stderr.is_some() {
+            // `ConsoleLines::next_segment` is cancel-safe, so racing the two streams loses nothing
+            let (stdout, stderr) = tokio::select! {
+                segment = read_segment(&mut self.stdout), if self.stdout.is_some() => (Some(segment), None),
+                segment = read_segment(&mut self.stderr), if self.stderr.is_some() => (None, Some(segment)),
+            };
+            match (stdout, stderr) {
+                (Some(Some(Segment::Progress(text))), _)
+                | (_, Some(Some(Segment::Progress(text)))) => {
+                    return Some(OutputLine::Progress(text))
+                }
+                (Some(Some(Segment::Line(line))), _) => return Some(OutputLine::Stdout(line)),
+                (_, Some(Some(Segment::Line(line)))) => return Some(OutputLine::Stderr(line)),
+                (Some(None), _) => self.stdout = None,
+                (_, Some(None)) => self.stderr = None,
+                (None, None) => {}
+            }
+        }
+        None
+    }
+
+    async fn kill(&mut self) {
+        let _ = self.child.kill().await;
+    }
+
+    async fn wait(&mut self) -> Option<i32> {
+        self.child
+            .wait()
+            .await
+            .ok()
+            .and_then(|status| status.code())
+    }
+
+    fn pending_line(&self) -> Option<String> {
+        self.stdout
+            .as_ref()
+            .and_then(|lines| lines.pending())
+            .or_else(|| self.stderr.as_ref().and_then(|lines| lines.pending()))
+    }
+
+    async fn answer(&mut self, reply: &str) -> Result<(), String> {
+        let stdin = self.stdin.as_mut().ok_or(INPUT_CLOSED)?;
+        let written = async {
+            stdin.write_all(reply.as_bytes()).await?;
+            stdin.write_all(b"\n").await?;
+            stdin.flush().await
+        }
+        .await;
+        written.map_err(|e| format!("Failed to answer the CLI: {}", e))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env_async, EnvVarGuard};
+    use std::process::Stdio;
+    use tempfile::tempdir;
+
+    #[test]
+    fn maps_working_dir_onto_target_root() {
+        let project = Path::new("/home/me/repo");
+        assert_eq!(
+            map_working_dir(project, project, "/workspace").unwrap(),
+            "/workspace"
+        );
+        assert_eq!(
+            map_working_dir(project, &project.join("packages/web"), "/srv/repo/").unwrap(),
+            "/srv/repo/packages/web"
+        );
+        assert!(map_working_dir(project, Path::new("/tmp"), "/workspace").is_err());
+    }
+
+    #[tokio::test]
+    async fn process_execution_streams_both_outputs_then_exit_code() {
+        let _env_lock = lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "echo out; echo err >&2; exit 4"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut execution = ProcessExecution::spawn(cmd, "project").unwrap();
+
+        let mut lines = Vec::new();
+        while let Some(line) = execution.next_line().await {
+            lines.push(line);
+        }
+        lines.sort_by_key(|line| matches!(line, OutputLine::Stderr(_)));
+        assert_eq!(
+            lines,
+            vec![
+                OutputLine::Stdout("out".to_string()),
+                OutputLine::Stderr("err".to_string())
+            ]
+        );
+        assert_eq!(execution.wait().await, Some(4));
+    }
+}
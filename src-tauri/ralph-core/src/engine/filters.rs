@@ -0,0 +1,86 @@
+use crate::storage::models::SuppressedCount;
+use regex::Regex;
+
+/// Reject patterns that are empty or not valid regexes
+pub fn validate_patterns(patterns: &[String]) -> Result<(), String> {
+    OutputFilter::new(patterns).map(|_| ())
+}
+
+/// Mutes output lines matching any configured regex, counting what it hides
+#[derive(Debug, Default)]
+pub struct OutputFilter {
+    rules: Vec<(Regex, u64)>,
+}
+
+impl OutputFilter {
+    pub fn new(patterns: &[String]) -> Result<Self, String> {
+        let rules = patterns
+            .iter()
+            .map(|pattern| {
+                if pattern.trim().is_empty() {
+                    return Err("Output filter cannot be empty".to_string());
+                }
+                Regex::new(pattern)
+                    .map(|re| (re, 0))
+                    .map_err(|e| format!("Invalid output filter {}: {}", pattern, e))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Whether `line` should be hidden; the first matching pattern is credited
+    pub fn suppresses(&mut self, line: &str) -> bool {
+        match self.rules.iter_mut().find(|(re, _)| re.is_match(line)) {
+            Some((_, count)) => {
+                *count += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.rules.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Suppressed line counts per pattern, omitting patterns that never matched
+    pub fn counts(&self) -> Vec<SuppressedCount> {
+        self.rules
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .map(|(re, count)| SuppressedCount {
+                pattern: re.as_str().to_string(),
+                count: *count,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suppresses_matching_lines_and_counts_them() {
+        let mut filter =
+            OutputFilter::new(&["^npm (WARN|notice)".to_string(), "telemetry".to_string()])
+                .unwrap();
+
+        assert!(filter.suppresses("npm WARN deprecated inflight@1.0.6"));
+        assert!(filter.suppresses("npm notice New minor version"));
+        assert!(filter.suppresses("Sending telemetry event"));
+        assert!(!filter.suppresses("All tests passed"));
+
+        assert_eq!(filter.total(), 3);
+        let counts = filter.counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[0].count, 2);
+    }
+
+    #[test]
+    fn rejects_invalid_patterns() {
+        assert!(validate_patterns(&["(unclosed".to_string()]).is_err());
+        assert!(validate_patterns(&["  ".to_string()]).is_err());
+        assert!(validate_patterns(&[]).is_ok());
+    }
+}
@@ -0,0 +1,284 @@
+//! Project health: a 0–100 score from the project's recent sessions, with the
+//! changes to its task most likely to help.
+
+use crate::storage::models::{
+    HealthRecommendation, IterationOutcome, IterationRecord, ProjectHealth, RecommendationKind,
+    TaskConfig,
+};
+
+/// Sessions the score looks back on
+pub const RECENT_SESSIONS: usize = 10;
+/// Iterations in a row without a commit after which a session counts as stalled
+const STALL_STREAK: usize = 3;
+/// Prompts longer than this are suggested to be shortened when sessions fail
+const LONG_PROMPT_WORDS: usize = 400;
+
+fn finished(session: &[IterationRecord]) -> bool {
+    session
+        .last()
+        .is_some_and(|record| record.outcome != IterationOutcome::Running)
+}
+
+fn completed(session: &[IterationRecord]) -> bool {
+    session
+        .iter()
+        .any(|record| record.outcome == IterationOutcome::Completed)
+}
+
+fn productive(record: &IterationRecord) -> bool {
+    !record.commit_shas.is_empty() || record.outcome == IterationOutcome::Completed
+}
+
+fn stalled(session: &[IterationRecord]) -> bool {
+    let mut streak = 0;
+    for record in session {
+        streak = if productive(record) { 0 } else { streak + 1 };
+        if streak >= STALL_STREAK {
+            return true;
+        }
+    }
+    false
+}
+
+fn ratio(count: usize, total: usize) -> Option<f64> {
+    (total > 0).then(|| count as f64 / total as f64)
+}
+
+fn validation_pass_rate(sessions: &[&[IterationRecord]]) -> Option<f64> {
+    let results: Vec<bool> = sessions
+        .iter()
+        .flat_map(|session| session.iter().filter_map(|record| record.validation_passed))
+        .collect();
+    ratio(
+        results.iter().filter(|passed| **passed).count(),
+        results.len(),
+    )
+}
+
+fn tokens(record: &IterationRecord) -> u64 {
+    record
+        .tokens
+        .as_ref()
+        .map_or(0, |usage| usage.input_tokens + usage.output_tokens)
+}
+
+fn percent(value: f64) -> u32 {
+    (value * 100.0).round() as u32
+}
+
+/// Score and recommendations from `sessions`, newest first, run for `task`
+pub fn assess(task: Option<&TaskConfig>, sessions: &[Vec<IterationRecord>]) -> ProjectHealth {
+    let sessions: Vec<&[IterationRecord]> = sessions
+        .iter()
+        .map(Vec::as_slice)
+        .filter(|session| finished(session))
+        .take(RECENT_SESSIONS)
+        .collect();
+    let completed_count = sessions.iter().filter(|s| completed(s)).count();
+    let success_ratio = ratio(completed_count, sessions.len());
+
+    // Without auto-commit no commits are recorded, so every session would look stalled
+    let stall_frequency = task.filter(|task| task.auto_commit).and_then(|_| {
+        ratio(
+            sessions.iter().filter(|s| stalled(s)).count(),
+            sessions.len(),
+        )
+    });
+
+    let test_pass_rate = validation_pass_rate(&sessions);
+    let (newer, older) = sessions.split_at(sessions.len() / 2);
+    let test_pass_trend = validation_pass_rate(newer)
+        .zip(validation_pass_rate(older))
+        .map(|(newer, older)| newer - older);
+
+    let records = sessions.iter().flat_map(|session| session.iter());
+    let total_tokens: u64 = records.clone().map(tokens).sum();
+    let productive_tokens: u64 = records.filter(|r| productive(r)).map(tokens).sum();
+    let cost_efficiency =
+        (total_tokens > 0).then(|| productive_tokens as f64 / total_tokens as f64);
+
+    let components = [
+        (success_ratio, 0.4),
+        (stall_frequency.map(|f| 1.0 - f), 0.2),
+        (test_pass_rate, 0.2),
+        (cost_efficiency, 0.2),
+    ];
+    let weight: f64 = components
+        .iter()
+        .filter(|(v, _)| v.is_some())
+        .map(|(_, w)| w)
+        .sum();
+    let score = success_ratio.map(|_| {
+        let total: f64 = components
+            .iter()
+            .filter_map(|(v, w)| v.map(|v| v * w))
+            .sum();
+        (total / weight * 100.0).round() as u8
+    });
+
+    let mut recommendations = Vec::new();
+    if let Some(task) = task {
+        let mut recommend = |kind, message: String| {
+            recommendations.push(HealthRecommendation { kind, message });
+        };
+        let words = task.prompt.split_whitespace().count();
+        if success_ratio.is_some_and(|r| r < 0.5) && words > LONG_PROMPT_WORDS {
+            recommend(
+                RecommendationKind::ShortenPrompt,
+                format!(
+                    "Only {}% of sessions completed with this {}-word prompt; shorten it to the goal and its constraints, or split the task",
+                    percent(success_ratio.unwrap_or_default()),
+                    words
+                ),
+            );
+        }
+        let struggling =
+            success_ratio.is_some_and(|r| r < 0.8) || stall_frequency.is_some_and(|f| f >= 0.3);
+        if task.validation_command.is_none() && struggling {
+            recommend(
+                RecommendationKind::EnableTestGate,
+                "Nothing checks the agent's work between iterations; set a validation command such as the test suite so failures go into the next prompt".to_string(),
+            );
+        }
+        if let Some(trend) = test_pass_trend.filter(|t| *t <= -0.2) {
+            recommend(
+                RecommendationKind::ReviewValidation,
+                format!(
+                    "The validation command passes {} points less often than in earlier sessions; check whether the tests or the task changed",
+                    percent(-trend)
+                ),
+            );
+        }
+        if let Some(frequency) = stall_frequency.filter(|f| *f >= 0.3) {
+            if !task.carry_over_progress {
+                recommend(
+                    RecommendationKind::CarryOverProgress,
+                    format!(
+                        "{}% of sessions went {} or more iterations without a commit; carry a summary of each iteration into the next prompt so the agent picks up where it left off",
+                        percent(frequency),
+                        STALL_STREAK
+                    ),
+                );
+            }
+        }
+        let needed = sessions
+            .iter()
+            .filter(|s| completed(s))
+            .map(|s| s.len() as u32)
+            .max();
+        if let Some(needed) =
+            needed.filter(|n| completed_count >= 2 && task.max_iterations >= n * 2 + 5)
+        {
+            recommend(
+                RecommendationKind::ReduceIterations,
+                format!(
+                    "Completed sessions took at most {} iterations; lowering max iterations from {} to {} limits what a session that goes astray can spend",
+                    needed,
+                    task.max_iterations,
+                    needed + needed.div_ceil(2)
+                ),
+            );
+        } else if cost_efficiency.is_some_and(|e| e < 0.5) && task.max_iterations > 5 {
+            recommend(
+                RecommendationKind::ReduceIterations,
+                format!(
+                    "Only {}% of tokens went to iterations that committed or completed; fewer than {} max iterations stops unproductive sessions sooner",
+                    percent(cost_efficiency.unwrap_or_default()),
+                    task.max_iterations
+                ),
+            );
+        }
+    }
+
+    ProjectHealth {
+        sessions: sessions.len() as u32,
+        score,
+        success_ratio,
+        stall_frequency,
+        test_pass_rate,
+        test_pass_trend,
+        cost_efficiency,
+        recommendations,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::{CliType, IterationUsage};
+
+    fn record(number: u32, outcome: IterationOutcome, committed: bool) -> IterationRecord {
+        let mut record = IterationRecord::start(number);
+        record.finish(outcome);
+        if committed {
+            record.commit_shas.push(format!("sha{}", number));
+        }
+        record.tokens = Some(IterationUsage {
+            iteration: number,
+            cli: CliType::Claude,
+            input_tokens: 1_000,
+            output_tokens: 0,
+            cache_read_tokens: 0,
+            cost_usd: None,
+        });
+        record
+    }
+
+    fn session(outcomes: &[(IterationOutcome, bool)]) -> Vec<IterationRecord> {
+        outcomes
+            .iter()
+            .enumerate()
+            .map(|(i, (outcome, committed))| record(i as u32 + 1, *outcome, *committed))
+            .collect()
+    }
+
+    #[test]
+    fn healthy_sessions_score_high_and_suggest_fewer_iterations() {
+        use IterationOutcome::*;
+        let task = TaskConfig {
+            max_iterations: 50,
+            auto_commit: true,
+            ..TaskConfig::default()
+        };
+        let sessions = vec![
+            session(&[(Continued, true), (Completed, true)]),
+            session(&[(Continued, true), (Continued, true), (Completed, true)]),
+        ];
+        let health = assess(Some(&task), &sessions);
+        assert_eq!(health.score, Some(100));
+        assert_eq!(health.stall_frequency, Some(0.0));
+        let kinds: Vec<_> = health.recommendations.iter().map(|r| r.kind).collect();
+        assert_eq!(kinds, vec![RecommendationKind::ReduceIterations]);
+        assert!(health.recommendations[0].message.contains("from 50 to 5"));
+    }
+
+    #[test]
+    fn stalling_sessions_without_a_test_gate_get_recommendations() {
+        use IterationOutcome::*;
+        let task = TaskConfig {
+            auto_commit: true,
+            ..TaskConfig::default()
+        };
+        let sessions = vec![
+            session(&[(Continued, false), (Continued, false), (Continued, false)]),
+            session(&[(Continued, true), (Completed, true)]),
+            // Still running; not assessed
+            vec![IterationRecord::start(1)],
+        ];
+        let health = assess(Some(&task), &sessions);
+        assert_eq!(health.sessions, 2);
+        assert_eq!(health.success_ratio, Some(0.5));
+        assert_eq!(health.stall_frequency, Some(0.5));
+        assert!(health.score.is_some_and(|score| score < 60));
+        let kinds: Vec<_> = health.recommendations.iter().map(|r| r.kind).collect();
+        assert!(kinds.contains(&RecommendationKind::EnableTestGate));
+        assert!(kinds.contains(&RecommendationKind::CarryOverProgress));
+    }
+
+    #[test]
+    fn no_sessions_no_score() {
+        let health = assess(None, &[]);
+        assert_eq!(health.score, None);
+        assert!(health.recommendations.is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::storage::models::{Highlight, HighlightRule, HighlightSeverity};
+use regex::Regex;
+
+/// Most highlights kept per session; later matches are still flagged live
+pub const MAX_SESSION_HIGHLIGHTS: usize = 500;
+
+/// Default rules, checked in order so test failures win over generic errors
+pub fn builtin_rules() -> Vec<HighlightRule> {
+    let rule = |pattern: &str, label: &str, severity| HighlightRule {
+        pattern: pattern.to_string(),
+        label: label.to_string(),
+        severity,
+    };
+    vec![
+        rule(
+            r"(?i)\btests? (failed|failing)\b|\bFAILED\b|^\s*(✗|✕)",
+            "test-failure",
+            HighlightSeverity::Error,
+        ),
+        rule(
+            r"(?i)\b(error|exception|panicked)\b",
+            "error",
+            HighlightSeverity::Error,
+        ),
+        rule(r"(?i)\bwarn(ing)?\b", "warning", HighlightSeverity::Warning),
+        rule(r"\b(TODO|FIXME|XXX)\b", "todo", HighlightSeverity::Info),
+    ]
+}
+
+/// Reject rules with empty labels or invalid regexes
+pub fn validate_rules(rules: &[HighlightRule]) -> Result<(), String> {
+    Highlighter::new(rules).map(|_| ())
+}
+
+/// Labels output lines with the first highlight rule they match
+#[derive(Debug, Default)]
+pub struct Highlighter {
+    rules: Vec<(Regex, Highlight)>,
+}
+
+impl Highlighter {
+    pub fn new(rules: &[HighlightRule]) -> Result<Self, String> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                if rule.label.trim().is_empty() {
+                    return Err(format!("Highlight rule {} needs a label", rule.pattern));
+                }
+                let re = Regex::new(&rule.pattern)
+                    .map_err(|e| format!("Invalid highlight rule {}: {}", rule.pattern, e))?;
+                Ok((
+                    re,
+                    Highlight {
+                        label: rule.label.trim().to_string(),
+                        severity: rule.severity,
+                    },
+                ))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { rules })
+    }
+
+    pub fn classify(&self, line: &str) -> Option<Highlight> {
+        self.rules
+            .iter()
+            .find(|(re, _)| re.is_match(line))
+            .map(|(_, highlight)| highlight.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_rules_classify_common_output() {
+        let highlighter = Highlighter::new(&builtin_rules()).unwrap();
+        let label = |line: &str| highlighter.classify(line).map(|h| h.label);
+
+        assert_eq!(
+            label("test result: FAILED. 3 passed; 1 failed").as_deref(),
+            Some("test-failure")
+        );
+        assert_eq!(
+            label("error[E0308]: mismatched types").as_deref(),
+            Some("error")
+        );
+        assert_eq!(
+            label("warning: unused variable `x`").as_deref(),
+            Some("warning")
+        );
+        assert_eq!(label("// TODO: handle retries").as_deref(), Some("todo"));
+        assert_eq!(label("Compiling ralph-desktop v0.1.0"), None);
+    }
+
+    #[test]
+    fn rejects_invalid_rules() {
+        let mut rules = vec![HighlightRule {
+            pattern: "(unclosed".to_string(),
+            label: "broken".to_string(),
+            severity: HighlightSeverity::Info,
+        }];
+        assert!(validate_rules(&rules).is_err());
+
+        rules[0].pattern = "ok".to_string();
+        rules[0].label = " ".to_string();
+        assert!(validate_rules(&rules).is_err());
+    }
+}
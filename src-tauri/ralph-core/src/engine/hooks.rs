@@ -0,0 +1,79 @@
+//! Script run before every iteration, such as `git pull`, a dependency install
+//! or an environment reset, with its output streamed into the loop's output.
+
+use super::executor::{Execution, OutputLine, ProcessExecution};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Longest a pre-iteration script may take
+const HOOK_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Run `command` in `dir`, handing each output line and whether it came from
+/// stderr to `on_line`; the error says how it failed
+pub async fn run_pre_iteration(
+    project_id: &str,
+    command: &str,
+    dir: &Path,
+    mut on_line: impl FnMut(String, bool),
+) -> Result<(), String> {
+    let mut cmd = super::screenshot::shell_command(command, dir);
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let mut execution = ProcessExecution::spawn(cmd, project_id)?;
+    let streamed = tokio::time::timeout(HOOK_TIMEOUT, async {
+        while let Some(line) = execution.next_line().await {
+            match line {
+                OutputLine::Stdout(line) => on_line(line, false),
+                OutputLine::Stderr(line) => on_line(line, true),
+                OutputLine::Progress(_) => {}
+            }
+        }
+    })
+    .await;
+    if streamed.is_err() {
+        execution.kill().await;
+        return Err(format!(
+            "timed out after {} seconds",
+            HOOK_TIMEOUT.as_secs()
+        ));
+    }
+    match execution.wait().await {
+        Some(0) => Ok(()),
+        Some(code) => Err(format!("exited with code {}", code)),
+        None => Err("was terminated".to_string()),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env_async, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn streams_output_and_reports_the_exit_code() {
+        let _env_lock = lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let mut lines = Vec::new();
+        let result = run_pre_iteration(
+            "project",
+            "echo pulled; echo conflict >&2; exit 2",
+            home_dir.path(),
+            |line, is_stderr| lines.push((line, is_stderr)),
+        )
+        .await;
+        assert_eq!(result, Err("exited with code 2".to_string()));
+        lines.sort_by_key(|(_, is_stderr)| *is_stderr);
+        assert_eq!(
+            lines,
+            vec![
+                ("pulled".to_string(), false),
+                ("conflict".to_string(), true)
+            ]
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use crate::adapters::hide_console_window;
+use crate::storage::models::FileHotspot;
+use std::collections::HashMap;
+use std::path::Path;
+use tokio::process::Command;
+
+const DELETED: &str = "deleted";
+
+/// Counts how often each file changes across the iterations of a session
+#[derive(Debug, Default)]
+pub struct ChangeTracker {
+    fingerprints: HashMap<String, String>,
+    counts: HashMap<String, u32>,
+}
+
+impl ChangeTracker {
+    /// Record the working tree state without counting it as a change
+    pub async fn baseline(&mut self, project_path: &Path) {
+        if let Some(fingerprints) = dirty_fingerprints(project_path).await {
+            self.fingerprints = fingerprints;
+        }
+    }
+
+    /// Count files whose content changed since the last snapshot
+    pub async fn record_iteration(&mut self, project_path: &Path) -> Vec<String> {
+        let Some(next) = dirty_fingerprints(project_path).await else {
+            return Vec::new();
+        };
+        let changed = changed_files(&self.fingerprints, &next);
+        for path in &changed {
+            *self.counts.entry(path.clone()).or_insert(0) += 1;
+        }
+        self.fingerprints = next;
+        changed
+    }
+
+    pub fn hotspots(&self) -> Vec<FileHotspot> {
+        rank(&self.counts)
+    }
+}
+
+/// Files that are new, modified, or no longer dirty in `next` compared to `prev`
+fn changed_files(prev: &HashMap<String, String>, next: &HashMap<String, String>) -> Vec<String> {
+    let mut changed: Vec<String> = next
+        .iter()
+        .filter(|(path, hash)| prev.get(*path) != Some(*hash))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.sort();
+    changed
+}
+
+pub fn rank(counts: &HashMap<String, u32>) -> Vec<FileHotspot> {
+    let mut hotspots: Vec<FileHotspot> = counts
+        .iter()
+        .map(|(path, changes)| FileHotspot {
+            path: path.clone(),
+            changes: *changes,
+        })
+        .collect();
+    hotspots.sort_by(|a, b| b.changes.cmp(&a.changes).then_with(|| a.path.cmp(&b.path)));
+    hotspots
+}
+
+fn parse_porcelain(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter(|line| line.len() > 3)
+        .map(|line| {
+            let path = &line[3..];
+            let path = path.rsplit(" -> ").next().unwrap_or(path);
+            path.trim_matches('"').to_string()
+        })
+        .collect()
+}
+
+async fn git_output(project_path: &Path, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(project_path).args(args);
+    hide_console_window(&mut cmd);
+    let output = cmd.output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Content hash of every dirty file in the working tree
+async fn dirty_fingerprints(project_path: &Path) -> Option<HashMap<String, String>> {
+    let status = git_output(project_path, &["status", "--porcelain", "-uall"]).await?;
+    let paths = parse_porcelain(&status);
+    let mut fingerprints = HashMap::new();
+
+    let (existing, deleted): (Vec<String>, Vec<String>) = paths
+        .into_iter()
+        .partition(|path| project_path.join(path).is_file());
+    for path in deleted {
+        fingerprints.insert(path, DELETED.to_string());
+    }
+    if !existing.is_empty() {
+        let mut args = vec!["hash-object", "--"];
+        args.extend(existing.iter().map(String::as_str));
+        let hashes = git_output(project_path, &args).await?;
+        for (path, hash) in existing.into_iter().zip(hashes.lines()) {
+            fingerprints.insert(path, hash.trim().to_string());
+        }
+    }
+    Some(fingerprints)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, &str)]) -> HashMap<String, String> {
+        entries
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn changed_files_detects_new_and_modified() {
+        let prev = map(&[("a.rs", "1"), ("b.rs", "2")]);
+        let next = map(&[("a.rs", "1"), ("b.rs", "3"), ("c.rs", "4")]);
+        assert_eq!(changed_files(&prev, &next), vec!["b.rs", "c.rs"]);
+    }
+
+    #[test]
+    fn parse_porcelain_handles_renames_and_untracked() {
+        let output = " M src/main.rs\n?? notes.md\nR  old.rs -> new.rs\n";
+        assert_eq!(
+            parse_porcelain(output),
+            vec!["src/main.rs", "notes.md", "new.rs"]
+        );
+    }
+
+    #[test]
+    fn rank_orders_by_change_count() {
+        let counts: HashMap<String, u32> =
+            [("a.rs".to_string(), 2), ("b.rs".to_string(), 15)].into();
+        let ranked = rank(&counts);
+        assert_eq!(ranked[0].path, "b.rs");
+        assert_eq!(ranked[0].changes, 15);
+    }
+}
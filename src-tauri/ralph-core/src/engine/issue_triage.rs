@@ -0,0 +1,159 @@
+//! Built-in recipe for triaging a repository's open GitHub issues: the GitHub
+//! CLI lists them, then a readonly loop proposes labels, priorities and
+//! duplicates in a report that is kept as a session artifact.
+
+use crate::adapters::{apply_extended_path, hide_console_window};
+use serde::Deserialize;
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Where the agent writes its report, relative to the working directory
+pub const REPORT_FILE: &str = ".ralph/triage-report.md";
+/// Most issues handed to one triage session
+const MAX_ISSUES: usize = 100;
+/// Longest part of an issue body quoted in the prompt
+const MAX_BODY_CHARS: usize = 1500;
+/// Longest a `gh` call may take
+const GH_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Deserialize)]
+struct Label {
+    name: String,
+}
+
+/// Open issue as `gh issue list --json` reports it
+#[derive(Debug, Clone, Deserialize)]
+pub struct Issue {
+    pub number: u64,
+    pub title: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    labels: Vec<Label>,
+}
+
+async fn run_gh(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let mut cmd = Command::new("gh");
+    cmd.args(args).current_dir(dir).kill_on_drop(true);
+    apply_extended_path(&mut cmd);
+    hide_console_window(&mut cmd);
+    let output = tokio::time::timeout(GH_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| "The GitHub CLI timed out".to_string())?
+        .map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                "Issue triage needs the GitHub CLI (gh); install it and run `gh auth login`"
+                    .to_string()
+            }
+            _ => format!("Failed to run gh: {}", e),
+        })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("gh failed: {}", stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Open issues of the GitHub repository `dir` belongs to, newest first
+pub async fn open_issues(dir: &Path) -> Result<Vec<Issue>, String> {
+    let limit = MAX_ISSUES.to_string();
+    let json = run_gh(
+        dir,
+        &[
+            "issue",
+            "list",
+            "--state",
+            "open",
+            "--limit",
+            &limit,
+            "--json",
+            "number,title,body,labels",
+        ],
+    )
+    .await?;
+    serde_json::from_str(&json).map_err(|e| format!("Unexpected gh output: {}", e))
+}
+
+/// Names of the labels the repository defines
+pub async fn repo_labels(dir: &Path) -> Result<Vec<String>, String> {
+    let json = run_gh(dir, &["label", "list", "--limit", "200", "--json", "name"]).await?;
+    let labels: Vec<Label> =
+        serde_json::from_str(&json).map_err(|e| format!("Unexpected gh output: {}", e))?;
+    Ok(labels.into_iter().map(|label| label.name).collect())
+}
+
+fn quoted_body(body: &str) -> String {
+    let body = body.trim();
+    if body.is_empty() {
+        return "(no description)".to_string();
+    }
+    if body.chars().count() <= MAX_BODY_CHARS {
+        return body.to_string();
+    }
+    let mut truncated: String = body.chars().take(MAX_BODY_CHARS).collect();
+    truncated.push_str("\n... (truncated) ...");
+    truncated
+}
+
+/// Task prompt of a triage loop
+pub fn triage_prompt(issues: &[Issue], labels: &[String]) -> String {
+    let mut prompt = format!(
+        "Triage the open GitHub issues of this repository, listed below. This is a readonly \
+         pass: do NOT modify, create or delete any file except `{REPORT_FILE}`, do not commit, \
+         and do not comment on, label or close issues on GitHub. Read the code where it helps \
+         to judge an issue.\n\n\
+         Write the triage report in Markdown to `{REPORT_FILE}`, replacing it if it exists:\n\
+         - A table with one row per issue: number, title, proposed labels, priority (P0 \
+         critical to P3 minor) and a one-line summary of what is asked\n\
+         - Likely duplicates, each with the issue it duplicates and why\n\
+         - Issues that need more information before anyone can act on them, and what to ask\n\n"
+    );
+    if labels.is_empty() {
+        prompt.push_str("The repository defines no labels yet; propose short, conventional ones.");
+    } else {
+        prompt.push_str(&format!(
+            "Prefer the repository's existing labels: {}.",
+            labels.join(", ")
+        ));
+    }
+    prompt.push_str("\n\n## Open issues\n");
+    for issue in issues {
+        prompt.push_str(&format!("\n### #{} {}\n", issue.number, issue.title.trim()));
+        if !issue.labels.is_empty() {
+            let current: Vec<&str> = issue.labels.iter().map(|l| l.name.as_str()).collect();
+            prompt.push_str(&format!("Labels: {}\n", current.join(", ")));
+        }
+        prompt.push_str(&format!("\n{}\n", quoted_body(&issue.body)));
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_lists_issues_and_labels() {
+        let issues: Vec<Issue> = serde_json::from_str(
+            r#"[
+                {"number": 12, "title": "Crash on empty input", "body": "Steps: ...", "labels": [{"id": "1", "name": "bug"}]},
+                {"number": 13, "title": "Dark mode", "body": "", "labels": []}
+            ]"#,
+        )
+        .unwrap();
+        let prompt = triage_prompt(&issues, &["bug".to_string(), "enhancement".to_string()]);
+        assert!(prompt.contains("### #12 Crash on empty input\nLabels: bug\n"));
+        assert!(prompt.contains("### #13 Dark mode\n\n(no description)"));
+        assert!(prompt.contains("existing labels: bug, enhancement."));
+        assert!(prompt.contains(REPORT_FILE));
+    }
+
+    #[test]
+    fn truncates_long_issue_bodies() {
+        let body = "x".repeat(MAX_BODY_CHARS + 10);
+        let quoted = quoted_body(&body);
+        assert!(quoted.ends_with("(truncated) ..."));
+        assert!(quoted.len() < body.len() + 30);
+    }
+}
@@ -0,0 +1,133 @@
+//! Opt-in knowledge base of what past sessions learned, across projects: the
+//! summaries sessions completed with and the fixes they committed. Before a
+//! loop starts, the entries most relevant to its task are looked up in a local
+//! keyword index and offered for the prompt, so an environment quirk solved
+//! once is not solved again in every project.
+
+use crate::index::TextIndex;
+use crate::storage;
+use crate::storage::models::{KnowledgeEntry, KnowledgeKind, KnowledgeMatch};
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// Learnings offered before a loop starts
+pub const TOP_K: usize = 3;
+
+/// Entries by id with their index
+#[derive(Default)]
+struct KnowledgeBase {
+    entries: HashMap<Uuid, KnowledgeEntry>,
+    index: TextIndex<Uuid>,
+}
+
+impl KnowledgeBase {
+    fn insert(&mut self, entry: KnowledgeEntry) {
+        self.index.upsert(entry.id, &entry.text);
+        self.entries.insert(entry.id, entry);
+    }
+
+    fn search(&self, query: &str, k: usize) -> Vec<KnowledgeMatch> {
+        self.index
+            .search(query, k)
+            .into_iter()
+            .filter_map(|(id, score)| {
+                Some(KnowledgeMatch {
+                    entry: self.entries.get(&id)?.clone(),
+                    score,
+                    project_name: None,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Built from storage on the first lookup, then updated as entries are recorded
+static LOADED: Mutex<Option<KnowledgeBase>> = Mutex::new(None);
+
+/// Save entries to the knowledge base and add them to its index
+pub fn record(entries: Vec<KnowledgeEntry>) -> Result<(), String> {
+    storage::append_knowledge_entries(&entries).map_err(|e| e.to_string())?;
+    let mut loaded = LOADED.lock().map_err(|e| e.to_string())?;
+    if let Some(base) = loaded.as_mut() {
+        for entry in entries {
+            base.insert(entry);
+        }
+    }
+    Ok(())
+}
+
+/// The `k` entries most relevant to `query`, best first; entries sharing no keyword are left out
+pub fn suggest(query: &str, k: usize) -> Result<Vec<KnowledgeMatch>, String> {
+    let mut loaded = LOADED.lock().map_err(|e| e.to_string())?;
+    if loaded.is_none() {
+        let mut base = KnowledgeBase::default();
+        for entry in storage::load_knowledge_entries().map_err(|e| e.to_string())? {
+            base.insert(entry);
+        }
+        *loaded = Some(base);
+    }
+    Ok(loaded
+        .as_ref()
+        .map(|base| base.search(query, k))
+        .unwrap_or_default())
+}
+
+/// Entries for a finished session: its completion summary and the fixes it committed
+pub fn session_entries(
+    project_id: Uuid,
+    session_id: &str,
+    summary: Option<&str>,
+    fixes: &[String],
+) -> Vec<KnowledgeEntry> {
+    let entry = |kind, text: &str| KnowledgeEntry {
+        id: Uuid::new_v4(),
+        project_id,
+        session_id: session_id.to_string(),
+        kind,
+        text: text.trim().to_string(),
+        created_at: Utc::now(),
+    };
+    summary
+        .filter(|text| !text.trim().is_empty())
+        .map(|text| entry(KnowledgeKind::Summary, text))
+        .into_iter()
+        .chain(fixes.iter().map(|fix| entry(KnowledgeKind::Fix, fix)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_entries_by_their_text() {
+        let fixes = vec![
+            "Fix the build on macOS by pinning openssl to the Homebrew prefix".to_string(),
+            "Fix flaky login test by waiting for the session cookie".to_string(),
+        ];
+        let mut base = KnowledgeBase::default();
+        for entry in session_entries(Uuid::new_v4(), "s1", None, &fixes) {
+            base.insert(entry);
+        }
+        let matches = base.search("Set up the Rust build with openssl on macOS", TOP_K);
+        assert_eq!(matches.len(), 1);
+        assert!(matches[0].entry.text.contains("openssl"));
+    }
+
+    #[test]
+    fn keeps_summaries_and_fixes_of_a_session() {
+        let project = Uuid::new_v4();
+        let found = session_entries(
+            project,
+            "s1",
+            Some("Login works end to end."),
+            &["Fix crash on empty input".to_string()],
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].kind, KnowledgeKind::Summary);
+        assert_eq!(found[1].kind, KnowledgeKind::Fix);
+        assert!(session_entries(project, "s2", Some("  "), &[]).is_empty());
+    }
+}
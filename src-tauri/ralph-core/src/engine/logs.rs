@@ -0,0 +1,664 @@
+use crate::storage::models::SessionNote;
+use crate::storage::{crypto, ensure_project_dir, get_project_dir};
+use chrono::Utc;
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// Stable log path that always points at the running session, for `tail -f`
+pub const CURRENT_LOG: &str = "current.log";
+/// Lines waiting for the writer before the oldest are dropped
+const QUEUE_CAPACITY: usize = 4096;
+/// Longest written lines may go without reaching the disk
+const SYNC_INTERVAL: Duration = Duration::from_secs(1);
+
+enum Record {
+    Line(String),
+    Raw(String),
+    /// Start capturing raw output into this file
+    RawFile(BufWriter<File>),
+    /// Answered once everything queued before it is on disk
+    Barrier(mpsc::Sender<()>),
+}
+
+#[derive(Default)]
+struct QueueState {
+    records: VecDeque<Record>,
+    /// Lines dropped since the writer last took a batch
+    dropped: u64,
+    closed: bool,
+}
+
+/// Bounded queue between the engine and the writer thread; when the disk falls
+/// behind, the oldest lines are dropped and counted instead of blocking the engine
+struct LogQueue {
+    capacity: usize,
+    state: Mutex<QueueState>,
+    ready: Condvar,
+}
+
+impl LogQueue {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(QueueState::default()),
+            ready: Condvar::new(),
+        }
+    }
+
+    fn push(&self, record: Record) {
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        if state.closed {
+            return;
+        }
+        if state.records.len() >= self.capacity {
+            let oldest = state
+                .records
+                .iter()
+                .position(|r| matches!(r, Record::Line(_) | Record::Raw(_)));
+            if let Some(index) = oldest {
+                state.records.remove(index);
+                state.dropped += 1;
+            }
+        }
+        state.records.push_back(record);
+        self.ready.notify_one();
+    }
+
+    fn close(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.closed = true;
+        }
+        self.ready.notify_one();
+    }
+
+    /// Everything queued with the number of lines dropped before it; empty once
+    /// closed and drained
+    fn take_batch(&self) -> Option<(VecDeque<Record>, u64)> {
+        let mut state = self.state.lock().ok()?;
+        while state.records.is_empty() && !state.closed {
+            state = self.ready.wait(state).ok()?;
+        }
+        if state.records.is_empty() {
+            return None;
+        }
+        Some((
+            std::mem::take(&mut state.records),
+            std::mem::take(&mut state.dropped),
+        ))
+    }
+}
+
+/// Files of the running session, owned by the writer thread
+struct SessionFiles {
+    log: BufWriter<File>,
+    /// Copy of every line written to `current.log` where symlinks are unavailable
+    mirror: Option<BufWriter<File>>,
+    /// Unparsed CLI output, only when raw debugging is enabled
+    raw: Option<BufWriter<File>>,
+}
+
+impl SessionFiles {
+    fn write_line(&mut self, line: &str) {
+        for writer in [Some(&mut self.log), self.mirror.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = writeln!(writer, "{}", line);
+        }
+    }
+
+    fn flush(&mut self, sync: bool) {
+        for writer in [Some(&mut self.log), self.mirror.as_mut(), self.raw.as_mut()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = writer.flush();
+            if sync {
+                let _ = writer.get_ref().sync_data();
+            }
+        }
+    }
+}
+
+/// Write batches as they come: flushed right away so tailing readers see them,
+/// synced to disk at most every `SYNC_INTERVAL` and when the session ends
+fn run_writer(queue: Arc<LogQueue>, mut files: SessionFiles) {
+    let mut last_sync = Instant::now();
+    while let Some((batch, dropped)) = queue.take_batch() {
+        if dropped > 0 {
+            files.write_line(&format!(
+                "# {} log lines dropped while the disk fell behind",
+                dropped
+            ));
+        }
+        let mut barriers = Vec::new();
+        for record in batch {
+            match record {
+                Record::Line(line) => files.write_line(&line),
+                Record::Raw(line) => {
+                    if let Some(writer) = files.raw.as_mut() {
+                        let _ = writeln!(writer, "{}", line);
+                    }
+                }
+                Record::RawFile(writer) => files.raw = Some(writer),
+                Record::Barrier(done) => barriers.push(done),
+            }
+        }
+        let sync = !barriers.is_empty() || last_sync.elapsed() >= SYNC_INTERVAL;
+        files.flush(sync);
+        if sync {
+            last_sync = Instant::now();
+        }
+        for done in barriers {
+            let _ = done.send(());
+        }
+    }
+    files.flush(true);
+}
+
+struct LogWriter {
+    queue: Arc<LogQueue>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl LogWriter {
+    fn spawn(files: SessionFiles) -> Result<Self, String> {
+        let queue = Arc::new(LogQueue::new(QUEUE_CAPACITY));
+        let writer_queue = queue.clone();
+        let thread = std::thread::Builder::new()
+            .name("session-log-writer".to_string())
+            .spawn(move || run_writer(writer_queue, files))
+            .map_err(|e| e.to_string())?;
+        Ok(Self {
+            queue,
+            thread: Some(thread),
+        })
+    }
+
+    /// Wait until everything queued so far is on disk
+    fn flush(&self) {
+        let (done, written) = mpsc::channel();
+        self.queue.push(Record::Barrier(done));
+        let _ = written.recv();
+    }
+
+    /// Write what is queued, then stop the thread
+    fn finish(mut self) {
+        self.queue.close();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for LogWriter {
+    fn drop(&mut self) {
+        self.queue.close();
+    }
+}
+
+/// Log manager for persisting execution logs; lines are handed to a writer
+/// thread so a slow disk never stalls the engine
+#[allow(dead_code)]
+pub struct LogManager {
+    project_id: uuid::Uuid,
+    log_path: Option<PathBuf>,
+    writer: Option<LogWriter>,
+}
+
+#[allow(dead_code)]
+impl LogManager {
+    pub fn new(project_id: uuid::Uuid) -> Self {
+        Self {
+            project_id,
+            log_path: None,
+            writer: None,
+        }
+    }
+
+    /// Path of the stable `current.log` for a project
+    pub fn current_log_path(project_id: &uuid::Uuid) -> Result<PathBuf, String> {
+        let project_dir = get_project_dir(project_id).map_err(|e| e.to_string())?;
+        Ok(project_dir.join("logs").join(CURRENT_LOG))
+    }
+
+    /// Point `current.log` at the new session file; returns the copy to write
+    /// where symlinks are unavailable
+    fn link_current_log(&self, log_path: &Path) -> Result<Option<BufWriter<File>>, String> {
+        let current = Self::current_log_path(&self.project_id)?;
+        let _ = fs::remove_file(&current);
+
+        #[cfg(unix)]
+        if std::os::unix::fs::symlink(log_path, &current).is_ok() {
+            return Ok(None);
+        }
+        #[cfg(not(unix))]
+        let _ = log_path;
+
+        let file = File::create(&current).map_err(|e| e.to_string())?;
+        Ok(Some(BufWriter::new(file)))
+    }
+
+    /// Log file of a loop session
+    pub fn session_log_path(project_id: &uuid::Uuid, session_id: &str) -> Result<PathBuf, String> {
+        let project_dir = get_project_dir(project_id).map_err(|e| e.to_string())?;
+        Ok(project_dir.join("logs").join(format!("{}.log", session_id)))
+    }
+
+    /// Start a new log session, named after the loop session id
+    pub fn start_session(&mut self, session_id: &str) -> Result<PathBuf, String> {
+        if let Some(writer) = self.writer.take() {
+            writer.finish();
+        }
+        let project_dir = ensure_project_dir(&self.project_id).map_err(|e| e.to_string())?;
+        let logs_dir = project_dir.join("logs");
+        fs::create_dir_all(&logs_dir).map_err(|e| e.to_string())?;
+
+        let log_path = logs_dir.join(format!("{}.log", session_id));
+
+        let file = File::create(&log_path).map_err(|e| e.to_string())?;
+        let mirror = self.link_current_log(&log_path)?;
+        self.writer = Some(LogWriter::spawn(SessionFiles {
+            log: BufWriter::new(file),
+            mirror,
+            raw: None,
+        })?);
+        self.log_path = Some(log_path.clone());
+
+        // Write header
+        self.write_line("# Ralph Desktop Execution Log");
+        self.write_line(&format!("# Started: {}", Utc::now().to_rfc3339()));
+        self.write_line(&format!("# Project ID: {}", self.project_id));
+        self.write_line("");
+
+        Ok(log_path)
+    }
+
+    /// Write a log entry
+    pub fn write_entry(&mut self, iteration: u32, content: &str, is_stderr: bool) {
+        let prefix = if is_stderr { "ERR" } else { "OUT" };
+        let timestamp = Utc::now().format("%H:%M:%S").to_string();
+        let line = format!("[#{}] {} [{}] {}", iteration, timestamp, prefix, content);
+        self.write_line(&line);
+    }
+
+    /// Start capturing raw CLI output next to the current session log
+    pub fn enable_raw_capture(&mut self) -> Result<PathBuf, String> {
+        let log_path = self.log_path.as_ref().ok_or("No active log session")?;
+        let writer = self.writer.as_ref().ok_or("No active log session")?;
+        let raw_path = log_path.with_extension("raw.txt");
+        let file = File::create(&raw_path).map_err(|e| e.to_string())?;
+        writer.queue.push(Record::RawFile(BufWriter::new(file)));
+        Ok(raw_path)
+    }
+
+    /// Write a raw (pre-parsing) CLI output line
+    pub fn write_raw(&mut self, iteration: u32, line: &str, is_stderr: bool) {
+        if let Some(writer) = &self.writer {
+            let prefix = if is_stderr { "ERR" } else { "OUT" };
+            writer.queue.push(Record::Raw(format!(
+                "[#{}] [{}] {}",
+                iteration, prefix, line
+            )));
+        }
+    }
+
+    /// Write an iteration boundary marker
+    pub fn write_marker(&mut self, text: &str) {
+        self.write_line(&format!("# {}", text));
+    }
+
+    /// Queue a line for the writer
+    fn write_line(&mut self, line: &str) {
+        if let Some(writer) = &self.writer {
+            writer.queue.push(Record::Line(line.to_string()));
+        }
+    }
+
+    /// Wait until every line written so far is on disk
+    pub fn flush(&self) {
+        if let Some(writer) = &self.writer {
+            writer.flush();
+        }
+    }
+
+    /// End the log session
+    pub fn end_session(&mut self, status: &str) {
+        self.write_line("");
+        self.write_line(&format!("# Ended: {}", Utc::now().to_rfc3339()));
+        self.write_line(&format!("# Status: {}", status));
+
+        if let Some(writer) = self.writer.take() {
+            writer.finish();
+        }
+
+        if crypto::enabled() {
+            self.encrypt_finished_log();
+        }
+    }
+
+    /// Seal the finished session log and its raw capture; the running session
+    /// stays plaintext so it can be tailed, and `current.log` is dropped so no
+    /// copy is left behind
+    fn encrypt_finished_log(&self) {
+        if let Some(log_path) = &self.log_path {
+            let raw_path = log_path.with_extension("raw.txt");
+            let paths =
+                std::iter::once(log_path.clone()).chain(raw_path.exists().then_some(raw_path));
+            for path in paths {
+                if let Err(e) = crypto::convert_file(&path, true) {
+                    eprintln!("[Logs] Failed to encrypt {}: {}", path.display(), e);
+                    return;
+                }
+            }
+        }
+        if let Ok(current) = Self::current_log_path(&self.project_id) {
+            let _ = fs::remove_file(current);
+        }
+    }
+
+    /// Get the current log path
+    pub fn get_log_path(&self) -> Option<&PathBuf> {
+        self.log_path.as_ref()
+    }
+
+    /// Retrieve the content of the latest log session
+    pub fn get_latest_session_log(&self) -> Result<Vec<String>, String> {
+        let project_dir = get_project_dir(&self.project_id).map_err(|e| e.to_string())?;
+        let logs_dir = project_dir.join("logs");
+
+        if !logs_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries: Vec<_> = fs::read_dir(&logs_dir)
+            .map_err(|e| e.to_string())?
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
+            .filter(|e| e.file_name() != CURRENT_LOG)
+            .collect();
+
+        // Sort by name (timestamp) descending
+        entries.sort_by_key(|e| std::cmp::Reverse(e.file_name()));
+
+        if let Some(latest) = entries.first() {
+            let content = crypto::read_text(&latest.path()).map_err(|e| e.to_string())?;
+            return Ok(content.lines().map(|s| s.to_string()).collect());
+        }
+
+        Ok(Vec::new())
+    }
+}
+
+/// Session log with notes placed after their iteration's start marker
+pub fn render_transcript(log_lines: &[String], notes: &[SessionNote]) -> String {
+    let note_line = |note: &SessionNote| {
+        format!(
+            "> Note ({}): {}",
+            note.created_at.format("%Y-%m-%d %H:%M"),
+            note.text
+        )
+    };
+    let mut output = Vec::new();
+    let general: Vec<String> = notes
+        .iter()
+        .filter(|n| n.iteration.is_none())
+        .map(note_line)
+        .collect();
+    if !general.is_empty() {
+        output.push("# Session Notes".to_string());
+        output.extend(general);
+        output.push(String::new());
+    }
+
+    let mut placed = vec![false; notes.len()];
+    for line in log_lines {
+        output.push(line.clone());
+        let Some(iteration) = line
+            .strip_prefix("# Iteration ")
+            .and_then(|rest| rest.strip_suffix(" started"))
+            .and_then(|n| n.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        for (index, note) in notes.iter().enumerate() {
+            if note.iteration == Some(iteration) {
+                output.push(note_line(note));
+                placed[index] = true;
+            }
+        }
+    }
+
+    // Notes for iterations missing from the log still belong in the export
+    let orphaned: Vec<String> = notes
+        .iter()
+        .zip(&placed)
+        .filter(|(note, placed)| note.iteration.is_some() && !**placed)
+        .map(|(note, _)| {
+            format!(
+                "{} [iteration {}]",
+                note_line(note),
+                note.iteration.unwrap_or_default()
+            )
+        })
+        .collect();
+    if !orphaned.is_empty() {
+        output.push(String::new());
+        output.extend(orphaned);
+    }
+    output.join("\n") + "\n"
+}
+
+/// Clean up old logs based on retention policy
+pub fn cleanup_old_logs(project_id: &uuid::Uuid, retention_days: u32) -> Result<u32, String> {
+    let project_dir = get_project_dir(project_id).map_err(|e| e.to_string())?;
+    let logs_dir = project_dir.join("logs");
+
+    if !logs_dir.exists() {
+        return Ok(0);
+    }
+
+    let cutoff = Utc::now() - chrono::Duration::days(retention_days as i64);
+    let mut deleted_count = 0u32;
+
+    let entries = fs::read_dir(&logs_dir).map_err(|e| e.to_string())?;
+
+    for entry in entries.flatten() {
+        // Logs of pinned sessions are kept regardless of age
+        let name = entry.file_name().to_string_lossy().to_string();
+        let session_id = name.split('.').next().unwrap_or_default();
+        if crate::storage::load_session_pins(project_id, session_id).is_ok_and(|p| p.is_pinned()) {
+            continue;
+        }
+        if let Ok(metadata) = entry.metadata() {
+            if let Ok(modified) = metadata.modified() {
+                let modified_time: chrono::DateTime<Utc> = modified.into();
+                if modified_time < cutoff && fs::remove_file(entry.path()).is_ok() {
+                    deleted_count += 1;
+                }
+            }
+        }
+    }
+
+    Ok(deleted_count)
+}
+
+/// Clean up all project logs
+pub fn cleanup_all_logs(retention_days: u32) -> Result<u32, String> {
+    let data_dir = crate::storage::get_data_dir().map_err(|e| e.to_string())?;
+    let projects_dir = data_dir.join("projects");
+
+    if !projects_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut total_deleted = 0u32;
+
+    let entries = fs::read_dir(&projects_dir).map_err(|e| e.to_string())?;
+
+    for entry in entries.flatten() {
+        if entry.path().is_dir() {
+            if let Some(name) = entry.file_name().to_str() {
+                if let Ok(project_id) = uuid::Uuid::parse_str(name) {
+                    if let Ok(count) = cleanup_old_logs(&project_id, retention_days) {
+                        total_deleted += count;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(total_deleted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::SessionPins;
+    use crate::test_support::{lock_env, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[test]
+    fn current_log_follows_session_output() {
+        let _env_lock = lock_env();
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let project_id = uuid::Uuid::new_v4();
+        let mut manager = LogManager::new(project_id);
+        manager.start_session("2026-01-01T00-00-00").unwrap();
+        manager.write_entry(1, "hello from the agent", false);
+        manager.flush();
+
+        let current = LogManager::current_log_path(&project_id).unwrap();
+        let content = fs::read_to_string(current).unwrap();
+        assert!(content.contains("hello from the agent"));
+
+        let latest = manager.get_latest_session_log().unwrap();
+        assert!(latest.iter().any(|l| l.contains("hello from the agent")));
+        manager.end_session("completed");
+    }
+
+    #[test]
+    fn transcript_places_notes_after_iteration_markers() {
+        let note = |iteration: Option<u32>, text: &str| SessionNote {
+            iteration,
+            text: text.to_string(),
+            created_at: Utc::now(),
+        };
+        let lines: Vec<String> = [
+            "# Ralph Desktop Execution Log",
+            "# Iteration 1 started",
+            "[#1] 10:00:00 [OUT] first",
+            "# Iteration 2 started",
+            "[#2] 10:01:00 [OUT] second",
+        ]
+        .iter()
+        .map(|l| l.to_string())
+        .collect();
+        let notes = vec![
+            note(Some(2), "introduced the flaky test"),
+            note(None, "watching closely"),
+            note(Some(9), "never ran"),
+        ];
+
+        let transcript = render_transcript(&lines, &notes);
+        let rendered: Vec<&str> = transcript.lines().collect();
+        assert_eq!(rendered[0], "# Session Notes");
+        assert!(rendered[1].ends_with("watching closely"));
+        let marker = rendered
+            .iter()
+            .position(|l| *l == "# Iteration 2 started")
+            .unwrap();
+        assert!(rendered[marker + 1].ends_with("introduced the flaky test"));
+        assert!(rendered
+            .last()
+            .unwrap()
+            .ends_with("never ran [iteration 9]"));
+    }
+
+    #[test]
+    fn cleanup_keeps_pinned_sessions() {
+        let _env_lock = lock_env();
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let project_id = uuid::Uuid::new_v4();
+        for session in ["2026-01-01T00-00-00", "2026-01-02T00-00-00"] {
+            let mut manager = LogManager::new(project_id);
+            manager.start_session(session).unwrap();
+            manager.end_session("completed");
+        }
+        crate::storage::save_session_pins(
+            &project_id,
+            "2026-01-02T00-00-00",
+            &SessionPins {
+                pinned: false,
+                iterations: vec![3],
+            },
+        )
+        .unwrap();
+
+        // Zero-day retention prunes everything that is not pinned
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        cleanup_old_logs(&project_id, 0).unwrap();
+        assert!(
+            !LogManager::session_log_path(&project_id, "2026-01-01T00-00-00")
+                .unwrap()
+                .exists()
+        );
+        assert!(
+            LogManager::session_log_path(&project_id, "2026-01-02T00-00-00")
+                .unwrap()
+                .exists()
+        );
+    }
+
+    #[test]
+    fn full_queue_drops_and_counts_the_oldest_lines() {
+        let queue = LogQueue::new(2);
+        for line in ["one", "two", "three"] {
+            queue.push(Record::Line(line.to_string()));
+        }
+        queue.close();
+        let (batch, dropped) = queue.take_batch().unwrap();
+        let lines: Vec<String> = batch
+            .into_iter()
+            .filter_map(|record| match record {
+                Record::Line(line) => Some(line),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(lines, vec!["two", "three"]);
+        assert_eq!(dropped, 1);
+        assert!(queue.take_batch().is_none());
+    }
+
+    #[test]
+    fn raw_capture_writes_separate_file() {
+        let _env_lock = lock_env();
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let mut manager = LogManager::new(uuid::Uuid::new_v4());
+        manager.start_session("2026-01-01T00-00-00").unwrap();
+        let raw_path = manager.enable_raw_capture().unwrap();
+        manager.write_raw(2, r#"{"type":"item.delta"}"#, false);
+        manager.write_entry(2, "parsed", false);
+        manager.flush();
+
+        let raw = fs::read_to_string(&raw_path).unwrap();
+        assert!(raw.contains(r#"[#2] [OUT] {"type":"item.delta"}"#));
+        assert!(!raw.contains("parsed"));
+        let latest = manager.get_latest_session_log().unwrap();
+        assert!(!latest.iter().any(|l| l.contains("item.delta")));
+    }
+}
@@ -0,0 +1,2651 @@
+use crate::adapters::hide_console_window;
+use crate::adapters::{
+    get_adapter_at, output_with_prompt, CliAdapter, CommandOptions, LineType, TokenUsage,
+};
+use crate::engine::logs::LogManager;
+use crate::storage;
+use crate::storage::models::{
+    CliType, CliVersionRecord, CommitHygiene, CommitIdentity, CompletionMode, CompletionPayload,
+    CompletionStrategy, ContainerConfig, GlobalConfig, Highlight, HighlightRule, HighlightSeverity,
+    IterationArtifact, IterationOutcome, IterationRecord, IterationUsage, ProcessPriority,
+    ProjectState, PromptAnswer, PromptRevision, RemoteTarget, ScreenshotConfig, SessionHighlight,
+    SessionMetrics, SessionSnapshot, TaskConfig,
+};
+use chrono::{DateTime, Utc};
+use executor::{Execution, IterationRequest, OutputLine};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+use tokio::sync::Notify;
+
+pub mod ai_brainstorm;
+pub mod anomalies;
+pub mod artifacts;
+pub mod attachments;
+pub mod authorship;
+pub mod auto_answer;
+pub mod benchmark;
+pub mod carry_over;
+pub mod changelog;
+pub mod commit_hygiene;
+pub mod completion;
+pub mod console;
+pub mod container;
+pub mod context;
+pub mod cost;
+pub mod dependency_updates;
+pub mod env_profiles;
+pub mod executor;
+pub mod filters;
+pub mod health;
+pub mod highlights;
+pub mod hooks;
+pub mod hotspots;
+pub mod issue_triage;
+pub mod knowledge;
+pub mod logs;
+pub mod network;
+pub mod personas;
+pub mod plugins;
+pub mod ports;
+pub mod postmortem;
+pub mod priority;
+pub mod processes;
+pub mod prompt;
+pub mod questions;
+pub mod readme;
+pub mod remote;
+pub mod repeats;
+pub mod repo_summary;
+pub mod report;
+pub mod resources;
+pub mod review;
+pub mod scheduler;
+pub mod screenshot;
+pub mod secrets;
+pub mod snapshot;
+pub mod status_file;
+pub mod supervisor;
+pub mod targets;
+pub mod terminal;
+pub mod time_travel;
+pub mod transcribe;
+pub mod validation;
+pub mod verbosity;
+pub mod warmup;
+pub mod workdir;
+
+pub const CODEX_GIT_REPO_CHECK_REQUIRED: &str = "codex_git_repo_check_required";
+/// Error code returned when `start_loop` finds the project already running or starting
+pub const LOOP_ALREADY_RUNNING: &str = "loop_already_running";
+/// Error code persisted when the loop stopped because the project directory disappeared
+pub const PROJECT_PATH_MISSING: &str = "project_path_missing";
+
+/// Minimum time between progress redraws sent to the frontend
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Loop events sent to frontend
+#[allow(dead_code)]
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum LoopEvent {
+    #[serde(rename_all = "camelCase")]
+    IterationStart { project_id: String, iteration: u32 },
+    #[serde(rename_all = "camelCase")]
+    Output {
+        project_id: String,
+        iteration: u32,
+        content: String,
+        is_stderr: bool,
+        /// Set when a highlight rule matched the line
+        #[serde(skip_serializing_if = "Option::is_none")]
+        highlight: Option<Highlight>,
+    },
+    #[serde(rename_all = "camelCase")]
+    IterationEnd {
+        project_id: String,
+        iteration: u32,
+        stats: IterationStats,
+    },
+    #[serde(rename_all = "camelCase")]
+    Pausing { project_id: String, iteration: u32 },
+    #[serde(rename_all = "camelCase")]
+    Paused { project_id: String, iteration: u32 },
+    #[serde(rename_all = "camelCase")]
+    Resumed { project_id: String, iteration: u32 },
+    #[serde(rename_all = "camelCase")]
+    Completed {
+        project_id: String,
+        iteration: u32,
+        /// Set when the agent finished with `<done>{json}</done>`
+        #[serde(skip_serializing_if = "Option::is_none")]
+        payload: Option<CompletionPayload>,
+    },
+    #[serde(rename_all = "camelCase")]
+    MaxIterationsReached { project_id: String, iteration: u32 },
+    /// The run used up its wall-clock budget; `iteration` was the last to run
+    #[serde(rename_all = "camelCase")]
+    TimeBudgetReached { project_id: String, iteration: u32 },
+    #[serde(rename_all = "camelCase")]
+    Error {
+        project_id: String,
+        iteration: u32,
+        error: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    Stopped { project_id: String },
+    /// All loop slots are taken; the session starts once it reaches the front of the queue
+    #[serde(rename_all = "camelCase")]
+    Queued { project_id: String, position: usize },
+    /// The project directory is gone; the loop stopped until it is re-linked
+    #[serde(rename_all = "camelCase")]
+    PathMissing {
+        project_id: String,
+        iteration: u32,
+        path: String,
+    },
+    /// Output lines not streamed while the window was unfocused; they are in the session log
+    #[serde(rename_all = "camelCase")]
+    OutputSummary {
+        project_id: String,
+        iteration: u32,
+        lines: u32,
+        last_line: String,
+    },
+    /// A managed dev server's port is taken; `iteration` is 0 before the first one
+    #[serde(rename_all = "camelCase")]
+    PortConflict {
+        project_id: String,
+        iteration: u32,
+        port: u16,
+        pid: Option<u32>,
+        program: Option<String>,
+        message: String,
+    },
+    /// In-place redraw of the current output line (`\r` progress bars); not logged
+    #[serde(rename_all = "camelCase")]
+    Progress {
+        project_id: String,
+        iteration: u32,
+        content: String,
+    },
+    /// Tokens the CLI reported for the iteration so far; input includes cache reads
+    #[serde(rename_all = "camelCase")]
+    Usage {
+        project_id: String,
+        iteration: u32,
+        input_tokens: u64,
+        output_tokens: u64,
+        cache_read_tokens: u64,
+    },
+    /// The supervisor replaced the working prompt for the iterations that follow
+    #[serde(rename_all = "camelCase")]
+    PromptRevised {
+        project_id: String,
+        iteration: u32,
+        previous: String,
+        prompt: String,
+        reason: String,
+        message: String,
+    },
+    /// The CLI reports a different version than in the last successful session
+    #[serde(rename_all = "camelCase")]
+    CliVersionChanged {
+        project_id: String,
+        cli: CliType,
+        previous: String,
+        current: String,
+        message: String,
+    },
+}
+
+/// Summary of a finished iteration, sent with `IterationEnd`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IterationStats {
+    pub duration_ms: u64,
+    /// Lines read from the CLI, before filtering
+    pub stdout_lines: u32,
+    pub stderr_lines: u32,
+    /// Lines hidden by output filters
+    pub suppressed_lines: u64,
+    /// `None` when the process was killed or its status was unavailable
+    pub exit_code: Option<i32>,
+    /// Auto-commit created for the iteration, if any
+    pub commit_sha: Option<String>,
+    /// Spend the CLI reported for the iteration, if it reports any
+    pub cost_usd: Option<f64>,
+    /// Tokens the CLI reported for the iteration, if it reports any
+    pub input_tokens: Option<u64>,
+    pub output_tokens: Option<u64>,
+}
+
+/// Unparsed CLI output line, sent when raw debugging is enabled
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RawOutputEvent {
+    pub project_id: String,
+    pub iteration: u32,
+    pub line: String,
+    pub is_stderr: bool,
+}
+
+/// Receives what a loop emits; the desktop app forwards it to its windows
+pub trait EventSink: Send + Sync {
+    /// `isolated` is set for benchmark runs, shown apart from project loops
+    fn loop_event(&self, event: &LoopEvent, isolated: bool);
+    /// Unparsed CLI output, only sent when raw debugging is enabled
+    fn raw_output(&self, event: &RawOutputEvent);
+}
+
+/// Loop engine state
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopState {
+    Idle,
+    Running {
+        iteration: u32,
+    },
+    Pausing {
+        iteration: u32,
+    },
+    Paused {
+        iteration: u32,
+    },
+    Completed {
+        iteration: u32,
+    },
+    MaxIterationsReached {
+        iteration: u32,
+    },
+    TimeBudgetReached {
+        iteration: u32,
+    },
+    Failed {
+        iteration: u32,
+    },
+    /// The project directory disappeared; the loop stopped before `iteration`
+    PathMissing {
+        iteration: u32,
+    },
+}
+
+/// Settings for a single loop run, resolved from the task and global config
+#[derive(Debug, Clone)]
+pub struct LoopConfig {
+    pub cli_type: CliType,
+    /// Executable to run instead of the one found on PATH
+    pub cli_path: Option<String>,
+    pub prompt: String,
+    pub first_iteration_prompt: Option<String>,
+    pub subsequent_iteration_prompt: Option<String>,
+    pub max_iterations: u32,
+    /// When the run's wall-clock budget is used up, shared by its target sessions
+    pub run_deadline: Option<Instant>,
+    pub auto_commit: bool,
+    /// Files auto-commit leaves out
+    pub commit_hygiene: CommitHygiene,
+    /// Author and co-author trailer of auto-commits
+    pub commit_identity: CommitIdentity,
+    pub completion_signal: String,
+    /// `completion_signal` is a regex for the final message
+    pub completion_signal_regex: bool,
+    pub extra_completion_signals: Vec<String>,
+    pub completion_mode: CompletionMode,
+    pub completion_strategy: CompletionStrategy,
+    /// Command that must pass before a completion signal is accepted; not run for remote projects
+    pub completion_check: Option<String>,
+    /// Command run after every iteration whose failure output goes into the next prompt; not run for remote projects
+    pub validation_command: Option<String>,
+    /// Summarize each iteration's output into the next prompt; skipped for remote projects
+    pub carry_over_progress: bool,
+    /// Have a readonly CLI call review each auto-commit's diff first; skipped
+    /// for remote projects
+    pub review_gate: bool,
+    /// What the reviewer checks; `review::DEFAULT_RUBRIC` when unset
+    pub review_rubric: Option<String>,
+    /// Review the loop every this many iterations and revise its prompt when
+    /// stuck; skipped for remote projects
+    pub supervisor_interval: Option<u32>,
+    /// Script run before every iteration; not run for remote projects
+    pub pre_iteration_command: Option<String>,
+    /// Skip the iteration when the pre-iteration script fails
+    pub pre_iteration_abort_on_failure: bool,
+    pub iteration_timeout: Option<Duration>,
+    pub idle_timeout: Option<Duration>,
+    /// Kill and retry an iteration whose CLI prints nothing this long after starting
+    pub first_output_timeout: Option<Duration>,
+    /// Fail the loop after this many non-zero CLI exits in a row; `None` never fails
+    pub max_consecutive_failures: Option<u32>,
+    pub skip_git_repo_check: bool,
+    /// Policy text prepended to the prompt; `None` disables injection
+    pub auto_decide_policy: Option<String>,
+    /// Language agents must write in; `None` lets them follow the input
+    pub agent_language: Option<String>,
+    /// Absolute paths of images attached to the task
+    pub attachments: Vec<String>,
+    pub warmup_context: bool,
+    /// Token budget for repository context; `None` disables it
+    pub repo_context_budget: Option<usize>,
+    pub session_id: String,
+    /// Regexes for output lines hidden from events and logs (global, then project)
+    pub output_filters: Vec<String>,
+    /// Rules flagging notable output lines, first match wins
+    pub highlight_rules: Vec<HighlightRule>,
+    /// Replies typed in when the CLI stops on a matching prompt; empty disables answering
+    pub prompt_answers: Vec<PromptAnswer>,
+    /// Directory the agent runs in when it differs from the project (git) root
+    pub working_dir: Option<PathBuf>,
+    /// Globs relative to the working directory collected after each iteration
+    pub artifacts: Vec<String>,
+    /// Scheduling priority of a locally spawned CLI
+    pub priority: ProcessPriority,
+    /// Memory limit of a locally spawned CLI, in MB
+    pub memory_limit_mb: Option<u64>,
+    /// Run a local CLI in a pseudo-terminal the app can attach to
+    pub interactive_terminal: bool,
+    /// Run the CLI in this container instead of on the host
+    pub container: Option<ContainerConfig>,
+    /// Run the CLI and git over SSH on this host; takes precedence over `container`
+    pub remote: Option<RemoteTarget>,
+    /// Capture the running web app after each iteration; skipped for remote projects
+    pub screenshot: Option<ScreenshotConfig>,
+    /// Log and emit raw adapter output alongside parsed content
+    pub debug_raw_output: bool,
+    /// Keep the agent's tools offline and flag commands that reach for the network
+    pub network_blocked: bool,
+    /// Hosts of the configured model endpoints, exempt from the blocked network
+    pub api_hosts: Vec<String>,
+    /// Record the session's totals for the local monthly report
+    pub record_metrics: bool,
+    /// Add the session's summary and fixes to the local knowledge base
+    pub knowledge_base: bool,
+    /// Learnings from past sessions listed in the prompt
+    pub learnings: Vec<String>,
+    /// Write the session's outcome to `.ralph/status.json` in the project
+    pub write_status_file: bool,
+    /// Document the project in its README after a completed loop; skipped for remote projects
+    pub generate_readme: bool,
+    /// Show runs of identical output lines once, followed by a repeat count
+    pub collapse_repeats: bool,
+    /// Extra environment for the CLI, from the global config, the project's
+    /// workspace and the task's environment profile
+    pub env: Vec<(String, String)>,
+    /// Name of the environment profile merged into `env`
+    pub env_profile: Option<String>,
+    /// Enabled plugins, called for events, prompts and commits
+    pub plugins: plugins::Plugins,
+    /// Benchmark runs: events are sent as isolated and the project's execution
+    /// state is left alone
+    pub isolated: bool,
+}
+
+impl LoopConfig {
+    /// Loop settings shared by every session of a task, before per-target prompt,
+    /// working directory and session id are filled in
+    pub fn for_task(
+        task: &TaskConfig,
+        config: &GlobalConfig,
+        project_state: &ProjectState,
+        project_path: &Path,
+    ) -> Result<Self, String> {
+        let iteration_timeout = if config.iteration_timeout_ms == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(config.iteration_timeout_ms))
+        };
+        let idle_timeout = if config.idle_timeout_ms == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(config.idle_timeout_ms))
+        };
+
+        let auto_decide_policy = if task.auto_decide {
+            Some(config.auto_decide_policy.clone())
+        } else {
+            None
+        };
+
+        let attachments: Vec<String> = task
+            .attachments
+            .iter()
+            .map(|name| {
+                attachments::attachments_dir(project_path)
+                    .join(name)
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect();
+
+        let output_filters: Vec<String> = config
+            .output_filters
+            .iter()
+            .chain(&project_state.output_filters)
+            .cloned()
+            .collect();
+        filters::validate_patterns(&output_filters)?;
+
+        let env: Vec<(String, String)> =
+            env_profiles::task_env(config, task.env_profile.as_deref())?
+                .into_iter()
+                .collect();
+        let custom_args: &[String] = if task.cli == CliType::Custom {
+            &config.custom_cli.args
+        } else {
+            &[]
+        };
+        let api_hosts = network::configured_api_hosts(
+            config.api_backend.base_url.as_deref(),
+            env.iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+            custom_args.iter().map(String::as_str),
+        );
+
+        Ok(Self {
+            cli_type: task.cli,
+            cli_path: task.cli_path_override.clone(),
+            prompt: task.prompt.clone(),
+            first_iteration_prompt: task.first_iteration_prompt.clone(),
+            subsequent_iteration_prompt: task.subsequent_iteration_prompt.clone(),
+            max_iterations: task.max_iterations,
+            run_deadline: task
+                .max_run_duration_mins
+                .map(|mins| Instant::now() + Duration::from_secs(u64::from(mins) * 60)),
+            auto_commit: task.auto_commit,
+            commit_hygiene: config.commit_hygiene.clone(),
+            commit_identity: config.commit_identity.clone(),
+            completion_signal: task.completion_signal.clone(),
+            completion_signal_regex: task.completion_signal_regex,
+            extra_completion_signals: task.extra_completion_signals.clone(),
+            completion_mode: task.completion_mode,
+            completion_strategy: task.completion_strategy.clone(),
+            completion_check: None,
+            validation_command: task.validation_command.clone(),
+            carry_over_progress: task.carry_over_progress,
+            supervisor_interval: task.supervisor_interval.filter(|k| *k > 0),
+            review_gate: task.review_gate,
+            review_rubric: task.review_rubric.clone(),
+            pre_iteration_command: task.pre_iteration_command.clone(),
+            pre_iteration_abort_on_failure: task.pre_iteration_abort_on_failure,
+            iteration_timeout,
+            idle_timeout,
+            first_output_timeout: (config.first_output_timeout_ms > 0)
+                .then(|| Duration::from_millis(config.first_output_timeout_ms)),
+            max_consecutive_failures: (config.max_consecutive_failures > 0)
+                .then_some(config.max_consecutive_failures),
+            skip_git_repo_check: project_state.skip_git_repo_check,
+            auto_decide_policy,
+            agent_language: prompt::language_name(&config.agent_language)
+                .map(|_| config.agent_language.clone()),
+            attachments,
+            warmup_context: task.warmup_context,
+            repo_context_budget: task
+                .repo_context
+                .then_some(config.context_token_budget as usize),
+            session_id: String::new(),
+            output_filters,
+            highlight_rules: config.highlight_rules.clone(),
+            prompt_answers: if config.auto_answer_prompts {
+                config.prompt_answers.clone()
+            } else {
+                Vec::new()
+            },
+            working_dir: None,
+            artifacts: task.artifacts.clone(),
+            priority: task.priority,
+            memory_limit_mb: task.memory_limit_mb,
+            interactive_terminal: task.interactive_terminal,
+            container: project_state.container.clone(),
+            remote: project_state.remote.clone(),
+            screenshot: project_state.screenshot.clone(),
+            debug_raw_output: project_state.debug_raw_output,
+            network_blocked: project_state.network_blocked,
+            api_hosts,
+            record_metrics: config.local_analytics,
+            knowledge_base: config.knowledge_base,
+            learnings: Vec::new(),
+            write_status_file: task.write_status_file,
+            generate_readme: task.generate_readme,
+            collapse_repeats: config.collapse_repeated_lines,
+            env,
+            env_profile: task.env_profile.clone(),
+            plugins: plugins::Plugins::new(plugins::load_enabled(&config.enabled_plugins)),
+            isolated: false,
+        })
+    }
+}
+
+/// What earlier iterations left for the next prompt to address
+#[derive(Debug, Default)]
+struct IterationFeedback {
+    /// Likely secrets that blocked the last commit
+    blocked_secrets: Vec<secrets::Finding>,
+    /// Question the agent ended its last iteration on
+    unanswered: Option<questions::AskedQuestion>,
+    /// Why the completion check rejected a claimed completion
+    failed_check: Option<String>,
+    failed_validation: Option<validation::Failure>,
+    previous_progress: Option<String>,
+    /// Comments of a review that blocked the last commit
+    review_comments: Option<String>,
+}
+
+/// Ralph Loop execution engine
+pub struct LoopEngine {
+    project_id: String,
+    project_path: PathBuf,
+    config: LoopConfig,
+    pause_requested: Arc<AtomicBool>,
+    stop_requested: Arc<AtomicBool>,
+    resume_notify: Arc<Notify>,
+    log: Mutex<LogManager>,
+    total_cost_usd: Mutex<Option<f64>>,
+    iterations_run: AtomicU32,
+    commits_made: AtomicU32,
+    /// Iterations that ended with a question for the user
+    questions_asked: AtomicU32,
+    /// Task prompt, as last revised by the supervisor
+    working_prompt: Mutex<String>,
+    output_throttle: Mutex<verbosity::OutputThrottle>,
+    events: Arc<dyn EventSink>,
+}
+
+#[allow(dead_code)]
+impl LoopEngine {
+    pub fn new(
+        project_id: String,
+        project_path: PathBuf,
+        config: LoopConfig,
+        events: Arc<dyn EventSink>,
+    ) -> Self {
+        let log = LogManager::new(uuid::Uuid::parse_str(&project_id).unwrap_or_default());
+        let working_prompt = Mutex::new(config.prompt.clone());
+        Self {
+            project_id,
+            project_path,
+            config,
+            pause_requested: Arc::new(AtomicBool::new(false)),
+            stop_requested: Arc::new(AtomicBool::new(false)),
+            resume_notify: Arc::new(Notify::new()),
+            log: Mutex::new(log),
+            total_cost_usd: Mutex::new(None),
+            iterations_run: AtomicU32::new(0),
+            commits_made: AtomicU32::new(0),
+            questions_asked: AtomicU32::new(0),
+            working_prompt,
+            output_throttle: Mutex::new(verbosity::OutputThrottle::default()),
+            events,
+        }
+    }
+
+    /// Adapter for the loop's CLI, honoring the project's executable override
+    fn adapter(&self) -> Box<dyn CliAdapter> {
+        get_adapter_at(self.config.cli_type, self.config.cli_path.as_deref())
+    }
+
+    /// Directory the agent CLI runs in; git operations always use the project root
+    fn working_dir(&self) -> &Path {
+        self.config
+            .working_dir
+            .as_deref()
+            .unwrap_or(&self.project_path)
+    }
+
+    /// Prompt sent to the CLI for an iteration, with runtime-only injections applied
+    fn effective_prompt(
+        &self,
+        iteration: u32,
+        context: Option<&str>,
+        screenshot: Option<&str>,
+        feedback: &IterationFeedback,
+    ) -> String {
+        let base_prompt = self.working_prompt();
+        let rendered = prompt::render_iteration_prompt(
+            &prompt::strip_legacy_auto_decide(&base_prompt),
+            iteration,
+            self.config.first_iteration_prompt.as_deref(),
+            self.config.subsequent_iteration_prompt.as_deref(),
+        );
+        let rendered =
+            prompt::apply_previous_progress(&rendered, feedback.previous_progress.as_deref());
+        let rendered = prompt::apply_codebase_context(&rendered, context);
+        let rendered = prompt::apply_attachments(
+            &rendered,
+            &self.config.attachments,
+            self.adapter().supports_image_input(),
+        );
+        let rendered = prompt::apply_screenshot(&rendered, screenshot);
+        let rendered = prompt::apply_blocked_secrets(&rendered, &feedback.blocked_secrets);
+        let rendered = prompt::apply_unanswered_question(&rendered, feedback.unanswered.as_ref());
+        let rendered =
+            prompt::apply_failed_completion_check(&rendered, feedback.failed_check.as_deref());
+        let rendered =
+            prompt::apply_failed_validation(&rendered, feedback.failed_validation.as_ref());
+        let rendered =
+            prompt::apply_review_comments(&rendered, feedback.review_comments.as_deref());
+        let rendered = prompt::apply_learnings(&rendered, &self.config.learnings);
+        let rendered = prompt::apply_network_policy(&rendered, self.config.network_blocked);
+        let rendered = prompt::apply_language(&rendered, self.config.agent_language.as_deref());
+        prompt::apply_auto_decide_policy(&rendered, self.config.auto_decide_policy.as_deref())
+    }
+
+    fn working_prompt(&self) -> String {
+        self.working_prompt
+            .lock()
+            .map(|prompt| prompt.clone())
+            .unwrap_or_else(|_| self.config.prompt.clone())
+    }
+
+    /// Returns whether the iteration changed any file
+    async fn record_hotspots(&self, tracker: &mut hotspots::ChangeTracker) -> bool {
+        if tracker
+            .record_iteration(&self.project_path)
+            .await
+            .is_empty()
+        {
+            return false;
+        }
+        if let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) {
+            let _ =
+                storage::save_session_hotspots(&uuid, &self.config.session_id, &tracker.hotspots());
+        }
+        true
+    }
+
+    /// HEAD and `git status` of the working tree, only taken for strategies that
+    /// complete without a signal
+    async fn tree_state(&self) -> Option<(String, String)> {
+        if self.config.completion_strategy == CompletionStrategy::Signal {
+            return None;
+        }
+        let head = self
+            .run_git(&["rev-parse", "HEAD"])
+            .await
+            .unwrap_or_default();
+        let status = self
+            .run_git(&["status", "--porcelain", "--untracked-files=all"])
+            .await
+            .ok()?;
+        Some((head, status))
+    }
+
+    /// Add the iteration's spend to the loop total and the session's usage history
+    fn record_usage(
+        &self,
+        iteration: u32,
+        tokens: Option<TokenUsage>,
+        cost_usd: Option<f64>,
+        recorded: &mut Vec<IterationUsage>,
+    ) {
+        if let Some(cost) = cost_usd {
+            if let Ok(mut total) = self.total_cost_usd.lock() {
+                *total = Some(total.unwrap_or(0.0) + cost);
+            }
+        }
+        if tokens.is_none() && cost_usd.is_none() {
+            return;
+        }
+        let tokens = tokens.unwrap_or_default();
+        recorded.push(IterationUsage {
+            iteration,
+            cli: self.config.cli_type,
+            input_tokens: tokens.input_tokens,
+            output_tokens: tokens.output_tokens,
+            cache_read_tokens: tokens.cache_read_tokens,
+            cost_usd,
+        });
+        if let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) {
+            let _ = storage::save_session_usage(&uuid, &self.config.session_id, recorded);
+        }
+    }
+
+    /// Copy the iteration's artifacts into the session and record them in its manifest
+    async fn collect_artifacts(&self, iteration: u32, collected: &mut Vec<IterationArtifact>) {
+        // Files of remote projects live on the SSH host
+        if self.config.artifacts.is_empty() || self.config.remote.is_some() {
+            return;
+        }
+        let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) else {
+            return;
+        };
+        let Ok(dest) =
+            storage::get_iteration_artifacts_dir(&uuid, &self.config.session_id, iteration)
+        else {
+            return;
+        };
+        let root = self.working_dir().to_path_buf();
+        let patterns = self.config.artifacts.clone();
+        let artifacts = tokio::task::spawn_blocking(move || {
+            artifacts::collect(&root, &patterns, &dest, iteration)
+        })
+        .await
+        .unwrap_or_default();
+        if artifacts.is_empty() {
+            return;
+        }
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!("[artifacts] Collected {} files", artifacts.len()),
+            is_stderr: false,
+            highlight: None,
+        });
+        collected.extend(artifacts);
+        let _ = storage::save_session_artifacts(&uuid, &self.config.session_id, collected);
+    }
+
+    /// Check the managed dev server's port before starting it, reporting who holds it.
+    /// Returns `false` on a conflict.
+    async fn dev_server_port_free(&self, iteration: u32) -> bool {
+        let Some(config) = &self.config.screenshot else {
+            return true;
+        };
+        if config
+            .dev_command
+            .as_deref()
+            .is_none_or(|c| c.trim().is_empty())
+        {
+            return true;
+        }
+        let Some(port) = ports::local_port(&config.url) else {
+            return true;
+        };
+        let Some(conflict) = ports::check(port).await else {
+            return true;
+        };
+        self.emit_event(LoopEvent::PortConflict {
+            project_id: self.project_id.clone(),
+            iteration,
+            port,
+            pid: conflict.pid,
+            program: conflict.program.clone(),
+            message: conflict.message(),
+        });
+        false
+    }
+
+    /// Screenshot the running app into the iteration's artifacts, returning the
+    /// image path when it should be shown to the agent next iteration
+    async fn capture_screenshot(
+        &self,
+        iteration: u32,
+        collected: &mut Vec<IterationArtifact>,
+    ) -> Option<String> {
+        let config = self.config.screenshot.as_ref()?;
+        // A server the agent left running would answer in place of ours
+        if self.config.remote.is_some() || !self.dev_server_port_free(iteration).await {
+            return None;
+        }
+        let uuid = uuid::Uuid::parse_str(&self.project_id).ok()?;
+        let output =
+            storage::get_iteration_artifacts_dir(&uuid, &self.config.session_id, iteration)
+                .ok()?
+                .join(screenshot::SCREENSHOT_FILE);
+        if let Err(e) = screenshot::capture(config, self.working_dir(), &output).await {
+            self.emit_event(LoopEvent::Output {
+                project_id: self.project_id.clone(),
+                iteration,
+                content: format!("[screenshot] {}", e),
+                is_stderr: true,
+                highlight: None,
+            });
+            return None;
+        }
+        let stored_path = output.to_string_lossy().to_string();
+        collected.push(IterationArtifact {
+            iteration,
+            path: screenshot::SCREENSHOT_FILE.to_string(),
+            stored_path: stored_path.clone(),
+            size_bytes: std::fs::metadata(&output).map(|m| m.len()).unwrap_or(0),
+        });
+        let _ = storage::save_session_artifacts(&uuid, &self.config.session_id, collected);
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!("[screenshot] Captured {}", config.url),
+            is_stderr: false,
+            highlight: None,
+        });
+
+        // Containers cannot see the app data directory the image is stored in
+        let show = config.feed_back
+            && self.config.container.is_none()
+            && self.adapter().supports_image_input();
+        show.then_some(stored_path)
+    }
+
+    /// Persist the iteration's exit status to the project's execution state
+    fn record_exit_code(&self, iteration: u32, exit_code: Option<i32>) {
+        if self.config.isolated {
+            return;
+        }
+        let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) else {
+            return;
+        };
+        // Only these fields, so a status the loop commands wrote meanwhile is kept
+        let _ = storage::update_project_state(&uuid, |state| {
+            if let Some(ref mut exec) = state.execution {
+                exec.current_iteration = iteration;
+                exec.last_exit_code = exit_code;
+            }
+        });
+    }
+
+    /// Classify an output line, remembering the match for `list_highlights`
+    fn highlight(
+        &self,
+        highlighter: &highlights::Highlighter,
+        session_highlights: &mut Vec<SessionHighlight>,
+        iteration: u32,
+        line: u32,
+        content: &str,
+    ) -> Option<Highlight> {
+        let highlight = highlighter.classify(content)?;
+        if session_highlights.len() < highlights::MAX_SESSION_HIGHLIGHTS {
+            session_highlights.push(SessionHighlight {
+                iteration,
+                line,
+                label: highlight.label.clone(),
+                severity: highlight.severity,
+                content: content.to_string(),
+            });
+        }
+        Some(highlight)
+    }
+
+    /// Flag shell commands that reach for the network while the project blocks it
+    fn check_network_use(
+        &self,
+        commands: Vec<String>,
+        session_highlights: &mut Vec<SessionHighlight>,
+        iteration: u32,
+        line: u32,
+    ) {
+        if !self.config.network_blocked {
+            return;
+        }
+        for command in commands {
+            let Some(kind) = network::violation(&command) else {
+                continue;
+            };
+            let highlight = Highlight {
+                label: network::VIOLATION_LABEL.to_string(),
+                severity: HighlightSeverity::Error,
+            };
+            // Seen only once the CLI issued it; tools that ignore the proxy may already have run
+            let content = format!(
+                "[network] Detected network use ({}): {}",
+                kind,
+                command.trim()
+            );
+            if session_highlights.len() < highlights::MAX_SESSION_HIGHLIGHTS {
+                session_highlights.push(SessionHighlight {
+                    iteration,
+                    line,
+                    label: highlight.label.clone(),
+                    severity: highlight.severity,
+                    content: content.clone(),
+                });
+            }
+            self.emit_event(LoopEvent::Output {
+                project_id: self.project_id.clone(),
+                iteration,
+                content,
+                is_stderr: true,
+                highlight: Some(highlight),
+            });
+        }
+    }
+
+    /// Reply to a prompt the CLI stopped on, recording the reply as a highlight.
+    /// Returns whether a reply was typed in.
+    async fn answer_prompt(
+        &self,
+        execution: &mut dyn Execution,
+        answerer: &mut auto_answer::PromptAnswerer,
+        line: &str,
+        session_highlights: &mut Vec<SessionHighlight>,
+        iteration: u32,
+        output_line: u32,
+    ) -> bool {
+        let Some((prompt, reply)) = answerer.answer(line) else {
+            return false;
+        };
+        let shown = if reply.is_empty() {
+            "Enter".to_string()
+        } else {
+            format!("{:?}", reply)
+        };
+        let result = execution.answer(&reply).await;
+        let (content, severity) = match &result {
+            Ok(()) => (
+                format!("[auto-answer] Replied {} to: {}", shown, prompt),
+                HighlightSeverity::Warning,
+            ),
+            Err(e) => (
+                format!(
+                    "[auto-answer] Could not reply {} to: {} ({})",
+                    shown, prompt, e
+                ),
+                HighlightSeverity::Error,
+            ),
+        };
+        let highlight = Highlight {
+            label: auto_answer::ANSWER_LABEL.to_string(),
+            severity,
+        };
+        if session_highlights.len() < highlights::MAX_SESSION_HIGHLIGHTS {
+            session_highlights.push(SessionHighlight {
+                iteration,
+                line: output_line,
+                label: highlight.label.clone(),
+                severity,
+                content: content.clone(),
+            });
+        }
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content,
+            is_stderr: result.is_err(),
+            highlight: Some(highlight),
+        });
+        result.is_ok()
+    }
+
+    /// Note filtered lines in the transcript so nothing disappears silently
+    fn report_suppressed(&self, filter: &filters::OutputFilter, iteration: u32, before: u64) {
+        let suppressed = filter.total() - before;
+        if suppressed == 0 {
+            return;
+        }
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!("[filter] Suppressed {} output lines", suppressed),
+            is_stderr: false,
+            highlight: None,
+        });
+        if let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) {
+            let _ =
+                storage::save_suppressed_counts(&uuid, &self.config.session_id, &filter.counts());
+        }
+    }
+
+    /// Combine the warm-up map and repository context injected into every iteration
+    async fn build_context(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(map) = self.warm_up().await {
+            parts.push(map);
+        }
+        if let Some(budget) = self.config.repo_context_budget {
+            let repo =
+                context::build_repo_context(self.working_dir(), &self.config.prompt, budget).await;
+            if !repo.is_empty() {
+                parts.push(repo);
+            }
+        }
+        if parts.is_empty() {
+            None
+        } else {
+            Some(parts.join("\n\n"))
+        }
+    }
+
+    /// Build the codebase map before iteration 1; failures fall back to an existing map
+    async fn warm_up(&self) -> Option<String> {
+        if !self.config.warmup_context {
+            return None;
+        }
+
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration: 0,
+            content: format!("[warm-up] Mapping codebase into {}", warmup::CONTEXT_FILE),
+            is_stderr: false,
+            highlight: None,
+        });
+
+        match warmup::run_warmup(
+            self.config.cli_type,
+            self.working_dir(),
+            self.config.skip_git_repo_check,
+        )
+        .await
+        {
+            Ok(context) => Some(context),
+            Err(err) => {
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration: 0,
+                    content: format!("[warm-up] {}", err),
+                    is_stderr: true,
+                    highlight: None,
+                });
+                warmup::load_context(self.working_dir())
+            }
+        }
+    }
+
+    fn is_codex_git_repo_check_error(&self, line: &str) -> bool {
+        self.config.cli_type == CliType::Codex
+            && line.contains("Not inside a trusted directory")
+            && line.contains("skip-git-repo-check")
+    }
+
+    fn emit_event(&self, event: LoopEvent) {
+        self.log_event(&event);
+        let Ok(mut throttle) = self.output_throttle.lock() else {
+            return self.send_event(&event);
+        };
+        // Highlighted lines are worth streaming even in the background
+        if let LoopEvent::Output {
+            iteration,
+            content,
+            highlight: None,
+            ..
+        } = &event
+        {
+            if !verbosity::window_focused() {
+                if let Some(held) = throttle.hold(*iteration, content, Instant::now()) {
+                    self.send_summary(held);
+                }
+                return;
+            }
+        }
+        // Report held lines first so the summary lands before what followed them
+        if let Some(held) = throttle.flush() {
+            self.send_summary(held);
+        }
+        self.send_event(&event);
+    }
+
+    fn send_summary(&self, held: verbosity::HeldOutput) {
+        self.send_event(&LoopEvent::OutputSummary {
+            project_id: self.project_id.clone(),
+            iteration: held.iteration,
+            lines: held.lines,
+            last_line: held.last_line,
+        });
+    }
+
+    fn send_event(&self, event: &LoopEvent) {
+        self.events.loop_event(event, self.config.isolated);
+        self.config.plugins.notify(event);
+    }
+
+    /// What plugins are told about the iteration they are called for
+    fn plugin_context(&self, iteration: u32) -> serde_json::Value {
+        serde_json::json!({
+            "projectId": self.project_id,
+            "sessionId": self.config.session_id,
+            "iteration": iteration,
+            "projectPath": self.project_path,
+            "workingDir": self.working_dir(),
+        })
+    }
+
+    fn report_plugins(&self, iteration: u32, notes: Vec<String>) {
+        for note in notes {
+            self.emit_event(LoopEvent::Output {
+                project_id: self.project_id.clone(),
+                iteration,
+                content: format!("[plugin] {}", note),
+                is_stderr: false,
+                highlight: None,
+            });
+        }
+    }
+
+    fn emit_raw(&self, iteration: u32, line: &str, is_stderr: bool) {
+        if !self.config.debug_raw_output {
+            return;
+        }
+        if let Ok(mut log) = self.log.lock() {
+            log.write_raw(iteration, line, is_stderr);
+        }
+        self.events.raw_output(&RawOutputEvent {
+            project_id: self.project_id.clone(),
+            iteration,
+            line: line.to_string(),
+            is_stderr,
+        });
+    }
+
+    /// Mirror streamed output into the session log so it can be tailed from a terminal
+    fn log_event(&self, event: &LoopEvent) {
+        let Ok(mut log) = self.log.lock() else {
+            return;
+        };
+        match event {
+            LoopEvent::IterationStart { iteration, .. } => {
+                log.write_marker(&format!("Iteration {} started", iteration));
+            }
+            LoopEvent::IterationEnd {
+                iteration, stats, ..
+            } => {
+                let exit_code = stats
+                    .exit_code
+                    .map_or_else(|| "none".to_string(), |code| code.to_string());
+                log.write_marker(&format!(
+                    "Iteration {} finished in {} ms (exit code {})",
+                    iteration, stats.duration_ms, exit_code
+                ));
+            }
+            LoopEvent::Output {
+                iteration,
+                content,
+                is_stderr,
+                ..
+            } if !content.is_empty() => log.write_entry(*iteration, content, *is_stderr),
+            LoopEvent::Error {
+                iteration, error, ..
+            } => log.write_entry(*iteration, error, true),
+            LoopEvent::PortConflict {
+                iteration, message, ..
+            } => log.write_entry(*iteration, message, true),
+            LoopEvent::PathMissing { path, .. } => {
+                log.write_marker(&format!("Project directory not found: {}", path));
+            }
+            LoopEvent::CliVersionChanged { message, .. } => log.write_marker(message),
+            LoopEvent::PromptRevised { message, .. } => log.write_marker(message),
+            LoopEvent::TimeBudgetReached { iteration, .. } => {
+                log.write_marker(&format!(
+                    "Time budget used up after iteration {}",
+                    iteration
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    /// Commit the iteration's changes when auto-commit is on, returning the new commit SHA.
+    /// Likely secrets block the commit and are left in `blocked_secrets` for the next prompt,
+    /// as are the comments of a review that blocks it in `review_comments`.
+    async fn commit_iteration_if_needed(
+        &self,
+        iteration: u32,
+        blocked_secrets: &mut Vec<secrets::Finding>,
+        review_comments: &mut Option<String>,
+    ) -> Result<Option<String>, String> {
+        blocked_secrets.clear();
+        *review_comments = None;
+        if !self.config.auto_commit {
+            return Ok(None);
+        }
+
+        if !self.is_git_repo().await? {
+            return Ok(None);
+        }
+
+        let status = self
+            .run_git(&["status", "--porcelain", "-z", "--untracked-files=all"])
+            .await?;
+        let changed = commit_hygiene::parse_status(&status);
+        if changed.is_empty() {
+            return Ok(None);
+        }
+        // Sizes of remote files would need a round trip each; only the globs apply there
+        let root = self.project_path.clone();
+        let local = self.config.remote.is_none();
+        let skipped = commit_hygiene::check(&self.config.commit_hygiene, &changed, |path| {
+            if !local {
+                return None;
+            }
+            std::fs::metadata(root.join(path)).ok().map(|m| m.len())
+        });
+        if !skipped.is_empty() {
+            self.emit_event(LoopEvent::Output {
+                project_id: self.project_id.clone(),
+                iteration,
+                content: format!("[auto-commit] {}", commit_hygiene::summary(&skipped)),
+                is_stderr: false,
+                highlight: None,
+            });
+            if skipped.len() == changed.len() {
+                return Ok(None);
+            }
+        }
+
+        let pathspecs = commit_hygiene::add_pathspecs(&skipped);
+        let mut add_args = vec!["add", "-A", "--"];
+        add_args.extend(pathspecs.iter().map(String::as_str));
+        self.run_git(&add_args).await?;
+
+        // Scanned before the diff is handed to the CLI for a commit message
+        if self.config.commit_hygiene.scan_secrets {
+            let staged = self
+                .run_git(&["diff", "--cached", "-U0", "--no-color", "--no-ext-diff"])
+                .await?;
+            let findings = secrets::scan_diff(&staged);
+            if !findings.is_empty() {
+                // The changes stay in the working tree for the agent to clean up
+                let _ = self.run_git(&["reset", "-q"]).await;
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: format!("[auto-commit] {}", secrets::summary(&findings)),
+                    is_stderr: true,
+                    highlight: None,
+                });
+                *blocked_secrets = findings;
+                return Ok(None);
+            }
+        }
+
+        if !self.config.plugins.is_empty() {
+            let staged = self.run_git(&["diff", "--cached", "--name-only"]).await?;
+            let files: Vec<String> = staged.lines().map(str::to_string).collect();
+            let (blocked, notes) = self
+                .config
+                .plugins
+                .check_commit(&self.plugin_context(iteration), &files)
+                .await;
+            self.report_plugins(iteration, notes);
+            if let Some(blocked) = blocked {
+                let _ = self.run_git(&["reset", "-q"]).await;
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: format!("[auto-commit] {}", blocked),
+                    is_stderr: true,
+                    highlight: None,
+                });
+                return Ok(None);
+            }
+        }
+
+        match self.review_staged_changes().await {
+            review::Outcome::Approved => {}
+            review::Outcome::Blocked(comments) => {
+                let _ = self.run_git(&["reset", "-q"]).await;
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: format!(
+                        "[review] The reviewer flagged blockers; the changes stay uncommitted:\n{}",
+                        comments
+                    ),
+                    is_stderr: true,
+                    highlight: None,
+                });
+                *review_comments = Some(comments);
+                return Ok(None);
+            }
+            review::Outcome::Failed(reason) => {
+                // Left staged, so the next iteration's review covers them again
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: format!(
+                        "[review] Could not review the changes; they stay staged and uncommitted: {}",
+                        reason
+                    ),
+                    is_stderr: true,
+                    highlight: None,
+                });
+                return Ok(None);
+            }
+        }
+
+        // Only what is staged, so files hygiene kept out never reach the CLI
+        let diff_stat = self
+            .run_git(&["diff", "--cached", "--stat"])
+            .await
+            .unwrap_or_default();
+        let diff_full = self
+            .run_git(&["diff", "--cached"])
+            .await
+            .unwrap_or_default();
+        let diff = Self::truncate_for_prompt(&diff_full, 4000);
+        let message = match self
+            .generate_commit_message(iteration, &diff_stat, &diff)
+            .await
+        {
+            Ok(msg) => msg,
+            Err(_) => format!("ralph: iteration {}", iteration),
+        };
+        let message = Self::normalize_commit_message(&message, iteration);
+
+        let commit_args = authorship::commit_args(
+            &self.config.commit_identity,
+            self.config.cli_type,
+            self.adapter().name(),
+            &message,
+        );
+        let commit_args: Vec<&str> = commit_args.iter().map(String::as_str).collect();
+        let _ = self.run_git(&commit_args).await?;
+        let sha = self.run_git(&["rev-parse", "HEAD"]).await?;
+        Ok(Some(sha.trim().to_string()))
+    }
+
+    /// Review the staged changes; approved when the review gate is off
+    async fn review_staged_changes(&self) -> review::Outcome {
+        // The reviewer runs locally, where remote projects have no directory
+        if !self.config.review_gate || self.config.remote.is_some() {
+            return review::Outcome::Approved;
+        }
+        let diff = match self
+            .run_git(&["diff", "--cached", "--no-color", "--no-ext-diff"])
+            .await
+        {
+            Ok(diff) => diff,
+            Err(e) => return review::Outcome::Failed(e),
+        };
+        let prompt = review::review_prompt(self.config.review_rubric.as_deref(), &diff);
+        let adapter = self.adapter();
+        let options = CommandOptions {
+            skip_git_repo_check: self.config.skip_git_repo_check,
+            ..Default::default()
+        };
+        let mut cmd = adapter.build_readonly_command(&prompt, &self.project_path, options);
+        cmd.kill_on_drop(true);
+        let run = match tokio::time::timeout(
+            review::REVIEW_TIMEOUT,
+            output_with_prompt(adapter.as_ref(), cmd, &prompt),
+        )
+        .await
+        {
+            Ok(Ok(output)) if output.status.success() => {
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+            Ok(Ok(output)) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(format!("timed out after {:?}", review::REVIEW_TIMEOUT)),
+        };
+        review::outcome(run)
+    }
+
+    async fn generate_commit_message(
+        &self,
+        iteration: u32,
+        diff_stat: &str,
+        diff: &str,
+    ) -> Result<String, String> {
+        let prompt = format!(
+            "Generate a concise git commit message for iteration {iteration}.
+Rules:
+- Output only the commit message (single line).
+- Max 72 characters.
+- Use imperative mood.
+
+Diff summary:
+{diff_stat}
+
+Diff:
+{diff}
+"
+        );
+
+        let adapter = self.adapter();
+        let options = CommandOptions {
+            skip_git_repo_check: self.config.skip_git_repo_check,
+            ..Default::default()
+        };
+        let cmd = adapter.build_readonly_command(&prompt, &self.project_path, options);
+        let output = output_with_prompt(adapter.as_ref(), cmd, &prompt).await?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!(
+                "Commit message generation failed: {}",
+                stderr.trim()
+            ));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.trim().to_string())
+    }
+
+    /// Summary of the iteration's assistant output for the next prompt, when
+    /// carry-over is on; a failed summary is reported and left out
+    async fn summarize_progress(&self, iteration: u32, assistant_text: &str) -> Option<String> {
+        // The summarizer runs locally, where remote projects have no directory
+        if !self.config.carry_over_progress || self.config.remote.is_some() {
+            return None;
+        }
+        let prompt = carry_over::summary_prompt(iteration, assistant_text)?;
+        let adapter = self.adapter();
+        let options = CommandOptions {
+            skip_git_repo_check: self.config.skip_git_repo_check,
+            ..Default::default()
+        };
+        let mut cmd = adapter.build_readonly_command(&prompt, &self.project_path, options);
+        cmd.kill_on_drop(true);
+        let failure = match tokio::time::timeout(
+            carry_over::SUMMARY_TIMEOUT,
+            output_with_prompt(adapter.as_ref(), cmd, &prompt),
+        )
+        .await
+        {
+            Ok(Ok(output)) if output.status.success() => {
+                return carry_over::bound_summary(&String::from_utf8_lossy(&output.stdout));
+            }
+            Ok(Ok(output)) => String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            Ok(Err(err)) => err,
+            Err(_) => format!("timed out after {:?}", carry_over::SUMMARY_TIMEOUT),
+        };
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!(
+                "[carry-over] Could not summarize the iteration: {}",
+                failure
+            ),
+            is_stderr: true,
+            highlight: None,
+        });
+        None
+    }
+
+    /// Let a readonly CLI call review the iterations in `outputs` and adopt the
+    /// prompt it revises, if any
+    async fn supervise(&self, iteration: u32, outputs: &[(u32, String)]) {
+        if self.config.remote.is_some() {
+            return;
+        }
+        let count = outputs.len().max(1).to_string();
+        let mut diff = self
+            .run_git(&[
+                "log",
+                "-p",
+                "--no-color",
+                "--format=commit %h %s",
+                "-n",
+                &count,
+            ])
+            .await
+            .unwrap_or_default();
+        diff.push_str(
+            &self
+                .run_git(&["diff", "HEAD", "--no-color"])
+                .await
+                .unwrap_or_default(),
+        );
+        let current = self.working_prompt();
+        let prompt = supervisor::review_prompt(&current, iteration, &diff, outputs);
+        let adapter = self.adapter();
+        let options = CommandOptions {
+            skip_git_repo_check: self.config.skip_git_repo_check,
+            ..Default::default()
+        };
+        let mut cmd = adapter.build_readonly_command(&prompt, &self.project_path, options);
+        cmd.kill_on_drop(true);
+        let answer = match tokio::time::timeout(
+            supervisor::REVIEW_TIMEOUT,
+            output_with_prompt(adapter.as_ref(), cmd, &prompt),
+        )
+        .await
+        {
+            Ok(Ok(output)) if output.status.success() => {
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+            Ok(Ok(output)) => Err(String::from_utf8_lossy(&output.stderr).trim().to_string()),
+            Ok(Err(err)) => Err(err),
+            Err(_) => Err(format!("timed out after {:?}", supervisor::REVIEW_TIMEOUT)),
+        };
+        let revision = match answer {
+            Ok(answer) => supervisor::parse_review(&answer, &current),
+            Err(failure) => {
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: format!("[supervisor] Could not review the loop: {}", failure),
+                    is_stderr: true,
+                    highlight: None,
+                });
+                None
+            }
+        };
+        let Some(revision) = revision else {
+            return;
+        };
+        if let Ok(mut working) = self.working_prompt.lock() {
+            *working = revision.prompt.clone();
+        }
+        if let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) {
+            let record = PromptRevision {
+                iteration,
+                revised_at: Utc::now(),
+                previous: current.clone(),
+                prompt: revision.prompt.clone(),
+                reason: revision.reason.clone(),
+            };
+            let _ = storage::append_prompt_revision(&uuid, &self.config.session_id, &record);
+        }
+        self.emit_event(LoopEvent::PromptRevised {
+            project_id: self.project_id.clone(),
+            iteration,
+            message: format!(
+                "Supervisor revised the prompt after iteration {}: {}",
+                iteration, revision.reason
+            ),
+            previous: current,
+            prompt: revision.prompt,
+            reason: revision.reason,
+        });
+    }
+
+    /// `git -C <project> <args>`, on the remote host for SSH projects
+    fn git_command(&self, args: &[&str]) -> Command {
+        if let Some(target) = &self.config.remote {
+            return remote::ssh_command(target, &remote::git_script(target, args));
+        }
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(&self.project_path).args(args);
+        hide_console_window(&mut cmd);
+        cmd
+    }
+
+    async fn run_git(&self, args: &[&str]) -> Result<String, String> {
+        let mut cmd = self.git_command(args);
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run git: {e}"))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("git {} failed: {}", args.join(" "), stderr.trim()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    async fn is_git_repo(&self) -> Result<bool, String> {
+        let mut cmd = self.git_command(&["rev-parse", "--is-inside-work-tree"]);
+        let output = cmd
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run git: {e}"))?;
+
+        if !output.status.success() {
+            return Ok(false);
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.trim() == "true")
+    }
+
+    fn normalize_commit_message(raw: &str, iteration: u32) -> String {
+        let mut line = raw
+            .lines()
+            .map(|l| l.trim())
+            .find(|l| !l.is_empty())
+            .unwrap_or("")
+            .trim_matches('`')
+            .trim_matches('"')
+            .trim_matches('\'')
+            .to_string();
+        if line.is_empty() {
+            line = format!("ralph: iteration {}", iteration);
+        }
+        if line.chars().count() > 72 {
+            line = line.chars().take(72).collect();
+        }
+        line
+    }
+
+    fn truncate_for_prompt(input: &str, max_chars: usize) -> String {
+        if input.chars().count() <= max_chars {
+            return input.to_string();
+        }
+        let mut truncated: String = input.chars().take(max_chars).collect();
+        truncated.push_str("\n... (truncated) ...");
+        truncated
+    }
+
+    pub async fn start(&self) -> Result<LoopState, String> {
+        if let Ok(mut log) = self.log.lock() {
+            if log.start_session(&self.config.session_id).is_ok() && self.config.debug_raw_output {
+                let _ = log.enable_raw_capture();
+            }
+        }
+
+        self.record_snapshot().await;
+        let started_at = Utc::now();
+        let result = self.run().await;
+        if matches!(result, Ok(LoopState::Completed { .. })) {
+            self.update_readme().await;
+        }
+
+        let status = match &result {
+            Ok(LoopState::Completed { .. }) => "completed",
+            Ok(LoopState::MaxIterationsReached { .. }) => "max_iterations_reached",
+            Ok(LoopState::TimeBudgetReached { .. }) => "time_budget_reached",
+            Ok(LoopState::Failed { .. }) => "failed",
+            Ok(LoopState::PathMissing { .. }) => "path_missing",
+            Ok(LoopState::Idle) => "stopped",
+            Ok(_) => "ended",
+            Err(_) => "error",
+        };
+        if let Ok(mut log) = self.log.lock() {
+            log.end_session(status);
+        }
+        if status == "completed" {
+            self.record_good_cli_version();
+        }
+        self.record_metrics(started_at, status);
+        self.record_knowledge().await;
+        self.write_status_file(started_at, status);
+        result
+    }
+
+    /// Report how often the previous output line repeated, counting it as an output line
+    fn emit_repeats(
+        &self,
+        iteration: u32,
+        summary: Option<repeats::RepeatSummary>,
+        output_lines: &mut u32,
+    ) {
+        let Some(summary) = summary else {
+            return;
+        };
+        *output_lines += 1;
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: summary.message(),
+            is_stderr: summary.is_stderr,
+            highlight: None,
+        });
+    }
+
+    /// Save what this session runs with, so results can be traced to versions later
+    async fn record_snapshot(&self) {
+        if self.config.isolated {
+            return;
+        }
+        let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) else {
+            return;
+        };
+        let cli_version = self.adapter().version().await;
+        let last_good = storage::load_last_good_cli_version(&uuid).ok().flatten();
+        let previous_cli_version = snapshot::version_change(
+            last_good.as_ref(),
+            self.config.cli_type,
+            cli_version.as_deref(),
+        );
+        if let (Some(previous), Some(current)) = (&previous_cli_version, &cli_version) {
+            self.emit_event(LoopEvent::CliVersionChanged {
+                project_id: self.project_id.clone(),
+                cli: self.config.cli_type,
+                previous: previous.clone(),
+                current: current.clone(),
+                message: format!(
+                    "CLI version changed since the last successful session: {} -> {}",
+                    previous, current
+                ),
+            });
+        }
+        let model = (self.config.cli_type == CliType::Api)
+            .then(|| storage::load_config().ok().map(|c| c.api_backend.model))
+            .flatten();
+        let git_head = self
+            .run_git(&["rev-parse", "HEAD"])
+            .await
+            .ok()
+            .map(|sha| sha.trim().to_string())
+            .filter(|sha| !sha.is_empty());
+        let _ = storage::save_session_snapshot(
+            &uuid,
+            &self.config.session_id,
+            &SessionSnapshot {
+                captured_at: Utc::now(),
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                cli: self.config.cli_type,
+                cli_version,
+                model,
+                git_head,
+                prompt_sha256: snapshot::prompt_hash(&self.config),
+                settings: snapshot::settings(&self.config),
+                previous_cli_version,
+            },
+        );
+    }
+
+    /// Remember the CLI version of a successful session for later version checks
+    fn record_good_cli_version(&self) {
+        if self.config.isolated {
+            return;
+        }
+        let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) else {
+            return;
+        };
+        let Some(version) = storage::load_session_snapshot(&uuid, &self.config.session_id)
+            .ok()
+            .flatten()
+            .and_then(|snapshot| snapshot.cli_version)
+        else {
+            return;
+        };
+        let _ = storage::save_last_good_cli_version(
+            &uuid,
+            &CliVersionRecord {
+                cli: self.config.cli_type,
+                version,
+                session_id: self.config.session_id.clone(),
+                recorded_at: Utc::now(),
+            },
+        );
+    }
+
+    /// Append the finished session to the local metrics when the user opted in
+    fn record_metrics(&self, started_at: DateTime<Utc>, status: &str) {
+        if !self.config.record_metrics || self.config.isolated {
+            return;
+        }
+        let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) else {
+            return;
+        };
+        let usage = storage::load_session_usage(&uuid, &self.config.session_id).unwrap_or_default();
+        let _ = storage::append_session_metrics(&SessionMetrics {
+            project_id: uuid,
+            session_id: self.config.session_id.clone(),
+            cli: self.config.cli_type,
+            started_at,
+            ended_at: Utc::now(),
+            status: status.to_string(),
+            iterations: self.iterations_run.load(Ordering::SeqCst),
+            commits: self.commits_made.load(Ordering::SeqCst),
+            questions_asked: self.questions_asked.load(Ordering::SeqCst),
+            input_tokens: usage.iter().map(|u| u.input_tokens).sum(),
+            output_tokens: usage.iter().map(|u| u.output_tokens).sum(),
+            cost_usd: self.total_cost_usd(),
+        });
+    }
+
+    /// Add the session's summary and committed fixes to the knowledge base when the user opted in
+    async fn record_knowledge(&self) {
+        if !self.config.knowledge_base || self.config.isolated {
+            return;
+        }
+        let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) else {
+            return;
+        };
+        let session_id = &self.config.session_id;
+        let summary = storage::load_session_completion(&uuid, session_id)
+            .ok()
+            .flatten()
+            .and_then(|payload| payload.summary);
+        let snapshot = storage::load_session_snapshot(&uuid, session_id)
+            .ok()
+            .flatten();
+        // The history of remote projects lives on the SSH host
+        let fixes = match snapshot {
+            Some(snapshot) if self.config.remote.is_none() => {
+                changelog::commit_subjects(&self.project_path, snapshot.git_head.as_deref(), "HEAD")
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|subject| changelog::is_fix(subject))
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        let entries = knowledge::session_entries(uuid, session_id, summary.as_deref(), &fixes);
+        if !entries.is_empty() {
+            let _ = knowledge::record(entries);
+        }
+    }
+
+    /// Have the CLI document what was built in the README, committed apart from
+    /// the iterations when auto-commit is on
+    async fn update_readme(&self) {
+        if !self.config.generate_readme || self.config.isolated || self.config.remote.is_some() {
+            return;
+        }
+        let iteration = self.iterations_run.load(Ordering::SeqCst);
+        let report = |content: String, is_stderr: bool| {
+            self.emit_event(LoopEvent::Output {
+                project_id: self.project_id.clone(),
+                iteration,
+                content: format!("[readme] {}", content),
+                is_stderr,
+                highlight: None,
+            });
+        };
+        report("Writing the README".to_string(), false);
+        let changed = readme::generate(
+            self.config.cli_type,
+            self.working_dir(),
+            &self.config.prompt,
+            self.config.skip_git_repo_check,
+        )
+        .await;
+        match changed {
+            Ok(false) => report("README is already up to date".to_string(), false),
+            Ok(true) => match self.commit_readme().await {
+                Ok(Some(sha)) => {
+                    report(format!("Updated and committed the README ({})", sha), false)
+                }
+                Ok(None) => report("Updated the README".to_string(), false),
+                Err(e) => report(
+                    format!("Updated the README but could not commit it: {}", e),
+                    true,
+                ),
+            },
+            Err(e) => report(e, true),
+        }
+    }
+
+    /// Commit only the README, leaving anything else in the tree as it is
+    async fn commit_readme(&self) -> Result<Option<String>, String> {
+        if !self.config.auto_commit || !self.is_git_repo().await? {
+            return Ok(None);
+        }
+        let path = self.working_dir().join(readme::README_FILE);
+        let relative = path
+            .strip_prefix(&self.project_path)
+            .map_err(|e| e.to_string())?
+            .to_string_lossy()
+            .replace('\\', "/");
+        self.run_git(&["add", "--", &relative]).await?;
+        let mut args = authorship::commit_args(
+            &self.config.commit_identity,
+            self.config.cli_type,
+            self.adapter().name(),
+            "ralph: update README",
+        );
+        args.extend(["--".to_string(), relative]);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.run_git(&args).await?;
+        self.commits_made.fetch_add(1, Ordering::SeqCst);
+        let sha = self.run_git(&["rev-parse", "--short", "HEAD"]).await?;
+        Ok(Some(sha.trim().to_string()))
+    }
+
+    /// The completion check when the agent signalled completion but the check fails
+    async fn failed_completion_check(&self, iteration: u32) -> Option<String> {
+        let command = self.config.completion_check.as_ref()?;
+        if self.config.remote.is_some() || benchmark::run_tests(command, self.working_dir()).await {
+            return None;
+        }
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!(
+                "[completion-check] `{}` failed; the loop continues until it passes",
+                command
+            ),
+            is_stderr: true,
+            highlight: None,
+        });
+        Some(command.clone())
+    }
+
+    /// Save the session's iteration records
+    fn save_iterations(&self, records: &[IterationRecord]) {
+        if let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) {
+            let _ = storage::save_session_iterations(&uuid, &self.config.session_id, records);
+        }
+    }
+
+    /// End the running iteration's record with `outcome` and save the records
+    fn end_iteration(&self, records: &mut [IterationRecord], outcome: IterationOutcome) {
+        if let Some(record) = records.last_mut() {
+            record.finish(outcome);
+        }
+        self.save_iterations(records);
+    }
+
+    /// Run the pre-iteration script with its output in the loop's output; `false`
+    /// when it failed and the iteration is to be skipped
+    async fn run_pre_iteration_hook(&self, iteration: u32) -> bool {
+        let Some(command) = &self.config.pre_iteration_command else {
+            return true;
+        };
+        if self.config.remote.is_some() {
+            return true;
+        }
+        let result = hooks::run_pre_iteration(
+            &self.project_id,
+            command,
+            self.working_dir(),
+            |line, is_stderr| {
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: format!("[pre-iteration] {}", line),
+                    is_stderr,
+                    highlight: None,
+                });
+            },
+        )
+        .await;
+        let Err(reason) = result else {
+            return true;
+        };
+        if self.config.pre_iteration_abort_on_failure {
+            self.emit_event(LoopEvent::Error {
+                project_id: self.project_id.clone(),
+                iteration,
+                error: format!(
+                    "Pre-iteration script `{}` {}; skipping the iteration",
+                    command, reason
+                ),
+            });
+            return false;
+        }
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!("[pre-iteration] `{}` {}; continuing", command, reason),
+            is_stderr: true,
+            highlight: None,
+        });
+        true
+    }
+
+    /// Run the validation hook after an iteration; its failure is reported and
+    /// handed to the next iteration
+    async fn failed_validation(&self, iteration: u32) -> Option<validation::Failure> {
+        let command = self.config.validation_command.as_ref()?;
+        if self.config.remote.is_some() {
+            return None;
+        }
+        let failure = validation::run(command, self.working_dir()).await?;
+        self.emit_event(LoopEvent::Output {
+            project_id: self.project_id.clone(),
+            iteration,
+            content: format!(
+                "[validation] `{}` failed; its output goes into the next prompt",
+                command
+            ),
+            is_stderr: true,
+            highlight: None,
+        });
+        Some(failure)
+    }
+
+    /// Leave the session's outcome in the project for badges and CI; remote
+    /// projects and benchmark worktrees are skipped
+    fn write_status_file(&self, started_at: DateTime<Utc>, status: &str) {
+        if !self.config.write_status_file || self.config.isolated || self.config.remote.is_some() {
+            return;
+        }
+        let record = status_file::SessionStatus::new(
+            status,
+            self.iterations_run.load(Ordering::SeqCst),
+            self.commits_made.load(Ordering::SeqCst),
+            self.config.cli_type,
+            &self.config.session_id,
+            started_at,
+            Utc::now(),
+        );
+        let _ = status_file::write(&self.project_path, &record);
+    }
+
+    /// Emit `PathMissing` when the local project directory is gone; remote
+    /// projects live on their SSH host and are not checked
+    fn project_path_missing(&self, iteration: u32) -> bool {
+        if self.config.remote.is_some() || !workdir::path_missing(&self.project_path) {
+            return false;
+        }
+        self.emit_event(LoopEvent::PathMissing {
+            project_id: self.project_id.clone(),
+            iteration,
+            path: self.project_path.to_string_lossy().to_string(),
+        });
+        true
+    }
+
+    async fn run(&self) -> Result<LoopState, String> {
+        let adapter = self.adapter();
+        let executor = executor::for_config(&self.config, &self.project_path);
+        let mut iteration = 0u32;
+
+        // Reset flags
+        self.stop_requested.store(false, Ordering::SeqCst);
+        self.pause_requested.store(false, Ordering::SeqCst);
+
+        let context = self.build_context().await;
+        let mut tracker = hotspots::ChangeTracker::default();
+        tracker.baseline(&self.project_path).await;
+        let mut anomalies = anomalies::AnomalyRecorder::new(self.config.cli_type);
+        let mut filter =
+            filters::OutputFilter::new(&self.config.output_filters).unwrap_or_default();
+        let highlighter =
+            highlights::Highlighter::new(&self.config.highlight_rules).unwrap_or_default();
+        let signal_list: Vec<String> = std::iter::once(self.config.completion_signal.clone())
+            .chain(self.config.extra_completion_signals.iter().cloned())
+            .collect();
+        let mut signals = completion::Signals::new(
+            &signal_list,
+            self.config.completion_signal_regex,
+            self.config.completion_mode,
+        )
+        .or_else(|_| completion::Signals::new(&signal_list, false, self.config.completion_mode))
+        .unwrap_or_default();
+        let mut answerer =
+            auto_answer::PromptAnswerer::new(&self.config.prompt_answers).unwrap_or_default();
+        let mut strategy = completion::StrategyTracker::default();
+        let mut repeats = repeats::RepeatCollapser::new(self.config.collapse_repeats);
+        let mut session_highlights = Vec::new();
+        let mut session_artifacts = Vec::new();
+        let mut session_usage = Vec::new();
+        let mut session_iterations: Vec<IterationRecord> = Vec::new();
+        let mut latest_screenshot: Option<String> = None;
+        let mut feedback = IterationFeedback::default();
+        let mut question_history = questions::QuestionHistory::default();
+        // Output of the iterations since the supervisor last reviewed the loop
+        let mut unreviewed: Vec<(u32, String)> = Vec::new();
+        if self.config.remote.is_none() {
+            self.dev_server_port_free(0).await;
+        }
+        let mut consecutive_failures = 0u32;
+        // Flipped after a spawn hang, for adapters that take the prompt either way
+        let mut alternate_prompt_delivery = false;
+        let can_switch_delivery = adapter.has_alternate_prompt_delivery()
+            && self.config.remote.is_none()
+            && self.config.container.is_none()
+            && !self.config.interactive_terminal;
+        let mut retrying_hang = false;
+
+        while iteration < self.config.max_iterations {
+            // Check stop request before iteration
+            if self.stop_requested.load(Ordering::SeqCst) {
+                self.emit_event(LoopEvent::Stopped {
+                    project_id: self.project_id.clone(),
+                });
+                return Ok(LoopState::Idle);
+            }
+
+            // Check pause request before iteration
+            if self.pause_requested.load(Ordering::SeqCst) {
+                self.emit_event(LoopEvent::Paused {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                });
+
+                // Wait for resume or stop
+                loop {
+                    tokio::select! {
+                        _ = self.resume_notify.notified() => break,
+                        _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                            if self.stop_requested.load(Ordering::SeqCst) {
+                                self.emit_event(LoopEvent::Stopped {
+                                    project_id: self.project_id.clone(),
+                                });
+                                return Ok(LoopState::Idle);
+                            }
+                        }
+                    }
+                }
+
+                self.pause_requested.store(false, Ordering::SeqCst);
+                self.emit_event(LoopEvent::Resumed {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                });
+            }
+
+            if self.project_path_missing(iteration) {
+                return Ok(LoopState::PathMissing { iteration });
+            }
+
+            iteration += 1;
+            self.iterations_run.store(iteration, Ordering::SeqCst);
+            self.emit_event(LoopEvent::IterationStart {
+                project_id: self.project_id.clone(),
+                iteration,
+            });
+            // A spawn hang runs the same iteration again
+            session_iterations.retain(|record| record.number != iteration);
+            session_iterations.push(IterationRecord::start(iteration));
+            self.save_iterations(&session_iterations);
+            if !self.run_pre_iteration_hook(iteration).await {
+                self.end_iteration(&mut session_iterations, IterationOutcome::Skipped);
+                continue;
+            }
+
+            let iteration_started = Instant::now();
+            let state_before = self.tree_state().await;
+            let iteration_deadline = self
+                .config
+                .iteration_timeout
+                .map(|timeout| Instant::now() + timeout);
+
+            // Build and spawn command
+            let prompt = self.effective_prompt(
+                iteration,
+                context.as_deref(),
+                latest_screenshot.as_deref(),
+                &feedback,
+            );
+            let (prompt, notes) = self
+                .config
+                .plugins
+                .rewrite_prompt(&self.plugin_context(iteration), prompt)
+                .await;
+            self.report_plugins(iteration, notes);
+            let options = CommandOptions {
+                skip_git_repo_check: self.config.skip_git_repo_check,
+                alternate_prompt_delivery,
+            };
+            let mut execution = match executor
+                .start(IterationRequest {
+                    project_id: &self.project_id,
+                    prompt: &prompt,
+                    working_dir: self.working_dir(),
+                    options,
+                    network_blocked: self.config.network_blocked,
+                    api_hosts: &self.config.api_hosts,
+                    env: &self.config.env,
+                })
+                .await
+            {
+                Ok(execution) => execution,
+                Err(_) if self.project_path_missing(iteration) => {
+                    self.end_iteration(&mut session_iterations, IterationOutcome::Skipped);
+                    return Ok(LoopState::PathMissing { iteration });
+                }
+                Err(e) => {
+                    self.emit_event(LoopEvent::Error {
+                        project_id: self.project_id.clone(),
+                        iteration,
+                        error: format!("Failed to spawn CLI: {}", e),
+                    });
+                    self.end_iteration(&mut session_iterations, IterationOutcome::Skipped);
+                    continue;
+                }
+            };
+
+            let mut last_output_time = Instant::now();
+            let first_output_deadline = self
+                .config
+                .first_output_timeout
+                .map(|timeout| Instant::now() + timeout);
+            let mut has_output = false;
+            let mut spawn_hung = false;
+            let mut last_progress: Option<Instant> = None;
+            let mut completed = false;
+            let suppressed_before = filter.total();
+            let mut output_lines = 0u32;
+            let mut stdout_lines = 0u32;
+            let mut stderr_lines = 0u32;
+            let mut iteration_cost: Option<f64> = None;
+            let mut iteration_tokens: Option<TokenUsage> = None;
+            let mut assistant_text = String::new();
+            let mut final_message = questions::FinalMessage::default();
+            signals.reset();
+            let mut payload: Option<CompletionPayload> = None;
+
+            loop {
+                // Check stop request
+                if self.stop_requested.load(Ordering::SeqCst) {
+                    execution.kill().await;
+                    anomalies.flush();
+                    self.end_iteration(&mut session_iterations, IterationOutcome::Stopped);
+                    self.emit_event(LoopEvent::Stopped {
+                        project_id: self.project_id.clone(),
+                    });
+                    return Ok(LoopState::Idle);
+                }
+
+                tokio::select! {
+                    line = execution.next_line() => {
+                        match line {
+                            Some(OutputLine::Stdout(line)) => {
+                                last_output_time = Instant::now();
+                                has_output = true;
+                                stdout_lines += 1;
+                                self.emit_raw(iteration, &line, false);
+                                if let Some(cost) = adapter.cost_usd(&line) {
+                                    iteration_cost = Some(iteration_cost.unwrap_or(0.0) + cost);
+                                }
+                                if let Some(usage) = adapter.token_usage(&line) {
+                                    let total = iteration_tokens.get_or_insert_with(TokenUsage::default);
+                                    total.input_tokens += usage.input_tokens;
+                                    total.output_tokens += usage.output_tokens;
+                                    total.cache_read_tokens += usage.cache_read_tokens;
+                                    self.emit_event(LoopEvent::Usage {
+                                        project_id: self.project_id.clone(),
+                                        iteration,
+                                        input_tokens: total.input_tokens,
+                                        output_tokens: total.output_tokens,
+                                        cache_read_tokens: total.cache_read_tokens,
+                                    });
+                                }
+                                self.answer_prompt(execution.as_mut(), &mut answerer, &line, &mut session_highlights, iteration, output_lines).await;
+                                let parsed = adapter.parse_output_line(&line);
+                                if let Some(kind) = &parsed.anomaly {
+                                    anomalies.record(kind, &line);
+                                }
+                                self.check_network_use(adapter.shell_commands(&line), &mut session_highlights, iteration, output_lines);
+                                final_message.observe(parsed.is_assistant, &parsed.content);
+
+                                if parsed.content.is_empty() || !filter.suppresses(&parsed.content) {
+                                    if let repeats::LineAction::Emit(summary) = repeats.observe(false, &parsed.content) {
+                                        self.emit_repeats(iteration, summary, &mut output_lines);
+                                        output_lines += 1;
+                                        let highlight = self.highlight(&highlighter, &mut session_highlights, iteration, output_lines, &parsed.content);
+                                        self.emit_event(LoopEvent::Output {
+                                            project_id: self.project_id.clone(),
+                                            iteration,
+                                            content: parsed.content.clone(),
+                                            is_stderr: parsed.line_type == LineType::Error,
+                                            highlight,
+                                        });
+                                    }
+                                }
+
+                                // Check completion signal, or a structured `<done>{json}</done>`
+                                if parsed.is_assistant {
+                                    completion::push_output(&mut assistant_text, &parsed.content);
+                                    let signalled = signals.observe_line(&parsed.content);
+                                    if signalled || parsed.content.contains(completion::CLOSE) {
+                                        payload = completion::parse_payload(&assistant_text);
+                                        completed = signalled || (payload.is_some() && signals.accepts_payload());
+                                    }
+                                    if completed {
+                                        execution.kill().await;
+                                        break;
+                                    }
+                                }
+                            }
+                            Some(OutputLine::Stderr(line)) => {
+                                stderr_lines += 1;
+                                self.emit_raw(iteration, &line, true);
+                                if self.is_codex_git_repo_check_error(&line) {
+                                    self.emit_event(LoopEvent::Error {
+                                        project_id: self.project_id.clone(),
+                                        iteration,
+                                        error: CODEX_GIT_REPO_CHECK_REQUIRED.to_string(),
+                                    });
+                                    execution.kill().await;
+                                    self.end_iteration(&mut session_iterations, IterationOutcome::Failed);
+                                    return Ok(LoopState::Failed { iteration });
+                                }
+                                last_output_time = Instant::now();
+                                has_output = true;
+                                self.answer_prompt(execution.as_mut(), &mut answerer, &line, &mut session_highlights, iteration, output_lines).await;
+                                let is_stderr = self.config.cli_type != CliType::Codex;
+                                if !filter.suppresses(&line) {
+                                    if let repeats::LineAction::Emit(summary) = repeats.observe(is_stderr, &line) {
+                                        self.emit_repeats(iteration, summary, &mut output_lines);
+                                        output_lines += 1;
+                                        let highlight = self.highlight(&highlighter, &mut session_highlights, iteration, output_lines, &line);
+                                        self.emit_event(LoopEvent::Output {
+                                            project_id: self.project_id.clone(),
+                                            iteration,
+                                            content: line,
+                                            is_stderr,
+                                            highlight,
+                                        });
+                                    }
+                                }
+                            }
+                            Some(OutputLine::Progress(content)) => {
+                                last_output_time = Instant::now();
+                                has_output = true;
+                                // Redraws can come hundreds of times a second; nobody sees them unfocused
+                                if verbosity::window_focused()
+                                    && last_progress.is_none_or(|at| at.elapsed() >= PROGRESS_INTERVAL)
+                                {
+                                    last_progress = Some(Instant::now());
+                                    self.emit_event(LoopEvent::Progress {
+                                        project_id: self.project_id.clone(),
+                                        iteration,
+                                        content,
+                                    });
+                                }
+                            }
+                            None => break,
+                        }
+                    }
+
+                    // Timeout check
+                    _ = tokio::time::sleep(Duration::from_secs(1)) => {
+                        let now = Instant::now();
+
+                        // Prompts usually wait on an unfinished line, seen once the CLI goes quiet
+                        if now.duration_since(last_output_time) >= auto_answer::PROMPT_QUIET {
+                            if let Some(line) = execution.pending_line() {
+                                if self.answer_prompt(execution.as_mut(), &mut answerer, &line, &mut session_highlights, iteration, output_lines).await {
+                                    last_output_time = Instant::now();
+                                }
+                            }
+                        }
+
+                        // Spawns through Windows shims sometimes hang before printing anything
+                        if !has_output && first_output_deadline.is_some_and(|deadline| now >= deadline) {
+                            execution.kill().await;
+                            spawn_hung = true;
+                            break;
+                        }
+
+                        // Iteration timeout
+                        if let Some(deadline) = iteration_deadline {
+                            if now >= deadline {
+                                self.emit_event(LoopEvent::Error {
+                                    project_id: self.project_id.clone(),
+                                    iteration,
+                                    error: format!("Iteration timeout: exceeded {:?}", self.config.iteration_timeout),
+                                });
+                                execution.kill().await;
+                                break;
+                            }
+                        }
+
+                        // Idle timeout
+                        if let Some(idle_timeout) = self.config.idle_timeout {
+                            if now.duration_since(last_output_time) > idle_timeout {
+                                self.emit_event(LoopEvent::Error {
+                                    project_id: self.project_id.clone(),
+                                    iteration,
+                                    error: format!("Idle timeout: no output for {:?}", self.config.idle_timeout),
+                                });
+                                execution.kill().await;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+
+            if spawn_hung {
+                let _ = execution.wait().await;
+                if !retrying_hang {
+                    retrying_hang = true;
+                    if can_switch_delivery {
+                        alternate_prompt_delivery = !alternate_prompt_delivery;
+                    }
+                    self.emit_event(LoopEvent::Output {
+                        project_id: self.project_id.clone(),
+                        iteration,
+                        content: format!(
+                            "[spawn-hang] No output within {:?} of starting the CLI; retrying the iteration{}",
+                            self.config.first_output_timeout.unwrap_or_default(),
+                            if can_switch_delivery { " with the prompt delivered the other way" } else { "" }
+                        ),
+                        is_stderr: true,
+                        highlight: None,
+                    });
+                    iteration -= 1;
+                    continue;
+                }
+                self.emit_event(LoopEvent::Error {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    error: format!(
+                        "Spawn hang: no output within {:?} of starting the CLI",
+                        self.config.first_output_timeout.unwrap_or_default()
+                    ),
+                });
+            }
+            retrying_hang = false;
+
+            let held = repeats.finish();
+            self.emit_repeats(iteration, held, &mut output_lines);
+
+            // Wait for process to finish
+            let exit_code = execution.wait().await;
+            self.record_exit_code(iteration, exit_code);
+            if !completed && signals.observe_final_message(final_message.text()) {
+                payload = completion::parse_payload(&assistant_text);
+                completed = true;
+            }
+
+            self.record_usage(
+                iteration,
+                iteration_tokens,
+                iteration_cost,
+                &mut session_usage,
+            );
+
+            anomalies.flush();
+            self.report_suppressed(&filter, iteration, suppressed_before);
+            if !session_highlights.is_empty() {
+                if let Ok(uuid) = uuid::Uuid::parse_str(&self.project_id) {
+                    let _ = storage::save_session_highlights(
+                        &uuid,
+                        &self.config.session_id,
+                        &session_highlights,
+                    );
+                }
+            }
+            let files_changed = self.record_hotspots(&mut tracker).await;
+            if let (false, Some(before), Some(after)) =
+                (completed, &state_before, self.tree_state().await)
+            {
+                let changed = files_changed || *before != after;
+                if strategy.observe(
+                    &self.config.completion_strategy,
+                    exit_code,
+                    changed,
+                    after.1.trim().is_empty(),
+                ) {
+                    completed = true;
+                    self.emit_event(LoopEvent::Output {
+                        project_id: self.project_id.clone(),
+                        iteration,
+                        content:
+                            "[completion] The CLI exited cleanly and the completion strategy is met"
+                                .to_string(),
+                        is_stderr: false,
+                        highlight: None,
+                    });
+                }
+            }
+            self.collect_artifacts(iteration, &mut session_artifacts)
+                .await;
+            latest_screenshot = self
+                .capture_screenshot(iteration, &mut session_artifacts)
+                .await;
+
+            let commit_sha = match self
+                .commit_iteration_if_needed(
+                    iteration,
+                    &mut feedback.blocked_secrets,
+                    &mut feedback.review_comments,
+                )
+                .await
+            {
+                Ok(sha) => {
+                    if sha.is_some() {
+                        self.commits_made.fetch_add(1, Ordering::SeqCst);
+                    }
+                    sha
+                }
+                Err(err) => {
+                    self.emit_event(LoopEvent::Output {
+                        project_id: self.project_id.clone(),
+                        iteration,
+                        content: format!("[auto-commit] {}", err),
+                        is_stderr: true,
+                        highlight: None,
+                    });
+                    None
+                }
+            };
+            // Committed changes leave a clean tree; re-baseline so they are not recounted
+            tracker.baseline(&self.project_path).await;
+
+            self.emit_event(LoopEvent::IterationEnd {
+                project_id: self.project_id.clone(),
+                iteration,
+                stats: IterationStats {
+                    duration_ms: iteration_started.elapsed().as_millis() as u64,
+                    stdout_lines,
+                    stderr_lines,
+                    suppressed_lines: filter.total() - suppressed_before,
+                    exit_code,
+                    commit_sha: commit_sha.clone(),
+                    cost_usd: iteration_cost,
+                    input_tokens: iteration_tokens.map(|t| t.input_tokens),
+                    output_tokens: iteration_tokens.map(|t| t.output_tokens),
+                },
+            });
+
+            if completed {
+                feedback.failed_check = self.failed_completion_check(iteration).await;
+                completed = feedback.failed_check.is_none();
+            }
+
+            if let Some(record) = session_iterations.last_mut() {
+                record.exit_code = exit_code;
+                record.commit_shas.extend(commit_sha.clone());
+                record.tokens = session_usage
+                    .last()
+                    .filter(|usage| usage.iteration == iteration)
+                    .cloned();
+            }
+            let outcome = match (completed, exit_code) {
+                (true, _) => IterationOutcome::Completed,
+                (false, Some(code)) if code != 0 => IterationOutcome::Failed,
+                _ => IterationOutcome::Continued,
+            };
+            self.end_iteration(&mut session_iterations, outcome);
+
+            if completed {
+                if let (Some(payload), Ok(uuid)) =
+                    (&payload, uuid::Uuid::parse_str(&self.project_id))
+                {
+                    let _ =
+                        storage::save_session_completion(&uuid, &self.config.session_id, payload);
+                }
+                self.emit_event(LoopEvent::Completed {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    payload,
+                });
+                return Ok(LoopState::Completed { iteration });
+            }
+
+            feedback.failed_validation = self.failed_validation(iteration).await;
+            if self.config.validation_command.is_some() && self.config.remote.is_none() {
+                if let Some(record) = session_iterations.last_mut() {
+                    record.validation_passed = Some(feedback.failed_validation.is_none());
+                }
+                self.save_iterations(&session_iterations);
+            }
+            feedback.previous_progress = self.summarize_progress(iteration, &assistant_text).await;
+            if let Some(interval) = self.config.supervisor_interval {
+                unreviewed.push((iteration, assistant_text.clone()));
+                if iteration.is_multiple_of(interval) {
+                    self.supervise(iteration, &unreviewed).await;
+                    unreviewed.clear();
+                }
+            }
+
+            feedback.unanswered = questions::final_question(final_message.text())
+                .map(|question| question_history.record(question, iteration));
+            if feedback.unanswered.is_some() {
+                self.questions_asked.fetch_add(1, Ordering::SeqCst);
+                self.emit_event(LoopEvent::Output {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                    content: "[question] The agent ended with a question; the next iteration will be told to proceed on its own judgment".to_string(),
+                    is_stderr: false,
+                    highlight: None,
+                });
+            }
+
+            // Processes we killed report no code and do not count either way
+            match exit_code {
+                Some(0) => consecutive_failures = 0,
+                Some(code) => {
+                    consecutive_failures += 1;
+                    if self
+                        .config
+                        .max_consecutive_failures
+                        .is_some_and(|max| consecutive_failures >= max)
+                    {
+                        self.emit_event(LoopEvent::Error {
+                            project_id: self.project_id.clone(),
+                            iteration,
+                            error: format!(
+                                "CLI exited with a non-zero status {} times in a row (last exit code {})",
+                                consecutive_failures, code
+                            ),
+                        });
+                        return Ok(LoopState::Failed { iteration });
+                    }
+                }
+                None => {}
+            }
+
+            // Checked between iterations, so the one in progress finishes and commits first
+            if self
+                .config
+                .run_deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+            {
+                self.emit_event(LoopEvent::TimeBudgetReached {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                });
+                return Ok(LoopState::TimeBudgetReached { iteration });
+            }
+
+            // Check pause after iteration
+            if self.pause_requested.load(Ordering::SeqCst) {
+                self.emit_event(LoopEvent::Paused {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                });
+
+                loop {
+                    tokio::select! {
+                        _ = self.resume_notify.notified() => break,
+                        _ = tokio::time::sleep(Duration::from_millis(100)) => {
+                            if self.stop_requested.load(Ordering::SeqCst) {
+                                self.emit_event(LoopEvent::Stopped {
+                                    project_id: self.project_id.clone(),
+                                });
+                                return Ok(LoopState::Idle);
+                            }
+                        }
+                    }
+                }
+
+                self.pause_requested.store(false, Ordering::SeqCst);
+                self.emit_event(LoopEvent::Resumed {
+                    project_id: self.project_id.clone(),
+                    iteration,
+                });
+            }
+        }
+
+        // Max iterations reached
+        self.emit_event(LoopEvent::MaxIterationsReached {
+            project_id: self.project_id.clone(),
+            iteration,
+        });
+
+        Ok(LoopState::MaxIterationsReached { iteration })
+    }
+
+    pub fn pause(&self) {
+        self.pause_requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.resume_notify.notify_one();
+    }
+
+    pub fn stop(&self) {
+        self.stop_requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Spend the CLI reported across all iterations so far
+    pub fn total_cost_usd(&self) -> Option<f64> {
+        self.total_cost_usd.lock().ok().and_then(|total| *total)
+    }
+
+    pub fn get_pause_flag(&self) -> Arc<AtomicBool> {
+        self.pause_requested.clone()
+    }
+
+    pub fn get_stop_flag(&self) -> Arc<AtomicBool> {
+        self.stop_requested.clone()
+    }
+
+    pub fn get_resume_notify(&self) -> Arc<Notify> {
+        self.resume_notify.clone()
+    }
+}
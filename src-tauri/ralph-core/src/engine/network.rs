@@ -0,0 +1,198 @@
+//! Network kill-switch for sensitive projects. The agent CLI itself still has to
+//! reach its API, so nothing is firewalled; instead every proxy-aware tool the
+//! agent starts is pointed at a dead proxy, package managers are put in offline
+//! mode, the prompt forbids network use, and shell commands that reach for the
+//! network are flagged as they happen.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// The discard port: connections are refused, so proxied requests fail fast
+const DEAD_PROXY: &str = "http://127.0.0.1:9";
+
+/// Hosts the agent CLIs talk to themselves, exempt from the dead proxy
+const CLI_HOSTS: &[&str] = &[
+    "localhost",
+    "127.0.0.1",
+    "::1",
+    ".anthropic.com",
+    ".claude.ai",
+    ".openai.com",
+    ".chatgpt.com",
+    // Gemini CLI: API-key calls, Code Assist for Google login, and token refresh
+    "generativelanguage.googleapis.com",
+    "cloudcode-pa.googleapis.com",
+    "oauth2.googleapis.com",
+    ".opencode.ai",
+    ".ampcode.com",
+];
+
+/// Label of highlights raised for network use
+pub const VIOLATION_LABEL: &str = "network";
+
+/// Environment keys whose values point the agent at its model API
+const ENDPOINT_KEY_SUFFIXES: &[&str] = &["_BASE_URL", "_API_BASE", "_ENDPOINT"];
+
+/// Hosts of the model endpoints a loop is configured with: the API backend's
+/// base URL, endpoint variables in the task environment, and URLs passed to a
+/// custom CLI. Values that do not parse as URLs are ignored.
+pub fn configured_api_hosts<'a>(
+    base_url: Option<&'a str>,
+    env: impl IntoIterator<Item = (&'a str, &'a str)>,
+    custom_args: impl IntoIterator<Item = &'a str>,
+) -> Vec<String> {
+    let endpoint_values = env
+        .into_iter()
+        .filter(|(key, _)| {
+            let key = key.to_ascii_uppercase();
+            ENDPOINT_KEY_SUFFIXES
+                .iter()
+                .any(|suffix| key.ends_with(suffix))
+        })
+        .map(|(_, value)| value);
+    // `--base-url=https://...` as well as a bare URL argument
+    let arg_urls = custom_args
+        .into_iter()
+        .flat_map(|arg| arg.split(|c: char| c == '=' || c.is_whitespace()))
+        .filter(|part| part.contains("://"));
+    let mut hosts: Vec<String> = Vec::new();
+    for value in base_url.into_iter().chain(endpoint_values).chain(arg_urls) {
+        let Ok(url) = reqwest::Url::parse(value.trim()) else {
+            continue;
+        };
+        if let Some(host) = url.host_str() {
+            let host = host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .to_string();
+            if !hosts.contains(&host) {
+                hosts.push(host);
+            }
+        }
+    }
+    hosts
+}
+
+/// Environment for an iteration with the network blocked. Replaces any proxy
+/// the user configured, so it cannot be combined with one the CLI relies on.
+/// `api_hosts` are the loop's configured endpoints, exempt alongside the
+/// built-in CLI hosts.
+pub fn offline_env(api_hosts: &[String]) -> Vec<(String, String)> {
+    let no_proxy = CLI_HOSTS
+        .iter()
+        .copied()
+        .chain(api_hosts.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(",");
+    let mut env = Vec::new();
+    for key in ["HTTP_PROXY", "HTTPS_PROXY", "ALL_PROXY"] {
+        env.push((key.to_string(), DEAD_PROXY.to_string()));
+        env.push((key.to_ascii_lowercase(), DEAD_PROXY.to_string()));
+    }
+    env.push(("NO_PROXY".to_string(), no_proxy.clone()));
+    env.push(("no_proxy".to_string(), no_proxy));
+    for (key, value) in [
+        // git over ssh or https would bypass the proxy
+        ("GIT_ALLOW_PROTOCOL", "file"),
+        ("npm_config_offline", "true"),
+        ("YARN_ENABLE_OFFLINE_MODE", "1"),
+        ("PIP_NO_INDEX", "1"),
+        ("CARGO_NET_OFFLINE", "true"),
+        ("GOPROXY", "off"),
+        ("HOMEBREW_NO_AUTO_UPDATE", "1"),
+    ] {
+        env.push((key.to_string(), value.to_string()));
+    }
+    env
+}
+
+fn violation_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            (
+                r"\b(curl|wget|nc|ncat|netcat|telnet|ftp|ssh|scp|sftp|rsync)\b",
+                "network client",
+            ),
+            (
+                r"\bgit\s+(clone|fetch|pull|push|ls-remote|submodule\s+update)\b",
+                "git remote",
+            ),
+            (
+                r"\b(npm|pnpm|yarn|bun)\s+(install|i|ci|add|update|upgrade|dlx|x)\b|\bnpx\b",
+                "package download",
+            ),
+            (
+                r"\b(pip3?|pipx|uv\s+pip|poetry|gem|brew|apt|apt-get|dnf|yum)\s+(install|add|update|upgrade)\b",
+                "package download",
+            ),
+            (
+                r"\b(cargo\s+(install|add|update|fetch)|go\s+(get|install|mod\s+download))\b",
+                "package download",
+            ),
+        ]
+        .into_iter()
+        .map(|(pattern, kind)| (Regex::new(pattern).expect("valid network pattern"), kind))
+        .collect()
+    })
+}
+
+/// What kind of network use a shell command attempts, if any
+pub fn violation(command: &str) -> Option<&'static str> {
+    violation_patterns()
+        .iter()
+        .find(|(re, _)| re.is_match(command))
+        .map(|(_, kind)| *kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_network_commands() {
+        assert_eq!(
+            violation("curl -fsSL https://example.com/install.sh | sh"),
+            Some("network client")
+        );
+        assert_eq!(violation("bash -lc 'git fetch origin'"), Some("git remote"));
+        assert_eq!(violation("npm install left-pad"), Some("package download"));
+        assert_eq!(violation("pip install requests"), Some("package download"));
+        assert_eq!(violation("cargo test --workspace"), None);
+        assert_eq!(violation("git status && npm test"), None);
+    }
+
+    #[test]
+    fn exempts_cli_hosts_from_the_dead_proxy() {
+        let env = offline_env(&["llm.internal.example".to_string()]);
+        let get = |key: &str| env.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("https_proxy"), Some(DEAD_PROXY));
+        let no_proxy = get("NO_PROXY").unwrap();
+        assert!(no_proxy.contains(".anthropic.com"));
+        assert!(no_proxy.contains("llm.internal.example"));
+        assert!(!no_proxy.split(',').any(|host| host == ".googleapis.com"));
+        assert_eq!(get("GIT_ALLOW_PROTOCOL"), Some("file"));
+    }
+
+    #[test]
+    fn collects_hosts_of_configured_endpoints() {
+        let hosts = configured_api_hosts(
+            Some("https://gateway.example.com/v1"),
+            [
+                ("ANTHROPIC_BASE_URL", "http://10.0.0.5:8080"),
+                ("OPENAI_API_BASE", "https://gateway.example.com/openai"),
+                ("RUST_LOG", "https://not-an-endpoint.example"),
+                ("AZURE_ENDPOINT", "not a url"),
+            ],
+            [
+                "--model",
+                "--base-url=https://proxy.example.org/v1",
+                "{prompt}",
+            ],
+        );
+        assert_eq!(
+            hosts,
+            vec!["gateway.example.com", "10.0.0.5", "proxy.example.org"]
+        );
+    }
+}
@@ -0,0 +1,86 @@
+use crate::storage;
+use crate::storage::models::BrainstormPersona;
+
+/// Persona used when a conversation has none selected
+pub const DEFAULT_PERSONA_ID: &str = "thought_partner";
+
+fn builtin(id: &str, name: &str, prompt: &str) -> BrainstormPersona {
+    BrainstormPersona {
+        id: id.to_string(),
+        name: name.to_string(),
+        prompt: prompt.to_string(),
+        built_in: true,
+    }
+}
+
+/// Personas shipped with the app
+pub fn builtin_personas() -> Vec<BrainstormPersona> {
+    vec![
+        builtin(DEFAULT_PERSONA_ID, "Thought Partner", ""),
+        builtin(
+            "architect",
+            "Architect",
+            "Act as a pragmatic software architect. Focus on the existing codebase, module boundaries, data flow, migration paths and risks. Ask about constraints, backwards compatibility and how the change will be rolled out before discussing features.",
+        ),
+        builtin(
+            "product_manager",
+            "Product Manager",
+            "Act as a product manager. Focus on the target users, the problem being solved, the smallest valuable scope and how success will be measured. Push back on features that do not serve the core use case.",
+        ),
+        builtin(
+            "qa",
+            "QA Engineer",
+            "Act as a QA engineer. Focus on acceptance criteria, edge cases, failure modes and how every requirement will be verified. Make sure the final prompt contains concrete test commands and expected results.",
+        ),
+    ]
+}
+
+/// Built-in personas followed by the user's own
+pub fn list_personas() -> Vec<BrainstormPersona> {
+    let mut personas = builtin_personas();
+    personas.extend(storage::load_custom_personas().unwrap_or_default());
+    personas
+}
+
+/// Look up a persona's prompt by id; unknown ids fall back to no persona
+pub fn persona_prompt(id: Option<&str>) -> Option<String> {
+    let id = id?;
+    list_personas()
+        .into_iter()
+        .find(|p| p.id == id)
+        .map(|p| p.prompt)
+        .filter(|p| !p.trim().is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[test]
+    fn custom_personas_are_listed_after_builtins() {
+        let _lock = lock_env();
+        let home = tempdir().unwrap();
+        let _home = EnvVarGuard::set("HOME", home.path());
+
+        storage::save_custom_personas(&[BrainstormPersona {
+            id: "game_designer".to_string(),
+            name: "Game Designer".to_string(),
+            prompt: "Focus on game feel.".to_string(),
+            built_in: false,
+        }])
+        .unwrap();
+
+        let personas = list_personas();
+        assert_eq!(personas.first().unwrap().id, DEFAULT_PERSONA_ID);
+        assert_eq!(personas.last().unwrap().id, "game_designer");
+        assert_eq!(
+            persona_prompt(Some("game_designer")).as_deref(),
+            Some("Focus on game feel.")
+        );
+        assert!(persona_prompt(Some(DEFAULT_PERSONA_ID)).is_none());
+        assert!(persona_prompt(Some("missing")).is_none());
+        assert!(persona_prompt(Some("qa")).unwrap().contains("QA engineer"));
+    }
+}
@@ -0,0 +1,445 @@
+//! Plugins: executables in `~/.ralph-desktop/plugins/<id>/`, described by a
+//! `plugin.json`, that receive loop events and can rewrite prompts or block
+//! commits. A plugin is started once per call with one JSON request on stdin
+//! and answers with one JSON object on stdout:
+//!
+//! - `{"hook": "events", "events": [...]}` with the events of the types it
+//!   subscribes to, in order; the answer is ignored, so a plugin can forward
+//!   events anywhere it likes. Calls are made one at a time, each with the
+//!   events queued since the last; while a plugin falls behind, new events are
+//!   dropped rather than queued without limit
+//! - `{"hook": "beforePrompt", "context": {...}, "prompt": "..."}`, answered
+//!   with `{"prompt": "..."}` to replace the prompt
+//! - `{"hook": "beforeCommit", "context": {...}, "files": [...]}`, answered
+//!   with `{"block": true, "reason": "..."}` to keep the changes uncommitted
+//!
+//! Plugins run in their own directory with a minimal environment, a time limit
+//! and, where supported, a memory limit. Only plugins enabled in the settings
+//! load, a loop only starts once running them was acknowledged, and a plugin
+//! that fails or times out never blocks anything.
+//! WebAssembly plugins are not supported.
+
+use super::resources;
+use super::LoopEvent;
+use crate::adapters::hide_console_window;
+use crate::storage;
+use crate::storage::models::{PluginHook, PluginInfo, PluginManifest};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+pub const MANIFEST_FILE: &str = "plugin.json";
+/// Longest one plugin call may take
+const CALL_TIMEOUT: Duration = Duration::from_secs(30);
+/// Larger answers are cut off, and then no longer parse
+const MAX_RESPONSE_BYTES: u64 = 1024 * 1024;
+const MEMORY_LIMIT_MB: u64 = 512;
+/// Events waiting for one plugin; further ones are dropped until it catches up
+const EVENT_QUEUE_CAPACITY: usize = 256;
+/// Most events handed to a plugin in one call
+const MAX_EVENT_BATCH: usize = 64;
+/// Environment variables a plugin keeps; everything else is cleared
+const KEPT_ENV: &[&str] = &[
+    "PATH",
+    "HOME",
+    "USERPROFILE",
+    "SYSTEMROOT",
+    "TEMP",
+    "TMP",
+    "TMPDIR",
+    "LANG",
+];
+
+/// A loaded plugin and the directory it runs in
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub manifest: PluginManifest,
+    pub dir: PathBuf,
+}
+
+/// What a plugin answers; every field is optional
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Response {
+    #[serde(default)]
+    block: bool,
+    reason: Option<String>,
+    prompt: Option<String>,
+}
+
+fn read_manifest(dir: &Path) -> Result<PluginManifest, String> {
+    let content = std::fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Cannot read {}: {}", MANIFEST_FILE, e))?;
+    let manifest: PluginManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))?;
+    if manifest.command.trim().is_empty() {
+        return Err(format!("{} has no command", MANIFEST_FILE));
+    }
+    Ok(manifest)
+}
+
+/// Every plugin in the plugins directory, by directory name
+pub fn discover(enabled: &[String]) -> Result<Vec<PluginInfo>, String> {
+    let root = storage::get_plugins_dir().map_err(|e| e.to_string())?;
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+    let mut found = Vec::new();
+    for entry in std::fs::read_dir(&root)
+        .map_err(|e| e.to_string())?
+        .flatten()
+    {
+        let dir = entry.path();
+        if !dir.is_dir() {
+            continue;
+        }
+        let id = entry.file_name().to_string_lossy().to_string();
+        let (manifest, error) = match read_manifest(&dir) {
+            Ok(manifest) => (Some(manifest), None),
+            Err(err) => (None, Some(err)),
+        };
+        found.push(PluginInfo {
+            enabled: enabled.contains(&id),
+            id,
+            dir: dir.to_string_lossy().to_string(),
+            manifest,
+            error,
+        });
+    }
+    found.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(found)
+}
+
+/// Enabled plugins whose manifest reads; broken ones are left out
+pub fn load_enabled(enabled: &[String]) -> Vec<Plugin> {
+    let Ok(root) = storage::get_plugins_dir() else {
+        return Vec::new();
+    };
+    enabled
+        .iter()
+        .filter_map(|id| {
+            let dir = root.join(id);
+            let manifest = read_manifest(&dir).ok()?;
+            Some(Plugin { manifest, dir })
+        })
+        .collect()
+}
+
+impl Plugin {
+    fn has_hook(&self, hook: PluginHook) -> bool {
+        self.manifest.hooks.contains(&hook)
+    }
+
+    fn subscribes_to(&self, event_type: &str) -> bool {
+        self.manifest
+            .events
+            .iter()
+            .any(|subscribed| subscribed == event_type || subscribed == "*")
+    }
+
+    /// What running the plugin executes, shown when asking to allow it
+    pub fn command_line(&self) -> String {
+        std::iter::once(self.program().to_string_lossy().to_string())
+            .chain(self.manifest.args.iter().cloned())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// The command inside the plugin's directory, or else one on PATH
+    fn program(&self) -> PathBuf {
+        let local = self.dir.join(&self.manifest.command);
+        if local.is_file() {
+            local
+        } else {
+            PathBuf::from(&self.manifest.command)
+        }
+    }
+
+    async fn call(&self, request: &Value) -> Result<Response, String> {
+        let name = &self.manifest.name;
+        let mut cmd = Command::new(self.program());
+        cmd.args(&self.manifest.args)
+            .current_dir(&self.dir)
+            .env_clear()
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        for key in KEPT_ENV {
+            if let Some(value) = std::env::var_os(key) {
+                cmd.env(key, value);
+            }
+        }
+        hide_console_window(&mut cmd);
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to start plugin {}: {}", name, e))?;
+        if let Some(pid) = child.id() {
+            let limit =
+                cfg!(any(target_os = "linux", target_os = "windows")).then_some(MEMORY_LIMIT_MB);
+            let _ = resources::apply_to_process(pid, limit);
+        }
+        let exchange = async {
+            if let Some(mut stdin) = child.stdin.take() {
+                // Plugins that only look at their arguments may exit without reading
+                let _ = stdin.write_all(format!("{}\n", request).as_bytes()).await;
+            }
+            let mut output = Vec::new();
+            if let Some(stdout) = child.stdout.take() {
+                stdout
+                    .take(MAX_RESPONSE_BYTES)
+                    .read_to_end(&mut output)
+                    .await?;
+            }
+            let status = child.wait().await?;
+            Ok::<_, std::io::Error>((status, output))
+        };
+        let (status, output) = tokio::time::timeout(CALL_TIMEOUT, exchange)
+            .await
+            .map_err(|_| format!("Plugin {} timed out after {:?}", name, CALL_TIMEOUT))?
+            .map_err(|e| format!("Plugin {} failed: {}", name, e))?;
+        if !status.success() {
+            return Err(format!("Plugin {} exited with {}", name, status));
+        }
+        let output = String::from_utf8_lossy(&output);
+        if output.trim().is_empty() {
+            return Ok(Response::default());
+        }
+        serde_json::from_str(output.trim())
+            .map_err(|e| format!("Plugin {} answered with invalid JSON: {}", name, e))
+    }
+}
+
+/// Hand queued events to `plugin` one call at a time until the queue closes
+async fn deliver_events(plugin: Plugin, mut queue: mpsc::Receiver<Value>) {
+    while let Some(first) = queue.recv().await {
+        let mut events = vec![first];
+        while events.len() < MAX_EVENT_BATCH {
+            match queue.try_recv() {
+                Ok(event) => events.push(event),
+                Err(_) => break,
+            }
+        }
+        let _ = plugin
+            .call(&json!({ "hook": "events", "events": events }))
+            .await;
+    }
+}
+
+/// The plugins a loop calls at its extension points
+#[derive(Debug, Clone, Default)]
+pub struct Plugins {
+    plugins: Vec<Plugin>,
+    /// Event queue per plugin, `None` for those without subscriptions; the
+    /// workers start on the first event and stop once every clone is dropped
+    queues: Arc<OnceLock<Vec<Option<mpsc::Sender<Value>>>>>,
+}
+
+impl Plugins {
+    pub fn new(plugins: Vec<Plugin>) -> Self {
+        Self {
+            plugins,
+            queues: Arc::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.plugins.is_empty()
+    }
+
+    /// Queue `event` for the plugins subscribed to its type without waiting
+    /// for them; dropped for a plugin whose queue is full
+    pub fn notify(&self, event: &LoopEvent) {
+        if self.plugins.iter().all(|p| p.manifest.events.is_empty()) {
+            return;
+        }
+        let Ok(event) = serde_json::to_value(event) else {
+            return;
+        };
+        let event_type = event["type"].as_str().unwrap_or_default().to_string();
+        let queues = self.queues.get_or_init(|| {
+            self.plugins
+                .iter()
+                .map(|plugin| {
+                    (!plugin.manifest.events.is_empty()).then(|| {
+                        let (sender, receiver) = mpsc::channel(EVENT_QUEUE_CAPACITY);
+                        tokio::spawn(deliver_events(plugin.clone(), receiver));
+                        sender
+                    })
+                })
+                .collect()
+        });
+        for (plugin, queue) in self.plugins.iter().zip(queues) {
+            if let Some(queue) = queue.as_ref().filter(|_| plugin.subscribes_to(&event_type)) {
+                let _ = queue.try_send(event.clone());
+            }
+        }
+    }
+
+    /// Let each `beforePrompt` plugin replace the prompt in turn; also returns
+    /// what to report about the calls
+    pub async fn rewrite_prompt(
+        &self,
+        context: &Value,
+        mut prompt: String,
+    ) -> (String, Vec<String>) {
+        let mut notes = Vec::new();
+        for plugin in self
+            .plugins
+            .iter()
+            .filter(|p| p.has_hook(PluginHook::BeforePrompt))
+        {
+            let request = json!({ "hook": "beforePrompt", "context": context, "prompt": prompt });
+            match plugin.call(&request).await {
+                Ok(Response {
+                    prompt: Some(rewritten),
+                    ..
+                }) if !rewritten.trim().is_empty() && rewritten != prompt => {
+                    notes.push(format!("{} rewrote the prompt", plugin.manifest.name));
+                    prompt = rewritten;
+                }
+                Ok(_) => {}
+                Err(err) => notes.push(err),
+            }
+        }
+        (prompt, notes)
+    }
+
+    /// Why the first `beforeCommit` plugin that objects blocks the commit; also
+    /// returns what to report about failed calls
+    pub async fn check_commit(
+        &self,
+        context: &Value,
+        files: &[String],
+    ) -> (Option<String>, Vec<String>) {
+        let mut notes = Vec::new();
+        for plugin in self
+            .plugins
+            .iter()
+            .filter(|p| p.has_hook(PluginHook::BeforeCommit))
+        {
+            let request = json!({ "hook": "beforeCommit", "context": context, "files": files });
+            match plugin.call(&request).await {
+                Ok(response) if response.block => {
+                    let reason = response
+                        .reason
+                        .filter(|r| !r.trim().is_empty())
+                        .unwrap_or_else(|| "no reason given".to_string());
+                    let blocked =
+                        format!("{} blocked the commit: {}", plugin.manifest.name, reason);
+                    return (Some(blocked), notes);
+                }
+                Ok(_) => {}
+                Err(err) => notes.push(err),
+            }
+        }
+        (None, notes)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::tempdir;
+
+    fn script_plugin(dir: &Path, script: &str, hooks: Vec<PluginHook>) -> Plugin {
+        let path = dir.join("run.sh");
+        std::fs::write(&path, format!("#!/bin/sh\n{}", script)).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        Plugin {
+            manifest: PluginManifest {
+                name: "test".to_string(),
+                description: String::new(),
+                command: "run.sh".to_string(),
+                args: Vec::new(),
+                events: Vec::new(),
+                hooks,
+            },
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    #[tokio::test]
+    async fn plugins_rewrite_prompts_and_block_commits() {
+        let dir = tempdir().unwrap();
+        let plugin = script_plugin(
+            dir.path(),
+            "read request\ncase \"$request\" in\n\
+             *beforePrompt*) echo '{\"prompt\": \"Rewritten\"}' ;;\n\
+             *beforeCommit*) echo '{\"block\": true, \"reason\": \"tests are red\"}' ;;\n\
+             esac\n",
+            vec![PluginHook::BeforePrompt, PluginHook::BeforeCommit],
+        );
+        let plugins = Plugins::new(vec![plugin]);
+        let context = json!({ "iteration": 1 });
+
+        let (prompt, notes) = plugins
+            .rewrite_prompt(&context, "Build it".to_string())
+            .await;
+        assert_eq!(prompt, "Rewritten");
+        assert_eq!(notes, vec!["test rewrote the prompt".to_string()]);
+
+        let (blocked, _) = plugins
+            .check_commit(&context, &["src/main.rs".to_string()])
+            .await;
+        assert_eq!(
+            blocked.as_deref(),
+            Some("test blocked the commit: tests are red")
+        );
+    }
+
+    #[tokio::test]
+    async fn events_reach_subscribed_plugins_in_order() {
+        let dir = tempdir().unwrap();
+        let mut plugin = script_plugin(dir.path(), "cat >> events.jsonl\necho\n", Vec::new());
+        plugin.manifest.events = vec!["iterationStart".to_string()];
+        let plugins = Plugins::new(vec![plugin]);
+        for iteration in 1..=3 {
+            plugins.notify(&LoopEvent::IterationStart {
+                project_id: "p".to_string(),
+                iteration,
+            });
+            plugins.notify(&LoopEvent::Paused {
+                project_id: "p".to_string(),
+                iteration,
+            });
+        }
+
+        let log = dir.path().join("events.jsonl");
+        let mut delivered = Vec::new();
+        for _ in 0..100 {
+            delivered = std::fs::read_to_string(&log)
+                .unwrap_or_default()
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+                .flat_map(|request| request["events"].as_array().cloned().unwrap_or_default())
+                .map(|event| event["iteration"].as_u64().unwrap())
+                .collect();
+            if delivered.len() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert_eq!(delivered, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn failing_plugins_never_block() {
+        let dir = tempdir().unwrap();
+        let plugins = Plugins::new(vec![script_plugin(
+            dir.path(),
+            "echo 'not json'\n",
+            vec![PluginHook::BeforeCommit],
+        )]);
+        let (blocked, notes) = plugins.check_commit(&json!({}), &[]).await;
+        assert!(blocked.is_none());
+        assert!(notes[0].contains("invalid JSON"));
+    }
+}
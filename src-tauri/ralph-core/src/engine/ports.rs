@@ -0,0 +1,190 @@
+//! Detects when a managed dev server's port is already taken, usually by a
+//! server an agent started and left running, so the loop can say which
+//! process to stop instead of failing every capture with EADDRINUSE.
+
+use crate::adapters::hide_console_window;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::process::Command;
+
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// A process already listening on a port the loop needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortConflict {
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub program: Option<String>,
+}
+
+impl PortConflict {
+    pub fn message(&self) -> String {
+        let owner = match (&self.program, self.pid) {
+            (Some(program), Some(pid)) => format!("{} (PID {})", program, pid),
+            (None, Some(pid)) => format!("PID {}", pid),
+            _ => "another process".to_string(),
+        };
+        format!(
+            "Port {} for the dev server is already in use by {}; stop it or change the screenshot URL",
+            self.port, owner
+        )
+    }
+}
+
+/// Port of a URL served on this machine; remote hosts are not ours to check
+pub fn local_port(url: &str) -> Option<u16> {
+    let url = reqwest::Url::parse(url).ok()?;
+    let local = matches!(
+        url.host_str()?,
+        "localhost" | "127.0.0.1" | "0.0.0.0" | "[::1]" | "::1"
+    );
+    if local {
+        url.port_or_known_default()
+    } else {
+        None
+    }
+}
+
+/// Whether anything accepts connections on `port`, over IPv4 or IPv6
+/// (dev servers binding `localhost` often listen on `::1` only)
+pub async fn is_in_use(port: u16) -> bool {
+    let addrs = [
+        SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+        SocketAddr::from((Ipv6Addr::LOCALHOST, port)),
+    ];
+    for addr in addrs {
+        if let Ok(Ok(_)) = tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr)).await {
+            return true;
+        }
+    }
+    false
+}
+
+/// First PID in `lsof -t` output
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn parse_lsof_pid(output: &str) -> Option<u32> {
+    output.lines().find_map(|line| line.trim().parse().ok())
+}
+
+/// PID listening on `port` in `netstat -ano` output
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn parse_netstat_pid(output: &str, port: u16) -> Option<u32> {
+    let suffix = format!(":{}", port);
+    output.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields.as_slice() {
+            ["TCP", local, _, "LISTENING", pid] if local.ends_with(&suffix) => pid.parse().ok(),
+            _ => None,
+        }
+    })
+}
+
+async fn command_output(mut cmd: Command) -> Option<String> {
+    hide_console_window(&mut cmd);
+    let output = cmd.output().await.ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn listener_pid(port: u16) -> Option<u32> {
+    let mut cmd = Command::new("lsof");
+    cmd.args(["-nP", "-t", "-sTCP:LISTEN"])
+        .arg(format!("-iTCP:{}", port));
+    parse_lsof_pid(&command_output(cmd).await?)
+}
+
+#[cfg(target_os = "windows")]
+async fn listener_pid(port: u16) -> Option<u32> {
+    let mut cmd = Command::new("netstat");
+    cmd.args(["-ano", "-p", "tcp"]);
+    parse_netstat_pid(&command_output(cmd).await?, port)
+}
+
+#[cfg(not(target_os = "windows"))]
+async fn program_name(pid: u32) -> Option<String> {
+    let mut cmd = Command::new("ps");
+    cmd.args(["-o", "comm=", "-p"]).arg(pid.to_string());
+    let name = command_output(cmd).await?;
+    let name = name.trim();
+    // `comm` is a full path on macOS
+    let name = name.rsplit('/').next().unwrap_or(name);
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+#[cfg(target_os = "windows")]
+async fn program_name(pid: u32) -> Option<String> {
+    let mut cmd = Command::new("tasklist");
+    cmd.args(["/FO", "CSV", "/NH", "/FI"])
+        .arg(format!("PID eq {}", pid));
+    let listing = command_output(cmd).await?;
+    let name = listing.split(',').next()?.trim().trim_matches('"');
+    (!name.is_empty() && !name.starts_with("INFO:")).then(|| name.to_string())
+}
+
+/// The process holding `port`, if it is taken
+pub async fn check(port: u16) -> Option<PortConflict> {
+    if !is_in_use(port).await {
+        return None;
+    }
+    let pid = listener_pid(port).await;
+    let program = match pid {
+        Some(pid) => program_name(pid).await,
+        None => None,
+    };
+    Some(PortConflict { port, pid, program })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_local_ports() {
+        assert_eq!(local_port("http://localhost:5173/"), Some(5173));
+        assert_eq!(local_port("http://127.0.0.1/"), Some(80));
+        assert_eq!(local_port("https://[::1]/app"), Some(443));
+        assert_eq!(local_port("https://staging.example.com:8443/"), None);
+        assert_eq!(local_port("not a url"), None);
+    }
+
+    #[test]
+    fn parses_listener_pids() {
+        assert_eq!(parse_lsof_pid("4242\n4243\n"), Some(4242));
+        assert_eq!(parse_lsof_pid(""), None);
+
+        let netstat = "\
+Active Connections
+
+  Proto  Local Address          Foreign Address        State           PID
+  TCP    0.0.0.0:135            0.0.0.0:0              LISTENING       1024
+  TCP    127.0.0.1:51730        127.0.0.1:5173         ESTABLISHED     900
+  TCP    [::1]:5173             [::]:0                 LISTENING       7788
+";
+        assert_eq!(parse_netstat_pid(netstat, 5173), Some(7788));
+        assert_eq!(parse_netstat_pid(netstat, 3000), None);
+    }
+
+    #[test]
+    fn conflict_message_names_the_process() {
+        let conflict = PortConflict {
+            port: 3000,
+            pid: Some(4242),
+            program: Some("node".to_string()),
+        };
+        assert_eq!(
+            conflict.message(),
+            "Port 3000 for the dev server is already in use by node (PID 4242); \
+             stop it or change the screenshot URL"
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_a_bound_port() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert!(is_in_use(port).await);
+        drop(listener);
+        assert!(!is_in_use(port).await);
+    }
+}
@@ -0,0 +1,149 @@
+//! Postmortem of a session that failed or ran out of iterations: a readonly CLI
+//! call reads the session's log and changes, explains what went wrong and
+//! suggests changes to the prompt and settings.
+
+use super::ai_brainstorm::call_brainstorm_cli;
+use super::changelog::run_git;
+use crate::storage::models::CliType;
+use std::path::Path;
+
+/// Session statuses a postmortem can be generated for
+const FAILED_STATUSES: &[&str] = &["failed", "max_iterations_reached"];
+/// End of the session log quoted in the prompt
+const MAX_LOG_CHARS: usize = 20_000;
+/// End of the session's diff quoted in the prompt
+const MAX_DIFF_CHARS: usize = 12_000;
+
+fn tail(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let kept: String = text.chars().skip(count - max_chars).collect();
+    format!("... (truncated) ...\n{}", kept)
+}
+
+/// Status a session log ends with, e.g. `failed`; `None` while it runs
+pub fn session_status(log: &str) -> Option<&str> {
+    log.lines()
+        .rev()
+        .find_map(|line| line.strip_prefix("# Status: "))
+        .map(str::trim)
+}
+
+/// Error unless `status` is one a postmortem is generated for
+pub fn check_status(status: Option<&str>) -> Result<(), String> {
+    match status {
+        Some(status) if FAILED_STATUSES.contains(&status) => Ok(()),
+        Some(status) => Err(format!(
+            "Postmortems are only generated for failed sessions; this one ended as {}",
+            status
+        )),
+        None => Err("The session has not ended".to_string()),
+    }
+}
+
+/// Prompt for the postmortem of a session that ended as `status`, with the
+/// rendered session snapshot as `settings`
+pub fn postmortem_prompt(
+    task: &str,
+    status: &str,
+    settings: &str,
+    log: &str,
+    diff: &str,
+) -> String {
+    let diff = if diff.trim().is_empty() {
+        "(no changes)".to_string()
+    } else {
+        tail(diff, MAX_DIFF_CHARS)
+    };
+    format!(
+        "You are reviewing a coding agent's loop session that ended as `{status}` instead of \
+         completing its task. Do NOT modify any files.\n\n\
+         Write a postmortem in Markdown with these sections:\n\
+         - ## What went wrong: the likely root causes, pointing to log lines or changes\n\
+         - ## Prompt changes: concrete edits to the task prompt\n\
+         - ## Settings changes: concrete changes to the loop settings, such as max \
+           iterations, a validation command or the completion signal\n\
+         Be specific and brief. Output only the Markdown, without surrounding code fences.\n\n\
+         Task prompt:\n<task>\n{task}\n</task>\n\n\
+         {settings}\n\n\
+         Changes the session made:\n{diff}\n\n\
+         Session log:\n{log}\n",
+        task = task.trim(),
+        settings = settings.trim(),
+        log = tail(log, MAX_LOG_CHARS),
+    )
+}
+
+/// The postmortem in CLI output, without a code fence wrapping the whole of it
+fn extract_markdown(output: &str) -> Option<String> {
+    let trimmed = output.trim();
+    let unfenced = trimmed
+        .strip_prefix("```markdown")
+        .or_else(|| trimmed.strip_prefix("```md"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    (!unfenced.is_empty()).then(|| format!("{}\n", unfenced))
+}
+
+/// What the session changed from `from` up to `to`; empty when it is unknown
+pub async fn session_diff(project_path: &Path, from: Option<&str>, to: &str) -> String {
+    let Some(from) = from else {
+        return String::new();
+    };
+    let range = format!("{}..{}", from, to);
+    run_git(
+        project_path,
+        &["diff", "--no-color", "--stat", "--patch", &range],
+    )
+    .await
+    .unwrap_or_default()
+}
+
+/// Run `prompt` from `postmortem_prompt` through a readonly `cli_type` call
+pub async fn generate(
+    cli_type: CliType,
+    project_path: &Path,
+    prompt: &str,
+    skip_git_repo_check: bool,
+) -> Result<String, String> {
+    let output = call_brainstorm_cli(cli_type, project_path, prompt, skip_git_repo_check).await?;
+    extract_markdown(&output).ok_or_else(|| "Postmortem generation produced no output".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_failed_sessions_get_a_postmortem() {
+        let log = "# Session: s1\n[1] working\n# Status: max_iterations_reached\n";
+        assert_eq!(session_status(log), Some("max_iterations_reached"));
+        assert!(check_status(session_status(log)).is_ok());
+        assert!(check_status(Some("completed")).is_err());
+        assert!(check_status(session_status("# Session: s1\n")).is_err());
+    }
+
+    #[test]
+    fn prompt_keeps_the_end_of_the_log() {
+        let log = format!("{}LAST LINE", "x".repeat(MAX_LOG_CHARS));
+        let prompt = postmortem_prompt("Build it", "failed", "# Settings", &log, "");
+        assert!(prompt.contains("ended as `failed`"));
+        assert!(prompt.contains("<task>\nBuild it\n</task>"));
+        assert!(prompt.contains("(no changes)"));
+        assert!(prompt.ends_with("LAST LINE\n"));
+        assert!(prompt.contains("... (truncated) ..."));
+    }
+
+    #[test]
+    fn extracts_fenced_output() {
+        assert_eq!(
+            extract_markdown("```markdown\n## What went wrong\nTests\n```").as_deref(),
+            Some("## What went wrong\nTests\n")
+        );
+        assert_eq!(extract_markdown(" \n"), None);
+    }
+}
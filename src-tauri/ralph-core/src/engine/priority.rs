@@ -0,0 +1,141 @@
+//! Lower scheduling priority for loop CLIs so the machine stays usable while a
+//! loop grinds in the background. Windows starts the CLI in a lower priority
+//! class, and Unix lowers the nice value (and on Linux, the I/O class) in the
+//! forked child before it execs. Either way the CLI and everything it spawns
+//! start at the lower priority. Terminal sessions are spawned by portable-pty,
+//! which has no pre-exec hook, so those are lowered by pid right after spawn.
+
+use crate::storage::models::ProcessPriority;
+use tokio::process::Command;
+
+/// Nice value for a priority
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+fn nice_value(priority: ProcessPriority) -> Option<i32> {
+    match priority {
+        ProcessPriority::Normal => None,
+        ProcessPriority::Low => Some(10),
+        ProcessPriority::Background => Some(19),
+    }
+}
+
+/// `ioprio_set` value: lowest best-effort, or idle-only for background loops
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn io_priority(priority: ProcessPriority) -> Option<i32> {
+    const IOPRIO_CLASS_SHIFT: i32 = 13;
+    const IOPRIO_CLASS_BE: i32 = 2;
+    const IOPRIO_CLASS_IDLE: i32 = 3;
+    match priority {
+        ProcessPriority::Normal => None,
+        ProcessPriority::Low => Some((IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT) | 7),
+        ProcessPriority::Background => Some(IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT),
+    }
+}
+
+/// Start `cmd` in the priority class for `priority`
+#[cfg(target_os = "windows")]
+pub fn apply_to_command(cmd: &mut Command, priority: ProcessPriority) {
+    const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x0000_4000;
+    const IDLE_PRIORITY_CLASS: u32 = 0x0000_0040;
+    let class = match priority {
+        ProcessPriority::Normal => return,
+        ProcessPriority::Low => BELOW_NORMAL_PRIORITY_CLASS,
+        ProcessPriority::Background => IDLE_PRIORITY_CLASS,
+    };
+    // Replaces the flags set by `hide_console_window`, so keep its flag
+    cmd.creation_flags(crate::adapters::CREATE_NO_WINDOW | class);
+}
+
+/// Lower the priority of `pid`, or of the calling process when `pid` is 0.
+/// Only async-signal-safe calls, since it also runs between fork and exec.
+#[cfg(unix)]
+fn lower(pid: u32, priority: ProcessPriority) -> std::io::Result<()> {
+    let Some(nice) = nice_value(priority) else {
+        return Ok(());
+    };
+    // SAFETY: plain syscalls on integer arguments. Raising the nice value back
+    // needs privileges, so a process that is already nicer is left alone.
+    unsafe {
+        let who = pid as libc::id_t;
+        if libc::getpriority(libc::PRIO_PROCESS as _, who) < nice
+            && libc::setpriority(libc::PRIO_PROCESS as _, who, nice) != 0
+        {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(io_priority) = io_priority(priority) {
+        const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+        // SAFETY: as above; glibc has no wrapper for ioprio_set
+        let result = unsafe {
+            libc::syscall(
+                libc::SYS_ioprio_set,
+                IOPRIO_WHO_PROCESS,
+                pid as libc::c_long,
+                io_priority as libc::c_long,
+            )
+        };
+        if result != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Start `cmd` at the lower priority, so it and all of its children run there
+#[cfg(unix)]
+pub fn apply_to_command(cmd: &mut Command, priority: ProcessPriority) {
+    if nice_value(priority).is_none() {
+        return;
+    }
+    // SAFETY: `lower` only makes async-signal-safe syscalls
+    unsafe {
+        cmd.pre_exec(move || lower(0, priority));
+    }
+}
+
+/// Lower the priority of a CLI already started in a terminal
+#[cfg(unix)]
+pub fn apply_to_process(pid: u32, priority: ProcessPriority) -> Result<(), String> {
+    lower(pid, priority).map_err(|e| e.to_string())
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_priorities_to_nice_and_io_priority() {
+        assert_eq!(nice_value(ProcessPriority::Normal), None);
+        assert_eq!(nice_value(ProcessPriority::Low), Some(10));
+        assert_eq!(io_priority(ProcessPriority::Background), Some(3 << 13));
+    }
+
+    fn nice_of(pid: u32) -> i32 {
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).unwrap();
+        // Field 19 is the nice value; the command name in field 2 has no spaces here
+        stat.split_whitespace().nth(18).unwrap().parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn starts_a_command_at_low_priority() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("5");
+        apply_to_command(&mut cmd, ProcessPriority::Low);
+        let mut child = cmd.spawn().unwrap();
+        let nice = nice_of(child.id().unwrap());
+        let _ = child.kill().await;
+        assert!(nice >= 10, "nice value was {}", nice);
+    }
+
+    #[tokio::test]
+    async fn renices_a_running_process() {
+        let mut child = Command::new("sleep").arg("5").spawn().unwrap();
+        let pid = child.id().unwrap();
+        apply_to_process(pid, ProcessPriority::Low).unwrap();
+
+        let nice = nice_of(pid);
+        let _ = child.kill().await;
+        assert!(nice >= 10, "nice value was {}", nice);
+    }
+}
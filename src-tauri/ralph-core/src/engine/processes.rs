@@ -0,0 +1,333 @@
+use crate::adapters::hide_console_window;
+use crate::storage;
+use crate::storage::models::{OrphanFailure, OrphanReport, ProcessRecord};
+use chrono::Utc;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+/// Serializes read-modify-write of the process registry across running loops
+static REGISTRY_LOCK: Mutex<()> = Mutex::new(());
+
+fn update_records(update: impl FnOnce(&mut Vec<ProcessRecord>)) {
+    let _lock = REGISTRY_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut records = storage::load_process_records().unwrap_or_default();
+    update(&mut records);
+    let _ = storage::save_process_records(&records);
+}
+
+/// Keeps a spawned CLI in the process registry until dropped
+pub struct TrackedProcess {
+    pid: Option<u32>,
+}
+
+impl Drop for TrackedProcess {
+    fn drop(&mut self) {
+        if let Some(pid) = self.pid {
+            let app_pid = std::process::id();
+            update_records(|records| records.retain(|r| r.pid != pid || r.app_pid != app_pid));
+        }
+    }
+}
+
+/// Record a spawned CLI so it can be found again if Ralph exits without reaping it
+pub fn track(project_id: &str, pid: Option<u32>, program: &Path) -> TrackedProcess {
+    if let Some(pid) = pid {
+        let program = program
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        update_records(|records| {
+            records.push(ProcessRecord {
+                pid,
+                program,
+                project_id: project_id.to_string(),
+                app_pid: std::process::id(),
+                started_at: Utc::now(),
+                process_started: None,
+                command_line: None,
+            })
+        });
+        // Read off the spawning thread; a record without an identity is never reaped
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(record_identity(pid));
+        }
+    }
+    TrackedProcess { pid }
+}
+
+async fn record_identity(pid: u32) {
+    let Some(found) = identity(pid).await else {
+        return;
+    };
+    let app_pid = std::process::id();
+    update_records(|records| {
+        for record in records
+            .iter_mut()
+            .filter(|r| r.pid == pid && r.app_pid == app_pid)
+        {
+            record.process_started = Some(found.started.clone());
+            record.command_line = Some(found.command_line.clone());
+        }
+    });
+}
+
+/// What a PID is running, to tell a tracked process from a later one that
+/// reuses its PID
+#[derive(Debug, Clone, PartialEq)]
+struct ProcessIdentity {
+    started: String,
+    command_line: String,
+}
+
+/// Start time and full command line of `pid`; `None` when it is not running
+async fn identity(pid: u32) -> Option<ProcessIdentity> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("powershell");
+        cmd.args(["-NoProfile", "-NonInteractive", "-Command"])
+            .arg(format!(
+                "$p = Get-CimInstance Win32_Process -Filter 'ProcessId={}'; \
+             if ($p) {{ $p.CreationDate.ToUniversalTime().ToString('o'); $p.CommandLine }}",
+                pid
+            ));
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("ps");
+        // `lstart` is five fields in the C locale, e.g. `Thu Oct 16 10:00:00 2026`
+        cmd.env("LC_ALL", "C")
+            .args(["-o", "lstart=", "-o", "args=", "-p"])
+            .arg(pid.to_string());
+        cmd
+    };
+    hide_console_window(&mut cmd);
+    let output = cmd.output().await.ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_identity(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "windows"))]
+fn parse_identity(listing: &str) -> Option<ProcessIdentity> {
+    let fields: Vec<&str> = listing.split_whitespace().collect();
+    if fields.len() < 6 {
+        return None;
+    }
+    Some(ProcessIdentity {
+        started: fields[..5].join(" "),
+        command_line: fields[5..].join(" "),
+    })
+}
+
+#[cfg(target_os = "windows")]
+fn parse_identity(listing: &str) -> Option<ProcessIdentity> {
+    let (started, command_line) = listing.trim().split_once('\n')?;
+    Some(ProcessIdentity {
+        started: started.trim().to_string(),
+        command_line: command_line.trim().to_string(),
+    })
+}
+
+/// Whether the tracked process is still the one running under its PID: both
+/// the start time and the command line must match what was recorded
+async fn is_running(record: &ProcessRecord) -> bool {
+    let (Some(started), Some(command_line)) = (&record.process_started, &record.command_line)
+    else {
+        return false;
+    };
+    identity(record.pid)
+        .await
+        .is_some_and(|found| &found.started == started && &found.command_line == command_line)
+}
+
+/// Whether the Ralph instance with this PID is still running, e.g. a second window
+async fn app_running(app_pid: u32) -> bool {
+    let program = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.file_stem().map(|s| s.to_string_lossy().to_lowercase()))
+        .unwrap_or_default();
+    !program.is_empty()
+        && identity(app_pid)
+            .await
+            .is_some_and(|found| found.command_line.to_lowercase().contains(&program))
+}
+
+/// CLI processes left behind by Ralph runs that have exited and are still alive;
+/// dead entries, and those whose PID now runs something else, are pruned
+pub async fn scan_orphans() -> Vec<ProcessRecord> {
+    let app_pid = std::process::id();
+    let records = storage::load_process_records().unwrap_or_default();
+
+    let mut orphans = Vec::new();
+    let mut dead = Vec::new();
+    let mut live_apps = HashMap::new();
+    for record in records.into_iter().filter(|r| r.app_pid != app_pid) {
+        let app_alive = match live_apps.entry(record.app_pid) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => *entry.insert(app_running(record.app_pid).await),
+        };
+        if app_alive {
+            continue;
+        }
+        if is_running(&record).await {
+            orphans.push(record);
+        } else {
+            dead.push(record);
+        }
+    }
+    if !dead.is_empty() {
+        update_records(|records| {
+            records.retain(|r| !dead.contains(r));
+        });
+    }
+    orphans
+}
+
+/// Terminate an orphaned process and drop it from the registry
+pub async fn terminate(record: &ProcessRecord) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/T", "/F", "/PID"]).arg(record.pid.to_string());
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("kill");
+        cmd.arg("-TERM").arg(record.pid.to_string());
+        cmd
+    };
+    hide_console_window(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to terminate process {}: {}", record.pid, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "Failed to terminate process {}: {}",
+            record.pid,
+            stderr.trim()
+        ));
+    }
+    let (pid, app_pid) = (record.pid, record.app_pid);
+    update_records(|records| records.retain(|r| r.pid != pid || r.app_pid != app_pid));
+    Ok(())
+}
+
+/// Terminate every orphaned process, reporting what was cleaned up
+pub async fn reap_orphans() -> OrphanReport {
+    let mut report = OrphanReport::default();
+    for record in scan_orphans().await {
+        match terminate(&record).await {
+            Ok(()) => report.terminated.push(record),
+            Err(error) => report.failed.push(OrphanFailure {
+                process: record,
+                error,
+            }),
+        }
+    }
+    report
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env_async, EnvVarGuard};
+    use tempfile::tempdir;
+
+    /// A record of `pid` left by an app instance that is gone
+    async fn orphan_record(pid: u32) -> ProcessRecord {
+        let found = identity(pid).await.unwrap();
+        assert!(found.command_line.starts_with("sleep"));
+        ProcessRecord {
+            pid,
+            program: "sleep".to_string(),
+            project_id: "project".to_string(),
+            app_pid: 0,
+            started_at: Utc::now(),
+            process_started: Some(found.started),
+            command_line: Some(found.command_line),
+        }
+    }
+
+    #[test]
+    fn parses_start_time_and_command_line() {
+        let found = parse_identity("Thu Oct 16 10:00:00 2026 claude  -p  hi\n").unwrap();
+        assert_eq!(found.started, "Thu Oct 16 10:00:00 2026");
+        assert_eq!(found.command_line, "claude -p hi");
+        assert!(parse_identity("").is_none());
+    }
+
+    #[tokio::test]
+    async fn tracks_processes_and_finds_orphans_from_earlier_runs() {
+        let _env_lock = lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        // A PID that cannot exist, so no identity is recorded in the background
+        let guard = track("project", Some(u32::MAX - 2), Path::new("/usr/bin/claude"));
+        let records = storage::load_process_records().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].program, "claude");
+        // Processes owned by this app are never reported as orphans
+        assert!(scan_orphans().await.is_empty());
+        drop(guard);
+        assert!(storage::load_process_records().unwrap().is_empty());
+
+        // A live process recorded by an earlier app instance
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let stale = orphan_record(child.id()).await;
+        let gone = ProcessRecord {
+            pid: u32::MAX - 1,
+            ..stale.clone()
+        };
+        // The PID is alive but runs something other than what was recorded
+        let reused = ProcessRecord {
+            command_line: Some("sleep 31".to_string()),
+            ..stale.clone()
+        };
+        let unidentified = ProcessRecord {
+            process_started: None,
+            command_line: None,
+            ..stale.clone()
+        };
+        storage::save_process_records(&[stale.clone(), gone, reused, unidentified]).unwrap();
+
+        let orphans = scan_orphans().await;
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].pid, stale.pid);
+        assert_eq!(storage::load_process_records().unwrap().len(), 1);
+
+        terminate(&orphans[0]).await.unwrap();
+        let _ = child.wait();
+        assert!(storage::load_process_records().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn reaps_orphans_into_a_report() {
+        let _env_lock = lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        storage::save_process_records(&[orphan_record(child.id()).await]).unwrap();
+
+        let report = reap_orphans().await;
+        let _ = child.wait();
+        assert_eq!(report.terminated.len(), 1);
+        assert!(report.failed.is_empty());
+        assert!(reap_orphans().await.is_empty());
+    }
+}
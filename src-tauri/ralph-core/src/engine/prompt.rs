@@ -0,0 +1,482 @@
+use crate::engine::questions::AskedQuestion;
+use crate::engine::secrets::Finding;
+use crate::engine::validation::Failure;
+use thiserror::Error;
+
+/// Marker line that opens the auto-decision policy block
+pub const AUTO_DECIDE_MARKER: &str = "[Ralph Auto-Decision Policy]";
+
+/// Default policy text injected ahead of the task prompt
+pub fn default_auto_decide_policy() -> String {
+    [
+        "You MUST NOT ask the user any questions during execution.",
+        "Assume the user is away and cannot respond.",
+        "If multiple valid choices exist, prefer the more maintainable, clear, engineering-oriented option.",
+        "If required information is missing, make reasonable assumptions and proceed without blocking.",
+        "Never pause for clarification; log assumptions in the output when necessary.",
+    ]
+    .join("\n")
+}
+
+/// Remove a policy block persisted into the prompt by older versions
+pub fn strip_legacy_auto_decide(prompt: &str) -> String {
+    let trimmed = prompt.trim_start();
+    if !trimmed.starts_with(AUTO_DECIDE_MARKER) {
+        return prompt.to_string();
+    }
+    match trimmed.find("\n\n") {
+        Some(end) => trimmed[end..].trim_start().to_string(),
+        None => String::new(),
+    }
+}
+
+/// Prepend the auto-decision policy to a prompt, if a policy is set
+pub fn apply_auto_decide_policy(prompt: &str, policy: Option<&str>) -> String {
+    let prompt = strip_legacy_auto_decide(prompt);
+    match policy.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(policy) => format!("{AUTO_DECIDE_MARKER}\n{policy}\n\n{}", prompt.trim()),
+        None => prompt,
+    }
+}
+
+/// Prepend a codebase map produced by the warm-up pass
+pub fn apply_codebase_context(prompt: &str, context: Option<&str>) -> String {
+    match context.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(context) => format!("## Codebase Context\n{context}\n\n## Task\n{prompt}"),
+        None => prompt.to_string(),
+    }
+}
+
+/// Display name for an agent language code; `None` for "auto" or unknown codes
+pub fn language_name(code: &str) -> Option<&'static str> {
+    match code.trim().to_ascii_lowercase().as_str() {
+        "zh" => Some("Chinese"),
+        "en" => Some("English"),
+        "ja" => Some("Japanese"),
+        "ko" => Some("Korean"),
+        _ => None,
+    }
+}
+
+/// Instruction pinning the language agents write in, if one is set
+pub fn language_instruction(code: Option<&str>) -> Option<String> {
+    let name = language_name(code?)?;
+    Some(format!(
+        "Write all questions, explanations, summaries and progress notes in {name}, regardless of the language used in the task, the conversation or the code."
+    ))
+}
+
+/// Point the agent at a screenshot of the app as the previous iteration left it
+pub fn apply_screenshot(prompt: &str, screenshot: Option<&str>) -> String {
+    match screenshot {
+        Some(path) => format!(
+            "{prompt}\n\n## Current UI\nA screenshot of the running app after the previous iteration is at {path}. \
+             Open it and check the UI against the task before continuing."
+        ),
+        None => prompt.to_string(),
+    }
+}
+
+/// Ask the agent to take out the secrets that blocked the previous commit
+pub fn apply_blocked_secrets(prompt: &str, findings: &[Finding]) -> String {
+    if findings.is_empty() {
+        return prompt.to_string();
+    }
+    let locations: Vec<String> = findings
+        .iter()
+        .map(|f| format!("- {}:{} ({})", f.path, f.line, f.rule))
+        .collect();
+    format!(
+        "{prompt}\n\n## Secrets\nThe previous iteration's changes were not committed because they appear \
+         to contain secrets:\n{}\nRemove them before doing anything else: read the values from environment \
+         variables or an ignored local config file instead. If a finding is not a real secret, replace it \
+         with an obvious placeholder.",
+        locations.join("\n")
+    )
+}
+
+/// Answer the question the previous iteration ended with: nobody will, so decide and move on
+pub fn apply_unanswered_question(prompt: &str, asked: Option<&AskedQuestion>) -> String {
+    let Some(asked) = asked else {
+        return prompt.to_string();
+    };
+    let repeat = match asked.first_asked {
+        Some(first) => {
+            format!(" You already asked this in iteration {first} and it will not be answered.")
+        }
+        None => String::new(),
+    };
+    format!(
+        "{prompt}\n\n## Your Question\nThe previous iteration ended with a question:\n\n{}\n\n\
+         No one is available to answer during this loop.{repeat} Proceed with your best judgment; \
+         do not ask again. Note the assumption you made and continue with the task.",
+        asked.question
+    )
+}
+
+/// Send the agent back to work when its completion signal did not pass the check
+pub fn apply_failed_completion_check(prompt: &str, command: Option<&str>) -> String {
+    let Some(command) = command else {
+        return prompt.to_string();
+    };
+    format!(
+        "{prompt}\n\n## Completion Check Failed\nThe previous iteration signalled completion, but \
+         `{command}` failed, so the task is not done. Run it, fix the failures, and signal \
+         completion again only once it passes."
+    )
+}
+
+/// Put the summary of the previous iteration's progress in front of the prompt
+pub fn apply_previous_progress(prompt: &str, summary: Option<&str>) -> String {
+    let Some(summary) = summary.map(str::trim).filter(|s| !s.is_empty()) else {
+        return prompt.to_string();
+    };
+    format!(
+        "## Previous Progress\nA summary of what the previous iteration did. Check it against the \
+         repository before relying on it:\n{summary}\n\n{prompt}"
+    )
+}
+
+/// Hand the validation hook's failure output to the agent
+pub fn apply_failed_validation(prompt: &str, failure: Option<&Failure>) -> String {
+    let Some(failure) = failure else {
+        return prompt.to_string();
+    };
+    format!(
+        "{prompt}\n\n## Validation Failed\nPrevious iteration failed validation: `{}` reported:\n\n\
+         ```\n{}\n```\n\nFix these failures before continuing with the task.",
+        failure.command, failure.output
+    )
+}
+
+/// Hand the review that kept the previous iteration's changes uncommitted to the agent
+pub fn apply_review_comments(prompt: &str, comments: Option<&str>) -> String {
+    let Some(comments) = comments else {
+        return prompt.to_string();
+    };
+    format!(
+        "{prompt}\n\n## Review Blockers\nThe previous iteration's changes were not committed: a \
+         reviewer flagged these blockers:\n\n{}\n\nAddress them before continuing with the task; \
+         the changes are still in the working tree.",
+        comments.trim()
+    )
+}
+
+/// List learnings from past sessions the user chose to include
+pub fn apply_learnings(prompt: &str, learnings: &[String]) -> String {
+    if learnings.is_empty() {
+        return prompt.to_string();
+    }
+    let items: Vec<String> = learnings
+        .iter()
+        .map(|l| format!("- {}", l.trim()))
+        .collect();
+    format!(
+        "{prompt}\n\n## Learnings From Past Sessions\nEarlier sessions, possibly in other projects, \
+         learned the following. Apply what is relevant instead of rediscovering it:\n{}",
+        items.join("\n")
+    )
+}
+
+/// Tell the agent the network is off limits, when the project blocks it
+pub fn apply_network_policy(prompt: &str, blocked: bool) -> String {
+    if !blocked {
+        return prompt.to_string();
+    }
+    format!(
+        "{prompt}\n\n## Network\nNetwork access is disabled for this project. Do not download packages, \
+         clone or fetch repositories, or contact any host; work only with what is already on disk. \
+         If the task cannot be finished offline, say so and stop."
+    )
+}
+
+/// Append a language section to a prompt, if a language is set
+pub fn apply_language(prompt: &str, code: Option<&str>) -> String {
+    match language_instruction(code) {
+        Some(instruction) => format!("{prompt}\n\n## Language\n{instruction}"),
+        None => prompt.to_string(),
+    }
+}
+
+/// List attached images after the prompt, asking image-capable CLIs to view them
+pub fn apply_attachments(prompt: &str, attachments: &[String], can_view_images: bool) -> String {
+    if attachments.is_empty() {
+        return prompt.to_string();
+    }
+    let intro = if can_view_images {
+        "Open and view each of these images before starting; they are part of the task:"
+    } else {
+        "The task references these image files (screenshots or mockups) in the project:"
+    };
+    let list: Vec<String> = attachments.iter().map(|a| format!("- {a}")).collect();
+    format!("{prompt}\n\n## Attachments\n{intro}\n{}", list.join("\n"))
+}
+
+/// Prompts shorter than this rarely give an agent enough to work with
+const MIN_PROMPT_CHARS: usize = 200;
+/// Prompts longer than this crowd out room for the agent's own work
+const MAX_PROMPT_TOKENS: usize = 8000;
+
+/// Flag common problems in a task prompt before it is accepted
+pub fn lint_prompt(prompt: &str, completion_signal: &str) -> Vec<String> {
+    let trimmed = prompt.trim();
+    if trimmed.is_empty() {
+        return vec!["Prompt is empty".to_string()];
+    }
+    let mut warnings = Vec::new();
+    if !trimmed.contains(completion_signal) {
+        warnings.push(format!(
+            "Prompt does not mention the completion signal {}",
+            completion_signal
+        ));
+    }
+    if !trimmed.to_lowercase().contains("test") {
+        warnings.push("Prompt does not describe how to test or validate the work".to_string());
+    }
+    if trimmed.chars().count() < MIN_PROMPT_CHARS {
+        warnings.push("Prompt is very short; add goals, features and success criteria".to_string());
+    }
+    let tokens = super::context::estimate_tokens(trimmed);
+    if tokens > MAX_PROMPT_TOKENS {
+        warnings.push(format!(
+            "Prompt is about {} tokens; consider moving details into a design doc",
+            tokens
+        ));
+    }
+    warnings
+}
+
+/// Pick the template for an iteration and fill in `{base_prompt}` and `{iteration}`
+pub fn render_iteration_prompt(
+    base_prompt: &str,
+    iteration: u32,
+    first_template: Option<&str>,
+    subsequent_template: Option<&str>,
+) -> String {
+    let template = if iteration <= 1 {
+        first_template
+    } else {
+        subsequent_template
+    };
+    match template.filter(|t| !t.trim().is_empty()) {
+        Some(template) => template
+            .replace("{base_prompt}", base_prompt)
+            .replace("{iteration}", &iteration.to_string()),
+        None => base_prompt.to_string(),
+    }
+}
+
+/// Why text from the UI was refused
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PromptInputError {
+    #[error("Prompt is {size} bytes, over the {limit} byte limit")]
+    TooLarge { size: usize, limit: usize },
+}
+
+/// Clean prompt text pasted into the UI before it is saved or sent: line endings
+/// become `\n`, NULs and other control characters (and a stray BOM) are dropped,
+/// and the result must fit in `max_bytes` (0 means no limit)
+pub fn sanitize_input(text: &str, max_bytes: usize) -> Result<String, PromptInputError> {
+    let cleaned: String = text
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .chars()
+        .filter(|c| matches!(c, '\n' | '\t') || !(c.is_control() || *c == '\u{feff}'))
+        .collect();
+    if max_bytes > 0 && cleaned.len() > max_bytes {
+        return Err(PromptInputError::TooLarge {
+            size: cleaned.len(),
+            limit: max_bytes,
+        });
+    }
+    Ok(cleaned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn apply_screenshot_appends_path() {
+        assert_eq!(apply_screenshot("Build it", None), "Build it");
+        let prompt = apply_screenshot("Build it", Some("/data/ralph-screenshot.png"));
+        assert!(prompt.starts_with("Build it\n\n## Current UI\n"));
+        assert!(prompt.contains("/data/ralph-screenshot.png. Open it"));
+    }
+
+    #[test]
+    fn apply_blocked_secrets_lists_locations_without_values() {
+        assert_eq!(apply_blocked_secrets("Build it", &[]), "Build it");
+        let prompt = apply_blocked_secrets(
+            "Build it",
+            &[Finding {
+                rule: "AWS access key",
+                path: "src/config.ts".to_string(),
+                line: 5,
+                redacted: "AKIA****".to_string(),
+            }],
+        );
+        assert!(prompt.contains("- src/config.ts:5 (AWS access key)"));
+        assert!(!prompt.contains("AKIA"));
+    }
+
+    #[test]
+    fn apply_unanswered_question_mentions_repeats() {
+        let asked = AskedQuestion {
+            question: "Should I use SQLite?".to_string(),
+            iteration: 4,
+            first_asked: Some(2),
+        };
+        let prompt = apply_unanswered_question("Build it", Some(&asked));
+        assert!(prompt.contains("Should I use SQLite?"));
+        assert!(prompt.contains("already asked this in iteration 2"));
+        assert!(prompt.contains("do not ask again"));
+        assert_eq!(apply_unanswered_question("Build it", None), "Build it");
+    }
+
+    #[test]
+    fn apply_failed_validation_quotes_the_output() {
+        let failure = Failure {
+            command: "cargo check".to_string(),
+            output: "error[E0308]: mismatched types".to_string(),
+        };
+        let prompt = apply_failed_validation("Build it", Some(&failure));
+        assert!(prompt.contains("Previous iteration failed validation: `cargo check`"));
+        assert!(prompt.contains("```\nerror[E0308]: mismatched types\n```"));
+        assert_eq!(apply_failed_validation("Build it", None), "Build it");
+    }
+
+    #[test]
+    fn apply_review_comments_lists_the_blockers() {
+        let prompt = apply_review_comments("Build it", Some("- src/a.rs: remove dbg!\n"));
+        assert!(prompt.starts_with("Build it\n\n## Review Blockers"));
+        assert!(prompt.contains("\n\n- src/a.rs: remove dbg!\n\n"));
+        assert_eq!(apply_review_comments("Build it", None), "Build it");
+    }
+
+    #[test]
+    fn apply_previous_progress_goes_first() {
+        let prompt = apply_previous_progress("Build it", Some("- Added the login form\n"));
+        assert!(prompt.starts_with("## Previous Progress\n"));
+        assert!(prompt.ends_with("- Added the login form\n\nBuild it"));
+        assert_eq!(apply_previous_progress("Build it", Some("  ")), "Build it");
+        assert_eq!(apply_previous_progress("Build it", None), "Build it");
+    }
+
+    #[test]
+    fn apply_network_policy_only_when_blocked() {
+        assert_eq!(apply_network_policy("Build it", false), "Build it");
+        assert!(apply_network_policy("Build it", true)
+            .starts_with("Build it\n\n## Network\nNetwork access is disabled"));
+    }
+
+    #[test]
+    fn apply_policy_prepends_marker_and_text() {
+        let prompt = apply_auto_decide_policy("Build it", Some("Do not ask."));
+        assert_eq!(
+            prompt,
+            format!("{AUTO_DECIDE_MARKER}\nDo not ask.\n\nBuild it")
+        );
+    }
+
+    #[test]
+    fn apply_policy_without_policy_leaves_prompt() {
+        assert_eq!(apply_auto_decide_policy("Build it", None), "Build it");
+        assert_eq!(apply_auto_decide_policy("Build it", Some("  ")), "Build it");
+    }
+
+    #[test]
+    fn render_uses_first_template_on_iteration_one() {
+        let first = Some("Read the code and write PLAN.md.\n\n{base_prompt}");
+        let next = Some("Iteration {iteration}: continue PLAN.md.\n\n{base_prompt}");
+        assert_eq!(
+            render_iteration_prompt("Goal", 1, first, next),
+            "Read the code and write PLAN.md.\n\nGoal"
+        );
+        assert_eq!(
+            render_iteration_prompt("Goal", 3, first, next),
+            "Iteration 3: continue PLAN.md.\n\nGoal"
+        );
+    }
+
+    #[test]
+    fn render_falls_back_to_base_prompt() {
+        assert_eq!(render_iteration_prompt("Goal", 1, None, None), "Goal");
+        assert_eq!(
+            render_iteration_prompt("Goal", 2, Some("{base_prompt}!"), Some("")),
+            "Goal"
+        );
+    }
+
+    #[test]
+    fn codebase_context_is_prepended() {
+        assert_eq!(
+            apply_codebase_context("Goal", Some("- src/")),
+            "## Codebase Context\n- src/\n\n## Task\nGoal"
+        );
+        assert_eq!(apply_codebase_context("Goal", None), "Goal");
+    }
+
+    #[test]
+    fn language_is_appended_only_when_explicit() {
+        let prompt = apply_language("Goal", Some("ja"));
+        assert!(prompt.starts_with("Goal\n\n## Language\n"));
+        assert!(prompt.contains("Japanese"));
+        assert_eq!(apply_language("Goal", Some("auto")), "Goal");
+        assert_eq!(apply_language("Goal", None), "Goal");
+    }
+
+    #[test]
+    fn lint_flags_missing_signal_and_tests() {
+        let signal = "<done>COMPLETE</done>";
+        let warnings = lint_prompt("Build a snake game", signal);
+        assert!(warnings.iter().any(|w| w.contains(signal)));
+        assert!(warnings.iter().any(|w| w.contains("test")));
+        assert!(warnings.iter().any(|w| w.contains("very short")));
+
+        let good = format!(
+            "{}\nRun `npm test` and make sure all unit tests pass.\nOutput {signal} when done.",
+            "Build a snake game with smooth controls and a score board. ".repeat(4)
+        );
+        assert!(lint_prompt(&good, signal).is_empty());
+        assert_eq!(
+            lint_prompt("  ", signal),
+            vec!["Prompt is empty".to_string()]
+        );
+    }
+
+    #[test]
+    fn attachments_are_listed_after_prompt() {
+        let images = vec!["/p/.ralph/attachments/bug.png".to_string()];
+        let native = apply_attachments("Fix it", &images, true);
+        assert!(native.starts_with("Fix it\n\n## Attachments\nOpen and view"));
+        assert!(native.ends_with("- /p/.ralph/attachments/bug.png"));
+        assert!(
+            apply_attachments("Fix it", &images, false).contains("references these image files")
+        );
+        assert_eq!(apply_attachments("Fix it", &[], true), "Fix it");
+    }
+
+    #[test]
+    fn legacy_policy_block_is_not_duplicated() {
+        let legacy = format!("{AUTO_DECIDE_MARKER}\nOld policy\nMore\n\nBuild it");
+        assert_eq!(strip_legacy_auto_decide(&legacy), "Build it");
+        let prompt = apply_auto_decide_policy(&legacy, Some("New policy"));
+        assert_eq!(prompt.matches(AUTO_DECIDE_MARKER).count(), 1);
+        assert!(prompt.contains("New policy"));
+        assert!(!prompt.contains("Old policy"));
+    }
+
+    #[test]
+    fn sanitizes_pasted_input() {
+        assert_eq!(
+            sanitize_input("\u{feff}Fix\r\nthe\rbug\0\u{1b}[0m\tnow", 0).unwrap(),
+            "Fix\nthe\nbug[0m\tnow"
+        );
+        assert_eq!(sanitize_input("héllo", 6).unwrap(), "héllo");
+        assert_eq!(
+            sanitize_input("héllo!", 6),
+            Err(PromptInputError::TooLarge { size: 7, limit: 6 })
+        );
+    }
+}
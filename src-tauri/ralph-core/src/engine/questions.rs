@@ -0,0 +1,174 @@
+//! Questions agents end an iteration with despite the auto-decide policy.
+//! Nobody answers them during a loop, so the next iteration is told to
+//! proceed on its own judgment, and more firmly when it asks again.
+
+/// Longest question quoted back to the agent
+const MAX_QUESTION_CHARS: usize = 500;
+
+/// Openings of a final paragraph that asks for a decision without a question mark
+const ASKING_PHRASES: &[&str] = &[
+    "should i ",
+    "shall i ",
+    "would you like",
+    "do you want me to",
+    "let me know if",
+    "let me know which",
+    "let me know whether",
+    "let me know how",
+    "please confirm",
+    "please let me know",
+];
+
+/// The agent's last message in an iteration: assistant lines after its last tool use
+#[derive(Debug, Default)]
+pub struct FinalMessage {
+    text: String,
+    ended: bool,
+}
+
+impl FinalMessage {
+    pub fn observe(&mut self, is_assistant: bool, content: &str) {
+        if content.trim().is_empty() {
+            // Keeps paragraphs apart for CLIs that stream the message line by line
+            if is_assistant && !self.ended && !self.text.is_empty() {
+                self.text.push('\n');
+            }
+            return;
+        }
+        if !is_assistant {
+            self.ended = true;
+            return;
+        }
+        if self.ended {
+            self.text.clear();
+            self.ended = false;
+        }
+        super::completion::push_output(&mut self.text, content);
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Markdown emphasis and quotes around a sentence
+fn plain(line: &str) -> &str {
+    line.trim()
+        .trim_start_matches(['>', '-', '*', '#', ' '])
+        .trim_end_matches(['*', '_', '`', '"', ')', ' '])
+}
+
+/// The question a message ends with: its last paragraph, when that asks something
+pub fn final_question(message: &str) -> Option<String> {
+    let paragraph = message
+        .trim()
+        .rsplit("\n\n")
+        .map(str::trim)
+        .find(|p| !p.is_empty())?;
+    let last_line = plain(paragraph.lines().last()?);
+    let lower = paragraph.to_lowercase();
+    let asks = last_line.ends_with(['?', '？'])
+        || ASKING_PHRASES.iter().any(|phrase| lower.contains(phrase));
+    if !asks {
+        return None;
+    }
+    let mut question: String = paragraph.chars().take(MAX_QUESTION_CHARS).collect();
+    if question.len() < paragraph.len() {
+        question.push('…');
+    }
+    Some(question)
+}
+
+/// A question from an earlier iteration and when it was asked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AskedQuestion {
+    pub question: String,
+    pub iteration: u32,
+    /// Iteration the same question was first asked in, when it is a repeat
+    pub first_asked: Option<u32>,
+}
+
+/// Questions asked so far in a session
+#[derive(Debug, Default)]
+pub struct QuestionHistory {
+    asked: Vec<(String, u32)>,
+}
+
+fn normalize(question: &str) -> String {
+    question
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric() || c.is_whitespace())
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl QuestionHistory {
+    pub fn record(&mut self, question: String, iteration: u32) -> AskedQuestion {
+        let key = normalize(&question);
+        let first_asked = self
+            .asked
+            .iter()
+            .find(|(asked, _)| *asked == key)
+            .map(|(_, iteration)| *iteration);
+        if first_asked.is_none() {
+            self.asked.push((key, iteration));
+        }
+        AskedQuestion {
+            question,
+            iteration,
+            first_asked,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_message_starts_after_the_last_tool_use() {
+        let mut message = FinalMessage::default();
+        message.observe(true, "Let me look at the parser.");
+        message.observe(false, "$ cargo test");
+        message.observe(true, "Tests pass.");
+        message.observe(true, "Should I also update the docs?");
+        assert_eq!(
+            message.text(),
+            "Tests pass.\nShould I also update the docs?\n"
+        );
+    }
+
+    #[test]
+    fn detects_questions_in_the_last_paragraph() {
+        assert_eq!(
+            final_question(
+                "Done with the parser.\n\nWhich database should I use, **SQLite or Postgres?**"
+            )
+            .as_deref(),
+            Some("Which database should I use, **SQLite or Postgres?**")
+        );
+        assert!(final_question("Would you like me to add tests as well.").is_some());
+        assert!(final_question("Why was this broken? The cache was stale.\n\nFixed it.").is_none());
+        assert!(final_question("All tasks are complete.").is_none());
+    }
+
+    #[test]
+    fn remembers_repeated_questions() {
+        let mut history = QuestionHistory::default();
+        assert_eq!(
+            history.record("Use SQLite?".to_string(), 2).first_asked,
+            None
+        );
+        assert_eq!(
+            history.record("use sqlite ?".to_string(), 4).first_asked,
+            Some(2)
+        );
+        assert_eq!(
+            history.record("Use SQLite?".to_string(), 5).first_asked,
+            Some(2)
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! README written after a loop completes: a readonly CLI call describes what
+//! was built, how to run it and how to test it, and Ralph saves the result.
+
+use crate::engine::ai_brainstorm::call_brainstorm_cli;
+use crate::storage::models::CliType;
+use std::fs;
+use std::path::Path;
+
+pub const README_FILE: &str = "README.md";
+
+/// Longest part of the task or the existing README quoted in the prompt
+const MAX_QUOTED_CHARS: usize = 6000;
+
+fn quoted(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_QUOTED_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_QUOTED_CHARS).collect();
+    truncated.push_str("\n... (truncated) ...");
+    truncated
+}
+
+fn readme_prompt(task: &str, existing: Option<&str>) -> String {
+    let mut prompt = String::from(
+        "You are documenting a project another coding agent just built. Do NOT modify any files.\n\n\
+         Explore the repository in the current directory and write its README.md in Markdown. Cover:\n\
+         - What the project is and what it does\n\
+         - How to install dependencies and run it\n\
+         - How to run the tests\n\n\
+         Only describe commands and features that exist in the repository. \
+         Output only the complete README Markdown, without surrounding code fences.",
+    );
+    prompt.push_str(&format!("\n\nThe agent's task was:\n\n{}", quoted(task)));
+    if let Some(existing) = existing.filter(|text| !text.trim().is_empty()) {
+        prompt.push_str(&format!(
+            "\n\nUpdate the current README below. Keep sections that are still accurate \
+             and correct or extend the rest:\n\n{}",
+            quoted(existing)
+        ));
+    }
+    prompt
+}
+
+/// The README in CLI output, without a code fence wrapping the whole of it
+fn extract_readme(output: &str) -> Option<String> {
+    let trimmed = output.trim();
+    let unfenced = trimmed
+        .strip_prefix("```markdown")
+        .or_else(|| trimmed.strip_prefix("```md"))
+        .or_else(|| trimmed.strip_prefix("```"))
+        .and_then(|rest| rest.trim_end().strip_suffix("```"))
+        .unwrap_or(trimmed)
+        .trim();
+    (!unfenced.is_empty()).then(|| format!("{}\n", unfenced))
+}
+
+/// Generate `README.md` in `dir`, replacing any existing one; returns whether it changed
+pub async fn generate(
+    cli_type: CliType,
+    dir: &Path,
+    task: &str,
+    skip_git_repo_check: bool,
+) -> Result<bool, String> {
+    let path = dir.join(README_FILE);
+    let existing = fs::read_to_string(&path).ok();
+    let prompt = readme_prompt(task, existing.as_deref());
+    let output = call_brainstorm_cli(cli_type, dir, &prompt, skip_git_repo_check).await?;
+    let readme = extract_readme(&output).ok_or("README generation produced no output")?;
+    if existing.as_deref() == Some(readme.as_str()) {
+        return Ok(false);
+    }
+    fs::write(&path, readme).map_err(|e| format!("Failed to write {}: {}", README_FILE, e))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_fenced_and_plain_output() {
+        assert_eq!(
+            extract_readme("```markdown\n# Todo\n\nRun `npm start`.\n```\n").as_deref(),
+            Some("# Todo\n\nRun `npm start`.\n")
+        );
+        assert_eq!(
+            extract_readme("# Todo\n\n```sh\nnpm test\n```").as_deref(),
+            Some("# Todo\n\n```sh\nnpm test\n```\n")
+        );
+        assert_eq!(extract_readme("  \n"), None);
+    }
+
+    #[test]
+    fn prompt_includes_the_existing_readme() {
+        let prompt = readme_prompt("Build a todo app", Some("# Todo\nOld notes"));
+        assert!(prompt.contains("Build a todo app"));
+        assert!(prompt.contains("Update the current README below"));
+        assert!(prompt.contains("Old notes"));
+        assert!(!readme_prompt("Build it", None).contains("Update the current README"));
+    }
+}
@@ -0,0 +1,241 @@
+//! Runs the agent CLI and git on a remote host over SSH, streaming output back.
+
+use super::env_profiles::is_valid_env_name;
+use super::executor::{map_working_dir, Execution, Executor, IterationRequest, ProcessExecution};
+use crate::adapters::{apply_extended_path, hide_console_window, CliAdapter};
+use crate::storage::models::RemoteTarget;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tokio::process::Command;
+
+/// Quote a word for a POSIX shell
+pub(super) fn quote(word: &str) -> String {
+    if !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./=:@%+,".contains(c))
+    {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+fn quote_all<S: AsRef<str>>(words: &[S]) -> String {
+    words
+        .iter()
+        .map(|w| quote(w.as_ref()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Whether `word` can go on the `ssh` command line without being read as an option
+fn is_plain_ssh_word(word: &str) -> bool {
+    !word.is_empty()
+        && !word.starts_with('-')
+        && !word.chars().any(|c| c.is_whitespace() || c.is_control())
+}
+
+/// Reject a target whose host or user `ssh` could mistake for options
+pub fn validate_target(target: &RemoteTarget) -> Result<(), String> {
+    if target.host.trim().is_empty() {
+        return Err("Remote host cannot be empty".to_string());
+    }
+    if !is_plain_ssh_word(&target.host) {
+        return Err(format!("Invalid remote host: {:?}", target.host));
+    }
+    if let Some(user) = target.user.as_deref().filter(|s| !s.is_empty()) {
+        if !is_plain_ssh_word(user) {
+            return Err(format!("Invalid remote user: {:?}", user));
+        }
+    }
+    if !target.directory.starts_with('/') {
+        return Err("Remote directory must be an absolute path".to_string());
+    }
+    Ok(())
+}
+
+/// `ssh` options and destination, without the remote command
+fn ssh_args(target: &RemoteTarget) -> Vec<String> {
+    // BatchMode fails fast instead of hanging on a password prompt nobody can answer
+    let mut args = vec!["-o".to_string(), "BatchMode=yes".to_string()];
+    if let Some(port) = target.port {
+        args.push("-p".to_string());
+        args.push(port.to_string());
+    }
+    if let Some(identity) = target.identity_file.as_deref().filter(|s| !s.is_empty()) {
+        args.push("-i".to_string());
+        args.push(identity.to_string());
+    }
+    let destination = match target.user.as_deref().filter(|s| !s.is_empty()) {
+        Some(user) => format!("{}@{}", user, target.host),
+        None => target.host.clone(),
+    };
+    // Ends option parsing, so the destination can never be taken for one
+    args.push("--".to_string());
+    args.push(destination);
+    args
+}
+
+/// `ssh` invocation running `script` with `sh` on the remote host
+pub fn ssh_command(target: &RemoteTarget, script: &str) -> Command {
+    let mut cmd = Command::new("ssh");
+    cmd.args(ssh_args(target))
+        .arg(format!("sh -lc {}", quote(script)));
+    apply_extended_path(&mut cmd);
+    hide_console_window(&mut cmd);
+    cmd
+}
+
+/// Remote script for `git -C <directory> <args>`
+pub fn git_script(target: &RemoteTarget, args: &[&str]) -> String {
+    format!("git -C {} {}", quote(&target.directory), quote_all(args))
+}
+
+/// Remote script that runs `argv` in `workdir` and kills it when the SSH session drops.
+/// The agent runs in the background while `cat` watches our end of stdin, which
+/// closes when Ralph kills `ssh`; otherwise the remote agent would outlive the loop.
+/// Variable names are written unquoted, so anything but a plain shell name is rejected.
+fn agent_script(
+    workdir: &str,
+    env: &[(String, String)],
+    argv: &[String],
+) -> Result<String, String> {
+    if let Some((key, _)) = env.iter().find(|(key, _)| !is_valid_env_name(key)) {
+        return Err(format!(
+            "Invalid environment variable name for a remote host: {:?}",
+            key
+        ));
+    }
+    let env: String = env
+        .iter()
+        .map(|(key, value)| format!("{}={} ", key, quote(value)))
+        .collect();
+    Ok(format!(
+        "cd {} || exit 127; exec 3<&0; {}{} 3<&- & pid=$!; \
+         (cat <&3 >/dev/null 2>&1; kill -TERM $pid 2>/dev/null) >/dev/null 2>&1 & \
+         exec 3<&-; wait $pid",
+        quote(workdir),
+        env,
+        quote_all(argv)
+    ))
+}
+
+pub struct SshExecutor {
+    target: RemoteTarget,
+    project_path: PathBuf,
+    adapter: Box<dyn CliAdapter>,
+}
+
+impl SshExecutor {
+    pub fn new(target: RemoteTarget, project_path: &Path, adapter: Box<dyn CliAdapter>) -> Self {
+        Self {
+            target,
+            project_path: project_path.to_path_buf(),
+            adapter,
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for SshExecutor {
+    async fn start(&self, request: IterationRequest<'_>) -> Result<Box<dyn Execution>, String> {
+        let mut command = self
+            .adapter
+            .portable_command(request.prompt, request.options)
+            .ok_or_else(|| format!("{} cannot run on a remote host", self.adapter.name()))?;
+        command.env.extend(request.env.iter().cloned());
+        if request.network_blocked {
+            command
+                .env
+                .extend(super::network::offline_env(request.api_hosts));
+        }
+        let workdir = map_working_dir(
+            &self.project_path,
+            request.working_dir,
+            &self.target.directory,
+        )?;
+
+        let mut cmd = ssh_command(
+            &self.target,
+            &agent_script(&workdir, &command.env, &command.argv)?,
+        );
+        // Held open for the life of the session; see `agent_script`
+        cmd.stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        Ok(Box::new(ProcessExecution::spawn(cmd, request.project_id)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target() -> RemoteTarget {
+        RemoteTarget {
+            host: "build.example.com".to_string(),
+            user: Some("ci".to_string()),
+            port: Some(2222),
+            identity_file: Some("/home/me/.ssh/id_build".to_string()),
+            directory: "/srv/my repo".to_string(),
+        }
+    }
+
+    #[test]
+    fn quotes_shell_words() {
+        assert_eq!(quote("plain-word"), "plain-word");
+        assert_eq!(quote("two words"), "'two words'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn builds_ssh_and_git_invocations() {
+        assert_eq!(
+            ssh_args(&target()).join(" "),
+            "-o BatchMode=yes -p 2222 -i /home/me/.ssh/id_build -- ci@build.example.com"
+        );
+        assert_eq!(
+            git_script(&target(), &["commit", "-m", "ralph: iteration 1"]),
+            "git -C '/srv/my repo' commit -m 'ralph: iteration 1'"
+        );
+    }
+
+    #[test]
+    fn rejects_hosts_and_users_ssh_would_read_as_options() {
+        assert!(validate_target(&target()).is_ok());
+        let mut proxy = target();
+        proxy.host = "-oProxyCommand=touch /tmp/pwned".to_string();
+        assert!(validate_target(&proxy).is_err());
+        let mut spaced = target();
+        spaced.user = Some("ci -v".to_string());
+        assert!(validate_target(&spaced).is_err());
+        let mut relative = target();
+        relative.directory = "repo".to_string();
+        assert!(validate_target(&relative).is_err());
+    }
+
+    #[test]
+    fn agent_script_runs_cli_in_workdir() {
+        let script = agent_script(
+            "/srv/my repo/web",
+            &[("OPENCODE_CONFIG_CONTENT".to_string(), "{}".to_string())],
+            &[
+                "codex".to_string(),
+                "exec".to_string(),
+                "fix it".to_string(),
+            ],
+        )
+        .unwrap();
+        assert!(script.starts_with("cd '/srv/my repo/web' || exit 127;"));
+        assert!(script.contains("OPENCODE_CONFIG_CONTENT='{}' codex exec 'fix it' 3<&- &"));
+        assert!(script.ends_with("wait $pid"));
+    }
+
+    #[test]
+    fn agent_script_rejects_unsafe_variable_names() {
+        let env = [("X;rm -rf ~".to_string(), "1".to_string())];
+        assert!(agent_script("/srv/repo", &env, &["codex".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,113 @@
+//! Collapses runs of identical output lines (spinners, polling messages) into
+//! the first line plus a repeat count, for both events and logs.
+
+/// Held-back repeats of the last emitted line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepeatSummary {
+    pub is_stderr: bool,
+    pub count: u32,
+}
+
+impl RepeatSummary {
+    pub fn message(&self) -> String {
+        let times = if self.count == 1 { "time" } else { "times" };
+        format!("(previous line repeated {} more {})", self.count, times)
+    }
+}
+
+/// What to do with an output line
+#[derive(Debug, PartialEq, Eq)]
+pub enum LineAction {
+    /// Same as the previous line; counted instead of shown
+    Hold,
+    /// Show the line, after the previous line's repeat count if it had any
+    Emit(Option<RepeatSummary>),
+}
+
+#[derive(Debug, Default)]
+pub struct RepeatCollapser {
+    enabled: bool,
+    last: Option<(bool, String)>,
+    repeats: u32,
+}
+
+impl RepeatCollapser {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    /// Decide on a line about to be shown; empty lines pass through untouched
+    pub fn observe(&mut self, is_stderr: bool, line: &str) -> LineAction {
+        if !self.enabled || line.is_empty() {
+            return LineAction::Emit(None);
+        }
+        if self
+            .last
+            .as_ref()
+            .is_some_and(|(stderr, last)| *stderr == is_stderr && last == line)
+        {
+            self.repeats += 1;
+            return LineAction::Hold;
+        }
+        let summary = self.finish();
+        self.last = Some((is_stderr, line.to_string()));
+        LineAction::Emit(summary)
+    }
+
+    /// Repeat count still held back, e.g. when the iteration ends
+    pub fn finish(&mut self) -> Option<RepeatSummary> {
+        let count = std::mem::take(&mut self.repeats);
+        let is_stderr = self.last.take()?.0;
+        (count > 0).then_some(RepeatSummary { is_stderr, count })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collapses_consecutive_identical_lines() {
+        let mut collapser = RepeatCollapser::new(true);
+        assert_eq!(
+            collapser.observe(false, "Thinking..."),
+            LineAction::Emit(None)
+        );
+        assert_eq!(collapser.observe(false, "Thinking..."), LineAction::Hold);
+        assert_eq!(collapser.observe(false, ""), LineAction::Emit(None));
+        assert_eq!(collapser.observe(false, "Thinking..."), LineAction::Hold);
+
+        let summary = RepeatSummary {
+            is_stderr: false,
+            count: 2,
+        };
+        assert_eq!(
+            collapser.observe(false, "Done"),
+            LineAction::Emit(Some(summary.clone()))
+        );
+        assert_eq!(summary.message(), "(previous line repeated 2 more times)");
+
+        // The same text on the other stream is a different line
+        assert_eq!(collapser.observe(true, "Done"), LineAction::Emit(None));
+        assert_eq!(collapser.observe(true, "Done"), LineAction::Hold);
+        assert_eq!(
+            collapser.finish(),
+            Some(RepeatSummary {
+                is_stderr: true,
+                count: 1
+            })
+        );
+        assert_eq!(collapser.finish(), None);
+    }
+
+    #[test]
+    fn disabled_collapser_emits_everything() {
+        let mut collapser = RepeatCollapser::new(false);
+        assert_eq!(collapser.observe(false, "x"), LineAction::Emit(None));
+        assert_eq!(collapser.observe(false, "x"), LineAction::Emit(None));
+        assert_eq!(collapser.finish(), None);
+    }
+}
@@ -0,0 +1,269 @@
+use super::context::{list_project_files, read_text_file};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Stop scanning for TODOs after this many files
+const MAX_TODO_FILES: usize = 500;
+const MAX_TODO_SAMPLES: usize = 5;
+
+fn language_for(path: &Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "rs" => "Rust",
+        "ts" | "tsx" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "svelte" => "Svelte",
+        "vue" => "Vue",
+        "py" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "swift" => "Swift",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "cs" => "C#",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "hpp" => "C++",
+        "dart" => "Dart",
+        _ => return None,
+    })
+}
+
+fn is_test_file(path: &Path) -> bool {
+    let text = path.to_string_lossy().replace('\\', "/").to_lowercase();
+    let name = text.rsplit('/').next().unwrap_or("");
+    text.split('/')
+        .any(|part| matches!(part, "test" | "tests" | "__tests__" | "spec" | "e2e"))
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || name.ends_with("_test.go")
+        || name.ends_with("_test.py")
+        || name.starts_with("test_")
+}
+
+/// Frameworks and test tools declared in package.json
+fn scan_package_json(root: &Path, frameworks: &mut BTreeSet<String>, tests: &mut BTreeSet<String>) {
+    let Some(content) = read_text_file(&root.join("package.json")) else {
+        return;
+    };
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) else {
+        return;
+    };
+    let deps: Vec<&str> = ["dependencies", "devDependencies"]
+        .iter()
+        .filter_map(|key| json.get(key).and_then(|d| d.as_object()))
+        .flat_map(|d| d.keys().map(String::as_str))
+        .collect();
+    let known = [
+        ("next", "Next.js"),
+        ("react", "React"),
+        ("vue", "Vue"),
+        ("nuxt", "Nuxt"),
+        ("svelte", "Svelte"),
+        ("@sveltejs/kit", "SvelteKit"),
+        ("@angular/core", "Angular"),
+        ("express", "Express"),
+        ("vite", "Vite"),
+        ("@tauri-apps/api", "Tauri"),
+        ("electron", "Electron"),
+    ];
+    for (dep, name) in known {
+        if deps.contains(&dep) {
+            frameworks.insert(name.to_string());
+        }
+    }
+    let test_tools = [
+        ("vitest", "Vitest"),
+        ("jest", "Jest"),
+        ("mocha", "Mocha"),
+        ("@playwright/test", "Playwright"),
+        ("cypress", "Cypress"),
+    ];
+    for (dep, name) in test_tools {
+        if deps.contains(&dep) {
+            tests.insert(name.to_string());
+        }
+    }
+    if let Some(script) = json.pointer("/scripts/test").and_then(|s| s.as_str()) {
+        tests.insert(format!("npm test: `{}`", script));
+    }
+}
+
+/// Frameworks declared in other common manifests
+fn scan_manifests(root: &Path, files: &[PathBuf], frameworks: &mut BTreeSet<String>) {
+    let manifests = [
+        (
+            "Cargo.toml",
+            &[
+                ("tauri", "Tauri"),
+                ("axum", "Axum"),
+                ("actix-web", "Actix Web"),
+            ][..],
+        ),
+        (
+            "pyproject.toml",
+            &[
+                ("django", "Django"),
+                ("flask", "Flask"),
+                ("fastapi", "FastAPI"),
+            ][..],
+        ),
+        (
+            "requirements.txt",
+            &[
+                ("django", "Django"),
+                ("flask", "Flask"),
+                ("fastapi", "FastAPI"),
+            ][..],
+        ),
+        (
+            "go.mod",
+            &[("gin-gonic", "Gin"), ("labstack/echo", "Echo")][..],
+        ),
+    ];
+    for (manifest, known) in manifests {
+        // Manifests may live in a subdirectory (e.g. src-tauri/Cargo.toml)
+        for file in files
+            .iter()
+            .filter(|f| f.file_name().is_some_and(|n| n == manifest))
+        {
+            let Some(content) = read_text_file(&root.join(file)) else {
+                continue;
+            };
+            let content = content.to_lowercase();
+            for (needle, name) in known {
+                if content.contains(needle) {
+                    frameworks.insert(name.to_string());
+                }
+            }
+        }
+    }
+}
+
+/// Count TODO/FIXME markers and keep a few examples
+fn scan_todos(root: &Path, files: &[PathBuf]) -> (usize, Vec<String>) {
+    let mut count = 0;
+    let mut samples = Vec::new();
+    for file in files
+        .iter()
+        .filter(|f| language_for(f).is_some())
+        .take(MAX_TODO_FILES)
+    {
+        let Some(content) = read_text_file(&root.join(file)) else {
+            continue;
+        };
+        for (index, line) in content.lines().enumerate() {
+            if !(line.contains("TODO") || line.contains("FIXME")) {
+                continue;
+            }
+            count += 1;
+            if samples.len() < MAX_TODO_SAMPLES {
+                samples.push(format!(
+                    "{}:{}: {}",
+                    file.to_string_lossy().replace('\\', "/"),
+                    index + 1,
+                    line.trim().chars().take(120).collect::<String>()
+                ));
+            }
+        }
+    }
+    (count, samples)
+}
+
+/// Summarize an existing codebase for the brainstorm; `None` when there is no code yet
+pub async fn summarize_repo(root: &Path) -> Option<String> {
+    let files = list_project_files(root).await;
+    let mut languages: HashMap<&str, usize> = HashMap::new();
+    for file in &files {
+        if let Some(language) = language_for(file) {
+            *languages.entry(language).or_default() += 1;
+        }
+    }
+    if languages.is_empty() {
+        return None;
+    }
+    let mut languages: Vec<(&str, usize)> = languages.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let mut frameworks = BTreeSet::new();
+    let mut test_setup = BTreeSet::new();
+    scan_package_json(root, &mut frameworks, &mut test_setup);
+    scan_manifests(root, &files, &mut frameworks);
+    let test_files = files.iter().filter(|f| is_test_file(f)).count();
+    if test_files > 0 {
+        test_setup.insert(format!("{} test files", test_files));
+    }
+    let (todo_count, todo_samples) = scan_todos(root, &files);
+
+    let mut summary = format!(
+        "- Files: {}\n- Languages: {}\n",
+        files.len(),
+        languages
+            .iter()
+            .map(|(name, count)| format!("{} ({})", name, count))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    if !frameworks.is_empty() {
+        summary.push_str(&format!(
+            "- Frameworks: {}\n",
+            frameworks.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    if test_setup.is_empty() {
+        summary.push_str("- Tests: none found\n");
+    } else {
+        summary.push_str(&format!(
+            "- Tests: {}\n",
+            test_setup.into_iter().collect::<Vec<_>>().join(", ")
+        ));
+    }
+    summary.push_str(&format!("- TODO/FIXME markers: {}\n", todo_count));
+    for sample in todo_samples {
+        summary.push_str(&format!("  - {}\n", sample));
+    }
+    Some(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let full = root.join(path);
+        fs::create_dir_all(full.parent().unwrap()).unwrap();
+        fs::write(full, content).unwrap();
+    }
+
+    #[tokio::test]
+    async fn summary_reports_stack_tests_and_todos() {
+        let dir = tempdir().unwrap();
+        write(
+            dir.path(),
+            "package.json",
+            r#"{"dependencies":{"next":"14","react":"18"},"scripts":{"dev":"next dev"}}"#,
+        );
+        write(
+            dir.path(),
+            "app/page.tsx",
+            "// TODO: handle empty state\nexport default 1;",
+        );
+        write(dir.path(), "app/layout.tsx", "export default 2;");
+
+        let summary = summarize_repo(dir.path()).await.unwrap();
+        assert!(summary.contains("TypeScript (2)"));
+        assert!(summary.contains("Next.js, React"));
+        assert!(summary.contains("Tests: none found"));
+        assert!(summary.contains("TODO/FIXME markers: 1"));
+        assert!(summary.contains("app/page.tsx:1: // TODO: handle empty state"));
+    }
+
+    #[tokio::test]
+    async fn empty_project_has_no_summary() {
+        let dir = tempdir().unwrap();
+        write(dir.path(), "README.md", "# Idea");
+        assert!(summarize_repo(dir.path()).await.is_none());
+    }
+}
@@ -0,0 +1,226 @@
+//! The local monthly "Ralph report": what loops got done, how long agents worked
+//! and roughly what it cost, rendered from the sessions recorded on this machine.
+
+use super::cost::default_rates;
+use crate::adapters::get_adapter;
+use crate::storage::models::SessionMetrics;
+use chrono::{Datelike, Local, NaiveDate};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+const MAX_WINS: usize = 5;
+
+/// First day of a `YYYY-MM` month
+pub fn parse_month(month: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-01", month.trim()), "%Y-%m-%d")
+        .map_err(|_| format!("Invalid month '{}', expected YYYY-MM", month))
+}
+
+pub fn current_month() -> String {
+    Local::now().format("%Y-%m").to_string()
+}
+
+fn in_month(session: &SessionMetrics, first: NaiveDate) -> bool {
+    let started = session.started_at.with_timezone(&Local);
+    started.year() == first.year() && started.month() == first.month()
+}
+
+/// Reported spend, or the tokens at list price when the CLI reports none
+fn session_cost(session: &SessionMetrics) -> Option<f64> {
+    session.cost_usd.or_else(|| {
+        let rates = default_rates(session.cli)?;
+        (session.input_tokens + session.output_tokens > 0)
+            .then(|| rates.cost(session.input_tokens, session.output_tokens))
+    })
+}
+
+fn hours(sessions: &[&SessionMetrics]) -> f64 {
+    let seconds: i64 = sessions
+        .iter()
+        .map(|s| (s.ended_at - s.started_at).num_seconds().max(0))
+        .sum();
+    seconds as f64 / 3600.0
+}
+
+fn spend(sessions: &[&SessionMetrics]) -> String {
+    let costs: Vec<f64> = sessions.iter().filter_map(|s| session_cost(s)).collect();
+    if costs.is_empty() {
+        "-".to_string()
+    } else {
+        format!("${:.2}", costs.iter().sum::<f64>())
+    }
+}
+
+/// Markdown report of the sessions started in `month`
+pub fn render(
+    month: NaiveDate,
+    sessions: &[SessionMetrics],
+    project_names: &HashMap<Uuid, String>,
+) -> String {
+    let sessions: Vec<&SessionMetrics> = sessions.iter().filter(|s| in_month(s, month)).collect();
+    let mut lines = vec![
+        format!("# Ralph report: {}", month.format("%B %Y")),
+        String::new(),
+    ];
+    if sessions.is_empty() {
+        lines.push("No loops ran this month.".to_string());
+        return lines.join("\n") + "\n";
+    }
+
+    let count = |status: &str| sessions.iter().filter(|s| s.status == status).count();
+    let completed = count("completed");
+    let max_iterations = count("max_iterations_reached");
+    let projects_completed: HashSet<Uuid> = sessions
+        .iter()
+        .filter(|s| s.status == "completed")
+        .map(|s| s.project_id)
+        .collect();
+    lines.extend([
+        format!(
+            "- Sessions: {} ({} completed, {} at max iterations, {} stopped or failed)",
+            sessions.len(),
+            completed,
+            max_iterations,
+            sessions.len() - completed - max_iterations
+        ),
+        format!("- Projects completed: {}", projects_completed.len()),
+        format!("- Agent time: {:.1} hours", hours(&sessions)),
+        format!(
+            "- Iterations: {}",
+            sessions.iter().map(|s| s.iterations).sum::<u32>()
+        ),
+        format!(
+            "- Auto-commits: {}",
+            sessions.iter().map(|s| s.commits).sum::<u32>()
+        ),
+        format!(
+            "- Iterations ending in a question: {}",
+            sessions.iter().map(|s| s.questions_asked).sum::<u32>()
+        ),
+        format!("- Estimated spend: {}", spend(&sessions)),
+        String::new(),
+        "## By CLI".to_string(),
+        String::new(),
+        "| CLI | Sessions | Iterations | Hours | Spend |".to_string(),
+        "| --- | --- | --- | --- | --- |".to_string(),
+    ]);
+    let mut clis: Vec<_> = sessions.iter().map(|s| s.cli).collect();
+    clis.sort_by_key(|cli| get_adapter(*cli).name().to_string());
+    clis.dedup();
+    for cli in clis {
+        let runs: Vec<&SessionMetrics> =
+            sessions.iter().copied().filter(|s| s.cli == cli).collect();
+        lines.push(format!(
+            "| {} | {} | {} | {:.1} | {} |",
+            get_adapter(cli).name(),
+            runs.len(),
+            runs.iter().map(|s| s.iterations).sum::<u32>(),
+            hours(&runs),
+            spend(&runs)
+        ));
+    }
+
+    lines.push(String::new());
+    lines.push("## Biggest wins".to_string());
+    lines.push(String::new());
+    let mut wins: Vec<&SessionMetrics> = sessions
+        .iter()
+        .copied()
+        .filter(|s| s.status == "completed")
+        .collect();
+    // The most committed work first, then the longest haul
+    wins.sort_by_key(|s| std::cmp::Reverse((s.commits, s.iterations)));
+    if wins.is_empty() {
+        lines.push("No loop ran to completion this month.".to_string());
+    }
+    for (rank, win) in wins.iter().take(MAX_WINS).enumerate() {
+        let name = project_names
+            .get(&win.project_id)
+            .map_or("A deleted project", String::as_str);
+        lines.push(format!(
+            "{}. **{}** finished in {} iterations with {} commits ({:.1} h) on {}",
+            rank + 1,
+            name,
+            win.iterations,
+            win.commits,
+            hours(&[*win]),
+            win.ended_at.with_timezone(&Local).format("%b %-d")
+        ));
+    }
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::CliType;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn session(project_id: Uuid, status: &str, day: u32, commits: u32) -> SessionMetrics {
+        let started_at = Utc.with_ymd_and_hms(2026, 3, day, 12, 0, 0).unwrap();
+        SessionMetrics {
+            project_id,
+            session_id: format!("2026-03-{:02}", day),
+            cli: CliType::Claude,
+            started_at,
+            ended_at: started_at + Duration::minutes(90),
+            status: status.to_string(),
+            iterations: 6,
+            commits,
+            questions_asked: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            cost_usd: Some(1.5),
+        }
+    }
+
+    #[test]
+    fn parses_months() {
+        assert_eq!(
+            parse_month("2026-03"),
+            Ok(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap())
+        );
+        assert!(parse_month("March").is_err());
+    }
+
+    #[test]
+    fn summarizes_the_month() {
+        let shop = Uuid::new_v4();
+        let blog = Uuid::new_v4();
+        let names = HashMap::from([(shop, "shop".to_string())]);
+        let sessions = vec![
+            session(shop, "completed", 3, 5),
+            session(shop, "stopped", 10, 0),
+            session(blog, "completed", 20, 9),
+            session(blog, "completed", 1, 1),
+        ];
+        let mut april = session(shop, "completed", 15, 40);
+        april.started_at = Utc.with_ymd_and_hms(2026, 4, 15, 12, 0, 0).unwrap();
+
+        let report = render(
+            parse_month("2026-03").unwrap(),
+            &[sessions, vec![april]].concat(),
+            &names,
+        );
+        assert!(report.starts_with("# Ralph report: March 2026\n"));
+        assert!(report
+            .contains("- Sessions: 4 (3 completed, 0 at max iterations, 1 stopped or failed)"));
+        assert!(report.contains("- Projects completed: 2"));
+        assert!(report.contains("- Agent time: 6.0 hours"));
+        assert!(report.contains("- Estimated spend: $6.00"));
+        assert!(report.contains("| Claude Code | 4 | 24 | 6.0 | $6.00 |"));
+        assert!(report.contains("1. **A deleted project** finished in 6 iterations with 9 commits"));
+        assert!(report.contains("2. **shop** finished in 6 iterations with 5 commits"));
+    }
+
+    #[test]
+    fn estimates_spend_from_tokens() {
+        let mut run = session(Uuid::nil(), "completed", 5, 0);
+        run.cost_usd = None;
+        run.input_tokens = 1_000_000;
+        run.output_tokens = 100_000;
+        assert_eq!(session_cost(&run), Some(4.5));
+        run.cli = CliType::Amp;
+        assert_eq!(session_cost(&run), None);
+    }
+}
@@ -0,0 +1,196 @@
+//! Memory cap for loop CLIs, so a runaway agent cannot exhaust the machine.
+//! Unix limits the CLI's data segment in the forked child before it execs, so
+//! the CLI and everything it spawns run under the limit. Windows puts the CLI
+//! in a job object whose memory limit covers the CLI and all of its children.
+//! Terminal sessions are spawned by portable-pty, which has no pre-exec hook;
+//! Linux limits those by pid right after spawn, other Unixes cannot limit them.
+
+/// Smallest limit accepted; agents built on Node need a few hundred MB to start
+pub const MIN_MEMORY_LIMIT_MB: u64 = 256;
+
+/// Check a task's memory limit before it is saved
+pub fn validate_memory_limit(limit_mb: Option<u64>) -> Result<(), String> {
+    let Some(limit_mb) = limit_mb else {
+        return Ok(());
+    };
+    if !cfg!(any(unix, target_os = "windows")) {
+        return Err("Memory limits are not supported on this platform".to_string());
+    }
+    if limit_mb < MIN_MEMORY_LIMIT_MB {
+        return Err(format!(
+            "Memory limit must be at least {} MB",
+            MIN_MEMORY_LIMIT_MB
+        ));
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(any(unix, target_os = "windows")), allow(dead_code))]
+fn limit_bytes(limit_mb: u64) -> u64 {
+    limit_mb.saturating_mul(1024 * 1024)
+}
+
+#[cfg(unix)]
+fn data_limit(limit_mb: u64) -> libc::rlimit {
+    let bytes = limit_bytes(limit_mb) as libc::rlim_t;
+    libc::rlimit {
+        rlim_cur: bytes,
+        rlim_max: bytes,
+    }
+}
+
+/// Start `cmd` with its memory capped, so it and all of its children run under the limit
+#[cfg(unix)]
+pub fn apply_to_command(cmd: &mut tokio::process::Command, limit_mb: Option<u64>) {
+    let Some(limit_mb) = limit_mb else {
+        return;
+    };
+    let limit = data_limit(limit_mb);
+    // SAFETY: setrlimit is async-signal-safe and `limit` is owned by the closure
+    unsafe {
+        cmd.pre_exec(move || {
+            if libc::setrlimit(libc::RLIMIT_DATA, &limit) != 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            Ok(())
+        });
+    }
+}
+
+/// Cap the memory of a CLI already started in a terminal, and its future children
+#[cfg(target_os = "linux")]
+pub fn apply_to_process(pid: u32, limit_mb: Option<u64>) -> Result<(), String> {
+    let Some(limit_mb) = limit_mb else {
+        return Ok(());
+    };
+    let limit = data_limit(limit_mb);
+    // SAFETY: `limit` outlives the call, and a null old-limit pointer is allowed
+    let result = unsafe {
+        libc::prlimit(
+            pid as libc::pid_t,
+            libc::RLIMIT_DATA,
+            &limit,
+            std::ptr::null_mut(),
+        )
+    };
+    if result != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+    Ok(())
+}
+
+/// Cap the memory of a started CLI and its future children
+#[cfg(target_os = "windows")]
+pub fn apply_to_process(pid: u32, limit_mb: Option<u64>) -> Result<(), String> {
+    use std::ffi::c_void;
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_JOB_MEMORY,
+    };
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, PROCESS_SET_QUOTA, PROCESS_TERMINATE,
+    };
+
+    let Some(limit_mb) = limit_mb else {
+        return Ok(());
+    };
+    let last_error = || std::io::Error::last_os_error().to_string();
+    // SAFETY: handles are checked before use and closed once; the job stays
+    // alive after its handle closes for as long as processes are assigned to it
+    unsafe {
+        let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+        if job.is_null() {
+            return Err(last_error());
+        }
+        let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+        info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_JOB_MEMORY;
+        info.JobMemoryLimit = limit_bytes(limit_mb) as usize;
+        let configured = SetInformationJobObject(
+            job,
+            JobObjectExtendedLimitInformation,
+            &info as *const _ as *const c_void,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        );
+        if configured == 0 {
+            let error = last_error();
+            CloseHandle(job);
+            return Err(error);
+        }
+        let process = OpenProcess(PROCESS_SET_QUOTA | PROCESS_TERMINATE, 0, pid);
+        if process.is_null() {
+            let error = last_error();
+            CloseHandle(job);
+            return Err(error);
+        }
+        let assigned = AssignProcessToJobObject(job, process);
+        let error = (assigned == 0).then(last_error);
+        CloseHandle(process);
+        CloseHandle(job);
+        error.map_or(Ok(()), Err)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn apply_to_process(_pid: u32, limit_mb: Option<u64>) -> Result<(), String> {
+    match limit_mb {
+        Some(_) => {
+            Err("Memory limits are not supported in terminal mode on this platform".to_string())
+        }
+        None => Ok(()),
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_limits_too_small_to_start_an_agent() {
+        assert!(validate_memory_limit(None).is_ok());
+        assert!(validate_memory_limit(Some(2048)).is_ok());
+        assert!(validate_memory_limit(Some(64)).is_err());
+    }
+
+    fn data_limit_of(pid: u32) -> String {
+        let limits = std::fs::read_to_string(format!("/proc/{}/limits", pid)).unwrap();
+        limits
+            .lines()
+            .find(|line| line.starts_with("Max data size"))
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn starts_a_command_with_limited_memory() {
+        let mut cmd = tokio::process::Command::new("sleep");
+        cmd.arg("5");
+        apply_to_command(&mut cmd, Some(512));
+        let mut child = cmd.spawn().unwrap();
+        let data = data_limit_of(child.id().unwrap());
+        let _ = child.kill().await;
+        assert!(
+            data.contains(&(512u64 * 1024 * 1024).to_string()),
+            "{}",
+            data
+        );
+    }
+
+    #[tokio::test]
+    async fn limits_a_running_process() {
+        let mut child = tokio::process::Command::new("sleep")
+            .arg("5")
+            .spawn()
+            .unwrap();
+        let pid = child.id().unwrap();
+        apply_to_process(pid, Some(512)).unwrap();
+
+        let data = data_limit_of(pid);
+        let _ = child.kill().await;
+        assert!(
+            data.contains(&(512u64 * 1024 * 1024).to_string()),
+            "{}",
+            data
+        );
+    }
+}
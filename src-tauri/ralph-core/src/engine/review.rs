@@ -0,0 +1,150 @@
+//! Reviewer gate before auto-commit: a second, readonly CLI call reviews the
+//! staged diff against a rubric. Blockers keep the changes uncommitted and the
+//! review goes into the next prompt. A review that fails also keeps the changes
+//! uncommitted, left staged, so an unreviewed change is never committed.
+
+use std::time::Duration;
+
+/// Longest the reviewing CLI call may take
+pub const REVIEW_TIMEOUT: Duration = Duration::from_secs(180);
+/// Start of the staged diff handed to the reviewer
+const MAX_DIFF_CHARS: usize = 30_000;
+/// Longest review added to the next prompt
+const MAX_COMMENTS_CHARS: usize = 3_000;
+
+/// Rubric used when the task sets none
+pub const DEFAULT_RUBRIC: &str = "\
+- The change does what its code and comments claim, without obvious bugs
+- No debugging leftovers, disabled tests or commented-out code
+- No secrets, credentials or machine-specific paths
+- Errors are handled instead of silently ignored";
+
+/// What the reviewer decided
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Approve,
+    /// Review comments explaining the blockers
+    Block(String),
+}
+
+/// How the review gate ended for the staged changes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Approved,
+    /// Review comments explaining the blockers
+    Blocked(String),
+    /// Why no verdict was reached: the reviewer errored, timed out or gave none
+    Failed(String),
+}
+
+/// Outcome of a reviewer run, from its output or why it did not finish
+pub fn outcome(run: Result<String, String>) -> Outcome {
+    match run.map(|output| parse_verdict(&output)) {
+        Ok(Some(Verdict::Approve)) => Outcome::Approved,
+        Ok(Some(Verdict::Block(comments))) => Outcome::Blocked(comments),
+        Ok(None) => Outcome::Failed("the reviewer gave no verdict".to_string()),
+        Err(reason) => Outcome::Failed(reason),
+    }
+}
+
+/// Prompt asking the CLI to review `diff` against `rubric`
+pub fn review_prompt(rubric: Option<&str>, diff: &str) -> String {
+    let rubric = rubric
+        .map(str::trim)
+        .filter(|rubric| !rubric.is_empty())
+        .unwrap_or(DEFAULT_RUBRIC);
+    let count = diff.chars().count();
+    let diff = if count > MAX_DIFF_CHARS {
+        let kept: String = diff.chars().take(MAX_DIFF_CHARS).collect();
+        format!("{}\n... (truncated) ...", kept)
+    } else {
+        diff.to_string()
+    };
+    format!(
+        "You review a change a coding agent is about to commit. Do NOT modify any files.\n\
+         Check the diff against this rubric:\n{rubric}\n\n\
+         Only flag blockers: problems the rubric rules out, not matters of taste.\n\
+         Answer with a first line of exactly `VERDICT: APPROVE` or `VERDICT: BLOCK`. After \
+         BLOCK, list each blocker as a bullet with the file and what to fix.\n\n\
+         Diff:\n{diff}\n"
+    )
+}
+
+/// The verdict in the reviewer's answer; `None` when it gave none
+pub fn parse_verdict(output: &str) -> Option<Verdict> {
+    let mut lines = output.trim().lines();
+    let verdict = lines
+        .by_ref()
+        .find_map(|line| {
+            line.trim()
+                .trim_matches(['*', '`'])
+                .strip_prefix("VERDICT:")
+        })?
+        .trim()
+        .to_ascii_uppercase();
+    match verdict.as_str() {
+        "APPROVE" => Some(Verdict::Approve),
+        "BLOCK" => {
+            let comments = lines.collect::<Vec<_>>().join("\n");
+            let comments = comments.trim();
+            if comments.is_empty() {
+                return Some(Verdict::Block(
+                    "The reviewer blocked the change without comments.".to_string(),
+                ));
+            }
+            let mut kept: String = comments.chars().take(MAX_COMMENTS_CHARS).collect();
+            if kept.len() < comments.len() {
+                kept.push_str("\n...");
+            }
+            Some(Verdict::Block(kept))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_uses_the_task_rubric_or_the_default() {
+        let prompt = review_prompt(Some("- Every function has a test"), "+fn a() {}");
+        assert!(prompt.contains("- Every function has a test"));
+        assert!(!prompt.contains(DEFAULT_RUBRIC));
+        assert!(review_prompt(Some("  "), "").contains(DEFAULT_RUBRIC));
+    }
+
+    #[test]
+    fn parses_verdicts() {
+        assert_eq!(parse_verdict("VERDICT: APPROVE"), Some(Verdict::Approve));
+        assert_eq!(
+            parse_verdict("Looked at it.\n**VERDICT: BLOCK**\n- src/a.rs: remove the dbg! call\n"),
+            Some(Verdict::Block(
+                "- src/a.rs: remove the dbg! call".to_string()
+            ))
+        );
+        assert_eq!(
+            parse_verdict("VERDICT: BLOCK"),
+            Some(Verdict::Block(
+                "The reviewer blocked the change without comments.".to_string()
+            ))
+        );
+        assert_eq!(parse_verdict("Looks fine to me"), None);
+    }
+
+    #[test]
+    fn a_review_that_fails_does_not_approve() {
+        assert_eq!(
+            outcome(Ok("VERDICT: APPROVE".to_string())),
+            Outcome::Approved
+        );
+        assert_eq!(
+            outcome(Err("timed out after 180s".to_string())),
+            Outcome::Failed("timed out after 180s".to_string())
+        );
+        assert!(matches!(
+            outcome(Ok("Looks fine to me".to_string())),
+            Outcome::Failed(_)
+        ));
+    }
+}
@@ -0,0 +1,99 @@
+//! Loops waiting for a slot when more are started than `max_concurrent_projects`
+//! allows. Higher priorities run first; equal ones in the order they were queued.
+
+use crate::storage::models::QueuedLoop;
+use uuid::Uuid;
+
+/// Whether another loop may start next to `running` ones; 0 means no limit
+pub fn has_capacity(running: usize, max_concurrent: u32) -> bool {
+    max_concurrent == 0 || running < max_concurrent as usize
+}
+
+#[derive(Debug, Default)]
+pub struct LoopQueue {
+    /// In the order they will start
+    entries: Vec<QueuedLoop>,
+}
+
+impl LoopQueue {
+    /// Queue a loop behind those of its priority or higher; returns its 1-based position
+    pub fn push(&mut self, entry: QueuedLoop) -> usize {
+        let index = self
+            .entries
+            .iter()
+            .position(|queued| queued.priority < entry.priority)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(index, entry);
+        index + 1
+    }
+
+    pub fn peek(&self) -> Option<&QueuedLoop> {
+        self.entries.first()
+    }
+
+    pub fn pop(&mut self) -> Option<QueuedLoop> {
+        (!self.entries.is_empty()).then(|| self.entries.remove(0))
+    }
+
+    pub fn remove(&mut self, project_id: &Uuid) -> Option<QueuedLoop> {
+        let index = self
+            .entries
+            .iter()
+            .position(|queued| queued.project_id == *project_id)?;
+        Some(self.entries.remove(index))
+    }
+
+    pub fn contains(&self, project_id: &Uuid) -> bool {
+        self.entries
+            .iter()
+            .any(|queued| queued.project_id == *project_id)
+    }
+
+    pub fn entries(&self) -> &[QueuedLoop] {
+        &self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn queued(priority: i32) -> QueuedLoop {
+        QueuedLoop {
+            project_id: Uuid::new_v4(),
+            priority,
+            prompt: None,
+            completion_check: None,
+            readonly: false,
+            artifacts: Vec::new(),
+            queued_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn higher_priorities_start_first_then_fifo() {
+        let mut queue = LoopQueue::default();
+        let (first, second, urgent) = (queued(0), queued(0), queued(5));
+        assert_eq!(queue.push(first.clone()), 1);
+        assert_eq!(queue.push(second.clone()), 2);
+        assert_eq!(queue.push(urgent.clone()), 1);
+
+        let order: Vec<Uuid> = queue.entries().iter().map(|q| q.project_id).collect();
+        assert_eq!(
+            order,
+            vec![urgent.project_id, first.project_id, second.project_id]
+        );
+        assert!(queue.remove(&first.project_id).is_some());
+        assert_eq!(queue.pop().map(|q| q.project_id), Some(urgent.project_id));
+        assert_eq!(queue.pop().map(|q| q.project_id), Some(second.project_id));
+        assert!(queue.pop().is_none());
+    }
+
+    #[test]
+    fn zero_means_unlimited() {
+        assert!(has_capacity(1, 2));
+        assert!(!has_capacity(2, 2));
+        assert!(has_capacity(50, 0));
+    }
+}
@@ -0,0 +1,312 @@
+//! Post-iteration screenshots of web projects: start the dev server, wait for
+//! its URL to answer, capture the page, then stop the server again.
+
+use crate::adapters::{
+    apply_extended_path, apply_shell_env, hide_console_window, resolve_cli_path,
+};
+use crate::storage::models::ScreenshotConfig;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::{Child, Command};
+
+/// File name of the page screenshot within an iteration's artifacts
+pub const SCREENSHOT_FILE: &str = "ralph-screenshot.png";
+
+const CAPTURE_TIMEOUT: Duration = Duration::from_secs(60);
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+const CHROMIUM_BINARIES: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "chrome",
+    "msedge",
+];
+
+#[cfg(target_os = "macos")]
+const CHROMIUM_APPS: &[&str] = &[
+    "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+    "/Applications/Chromium.app/Contents/MacOS/Chromium",
+    "/Applications/Microsoft Edge.app/Contents/MacOS/Microsoft Edge",
+];
+
+/// Run `command` through the platform shell in `working_dir`
+pub(super) fn shell_command(command: &str, working_dir: &Path) -> Command {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    };
+    #[cfg(not(target_os = "windows"))]
+    let mut cmd = {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.current_dir(working_dir).stdin(Stdio::null());
+    apply_extended_path(&mut cmd);
+    apply_shell_env(&mut cmd);
+    hide_console_window(&mut cmd);
+    cmd
+}
+
+/// A dev server started for a capture. Package-manager scripts fork the real
+/// server, so it runs in its own process group and the whole group is stopped.
+pub struct DevServer {
+    child: Child,
+}
+
+impl DevServer {
+    pub fn start(command: &str, working_dir: &Path) -> Result<Self, String> {
+        let mut cmd = shell_command(command, working_dir);
+        cmd.stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+        let child = cmd
+            .spawn()
+            .map_err(|e| format!("Failed to start dev server: {}", e))?;
+        Ok(Self { child })
+    }
+
+    pub async fn stop(mut self) {
+        if let Some(pid) = self.child.id() {
+            #[cfg(target_os = "windows")]
+            let mut cmd = {
+                let mut cmd = Command::new("taskkill");
+                cmd.args(["/T", "/F", "/PID"]).arg(pid.to_string());
+                cmd
+            };
+            #[cfg(not(target_os = "windows"))]
+            let mut cmd = {
+                let mut cmd = Command::new("kill");
+                cmd.args(["-TERM", "--"]).arg(format!("-{}", pid));
+                cmd
+            };
+            cmd.stdout(Stdio::null()).stderr(Stdio::null());
+            hide_console_window(&mut cmd);
+            let _ = cmd.status().await;
+        }
+        let _ = self.child.kill().await;
+    }
+}
+
+/// Poll `url` until the server answers with any HTTP status
+pub async fn wait_until_ready(url: &str, timeout: Duration) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(2))
+        .build()
+        .map_err(|e| e.to_string())?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if client.get(url).send().await.is_ok() {
+            return Ok(());
+        }
+        if Instant::now() >= deadline {
+            return Err(format!("{} did not respond within {:?}", url, timeout));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// A Chromium-based browser that supports `--headless --screenshot`
+pub fn find_chromium() -> Option<String> {
+    if let Some(path) = CHROMIUM_BINARIES
+        .iter()
+        .find_map(|binary| resolve_cli_path(binary))
+    {
+        return Some(path);
+    }
+    #[cfg(target_os = "macos")]
+    if let Some(app) = CHROMIUM_APPS.iter().find(|app| Path::new(app).exists()) {
+        return Some(app.to_string());
+    }
+    None
+}
+
+fn chromium_args(config: &ScreenshotConfig, output: &Path, profile_dir: &Path) -> Vec<String> {
+    vec![
+        "--headless=new".to_string(),
+        "--disable-gpu".to_string(),
+        "--hide-scrollbars".to_string(),
+        "--no-first-run".to_string(),
+        "--no-default-browser-check".to_string(),
+        format!("--user-data-dir={}", profile_dir.display()),
+        format!("--window-size={},{}", config.width, config.height),
+        format!("--screenshot={}", output.display()),
+        config.url.clone(),
+    ]
+}
+
+#[cfg(target_os = "windows")]
+fn quote_arg(value: &str) -> String {
+    format!("\"{}\"", value)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn quote_arg(value: &str) -> String {
+    super::remote::quote(value)
+}
+
+/// Fill a user capture command's `{url}` and `{output}` placeholders
+fn expand_capture_command(template: &str, url: &str, output: &Path) -> String {
+    template
+        .replace("{url}", &quote_arg(url))
+        .replace("{output}", &quote_arg(&output.to_string_lossy()))
+}
+
+async fn run_capture(mut cmd: Command) -> Result<(), String> {
+    cmd.stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(CAPTURE_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| format!("Capture timed out after {:?}", CAPTURE_TIMEOUT))?
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Capture failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+async fn capture_page(
+    config: &ScreenshotConfig,
+    working_dir: &Path,
+    output: &Path,
+) -> Result<(), String> {
+    wait_until_ready(
+        &config.url,
+        Duration::from_secs(config.ready_timeout_secs as u64),
+    )
+    .await?;
+    match config
+        .capture_command
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+    {
+        Some(template) => {
+            let command = expand_capture_command(template, &config.url, output);
+            run_capture(shell_command(&command, working_dir)).await
+        }
+        None => {
+            let browser = find_chromium()
+                .ok_or("No Chromium-based browser found; configure a capture command")?;
+            let profile = browser_profile_dir()?;
+            let mut cmd = Command::new(browser);
+            cmd.args(chromium_args(config, output, &profile))
+                .stdin(Stdio::null());
+            hide_console_window(&mut cmd);
+            let result = run_capture(cmd).await;
+            let _ = std::fs::remove_dir_all(&profile);
+            result
+        }
+    }
+}
+
+/// Throwaway browser profile so captures never touch the user's own
+fn browser_profile_dir() -> Result<std::path::PathBuf, String> {
+    let dir =
+        std::env::temp_dir().join(format!("ralph-chromium-{}", uuid::Uuid::new_v4().simple()));
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    Ok(dir)
+}
+
+/// Start the dev server if one is configured, screenshot `config.url` into
+/// `output`, and stop the server again whatever happened
+pub async fn capture(
+    config: &ScreenshotConfig,
+    working_dir: &Path,
+    output: &Path,
+) -> Result<(), String> {
+    if let Some(parent) = output.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let server = match config
+        .dev_command
+        .as_deref()
+        .filter(|c| !c.trim().is_empty())
+    {
+        Some(command) => Some(DevServer::start(command, working_dir)?),
+        None => None,
+    };
+    let result = capture_page(config, working_dir, output).await;
+    if let Some(server) = server {
+        server.stop().await;
+    }
+    result?;
+    if !output.is_file() {
+        return Err("Capture finished without writing a screenshot".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn config() -> ScreenshotConfig {
+        ScreenshotConfig {
+            dev_command: Some("npm run dev".to_string()),
+            url: "http://localhost:5173/".to_string(),
+            ready_timeout_secs: 60,
+            capture_command: None,
+            width: 1280,
+            height: 800,
+            feed_back: false,
+        }
+    }
+
+    #[test]
+    fn builds_headless_chromium_arguments() {
+        let args = chromium_args(
+            &config(),
+            Path::new("/data/shot.png"),
+            Path::new("/tmp/profile"),
+        );
+        assert!(args.contains(&"--window-size=1280,800".to_string()));
+        assert!(args.contains(&"--screenshot=/data/shot.png".to_string()));
+        assert_eq!(args.last().unwrap(), "http://localhost:5173/");
+    }
+
+    #[test]
+    fn expands_capture_command_placeholders() {
+        assert_eq!(
+            expand_capture_command(
+                "npx playwright screenshot {url} {output}",
+                "http://localhost:3000/?a=1&b=2",
+                Path::new("/Library/Application Support/shot.png"),
+            ),
+            "npx playwright screenshot 'http://localhost:3000/?a=1&b=2' '/Library/Application Support/shot.png'"
+        );
+    }
+
+    #[tokio::test]
+    async fn user_capture_command_writes_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let output = dir.path().join("artifacts/1").join(SCREENSHOT_FILE);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let _ = socket
+                    .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\n\r\n")
+                    .await;
+            }
+        });
+
+        let config = ScreenshotConfig {
+            dev_command: None,
+            url: format!("http://127.0.0.1:{}/", port),
+            capture_command: Some("printf png > {output}".to_string()),
+            ..config()
+        };
+        capture(&config, dir.path(), &output).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&output).unwrap(), "png");
+    }
+}
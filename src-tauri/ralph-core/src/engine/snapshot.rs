@@ -0,0 +1,246 @@
+//! Reproducibility snapshot recorded when a session starts, so runs weeks
+//! apart can be compared by the exact CLI, commit, prompt and settings used.
+
+use super::LoopConfig;
+use crate::storage::models::{CliType, CliVersionRecord, SessionSnapshot};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+/// SHA-256 of the prompts a session runs with, so edits show up as a changed hash
+pub fn prompt_hash(config: &LoopConfig) -> String {
+    hash_prompts(
+        &config.prompt,
+        config.first_iteration_prompt.as_deref(),
+        config.subsequent_iteration_prompt.as_deref(),
+    )
+}
+
+fn hash_prompts(prompt: &str, first: Option<&str>, subsequent: Option<&str>) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prompt.as_bytes());
+    for extra in [first, subsequent] {
+        hasher.update([0]);
+        hasher.update(extra.unwrap_or_default().as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Version of the last successful session when the same CLI now reports another one
+pub fn version_change(
+    last_good: Option<&CliVersionRecord>,
+    cli: CliType,
+    current: Option<&str>,
+) -> Option<String> {
+    let last_good = last_good.filter(|record| record.cli == cli)?;
+    let current = current?;
+    (last_good.version.trim() != current.trim()).then(|| last_good.version.clone())
+}
+
+fn millis(duration: Option<std::time::Duration>) -> String {
+    duration.map_or(0, |d| d.as_millis()).to_string()
+}
+
+fn serde_name<T: serde::Serialize>(value: &T) -> String {
+    serde_json::to_value(value)
+        .ok()
+        .and_then(|v| v.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// Loop settings in effect; environment values are left out since they often hold secrets
+pub fn settings(config: &LoopConfig) -> BTreeMap<String, String> {
+    let mut settings = BTreeMap::new();
+    let mut set = |key: &str, value: String| {
+        settings.insert(key.to_string(), value);
+    };
+    set("maxIterations", config.max_iterations.to_string());
+    set("autoCommit", config.auto_commit.to_string());
+    set("completionSignal", config.completion_signal.clone());
+    set(
+        "completionSignalRegex",
+        config.completion_signal_regex.to_string(),
+    );
+    set(
+        "completionStrategy",
+        serde_json::to_string(&config.completion_strategy).unwrap_or_default(),
+    );
+    if !config.extra_completion_signals.is_empty() {
+        set(
+            "extraCompletionSignals",
+            config.extra_completion_signals.join("\n"),
+        );
+        set("completionMode", serde_name(&config.completion_mode));
+    }
+    set(
+        "validationCommand",
+        config.validation_command.clone().unwrap_or_default(),
+    );
+    set("carryOverProgress", config.carry_over_progress.to_string());
+    if config.review_gate {
+        set("reviewGate", "true".to_string());
+        if let Some(rubric) = &config.review_rubric {
+            set("reviewRubric", rubric.clone());
+        }
+    }
+    if let Some(interval) = config.supervisor_interval {
+        set("supervisorInterval", interval.to_string());
+    }
+    if let Some(command) = &config.pre_iteration_command {
+        set("preIterationCommand", command.clone());
+        set(
+            "preIterationAbortOnFailure",
+            config.pre_iteration_abort_on_failure.to_string(),
+        );
+    }
+    set("iterationTimeoutMs", millis(config.iteration_timeout));
+    set("idleTimeoutMs", millis(config.idle_timeout));
+    set("firstOutputTimeoutMs", millis(config.first_output_timeout));
+    set(
+        "maxConsecutiveFailures",
+        config
+            .max_consecutive_failures
+            .unwrap_or_default()
+            .to_string(),
+    );
+    set(
+        "autoDecide",
+        config.auto_decide_policy.is_some().to_string(),
+    );
+    set(
+        "agentLanguage",
+        config.agent_language.clone().unwrap_or_default(),
+    );
+    set("warmupContext", config.warmup_context.to_string());
+    set(
+        "repoContextBudget",
+        config.repo_context_budget.unwrap_or_default().to_string(),
+    );
+    set(
+        "workingDir",
+        config
+            .working_dir
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    );
+    set("priority", serde_name(&config.priority));
+    if let Some(profile) = &config.env_profile {
+        set("envProfile", profile.clone());
+    }
+    if let Some(path) = &config.cli_path {
+        set("cliPath", path.clone());
+    }
+    set("networkBlocked", config.network_blocked.to_string());
+    if let Some(container) = &config.container {
+        set("containerImage", container.image.clone());
+    }
+    if let Some(remote) = &config.remote {
+        set("remoteHost", remote.host.clone());
+    }
+    if !config.env.is_empty() {
+        let keys: Vec<&str> = config.env.iter().map(|(key, _)| key.as_str()).collect();
+        set("envKeys", keys.join(","));
+    }
+    settings
+}
+
+/// Markdown header placed at the top of exported transcripts
+pub fn render(snapshot: &SessionSnapshot) -> String {
+    let mut lines = vec![
+        "# Environment".to_string(),
+        format!(
+            "- Captured: {}",
+            snapshot.captured_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ),
+        format!("- Ralph Desktop: {}", snapshot.app_version),
+        format!(
+            "- CLI: {} {}",
+            serde_name(&snapshot.cli),
+            snapshot
+                .cli_version
+                .as_deref()
+                .unwrap_or("(unknown version)")
+        ),
+    ];
+    if let Some(model) = &snapshot.model {
+        lines.push(format!("- Model: {}", model));
+    }
+    lines.push(format!(
+        "- Git HEAD: {}",
+        snapshot.git_head.as_deref().unwrap_or("(none)")
+    ));
+    if let Some(previous) = &snapshot.previous_cli_version {
+        lines.push(format!(
+            "- CLI changed since last successful session: was {}",
+            previous
+        ));
+    }
+    lines.push(format!("- Prompt SHA-256: {}", snapshot.prompt_sha256));
+    for (key, value) in &snapshot.settings {
+        lines.push(format!("- {}: {}", key, value));
+    }
+    lines.push(String::new());
+    lines.join("\n") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompt_hash_changes_with_any_prompt() {
+        let base = hash_prompts("Build it", None, None);
+        assert_eq!(base, hash_prompts("Build it", None, None));
+        assert_ne!(base, hash_prompts("Build it", None, Some("Keep going")));
+        // Moving text between prompts is still a different setup
+        assert_ne!(
+            hash_prompts("", Some("Build it"), None),
+            hash_prompts("", None, Some("Build it"))
+        );
+        assert_eq!(base.len(), 64);
+    }
+
+    #[test]
+    fn renders_versions_and_settings() {
+        let rendered = render(&SessionSnapshot {
+            captured_at: chrono::Utc::now(),
+            app_version: "1.2.3".to_string(),
+            cli: CliType::Claude,
+            cli_version: Some("2.0.1".to_string()),
+            model: None,
+            git_head: None,
+            prompt_sha256: hash_prompts("Build it", None, None),
+            settings: BTreeMap::from([("maxIterations".to_string(), "5".to_string())]),
+            previous_cli_version: None,
+        });
+        assert!(rendered.starts_with("# Environment\n"));
+        assert!(rendered.contains("- CLI: claude 2.0.1"));
+        assert!(rendered.contains("- Git HEAD: (none)"));
+        assert!(rendered.contains("- maxIterations: 5"));
+        assert!(!rendered.contains("Model:"));
+    }
+
+    #[test]
+    fn flags_a_changed_version_of_the_same_cli() {
+        let record = CliVersionRecord {
+            cli: CliType::Claude,
+            version: "2.0.1".to_string(),
+            session_id: "s1".to_string(),
+            recorded_at: chrono::Utc::now(),
+        };
+        assert_eq!(
+            version_change(Some(&record), CliType::Claude, Some("2.1.0")),
+            Some("2.0.1".to_string())
+        );
+        assert_eq!(
+            version_change(Some(&record), CliType::Claude, Some("2.0.1")),
+            None
+        );
+        assert_eq!(
+            version_change(Some(&record), CliType::Codex, Some("0.9")),
+            None
+        );
+        assert_eq!(version_change(Some(&record), CliType::Claude, None), None);
+        assert_eq!(version_change(None, CliType::Claude, Some("2.1.0")), None);
+    }
+}
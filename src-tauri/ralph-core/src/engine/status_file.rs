@@ -0,0 +1,109 @@
+//! `.ralph/status.json`, written into the project after each session so teams
+//! can show the last loop's outcome as a badge or gate CI on it.
+//!
+//! Schema, version 1 (fields are only ever added within a version):
+//!
+//! ```json
+//! {
+//!   "schemaVersion": 1,
+//!   "outcome": "completed",
+//!   "succeeded": true,
+//!   "iterations": 4,
+//!   "commits": 3,
+//!   "cli": "claude",
+//!   "sessionId": "20250101-120000",
+//!   "startedAt": "2025-01-01T12:00:00Z",
+//!   "finishedAt": "2025-01-01T12:14:09Z"
+//! }
+//! ```
+//!
+//! `outcome` is one of `completed`, `max_iterations_reached`,
+//! `time_budget_reached`, `failed`, `path_missing`, `stopped` or `error`;
+//! `succeeded` is true only for `completed`.
+
+use crate::storage::models::CliType;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+pub const STATUS_FILE: &str = ".ralph/status.json";
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStatus {
+    pub schema_version: u32,
+    pub outcome: String,
+    pub succeeded: bool,
+    pub iterations: u32,
+    pub commits: u32,
+    pub cli: CliType,
+    pub session_id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+}
+
+impl SessionStatus {
+    pub fn new(
+        outcome: &str,
+        iterations: u32,
+        commits: u32,
+        cli: CliType,
+        session_id: &str,
+        started_at: DateTime<Utc>,
+        finished_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            outcome: outcome.to_string(),
+            succeeded: outcome == "completed",
+            iterations,
+            commits,
+            cli,
+            session_id: session_id.to_string(),
+            started_at,
+            finished_at,
+        }
+    }
+}
+
+/// Replace the status file, through a temporary file so readers never see half of it
+pub fn write(project_path: &Path, status: &SessionStatus) -> io::Result<()> {
+    let path = project_path.join(STATUS_FILE);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(status).map_err(io::Error::other)?;
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, format!("{}\n", json))?;
+    fs::rename(&temp, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn writes_the_documented_schema() {
+        let project = tempdir().unwrap();
+        let at = DateTime::parse_from_rfc3339("2025-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let status = SessionStatus::new("completed", 4, 3, CliType::Claude, "s1", at, at);
+        write(project.path(), &status).unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(project.path().join(STATUS_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(written["schemaVersion"], 1);
+        assert_eq!(written["outcome"], "completed");
+        assert_eq!(written["succeeded"], true);
+        assert_eq!(written["iterations"], 4);
+        assert_eq!(written["cli"], "claude");
+        assert_eq!(written["finishedAt"], "2025-01-01T12:00:00Z");
+        assert!(!SessionStatus::new("failed", 1, 0, CliType::Codex, "s2", at, at).succeeded);
+    }
+}
@@ -0,0 +1,128 @@
+//! Supervisor stage: every few iterations a readonly CLI call reviews what the
+//! loop changed and said since the last review, and may revise the working
+//! prompt when the agent looks stuck.
+
+use std::time::Duration;
+
+/// Longest the reviewing CLI call may take
+pub const REVIEW_TIMEOUT: Duration = Duration::from_secs(180);
+/// End of the recent diffs handed to the supervisor
+const MAX_DIFF_CHARS: usize = 12_000;
+/// End of each iteration's output handed to the supervisor
+const MAX_OUTPUT_CHARS: usize = 4_000;
+/// Answer meaning the prompt stays as it is
+const KEEP: &str = "KEEP";
+
+/// A revised working prompt and why the supervisor changed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Revision {
+    pub prompt: String,
+    pub reason: String,
+}
+
+fn tail(text: &str, max_chars: usize) -> String {
+    let text = text.trim();
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(max_chars)).collect()
+}
+
+/// Prompt asking the CLI to review the iterations up to `iteration`, given the
+/// working prompt, recent diffs and each reviewed iteration's output
+pub fn review_prompt(
+    prompt: &str,
+    iteration: u32,
+    diff: &str,
+    outputs: &[(u32, String)],
+) -> String {
+    let diff = tail(diff, MAX_DIFF_CHARS);
+    let diff = if diff.is_empty() {
+        "(no changes)".to_string()
+    } else {
+        diff
+    };
+    let outputs: String = outputs
+        .iter()
+        .map(|(number, text)| {
+            format!(
+                "### Iteration {}\n{}\n\n",
+                number,
+                tail(text, MAX_OUTPUT_CHARS)
+            )
+        })
+        .collect();
+    format!(
+        "You supervise a coding agent that works on a task in a loop, one fresh session per \
+         iteration, always with the same prompt. Review its last iterations, up to iteration \
+         {iteration}, and decide whether the prompt should change to unblock it.\n\
+         Rules:\n\
+         - If the agent is making progress, answer with only {KEEP}.\n\
+         - If it repeats itself, goes in circles or works on the wrong thing, answer with a \
+           line `REASON: <one sentence>` followed by the complete revised prompt between \
+           <prompt> and </prompt>.\n\
+         - Keep the goal of the task; clarify, reorder or add concrete next steps instead.\n\n\
+         Current prompt:\n<current>\n{prompt}\n</current>\n\n\
+         Recent changes:\n{diff}\n\n\
+         Agent output:\n{outputs}"
+    )
+}
+
+/// The revision in the supervisor's answer; `None` when it keeps the prompt or
+/// the answer has no usable prompt
+pub fn parse_review(output: &str, current: &str) -> Option<Revision> {
+    let output = output.trim();
+    if output == KEEP {
+        return None;
+    }
+    let (_, rest) = output.split_once("<prompt>")?;
+    let (prompt, _) = rest.split_once("</prompt>")?;
+    let prompt = prompt.trim();
+    if prompt.is_empty() || prompt == current.trim() {
+        return None;
+    }
+    let reason = output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("REASON:"))
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty())
+        .unwrap_or("no reason given");
+    Some(Revision {
+        prompt: prompt.to_string(),
+        reason: reason.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_prompt_includes_recent_work() {
+        let prompt = review_prompt(
+            "Build it",
+            4,
+            "",
+            &[(3, "tried X".to_string()), (4, "tried X again".to_string())],
+        );
+        assert!(prompt.contains("up to iteration 4"));
+        assert!(prompt.contains("<current>\nBuild it\n</current>"));
+        assert!(prompt.contains("(no changes)"));
+        assert!(prompt.contains("### Iteration 4\ntried X again"));
+    }
+
+    #[test]
+    fn revisions_need_a_changed_prompt() {
+        assert_eq!(parse_review("  KEEP\n", "Build it"), None);
+        assert_eq!(parse_review("<prompt>Build it</prompt>", "Build it"), None);
+        assert_eq!(parse_review("REASON: stuck\nno tags", "Build it"), None);
+        assert_eq!(
+            parse_review(
+                "REASON: It keeps retrying the same migration.\n<prompt>\nBuild it. Fix the migration first.\n</prompt>",
+                "Build it"
+            ),
+            Some(Revision {
+                prompt: "Build it. Fix the migration first.".to_string(),
+                reason: "It keeps retrying the same migration.".to_string(),
+            })
+        );
+    }
+}
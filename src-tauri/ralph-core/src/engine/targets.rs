@@ -0,0 +1,140 @@
+use crate::engine::workdir::resolve_working_dir;
+use crate::storage::models::TaskConfig;
+use std::path::{Path, PathBuf};
+
+/// One loop session within a run: the whole project, or a single monorepo target
+#[derive(Debug, Clone, PartialEq)]
+pub struct TargetRun {
+    pub target: Option<String>,
+    pub working_dir: PathBuf,
+    pub prompt: String,
+}
+
+/// Expand a task into the sessions it runs, validating every target before any start
+pub fn plan_target_runs(task: &TaskConfig, project_path: &Path) -> Result<Vec<TargetRun>, String> {
+    if task.targets.is_empty() {
+        return Ok(vec![TargetRun {
+            target: None,
+            working_dir: resolve_working_dir(project_path, task.working_subdir.as_deref())?,
+            prompt: task.prompt.clone(),
+        }]);
+    }
+
+    let mut runs = Vec::with_capacity(task.targets.len());
+    for target in &task.targets {
+        let name = target.name.trim();
+        if name.is_empty() {
+            return Err("Target name cannot be empty".to_string());
+        }
+        if runs
+            .iter()
+            .any(|r: &TargetRun| r.target.as_deref() == Some(name))
+        {
+            return Err(format!("Duplicate target: {}", name));
+        }
+        let working_dir = resolve_working_dir(project_path, Some(&target.subdir))
+            .map_err(|e| format!("Target {}: {}", name, e))?;
+        let prompt = match target.prompt_suffix.as_deref().map(str::trim) {
+            Some(suffix) if !suffix.is_empty() => format!("{}\n\n{}", task.prompt, suffix),
+            _ => task.prompt.clone(),
+        };
+        runs.push(TargetRun {
+            target: Some(name.to_string()),
+            working_dir,
+            prompt,
+        });
+    }
+    Ok(runs)
+}
+
+/// Session id for a run starting now, suffixed with the target so ids stay unique
+pub fn session_id_for(target: Option<&str>) -> String {
+    let timestamp = chrono::Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string();
+    match target {
+        Some(name) => {
+            let slug: String = name
+                .chars()
+                .map(|c| {
+                    if c.is_ascii_alphanumeric() {
+                        c.to_ascii_lowercase()
+                    } else {
+                        '-'
+                    }
+                })
+                .collect();
+            format!("{}-{}", timestamp, slug.trim_matches('-'))
+        }
+        None => timestamp,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::LoopTarget;
+    use tempfile::tempdir;
+
+    fn target(name: &str, subdir: &str, suffix: Option<&str>) -> LoopTarget {
+        LoopTarget {
+            name: name.to_string(),
+            subdir: subdir.to_string(),
+            prompt_suffix: suffix.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn plans_single_run_without_targets() {
+        let dir = tempdir().unwrap();
+        let task = TaskConfig {
+            prompt: "Build it".to_string(),
+            ..TaskConfig::default()
+        };
+
+        let runs = plan_target_runs(&task, dir.path()).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].target, None);
+        assert_eq!(runs[0].working_dir, dir.path());
+    }
+
+    #[test]
+    fn plans_one_run_per_target_in_order() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("packages/api")).unwrap();
+        std::fs::create_dir_all(dir.path().join("packages/web")).unwrap();
+        let task = TaskConfig {
+            prompt: "Add logging".to_string(),
+            targets: vec![
+                target("api", "packages/api", Some("Use tracing.")),
+                target("web", "packages/web", None),
+            ],
+            ..TaskConfig::default()
+        };
+
+        let runs = plan_target_runs(&task, dir.path()).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].target.as_deref(), Some("api"));
+        assert_eq!(runs[0].prompt, "Add logging\n\nUse tracing.");
+        assert_eq!(runs[1].working_dir, dir.path().join("packages/web"));
+        assert_eq!(runs[1].prompt, "Add logging");
+    }
+
+    #[test]
+    fn rejects_invalid_targets() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("api")).unwrap();
+        let mut task = TaskConfig {
+            targets: vec![target("api", "api", None), target("api", "api", None)],
+            ..TaskConfig::default()
+        };
+        assert!(plan_target_runs(&task, dir.path()).is_err());
+
+        task.targets = vec![target("web", "../web", None)];
+        assert!(plan_target_runs(&task, dir.path()).is_err());
+    }
+
+    #[test]
+    fn session_id_includes_target_slug() {
+        assert!(session_id_for(Some("API Server")).ends_with("-api-server"));
+        assert!(!session_id_for(None).contains("--"));
+    }
+}
@@ -0,0 +1,334 @@
+//! Interactive mode: the CLI runs in a pseudo-terminal the app can attach to,
+//! so a CLI unexpectedly waiting for input can be answered instead of killed.
+//! The engine still reads the terminal's output as lines; attached views get
+//! the raw bytes. A terminal merges stdout and stderr, so everything arrives
+//! as stdout.
+
+use super::console::{ConsoleLines, Segment};
+use super::executor::{Execution, Executor, IterationRequest, OutputLine};
+use super::processes::{self, TrackedProcess};
+use crate::adapters::CliAdapter;
+use crate::storage::models::ProcessPriority;
+use async_trait::async_trait;
+use portable_pty::{native_pty_system, CommandBuilder, MasterPty, PtySize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncWriteExt, DuplexStream};
+use tokio::process::Command;
+use tokio::sync::broadcast;
+
+/// Error code returned when a project has no interactive iteration running
+pub const TERMINAL_NOT_RUNNING: &str = "terminal_not_running";
+
+/// Output replayed to a view that attaches mid-iteration
+const SCROLLBACK_BYTES: usize = 64 * 1024;
+const READ_CHUNK: usize = 8 * 1024;
+const DEFAULT_SIZE: PtySize = PtySize {
+    rows: 40,
+    cols: 120,
+    pixel_width: 0,
+    pixel_height: 0,
+};
+
+static SESSIONS: Mutex<Option<HashMap<String, Arc<TerminalSession>>>> = Mutex::new(None);
+
+fn with_sessions<T>(f: impl FnOnce(&mut HashMap<String, Arc<TerminalSession>>) -> T) -> T {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    f(sessions.get_or_insert_with(HashMap::new))
+}
+
+/// The running interactive iteration of a project, if any
+pub fn session(project_id: &str) -> Option<Arc<TerminalSession>> {
+    with_sessions(|sessions| sessions.get(project_id).cloned())
+}
+
+/// Input and output ends of an iteration's pseudo-terminal
+pub struct TerminalSession {
+    master: Mutex<Box<dyn MasterPty + Send>>,
+    writer: Mutex<Box<dyn Write + Send>>,
+    output: broadcast::Sender<Vec<u8>>,
+    scrollback: Mutex<VecDeque<u8>>,
+    /// Id of the attached view, 0 when none is
+    attachment: AtomicU64,
+    next_attachment: AtomicU64,
+}
+
+impl TerminalSession {
+    /// Recent output and a receiver for everything after it, with nothing lost in between
+    pub fn subscribe(&self) -> (Vec<u8>, broadcast::Receiver<Vec<u8>>) {
+        let scrollback = self.scrollback.lock().unwrap_or_else(|e| e.into_inner());
+        (
+            scrollback.iter().copied().collect(),
+            self.output.subscribe(),
+        )
+    }
+
+    /// Attach a view, replacing any earlier one; returns its id
+    pub fn attach(&self) -> u64 {
+        let id = self.next_attachment.fetch_add(1, Ordering::SeqCst) + 1;
+        self.attachment.store(id, Ordering::SeqCst);
+        id
+    }
+
+    pub fn detach(&self) {
+        self.attachment.store(0, Ordering::SeqCst);
+    }
+
+    /// Whether the view with this id is still the attached one
+    pub fn is_attached(&self, id: u64) -> bool {
+        self.attachment.load(Ordering::SeqCst) == id
+    }
+
+    /// Type into the terminal, as if on the CLI's keyboard
+    pub fn write_input(&self, data: &[u8]) -> Result<(), String> {
+        let mut writer = self.writer.lock().unwrap_or_else(|e| e.into_inner());
+        writer
+            .write_all(data)
+            .and_then(|_| writer.flush())
+            .map_err(|e| format!("Failed to write to terminal: {}", e))
+    }
+
+    pub fn resize(&self, cols: u16, rows: u16) -> Result<(), String> {
+        let master = self.master.lock().unwrap_or_else(|e| e.into_inner());
+        master
+            .resize(PtySize {
+                rows: rows.max(1),
+                cols: cols.max(1),
+                pixel_width: 0,
+                pixel_height: 0,
+            })
+            .map_err(|e| e.to_string())
+    }
+
+    fn record(&self, chunk: &[u8]) {
+        let mut scrollback = self.scrollback.lock().unwrap_or_else(|e| e.into_inner());
+        scrollback.extend(chunk);
+        let excess = scrollback.len().saturating_sub(SCROLLBACK_BYTES);
+        scrollback.drain(..excess);
+        // Sent under the lock so `subscribe` sees each chunk exactly once
+        let _ = self.output.send(chunk.to_vec());
+    }
+}
+
+/// Runs the adapter's command inside a pseudo-terminal
+pub struct PtyExecutor {
+    pub adapter: Box<dyn CliAdapter>,
+    pub priority: ProcessPriority,
+    pub memory_limit_mb: Option<u64>,
+}
+
+/// The adapter's command line, environment and directory, for the terminal to run
+fn command_builder(cmd: &Command) -> CommandBuilder {
+    let std_cmd = cmd.as_std();
+    let mut builder = CommandBuilder::new(std_cmd.get_program());
+    builder.args(std_cmd.get_args());
+    for (key, value) in std_cmd.get_envs() {
+        match value {
+            Some(value) => builder.env(key, value),
+            None => builder.env_remove(key),
+        }
+    }
+    if let Some(dir) = std_cmd.get_current_dir() {
+        builder.cwd(dir);
+    }
+    builder
+}
+
+#[async_trait]
+impl Executor for PtyExecutor {
+    async fn start(&self, request: IterationRequest<'_>) -> Result<Box<dyn Execution>, String> {
+        let mut cmd =
+            self.adapter
+                .build_command(request.prompt, request.working_dir, request.options);
+        cmd.envs(request.env.iter().cloned());
+        if request.network_blocked {
+            cmd.envs(super::network::offline_env(request.api_hosts));
+        }
+        let mut execution = PtyExecution::spawn(&cmd, request.project_id)?;
+        #[cfg(unix)]
+        if let Some(pid) = execution.child.process_id() {
+            if let Err(e) = super::priority::apply_to_process(pid, self.priority) {
+                execution.kill().await;
+                return Err(format!(
+                    "Failed to lower {} priority: {}",
+                    self.adapter.name(),
+                    e
+                ));
+            }
+        }
+        if let Some(pid) = execution.child.process_id() {
+            if let Err(e) = super::resources::apply_to_process(pid, self.memory_limit_mb) {
+                execution.kill().await;
+                return Err(format!(
+                    "Failed to limit {} memory: {}",
+                    self.adapter.name(),
+                    e
+                ));
+            }
+        }
+        Ok(Box::new(execution))
+    }
+}
+
+/// Copy terminal output to attached views and the engine's line reader until the CLI exits
+fn spawn_reader(
+    mut reader: Box<dyn Read + Send>,
+    session: Arc<TerminalSession>,
+    mut lines: DuplexStream,
+) {
+    let runtime = tokio::runtime::Handle::current();
+    std::thread::spawn(move || {
+        let mut chunk = [0u8; READ_CHUNK];
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            session.record(&chunk[..read]);
+            if runtime.block_on(lines.write_all(&chunk[..read])).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// An iteration running in a pseudo-terminal
+pub struct PtyExecution {
+    project_id: String,
+    child: Box<dyn portable_pty::Child + Send + Sync>,
+    session: Arc<TerminalSession>,
+    lines: ConsoleLines<DuplexStream>,
+    killed: bool,
+    _tracked: TrackedProcess,
+}
+
+impl PtyExecution {
+    /// Spawn `cmd` in a new terminal registered for `project_id` until dropped
+    fn spawn(cmd: &Command, project_id: &str) -> Result<Self, String> {
+        let pair = native_pty_system()
+            .openpty(DEFAULT_SIZE)
+            .map_err(|e| format!("Failed to open a terminal: {}", e))?;
+        let child = pair
+            .slave
+            .spawn_command(command_builder(cmd))
+            .map_err(|e| e.to_string())?;
+        // The child holds its own handle; ours would keep the terminal open after it exits
+        drop(pair.slave);
+        let reader = pair.master.try_clone_reader().map_err(|e| e.to_string())?;
+        let writer = pair.master.take_writer().map_err(|e| e.to_string())?;
+
+        let session = Arc::new(TerminalSession {
+            master: Mutex::new(pair.master),
+            writer: Mutex::new(writer),
+            output: broadcast::channel(256).0,
+            scrollback: Mutex::new(VecDeque::new()),
+            attachment: AtomicU64::new(0),
+            next_attachment: AtomicU64::new(0),
+        });
+        with_sessions(|sessions| sessions.insert(project_id.to_string(), session.clone()));
+
+        let (lines_writer, lines_reader) = tokio::io::duplex(READ_CHUNK * 8);
+        spawn_reader(reader, session.clone(), lines_writer);
+
+        let program = PathBuf::from(cmd.as_std().get_program());
+        Ok(Self {
+            project_id: project_id.to_string(),
+            _tracked: processes::track(project_id, child.process_id(), &program),
+            child,
+            session,
+            lines: ConsoleLines::new(lines_reader),
+            killed: false,
+        })
+    }
+}
+
+impl Drop for PtyExecution {
+    fn drop(&mut self) {
+        with_sessions(|sessions| {
+            if sessions
+                .get(&self.project_id)
+                .is_some_and(|s| Arc::ptr_eq(s, &self.session))
+            {
+                sessions.remove(&self.project_id);
+            }
+        });
+    }
+}
+
+#[async_trait]
+impl Execution for PtyExecution {
+    async fn next_line(&mut self) -> Option<OutputLine> {
+        match self.lines.next_segment().await.ok().flatten()? {
+            Segment::Line(line) => Some(OutputLine::Stdout(line)),
+            Segment::Progress(text) => Some(OutputLine::Progress(text)),
+        }
+    }
+
+    async fn kill(&mut self) {
+        self.killed = true;
+        let _ = self.child.kill();
+    }
+
+    fn pending_line(&self) -> Option<String> {
+        self.lines.pending()
+    }
+
+    async fn answer(&mut self, reply: &str) -> Result<(), String> {
+        // Enter in a terminal is a carriage return
+        self.session.write_input(format!("{}\r", reply).as_bytes())
+    }
+
+    async fn wait(&mut self) -> Option<i32> {
+        // `Child::wait` blocks; polling keeps `kill` usable meanwhile
+        loop {
+            match self.child.try_wait() {
+                Ok(Some(status)) if !self.killed => return Some(status.exit_code() as i32),
+                Ok(Some(_)) | Err(_) => return None,
+                Ok(None) => tokio::time::sleep(Duration::from_millis(50)).await,
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env_async, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn runs_in_a_terminal_that_accepts_input() {
+        let _env_lock = lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", "read answer; echo \"got $answer\"; exit 3"]);
+        let mut execution = PtyExecution::spawn(&cmd, "pty-project").unwrap();
+
+        let terminal = session("pty-project").unwrap();
+        let first = terminal.attach();
+        let second = terminal.attach();
+        assert!(!terminal.is_attached(first));
+        assert!(terminal.is_attached(second));
+        let (_, mut output) = terminal.subscribe();
+        terminal.write_input(b"yes\n").unwrap();
+
+        let mut answered = false;
+        while let Some(line) = execution.next_line().await {
+            if line == OutputLine::Stdout("got yes".to_string()) {
+                answered = true;
+            }
+        }
+        assert!(answered);
+        assert!(!output.recv().await.unwrap().is_empty());
+        assert_eq!(execution.wait().await, Some(3));
+
+        drop(execution);
+        assert!(session("pty-project").is_none());
+    }
+}
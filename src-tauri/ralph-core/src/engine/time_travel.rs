@@ -0,0 +1,154 @@
+//! Time travel: the repository as of one iteration, checked out into a detached
+//! worktree so it can be run or inspected without touching the project's own
+//! checkout. Snapshot worktrees are removed again once they are stale.
+
+use super::benchmark::{add_worktree, remove_worktree};
+use crate::adapters::hide_console_window;
+use crate::storage;
+use crate::storage::models::{IterationRecord, IterationSnapshot};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::BTreeSet;
+use std::path::Path;
+use tokio::process::Command;
+
+/// Snapshot worktrees older than this are removed by `cleanup_stale`
+const STALE_AFTER_HOURS: i64 = 24;
+
+/// Commit the repository was at when `iteration` ended: that of the last
+/// iteration up to it that committed, or the session's starting commit
+pub fn commit_for_iteration(
+    records: &[IterationRecord],
+    iteration: u32,
+    session_start: Option<&str>,
+) -> Result<String, String> {
+    if !records.iter().any(|record| record.number == iteration) {
+        return Err(format!("The session has no iteration {}", iteration));
+    }
+    records
+        .iter()
+        .filter(|record| record.number <= iteration)
+        .rev()
+        .find_map(|record| record.commit_shas.last())
+        .map(String::as_str)
+        .or(session_start)
+        .map(str::to_string)
+        .ok_or_else(|| "The session started before the project's first commit".to_string())
+}
+
+fn is_stale(snapshot: &IterationSnapshot, now: DateTime<Utc>) -> bool {
+    now - snapshot.created_at > Duration::hours(STALE_AFTER_HOURS)
+        || !Path::new(&snapshot.path).exists()
+}
+
+/// Check out the snapshot's commit at its path, which must not exist or be
+/// empty, and remember it for cleanup
+pub async fn checkout(mut snapshot: IterationSnapshot) -> Result<IterationSnapshot, String> {
+    let dest = Path::new(&snapshot.path);
+    if dest
+        .read_dir()
+        .is_ok_and(|mut entries| entries.next().is_some())
+    {
+        return Err(format!("{} already exists and is not empty", snapshot.path));
+    }
+    add_worktree(Path::new(&snapshot.project_path), dest, &snapshot.commit).await?;
+    snapshot.created_at = Utc::now();
+    let mut snapshots = storage::load_iteration_snapshots().map_err(|e| e.to_string())?;
+    snapshots.push(snapshot.clone());
+    storage::save_iteration_snapshots(&snapshots).map_err(|e| e.to_string())?;
+    Ok(snapshot)
+}
+
+/// Remove the snapshot worktree at `path`, discarding whatever was changed in it
+pub async fn remove(path: &str) -> Result<(), String> {
+    let mut snapshots = storage::load_iteration_snapshots().map_err(|e| e.to_string())?;
+    let index = snapshots
+        .iter()
+        .position(|snapshot| snapshot.path == path)
+        .ok_or_else(|| format!("{} is not an iteration snapshot", path))?;
+    let snapshot = snapshots.remove(index);
+    remove_worktree(Path::new(&snapshot.project_path), Path::new(&snapshot.path)).await;
+    storage::save_iteration_snapshots(&snapshots).map_err(|e| e.to_string())
+}
+
+/// Remove snapshot worktrees that are stale or were deleted by hand; returns
+/// how many were removed
+pub async fn cleanup_stale() -> usize {
+    let Ok(snapshots) = storage::load_iteration_snapshots() else {
+        return 0;
+    };
+    let now = Utc::now();
+    let (stale, kept): (Vec<_>, Vec<_>) = snapshots
+        .into_iter()
+        .partition(|snapshot| is_stale(snapshot, now));
+    for snapshot in &stale {
+        remove_worktree(Path::new(&snapshot.project_path), Path::new(&snapshot.path)).await;
+    }
+    // Worktrees deleted by hand fail to remove and are only forgotten by a prune
+    let projects: BTreeSet<&str> = stale.iter().map(|s| s.project_path.as_str()).collect();
+    for project_path in projects {
+        let mut cmd = Command::new("git");
+        cmd.arg("-C").arg(project_path).args(["worktree", "prune"]);
+        hide_console_window(&mut cmd);
+        let _ = cmd.output().await;
+    }
+    if !stale.is_empty() {
+        let _ = storage::save_iteration_snapshots(&kept);
+    }
+    stale.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::models::IterationOutcome;
+
+    fn record(number: u32, commits: &[&str]) -> IterationRecord {
+        let mut record = IterationRecord::start(number);
+        record.finish(IterationOutcome::Continued);
+        record.commit_shas = commits.iter().map(|sha| sha.to_string()).collect();
+        record
+    }
+
+    #[test]
+    fn iterations_resolve_to_their_latest_commit() {
+        let records = vec![record(1, &["a1", "a2"]), record(2, &[]), record(3, &["c1"])];
+        assert_eq!(
+            commit_for_iteration(&records, 1, Some("start")).unwrap(),
+            "a2"
+        );
+        assert_eq!(
+            commit_for_iteration(&records, 2, Some("start")).unwrap(),
+            "a2"
+        );
+        assert_eq!(commit_for_iteration(&records, 3, None).unwrap(), "c1");
+        assert!(commit_for_iteration(&records, 4, Some("start")).is_err());
+
+        let uncommitted = vec![record(1, &[])];
+        assert_eq!(
+            commit_for_iteration(&uncommitted, 1, Some("start")).unwrap(),
+            "start"
+        );
+        assert!(commit_for_iteration(&uncommitted, 1, None).is_err());
+    }
+
+    #[test]
+    fn old_or_missing_snapshots_are_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let snapshot = IterationSnapshot {
+            project_id: uuid::Uuid::nil(),
+            session_id: "s1".to_string(),
+            iteration: 1,
+            commit: "a1".to_string(),
+            project_path: String::new(),
+            path: dir.path().to_string_lossy().to_string(),
+            created_at: Utc::now(),
+        };
+        assert!(!is_stale(&snapshot, Utc::now()));
+        assert!(is_stale(&snapshot, Utc::now() + Duration::hours(25)));
+        let missing = IterationSnapshot {
+            path: dir.path().join("gone").to_string_lossy().to_string(),
+            ..snapshot
+        };
+        assert!(is_stale(&missing, Utc::now()));
+    }
+}
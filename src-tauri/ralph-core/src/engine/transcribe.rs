@@ -0,0 +1,97 @@
+use crate::adapters::{apply_extended_path, command_for_cli, resolve_cli_path};
+use std::path::Path;
+use std::process::Stdio;
+
+/// whisper.cpp binaries tried when no transcription command is configured
+const WHISPER_BINARIES: &[&str] = &["whisper-cli", "whisper-cpp"];
+const WHISPER_ARGS: &str = "-nt -np -f {input}";
+
+/// Split a command template into program and arguments, filling in `{input}`
+fn build_args(template: &str, input: &str) -> Option<(String, Vec<String>)> {
+    let mut parts = template
+        .split_whitespace()
+        .map(|part| part.replace("{input}", input));
+    let exe = parts.next()?;
+    Some((exe, parts.collect()))
+}
+
+fn default_template() -> Option<String> {
+    WHISPER_BINARIES
+        .iter()
+        .find_map(|binary| resolve_cli_path(binary))
+        .map(|path| format!("{} {}", path, WHISPER_ARGS))
+}
+
+/// Transcribe an audio file with the configured command (or whisper.cpp), returning the text
+pub async fn transcribe_audio(audio_path: &Path, command: Option<&str>) -> Result<String, String> {
+    if !audio_path.is_file() {
+        return Err(format!("Audio file not found: {}", audio_path.display()));
+    }
+    let template = match command.map(str::trim).filter(|c| !c.is_empty()) {
+        Some(command) => command.to_string(),
+        None => default_template()
+            .ok_or("No transcription command configured and whisper.cpp was not found on PATH")?,
+    };
+    let input = audio_path.to_string_lossy();
+    let (exe, args) = build_args(&template, &input).ok_or("Transcription command is empty")?;
+    let working_dir = audio_path.parent().unwrap_or(Path::new("."));
+
+    let mut cmd = command_for_cli(&exe, &args, working_dir);
+    apply_extended_path(&mut cmd);
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run transcription command: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Transcription failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    let transcript = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    if transcript.is_empty() {
+        return Err("Transcription produced no text".to_string());
+    }
+    Ok(transcript)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn template_substitutes_input_after_splitting() {
+        let (exe, args) =
+            build_args("whisper-cli -m model.bin -f {input}", "/tmp/my note.wav").unwrap();
+        assert_eq!(exe, "whisper-cli");
+        assert_eq!(args, vec!["-m", "model.bin", "-f", "/tmp/my note.wav"]);
+        assert!(build_args("   ", "x").is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn transcribe_runs_configured_command() {
+        let _lock = crate::test_support::lock_env_async().await;
+        let _shell = crate::test_support::EnvVarGuard::set("SHELL", "/bin/sh");
+        let dir = tempfile::tempdir().unwrap();
+        let audio = dir.path().join("note.wav");
+        std::fs::write(&audio, b"RIFF").unwrap();
+
+        let transcript = transcribe_audio(&audio, Some("printf {input}"))
+            .await
+            .unwrap();
+        assert!(transcript.ends_with("note.wav"));
+
+        let missing = transcribe_audio(&dir.path().join("nope.wav"), Some("printf x")).await;
+        assert!(missing.unwrap_err().contains("not found"));
+    }
+}
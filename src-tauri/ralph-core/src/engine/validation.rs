@@ -0,0 +1,70 @@
+//! Validation hook run after every iteration, independently of completion: when
+//! the command (a build, a linter) fails, its output is handed to the next
+//! iteration so the agent works against the real state of the build.
+
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+/// Longest a validation run may take
+const VALIDATION_TIMEOUT: Duration = Duration::from_secs(600);
+/// Tail of the failure output passed on to the next prompt
+const MAX_OUTPUT_CHARS: usize = 4000;
+
+/// A validation run that did not pass
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    pub command: String,
+    /// Combined stdout and stderr, shortened to its end
+    pub output: String,
+}
+
+/// The end of `output`, where build tools usually sum up what went wrong
+fn tail(output: &str) -> String {
+    let output = output.trim();
+    let count = output.chars().count();
+    if count <= MAX_OUTPUT_CHARS {
+        return output.to_string();
+    }
+    let kept: String = output.chars().skip(count - MAX_OUTPUT_CHARS).collect();
+    format!("... (truncated) ...\n{}", kept)
+}
+
+/// Run `command` in `dir`; `None` when it exits zero
+pub async fn run(command: &str, dir: &Path) -> Option<Failure> {
+    let mut cmd = super::screenshot::shell_command(command, dir);
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    let output = match tokio::time::timeout(VALIDATION_TIMEOUT, cmd.output()).await {
+        Ok(Ok(output)) if output.status.success() => return None,
+        Ok(Ok(output)) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            match output.status.code() {
+                Some(code) => format!("{}\n(exit code {})", text.trim_end(), code),
+                None => text,
+            }
+        }
+        Ok(Err(e)) => format!("Failed to run: {}", e),
+        Err(_) => format!("Timed out after {} seconds", VALIDATION_TIMEOUT.as_secs()),
+    };
+    Some(Failure {
+        command: command.to_string(),
+        output: tail(&output),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_end_of_long_output() {
+        let output = format!("{}error[E0308]: mismatched types", "warning\n".repeat(1000));
+        let kept = tail(&output);
+        assert!(kept.starts_with("... (truncated) ..."));
+        assert!(kept.ends_with("mismatched types"));
+        assert_eq!(tail("  ok \n"), "ok");
+    }
+}
@@ -0,0 +1,102 @@
+//! Streams output only while the window has focus. In the background every line
+//! still goes to the session log, but the webview just gets a periodic summary,
+//! which keeps long unattended runs from burning CPU on rendering.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Minimum time between summaries of held-back output
+pub const SUMMARY_INTERVAL: Duration = Duration::from_secs(5);
+
+static WINDOW_FOCUSED: AtomicBool = AtomicBool::new(true);
+
+pub fn set_window_focused(focused: bool) {
+    WINDOW_FOCUSED.store(focused, Ordering::Relaxed);
+}
+
+pub fn window_focused() -> bool {
+    WINDOW_FOCUSED.load(Ordering::Relaxed)
+}
+
+/// Output lines held back since the last summary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeldOutput {
+    pub iteration: u32,
+    pub lines: u32,
+    pub last_line: String,
+}
+
+#[derive(Debug, Default)]
+pub struct OutputThrottle {
+    held: Option<HeldOutput>,
+    last_summary: Option<Instant>,
+}
+
+impl OutputThrottle {
+    /// Hold back a line, returning a summary once `SUMMARY_INTERVAL` has passed
+    /// since the previous one
+    pub fn hold(&mut self, iteration: u32, line: &str, now: Instant) -> Option<HeldOutput> {
+        let held = self.held.get_or_insert_with(|| HeldOutput {
+            iteration,
+            lines: 0,
+            last_line: String::new(),
+        });
+        held.iteration = iteration;
+        held.lines += 1;
+        held.last_line = line.to_string();
+
+        let due = self
+            .last_summary
+            .is_none_or(|last| now.duration_since(last) >= SUMMARY_INTERVAL);
+        if due {
+            self.last_summary = Some(now);
+            self.held.take()
+        } else {
+            None
+        }
+    }
+
+    /// Whatever is still held, e.g. before an iteration ends or focus returns
+    pub fn flush(&mut self) -> Option<HeldOutput> {
+        self.held.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_at_most_once_per_interval() {
+        let mut throttle = OutputThrottle::default();
+        let start = Instant::now();
+
+        let first = throttle.hold(1, "compiling", start).unwrap();
+        assert_eq!(first.lines, 1);
+        assert_eq!(throttle.hold(1, "a", start + Duration::from_secs(1)), None);
+        assert_eq!(throttle.hold(1, "b", start + Duration::from_secs(2)), None);
+
+        let second = throttle
+            .hold(1, "c", start + SUMMARY_INTERVAL + Duration::from_secs(1))
+            .unwrap();
+        assert_eq!(second.lines, 3);
+        assert_eq!(second.last_line, "c");
+        assert_eq!(throttle.flush(), None);
+    }
+
+    #[test]
+    fn flush_returns_pending_lines() {
+        let mut throttle = OutputThrottle::default();
+        let start = Instant::now();
+        throttle.hold(2, "x", start);
+        throttle.hold(2, "y", start);
+        assert_eq!(
+            throttle.flush(),
+            Some(HeldOutput {
+                iteration: 2,
+                lines: 1,
+                last_line: "y".to_string(),
+            })
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use crate::engine::ai_brainstorm::call_brainstorm_cli;
+use crate::storage::models::CliType;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Codebase map location, relative to the project root
+pub const CONTEXT_FILE: &str = ".ralph/context.md";
+
+const WARMUP_PROMPT: &str = r#"You are indexing a code repository for another coding agent. Do NOT modify any files.
+
+Explore the repository in the current directory and produce a concise codebase map in Markdown with these sections:
+## Key Directories
+## Entry Points
+## Build Commands
+## Test Commands
+## Conventions
+
+Keep it under 60 lines. Output only the Markdown."#;
+
+pub fn context_path(project_path: &Path) -> PathBuf {
+    project_path.join(CONTEXT_FILE)
+}
+
+/// Load a previously generated codebase map
+pub fn load_context(project_path: &Path) -> Option<String> {
+    let content = fs::read_to_string(context_path(project_path)).ok()?;
+    let trimmed = content.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+fn save_context(project_path: &Path, content: &str) -> Result<(), String> {
+    let path = context_path(project_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    fs::write(&path, format!("{}\n", content.trim())).map_err(|e| e.to_string())
+}
+
+/// Run a readonly adapter call that maps the codebase and persist the result
+pub async fn run_warmup(
+    cli_type: CliType,
+    project_path: &Path,
+    skip_git_repo_check: bool,
+) -> Result<String, String> {
+    let output =
+        call_brainstorm_cli(cli_type, project_path, WARMUP_PROMPT, skip_git_repo_check).await?;
+    let context = output.trim();
+    if context.is_empty() {
+        return Err("Warm-up produced no output".to_string());
+    }
+    save_context(project_path, context)?;
+    Ok(context.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn context_roundtrip() {
+        let dir = tempdir().unwrap();
+        assert!(load_context(dir.path()).is_none());
+        save_context(dir.path(), "## Key Directories\n- src/\n").unwrap();
+        assert_eq!(
+            load_context(dir.path()).as_deref(),
+            Some("## Key Directories\n- src/")
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use std::path::{Component, Path, PathBuf};
+
+/// Normalize a project-relative subdirectory; empty input means the project root
+pub fn normalize_subdir(subdir: &str) -> Result<Option<String>, String> {
+    let trimmed = subdir.trim().trim_matches(|c| c == '/' || c == '\\');
+    if trimmed.is_empty() || trimmed == "." {
+        return Ok(None);
+    }
+    let path = Path::new(trimmed);
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+            Component::CurDir => {}
+            _ => {
+                return Err(format!(
+                    "Working directory must stay inside the project: {}",
+                    subdir
+                ))
+            }
+        }
+    }
+    if parts.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(parts.join("/")))
+}
+
+/// Resolve the loop's working directory, rejecting paths that escape the project root
+pub fn resolve_working_dir(project_path: &Path, subdir: Option<&str>) -> Result<PathBuf, String> {
+    let Some(subdir) = subdir.map(normalize_subdir).transpose()?.flatten() else {
+        return Ok(project_path.to_path_buf());
+    };
+    let dir = project_path.join(&subdir);
+    if !dir.is_dir() {
+        return Err(format!("Working directory not found: {}", subdir));
+    }
+    // Symlinks may still point outside the project
+    let root = project_path.canonicalize().map_err(|e| e.to_string())?;
+    let resolved = dir.canonicalize().map_err(|e| e.to_string())?;
+    if !resolved.starts_with(&root) {
+        return Err(format!(
+            "Working directory must stay inside the project: {}",
+            subdir
+        ));
+    }
+    Ok(dir)
+}
+
+/// Whether the project directory is gone, e.g. deleted or on a network drive that dropped
+pub fn path_missing(path: &Path) -> bool {
+    match std::fs::metadata(path) {
+        Ok(metadata) => !metadata.is_dir(),
+        Err(e) => e.kind() == std::io::ErrorKind::NotFound,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn normalizes_and_rejects_escaping_subdirs() {
+        assert_eq!(normalize_subdir("  ").unwrap(), None);
+        assert_eq!(normalize_subdir("./").unwrap(), None);
+        assert_eq!(
+            normalize_subdir("/packages/api/").unwrap(),
+            Some("packages/api".to_string())
+        );
+        assert!(normalize_subdir("../other").is_err());
+        assert!(normalize_subdir("packages/../../other").is_err());
+    }
+
+    #[test]
+    fn resolves_existing_subdir_inside_project() {
+        let dir = tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("packages/api")).unwrap();
+
+        assert_eq!(resolve_working_dir(dir.path(), None).unwrap(), dir.path());
+        assert_eq!(
+            resolve_working_dir(dir.path(), Some("packages/api")).unwrap(),
+            dir.path().join("packages/api")
+        );
+        assert!(resolve_working_dir(dir.path(), Some("packages/web")).is_err());
+    }
+
+    #[test]
+    fn detects_a_missing_project_directory() {
+        let dir = tempdir().unwrap();
+        assert!(!path_missing(dir.path()));
+        assert!(path_missing(&dir.path().join("gone")));
+
+        let file = dir.path().join("file.txt");
+        std::fs::write(&file, "").unwrap();
+        assert!(path_missing(&file));
+    }
+}
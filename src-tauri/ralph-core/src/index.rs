@@ -0,0 +1,149 @@
+//! Dependency-light local text index: BM25 over tokenized documents, kept up to
+//! date one document at a time as they are saved, so search works offline
+//! without embeddings or a vector database.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// BM25 term-frequency saturation and length normalization
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+const STOPWORDS: &[&str] = &[
+    "and", "are", "but", "can", "for", "from", "has", "have", "into", "its", "not", "now", "that",
+    "the", "then", "this", "use", "was", "were", "when", "with", "which", "will", "all", "any",
+    "also", "only", "should", "make", "sure",
+];
+
+/// Lowercase keywords of `text`, without stopwords and very short words
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| word.chars().count() >= 3)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Inverted index of documents identified by `K`
+#[derive(Debug)]
+pub struct TextIndex<K> {
+    /// Term -> document -> occurrences
+    postings: HashMap<String, HashMap<K, u32>>,
+    /// Document -> its distinct terms and token count, for removal and normalization
+    documents: HashMap<K, (Vec<String>, usize)>,
+    total_tokens: usize,
+}
+
+impl<K> Default for TextIndex<K> {
+    fn default() -> Self {
+        Self {
+            postings: HashMap::new(),
+            documents: HashMap::new(),
+            total_tokens: 0,
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash> TextIndex<K> {
+    /// Index `text` as document `id`, replacing what it held before
+    pub fn upsert(&mut self, id: K, text: &str) {
+        self.remove(&id);
+        let tokens = tokenize(text);
+        let mut counts: HashMap<String, u32> = HashMap::new();
+        for token in &tokens {
+            *counts.entry(token.clone()).or_default() += 1;
+        }
+        let terms: Vec<String> = counts.keys().cloned().collect();
+        for (term, count) in counts {
+            self.postings
+                .entry(term)
+                .or_default()
+                .insert(id.clone(), count);
+        }
+        self.total_tokens += tokens.len();
+        self.documents.insert(id, (terms, tokens.len()));
+    }
+
+    pub fn remove(&mut self, id: &K) {
+        let Some((terms, len)) = self.documents.remove(id) else {
+            return;
+        };
+        for term in terms {
+            if let Some(docs) = self.postings.get_mut(&term) {
+                docs.remove(id);
+                if docs.is_empty() {
+                    self.postings.remove(&term);
+                }
+            }
+        }
+        self.total_tokens -= len;
+    }
+
+    /// The `k` documents scoring highest for `query`, best first; documents
+    /// sharing no keyword with it are left out
+    pub fn search(&self, query: &str, k: usize) -> Vec<(K, f64)> {
+        if self.documents.is_empty() {
+            return Vec::new();
+        }
+        let total = self.documents.len() as f64;
+        let average_len = (self.total_tokens as f64 / total).max(1.0);
+        let terms: HashSet<String> = tokenize(query).into_iter().collect();
+        let mut scores: HashMap<&K, f64> = HashMap::new();
+        for term in &terms {
+            let Some(docs) = self.postings.get(term) else {
+                continue;
+            };
+            let df = docs.len() as f64;
+            let idf = ((total - df + 0.5) / (df + 0.5) + 1.0).ln();
+            for (id, count) in docs {
+                let len = self.documents.get(id).map_or(0, |(_, len)| *len) as f64;
+                let frequency = f64::from(*count);
+                let norm = K1 * (1.0 - B + B * len / average_len);
+                *scores.entry(id).or_default() += idf * frequency * (K1 + 1.0) / (frequency + norm);
+            }
+        }
+        let mut ranked: Vec<(K, f64)> = scores
+            .into_iter()
+            .map(|(id, score)| (id.clone(), score))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        ranked.truncate(k);
+        ranked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_documents_sharing_rare_keywords_first() {
+        let mut index = TextIndex::default();
+        index.upsert(
+            1,
+            "Fix the build on macOS by pinning openssl to the Homebrew prefix",
+        );
+        index.upsert(2, "Fix flaky login test by waiting for the session cookie");
+        index.upsert(3, "Fix typo in the README");
+
+        let found = index.search("Set up the Rust build with openssl on macOS", 2);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 1);
+        assert!(index.search("the and for", 3).is_empty());
+    }
+
+    #[test]
+    fn updates_replace_and_remove_documents() {
+        let mut index = TextIndex::default();
+        index.upsert("a", "docker socket permission denied");
+        index.upsert("b", "postgres connection refused");
+        index.upsert("a", "node version mismatch");
+        assert!(index.search("docker", 5).is_empty());
+        assert_eq!(index.search("node version", 5)[0].0, "a");
+
+        index.remove(&"b");
+        assert!(index.search("postgres", 5).is_empty());
+        assert_eq!(index.documents.len(), 1);
+        assert_eq!(index.total_tokens, 3);
+    }
+}
@@ -0,0 +1,48 @@
+//! The Ralph loop without the desktop app: CLI adapters, the loop engine and
+//! the storage under `~/.ralph-desktop/` that Ralph Desktop is built on.
+//!
+//! A loop runs one coding-agent CLI again and again on a task until it signals
+//! completion or runs out of iterations. Events a loop emits go to an
+//! [`engine::EventSink`]; the desktop app forwards them to its windows, other
+//! programs can log or inspect them:
+//!
+//! ```no_run
+//! use ralph_core::engine::targets::session_id_for;
+//! use ralph_core::engine::{EventSink, LoopConfig, LoopEngine, LoopEvent, RawOutputEvent};
+//! use ralph_core::storage;
+//! use std::sync::Arc;
+//!
+//! struct Print;
+//!
+//! impl EventSink for Print {
+//!     fn loop_event(&self, event: &LoopEvent, _isolated: bool) {
+//!         println!("{}", serde_json::to_string(event).unwrap());
+//!     }
+//!
+//!     fn raw_output(&self, _event: &RawOutputEvent) {}
+//! }
+//!
+//! # async fn run(project_id: uuid::Uuid) -> Result<(), String> {
+//! let state = storage::load_project_state(&project_id).map_err(|e| e.to_string())?;
+//! let task = state.task.clone().ok_or("No task configured for this project")?;
+//! let config = storage::load_workspace_config(state.workspace_id.as_ref())
+//!     .map_err(|e| e.to_string())?;
+//! let path = std::path::PathBuf::from(&state.path);
+//!
+//! let mut loop_config = LoopConfig::for_task(&task, &config, &state, &path)?;
+//! loop_config.session_id = session_id_for(None);
+//! let engine = LoopEngine::new(project_id.to_string(), path, loop_config, Arc::new(Print));
+//! let outcome = engine.start().await?;
+//! println!("{:?}", outcome);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod adapters;
+pub mod api_agent;
+pub mod engine;
+pub mod index;
+pub mod security;
+pub mod storage;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
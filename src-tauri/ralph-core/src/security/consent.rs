@@ -0,0 +1,243 @@
+//! Per-capability acknowledgments: each risky capability is confirmed once
+//! where it is used, and again when what it points at changes.
+
+use crate::engine::plugins::{self, Plugin};
+use crate::storage::models::{
+    Capability, ConsentGrant, GlobalConfig, PendingConsent, ProjectState, RemoteTarget,
+};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Error code returned when an action needs a capability the user has not acknowledged
+pub const CONSENT_REQUIRED: &str = "consent_required";
+
+fn remote_scope(remote: &RemoteTarget) -> String {
+    match &remote.user {
+        Some(user) => format!("{}@{}", user, remote.host),
+        None => remote.host.clone(),
+    }
+}
+
+/// Acknowledgment needed to serve the observer feed
+pub fn observer_feed() -> PendingConsent {
+    PendingConsent {
+        capability: Capability::ObserverFeed,
+        project_id: None,
+        scope: None,
+    }
+}
+
+/// App-wide capabilities currently in use
+pub fn app_requirements(config: &GlobalConfig) -> Vec<PendingConsent> {
+    let mut required = vec![PendingConsent {
+        capability: Capability::FullDiskAccess,
+        project_id: None,
+        scope: None,
+    }];
+    if config.observer_enabled {
+        required.push(observer_feed());
+    }
+    required
+}
+
+/// Acknowledgment needed to run the enabled plugins. The scope lists what each
+/// one executes, so enabling another plugin or changing a command asks again.
+pub fn plugin_execution(plugins: &[Plugin]) -> Option<PendingConsent> {
+    if plugins.is_empty() {
+        return None;
+    }
+    let scope = plugins
+        .iter()
+        .map(|plugin| format!("{}: {}", plugin.manifest.name, plugin.command_line()))
+        .collect::<Vec<_>>()
+        .join("; ");
+    Some(PendingConsent {
+        capability: Capability::PluginExecution,
+        project_id: None,
+        scope: Some(scope),
+    })
+}
+
+/// Capabilities a loop for `project` uses under `config`
+pub fn loop_requirements(config: &GlobalConfig, project: &ProjectState) -> Vec<PendingConsent> {
+    let mut required = vec![PendingConsent {
+        capability: Capability::FullDiskAccess,
+        project_id: None,
+        scope: None,
+    }];
+    if let Some(remote) = &project.remote {
+        required.push(PendingConsent {
+            capability: Capability::RemoteExecution,
+            project_id: Some(project.id),
+            scope: Some(remote_scope(remote)),
+        });
+    }
+    required.extend(plugin_execution(&plugins::load_enabled(
+        &config.enabled_plugins,
+    )));
+    required
+}
+
+/// Full-disk access stays tied to the original permissions confirmation, so
+/// resetting that in settings asks again
+fn is_granted(grants: &[ConsentGrant], config: &GlobalConfig, request: &PendingConsent) -> bool {
+    if request.capability == Capability::FullDiskAccess {
+        return config.permissions_confirmed;
+    }
+    grants.iter().any(|grant| {
+        grant.capability == request.capability
+            && grant.project_id == request.project_id
+            && grant.scope == request.scope
+    })
+}
+
+/// Requirements without a matching grant
+pub fn pending(
+    grants: &[ConsentGrant],
+    config: &GlobalConfig,
+    required: Vec<PendingConsent>,
+) -> Vec<PendingConsent> {
+    required
+        .into_iter()
+        .filter(|request| !is_granted(grants, config, request))
+        .collect()
+}
+
+/// Record a grant, replacing an earlier one for the same capability and project
+pub fn grant(grants: &mut Vec<ConsentGrant>, request: PendingConsent, now: DateTime<Utc>) {
+    revoke(grants, request.capability, request.project_id);
+    grants.push(ConsentGrant {
+        capability: request.capability,
+        project_id: request.project_id,
+        scope: request.scope,
+        granted_at: now,
+    });
+}
+
+/// Drop a grant; returns whether one existed
+pub fn revoke(
+    grants: &mut Vec<ConsentGrant>,
+    capability: Capability,
+    project_id: Option<Uuid>,
+) -> bool {
+    let before = grants.len();
+    grants.retain(|g| !(g.capability == capability && g.project_id == project_id));
+    grants.len() != before
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn remote_project(host: &str) -> ProjectState {
+        let now = Utc::now();
+        ProjectState {
+            id: Uuid::new_v4(),
+            name: "p".to_string(),
+            path: "/tmp/p".to_string(),
+            status: crate::storage::models::ProjectStatus::Ready,
+            workspace_id: None,
+            skip_git_repo_check: false,
+            debug_raw_output: false,
+            network_blocked: false,
+            output_filters: Vec::new(),
+            container: None,
+            remote: Some(RemoteTarget {
+                host: host.to_string(),
+                user: Some("dev".to_string()),
+                port: None,
+                identity_file: None,
+                directory: "/srv/p".to_string(),
+            }),
+            screenshot: None,
+            dependency_updates: None,
+            brainstorm: None,
+            task: None,
+            execution: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn remote_execution_is_asked_again_for_a_new_host() {
+        let config = GlobalConfig {
+            permissions_confirmed: true,
+            ..GlobalConfig::default()
+        };
+        let mut project = remote_project("build-1");
+        let mut grants = Vec::new();
+
+        let needed = pending(&grants, &config, loop_requirements(&config, &project));
+        assert_eq!(needed.len(), 1);
+        assert_eq!(needed[0].scope.as_deref(), Some("dev@build-1"));
+
+        grant(&mut grants, needed[0].clone(), Utc::now());
+        assert!(pending(&grants, &config, loop_requirements(&config, &project)).is_empty());
+
+        project.remote.as_mut().unwrap().host = "build-2".to_string();
+        assert_eq!(
+            pending(&grants, &config, loop_requirements(&config, &project)).len(),
+            1
+        );
+        // The new grant replaces the one for the old host
+        let needed = pending(&grants, &config, loop_requirements(&config, &project));
+        grant(&mut grants, needed[0].clone(), Utc::now());
+        assert_eq!(grants.len(), 1);
+    }
+
+    #[test]
+    fn full_disk_access_follows_the_permissions_confirmation() {
+        let mut config = GlobalConfig::default();
+        let needed = pending(&[], &config, app_requirements(&config));
+        assert_eq!(needed[0].capability, Capability::FullDiskAccess);
+
+        config.permissions_confirmed = true;
+        config.observer_enabled = true;
+        assert_eq!(
+            pending(&[], &config, app_requirements(&config)),
+            vec![observer_feed()]
+        );
+    }
+
+    #[test]
+    fn plugin_execution_is_asked_again_when_a_command_changes() {
+        assert!(plugin_execution(&[]).is_none());
+
+        let mut plugin = Plugin {
+            manifest: crate::storage::models::PluginManifest {
+                name: "notify".to_string(),
+                description: String::new(),
+                command: "notify-send".to_string(),
+                args: vec!["--urgent".to_string()],
+                events: Vec::new(),
+                hooks: Vec::new(),
+            },
+            dir: std::path::PathBuf::from("/nonexistent/notify"),
+        };
+        let needed = plugin_execution(std::slice::from_ref(&plugin)).unwrap();
+        assert_eq!(
+            needed.scope.as_deref(),
+            Some("notify: notify-send --urgent")
+        );
+
+        let config = GlobalConfig {
+            permissions_confirmed: true,
+            ..GlobalConfig::default()
+        };
+        let mut grants = Vec::new();
+        grant(&mut grants, needed, Utc::now());
+        plugin.manifest.command = "curl".to_string();
+        let changed = plugin_execution(std::slice::from_ref(&plugin)).unwrap();
+        assert_eq!(pending(&grants, &config, vec![changed]).len(), 1);
+    }
+
+    #[test]
+    fn revoking_removes_only_the_matching_grant() {
+        let mut grants = Vec::new();
+        grant(&mut grants, observer_feed(), Utc::now());
+        assert!(!revoke(&mut grants, Capability::RemoteExecution, None));
+        assert!(revoke(&mut grants, Capability::ObserverFeed, None));
+        assert!(grants.is_empty());
+    }
+}
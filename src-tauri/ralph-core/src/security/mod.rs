@@ -1,3 +1,5 @@
+pub mod consent;
+
 use regex::Regex;
 
 /// Sanitize log content to remove sensitive information
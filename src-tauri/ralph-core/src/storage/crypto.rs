@@ -0,0 +1,181 @@
+//! Optional encryption of project states and finished session logs, with an
+//! AES-256-GCM key kept in the OS keychain rather than next to the data.
+
+use super::{Result, StorageError};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Prefix marking an encrypted file; anything else is read as plaintext
+const MAGIC: &[u8] = b"RALPHENC1";
+const KEY_LEN: usize = 32;
+const KEYCHAIN_SERVICE: &str = "ralph-desktop";
+const KEYCHAIN_USER: &str = "storage-key";
+
+static KEY: OnceLock<[u8; KEY_LEN]> = OnceLock::new();
+
+fn crypto_error(message: impl std::fmt::Display) -> StorageError {
+    StorageError::Crypto(message.to_string())
+}
+
+/// Whether new writes should be encrypted
+pub fn enabled() -> bool {
+    super::load_config().is_ok_and(|config| config.encrypt_at_rest)
+}
+
+/// Storage key from the keychain, created on first use
+fn key() -> Result<[u8; KEY_LEN]> {
+    if let Some(key) = KEY.get() {
+        return Ok(*key);
+    }
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_USER).map_err(crypto_error)?;
+    let key = match entry.get_password() {
+        Ok(encoded) => decode_key(&encoded)?,
+        Err(keyring::Error::NoEntry) => {
+            let mut key = [0u8; KEY_LEN];
+            SystemRandom::new().fill(&mut key).map_err(crypto_error)?;
+            entry
+                .set_password(&hex::encode(key))
+                .map_err(crypto_error)?;
+            key
+        }
+        Err(e) => return Err(crypto_error(format!("keychain unavailable: {}", e))),
+    };
+    Ok(*KEY.get_or_init(|| key))
+}
+
+/// Use a fixed key in place of the keychain, which tests cannot reach
+#[cfg(test)]
+pub(crate) fn use_test_key() {
+    KEY.get_or_init(|| [7u8; KEY_LEN]);
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; KEY_LEN]> {
+    hex::decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| crypto_error("storage key in keychain is malformed"))
+}
+
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+fn aead_key(key: &[u8; KEY_LEN]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, key).map_err(crypto_error)?;
+    Ok(LessSafeKey::new(key))
+}
+
+/// `MAGIC`, a random nonce, then the ciphertext with its tag
+fn seal(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce = [0u8; NONCE_LEN];
+    SystemRandom::new().fill(&mut nonce).map_err(crypto_error)?;
+    let mut body = plaintext.to_vec();
+    aead_key(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(MAGIC),
+            &mut body,
+        )
+        .map_err(crypto_error)?;
+
+    let mut sealed = Vec::with_capacity(MAGIC.len() + NONCE_LEN + body.len());
+    sealed.extend_from_slice(MAGIC);
+    sealed.extend_from_slice(&nonce);
+    sealed.extend_from_slice(&body);
+    Ok(sealed)
+}
+
+fn open(key: &[u8; KEY_LEN], sealed: &[u8]) -> Result<Vec<u8>> {
+    let rest = &sealed[MAGIC.len()..];
+    if rest.len() < NONCE_LEN {
+        return Err(crypto_error("encrypted file is truncated"));
+    }
+    let (nonce, body) = rest.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(crypto_error)?;
+    let mut body = body.to_vec();
+    let plaintext = aead_key(key)?
+        .open_in_place(nonce, Aad::from(MAGIC), &mut body)
+        .map_err(|_| crypto_error("cannot decrypt file; was the keychain entry replaced?"))?;
+    Ok(plaintext.to_vec())
+}
+
+/// Read a stored text file, decrypting it when it was written encrypted
+pub fn read_text(path: &Path) -> Result<String> {
+    let data = fs::read(path)?;
+    let data = if is_encrypted(&data) {
+        open(&key()?, &data)?
+    } else {
+        data
+    };
+    String::from_utf8(data).map_err(crypto_error)
+}
+
+/// Write a stored text file, encrypted when encryption at rest is enabled
+pub fn write_text(path: &Path, content: &str) -> Result<()> {
+    write_text_as(path, content, enabled())
+}
+
+/// Written through a temporary file, so a crash mid-write leaves the old file intact
+fn write_text_as(path: &Path, content: &str, encrypt: bool) -> Result<()> {
+    let data = if encrypt {
+        seal(&key()?, content.as_bytes())?
+    } else {
+        content.as_bytes().to_vec()
+    };
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    fs::write(&temp, data)?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// Rewrite an existing file encrypted or in plaintext; returns whether it changed
+pub fn convert_file(path: &Path, encrypt: bool) -> Result<bool> {
+    let data = fs::read(path)?;
+    if is_encrypted(&data) == encrypt {
+        return Ok(false);
+    }
+    let content = read_text(path)?;
+    write_text_as(path, &content, encrypt)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sealed_data_round_trips_and_hides_plaintext() {
+        let key = [7u8; KEY_LEN];
+        let sealed = seal(&key, b"secret prompt").unwrap();
+        assert!(is_encrypted(&sealed));
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(open(&key, &sealed).unwrap(), b"secret prompt");
+        // Fresh nonce per write
+        assert_ne!(sealed, seal(&key, b"secret prompt").unwrap());
+    }
+
+    #[test]
+    fn rejects_tampered_data_or_wrong_key() {
+        let key = [7u8; KEY_LEN];
+        let mut sealed = seal(&key, b"state").unwrap();
+        assert!(open(&[8u8; KEY_LEN], &sealed).is_err());
+        let last = sealed.len() - 1;
+        sealed[last] ^= 1;
+        assert!(open(&key, &sealed).is_err());
+        assert!(open(&key, MAGIC).is_err());
+    }
+
+    #[test]
+    fn plaintext_files_are_not_mistaken_for_encrypted() {
+        assert!(!is_encrypted(b"{\"id\":\"x\"}"));
+        assert!(decode_key("abcd").is_err());
+        assert_eq!(
+            decode_key(&hex::encode([1u8; KEY_LEN])).unwrap(),
+            [1u8; KEY_LEN]
+        );
+    }
+}
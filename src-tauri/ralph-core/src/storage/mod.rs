@@ -0,0 +1,1312 @@
+pub mod crypto;
+pub mod models;
+
+use crate::adapters::{gemini_family, resolve_cli_path};
+use crate::storage::models::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum StorageError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Home directory not found")]
+    HomeDirNotFound,
+    #[error("Project not found: {0}")]
+    ProjectNotFound(String),
+    #[error("Encryption error: {0}")]
+    Crypto(String),
+}
+
+pub type Result<T> = std::result::Result<T, StorageError>;
+
+/// Get the Ralph Desktop data directory (~/.ralph-desktop/)
+pub fn get_data_dir() -> Result<PathBuf> {
+    let home = dirs::home_dir().ok_or(StorageError::HomeDirNotFound)?;
+    let data_dir = home.join(".ralph-desktop");
+    Ok(data_dir)
+}
+
+/// Ensure the data directory structure exists
+pub fn ensure_data_dir() -> Result<PathBuf> {
+    let data_dir = get_data_dir()?;
+    fs::create_dir_all(&data_dir)?;
+    fs::create_dir_all(data_dir.join("projects"))?;
+    Ok(data_dir)
+}
+
+/// Detect the first available CLI to use as default
+fn detect_default_cli() -> CliType {
+    if resolve_cli_path("claude").is_some() {
+        return CliType::Claude;
+    }
+    if resolve_cli_path("codex").is_some() {
+        return CliType::Codex;
+    }
+    if resolve_cli_path("opencode").is_some() {
+        return CliType::OpenCode;
+    }
+    if resolve_cli_path("amp").is_some() {
+        return CliType::Amp;
+    }
+    for spec in gemini_family::FORKS {
+        if resolve_cli_path(spec.binary).is_some() {
+            return spec.cli_type;
+        }
+    }
+    if crate::adapters::api::resolve_api_key(&ApiBackendConfig::default()).is_some() {
+        return CliType::Api;
+    }
+    // Fallback to Claude (will show proper error if not installed)
+    CliType::Claude
+}
+
+/// Load global config
+pub fn load_config() -> Result<GlobalConfig> {
+    let data_dir = get_data_dir()?;
+    let config_path = data_dir.join("config.json");
+
+    if !config_path.exists() {
+        // Auto-detect installed CLI for first-time users
+        let config = GlobalConfig {
+            default_cli: detect_default_cli(),
+            ..GlobalConfig::default()
+        };
+        save_config(&config)?;
+        return Ok(config);
+    }
+
+    let content = fs::read_to_string(&config_path)?;
+    let mut config: GlobalConfig = serde_json::from_str(&content)?;
+    let mut updated = false;
+
+    if config.iteration_timeout_ms == 600000 {
+        config.iteration_timeout_ms = 0;
+        updated = true;
+    }
+    if config.idle_timeout_ms == 120000 {
+        config.idle_timeout_ms = 0;
+        updated = true;
+    }
+    if config.language.trim().is_empty() {
+        config.language = "system".to_string();
+        updated = true;
+    }
+    if crate::engine::prompt::language_name(&config.agent_language).is_none()
+        && config.agent_language != "auto"
+    {
+        config.agent_language = "auto".to_string();
+        updated = true;
+    }
+    if config.observer_port == 0 {
+        config.observer_port = DEFAULT_OBSERVER_PORT;
+        updated = true;
+    }
+
+    if updated {
+        save_config(&config)?;
+    }
+
+    Ok(config)
+}
+
+/// Save global config
+pub fn save_config(config: &GlobalConfig) -> Result<()> {
+    let data_dir = ensure_data_dir()?;
+    let config_path = data_dir.join("config.json");
+    let content = serde_json::to_string_pretty(config)?;
+    fs::write(config_path, content)?;
+    Ok(())
+}
+
+/// Serializes read-modify-write of the project index across commands and running loops
+static INDEX_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_index() -> MutexGuard<'static, ()> {
+    INDEX_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn project_index_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("projects.json"))
+}
+
+/// Load the index, creating an empty one if there is none; the caller holds `INDEX_LOCK`
+fn read_project_index() -> Result<ProjectIndex> {
+    let index_path = project_index_path()?;
+    if !index_path.exists() {
+        let index = ProjectIndex::default();
+        write_project_index(&index)?;
+        return Ok(index);
+    }
+
+    let content = fs::read_to_string(&index_path)?;
+    let index: ProjectIndex = serde_json::from_str(&content)?;
+    Ok(index)
+}
+
+/// Replace the index through a temporary file so readers never see half of it;
+/// the caller holds `INDEX_LOCK`
+fn write_project_index(index: &ProjectIndex) -> Result<()> {
+    ensure_data_dir()?;
+    let index_path = project_index_path()?;
+    let content = serde_json::to_string_pretty(index)?;
+    let temp = index_path.with_extension("json.tmp");
+    fs::write(&temp, content)?;
+    fs::rename(&temp, &index_path)?;
+    Ok(())
+}
+
+/// Load project index
+pub fn load_project_index() -> Result<ProjectIndex> {
+    let _guard = lock_index();
+    read_project_index()
+}
+
+/// Save project index. Prefer `update_project_index` for edits, so a change
+/// made between loading and saving is not lost.
+pub fn save_project_index(index: &ProjectIndex) -> Result<()> {
+    let _guard = lock_index();
+    write_project_index(index)
+}
+
+/// Load, edit and save the project index as one step
+pub fn update_project_index<T>(update: impl FnOnce(&mut ProjectIndex) -> T) -> Result<T> {
+    let _guard = lock_index();
+    let mut index = read_project_index()?;
+    let result = update(&mut index);
+    write_project_index(&index)?;
+    Ok(result)
+}
+
+fn workspaces_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("workspaces.json"))
+}
+
+/// Load all workspaces
+pub fn load_workspaces() -> Result<Vec<Workspace>> {
+    let path = workspaces_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save all workspaces
+pub fn save_workspaces(workspaces: &[Workspace]) -> Result<()> {
+    ensure_data_dir()?;
+    let content = serde_json::to_string_pretty(workspaces)?;
+    fs::write(workspaces_path()?, content)?;
+    Ok(())
+}
+
+/// Global config with the overrides of `workspace_id` applied; a workspace
+/// that no longer exists leaves the global config as is
+pub fn load_workspace_config(workspace_id: Option<&uuid::Uuid>) -> Result<GlobalConfig> {
+    let config = load_config()?;
+    let Some(workspace_id) = workspace_id else {
+        return Ok(config);
+    };
+    Ok(
+        match load_workspaces()?.iter().find(|w| &w.id == workspace_id) {
+            Some(workspace) => workspace.apply(config),
+            None => config,
+        },
+    )
+}
+
+/// Get project directory
+pub fn get_project_dir(project_id: &uuid::Uuid) -> Result<PathBuf> {
+    let data_dir = get_data_dir()?;
+    Ok(data_dir.join("projects").join(project_id.to_string()))
+}
+
+/// Ensure project directory exists
+pub fn ensure_project_dir(project_id: &uuid::Uuid) -> Result<PathBuf> {
+    let project_dir = get_project_dir(project_id)?;
+    fs::create_dir_all(&project_dir)?;
+    fs::create_dir_all(project_dir.join("logs"))?;
+    Ok(project_dir)
+}
+
+/// Load project state
+pub fn load_project_state(project_id: &uuid::Uuid) -> Result<ProjectState> {
+    let project_dir = get_project_dir(project_id)?;
+    let state_path = project_dir.join("state.json");
+
+    if !state_path.exists() {
+        return Err(StorageError::ProjectNotFound(project_id.to_string()));
+    }
+
+    let content = crypto::read_text(&state_path)?;
+    let state: ProjectState = serde_json::from_str(&content)?;
+    Ok(state)
+}
+
+/// Serializes read-modify-write of project states between commands and running loops
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_states() -> MutexGuard<'static, ()> {
+    STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Write a project state; the caller holds `STATE_LOCK`
+fn write_project_state(state: &ProjectState) -> Result<()> {
+    let project_dir = ensure_project_dir(&state.id)?;
+    let state_path = project_dir.join("state.json");
+    let content = serde_json::to_string_pretty(state)?;
+    crypto::write_text(&state_path, &content)?;
+    sync_index_status(state)
+}
+
+/// Save project state. Prefer `update_project_state` for edits, so a change
+/// made between loading and saving is not lost.
+pub fn save_project_state(state: &ProjectState) -> Result<()> {
+    let _guard = lock_states();
+    write_project_state(state)
+}
+
+/// Load, edit and save a project state as one step
+pub fn update_project_state(
+    project_id: &uuid::Uuid,
+    update: impl FnOnce(&mut ProjectState),
+) -> Result<ProjectState> {
+    let _guard = lock_states();
+    let mut state = load_project_state(project_id)?;
+    update(&mut state);
+    write_project_state(&state)?;
+    Ok(state)
+}
+
+/// Keep the status cached in the index in step with the state, so listing
+/// projects does not read every state file
+fn sync_index_status(state: &ProjectState) -> Result<()> {
+    let _guard = lock_index();
+    if !project_index_path()?.exists() {
+        return Ok(());
+    }
+    let mut index = read_project_index()?;
+    let Some(meta) = index.projects.iter_mut().find(|meta| meta.id == state.id) else {
+        return Ok(());
+    };
+    if meta.status == state.status && meta.workspace_id == state.workspace_id {
+        return Ok(());
+    }
+    meta.status = state.status;
+    meta.workspace_id = state.workspace_id;
+    write_project_index(&index)
+}
+
+/// Encrypt or decrypt every stored project state, finished session log, raw
+/// capture, session record and benchmark or experiment report, returning how
+/// many files were rewritten
+pub fn convert_stored_data(encrypt: bool) -> Result<usize> {
+    let projects_dir = get_data_dir()?.join("projects");
+    if !projects_dir.exists() {
+        return Ok(0);
+    }
+    let mut converted = 0;
+    for project in fs::read_dir(projects_dir)?.flatten() {
+        let dir = project.path();
+        let mut files = vec![dir.join("state.json")];
+        if let Ok(logs) = fs::read_dir(dir.join("logs")) {
+            files.extend(
+                logs.flatten()
+                    .filter(|e| e.file_name() != crate::engine::logs::CURRENT_LOG)
+                    .map(|e| e.path())
+                    .filter(|p| {
+                        p.extension().is_some_and(|ext| ext == "log")
+                            || p.to_string_lossy().ends_with(".raw.txt")
+                    }),
+            );
+        }
+        if let Ok(sessions) = fs::read_dir(dir.join("sessions")) {
+            for session in sessions.flatten() {
+                files.extend(session_record_files(&session.path()));
+            }
+        }
+        for (runs, record) in [
+            ("benchmarks", "report.json"),
+            ("experiments", "experiment.json"),
+        ] {
+            if let Ok(runs) = fs::read_dir(dir.join(runs)) {
+                for run in runs.flatten() {
+                    files.push(run.path().join(record));
+                    files.push(run.path().join("report.md"));
+                }
+            }
+        }
+        for file in files.iter().filter(|f| f.is_file()) {
+            if crypto::convert_file(file, encrypt)? {
+                converted += 1;
+            }
+        }
+    }
+    Ok(converted)
+}
+
+/// The records kept directly in a session's directory; collected artifacts
+/// in its subdirectories are left as they are
+fn session_record_files(session_dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(session_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|ext| ext == "json" || ext == "md" || ext == "txt")
+        })
+        .collect()
+}
+
+/// Directory holding a benchmark's report and worktrees
+pub fn get_benchmark_dir(project_id: &uuid::Uuid, benchmark_id: &str) -> Result<PathBuf> {
+    Ok(get_project_dir(project_id)?
+        .join("benchmarks")
+        .join(benchmark_id))
+}
+
+/// Save a benchmark report
+pub fn save_benchmark_report(report: &BenchmarkReport) -> Result<()> {
+    let dir = get_benchmark_dir(&report.project_id, &report.id)?;
+    fs::create_dir_all(&dir)?;
+    let content = serde_json::to_string_pretty(report)?;
+    crypto::write_text(&dir.join("report.json"), &content)?;
+    Ok(())
+}
+
+/// Read `file` from every subdirectory of `dir`, skipping unreadable ones
+fn read_run_records<T: serde::de::DeserializeOwned>(dir: PathBuf, file: &str) -> Result<Vec<T>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_dir(dir)?
+        .flatten()
+        .filter_map(|entry| crypto::read_text(&entry.path().join(file)).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect())
+}
+
+/// Load a project's benchmark reports, newest first
+pub fn list_benchmark_reports(project_id: &uuid::Uuid) -> Result<Vec<BenchmarkReport>> {
+    let dir = get_project_dir(project_id)?.join("benchmarks");
+    let mut reports: Vec<BenchmarkReport> = read_run_records(dir, "report.json")?;
+    reports.sort_by_key(|r| std::cmp::Reverse(r.started_at));
+    Ok(reports)
+}
+
+/// Directory holding a prompt experiment's results and worktrees
+pub fn get_experiment_dir(project_id: &uuid::Uuid, experiment_id: &str) -> Result<PathBuf> {
+    Ok(get_project_dir(project_id)?
+        .join("experiments")
+        .join(experiment_id))
+}
+
+/// Save a prompt experiment
+pub fn save_prompt_experiment(experiment: &PromptExperiment) -> Result<()> {
+    let dir = get_experiment_dir(&experiment.project_id, &experiment.id)?;
+    fs::create_dir_all(&dir)?;
+    let content = serde_json::to_string_pretty(experiment)?;
+    crypto::write_text(&dir.join("experiment.json"), &content)?;
+    Ok(())
+}
+
+/// Load a project's prompt experiments, newest first
+pub fn list_prompt_experiments(project_id: &uuid::Uuid) -> Result<Vec<PromptExperiment>> {
+    let dir = get_project_dir(project_id)?.join("experiments");
+    let mut experiments: Vec<PromptExperiment> = read_run_records(dir, "experiment.json")?;
+    experiments.sort_by_key(|e| std::cmp::Reverse(e.started_at));
+    Ok(experiments)
+}
+
+/// Get the data directory for one loop session of a project
+pub fn get_session_dir(project_id: &uuid::Uuid, session_id: &str) -> Result<PathBuf> {
+    Ok(get_project_dir(project_id)?
+        .join("sessions")
+        .join(session_id))
+}
+
+/// List session ids for a project (from session data and logs), newest first
+pub fn list_session_ids(project_id: &uuid::Uuid) -> Result<Vec<String>> {
+    let project_dir = get_project_dir(project_id)?;
+    let mut ids = Vec::new();
+    let sessions_dir = project_dir.join("sessions");
+    if sessions_dir.exists() {
+        ids.extend(
+            fs::read_dir(sessions_dir)?
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string()),
+        );
+    }
+    let logs_dir = project_dir.join("logs");
+    if logs_dir.exists() {
+        ids.extend(fs::read_dir(logs_dir)?.flatten().filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            name.strip_suffix(".log")
+                .filter(|stem| *stem != "current")
+                .map(|stem| stem.to_string())
+        }));
+    }
+    ids.sort_by(|a, b| b.cmp(a));
+    ids.dedup();
+    Ok(ids)
+}
+
+/// Load manual notes for a session
+pub fn load_session_notes(project_id: &uuid::Uuid, session_id: &str) -> Result<Vec<SessionNote>> {
+    let path = get_session_dir(project_id, session_id)?.join("notes.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Load the monorepo target a session ran for
+pub fn load_session_target(project_id: &uuid::Uuid, session_id: &str) -> Result<Option<String>> {
+    let path = get_session_dir(project_id, session_id)?.join("target.txt");
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(crypto::read_text(&path)?.trim().to_string()))
+}
+
+/// Record the monorepo target a session runs for
+pub fn save_session_target(project_id: &uuid::Uuid, session_id: &str, target: &str) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    crypto::write_text(&session_dir.join("target.txt"), target)?;
+    Ok(())
+}
+
+/// Load pin flags for a session
+pub fn load_session_pins(project_id: &uuid::Uuid, session_id: &str) -> Result<SessionPins> {
+    let path = get_session_dir(project_id, session_id)?.join("pins.json");
+    if !path.exists() {
+        return Ok(SessionPins::default());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save pin flags for a session
+pub fn save_session_pins(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    pins: &SessionPins,
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(pins)?;
+    crypto::write_text(&session_dir.join("pins.json"), &content)?;
+    Ok(())
+}
+
+/// Save manual notes for a session
+pub fn save_session_notes(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    notes: &[SessionNote],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(notes)?;
+    crypto::write_text(&session_dir.join("notes.json"), &content)?;
+    Ok(())
+}
+
+/// Save file change hotspots for a session
+pub fn save_session_hotspots(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    hotspots: &[FileHotspot],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(hotspots)?;
+    crypto::write_text(&session_dir.join("hotspots.json"), &content)?;
+    Ok(())
+}
+
+/// Load file change hotspots for a session
+pub fn load_session_hotspots(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<FileHotspot>> {
+    let path = get_session_dir(project_id, session_id)?.join("hotspots.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save highlighted output lines for a session
+pub fn save_session_highlights(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    highlights: &[SessionHighlight],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(highlights)?;
+    crypto::write_text(&session_dir.join("highlights.json"), &content)?;
+    Ok(())
+}
+
+/// Load highlighted output lines for a session
+pub fn load_session_highlights(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<SessionHighlight>> {
+    let path = get_session_dir(project_id, session_id)?.join("highlights.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Directory an iteration's artifacts are copied into
+pub fn get_iteration_artifacts_dir(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    iteration: u32,
+) -> Result<PathBuf> {
+    Ok(get_session_dir(project_id, session_id)?
+        .join("artifacts")
+        .join(iteration.to_string()))
+}
+
+/// Save the manifest of artifacts collected during a session
+pub fn save_session_artifacts(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    artifacts: &[IterationArtifact],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(artifacts)?;
+    crypto::write_text(&session_dir.join("artifacts.json"), &content)?;
+    Ok(())
+}
+
+/// Load the manifest of artifacts collected during a session
+pub fn load_session_artifacts(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<IterationArtifact>> {
+    let path = get_session_dir(project_id, session_id)?.join("artifacts.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save the per-iteration token usage of a session
+pub fn save_session_usage(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    usage: &[IterationUsage],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(usage)?;
+    crypto::write_text(&session_dir.join("usage.json"), &content)?;
+    Ok(())
+}
+
+/// Save the iteration records of a session
+pub fn save_session_iterations(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    records: &[IterationRecord],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(records)?;
+    crypto::write_text(&session_dir.join("iterations.json"), &content)?;
+    Ok(())
+}
+
+/// Load the iteration records of a session. Sessions from before records were
+/// kept get theirs rebuilt from the log and usage history, then saved.
+pub fn load_session_iterations(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<IterationRecord>> {
+    let path = get_session_dir(project_id, session_id)?.join("iterations.json");
+    if path.exists() {
+        let content = crypto::read_text(&path)?;
+        return Ok(serde_json::from_str(&content)?);
+    }
+    let log_path = get_project_dir(project_id)?
+        .join("logs")
+        .join(format!("{}.log", session_id));
+    if !log_path.exists() {
+        return Ok(Vec::new());
+    }
+    let log = crypto::read_text(&log_path)?;
+    let usage = load_session_usage(project_id, session_id)?;
+    let completed = load_session_completion(project_id, session_id)?.is_some();
+    let records = iterations_from_log(&log, &usage, completed);
+    if !records.is_empty() {
+        save_session_iterations(project_id, session_id, &records)?;
+    }
+    Ok(records)
+}
+
+/// Rebuild iteration records from a session log's `# Iteration N finished in
+/// X ms (exit code C)` markers. Start times are approximate: each iteration is
+/// taken to start when the previous one ended. Commits are not in the log.
+fn iterations_from_log(
+    log: &str,
+    usage: &[IterationUsage],
+    completed: bool,
+) -> Vec<IterationRecord> {
+    let started = log
+        .lines()
+        .find_map(|line| line.strip_prefix("# Started: "))
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(at.trim()).ok());
+    let Some(mut at) = started.map(|at| at.with_timezone(&chrono::Utc)) else {
+        return Vec::new();
+    };
+    let mut records = Vec::new();
+    for line in log.lines() {
+        let Some((number, rest)) = line
+            .strip_prefix("# Iteration ")
+            .and_then(|rest| rest.split_once(" finished in "))
+        else {
+            continue;
+        };
+        let Some((ms, exit_code)) = rest.split_once(" ms (exit code ") else {
+            continue;
+        };
+        let (Ok(number), Ok(ms)) = (number.parse::<u32>(), ms.parse::<i64>()) else {
+            continue;
+        };
+        let exit_code: Option<i32> = exit_code.trim_end_matches(')').parse().ok();
+        let ended_at = at + chrono::Duration::milliseconds(ms);
+        records.push(IterationRecord {
+            number,
+            started_at: at,
+            ended_at: Some(ended_at),
+            exit_code,
+            commit_shas: Vec::new(),
+            tokens: usage.iter().find(|u| u.iteration == number).cloned(),
+            validation_passed: None,
+            outcome: if exit_code.is_some_and(|code| code != 0) {
+                IterationOutcome::Failed
+            } else {
+                IterationOutcome::Continued
+            },
+        });
+        at = ended_at;
+    }
+    if let (true, Some(last)) = (completed, records.last_mut()) {
+        last.outcome = IterationOutcome::Completed;
+    }
+    records
+}
+
+/// Save the structured payload a session completed with
+pub fn save_session_completion(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    payload: &CompletionPayload,
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(payload)?;
+    crypto::write_text(&session_dir.join("completion.json"), &content)?;
+    Ok(())
+}
+
+/// Load a session's completion payload; `None` when it ended without one
+pub fn load_session_completion(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Option<CompletionPayload>> {
+    let path = get_session_dir(project_id, session_id)?.join("completion.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+/// Save a session's postmortem Markdown, returning where it was written
+pub fn save_session_postmortem(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    markdown: &str,
+) -> Result<PathBuf> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let path = session_dir.join("postmortem.md");
+    crypto::write_text(&path, markdown)?;
+    Ok(path)
+}
+
+/// Load a session's postmortem with where it is stored; `None` before one was generated
+pub fn load_session_postmortem(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Option<(String, PathBuf)>> {
+    let path = get_session_dir(project_id, session_id)?.join("postmortem.md");
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some((crypto::read_text(&path)?, path)))
+}
+
+/// Add a supervisor revision to the session's prompt history
+pub fn append_prompt_revision(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    revision: &PromptRevision,
+) -> Result<()> {
+    let mut revisions = load_prompt_revisions(project_id, session_id)?;
+    revisions.push(revision.clone());
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(&revisions)?;
+    crypto::write_text(&session_dir.join("prompt_revisions.json"), &content)?;
+    Ok(())
+}
+
+/// The supervisor's revisions of a session's prompt, oldest first
+pub fn load_prompt_revisions(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<PromptRevision>> {
+    let path = get_session_dir(project_id, session_id)?.join("prompt_revisions.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn iteration_snapshots_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("iteration_snapshots.json"))
+}
+
+/// Snapshot worktrees checked out from iterations that were not removed yet
+pub fn load_iteration_snapshots() -> Result<Vec<IterationSnapshot>> {
+    let path = iteration_snapshots_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+pub fn save_iteration_snapshots(snapshots: &[IterationSnapshot]) -> Result<()> {
+    ensure_data_dir()?;
+    let content = serde_json::to_string_pretty(snapshots)?;
+    fs::write(iteration_snapshots_path()?, content)?;
+    Ok(())
+}
+
+/// Save the reproducibility snapshot taken when a session started
+pub fn save_session_snapshot(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    snapshot: &SessionSnapshot,
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(snapshot)?;
+    crypto::write_text(&session_dir.join("snapshot.json"), &content)?;
+    Ok(())
+}
+
+/// Load a session's snapshot; `None` for sessions recorded before snapshots existed
+pub fn load_session_snapshot(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Option<SessionSnapshot>> {
+    let path = get_session_dir(project_id, session_id)?.join("snapshot.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+/// CLI version of the project's last successful session
+pub fn load_last_good_cli_version(project_id: &uuid::Uuid) -> Result<Option<CliVersionRecord>> {
+    let path = get_project_dir(project_id)?.join("cli-version.json");
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+/// Remember the CLI version a session completed successfully with
+pub fn save_last_good_cli_version(
+    project_id: &uuid::Uuid,
+    record: &CliVersionRecord,
+) -> Result<()> {
+    let project_dir = get_project_dir(project_id)?;
+    fs::create_dir_all(&project_dir)?;
+    let content = serde_json::to_string_pretty(record)?;
+    fs::write(project_dir.join("cli-version.json"), content)?;
+    Ok(())
+}
+
+/// Load the per-iteration token usage of a session
+pub fn load_session_usage(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<IterationUsage>> {
+    let path = get_session_dir(project_id, session_id)?.join("usage.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save suppressed output counts for a session
+pub fn save_suppressed_counts(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+    counts: &[SuppressedCount],
+) -> Result<()> {
+    let session_dir = get_session_dir(project_id, session_id)?;
+    fs::create_dir_all(&session_dir)?;
+    let content = serde_json::to_string_pretty(counts)?;
+    crypto::write_text(&session_dir.join("suppressed.json"), &content)?;
+    Ok(())
+}
+
+/// Load suppressed output counts for a session
+pub fn load_suppressed_counts(
+    project_id: &uuid::Uuid,
+    session_id: &str,
+) -> Result<Vec<SuppressedCount>> {
+    let path = get_session_dir(project_id, session_id)?.join("suppressed.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = crypto::read_text(&path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Load recorded CLI processes
+pub fn load_process_records() -> Result<Vec<ProcessRecord>> {
+    let path = get_data_dir()?.join("processes.json");
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save recorded CLI processes
+pub fn save_process_records(records: &[ProcessRecord]) -> Result<()> {
+    let data_dir = ensure_data_dir()?;
+    let content = serde_json::to_string_pretty(records)?;
+    fs::write(data_dir.join("processes.json"), content)?;
+    Ok(())
+}
+
+fn session_metrics_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("metrics.jsonl"))
+}
+
+/// Append a finished session to the local metrics, one JSON object per line
+pub fn append_session_metrics(metrics: &SessionMetrics) -> Result<()> {
+    use std::io::Write;
+    ensure_data_dir()?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(session_metrics_path()?)?;
+    writeln!(file, "{}", serde_json::to_string(metrics)?)?;
+    Ok(())
+}
+
+/// Load every recorded session, skipping lines that no longer parse
+pub fn load_session_metrics() -> Result<Vec<SessionMetrics>> {
+    let path = session_metrics_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn knowledge_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("knowledge.jsonl"))
+}
+
+/// Add entries to the local knowledge base, one JSON object per line
+pub fn append_knowledge_entries(entries: &[KnowledgeEntry]) -> Result<()> {
+    use std::io::Write;
+    ensure_data_dir()?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(knowledge_path()?)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    }
+    Ok(())
+}
+
+/// Load the knowledge base, skipping lines that no longer parse
+pub fn load_knowledge_entries() -> Result<Vec<KnowledgeEntry>> {
+    let path = knowledge_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Sessions from the local metrics included in a project bundle
+const BUNDLE_RECENT_SESSIONS: usize = 10;
+
+/// Everything external tools usually want about one project, in one document
+pub fn load_project_bundle(project_id: &uuid::Uuid) -> Result<ProjectBundle> {
+    let state = load_project_state(project_id)?;
+    let session_ids = list_session_ids(project_id)?;
+    let session_id = state
+        .execution
+        .as_ref()
+        .and_then(|e| e.session_id.clone())
+        .or_else(|| session_ids.first().cloned());
+    let (usage, snapshot) = match &session_id {
+        Some(id) => (
+            load_session_usage(project_id, id)?,
+            load_session_snapshot(project_id, id).ok().flatten(),
+        ),
+        None => (Vec::new(), None),
+    };
+    let last_completion = session_ids
+        .iter()
+        .find_map(|id| load_session_completion(project_id, id).ok().flatten());
+    let mut recent_sessions: Vec<SessionMetrics> = load_session_metrics()?
+        .into_iter()
+        .filter(|m| &m.project_id == project_id)
+        .collect();
+    recent_sessions.sort_by_key(|m| std::cmp::Reverse(m.ended_at));
+    recent_sessions.truncate(BUNDLE_RECENT_SESSIONS);
+    Ok(ProjectBundle {
+        state,
+        session_id,
+        usage,
+        recent_sessions,
+        last_completion,
+        snapshot,
+    })
+}
+
+/// Save a monthly report as `reports/ralph-report-<month>.md`
+pub fn save_usage_report(month: &str, markdown: &str) -> Result<PathBuf> {
+    let dir = ensure_data_dir()?.join("reports");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("ralph-report-{}.md", month));
+    fs::write(&path, markdown)?;
+    Ok(path)
+}
+
+fn brainstorm_prompt_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("brainstorm-prompt.md"))
+}
+
+/// Load the user's brainstorm prompt template, if one was saved
+pub fn load_brainstorm_prompt() -> Result<Option<String>> {
+    let path = brainstorm_prompt_path()?;
+    if !path.exists() {
+        return Ok(None);
+    }
+    Ok(Some(fs::read_to_string(path)?))
+}
+
+/// Save a brainstorm prompt template
+pub fn save_brainstorm_prompt(template: &str) -> Result<()> {
+    ensure_data_dir()?;
+    fs::write(brainstorm_prompt_path()?, template)?;
+    Ok(())
+}
+
+/// Remove the saved brainstorm prompt template
+pub fn delete_brainstorm_prompt() -> Result<()> {
+    let path = brainstorm_prompt_path()?;
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn consents_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("consents.json"))
+}
+
+/// Load recorded capability acknowledgments
+pub fn load_consents() -> Result<Vec<ConsentGrant>> {
+    let path = consents_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save recorded capability acknowledgments
+pub fn save_consents(grants: &[ConsentGrant]) -> Result<()> {
+    ensure_data_dir()?;
+    let content = serde_json::to_string_pretty(grants)?;
+    fs::write(consents_path()?, content)?;
+    Ok(())
+}
+
+/// Directory plugins are installed in, one subdirectory each
+pub fn get_plugins_dir() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("plugins"))
+}
+
+fn personas_path() -> Result<PathBuf> {
+    Ok(get_data_dir()?.join("personas.json"))
+}
+
+/// Load user-defined brainstorm personas
+pub fn load_custom_personas() -> Result<Vec<BrainstormPersona>> {
+    let path = personas_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+/// Save user-defined brainstorm personas
+pub fn save_custom_personas(personas: &[BrainstormPersona]) -> Result<()> {
+    ensure_data_dir()?;
+    let content = serde_json::to_string_pretty(personas)?;
+    fs::write(personas_path()?, content)?;
+    Ok(())
+}
+
+/// Keep at most this many distinct anomaly kinds per CLI
+const MAX_ANOMALY_KINDS: usize = 100;
+
+fn parse_anomalies_path(cli: CliType) -> Result<PathBuf> {
+    let name = match cli {
+        CliType::Claude => "claude",
+        CliType::Codex => "codex",
+        CliType::OpenCode => "opencode",
+        CliType::Amp => "amp",
+        CliType::QwenCode => "qwencode",
+        CliType::Iflow => "iflow",
+        CliType::Gemini => "gemini",
+        CliType::Api => "api",
+        CliType::Custom => "custom",
+    };
+    Ok(get_data_dir()?
+        .join("parse-anomalies")
+        .join(format!("{}.jsonl", name)))
+}
+
+/// Load recorded parse anomalies for a CLI, one JSON object per line
+pub fn load_parse_anomalies(cli: CliType) -> Result<Vec<ParseAnomaly>> {
+    let path = parse_anomalies_path(cli)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Merge new parse anomalies into the stored ones, summing counts per kind
+pub fn record_parse_anomalies(cli: CliType, anomalies: &[ParseAnomaly]) -> Result<()> {
+    if anomalies.is_empty() {
+        return Ok(());
+    }
+    let mut merged = load_parse_anomalies(cli)?;
+    for anomaly in anomalies {
+        match merged.iter_mut().find(|a| a.kind == anomaly.kind) {
+            Some(existing) => {
+                existing.count += anomaly.count;
+                existing.sample = anomaly.sample.clone();
+                existing.last_seen = anomaly.last_seen;
+            }
+            None => merged.push(anomaly.clone()),
+        }
+    }
+    merged.sort_by_key(|a| std::cmp::Reverse(a.last_seen));
+    merged.truncate(MAX_ANOMALY_KINDS);
+
+    let path = parse_anomalies_path(cli)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut content = String::new();
+    for anomaly in &merged {
+        content.push_str(&serde_json::to_string(anomaly)?);
+        content.push('\n');
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Delete project data
+pub fn delete_project_data(project_id: &uuid::Uuid) -> Result<()> {
+    let project_dir = get_project_dir(project_id)?;
+    if project_dir.exists() {
+        fs::remove_dir_all(project_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebuilds_iterations_from_legacy_logs() {
+        let log = "# Ralph Desktop Execution Log\n\
+                   # Started: 2026-03-01T10:00:00+00:00\n\
+                   # Project ID: p\n\
+                   \n\
+                   # Iteration 1 started\n\
+                   [#1] 10:00:01 [OUT] working\n\
+                   # Iteration 1 finished in 60000 ms (exit code 1)\n\
+                   # Iteration 2 started\n\
+                   # Iteration 2 finished in 30000 ms (exit code none)\n";
+        let usage = vec![IterationUsage {
+            iteration: 2,
+            cli: CliType::Claude,
+            input_tokens: 100,
+            output_tokens: 20,
+            cache_read_tokens: 0,
+            cost_usd: None,
+        }];
+        let records = iterations_from_log(log, &usage, true);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].exit_code, Some(1));
+        assert_eq!(records[0].outcome, IterationOutcome::Failed);
+        assert_eq!(records[1].started_at, records[0].ended_at.unwrap());
+        assert_eq!(records[1].exit_code, None);
+        assert_eq!(records[1].outcome, IterationOutcome::Completed);
+        assert_eq!(
+            records[1].tokens.as_ref().map(|t| t.input_tokens),
+            Some(100)
+        );
+        assert!(iterations_from_log("no header", &[], false).is_empty());
+    }
+
+    #[test]
+    fn concurrent_index_updates_are_not_lost() {
+        let _env_lock = crate::test_support::lock_env();
+        let home_dir = tempfile::tempdir().unwrap();
+        let _home_guard = crate::test_support::EnvVarGuard::set("HOME", home_dir.path());
+
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                std::thread::spawn(move || {
+                    let now = chrono::Utc::now();
+                    update_project_index(|index| {
+                        index.projects.push(ProjectMeta {
+                            id: uuid::Uuid::new_v4(),
+                            name: format!("project {}", i),
+                            path: format!("/tmp/project-{}", i),
+                            status: ProjectStatus::Brainstorming,
+                            created_at: now,
+                            last_opened_at: now,
+                            workspace_id: None,
+                        })
+                    })
+                    .unwrap();
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert_eq!(load_project_index().unwrap().projects.len(), 8);
+        assert!(!get_data_dir().unwrap().join("projects.json.tmp").exists());
+    }
+
+    #[test]
+    fn enabling_encryption_leaves_no_plaintext_records() {
+        let _env_lock = crate::test_support::lock_env();
+        let home_dir = tempfile::tempdir().unwrap();
+        let _home_guard = crate::test_support::EnvVarGuard::set("HOME", home_dir.path());
+        crypto::use_test_key();
+
+        let project_id = uuid::Uuid::new_v4();
+        let project_dir = ensure_project_dir(&project_id).unwrap();
+        let session_dir = get_session_dir(&project_id, "s1").unwrap();
+        let benchmark_dir = get_benchmark_dir(&project_id, "b1").unwrap();
+        let artifact_dir = get_iteration_artifacts_dir(&project_id, "s1", 1).unwrap();
+        for dir in [
+            &session_dir,
+            &benchmark_dir,
+            &artifact_dir,
+            &project_dir.join("logs"),
+        ] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        let secret = "secret prompt";
+        for file in [
+            project_dir.join("state.json"),
+            project_dir.join("logs").join("s1.log"),
+            session_dir.join("iterations.json"),
+            session_dir.join("completion.json"),
+            session_dir.join("prompt_revisions.json"),
+            session_dir.join("snapshot.json"),
+            session_dir.join("notes.json"),
+            benchmark_dir.join("report.json"),
+            benchmark_dir.join("report.md"),
+        ] {
+            fs::write(file, format!("\"{}\"", secret)).unwrap();
+        }
+        save_session_postmortem(&project_id, "s1", secret).unwrap();
+        fs::write(artifact_dir.join("shot.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        assert_eq!(convert_stored_data(true).unwrap(), 10);
+
+        let mut pending = vec![project_dir];
+        while let Some(dir) = pending.pop() {
+            for entry in fs::read_dir(dir).unwrap().flatten() {
+                let path = entry.path();
+                if path.is_dir() {
+                    pending.push(path);
+                } else if !path.starts_with(&artifact_dir) {
+                    let data = fs::read(&path).unwrap();
+                    assert!(
+                        crypto::is_encrypted(&data),
+                        "{} is plaintext",
+                        path.display()
+                    );
+                }
+            }
+        }
+        let (markdown, _) = load_session_postmortem(&project_id, "s1").unwrap().unwrap();
+        assert_eq!(markdown, secret);
+    }
+}
@@ -0,0 +1,1729 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+/// Global shortcut that captures the clipboard as a task
+pub const QUICK_CAPTURE_SHORTCUT: &str = "CommandOrControl+Alt+R";
+
+/// Port the observer feed serves on unless configured otherwise
+pub const DEFAULT_OBSERVER_PORT: u16 = 7421;
+
+/// Extension point a plugin is called at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PluginHook {
+    /// May replace each iteration's prompt
+    BeforePrompt,
+    /// May block each auto-commit
+    BeforeCommit,
+}
+
+/// `plugin.json` in a plugin's directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Executable in the plugin's directory, or else on PATH
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Loop event types forwarded to the plugin, e.g. `completed`; `*` for all
+    #[serde(default)]
+    pub events: Vec<String>,
+    #[serde(default)]
+    pub hooks: Vec<PluginHook>,
+}
+
+/// Plugin found in the plugins directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    /// Name of its directory
+    pub id: String,
+    pub dir: String,
+    pub enabled: bool,
+    /// `None` when `plugin.json` is missing or invalid, see `error`
+    pub manifest: Option<PluginManifest>,
+    pub error: Option<String>,
+}
+
+/// Global configuration stored in ~/.ralph-desktop/config.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GlobalConfig {
+    pub version: String,
+    pub default_cli: CliType,
+    pub default_max_iterations: u32,
+    pub max_concurrent_projects: u32,
+    pub iteration_timeout_ms: u64,
+    pub idle_timeout_ms: u64,
+    /// Retry an iteration whose CLI prints nothing this long after starting; 0 disables it
+    #[serde(default = "default_first_output_timeout_ms")]
+    pub first_output_timeout_ms: u64,
+    /// Fail a loop after this many non-zero CLI exits in a row; 0 disables the check
+    #[serde(default = "default_max_consecutive_failures")]
+    pub max_consecutive_failures: u32,
+    pub theme: Theme,
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Language agents write in during brainstorm and loops: auto, zh, en, ja or ko
+    #[serde(default = "default_agent_language")]
+    pub agent_language: String,
+    pub log_retention_days: u32,
+    pub permissions_confirmed: bool,
+    pub permissions_confirmed_at: Option<DateTime<Utc>>,
+    /// Policy injected ahead of task prompts so agents never block on questions
+    #[serde(default = "default_auto_decide_policy")]
+    pub auto_decide_policy: String,
+    /// Token budget for the repository context builder
+    #[serde(default = "default_context_token_budget")]
+    pub context_token_budget: u32,
+    /// Largest prompt or message accepted from the UI, in bytes; 0 disables the limit
+    #[serde(default = "default_max_prompt_bytes")]
+    pub max_prompt_bytes: u32,
+    /// Include repository context in brainstorm prompts
+    #[serde(default)]
+    pub brainstorm_repo_context: bool,
+    /// Command used to transcribe voice notes; `{input}` is replaced by the audio path
+    #[serde(default)]
+    pub transcription_command: Option<String>,
+    /// Serve a read-only status feed for observing runs from another machine
+    #[serde(default)]
+    pub observer_enabled: bool,
+    #[serde(default = "default_observer_port")]
+    pub observer_port: u16,
+    /// Bearer token observers must present; generated when the feed is first enabled
+    #[serde(default)]
+    pub observer_token: Option<String>,
+    #[serde(default)]
+    pub shortcuts: ShortcutBindings,
+    /// Regexes for loop output lines to mute in every project
+    #[serde(default)]
+    pub output_filters: Vec<String>,
+    /// Show runs of identical output lines once, followed by a repeat count
+    #[serde(default = "default_collapse_repeated_lines")]
+    pub collapse_repeated_lines: bool,
+    /// Encrypt project states and finished session logs with a key from the OS keychain
+    #[serde(default)]
+    pub encrypt_at_rest: bool,
+    /// Rules flagging notable output lines, checked in order
+    #[serde(default = "default_highlight_rules")]
+    pub highlight_rules: Vec<HighlightRule>,
+    /// Model endpoint used by the `api` backend, which runs without an installed CLI
+    #[serde(default)]
+    pub api_backend: ApiBackendConfig,
+    /// User-defined agent CLI used by the `custom` backend
+    #[serde(default)]
+    pub custom_cli: CustomCliConfig,
+    #[serde(default)]
+    pub quiet_hours: QuietHours,
+    /// Record finished sessions on this machine for the monthly report; nothing is sent anywhere
+    #[serde(default)]
+    pub local_analytics: bool,
+    /// Keep what sessions learned on this machine and offer relevant learnings before a loop starts
+    #[serde(default)]
+    pub knowledge_base: bool,
+    /// Plugins, by directory name, loaded for new loops
+    #[serde(default)]
+    pub enabled_plugins: Vec<String>,
+    /// Environment variables set for every loop's CLI
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Named variable sets a task can opt into, e.g. to point a CLI at another endpoint
+    #[serde(default)]
+    pub env_profiles: Vec<EnvProfile>,
+    /// Files auto-commit leaves out of iteration commits
+    #[serde(default)]
+    pub commit_hygiene: CommitHygiene,
+    /// Author of auto-commits; unset fields use the repository's git config
+    #[serde(default)]
+    pub commit_identity: CommitIdentity,
+    /// Reply to questions CLIs stop on, using `prompt_answers`
+    #[serde(default = "default_auto_answer_prompts")]
+    pub auto_answer_prompts: bool,
+    /// Replies to CLI prompts, checked in order
+    #[serde(default = "default_prompt_answers")]
+    pub prompt_answers: Vec<PromptAnswer>,
+    /// Terminate CLI processes a crashed Ralph left running when the app starts,
+    /// instead of only offering to
+    #[serde(default = "default_reap_orphans_on_startup")]
+    pub reap_orphans_on_startup: bool,
+}
+
+/// Environment variables applied on top of the global ones for tasks selecting the profile
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvProfile {
+    pub name: String,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+/// Regex for a question a CLI asks, and the reply typed in for it (Enter is added)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptAnswer {
+    pub pattern: String,
+    pub response: String,
+}
+
+/// Author identity for auto-commits, applied with `git -c user.name/user.email`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitIdentity {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub email: Option<String>,
+    /// Add a `Co-authored-by` trailer naming the CLI that made the change
+    #[serde(default)]
+    pub co_author_trailer: bool,
+}
+
+/// Rules keeping secrets and large binaries out of auto-commits; skipped files
+/// stay in the working tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitHygiene {
+    /// Files larger than this are not committed; 0 disables the limit
+    #[serde(default = "default_commit_max_file_bytes")]
+    pub max_file_bytes: u64,
+    /// Globs matched against each changed path and every trailing part of it,
+    /// so `*.pem` and `node_modules/**` apply at any depth
+    #[serde(default = "default_commit_deny_globs")]
+    pub deny_globs: Vec<String>,
+    /// Block commits whose added lines look like keys or tokens
+    #[serde(default = "default_scan_secrets")]
+    pub scan_secrets: bool,
+}
+
+impl Default for CommitHygiene {
+    fn default() -> Self {
+        Self {
+            max_file_bytes: default_commit_max_file_bytes(),
+            deny_globs: default_commit_deny_globs(),
+            scan_secrets: default_scan_secrets(),
+        }
+    }
+}
+
+fn default_scan_secrets() -> bool {
+    true
+}
+
+fn default_commit_max_file_bytes() -> u64 {
+    5 * 1024 * 1024
+}
+
+fn default_commit_deny_globs() -> Vec<String> {
+    [
+        "*.pem",
+        "*.key",
+        "*.p12",
+        "*.pfx",
+        ".env",
+        ".env.*",
+        "*.sqlite",
+        "*.sqlite3",
+        "*.db",
+        "node_modules/**",
+    ]
+    .iter()
+    .map(|glob| glob.to_string())
+    .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationSeverity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+/// Daily do-not-disturb window in local time, e.g. 22:00 to 07:00
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuietHours {
+    pub enabled: bool,
+    /// `HH:MM`; an end before the start wraps past midnight
+    pub start: String,
+    pub end: String,
+    /// Severities still delivered during quiet hours
+    #[serde(default = "default_quiet_hours_break_through")]
+    pub break_through: Vec<NotificationSeverity>,
+}
+
+impl Default for QuietHours {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "22:00".to_string(),
+            end: "07:00".to_string(),
+            break_through: default_quiet_hours_break_through(),
+        }
+    }
+}
+
+fn default_quiet_hours_break_through() -> Vec<NotificationSeverity> {
+    vec![NotificationSeverity::Error]
+}
+
+/// Notification held back during quiet hours, delivered later in a digest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedNotification {
+    pub severity: NotificationSeverity,
+    pub title: String,
+    pub message: Option<String>,
+    pub queued_at: DateTime<Utc>,
+    /// Workspace whose quiet hours held it back, if the project belongs to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub workspace_id: Option<Uuid>,
+}
+
+/// SSH host a project's loops run on; key-based auth only, since there is no prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteTarget {
+    /// Hostname or `~/.ssh/config` alias
+    pub host: String,
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub identity_file: Option<String>,
+    /// Absolute path of the project checkout on the remote host
+    pub directory: String,
+}
+
+/// Container sandbox for loop iterations; the project is bind-mounted at `/workspace`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerConfig {
+    /// Image with the agent CLI installed
+    pub image: String,
+    #[serde(default)]
+    pub network: ContainerNetwork,
+    /// Extra `docker run` arguments, e.g. mounts for CLI credentials
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ContainerNetwork {
+    /// Docker's default network with outbound access
+    #[default]
+    Bridge,
+    /// No network at all; only useful for CLIs talking to a local model
+    None,
+    Host,
+}
+
+/// Screenshot of a web project's running app taken after each iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotConfig {
+    /// Started before each capture and stopped after, e.g. `npm run dev`;
+    /// `None` when something else already serves `url`
+    #[serde(default)]
+    pub dev_command: Option<String>,
+    pub url: String,
+    /// How long to wait for `url` to answer
+    #[serde(default = "default_screenshot_ready_timeout_secs")]
+    pub ready_timeout_secs: u32,
+    /// Replaces headless Chromium; `{url}` and `{output}` are substituted
+    #[serde(default)]
+    pub capture_command: Option<String>,
+    #[serde(default = "default_screenshot_width")]
+    pub width: u32,
+    #[serde(default = "default_screenshot_height")]
+    pub height: u32,
+    /// Show the screenshot to image-capable CLIs in the next iteration's prompt
+    #[serde(default)]
+    pub feed_back: bool,
+}
+
+fn default_screenshot_ready_timeout_secs() -> u32 {
+    60
+}
+
+fn default_screenshot_width() -> u32 {
+    1280
+}
+
+fn default_screenshot_height() -> u32 {
+    800
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ApiProvider {
+    Anthropic,
+    /// Any `/chat/completions` endpoint, e.g. OpenAI or GLM
+    OpenAiCompatible,
+}
+
+/// Settings for calling a model API directly instead of driving a CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiBackendConfig {
+    pub provider: ApiProvider,
+    /// Overrides the provider's default endpoint
+    #[serde(default)]
+    pub base_url: Option<String>,
+    pub model: String,
+    /// Falls back to `ANTHROPIC_API_KEY` or `OPENAI_API_KEY` when unset
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Model round trips allowed per iteration
+    #[serde(default = "default_api_max_turns")]
+    pub max_turns: u32,
+    #[serde(default = "default_api_max_tokens")]
+    pub max_tokens: u32,
+}
+
+impl Default for ApiBackendConfig {
+    fn default() -> Self {
+        Self {
+            provider: ApiProvider::Anthropic,
+            base_url: None,
+            model: "claude-sonnet-4-5".to_string(),
+            api_key: None,
+            max_turns: default_api_max_turns(),
+            max_tokens: default_api_max_tokens(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CustomOutputFormat {
+    /// Every line is agent text
+    #[default]
+    Text,
+    /// One JSON object per line; text is found through `text_pointers`
+    Jsonl,
+}
+
+/// Any agent CLI, described by its command line instead of a dedicated adapter.
+/// `{prompt}` and `{cwd}` in arguments are replaced before running.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomCliConfig {
+    #[serde(default = "default_custom_cli_name")]
+    pub name: String,
+    /// Binary name on PATH or an absolute path; empty means not configured
+    #[serde(default)]
+    pub executable: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Arguments for brainstorm and commit-message calls; falls back to `args`
+    #[serde(default)]
+    pub readonly_args: Option<Vec<String>>,
+    /// Write the prompt to stdin instead of (or as well as) passing `{prompt}`
+    #[serde(default)]
+    pub prompt_via_stdin: bool,
+    #[serde(default)]
+    pub output_format: CustomOutputFormat,
+    /// JSON pointers tried in order to find a line's text, e.g. `/message/content`
+    #[serde(default)]
+    pub text_pointers: Vec<String>,
+    #[serde(default = "default_custom_version_args")]
+    pub version_args: Vec<String>,
+}
+
+impl Default for CustomCliConfig {
+    fn default() -> Self {
+        Self {
+            name: default_custom_cli_name(),
+            executable: String::new(),
+            args: vec!["{prompt}".to_string()],
+            readonly_args: None,
+            prompt_via_stdin: false,
+            output_format: CustomOutputFormat::Text,
+            text_pointers: Vec::new(),
+            version_args: default_custom_version_args(),
+        }
+    }
+}
+
+fn default_custom_cli_name() -> String {
+    "Custom".to_string()
+}
+
+fn default_custom_version_args() -> Vec<String> {
+    vec!["--version".to_string()]
+}
+
+fn default_api_max_turns() -> u32 {
+    50
+}
+
+fn default_api_max_tokens() -> u32 {
+    8192
+}
+
+/// Global shortcut accelerators such as `CommandOrControl+Alt+R`; `None` leaves an action unbound
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShortcutBindings {
+    #[serde(default)]
+    pub stop_all: Option<String>,
+    #[serde(default)]
+    pub pause_active: Option<String>,
+    #[serde(default = "default_quick_capture_shortcut")]
+    pub quick_capture: Option<String>,
+    #[serde(default)]
+    pub toggle_window: Option<String>,
+}
+
+impl Default for ShortcutBindings {
+    fn default() -> Self {
+        Self {
+            stop_all: None,
+            pause_active: None,
+            quick_capture: default_quick_capture_shortcut(),
+            toggle_window: None,
+        }
+    }
+}
+
+fn default_quick_capture_shortcut() -> Option<String> {
+    Some(QUICK_CAPTURE_SHORTCUT.to_string())
+}
+
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        Self {
+            version: "1.0.0".to_string(),
+            default_cli: CliType::Claude,
+            default_max_iterations: 10,
+            max_concurrent_projects: 3,
+            iteration_timeout_ms: 0, // 0 = no timeout
+            idle_timeout_ms: 0,      // 0 = no timeout
+            first_output_timeout_ms: default_first_output_timeout_ms(),
+            max_consecutive_failures: default_max_consecutive_failures(),
+            theme: Theme::System,
+            language: default_language(),
+            agent_language: default_agent_language(),
+            log_retention_days: 7,
+            permissions_confirmed: false,
+            permissions_confirmed_at: None,
+            auto_decide_policy: default_auto_decide_policy(),
+            context_token_budget: default_context_token_budget(),
+            max_prompt_bytes: default_max_prompt_bytes(),
+            brainstorm_repo_context: false,
+            transcription_command: None,
+            observer_enabled: false,
+            observer_port: default_observer_port(),
+            observer_token: None,
+            shortcuts: ShortcutBindings::default(),
+            output_filters: Vec::new(),
+            collapse_repeated_lines: default_collapse_repeated_lines(),
+            encrypt_at_rest: false,
+            highlight_rules: default_highlight_rules(),
+            api_backend: ApiBackendConfig::default(),
+            custom_cli: CustomCliConfig::default(),
+            quiet_hours: QuietHours::default(),
+            local_analytics: false,
+            knowledge_base: false,
+            enabled_plugins: Vec::new(),
+            env: BTreeMap::new(),
+            env_profiles: Vec::new(),
+            commit_hygiene: CommitHygiene::default(),
+            commit_identity: CommitIdentity::default(),
+            auto_answer_prompts: default_auto_answer_prompts(),
+            prompt_answers: default_prompt_answers(),
+            reap_orphans_on_startup: default_reap_orphans_on_startup(),
+        }
+    }
+}
+
+fn default_collapse_repeated_lines() -> bool {
+    true
+}
+
+fn default_auto_decide_policy() -> String {
+    crate::engine::prompt::default_auto_decide_policy()
+}
+
+fn default_context_token_budget() -> u32 {
+    4000
+}
+
+fn default_max_prompt_bytes() -> u32 {
+    256 * 1024
+}
+
+fn default_max_consecutive_failures() -> u32 {
+    3
+}
+
+fn default_first_output_timeout_ms() -> u64 {
+    120_000
+}
+
+fn default_highlight_rules() -> Vec<HighlightRule> {
+    crate::engine::highlights::builtin_rules()
+}
+
+fn default_auto_answer_prompts() -> bool {
+    true
+}
+
+fn default_reap_orphans_on_startup() -> bool {
+    true
+}
+
+fn default_prompt_answers() -> Vec<PromptAnswer> {
+    crate::engine::auto_answer::builtin_answers()
+}
+
+fn default_observer_port() -> u16 {
+    DEFAULT_OBSERVER_PORT
+}
+
+fn default_language() -> String {
+    "system".to_string()
+}
+
+fn default_agent_language() -> String {
+    "auto".to_string()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CliType {
+    Claude,
+    Codex,
+    OpenCode,
+    Amp,
+    QwenCode,
+    Iflow,
+    Gemini,
+    /// Model API called directly, see `ApiBackendConfig`
+    Api,
+    /// User-defined CLI, see `CustomCliConfig`
+    Custom,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+    System,
+}
+
+/// Projects grouped under shared defaults, stored in ~/.ralph-desktop/workspaces.json;
+/// unset fields fall back to the global config
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: Uuid,
+    pub name: String,
+    #[serde(default)]
+    pub default_cli: Option<CliType>,
+    #[serde(default)]
+    pub iteration_timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub idle_timeout_ms: Option<u64>,
+    /// Merged over the global environment; a workspace value wins
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Workspace {
+    /// The global config with this workspace's overrides applied
+    pub fn apply(&self, mut config: GlobalConfig) -> GlobalConfig {
+        if let Some(cli) = self.default_cli {
+            config.default_cli = cli;
+        }
+        if let Some(timeout) = self.iteration_timeout_ms {
+            config.iteration_timeout_ms = timeout;
+        }
+        if let Some(timeout) = self.idle_timeout_ms {
+            config.idle_timeout_ms = timeout;
+        }
+        config
+            .env
+            .extend(self.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if let Some(quiet_hours) = &self.quiet_hours {
+            config.quiet_hours = quiet_hours.clone();
+        }
+        config
+    }
+}
+
+/// Project index stored in ~/.ralph-desktop/projects.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectIndex {
+    pub version: String,
+    pub projects: Vec<ProjectMeta>,
+    /// Project statuses are kept in step with their states; older indexes are synced once
+    #[serde(default)]
+    pub statuses_cached: bool,
+}
+
+impl Default for ProjectIndex {
+    fn default() -> Self {
+        Self {
+            version: "1.0.0".to_string(),
+            projects: Vec::new(),
+            statuses_cached: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectMeta {
+    pub id: Uuid,
+    pub name: String,
+    pub path: String,
+    pub status: ProjectStatus,
+    pub created_at: DateTime<Utc>,
+    pub last_opened_at: DateTime<Utc>,
+    #[serde(default)]
+    pub workspace_id: Option<Uuid>,
+}
+
+/// Project state stored in ~/.ralph-desktop/projects/{id}/state.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectState {
+    pub id: Uuid,
+    pub name: String,
+    pub path: String,
+    pub status: ProjectStatus,
+    /// Workspace whose defaults override the global config for this project
+    #[serde(default)]
+    pub workspace_id: Option<Uuid>,
+    #[serde(default)]
+    pub skip_git_repo_check: bool,
+    /// Also capture raw (unparsed) CLI output for debugging adapter parsing
+    #[serde(default)]
+    pub debug_raw_output: bool,
+    /// Keep the agent's tools offline and flag commands that reach for the network
+    #[serde(default)]
+    pub network_blocked: bool,
+    /// Regexes for loop output lines to mute, applied after the global filters
+    #[serde(default)]
+    pub output_filters: Vec<String>,
+    /// Run the agent CLI inside this container instead of on the host
+    #[serde(default)]
+    pub container: Option<ContainerConfig>,
+    /// Run the agent and git on this SSH host instead of locally
+    #[serde(default)]
+    pub remote: Option<RemoteTarget>,
+    /// Capture the running web app after each iteration
+    #[serde(default)]
+    pub screenshot: Option<ScreenshotConfig>,
+    /// Recurring loop that upgrades dependencies; off when unset
+    #[serde(default)]
+    pub dependency_updates: Option<DependencyUpdateSchedule>,
+    pub brainstorm: Option<BrainstormState>,
+    pub task: Option<TaskConfig>,
+    pub execution: Option<ExecutionState>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// When a project's dependency-update loop runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyUpdateSchedule {
+    pub interval_days: u32,
+    /// Last check for outdated dependencies, whether or not it started a loop
+    #[serde(default)]
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProjectStatus {
+    Brainstorming,
+    Ready,
+    Queued,
+    Running,
+    Pausing,
+    Paused,
+    Done,
+    Partial,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrainstormState {
+    pub answers: Vec<BrainstormAnswer>,
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Id of the persona steering this conversation
+    #[serde(default)]
+    pub persona: Option<String>,
+    /// Generated prompt awaiting review before the task is finalized
+    #[serde(default)]
+    pub draft_prompt: Option<String>,
+}
+
+/// Lint and size report for a draft task prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPreview {
+    pub prompt: String,
+    pub token_estimate: u32,
+    pub warnings: Vec<String>,
+}
+
+/// A brainstorm persona that biases how questions are asked
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrainstormPersona {
+    pub id: String,
+    pub name: String,
+    /// Instructions added to the brainstorm system prompt
+    pub prompt: String,
+    #[serde(default)]
+    pub built_in: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrainstormAnswer {
+    pub question_id: String,
+    pub question: String,
+    pub answer: serde_json::Value, // String or Vec<String>
+    pub answered_at: DateTime<Utc>,
+}
+
+fn default_auto_commit() -> bool {
+    false
+}
+
+fn default_auto_init_git() -> bool {
+    false
+}
+
+fn default_auto_decide() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskConfig {
+    pub prompt: String,
+    pub design_doc_path: Option<String>,
+    pub cli: CliType,
+    pub max_iterations: u32,
+    #[serde(default = "default_auto_commit")]
+    pub auto_commit: bool,
+    #[serde(default = "default_auto_init_git")]
+    pub auto_init_git: bool,
+    /// Inject the global auto-decision policy into the prompt at runtime
+    #[serde(default = "default_auto_decide")]
+    pub auto_decide: bool,
+    /// Template for iteration 1; supports `{base_prompt}` and `{iteration}`
+    #[serde(default)]
+    pub first_iteration_prompt: Option<String>,
+    /// Template for iterations 2+; supports `{base_prompt}` and `{iteration}`
+    #[serde(default)]
+    pub subsequent_iteration_prompt: Option<String>,
+    /// Map the codebase into `.ralph/context.md` before iteration 1
+    #[serde(default)]
+    pub warmup_context: bool,
+    /// Inject a token-budgeted file tree and relevant excerpts into the prompt
+    #[serde(default)]
+    pub repo_context: bool,
+    pub completion_signal: String,
+    /// Match `completion_signal` as a regex against the agent's final message
+    /// instead of as text anywhere in its output
+    #[serde(default)]
+    pub completion_signal_regex: bool,
+    /// Further signals, combined with `completion_signal` by `completion_mode`
+    #[serde(default)]
+    pub extra_completion_signals: Vec<String>,
+    #[serde(default)]
+    pub completion_mode: CompletionMode,
+    #[serde(default)]
+    pub completion_strategy: CompletionStrategy,
+    /// Command run after every iteration, e.g. a build or linter; when it fails,
+    /// its output is handed to the next iteration
+    #[serde(default)]
+    pub validation_command: Option<String>,
+    /// Summarize each iteration's output into a "Previous Progress" section of the next prompt
+    #[serde(default)]
+    pub carry_over_progress: bool,
+    /// Second CLI `run_cli_comparison` runs the task with, next to `cli`
+    #[serde(default)]
+    pub compare_cli: Option<CliType>,
+    /// Have a readonly CLI call review each auto-commit's diff; blockers keep
+    /// the changes uncommitted and go into the next prompt
+    #[serde(default)]
+    pub review_gate: bool,
+    /// What the reviewer checks, e.g. a bullet list; a general rubric when unset
+    #[serde(default)]
+    pub review_rubric: Option<String>,
+    /// Let a readonly CLI call review the loop every this many iterations and
+    /// revise the prompt when the agent is stuck; off when unset
+    #[serde(default)]
+    pub supervisor_interval: Option<u32>,
+    /// Script run before every iteration, e.g. `git pull` or a dependency install
+    #[serde(default)]
+    pub pre_iteration_command: Option<String>,
+    /// Skip the iteration when the pre-iteration script fails
+    #[serde(default)]
+    pub pre_iteration_abort_on_failure: bool,
+    /// Image file names stored under `.ralph/attachments/`
+    #[serde(default)]
+    pub attachments: Vec<String>,
+    /// Project-relative directory the loop runs in, e.g. `packages/api`
+    #[serde(default)]
+    pub working_subdir: Option<String>,
+    /// Monorepo targets run as sequential sessions; empty runs the project once
+    #[serde(default)]
+    pub targets: Vec<LoopTarget>,
+    /// Globs relative to the working directory, copied into the session after each iteration
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    /// Scheduling priority of the CLI and everything it starts
+    #[serde(default)]
+    pub priority: ProcessPriority,
+    /// Memory the CLI and everything it starts may use, in MB; unlimited when unset
+    #[serde(default)]
+    pub memory_limit_mb: Option<u64>,
+    /// Executable to run for `cli` instead of the one found on PATH
+    #[serde(default)]
+    pub cli_path_override: Option<String>,
+    /// Name of the `GlobalConfig::env_profiles` entry applied to the CLI
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    /// Write each session's outcome to `.ralph/status.json` in the project
+    #[serde(default)]
+    pub write_status_file: bool,
+    /// Run the CLI in a terminal the app can attach to, e.g. to answer a prompt
+    #[serde(default)]
+    pub interactive_terminal: bool,
+    /// Write or update the project's README once the loop completes, committed on its own
+    #[serde(default)]
+    pub generate_readme: bool,
+    /// Wall-clock minutes a run may take; once used up the loop stops after the
+    /// iteration in progress has finished and committed
+    #[serde(default)]
+    pub max_run_duration_mins: Option<u32>,
+    /// Knowledge base entries added to the prompt as learnings from past sessions
+    #[serde(default)]
+    pub learning_ids: Vec<Uuid>,
+}
+
+/// When a loop counts as done; the completion signal is honored under every strategy
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CompletionStrategy {
+    /// Only when the agent prints the completion signal
+    #[default]
+    Signal,
+    /// The CLI exits 0 and leaves no uncommitted changes, checked before auto-commit
+    CleanExit,
+    /// The CLI exits 0 in `iterations` iterations in a row that change nothing
+    #[serde(rename_all = "camelCase")]
+    NoOpStreak { iterations: u32 },
+}
+
+/// Whether any completion signal ends a task, or only all of them in one iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CompletionMode {
+    #[default]
+    Any,
+    All,
+}
+
+/// How much CPU and disk time a loop's CLI gets next to the rest of the machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessPriority {
+    #[default]
+    Normal,
+    /// `nice 10` on Unix, below normal on Windows
+    Low,
+    /// Only runs when the machine is otherwise idle
+    Background,
+}
+
+/// A monorepo package the loop can be scoped to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoopTarget {
+    pub name: String,
+    /// Project-relative directory the target's session runs in
+    pub subdir: String,
+    /// Appended to the task prompt for this target only
+    #[serde(default)]
+    pub prompt_suffix: Option<String>,
+}
+
+impl Default for TaskConfig {
+    fn default() -> Self {
+        Self {
+            prompt: String::new(),
+            design_doc_path: None,
+            cli: CliType::Claude,
+            max_iterations: 50,
+            auto_commit: default_auto_commit(),
+            auto_init_git: default_auto_init_git(),
+            auto_decide: default_auto_decide(),
+            first_iteration_prompt: None,
+            subsequent_iteration_prompt: None,
+            warmup_context: false,
+            repo_context: false,
+            completion_signal: "<done>COMPLETE</done>".to_string(),
+            completion_signal_regex: false,
+            extra_completion_signals: Vec::new(),
+            completion_mode: CompletionMode::default(),
+            completion_strategy: CompletionStrategy::default(),
+            validation_command: None,
+            carry_over_progress: false,
+            supervisor_interval: None,
+            review_gate: false,
+            review_rubric: None,
+            compare_cli: None,
+            pre_iteration_command: None,
+            pre_iteration_abort_on_failure: false,
+            attachments: Vec::new(),
+            working_subdir: None,
+            targets: Vec::new(),
+            artifacts: Vec::new(),
+            priority: ProcessPriority::Normal,
+            memory_limit_mb: None,
+            cli_path_override: None,
+            env_profile: None,
+            write_status_file: false,
+            interactive_terminal: false,
+            generate_readme: false,
+            max_run_duration_mins: None,
+            learning_ids: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutionState {
+    pub started_at: DateTime<Utc>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub current_iteration: u32,
+    pub last_output: String,
+    pub last_error: Option<String>,
+    pub last_exit_code: Option<i32>,
+    /// Identifies the loop run; names the session's data directory
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Monorepo target the current session is scoped to
+    #[serde(default)]
+    pub target: Option<String>,
+}
+
+/// CLI process spawned by a loop, persisted so leftovers can be found after a crash
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessRecord {
+    pub pid: u32,
+    /// Executable name used to confirm the PID was not reused
+    pub program: String,
+    pub project_id: String,
+    /// PID of the Ralph instance that spawned the process
+    pub app_pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// Start time the OS reported for the PID; with `command_line` it must match
+    /// exactly before the process is reaped. `None` until read.
+    #[serde(default)]
+    pub process_started: Option<String>,
+    /// Full command line the OS reported for the PID; `None` until read
+    #[serde(default)]
+    pub command_line: Option<String>,
+}
+
+/// Orphaned CLI processes cleaned up at startup, emitted on `orphan-report`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanReport {
+    pub terminated: Vec<ProcessRecord>,
+    pub failed: Vec<OrphanFailure>,
+}
+
+impl OrphanReport {
+    pub fn is_empty(&self) -> bool {
+        self.terminated.is_empty() && self.failed.is_empty()
+    }
+}
+
+/// Orphaned process that could not be terminated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanFailure {
+    pub process: ProcessRecord,
+    pub error: String,
+}
+
+/// Project summary served to read-only observers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedProject {
+    pub id: Uuid,
+    pub name: String,
+    pub status: ProjectStatus,
+    pub current_iteration: Option<u32>,
+    pub session_id: Option<String>,
+    pub target: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Read-only snapshot of a project for external dashboards and scripts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectBundle {
+    pub state: ProjectState,
+    /// Running session, or the latest one when idle
+    pub session_id: Option<String>,
+    /// Per-iteration token usage of `session_id`
+    pub usage: Vec<IterationUsage>,
+    /// Finished sessions from the local metrics, newest first
+    pub recent_sessions: Vec<SessionMetrics>,
+    /// Payload of the most recent session that completed with one
+    pub last_completion: Option<CompletionPayload>,
+    /// What `session_id` ran with
+    pub snapshot: Option<SessionSnapshot>,
+}
+
+/// How to get past Codex refusing to run outside a Git repository
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum GitCheckResolution {
+    /// `git init` the project directory
+    InitRepo,
+    /// Pass `--skip-git-repo-check` from now on
+    SkipCheck,
+    /// Re-link the project to a directory inside a Git repository
+    #[serde(rename_all = "camelCase")]
+    ChangeDirectory { path: String },
+}
+
+/// Risky capability the user acknowledges before it is first used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Capability {
+    /// Agent CLIs run with their permission prompts skipped
+    FullDiskAccess,
+    /// The CLI and git run on another machine over SSH
+    RemoteExecution,
+    /// Run status and logs are served to other machines on the network
+    ObserverFeed,
+    /// Enabled plugins run their own executables during loops
+    PluginExecution,
+}
+
+/// A recorded acknowledgment, stored in ~/.ralph-desktop/consents.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConsentGrant {
+    pub capability: Capability,
+    /// Project the grant covers; `None` for app-wide capabilities
+    #[serde(default)]
+    pub project_id: Option<Uuid>,
+    /// What exactly was acknowledged, e.g. the remote host; a different value asks again
+    #[serde(default)]
+    pub scope: Option<String>,
+    pub granted_at: DateTime<Utc>,
+}
+
+/// A capability in use that has no matching grant yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingConsent {
+    pub capability: Capability,
+    pub project_id: Option<Uuid>,
+    pub scope: Option<String>,
+}
+
+/// State of the local observer feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObserverStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub port: u16,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HighlightSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// Regex flagging matching output lines with a label such as `error` or `todo`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighlightRule {
+    pub pattern: String,
+    pub label: String,
+    pub severity: HighlightSeverity,
+}
+
+/// Highlight attached to an output event
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Highlight {
+    pub label: String,
+    pub severity: HighlightSeverity,
+}
+
+/// Highlighted output line within a session; `line` counts output lines in the iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionHighlight {
+    pub iteration: u32,
+    pub line: u32,
+    pub label: String,
+    pub severity: HighlightSeverity,
+    pub content: String,
+}
+
+/// File copied from the working directory into the session after an iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IterationArtifact {
+    pub iteration: u32,
+    /// Path relative to the working directory with `/` separators, or
+    /// `ralph-screenshot.png` for the page screenshot
+    pub path: String,
+    /// Absolute path of the stored copy
+    pub stored_path: String,
+    pub size_bytes: u64,
+}
+
+/// Output lines hidden by one filter pattern during a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuppressedCount {
+    pub pattern: String,
+    pub count: u64,
+}
+
+/// How many iterations touched a file during a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileHotspot {
+    pub path: String,
+    pub changes: u32,
+}
+
+/// Tokens and spend one iteration used, as reported by its CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IterationUsage {
+    pub iteration: u32,
+    pub cli: CliType,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Part of `input_tokens` read from the prompt cache
+    #[serde(default)]
+    pub cache_read_tokens: u64,
+    pub cost_usd: Option<f64>,
+}
+
+/// How an iteration ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IterationOutcome {
+    /// Still running, or the app quit before it ended
+    Running,
+    /// The loop went on to another iteration
+    Continued,
+    /// The session completed with this iteration
+    Completed,
+    /// The CLI exited with a non-zero status
+    Failed,
+    /// The CLI never ran: its spawn or the pre-iteration script failed
+    Skipped,
+    /// The loop was stopped during the iteration
+    Stopped,
+}
+
+/// One iteration of a session, saved as it starts and again as it ends
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IterationRecord {
+    pub number: u32,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: Option<DateTime<Utc>>,
+    /// `None` while running and for CLIs that were killed
+    pub exit_code: Option<i32>,
+    /// Commits the iteration made, oldest first
+    #[serde(default)]
+    pub commit_shas: Vec<String>,
+    /// `None` when the CLI reported no usage
+    #[serde(default)]
+    pub tokens: Option<IterationUsage>,
+    /// Whether the validation command passed after it; `None` when none ran
+    #[serde(default)]
+    pub validation_passed: Option<bool>,
+    pub outcome: IterationOutcome,
+}
+
+impl IterationRecord {
+    pub fn start(number: u32) -> Self {
+        Self {
+            number,
+            started_at: Utc::now(),
+            ended_at: None,
+            exit_code: None,
+            commit_shas: Vec::new(),
+            tokens: None,
+            validation_passed: None,
+            outcome: IterationOutcome::Running,
+        }
+    }
+
+    pub fn finish(&mut self, outcome: IterationOutcome) {
+        self.ended_at = Some(Utc::now());
+        self.outcome = outcome;
+    }
+}
+
+/// Analysis of a session that failed or ran out of iterations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPostmortem {
+    pub session_id: String,
+    /// Status the session ended with, e.g. `failed`
+    pub status: String,
+    pub markdown: String,
+    /// Where it is stored with the session's data
+    pub path: String,
+}
+
+/// The repository as of one iteration, checked out into a detached worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IterationSnapshot {
+    pub project_id: Uuid,
+    pub session_id: String,
+    pub iteration: u32,
+    pub commit: String,
+    /// Checkout the worktree belongs to
+    pub project_path: String,
+    /// Where the worktree is checked out
+    pub path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A change the supervisor made to a session's working prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptRevision {
+    /// Iteration after which the prompt was revised
+    pub iteration: u32,
+    pub revised_at: DateTime<Utc>,
+    pub previous: String,
+    pub prompt: String,
+    pub reason: String,
+}
+
+/// What the agent reported when it finished with `<done>{json}</done>`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionPayload {
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Files the agent points the user to
+    #[serde(default, alias = "files_of_interest", alias = "filesOfInterest")]
+    pub files: Vec<String>,
+    /// Next tasks the agent suggests
+    #[serde(default, alias = "follow_ups", alias = "suggestions")]
+    pub follow_ups: Vec<String>,
+    /// How sure the agent is the task is done, from 0 to 1
+    #[serde(default)]
+    pub confidence: Option<f64>,
+}
+
+/// What a session ran with, recorded at its start for reproducibility
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    pub captured_at: DateTime<Utc>,
+    pub app_version: String,
+    pub cli: CliType,
+    /// `None` when the CLI did not report a version
+    pub cli_version: Option<String>,
+    /// Only known for the direct API backend
+    pub model: Option<String>,
+    /// `None` outside a git repository or before the first commit
+    pub git_head: Option<String>,
+    pub prompt_sha256: String,
+    /// Loop settings in effect, keyed by their config name
+    pub settings: BTreeMap<String, String>,
+    /// CLI version of the last successful session, set when it differs from `cli_version`
+    #[serde(default)]
+    pub previous_cli_version: Option<String>,
+}
+
+/// Loop waiting for a free slot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedLoop {
+    pub project_id: Uuid,
+    /// Higher starts sooner; 0 for loops queued by a plain start
+    pub priority: i32,
+    /// Prompt the session runs instead of the task's, e.g. a follow-up
+    pub prompt: Option<String>,
+    /// Command that must pass before the session may complete
+    #[serde(default)]
+    pub completion_check: Option<String>,
+    /// Report-only session: nothing is committed
+    #[serde(default)]
+    pub readonly: bool,
+    /// Artifact globs collected on top of the task's
+    #[serde(default)]
+    pub artifacts: Vec<String>,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// CHANGELOG entry written for a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionChangelog {
+    pub entry: String,
+    /// Absolute path of the changelog
+    pub path: String,
+    /// Short sha of the commit adding the entry, when it was committed
+    pub commit: Option<String>,
+}
+
+/// CLI version the project's last successful session ran with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliVersionRecord {
+    pub cli: CliType,
+    pub version: String,
+    pub session_id: String,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// What a knowledge base entry was taken from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnowledgeKind {
+    /// The summary a session completed with
+    Summary,
+    /// Subject of a fix the session committed
+    Fix,
+}
+
+/// Something a past session learned, kept in the local knowledge base
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeEntry {
+    pub id: Uuid,
+    pub project_id: Uuid,
+    pub session_id: String,
+    pub kind: KnowledgeKind,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Knowledge base entry relevant to a task, with its keyword relevance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KnowledgeMatch {
+    pub entry: KnowledgeEntry,
+    pub score: f64,
+    /// Name of the entry's project, when it still exists
+    #[serde(default)]
+    pub project_name: Option<String>,
+}
+
+/// Totals of one finished loop session, recorded for the local monthly report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionMetrics {
+    pub project_id: Uuid,
+    pub session_id: String,
+    pub cli: CliType,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    /// Status the session log ends with, e.g. `completed` or `stopped`
+    pub status: String,
+    pub iterations: u32,
+    pub commits: u32,
+    /// Iterations that ended with a question for the user
+    #[serde(default)]
+    pub questions_asked: u32,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    /// Spend the CLI reported; `None` when it reports none
+    pub cost_usd: Option<f64>,
+}
+
+/// What `get_project_health` suggests changing in a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecommendationKind {
+    ShortenPrompt,
+    EnableTestGate,
+    ReviewValidation,
+    CarryOverProgress,
+    ReduceIterations,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthRecommendation {
+    pub kind: RecommendationKind,
+    pub message: String,
+}
+
+/// How well a project's recent sessions went; ratios run from 0 to 1 and are
+/// `None` without the data to compute them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectHealth {
+    /// Finished sessions assessed
+    pub sessions: u32,
+    /// 0–100; `None` before the first session finished
+    pub score: Option<u8>,
+    /// Sessions that completed
+    pub success_ratio: Option<f64>,
+    /// Sessions that went several iterations in a row without a commit
+    pub stall_frequency: Option<f64>,
+    /// Iterations after which the validation command passed
+    pub test_pass_rate: Option<f64>,
+    /// Pass rate of the newer half of the sessions minus the older half's
+    pub test_pass_trend: Option<f64>,
+    /// Tokens spent on iterations that committed or completed
+    pub cost_efficiency: Option<f64>,
+    pub recommendations: Vec<HealthRecommendation>,
+}
+
+/// A rendered monthly report and where it was saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageReport {
+    /// `YYYY-MM`
+    pub month: String,
+    pub markdown: String,
+    pub path: String,
+}
+
+/// Expected spend of a loop before it starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub cli: CliType,
+    pub max_iterations: u32,
+    pub prompt_tokens: u64,
+    /// Past iterations of this project and CLI the estimate draws on
+    pub sampled_iterations: u32,
+    /// Average iterations past sessions took; `None` without history
+    pub typical_iterations: Option<u32>,
+    pub input_tokens_per_iteration: u64,
+    pub output_tokens_per_iteration: u64,
+    /// Both `None` when neither history nor list prices give a cost
+    pub low_usd: Option<f64>,
+    pub high_usd: Option<f64>,
+}
+
+/// How one CLI's benchmark run ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BenchmarkOutcome {
+    Completed,
+    MaxIterationsReached,
+    Failed,
+    Stopped,
+    /// The run could not start, e.g. the worktree could not be created
+    Error,
+}
+
+/// Metrics for one isolated run of a benchmark or prompt experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkResult {
+    pub cli: CliType,
+    pub outcome: BenchmarkOutcome,
+    pub iterations: u32,
+    pub duration_ms: u64,
+    /// Whether the test command passed afterwards; `None` without one
+    pub tests_passed: Option<bool>,
+    /// Only CLIs that report spend have a cost
+    pub cost_usd: Option<f64>,
+    /// Session with the run's log
+    pub session_id: Option<String>,
+    /// Worktree holding the code the CLI produced
+    pub worktree_path: String,
+    /// What the run changed from the base commit; `None` when it could not be read
+    #[serde(default)]
+    pub diff: Option<DiffStats>,
+    pub error: Option<String>,
+}
+
+/// Changes of a run against the commit it started from, committed or not
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffStats {
+    pub insertions: u64,
+    pub deletions: u64,
+    /// Changed paths, sorted
+    pub files: Vec<String>,
+}
+
+/// Files changed by one CLI of a comparison
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliFiles {
+    pub cli: CliType,
+    pub files: Vec<String>,
+}
+
+/// Where the runs of a benchmark went different ways
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Divergence {
+    /// Files every run changed
+    pub shared_files: Vec<String>,
+    /// Files only one run changed, in run order
+    pub unique_files: Vec<CliFiles>,
+    /// Whether the test results differ; `None` unless every run was tested
+    pub tests_differ: Option<bool>,
+}
+
+/// The same task run with several CLIs, each in its own worktree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchmarkReport {
+    pub id: String,
+    pub project_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the benchmark is still running
+    pub finished_at: Option<DateTime<Utc>>,
+    pub clis: Vec<CliType>,
+    pub max_iterations: u32,
+    pub test_command: Option<String>,
+    /// In run order; CLIs not yet run have no entry
+    pub results: Vec<BenchmarkResult>,
+    /// Set once the benchmark finished with at least two runs that changed code
+    #[serde(default)]
+    pub divergence: Option<Divergence>,
+}
+
+/// A prompt under test in an experiment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptVariant {
+    pub label: String,
+    pub prompt: String,
+}
+
+/// Prompt variants run with the same CLI, each in a worktree of the same commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptExperiment {
+    pub id: String,
+    pub project_id: Uuid,
+    pub started_at: DateTime<Utc>,
+    /// `None` while the experiment is still running
+    pub finished_at: Option<DateTime<Utc>>,
+    pub cli: CliType,
+    pub base_commit: String,
+    pub max_iterations: u32,
+    pub test_command: Option<String>,
+    pub variants: Vec<PromptVariant>,
+    /// In variant order; variants not yet run have no entry
+    pub results: Vec<BenchmarkResult>,
+}
+
+/// An output line an adapter could not fully parse, aggregated by kind
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParseAnomaly {
+    pub cli: CliType,
+    pub kind: String,
+    pub count: u32,
+    /// Redacted, truncated example of the offending line
+    pub sample: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// A manual annotation on a loop session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionNote {
+    /// Iteration the note refers to; `None` for the session as a whole
+    pub iteration: Option<u32>,
+    pub text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Pin flags on a session and on individual iterations of it
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionPins {
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub iterations: Vec<u32>,
+}
+
+impl SessionPins {
+    /// Whether anything in the session is pinned (and so kept from log pruning)
+    pub fn is_pinned(&self) -> bool {
+        self.pinned || !self.iterations.is_empty()
+    }
+}
+
+/// A loop session with its notes and pins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSummary {
+    pub id: String,
+    pub notes: Vec<SessionNote>,
+    pub pins: SessionPins,
+    /// Monorepo target the session ran for
+    pub target: Option<String>,
+}
+
+/// A session with pins, as returned by `list_pinned`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PinnedSession {
+    pub project_id: Uuid,
+    pub session_id: String,
+    pub pins: SessionPins,
+}
+
+/// CLI info returned by detect_installed_clis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CliInfo {
+    pub cli_type: CliType,
+    pub name: String,
+    pub version: Option<String>,
+    pub path: String,
+    pub available: bool,
+}
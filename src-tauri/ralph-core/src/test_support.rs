@@ -0,0 +1,39 @@
+//! Helpers for tests that change process-wide state, shared with Ralph Desktop's
+//! tests through the `test-support` feature.
+
+use tokio::sync::{Mutex, MutexGuard};
+
+static ENV_LOCK: Mutex<()> = Mutex::const_new(());
+
+pub fn lock_env() -> MutexGuard<'static, ()> {
+    ENV_LOCK.blocking_lock()
+}
+
+/// Same lock as [`lock_env`], for async tests that hold it across awaits
+pub async fn lock_env_async() -> MutexGuard<'static, ()> {
+    ENV_LOCK.lock().await
+}
+
+/// Sets an environment variable for the lifetime of the guard
+pub struct EnvVarGuard {
+    key: &'static str,
+    prev: Option<std::ffi::OsString>,
+}
+
+impl EnvVarGuard {
+    pub fn set(key: &'static str, value: impl AsRef<std::ffi::OsStr>) -> Self {
+        let prev = std::env::var_os(key);
+        std::env::set_var(key, value);
+        Self { key, prev }
+    }
+}
+
+impl Drop for EnvVarGuard {
+    fn drop(&mut self) {
+        if let Some(prev) = self.prev.take() {
+            std::env::set_var(self.key, prev);
+        } else {
+            std::env::remove_var(self.key);
+        }
+    }
+}
@@ -84,8 +84,13 @@ struct GithubRelease {
 #[derive(Debug, Clone, PartialEq)]
 enum UpdatePlan {
     NoUpdate,
-    WaitingForIdle { latest_version: String },
-    ReadyToDownload { latest_version: String, asset: GithubAsset },
+    WaitingForIdle {
+        latest_version: String,
+    },
+    ReadyToDownload {
+        latest_version: String,
+        asset: GithubAsset,
+    },
 }
 
 fn plan_update(
@@ -94,8 +99,8 @@ fn plan_update(
     idle_ok: bool,
 ) -> Result<UpdatePlan, String> {
     let tag = release.tag_name.trim_start_matches('v');
-    let latest_version = Version::parse(tag)
-        .map_err(|err| format!("Invalid version tag: {err}"))?;
+    let latest_version =
+        Version::parse(tag).map_err(|err| format!("Invalid version tag: {err}"))?;
 
     let current = Version::parse(current_version).unwrap_or_else(|_| Version::new(0, 0, 0));
     if latest_version <= current {
@@ -108,8 +113,8 @@ fn plan_update(
         });
     }
 
-    let asset = select_asset(&release.assets)
-        .ok_or_else(|| "No suitable asset found".to_string())?;
+    let asset =
+        select_asset(&release.assets).ok_or_else(|| "No suitable asset found".to_string())?;
 
     Ok(UpdatePlan::ReadyToDownload {
         latest_version: latest_version.to_string(),
@@ -186,7 +191,10 @@ impl AutoUpdateService {
                 save_update_state(&state)?;
                 return Ok(state);
             }
-            UpdatePlan::ReadyToDownload { latest_version, asset } => {
+            UpdatePlan::ReadyToDownload {
+                latest_version,
+                asset,
+            } => {
                 state.status = UpdateStatus::UpdateAvailable;
                 state.target_version = Some(latest_version.clone());
                 save_update_state(&state)?;
@@ -198,7 +206,10 @@ impl AutoUpdateService {
                 state.download_path = Some(download_path.to_string_lossy().to_string());
                 save_update_state(&state)?;
 
-                if let Err(err) = self.download_asset(&asset.browser_download_url, &download_path).await {
+                if let Err(err) = self
+                    .download_asset(&asset.browser_download_url, &download_path)
+                    .await
+                {
                     state.status = UpdateStatus::Failed;
                     state.failure_count += 1;
                     state.last_error = Some(err);
@@ -384,9 +395,7 @@ pub async fn apply_pending_update() -> storage::Result<()> {
 
         #[cfg(target_os = "macos")]
         {
-            let _ = std::process::Command::new("open")
-                .arg(&file_path)
-                .spawn();
+            let _ = std::process::Command::new("open").arg(&file_path).spawn();
         }
 
         #[cfg(target_os = "windows")]
@@ -416,7 +425,6 @@ pub async fn apply_pending_update() -> storage::Result<()> {
     Ok(())
 }
 
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -438,11 +446,17 @@ mod tests {
 
     fn asset_name_for_os() -> &'static str {
         #[cfg(target_os = "macos")]
-        { ".dmg" }
+        {
+            ".dmg"
+        }
         #[cfg(target_os = "windows")]
-        { ".exe" }
+        {
+            ".exe"
+        }
         #[cfg(target_os = "linux")]
-        { ".AppImage" }
+        {
+            ".AppImage"
+        }
     }
 
     #[test]
@@ -484,7 +498,10 @@ mod tests {
         };
         let plan = plan_update("1.0.0", &release, true).expect("plan");
         match plan {
-            UpdatePlan::ReadyToDownload { latest_version, asset } => {
+            UpdatePlan::ReadyToDownload {
+                latest_version,
+                asset,
+            } => {
                 assert_eq!(latest_version, "2.0.0");
                 assert!(asset.name.ends_with(asset_name_for_os()));
             }
@@ -513,7 +530,9 @@ mod tests {
                 sha256: "abc123".to_string(),
             };
             save_pending_update(&pending).expect("save pending");
-            let loaded = load_pending_update().expect("load pending").expect("pending");
+            let loaded = load_pending_update()
+                .expect("load pending")
+                .expect("pending");
             assert_eq!(loaded.version, pending.version);
             assert_eq!(loaded.asset_name, pending.asset_name);
             clear_pending_update().expect("clear pending");
@@ -0,0 +1,487 @@
+use super::*;
+use crate::engine::benchmark;
+use crate::engine::targets::session_id_for;
+use crate::engine::workdir::resolve_working_dir;
+use crate::engine::{LoopConfig, LoopEngine};
+use crate::security::consent;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use tauri::Emitter;
+
+/// Placeholder control handle while no engine of the benchmark is running
+fn idle_handle() -> Arc<LoopEngineHandle> {
+    Arc::new(LoopEngineHandle {
+        pause_flag: Arc::new(AtomicBool::new(false)),
+        stop_flag: Arc::new(AtomicBool::new(false)),
+        resume_notify: Arc::new(tokio::sync::Notify::new()),
+    })
+}
+
+/// Lower-case CLI name used for worktree directories and session ids
+fn cli_slug(cli: CliType) -> String {
+    serde_json::to_value(cli)
+        .ok()
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Save the report and its Markdown rendering, then tell the frontend
+fn publish_report(app_handle: &AppHandle, report: &BenchmarkReport) {
+    let _ = storage::save_benchmark_report(report);
+    if let Ok(dir) = storage::get_benchmark_dir(&report.project_id, &report.id) {
+        let _ = storage::crypto::write_text(
+            &dir.join("report.md"),
+            &benchmark::render_markdown(report),
+        );
+    }
+    let _ = app_handle.emit("benchmark-progress", report);
+}
+
+/// Save the experiment and its Markdown rendering, then tell the frontend
+fn publish_experiment(app_handle: &AppHandle, experiment: &PromptExperiment) {
+    let _ = storage::save_prompt_experiment(experiment);
+    if let Ok(dir) = storage::get_experiment_dir(&experiment.project_id, &experiment.id) {
+        let _ = storage::crypto::write_text(
+            &dir.join("report.md"),
+            &benchmark::render_experiment_markdown(experiment),
+        );
+    }
+    let _ = app_handle.emit("prompt-experiment-progress", experiment);
+}
+
+/// Load a project that can run isolated loops, a local checkout with no loop of
+/// its own running, together with its task
+async fn load_benchmarkable(
+    state: &AppState,
+    uuid: &Uuid,
+) -> Result<(ProjectState, TaskConfig), String> {
+    if state.running_loops.read().await.contains_key(uuid) {
+        return Err("Stop the project's loop before benchmarking".to_string());
+    }
+    let project_state = storage::load_project_state(uuid).map_err(|e| e.to_string())?;
+    let task = project_state
+        .task
+        .clone()
+        .ok_or("No task configured for this project")?;
+    if project_state.remote.is_some() {
+        return Err("Benchmarks need a local project checkout".to_string());
+    }
+    // The CLIs run with the same permissions as a loop, so need the same grants
+    let config = storage::load_workspace_config(project_state.workspace_id.as_ref())
+        .map_err(|e| e.to_string())?;
+    ensure_consented(&config, consent::loop_requirements(&config, &project_state))?;
+    Ok((project_state, task))
+}
+
+/// Reserve the project's single benchmark slot
+async fn claim_slot(state: &AppState, uuid: Uuid) -> Result<(), String> {
+    let mut benchmarks = state.running_benchmarks.write().await;
+    if benchmarks.contains_key(&uuid) {
+        return Err("A benchmark is already running for this project".to_string());
+    }
+    benchmarks.insert(uuid, idle_handle());
+    Ok(())
+}
+
+fn normalize_test_command(test_command: Option<String>) -> Option<String> {
+    test_command
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+}
+
+/// One loop of a benchmark or experiment, run in its own worktree
+struct IsolatedRun {
+    project_id: Uuid,
+    worktree: PathBuf,
+    /// Commit the worktree checks out
+    base_commit: String,
+    task: TaskConfig,
+    /// Names the run's session
+    label: String,
+    test_command: Option<String>,
+}
+
+/// Run one loop in its own worktree and collect its metrics
+async fn run_isolated(
+    app_handle: &AppHandle,
+    state: &AppState,
+    project_state: &ProjectState,
+    config: &GlobalConfig,
+    run: IsolatedRun,
+) -> BenchmarkResult {
+    let project_path = PathBuf::from(&project_state.path);
+    let worktree = run.worktree;
+    let mut result = BenchmarkResult {
+        cli: run.task.cli,
+        outcome: BenchmarkOutcome::Error,
+        iterations: 0,
+        duration_ms: 0,
+        tests_passed: None,
+        cost_usd: None,
+        session_id: None,
+        worktree_path: worktree.to_string_lossy().to_string(),
+        diff: None,
+        error: None,
+    };
+
+    if let Err(e) = benchmark::add_worktree(&project_path, &worktree, &run.base_commit).await {
+        result.error = Some(e);
+        return result;
+    }
+    let mut loop_config = match LoopConfig::for_task(&run.task, config, project_state, &worktree) {
+        Ok(loop_config) => loop_config,
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    };
+    match resolve_working_dir(&worktree, run.task.working_subdir.as_deref()) {
+        Ok(dir) => loop_config.working_dir = (dir != worktree).then_some(dir),
+        Err(e) => {
+            result.error = Some(e);
+            return result;
+        }
+    }
+    let session_id = session_id_for(Some(&run.label));
+    loop_config.session_id = session_id.clone();
+    loop_config.screenshot = None;
+    loop_config.isolated = true;
+
+    let engine = LoopEngine::new(
+        run.project_id.to_string(),
+        worktree.clone(),
+        loop_config,
+        Arc::new(WindowEvents(app_handle.clone())),
+    );
+    {
+        // `stop_benchmark` removes the entry; a missing one means stop before starting
+        let mut benchmarks = state.running_benchmarks.write().await;
+        let Some(handle) = benchmarks.get_mut(&run.project_id) else {
+            result.outcome = BenchmarkOutcome::Stopped;
+            return result;
+        };
+        *handle = Arc::new(LoopEngineHandle {
+            pause_flag: engine.get_pause_flag(),
+            stop_flag: engine.get_stop_flag(),
+            resume_notify: engine.get_resume_notify(),
+        });
+    }
+
+    let started = Instant::now();
+    let outcome = engine.start().await;
+    let (kind, iterations) = benchmark::outcome_of(&outcome);
+    result.outcome = kind;
+    result.iterations = iterations;
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result.cost_usd = engine.total_cost_usd();
+    result.session_id = Some(session_id);
+    result.error = outcome.err();
+    result.diff = benchmark::diff_stats(&worktree, &run.base_commit).await;
+    if kind != BenchmarkOutcome::Stopped {
+        if let Some(command) = &run.test_command {
+            result.tests_passed = Some(benchmark::run_tests(command, &worktree).await);
+        }
+    }
+    result
+}
+
+/// Task as run in isolation: commits would only land on the detached
+/// worktree, so skipping them saves the commit-message calls. Runs are
+/// unattended, so they never get an attachable terminal.
+fn isolated_task(task: &TaskConfig, max_iterations: u32) -> TaskConfig {
+    TaskConfig {
+        max_iterations,
+        auto_commit: false,
+        interactive_terminal: false,
+        ..task.clone()
+    }
+}
+
+/// Run the project's task once per CLI, each in a worktree of HEAD, and compare
+/// the results. Returns the report as started; `benchmark-progress` carries it
+/// again after every CLI and when the benchmark finishes.
+#[tauri::command]
+pub async fn run_benchmark(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    clis: Vec<CliType>,
+    iterations: u32,
+    test_command: Option<String>,
+) -> Result<BenchmarkReport, String> {
+    if clis.is_empty() {
+        return Err("Choose at least one CLI to benchmark".to_string());
+    }
+    if iterations == 0 {
+        return Err("Iterations must be greater than zero".to_string());
+    }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let (project_state, task) = load_benchmarkable(&state, &uuid).await?;
+    let base_commit = benchmark::head_commit(Path::new(&project_state.path)).await?;
+    let config = storage::load_workspace_config(project_state.workspace_id.as_ref())
+        .map_err(|e| e.to_string())?;
+
+    let report = BenchmarkReport {
+        id: Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string(),
+        project_id: uuid,
+        started_at: Utc::now(),
+        finished_at: None,
+        clis,
+        max_iterations: iterations,
+        test_command: normalize_test_command(test_command),
+        results: Vec::new(),
+        divergence: None,
+    };
+    claim_slot(&state, uuid).await?;
+    storage::save_benchmark_report(&report).map_err(|e| e.to_string())?;
+
+    let state = state.inner().clone();
+    let task = isolated_task(&task, iterations);
+    let dir = storage::get_benchmark_dir(&uuid, &report.id).map_err(|e| e.to_string())?;
+    let mut running = report.clone();
+    tokio::spawn(async move {
+        for cli in running.clis.clone() {
+            let slug = cli_slug(cli);
+            let run = IsolatedRun {
+                project_id: uuid,
+                worktree: dir.join(&slug),
+                base_commit: base_commit.clone(),
+                task: TaskConfig {
+                    cli,
+                    // The override names the task's own CLI, not the others compared
+                    cli_path_override: task.cli_path_override.clone().filter(|_| cli == task.cli),
+                    ..task.clone()
+                },
+                label: format!("benchmark {}", slug),
+                test_command: running.test_command.clone(),
+            };
+            let result = run_isolated(&app_handle, &state, &project_state, &config, run).await;
+            let stopped = result.outcome == BenchmarkOutcome::Stopped;
+            running.results.push(result);
+            publish_report(&app_handle, &running);
+            if stopped || !state.running_benchmarks.read().await.contains_key(&uuid) {
+                break;
+            }
+        }
+        running.finished_at = Some(Utc::now());
+        running.divergence = benchmark::divergence(&running.results);
+        publish_report(&app_handle, &running);
+        state.running_benchmarks.write().await.remove(&uuid);
+    });
+
+    Ok(report)
+}
+
+/// Run the project's task with its CLI and its comparison CLI, each in a
+/// worktree of HEAD, with the task's iteration limit and validation command as
+/// the test; a benchmark of the two whose report shows where they diverge
+#[tauri::command]
+pub async fn run_cli_comparison(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<BenchmarkReport, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let task = storage::load_project_state(&uuid)
+        .map_err(|e| e.to_string())?
+        .task
+        .ok_or("No task configured for this project")?;
+    let other = task
+        .compare_cli
+        .filter(|cli| *cli != task.cli)
+        .ok_or("Choose a second CLI to compare the task's CLI with")?;
+    run_benchmark(
+        app_handle,
+        state,
+        project_id,
+        vec![task.cli, other],
+        task.max_iterations,
+        task.validation_command,
+    )
+    .await
+}
+
+/// Run the project's task with two prompt variants, each with the task's CLI
+/// in a worktree of the current HEAD, and compare the results. Returns the
+/// experiment as started; `prompt-experiment-progress` carries it again after
+/// every variant and when the experiment finishes.
+#[tauri::command]
+pub async fn run_prompt_experiment(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    prompt_a: String,
+    prompt_b: String,
+    iterations: u32,
+    test_command: Option<String>,
+) -> Result<PromptExperiment, String> {
+    if prompt_a.trim().is_empty() || prompt_b.trim().is_empty() {
+        return Err("Both prompt variants need a prompt".to_string());
+    }
+    if prompt_a.trim() == prompt_b.trim() {
+        return Err("The prompt variants are identical".to_string());
+    }
+    if iterations == 0 {
+        return Err("Iterations must be greater than zero".to_string());
+    }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let (project_state, task) = load_benchmarkable(&state, &uuid).await?;
+    let base_commit = benchmark::head_commit(Path::new(&project_state.path)).await?;
+    let config = storage::load_workspace_config(project_state.workspace_id.as_ref())
+        .map_err(|e| e.to_string())?;
+    let task = isolated_task(&task, iterations);
+
+    let experiment = PromptExperiment {
+        id: Utc::now().format("%Y-%m-%dT%H-%M-%S").to_string(),
+        project_id: uuid,
+        started_at: Utc::now(),
+        finished_at: None,
+        cli: task.cli,
+        base_commit: base_commit.clone(),
+        max_iterations: iterations,
+        test_command: normalize_test_command(test_command),
+        variants: vec![
+            PromptVariant {
+                label: "A".to_string(),
+                prompt: prompt_a,
+            },
+            PromptVariant {
+                label: "B".to_string(),
+                prompt: prompt_b,
+            },
+        ],
+        results: Vec::new(),
+    };
+    claim_slot(&state, uuid).await?;
+    storage::save_prompt_experiment(&experiment).map_err(|e| e.to_string())?;
+
+    let state = state.inner().clone();
+    let dir = storage::get_experiment_dir(&uuid, &experiment.id).map_err(|e| e.to_string())?;
+    let mut running = experiment.clone();
+    tokio::spawn(async move {
+        for variant in running.variants.clone() {
+            let slug = format!("variant-{}", variant.label.to_lowercase());
+            let run = IsolatedRun {
+                project_id: uuid,
+                worktree: dir.join(&slug),
+                base_commit: base_commit.clone(),
+                task: TaskConfig {
+                    prompt: variant.prompt,
+                    ..task.clone()
+                },
+                label: format!("experiment {}", slug),
+                test_command: running.test_command.clone(),
+            };
+            let result = run_isolated(&app_handle, &state, &project_state, &config, run).await;
+            let stopped = result.outcome == BenchmarkOutcome::Stopped;
+            running.results.push(result);
+            publish_experiment(&app_handle, &running);
+            if stopped || !state.running_benchmarks.read().await.contains_key(&uuid) {
+                break;
+            }
+        }
+        running.finished_at = Some(Utc::now());
+        publish_experiment(&app_handle, &running);
+        state.running_benchmarks.write().await.remove(&uuid);
+    });
+
+    Ok(experiment)
+}
+
+/// Stop a project's running benchmark or prompt experiment; runs not yet
+/// started are skipped
+#[tauri::command]
+pub async fn stop_benchmark(state: State<'_, AppState>, project_id: String) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let handle = state
+        .running_benchmarks
+        .write()
+        .await
+        .remove(&uuid)
+        .ok_or("No benchmark running for this project")?;
+    handle.stop_flag.store(true, Ordering::SeqCst);
+    handle.resume_notify.notify_one();
+    Ok(())
+}
+
+/// Benchmark reports of a project, newest first
+#[tauri::command]
+pub async fn list_benchmarks(project_id: String) -> Result<Vec<BenchmarkReport>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    storage::list_benchmark_reports(&uuid).map_err(|e| e.to_string())
+}
+
+/// Prompt experiments of a project, newest first
+#[tauri::command]
+pub async fn list_prompt_experiments(project_id: String) -> Result<Vec<PromptExperiment>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    storage::list_prompt_experiments(&uuid).map_err(|e| e.to_string())
+}
+
+/// Remove the worktrees of finished runs, then their data directory
+async fn remove_runs(
+    state: &AppState,
+    uuid: &Uuid,
+    finished: bool,
+    results: &[BenchmarkResult],
+    dir: PathBuf,
+) -> Result<(), String> {
+    if !finished && state.running_benchmarks.read().await.contains_key(uuid) {
+        return Err("Stop the benchmark before deleting it".to_string());
+    }
+    let project_state = storage::load_project_state(uuid).map_err(|e| e.to_string())?;
+    let project_path = PathBuf::from(&project_state.path);
+    for result in results {
+        benchmark::remove_worktree(&project_path, &PathBuf::from(&result.worktree_path)).await;
+    }
+    std::fs::remove_dir_all(dir).map_err(|e| e.to_string())
+}
+
+/// Delete a finished benchmark with its report and worktrees
+#[tauri::command]
+pub async fn delete_benchmark(
+    state: State<'_, AppState>,
+    project_id: String,
+    benchmark_id: String,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let report = storage::list_benchmark_reports(&uuid)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|r| r.id == benchmark_id)
+        .ok_or("Benchmark not found")?;
+    let dir = storage::get_benchmark_dir(&uuid, &report.id).map_err(|e| e.to_string())?;
+    remove_runs(
+        &state,
+        &uuid,
+        report.finished_at.is_some(),
+        &report.results,
+        dir,
+    )
+    .await
+}
+
+/// Delete a finished prompt experiment with its results and worktrees
+#[tauri::command]
+pub async fn delete_prompt_experiment(
+    state: State<'_, AppState>,
+    project_id: String,
+    experiment_id: String,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let experiment = storage::list_prompt_experiments(&uuid)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|e| e.id == experiment_id)
+        .ok_or("Prompt experiment not found")?;
+    let dir = storage::get_experiment_dir(&uuid, &experiment.id).map_err(|e| e.to_string())?;
+    remove_runs(
+        &state,
+        &uuid,
+        experiment.finished_at.is_some(),
+        &experiment.results,
+        dir,
+    )
+    .await
+}
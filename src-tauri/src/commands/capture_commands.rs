@@ -0,0 +1,134 @@
+use super::project_commands::clean_prompt_input;
+use super::*;
+use crate::engine::ai_brainstorm::truncate_to_title;
+use serde::Serialize;
+use tauri::Emitter;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+const PREVIEW_CHARS: usize = 280;
+
+/// Sent on `quick-capture` so the UI can confirm before a task is created
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickCaptureRequest {
+    pub preview: String,
+}
+
+fn read_clipboard(app_handle: &AppHandle) -> Result<String, String> {
+    let text = app_handle
+        .clipboard()
+        .read_text()
+        .map_err(|e| e.to_string())?;
+    if text.trim().is_empty() {
+        return Err("Clipboard is empty".to_string());
+    }
+    Ok(text)
+}
+
+/// Ask the frontend to confirm a quick capture of the current clipboard
+pub fn request_quick_capture(app_handle: &AppHandle) {
+    let Ok(text) = read_clipboard(app_handle) else {
+        return;
+    };
+    let preview = text.trim().chars().take(PREVIEW_CHARS).collect();
+    let _ = app_handle.emit("quick-capture", QuickCaptureRequest { preview });
+}
+
+fn same_path(a: &str, b: &str) -> bool {
+    a.trim_end_matches(['/', '\\']) == b.trim_end_matches(['/', '\\'])
+}
+
+/// Turn `text` into a Ready task, on the project at `path` or the most recently opened one
+pub(crate) async fn quick_task_from_text(
+    path: Option<String>,
+    text: String,
+) -> Result<ProjectState, String> {
+    let prompt = clean_prompt_input(&text)?.trim().to_string();
+    if prompt.is_empty() {
+        return Err("Task text is empty".to_string());
+    }
+    let index = storage::load_project_index().map_err(|e| e.to_string())?;
+    let existing = match &path {
+        Some(path) => index.projects.iter().find(|p| same_path(&p.path, path)),
+        None => index.projects.iter().max_by_key(|p| p.last_opened_at),
+    };
+
+    let mut state = match (existing, path) {
+        (Some(meta), _) => storage::load_project_state(&meta.id).map_err(|e| e.to_string())?,
+        (None, Some(path)) => {
+            let first_line = prompt.lines().next().unwrap_or_default();
+            create_project(path, truncate_to_title(first_line, 15)).await?
+        }
+        (None, None) => return Err("No project to capture the task into".to_string()),
+    };
+    if matches!(
+        state.status,
+        ProjectStatus::Running | ProjectStatus::Pausing | ProjectStatus::Queued
+    ) {
+        return Err("Project already has a task in progress".to_string());
+    }
+
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let task = state.task.get_or_insert_with(|| TaskConfig {
+        cli: config.default_cli,
+        max_iterations: config.default_max_iterations,
+        ..TaskConfig::default()
+    });
+    task.prompt = prompt;
+    if let Some(ref mut brainstorm) = state.brainstorm {
+        brainstorm.draft_prompt = None;
+        brainstorm.completed_at = Some(Utc::now());
+    }
+    state.execution = None;
+    state.status = ProjectStatus::Ready;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Create a Ready task from the clipboard, on the project at `path` or the most recent one
+#[tauri::command]
+pub async fn quick_task_from_clipboard(
+    app_handle: AppHandle,
+    path: Option<String>,
+) -> Result<ProjectState, String> {
+    let text = read_clipboard(&app_handle)?;
+    quick_task_from_text(path, text).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env_async, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn quick_task_creates_or_reuses_project() {
+        let _lock = lock_env_async().await;
+        let home = tempdir().unwrap();
+        let _home = EnvVarGuard::set("HOME", home.path());
+        let project_dir = tempdir().unwrap();
+        let path = project_dir.path().to_string_lossy().to_string();
+
+        let created = quick_task_from_text(Some(path.clone()), "Fix the login bug\nsteps".into())
+            .await
+            .unwrap();
+        assert_eq!(created.status, ProjectStatus::Ready);
+        assert_eq!(created.name, "Fix the login b…");
+        assert_eq!(
+            created.task.as_ref().map(|t| t.prompt.as_str()),
+            Some("Fix the login bug\nsteps")
+        );
+
+        let reused = quick_task_from_text(None, "  Add dark mode  ".into())
+            .await
+            .unwrap();
+        assert_eq!(reused.id, created.id);
+        assert_eq!(
+            reused.task.as_ref().map(|t| t.prompt.as_str()),
+            Some("Add dark mode")
+        );
+        assert_eq!(storage::load_project_index().unwrap().projects.len(), 1);
+        assert!(quick_task_from_text(None, "   ".into()).await.is_err());
+    }
+}
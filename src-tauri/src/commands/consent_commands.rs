@@ -0,0 +1,80 @@
+use super::*;
+use crate::security::consent::{self, CONSENT_REQUIRED};
+
+/// Fail with `CONSENT_REQUIRED` while any of `required` lacks a grant
+pub(crate) fn ensure_consented(
+    config: &GlobalConfig,
+    required: Vec<PendingConsent>,
+) -> Result<(), String> {
+    let grants = storage::load_consents().map_err(|e| e.to_string())?;
+    if consent::pending(&grants, config, required).is_empty() {
+        Ok(())
+    } else {
+        Err(CONSENT_REQUIRED.to_string())
+    }
+}
+
+/// Capabilities awaiting acknowledgment: a project's loop requirements plus
+/// the app-wide ones, or only the app-wide ones without a project
+#[tauri::command]
+pub async fn get_pending_consents(
+    project_id: Option<String>,
+) -> Result<Vec<PendingConsent>, String> {
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let mut required = consent::app_requirements(&config);
+    if let Some(project_id) = project_id {
+        let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+        let project = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+        // Loops run with their workspace's settings, plugins included
+        let loop_config = storage::load_workspace_config(project.workspace_id.as_ref())
+            .map_err(|e| e.to_string())?;
+        for request in consent::loop_requirements(&loop_config, &project) {
+            if !required.contains(&request) {
+                required.push(request);
+            }
+        }
+    }
+    let grants = storage::load_consents().map_err(|e| e.to_string())?;
+    Ok(consent::pending(&grants, &config, required))
+}
+
+/// Acknowledge a pending capability
+#[tauri::command]
+pub async fn grant_consent(consent: PendingConsent) -> Result<(), String> {
+    if consent.capability == Capability::FullDiskAccess {
+        let mut config = storage::load_config().map_err(|e| e.to_string())?;
+        config.permissions_confirmed = true;
+        config.permissions_confirmed_at = Some(Utc::now());
+        storage::save_config(&config).map_err(|e| e.to_string())?;
+    }
+    let mut grants = storage::load_consents().map_err(|e| e.to_string())?;
+    consent::grant(&mut grants, consent, Utc::now());
+    storage::save_consents(&grants).map_err(|e| e.to_string())
+}
+
+/// Withdraw an acknowledgment so the capability is asked for again
+#[tauri::command]
+pub async fn revoke_consent(
+    capability: Capability,
+    project_id: Option<String>,
+) -> Result<bool, String> {
+    let project_id = project_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    if capability == Capability::FullDiskAccess {
+        let mut config = storage::load_config().map_err(|e| e.to_string())?;
+        config.permissions_confirmed = false;
+        config.permissions_confirmed_at = None;
+        storage::save_config(&config).map_err(|e| e.to_string())?;
+    }
+    let mut grants = storage::load_consents().map_err(|e| e.to_string())?;
+    let revoked = consent::revoke(&mut grants, capability, project_id);
+    storage::save_consents(&grants).map_err(|e| e.to_string())?;
+    Ok(revoked)
+}
+
+/// Recorded acknowledgments, oldest first
+#[tauri::command]
+pub async fn list_consents() -> Result<Vec<ConsentGrant>, String> {
+    storage::load_consents().map_err(|e| e.to_string())
+}
@@ -0,0 +1,45 @@
+use super::*;
+use std::path::PathBuf;
+
+const ALL_CLIS: [CliType; 9] = [
+    CliType::Claude,
+    CliType::Codex,
+    CliType::OpenCode,
+    CliType::Amp,
+    CliType::QwenCode,
+    CliType::Iflow,
+    CliType::Gemini,
+    CliType::Api,
+    CliType::Custom,
+];
+
+fn collect_parse_anomalies(cli: Option<CliType>) -> Result<Vec<ParseAnomaly>, String> {
+    let clis: Vec<CliType> = match cli {
+        Some(cli) => vec![cli],
+        None => ALL_CLIS.to_vec(),
+    };
+    let mut anomalies = Vec::new();
+    for cli in clis {
+        anomalies.extend(storage::load_parse_anomalies(cli).map_err(|e| e.to_string())?);
+    }
+    Ok(anomalies)
+}
+
+/// List output lines the CLI adapters failed to parse, optionally for one CLI
+#[tauri::command]
+pub async fn list_parse_anomalies(cli: Option<CliType>) -> Result<Vec<ParseAnomaly>, String> {
+    collect_parse_anomalies(cli)
+}
+
+/// Export recorded parse anomalies as JSONL so users can attach them to a bug report
+#[tauri::command]
+pub async fn export_parse_anomalies(destination: String) -> Result<u32, String> {
+    let anomalies = collect_parse_anomalies(None)?;
+    let mut content = String::new();
+    for anomaly in &anomalies {
+        content.push_str(&serde_json::to_string(anomaly).map_err(|e| e.to_string())?);
+        content.push('\n');
+    }
+    std::fs::write(PathBuf::from(destination), content).map_err(|e| e.to_string())?;
+    Ok(anomalies.len() as u32)
+}
@@ -0,0 +1,57 @@
+use super::*;
+use crate::engine::knowledge;
+
+/// Opt in or out of keeping what sessions learned in the local knowledge base
+#[tauri::command]
+pub async fn set_knowledge_base(enabled: bool) -> Result<GlobalConfig, String> {
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    config.knowledge_base = enabled;
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// Past learnings most relevant to the project's task, to offer before its loop
+/// starts; empty while the knowledge base is off
+#[tauri::command]
+pub async fn suggest_learnings(project_id: String) -> Result<Vec<KnowledgeMatch>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let config =
+        storage::load_workspace_config(state.workspace_id.as_ref()).map_err(|e| e.to_string())?;
+    let Some(task) = state.task.filter(|_| config.knowledge_base) else {
+        return Ok(Vec::new());
+    };
+    let names: HashMap<Uuid, String> = storage::load_project_index()
+        .map_err(|e| e.to_string())?
+        .projects
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    let mut matches = knowledge::suggest(&task.prompt, knowledge::TOP_K)?;
+    for found in &mut matches {
+        found.project_name = names.get(&found.entry.project_id).cloned();
+    }
+    Ok(matches)
+}
+
+/// Choose the knowledge base entries listed in the task's prompt as learnings
+#[tauri::command]
+pub async fn update_task_learnings(
+    project_id: String,
+    learning_ids: Vec<String>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let learning_ids = learning_ids
+        .iter()
+        .map(|id| Uuid::parse_str(id).map_err(|e| e.to_string()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.learning_ids = learning_ids;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
@@ -1,9 +1,20 @@
 use super::*;
 use crate::adapters::hide_console_window;
-use crate::engine::{LoopEngine, LoopEvent, CODEX_GIT_REPO_CHECK_REQUIRED};
-use std::path::PathBuf;
+use crate::engine::dependency_updates;
+use crate::engine::issue_triage;
+use crate::engine::scheduler;
+use crate::engine::targets::{plan_target_runs, session_id_for};
+use crate::engine::workdir;
+use crate::engine::{
+    LoopConfig, LoopEngine, LoopEvent, CODEX_GIT_REPO_CHECK_REQUIRED, LOOP_ALREADY_RUNNING,
+    PROJECT_PATH_MISSING,
+};
+use crate::security::consent;
+use futures_util::future::BoxFuture;
+use std::collections::hash_map::Entry;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
-use tauri::Emitter;
+use tauri::{Emitter, Manager};
 use tokio::process::Command;
 
 /// Start Ralph Loop for a project
@@ -13,18 +24,460 @@ pub async fn start_loop(
     state: State<'_, AppState>,
     project_id: String,
 ) -> Result<(), String> {
+    start_session(app_handle, &state, project_id, SessionOverrides::default()).await
+}
+
+/// Start a new session that works on one of the follow-ups the agent suggested
+/// when the project's latest structured completion was reached; the task prompt
+/// itself is left unchanged
+#[tauri::command]
+pub async fn start_followup(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    suggestion_index: usize,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let suggestions = latest_followups(&uuid)?;
+    let suggestion = suggestions
+        .get(suggestion_index)
+        .ok_or_else(|| format!("No follow-up suggestion #{}", suggestion_index + 1))?
+        .clone();
+    let overrides = SessionOverrides {
+        prompt: Some(suggestion),
+        ..SessionOverrides::default()
+    };
+    start_session(app_handle, &state, project_id, overrides).await
+}
+
+/// Apply the user's choice after Codex refused to run outside a Git repository,
+/// clear the error it left on the project and optionally start the loop again
+#[tauri::command]
+pub async fn resolve_codex_git_check(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    action: GitCheckResolution,
+    restart: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    if state.running_loops.read().await.contains_key(&uuid) {
+        return Err(LOOP_ALREADY_RUNNING.to_string());
+    }
+
+    let mut project_state = match action {
+        GitCheckResolution::InitRepo => {
+            let project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+            init_git_repo(&PathBuf::from(&project_state.path)).await?;
+            project_state
+        }
+        GitCheckResolution::SkipCheck => {
+            let mut project_state =
+                storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+            project_state.skip_git_repo_check = true;
+            project_state
+        }
+        GitCheckResolution::ChangeDirectory { path } => {
+            if !is_git_repo(&PathBuf::from(&path)).await? {
+                return Err(format!("Not inside a Git repository: {}", path));
+            }
+            super::project_commands::relink_project(&uuid, path)?
+        }
+    };
+    if let Some(ref mut exec) = project_state.execution {
+        if exec
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains(CODEX_GIT_REPO_CHECK_REQUIRED))
+        {
+            exec.last_error = None;
+        }
+    }
+    project_state.updated_at = Utc::now();
+    storage::save_project_state(&project_state).map_err(|e| e.to_string())?;
+
+    if restart {
+        start_session(app_handle, &state, project_id, SessionOverrides::default()).await?;
+        return storage::load_project_state(&uuid).map_err(|e| e.to_string());
+    }
+    Ok(project_state)
+}
+
+/// Follow-ups of the newest session that completed with a structured payload
+pub(crate) fn latest_followups(project_id: &Uuid) -> Result<Vec<String>, String> {
+    for session_id in storage::list_session_ids(project_id).map_err(|e| e.to_string())? {
+        if let Some(payload) =
+            storage::load_session_completion(project_id, &session_id).map_err(|e| e.to_string())?
+        {
+            return Ok(payload.follow_ups);
+        }
+    }
+    Ok(Vec::new())
+}
+
+/// Start a loop, or queue it with `priority` when every slot is taken;
+/// returns its position in the queue, 0 when it started right away
+#[tauri::command]
+pub async fn queue_loop(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+    priority: Option<i32>,
+) -> Result<usize, String> {
+    schedule_session(
+        app_handle,
+        &state,
+        project_id,
+        SessionOverrides::default(),
+        priority.unwrap_or(0),
+    )
+    .await
+}
+
+/// Loops waiting for a slot, in the order they will start
+#[tauri::command]
+pub async fn list_queue(state: State<'_, AppState>) -> Result<Vec<QueuedLoop>, String> {
+    Ok(state.loop_queue.read().await.entries().to_vec())
+}
+
+/// Priority of scheduled dependency updates, behind loops started by hand
+const DEPENDENCY_UPDATE_PRIORITY: i32 = -1;
+/// How often schedules are checked for due dependency updates
+const DEPENDENCY_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Check the project's dependencies now and start an update loop when some are
+/// outdated, with or without a schedule; returns whether a loop was started or queued
+#[tauri::command]
+pub async fn run_dependency_update(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<bool, String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    start_dependency_update(app_handle, &state, uuid, true).await
+}
+
+/// Start the dependency updates of projects whose schedule is due, for as long as the app runs
+pub async fn watch_dependency_updates(app_handle: AppHandle) {
+    loop {
+        let state = app_handle.state::<AppState>().inner().clone();
+        let projects = storage::load_project_index()
+            .map(|index| index.projects)
+            .unwrap_or_default();
+        for project in projects {
+            let started =
+                start_dependency_update(app_handle.clone(), &state, project.id, false).await;
+            if let Err(e) = started {
+                eprintln!("[Dependency updates] {}: {}", project.name, e);
+            }
+        }
+        tokio::time::sleep(DEPENDENCY_CHECK_INTERVAL).await;
+    }
+}
+
+/// Look for outdated dependencies when the schedule is due (or `force`d) and
+/// queue a loop upgrading them, completing only once the tests pass
+async fn start_dependency_update(
+    app_handle: AppHandle,
+    state: &AppState,
+    uuid: Uuid,
+    force: bool,
+) -> Result<bool, String> {
     let mut project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    if !force {
+        let busy = matches!(
+            project_state.status,
+            ProjectStatus::Brainstorming
+                | ProjectStatus::Queued
+                | ProjectStatus::Running
+                | ProjectStatus::Pausing
+                | ProjectStatus::Paused
+        );
+        let due = project_state
+            .dependency_updates
+            .as_ref()
+            .is_some_and(|schedule| dependency_updates::is_due(schedule, now));
+        if busy || !due {
+            return Ok(false);
+        }
+    }
+    if project_state.remote.is_some() {
+        return Err("Dependency updates are not available for remote projects".to_string());
+    }
+    let task = project_state
+        .task
+        .as_ref()
+        .ok_or("No task configured for this project")?;
+    let dir = workdir::resolve_working_dir(
+        Path::new(&project_state.path),
+        task.working_subdir.as_deref(),
+    )?;
+
+    // Recorded first, so a failing check also waits for the next interval
+    if let Some(ref mut schedule) = project_state.dependency_updates {
+        schedule.last_run_at = Some(now);
+        project_state.updated_at = now;
+        storage::save_project_state(&project_state).map_err(|e| e.to_string())?;
+    }
+
+    let ecosystems = dependency_updates::detect(&dir);
+    if ecosystems.is_empty() {
+        return Err("No package.json or Cargo.toml to update".to_string());
+    }
+    let Some(report) = dependency_updates::outdated_report(&dir, &ecosystems).await? else {
+        return Ok(false);
+    };
+    let test_command = dependency_updates::test_command(&dir, &ecosystems);
+    let overrides = SessionOverrides {
+        prompt: Some(dependency_updates::update_prompt(
+            &report,
+            test_command.as_deref(),
+        )),
+        completion_check: test_command,
+        ..SessionOverrides::default()
+    };
+    schedule_session(
+        app_handle,
+        state,
+        uuid.to_string(),
+        overrides,
+        DEPENDENCY_UPDATE_PRIORITY,
+    )
+    .await?;
+    Ok(true)
+}
 
+/// Triage the repository's open GitHub issues in a readonly session whose
+/// report is kept as an artifact; returns its queue position, 0 when it started
+#[tauri::command]
+pub async fn run_issue_triage(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    project_id: String,
+) -> Result<usize, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    if project_state.remote.is_some() {
+        return Err("Issue triage is not available for remote projects".to_string());
+    }
     let task = project_state
         .task
-        .as_mut()
+        .as_ref()
         .ok_or("No task configured for this project")?;
+    let dir = workdir::resolve_working_dir(
+        Path::new(&project_state.path),
+        task.working_subdir.as_deref(),
+    )?;
+
+    let issues = issue_triage::open_issues(&dir).await?;
+    if issues.is_empty() {
+        return Err("The repository has no open issues".to_string());
+    }
+    let labels = issue_triage::repo_labels(&dir).await?;
+    // A report left by an earlier triage would be collected before the new one is written
+    let report = dir.join(issue_triage::REPORT_FILE);
+    if report.exists() {
+        std::fs::remove_file(&report).map_err(|e| e.to_string())?;
+    }
+    let overrides = SessionOverrides {
+        prompt: Some(issue_triage::triage_prompt(&issues, &labels)),
+        readonly: true,
+        artifacts: vec![issue_triage::REPORT_FILE.to_string()],
+        ..SessionOverrides::default()
+    };
+    schedule_session(app_handle, &state, project_id, overrides, 0).await
+}
 
-    let config = storage::load_config().map_err(|e| e.to_string())?;
-    let project_path = PathBuf::from(&project_state.path);
+/// What one session runs instead of the task as configured
+#[derive(Debug, Clone, Default)]
+struct SessionOverrides {
+    prompt: Option<String>,
+    completion_check: Option<String>,
+    /// Report-only session: auto-commit and the README are turned off
+    readonly: bool,
+    artifacts: Vec<String>,
+}
+
+/// Launch a session, or queue it when every slot is taken
+async fn start_session(
+    app_handle: AppHandle,
+    state: &AppState,
+    project_id: String,
+    overrides: SessionOverrides,
+) -> Result<(), String> {
+    schedule_session(app_handle, state, project_id, overrides, 0)
+        .await
+        .map(|_| ())
+}
+
+/// Reserve a slot and launch the session, or queue it; returns the queue position, 0 when launched
+async fn schedule_session(
+    app_handle: AppHandle,
+    state: &AppState,
+    project_id: String,
+    overrides: SessionOverrides,
+    priority: i32,
+) -> Result<usize, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    if state.running_benchmarks.read().await.contains_key(&uuid) {
+        return Err("Stop the project's benchmark before starting the loop".to_string());
+    }
+    let max_concurrent = storage::load_config()
+        .map_err(|e| e.to_string())?
+        .max_concurrent_projects;
+    {
+        // Held until queued, so a loop finishing meanwhile cannot miss this one
+        let mut queue = state.loop_queue.write().await;
+        if queue.contains(&uuid) {
+            return Err(LOOP_ALREADY_RUNNING.to_string());
+        }
+        if !reserve_loop(state, uuid, max_concurrent).await? {
+            let position = queue.push(QueuedLoop {
+                project_id: uuid,
+                priority,
+                prompt: overrides.prompt,
+                completion_check: overrides.completion_check,
+                readonly: overrides.readonly,
+                artifacts: overrides.artifacts,
+                queued_at: Utc::now(),
+            });
+            drop(queue);
+            mark_queued(&app_handle, &uuid, position);
+            return Ok(position);
+        }
+    }
+    let result = launch_loop(app_handle, state, project_id, uuid, overrides).await;
+    if result.is_err() {
+        state.running_loops.write().await.remove(&uuid);
+    }
+    result.map(|_| 0)
+}
 
-    let _prompt_updated = ensure_autodecide_prompt(task);
+fn mark_queued(app_handle: &AppHandle, project_id: &Uuid, position: usize) {
+    if let Ok(mut project_state) = storage::load_project_state(project_id) {
+        project_state.status = ProjectStatus::Queued;
+        project_state.updated_at = Utc::now();
+        let _ = storage::save_project_state(&project_state);
+    }
+    let _ = app_handle.emit(
+        "loop-event",
+        LoopEvent::Queued {
+            project_id: project_id.to_string(),
+            position,
+        },
+    );
+}
+
+/// Launch queued sessions while slots are free; boxed since finishing loops call it
+fn start_queued(app_handle: AppHandle, state: AppState) -> BoxFuture<'static, ()> {
+    Box::pin(async move {
+        loop {
+            let max_concurrent = storage::load_config()
+                .map(|config| config.max_concurrent_projects)
+                .unwrap_or_default();
+            let next = {
+                let mut queue = state.loop_queue.write().await;
+                let Some(project_id) = queue.peek().map(|next| next.project_id) else {
+                    return;
+                };
+                match reserve_loop(&state, project_id, max_concurrent).await {
+                    Ok(true) => queue.pop(),
+                    Ok(false) => return,
+                    // Started directly in the meantime
+                    Err(_) => {
+                        queue.pop();
+                        continue;
+                    }
+                }
+            };
+            let Some(next) = next else {
+                return;
+            };
+            let uuid = next.project_id;
+            let project_id = uuid.to_string();
+            let launched = launch_loop(
+                app_handle.clone(),
+                &state,
+                project_id.clone(),
+                uuid,
+                SessionOverrides {
+                    prompt: next.prompt,
+                    completion_check: next.completion_check,
+                    readonly: next.readonly,
+                    artifacts: next.artifacts,
+                },
+            )
+            .await;
+            if let Err(error) = launched {
+                state.running_loops.write().await.remove(&uuid);
+                if let Ok(mut project_state) = storage::load_project_state(&uuid) {
+                    project_state.status = ProjectStatus::Failed;
+                    if let Some(ref mut exec) = project_state.execution {
+                        exec.last_error = Some(error.clone());
+                    }
+                    project_state.updated_at = Utc::now();
+                    let _ = storage::save_project_state(&project_state);
+                }
+                let _ = app_handle.emit(
+                    "loop-event",
+                    LoopEvent::Error {
+                        project_id,
+                        iteration: 0,
+                        error,
+                    },
+                );
+            }
+        }
+    })
+}
+
+/// Claim the project's slot in `running_loops` before any IO, so a second start
+/// arriving while the first is still preparing fails with `LOOP_ALREADY_RUNNING`;
+/// `Ok(false)` when `max_concurrent` loops already run
+async fn reserve_loop(
+    state: &AppState,
+    project_id: Uuid,
+    max_concurrent: u32,
+) -> Result<bool, String> {
+    let mut loops = state.running_loops.write().await;
+    let running = loops.len();
+    match loops.entry(project_id) {
+        Entry::Occupied(_) => Err(LOOP_ALREADY_RUNNING.to_string()),
+        Entry::Vacant(_) if !scheduler::has_capacity(running, max_concurrent) => Ok(false),
+        Entry::Vacant(slot) => {
+            slot.insert(Arc::new(LoopEngineHandle {
+                pause_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                stop_flag: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                resume_notify: Arc::new(tokio::sync::Notify::new()),
+            }));
+            Ok(true)
+        }
+    }
+}
+
+/// Prepare the project and spawn its sessions; the caller holds the reservation
+async fn launch_loop(
+    app_handle: AppHandle,
+    state: &AppState,
+    project_id: String,
+    uuid: Uuid,
+    overrides: SessionOverrides,
+) -> Result<(), String> {
+    let mut project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+
+    let mut task = project_state
+        .task
+        .clone()
+        .ok_or("No task configured for this project")?;
+    if let Some(prompt) = overrides.prompt {
+        task.prompt = prompt;
+    }
+
+    let config = storage::load_workspace_config(project_state.workspace_id.as_ref())
+        .map_err(|e| e.to_string())?;
+    ensure_consented(&config, consent::loop_requirements(&config, &project_state))?;
+    let project_path = PathBuf::from(&project_state.path);
 
     let mut is_repo = is_git_repo(&project_path).await?;
     if task.auto_init_git && !is_repo {
@@ -37,49 +490,56 @@ pub async fn start_loop(
         project_state.skip_git_repo_check = true;
     }
 
-    if task.cli == CliType::Codex && !project_state.skip_git_repo_check {
-        if !is_repo {
-            return Err(CODEX_GIT_REPO_CHECK_REQUIRED.to_string());
-        }
+    if task.cli == CliType::Codex && !project_state.skip_git_repo_check && !is_repo {
+        return Err(CODEX_GIT_REPO_CHECK_REQUIRED.to_string());
     }
 
-    let iteration_timeout = if config.iteration_timeout_ms == 0 {
-        None
-    } else {
-        Some(Duration::from_millis(config.iteration_timeout_ms))
-    };
-    let idle_timeout = if config.idle_timeout_ms == 0 {
-        None
-    } else {
-        Some(Duration::from_millis(config.idle_timeout_ms))
-    };
+    let mut base = LoopConfig::for_task(&task, &config, &project_state, &project_path)?;
+    base.completion_check = overrides.completion_check;
+    base.artifacts.extend(overrides.artifacts);
+    if config.knowledge_base && !task.learning_ids.is_empty() {
+        base.learnings = storage::load_knowledge_entries()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|entry| task.learning_ids.contains(&entry.id))
+            .map(|entry| entry.text)
+            .collect();
+    }
+    if overrides.readonly {
+        base.auto_commit = false;
+        base.generate_readme = false;
+    }
+
+    // One session per monorepo target, or a single session for the whole project
+    let runs = plan_target_runs(&task, &project_path)?;
+    let mut configs = runs.into_iter().map(|run| {
+        let working_dir = (run.working_dir != project_path).then_some(run.working_dir);
+        (
+            run.target,
+            LoopConfig {
+                prompt: run.prompt,
+                working_dir,
+                ..base.clone()
+            },
+        )
+    });
+    let (target, mut loop_config) = configs.next().ok_or("No loop targets to run")?;
+    let remaining: Vec<_> = configs.collect();
+
+    let session_id = session_id_for(target.as_deref());
+    loop_config.session_id = session_id.clone();
+    if let Some(ref name) = target {
+        storage::save_session_target(&uuid, &session_id, name).map_err(|e| e.to_string())?;
+    }
 
     // Create loop engine
     let engine = LoopEngine::new(
         project_id.clone(),
-        project_path,
-        task.cli,
-        task.prompt.clone(),
-        task.max_iterations,
-        task.auto_commit,
-        task.completion_signal.clone(),
-        iteration_timeout,
-        idle_timeout,
-        project_state.skip_git_repo_check,
-        app_handle.clone(),
+        project_path.clone(),
+        loop_config,
+        Arc::new(WindowEvents(app_handle.clone())),
     );
-
-    // Store engine handle
-    let handle = Arc::new(LoopEngineHandle {
-        pause_flag: engine.get_pause_flag(),
-        stop_flag: engine.get_stop_flag(),
-        resume_notify: engine.get_resume_notify(),
-    });
-
-    {
-        let mut loops = state.running_loops.write().await;
-        loops.insert(uuid, handle);
-    }
+    register_engine(state, uuid, &engine).await;
 
     // Update project status
     project_state.status = ProjectStatus::Running;
@@ -91,26 +551,67 @@ pub async fn start_loop(
         last_output: String::new(),
         last_error: None,
         last_exit_code: None,
+        session_id: Some(session_id),
+        target,
     });
     project_state.updated_at = Utc::now();
     storage::save_project_state(&project_state).map_err(|e| e.to_string())?;
 
     // Spawn loop in background
-    let state_clone = state.inner().clone();
+    let state_clone = state.clone();
     tokio::spawn(async move {
-        let result = engine.start().await;
+        let mut result = engine.start().await;
+        let mut partial = matches!(result, Ok(LoopState::MaxIterationsReached { .. }));
+
+        // Later targets run only while earlier ones finish without failing or stopping
+        for (target, mut loop_config) in remaining {
+            if !matches!(
+                result,
+                Ok(LoopState::Completed { .. }) | Ok(LoopState::MaxIterationsReached { .. })
+            ) {
+                break;
+            }
+            let session_id = session_id_for(target.as_deref());
+            loop_config.session_id = session_id.clone();
+            if let Some(ref name) = target {
+                let _ = storage::save_session_target(&uuid, &session_id, name);
+            }
+            let engine = LoopEngine::new(
+                project_id.clone(),
+                project_path.clone(),
+                loop_config,
+                Arc::new(WindowEvents(app_handle.clone())),
+            );
+            register_engine(&state_clone, uuid, &engine).await;
+            if let Ok(mut project_state) = storage::load_project_state(&uuid) {
+                if let Some(ref mut exec) = project_state.execution {
+                    exec.current_iteration = 0;
+                    exec.session_id = Some(session_id);
+                    exec.target = target;
+                }
+                project_state.updated_at = Utc::now();
+                let _ = storage::save_project_state(&project_state);
+            }
+            result = engine.start().await;
+            partial |= matches!(result, Ok(LoopState::MaxIterationsReached { .. }));
+        }
 
         // Update project state based on result
         if let Ok(mut project_state) = storage::load_project_state(&uuid) {
             match result {
                 Ok(LoopState::Completed { iteration }) => {
-                    project_state.status = ProjectStatus::Done;
+                    project_state.status = if partial {
+                        ProjectStatus::Partial
+                    } else {
+                        ProjectStatus::Done
+                    };
                     if let Some(ref mut exec) = project_state.execution {
                         exec.completed_at = Some(Utc::now());
                         exec.current_iteration = iteration;
                     }
                 }
-                Ok(LoopState::MaxIterationsReached { iteration }) => {
+                Ok(LoopState::MaxIterationsReached { iteration })
+                | Ok(LoopState::TimeBudgetReached { iteration }) => {
                     project_state.status = ProjectStatus::Partial;
                     if let Some(ref mut exec) = project_state.execution {
                         exec.completed_at = Some(Utc::now());
@@ -123,6 +624,14 @@ pub async fn start_loop(
                         exec.current_iteration = iteration;
                     }
                 }
+                Ok(LoopState::PathMissing { iteration }) => {
+                    project_state.status = ProjectStatus::Failed;
+                    if let Some(ref mut exec) = project_state.execution {
+                        exec.current_iteration = iteration;
+                        exec.last_error =
+                            Some(format!("{}: {}", PROJECT_PATH_MISSING, project_state.path));
+                    }
+                }
                 Ok(LoopState::Idle) => {
                     project_state.status = ProjectStatus::Cancelled;
                 }
@@ -132,33 +641,32 @@ pub async fn start_loop(
             let _ = storage::save_project_state(&project_state);
         }
 
-        // Remove from running loops
-        let mut loops = state_clone.running_loops.write().await;
-        loops.remove(&uuid);
+        // Remove from running loops and hand the slot to the next queued one
+        state_clone.running_loops.write().await.remove(&uuid);
+        start_queued(app_handle, state_clone).await;
     });
 
     Ok(())
 }
 
-const AUTO_DECIDE_MARKER: &str = "[Ralph Auto-Decision Policy]";
-
-fn ensure_autodecide_prompt(task: &mut TaskConfig) -> bool {
-    if task.prompt.contains(AUTO_DECIDE_MARKER) {
-        return false;
+/// Expose an engine's control flags to pause/resume/stop for the project
+async fn register_engine(state: &AppState, project_id: Uuid, engine: &LoopEngine) {
+    let handle = Arc::new(LoopEngineHandle {
+        pause_flag: engine.get_pause_flag(),
+        stop_flag: engine.get_stop_flag(),
+        resume_notify: engine.get_resume_notify(),
+    });
+    let mut loops = state.running_loops.write().await;
+    // A stop requested while the loop was still being prepared carries over
+    if loops
+        .get(&project_id)
+        .is_some_and(|previous| previous.stop_flag.load(std::sync::atomic::Ordering::SeqCst))
+    {
+        handle
+            .stop_flag
+            .store(true, std::sync::atomic::Ordering::SeqCst);
     }
-
-    let policy = [
-        AUTO_DECIDE_MARKER,
-        "You MUST NOT ask the user any questions during execution.",
-        "Assume the user is away and cannot respond.",
-        "If multiple valid choices exist, prefer the more maintainable, clear, engineering-oriented option.",
-        "If required information is missing, make reasonable assumptions and proceed without blocking.",
-        "Never pause for clarification; log assumptions in the output when necessary.",
-    ]
-    .join("\n");
-
-    task.prompt = format!("{policy}\n\n{}", task.prompt.trim());
-    true
+    loops.insert(project_id, handle);
 }
 
 async fn init_git_repo(project_path: &PathBuf) -> Result<(), String> {
@@ -185,7 +693,8 @@ async fn is_git_repo(project_path: &PathBuf) -> Result<bool, String> {
         .arg("rev-parse")
         .arg("--is-inside-work-tree");
     hide_console_window(&mut cmd);
-    let output = cmd.output()
+    let output = cmd
+        .output()
         .await
         .map_err(|e| format!("Failed to run git: {}", e))?;
 
@@ -197,23 +706,46 @@ async fn is_git_repo(project_path: &PathBuf) -> Result<bool, String> {
     Ok(stdout.trim() == "true")
 }
 
+/// Estimate what the project's configured loop will cost, from the prompt size,
+/// the usage past sessions with the same CLI reported and the max iterations
+#[tauri::command]
+pub async fn estimate_loop_cost(project_id: String) -> Result<CostEstimate, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = project_state
+        .task
+        .ok_or("No task configured for this project")?;
+    let sessions: Vec<Vec<IterationUsage>> = storage::list_session_ids(&uuid)
+        .map_err(|e| e.to_string())?
+        .iter()
+        .filter_map(|session_id| storage::load_session_usage(&uuid, session_id).ok())
+        .collect();
+    let prompt_tokens = crate::engine::context::estimate_tokens(&task.prompt) as u64;
+    Ok(crate::engine::cost::estimate(
+        task.cli,
+        prompt_tokens,
+        task.max_iterations,
+        &sessions,
+    ))
+}
+
 /// Pause Ralph Loop
 #[tauri::command]
-pub async fn pause_loop(
-    state: State<'_, AppState>,
-    project_id: String,
-) -> Result<(), String> {
+pub async fn pause_loop(state: State<'_, AppState>, project_id: String) -> Result<(), String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
 
     let loops = state.running_loops.read().await;
     if let Some(handle) = loops.get(&uuid) {
-        handle.pause_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+        handle
+            .pause_flag
+            .store(true, std::sync::atomic::Ordering::SeqCst);
 
         // Update project status
-        let mut project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
-        project_state.status = ProjectStatus::Pausing;
-        project_state.updated_at = Utc::now();
-        storage::save_project_state(&project_state).map_err(|e| e.to_string())?;
+        storage::update_project_state(&uuid, |project_state| {
+            project_state.status = ProjectStatus::Pausing;
+            project_state.updated_at = Utc::now();
+        })
+        .map_err(|e| e.to_string())?;
 
         Ok(())
     } else {
@@ -223,10 +755,7 @@ pub async fn pause_loop(
 
 /// Resume Ralph Loop
 #[tauri::command]
-pub async fn resume_loop(
-    state: State<'_, AppState>,
-    project_id: String,
-) -> Result<(), String> {
+pub async fn resume_loop(state: State<'_, AppState>, project_id: String) -> Result<(), String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
 
     let loops = state.running_loops.read().await;
@@ -234,13 +763,14 @@ pub async fn resume_loop(
         handle.resume_notify.notify_one();
 
         // Update project status
-        let mut project_state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
-        project_state.status = ProjectStatus::Running;
-        if let Some(ref mut exec) = project_state.execution {
-            exec.paused_at = None;
-        }
-        project_state.updated_at = Utc::now();
-        storage::save_project_state(&project_state).map_err(|e| e.to_string())?;
+        storage::update_project_state(&uuid, |project_state| {
+            project_state.status = ProjectStatus::Running;
+            if let Some(ref mut exec) = project_state.execution {
+                exec.paused_at = None;
+            }
+            project_state.updated_at = Utc::now();
+        })
+        .map_err(|e| e.to_string())?;
 
         Ok(())
     } else {
@@ -257,24 +787,25 @@ pub async fn stop_loop(
 ) -> Result<(), String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
 
-    let mut found = false;
+    let mut found = state.loop_queue.write().await.remove(&uuid).is_some();
     {
         let loops = state.running_loops.read().await;
         if let Some(handle) = loops.get(&uuid) {
-            handle.stop_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+            handle
+                .stop_flag
+                .store(true, std::sync::atomic::Ordering::SeqCst);
             handle.resume_notify.notify_one(); // In case it's paused
             found = true;
         }
     }
 
-    if let Ok(mut project_state) = storage::load_project_state(&uuid) {
+    let _ = storage::update_project_state(&uuid, |project_state| {
         project_state.status = ProjectStatus::Cancelled;
         if let Some(ref mut exec) = project_state.execution {
             exec.completed_at = Some(Utc::now());
         }
         project_state.updated_at = Utc::now();
-        let _ = storage::save_project_state(&project_state);
-    }
+    });
 
     let _ = app_handle.emit(
         "loop-event",
@@ -290,6 +821,45 @@ pub async fn stop_loop(
     }
 }
 
+/// Stop every running loop, returning how many were stopped
+#[tauri::command]
+pub async fn stop_all_loops(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<u32, String> {
+    let project_ids: Vec<Uuid> = state.running_loops.read().await.keys().copied().collect();
+    let mut stopped = 0;
+    for project_id in project_ids {
+        if stop_loop(app_handle.clone(), state.clone(), project_id.to_string())
+            .await
+            .is_ok()
+        {
+            stopped += 1;
+        }
+    }
+    Ok(stopped)
+}
+
+/// Pause every loop that is currently running, returning how many were paused
+#[tauri::command]
+pub async fn pause_all_loops(state: State<'_, AppState>) -> Result<u32, String> {
+    let project_ids: Vec<Uuid> = state.running_loops.read().await.keys().copied().collect();
+    let mut paused = 0;
+    for project_id in project_ids {
+        let running = storage::load_project_state(&project_id)
+            .map(|s| s.status == ProjectStatus::Running)
+            .unwrap_or(false);
+        if running
+            && pause_loop(state.clone(), project_id.to_string())
+                .await
+                .is_ok()
+        {
+            paused += 1;
+        }
+    }
+    Ok(paused)
+}
+
 /// Get loop status for a project
 #[tauri::command]
 pub async fn get_loop_status(
@@ -300,3 +870,33 @@ pub async fn get_loop_status(
     let loops = state.running_loops.read().await;
     Ok(loops.contains_key(&uuid))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn second_start_is_rejected_while_reserved() {
+        let state = AppState::default();
+        let project_id = Uuid::new_v4();
+        assert_eq!(reserve_loop(&state, project_id, 0).await, Ok(true));
+        assert_eq!(
+            reserve_loop(&state, project_id, 0).await,
+            Err(LOOP_ALREADY_RUNNING.to_string())
+        );
+        assert_eq!(reserve_loop(&state, Uuid::new_v4(), 0).await, Ok(true));
+
+        state.running_loops.write().await.remove(&project_id);
+        assert_eq!(reserve_loop(&state, project_id, 0).await, Ok(true));
+    }
+
+    #[tokio::test]
+    async fn starts_beyond_the_limit_are_not_reserved() {
+        let state = AppState::default();
+        assert_eq!(reserve_loop(&state, Uuid::new_v4(), 2).await, Ok(true));
+        assert_eq!(reserve_loop(&state, Uuid::new_v4(), 2).await, Ok(true));
+        let third = Uuid::new_v4();
+        assert_eq!(reserve_loop(&state, third, 2).await, Ok(false));
+        assert!(!state.running_loops.read().await.contains_key(&third));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::adapters;
-use crate::engine::LoopState;
+use crate::engine::{EventSink, LoopEvent, LoopState, RawOutputEvent};
 use crate::storage;
 use crate::storage::models::*;
 use chrono::Utc;
@@ -9,16 +9,39 @@ use tauri::{AppHandle, State};
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+pub mod benchmark_commands;
+pub mod capture_commands;
+pub mod consent_commands;
+pub mod diagnostic_commands;
+pub mod knowledge_commands;
 pub mod loop_commands;
+pub mod notification_commands;
+pub mod observer_commands;
+pub mod plugin_commands;
 pub mod project_commands;
 pub mod recovery_commands;
+pub mod report_commands;
+pub mod session_commands;
+pub mod shortcut_commands;
+pub mod terminal_commands;
 pub mod update_commands;
+pub mod workspace_commands;
 
 /// Application state shared across commands
 #[derive(Clone)]
 pub struct AppState {
     pub running_loops: Arc<RwLock<HashMap<Uuid, Arc<LoopEngineHandle>>>>,
+    /// Engine of the CLI a project's benchmark is currently running
+    pub running_benchmarks: Arc<RwLock<HashMap<Uuid, Arc<LoopEngineHandle>>>>,
     pub update_state: Arc<RwLock<crate::auto_update::UpdateState>>,
+    /// Stops the observer feed when `true` is sent; `None` while it is not serving
+    pub observer_shutdown: Arc<RwLock<Option<tokio::sync::watch::Sender<bool>>>>,
+    /// Notifications held back during quiet hours
+    pub notification_digest: Arc<RwLock<Vec<QueuedNotification>>>,
+    /// Cleanup done at startup, until the UI has shown it
+    pub orphan_report: Arc<RwLock<Option<OrphanReport>>>,
+    /// Loops waiting for a slot; locked before `running_loops` when both are needed
+    pub loop_queue: Arc<RwLock<crate::engine::scheduler::LoopQueue>>,
 }
 
 pub struct LoopEngineHandle {
@@ -31,13 +54,51 @@ impl Default for AppState {
     fn default() -> Self {
         Self {
             running_loops: Arc::new(RwLock::new(HashMap::new())),
+            running_benchmarks: Arc::new(RwLock::new(HashMap::new())),
             update_state: Arc::new(RwLock::new(crate::auto_update::UpdateState::default())),
+            observer_shutdown: Arc::new(RwLock::new(None)),
+            notification_digest: Arc::new(RwLock::new(Vec::new())),
+            orphan_report: Arc::new(RwLock::new(None)),
+            loop_queue: Arc::new(RwLock::new(Default::default())),
         }
     }
 }
 
+/// Forwards loop events to the windows on the channels the UI listens to
+pub struct WindowEvents(pub AppHandle);
+
+impl EventSink for WindowEvents {
+    fn loop_event(&self, event: &LoopEvent, isolated: bool) {
+        use tauri::Emitter;
+        let channel = if isolated {
+            "benchmark-loop-event"
+        } else {
+            "loop-event"
+        };
+        let _ = self.0.emit(channel, event);
+    }
+
+    fn raw_output(&self, event: &RawOutputEvent) {
+        use tauri::Emitter;
+        let _ = self.0.emit("loop-event-raw", event);
+    }
+}
+
 // Re-export commands
+pub use benchmark_commands::*;
+pub use capture_commands::*;
+pub use consent_commands::*;
+pub use diagnostic_commands::*;
+pub use knowledge_commands::*;
 pub use loop_commands::*;
+pub use notification_commands::*;
+pub use observer_commands::*;
+pub use plugin_commands::*;
 pub use project_commands::*;
 pub use recovery_commands::*;
+pub use report_commands::*;
+pub use session_commands::*;
+pub use shortcut_commands::*;
+pub use terminal_commands::*;
 pub use update_commands::*;
+pub use workspace_commands::*;
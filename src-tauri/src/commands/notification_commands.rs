@@ -0,0 +1,47 @@
+use super::*;
+use crate::notifications;
+
+/// Save the quiet hours window and which severities may break through it
+#[tauri::command]
+pub async fn update_quiet_hours(quiet_hours: QuietHours) -> Result<GlobalConfig, String> {
+    notifications::validate(&quiet_hours)?;
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    config.quiet_hours = quiet_hours;
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// Whether a notification should be shown now. During quiet hours (the project's
+/// workspace's, when it has its own) it is queued instead and delivered with the
+/// `notification-digest` event when they end.
+#[tauri::command]
+pub async fn route_notification(
+    state: State<'_, AppState>,
+    severity: NotificationSeverity,
+    title: String,
+    message: Option<String>,
+    project_id: Option<String>,
+) -> Result<bool, String> {
+    let workspace_id = project_id
+        .and_then(|id| Uuid::parse_str(&id).ok())
+        .and_then(|id| storage::load_project_state(&id).ok())
+        .and_then(|project| project.workspace_id);
+    let quiet_hours = storage::load_workspace_config(workspace_id.as_ref())
+        .map_err(|e| e.to_string())?
+        .quiet_hours;
+    if notifications::delivers_now(&quiet_hours, severity) {
+        return Ok(true);
+    }
+    state
+        .notification_digest
+        .write()
+        .await
+        .push(QueuedNotification {
+            severity,
+            title,
+            message,
+            queued_at: Utc::now(),
+            workspace_id,
+        });
+    Ok(false)
+}
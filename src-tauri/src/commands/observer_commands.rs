@@ -0,0 +1,83 @@
+use super::*;
+use crate::observer;
+use crate::security::consent;
+
+/// Start or stop the observer feed to match the saved config
+pub async fn sync_observer(state: &AppState) -> Result<ObserverStatus, String> {
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let mut shutdown = state.observer_shutdown.write().await;
+    if let Some(previous) = shutdown.take() {
+        let _ = previous.send(true);
+    }
+    if config.observer_enabled {
+        if let Some(ref token) = config.observer_token {
+            *shutdown = Some(observer::start(config.observer_port, token.clone()).await?);
+        }
+    }
+    Ok(ObserverStatus {
+        enabled: config.observer_enabled,
+        running: shutdown.is_some(),
+        port: config.observer_port,
+        token: config.observer_token,
+    })
+}
+
+/// Get the observer feed's settings and whether it is serving
+#[tauri::command]
+pub async fn get_observer_status(state: State<'_, AppState>) -> Result<ObserverStatus, String> {
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let running = state.observer_shutdown.read().await.is_some();
+    Ok(ObserverStatus {
+        enabled: config.observer_enabled,
+        running,
+        port: config.observer_port,
+        token: config.observer_token,
+    })
+}
+
+/// Enable or disable the read-only observer feed
+#[tauri::command]
+pub async fn set_observer_enabled(
+    state: State<'_, AppState>,
+    enabled: bool,
+) -> Result<ObserverStatus, String> {
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    if enabled {
+        ensure_consented(&config, vec![consent::observer_feed()])?;
+    }
+    config.observer_enabled = enabled;
+    if enabled && config.observer_token.is_none() {
+        config.observer_token = Some(observer::generate_token());
+    }
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    sync_observer(&state).await
+}
+
+/// Replace the observer token, disconnecting existing observers
+#[tauri::command]
+pub async fn regenerate_observer_token(
+    state: State<'_, AppState>,
+) -> Result<ObserverStatus, String> {
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    config.observer_token = Some(observer::generate_token());
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    sync_observer(&state).await
+}
+
+/// Fetch project summaries from another instance's observer feed
+#[tauri::command]
+pub async fn observe_remote_projects(
+    url: String,
+    token: String,
+) -> Result<Vec<ObservedProject>, String> {
+    let endpoint = format!("{}/api/projects", url.trim_end_matches('/'));
+    let response = reqwest::Client::new()
+        .get(endpoint)
+        .bearer_auth(token)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?
+        .error_for_status()
+        .map_err(|e| e.to_string())?;
+    response.json().await.map_err(|e| e.to_string())
+}
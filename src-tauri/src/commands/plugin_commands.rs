@@ -0,0 +1,34 @@
+use super::*;
+use crate::engine::plugins;
+
+/// Plugins installed in the plugins directory, with whether each is enabled
+#[tauri::command]
+pub async fn list_plugins() -> Result<Vec<PluginInfo>, String> {
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    plugins::discover(&config.enabled_plugins)
+}
+
+/// Enable or disable a plugin for loops started from now on
+#[tauri::command]
+pub async fn set_plugin_enabled(
+    plugin_id: String,
+    enabled: bool,
+) -> Result<Vec<PluginInfo>, String> {
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    let plugin = plugins::discover(&config.enabled_plugins)?
+        .into_iter()
+        .find(|p| p.id == plugin_id)
+        .ok_or_else(|| format!("Plugin not found: {}", plugin_id))?;
+    if enabled {
+        if let Some(err) = plugin.error {
+            return Err(err);
+        }
+        if !config.enabled_plugins.contains(&plugin_id) {
+            config.enabled_plugins.push(plugin_id);
+        }
+    } else {
+        config.enabled_plugins.retain(|id| *id != plugin_id);
+    }
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    plugins::discover(&config.enabled_plugins)
+}
@@ -1,9 +1,27 @@
 use super::*;
 use crate::adapters::hide_console_window;
 use crate::engine::ai_brainstorm::{
-    generate_project_title, run_ai_brainstorm, truncate_to_title, AiBrainstormResponse,
-    ConversationMessage,
+    generate_project_title, run_ai_brainstorm, truncate_to_title, validate_brainstorm_prompt,
+    AiBrainstormResponse, ConversationMessage, VoiceBrainstormResponse, BRAINSTORM_SYSTEM_PROMPT,
 };
+use crate::engine::artifacts;
+use crate::engine::attachments;
+use crate::engine::authorship;
+use crate::engine::auto_answer::validate_answers;
+use crate::engine::commit_hygiene;
+use crate::engine::completion::Signals;
+use crate::engine::context::{build_repo_context, estimate_tokens};
+use crate::engine::dependency_updates;
+use crate::engine::env_profiles::{is_valid_env_name, validate_profiles};
+use crate::engine::filters::validate_patterns;
+use crate::engine::highlights::validate_rules;
+use crate::engine::personas;
+use crate::engine::prompt::{lint_prompt, sanitize_input};
+use crate::engine::resources::validate_memory_limit;
+use crate::engine::targets::plan_target_runs;
+use crate::engine::transcribe::transcribe_audio;
+use crate::engine::workdir;
+use crate::engine::PROJECT_PATH_MISSING;
 use crate::security;
 use std::path::PathBuf;
 use tokio::process::Command;
@@ -11,149 +29,864 @@ use tokio::process::Command;
 /// List all projects with synced status
 #[tauri::command]
 pub async fn list_projects() -> Result<Vec<ProjectMeta>, String> {
-    let mut index = storage::load_project_index().map_err(|e| e.to_string())?;
+    let index = storage::load_project_index().map_err(|e| e.to_string())?;
+    if index.statuses_cached {
+        return Ok(index.projects);
+    }
+
+    // Statuses are cached in the index; indexes from before that are synced once
+    storage::update_project_index(|index| {
+        for meta in &mut index.projects {
+            if let Ok(state) = storage::load_project_state(&meta.id) {
+                meta.status = state.status;
+                meta.workspace_id = state.workspace_id;
+            }
+        }
+        index.statuses_cached = true;
+        index.projects.clone()
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Re-read a project's status from its state into the index, for when the
+/// cached one is suspected to be stale
+#[tauri::command]
+pub async fn refresh_project_status(project_id: String) -> Result<ProjectMeta, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    storage::update_project_index(|index| {
+        let meta = index.projects.iter_mut().find(|meta| meta.id == uuid)?;
+        meta.status = state.status;
+        meta.workspace_id = state.workspace_id;
+        Some(meta.clone())
+    })
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| "Project not found".to_string())
+}
+
+/// Create a new project
+#[tauri::command]
+pub async fn create_project(path: String, name: String) -> Result<ProjectState, String> {
+    let now = Utc::now();
+    let id = Uuid::new_v4();
+
+    // Create project meta
+    let meta = ProjectMeta {
+        id,
+        name: name.clone(),
+        path: path.clone(),
+        status: ProjectStatus::Brainstorming,
+        created_at: now,
+        last_opened_at: now,
+        workspace_id: None,
+    };
+
+    // Add to index
+    storage::update_project_index(|index| index.projects.push(meta)).map_err(|e| e.to_string())?;
+
+    // Create project state
+    let state = ProjectState {
+        id,
+        name,
+        path,
+        status: ProjectStatus::Brainstorming,
+        skip_git_repo_check: false,
+        screenshot: None,
+        dependency_updates: None,
+        remote: None,
+        container: None,
+        output_filters: Vec::new(),
+        debug_raw_output: false,
+        network_blocked: false,
+        workspace_id: None,
+        brainstorm: Some(BrainstormState {
+            answers: vec![],
+            completed_at: None,
+            persona: None,
+            draft_prompt: None,
+        }),
+        task: None,
+        execution: None,
+        created_at: now,
+        updated_at: now,
+    };
+
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+
+    Ok(state)
+}
+
+/// Get a project by ID
+#[tauri::command]
+pub async fn get_project(id: String) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    storage::load_project_state(&uuid).map_err(|e| e.to_string())
+}
+
+/// State, active session usage, recent sessions and last completion summary of a project
+#[tauri::command]
+pub async fn get_project_bundle(project_id: String) -> Result<ProjectBundle, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    storage::load_project_bundle(&uuid).map_err(|e| e.to_string())
+}
+
+/// Set whether to skip git repo check for a project
+#[tauri::command]
+pub async fn set_project_skip_git_repo_check(
+    project_id: String,
+    skip: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.skip_git_repo_check = skip;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Enable or disable raw CLI output capture for a project
+#[tauri::command]
+pub async fn set_project_debug_raw_output(
+    project_id: String,
+    enabled: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.debug_raw_output = enabled;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Block or allow network use by the agent's tools for a project
+#[tauri::command]
+pub async fn set_project_network_blocked(
+    project_id: String,
+    blocked: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.network_blocked = blocked;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Set the regexes muting loop output for a project
+#[tauri::command]
+pub async fn set_project_output_filters(
+    project_id: String,
+    filters: Vec<String>,
+) -> Result<ProjectState, String> {
+    validate_patterns(&filters)?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.output_filters = filters;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Run a project's loops inside a container, or on the host when `container` is `None`
+#[tauri::command]
+pub async fn set_project_container(
+    project_id: String,
+    container: Option<ContainerConfig>,
+) -> Result<ProjectState, String> {
+    if container
+        .as_ref()
+        .is_some_and(|c| c.image.trim().is_empty())
+    {
+        return Err("Container image cannot be empty".to_string());
+    }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.container = container;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Run a project's loops on an SSH host, or locally when `remote` is `None`
+#[tauri::command]
+pub async fn set_project_remote(
+    project_id: String,
+    remote: Option<RemoteTarget>,
+) -> Result<ProjectState, String> {
+    if let Some(target) = &remote {
+        crate::engine::remote::validate_target(target)?;
+    }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.remote = remote;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Screenshot a project's running web app after each iteration, or stop when `None`
+#[tauri::command]
+pub async fn set_project_screenshot(
+    project_id: String,
+    screenshot: Option<ScreenshotConfig>,
+) -> Result<ProjectState, String> {
+    if let Some(config) = &screenshot {
+        let url = config.url.trim();
+        if !url.starts_with("http://") && !url.starts_with("https://") {
+            return Err("Screenshot URL must start with http:// or https://".to_string());
+        }
+        if config.width == 0 || config.height == 0 {
+            return Err("Screenshot size must be greater than zero".to_string());
+        }
+    }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.screenshot = screenshot;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update max iterations for a project's task
+#[tauri::command]
+pub async fn update_task_max_iterations(
+    project_id: String,
+    max_iterations: u32,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.max_iterations = max_iterations;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update auto-commit setting for a project's task
+#[tauri::command]
+pub async fn update_task_auto_commit(
+    project_id: String,
+    auto_commit: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.auto_commit = auto_commit;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Turn writing `.ralph/status.json` after each session on or off for a project's task
+#[tauri::command]
+pub async fn update_task_write_status_file(
+    project_id: String,
+    write_status_file: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.write_status_file = write_status_file;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Turn README generation after a completed loop on or off for a project's task
+#[tauri::command]
+pub async fn update_task_generate_readme(
+    project_id: String,
+    generate_readme: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.generate_readme = generate_readme;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Run a project's iterations in a terminal the app can attach to
+#[tauri::command]
+pub async fn update_task_interactive_terminal(
+    project_id: String,
+    interactive_terminal: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.interactive_terminal = interactive_terminal;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update auto-init git setting for a project's task
+#[tauri::command]
+pub async fn update_task_auto_init(
+    project_id: String,
+    auto_init_git: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.auto_init_git = auto_init_git;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
 
-    // Sync status from each project's state
-    for meta in &mut index.projects {
-        if let Ok(state) = storage::load_project_state(&meta.id) {
-            meta.status = state.status;
+/// Run a project's task CLI from a specific executable; `None` or an empty
+/// path goes back to the one found on PATH
+#[tauri::command]
+pub async fn update_task_cli_path_override(
+    project_id: String,
+    path: Option<String>,
+) -> Result<ProjectState, String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(ref path) = path {
+        if !std::path::Path::new(path).is_file() {
+            return Err(format!("CLI executable not found: {}", path));
         }
     }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.cli_path_override = path;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
 
-    Ok(index.projects)
+/// Select the environment profile applied to a project's task CLI; `None` clears it
+#[tauri::command]
+pub async fn update_task_env_profile(
+    project_id: String,
+    profile: Option<String>,
+) -> Result<ProjectState, String> {
+    let profile = profile
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    if let Some(ref name) = profile {
+        let config = storage::load_workspace_config(state.workspace_id.as_ref())
+            .map_err(|e| e.to_string())?;
+        if !config.env_profiles.iter().any(|p| p.name.trim() == name) {
+            return Err(format!("Environment profile not found: {}", name));
+        }
+    }
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.env_profile = profile;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update auto-decision policy injection for a project's task
+#[tauri::command]
+pub async fn update_task_auto_decide(
+    project_id: String,
+    auto_decide: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.auto_decide = auto_decide;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update warm-up context setting for a project's task
+#[tauri::command]
+pub async fn update_task_warmup_context(
+    project_id: String,
+    warmup_context: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.warmup_context = warmup_context;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update repository context injection for a project's task
+#[tauri::command]
+pub async fn update_task_repo_context(
+    project_id: String,
+    repo_context: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.repo_context = repo_context;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update prompt content for a project's task
+#[tauri::command]
+pub async fn update_task_prompt(
+    project_id: String,
+    prompt: String,
+) -> Result<ProjectState, String> {
+    let prompt = clean_prompt_input(&prompt)?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.prompt = prompt;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Sanitize prompt text from the UI against the configured size limit
+pub(crate) fn clean_prompt_input(text: &str) -> Result<String, String> {
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    sanitize_input(text, config.max_prompt_bytes as usize).map_err(|e| e.to_string())
+}
+
+/// Update per-iteration prompt templates for a project's task
+#[tauri::command]
+pub async fn update_task_iteration_prompts(
+    project_id: String,
+    first_iteration_prompt: Option<String>,
+    subsequent_iteration_prompt: Option<String>,
+) -> Result<ProjectState, String> {
+    let first_iteration_prompt = first_iteration_prompt
+        .map(|p| clean_prompt_input(&p))
+        .transpose()?;
+    let subsequent_iteration_prompt = subsequent_iteration_prompt
+        .map(|p| clean_prompt_input(&p))
+        .transpose()?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.first_iteration_prompt = first_iteration_prompt.filter(|p| !p.trim().is_empty());
+    task.subsequent_iteration_prompt = subsequent_iteration_prompt.filter(|p| !p.trim().is_empty());
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update the project-relative directory the loop runs in
+#[tauri::command]
+pub async fn update_task_working_subdir(
+    project_id: String,
+    working_subdir: Option<String>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let project_path = PathBuf::from(&state.path);
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    let working_subdir = working_subdir
+        .as_deref()
+        .map(workdir::normalize_subdir)
+        .transpose()?
+        .flatten();
+    workdir::resolve_working_dir(&project_path, working_subdir.as_deref())?;
+    task.working_subdir = working_subdir;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Replace the monorepo targets a project's task runs as sequential sessions
+#[tauri::command]
+pub async fn update_task_targets(
+    project_id: String,
+    targets: Vec<LoopTarget>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let project_path = PathBuf::from(&state.path);
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.targets = targets;
+    plan_target_runs(task, &project_path)?;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Replace the globs of files collected into the session after each iteration
+#[tauri::command]
+pub async fn update_task_artifacts(
+    project_id: String,
+    artifacts: Vec<String>,
+) -> Result<ProjectState, String> {
+    artifacts::validate_patterns(&artifacts)?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.artifacts = artifacts.iter().map(|p| p.trim().to_string()).collect();
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Set the scheduling priority the task's CLI runs at
+#[tauri::command]
+pub async fn update_task_priority(
+    project_id: String,
+    priority: ProcessPriority,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.priority = priority;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Set the memory limit of the task's CLI, or remove it with `None`
+#[tauri::command]
+pub async fn update_task_memory_limit(
+    project_id: String,
+    memory_limit_mb: Option<u64>,
+) -> Result<ProjectState, String> {
+    validate_memory_limit(memory_limit_mb)?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.memory_limit_mb = memory_limit_mb;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Update the texts, or with `is_regex` the patterns, that mark the task complete;
+/// `mode` decides whether any of them is enough or all are required
+#[tauri::command]
+pub async fn update_task_completion_signal(
+    project_id: String,
+    completion_signal: String,
+    extra_completion_signals: Vec<String>,
+    is_regex: bool,
+    mode: CompletionMode,
+) -> Result<ProjectState, String> {
+    let extra_completion_signals: Vec<String> = extra_completion_signals
+        .into_iter()
+        .filter(|signal| !signal.trim().is_empty())
+        .collect();
+    let all: Vec<String> = std::iter::once(completion_signal.clone())
+        .chain(extra_completion_signals.iter().cloned())
+        .collect();
+    Signals::new(&all, is_regex, mode)?;
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.completion_signal = completion_signal;
+    task.completion_signal_regex = is_regex;
+    task.extra_completion_signals = extra_completion_signals;
+    task.completion_mode = mode;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Choose when the task's loops count as done besides the completion signal
+#[tauri::command]
+pub async fn update_task_completion_strategy(
+    project_id: String,
+    strategy: CompletionStrategy,
+) -> Result<ProjectState, String> {
+    if strategy == (CompletionStrategy::NoOpStreak { iterations: 0 }) {
+        return Err("A no-op streak needs at least one iteration".to_string());
+    }
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.completion_strategy = strategy;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Set the command validating every iteration, or remove it with `None`
+#[tauri::command]
+pub async fn update_task_validation_command(
+    project_id: String,
+    command: Option<String>,
+) -> Result<ProjectState, String> {
+    let command = command
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.validation_command = command;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
 }
 
-/// Create a new project
+/// Turn summarizing each iteration into the next prompt on or off
 #[tauri::command]
-pub async fn create_project(path: String, name: String) -> Result<ProjectState, String> {
-    let now = Utc::now();
-    let id = Uuid::new_v4();
-
-    // Create project meta
-    let meta = ProjectMeta {
-        id,
-        name: name.clone(),
-        path: path.clone(),
-        status: ProjectStatus::Brainstorming,
-        created_at: now,
-        last_opened_at: now,
-    };
-
-    // Add to index
-    let mut index = storage::load_project_index().map_err(|e| e.to_string())?;
-    index.projects.push(meta);
-    storage::save_project_index(&index).map_err(|e| e.to_string())?;
-
-    // Create project state
-    let state = ProjectState {
-        id,
-        name,
-        path,
-        status: ProjectStatus::Brainstorming,
-        skip_git_repo_check: false,
-        brainstorm: Some(BrainstormState {
-            answers: vec![],
-            completed_at: None,
-        }),
-        task: None,
-        execution: None,
-        created_at: now,
-        updated_at: now,
-    };
-
+pub async fn update_task_carry_over_progress(
+    project_id: String,
+    enabled: bool,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.carry_over_progress = enabled;
+    state.updated_at = Utc::now();
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
 
+/// Set the CLI the task is compared with by `run_cli_comparison`, or clear it
+#[tauri::command]
+pub async fn update_task_compare_cli(
+    project_id: String,
+    cli: Option<CliType>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    if cli == Some(task.cli) {
+        return Err("Choose a CLI other than the task's own".to_string());
+    }
+    task.compare_cli = cli;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
     Ok(state)
 }
 
-/// Get a project by ID
+/// Turn reviewing each auto-commit's diff on or off; `rubric` replaces the
+/// general rubric, and a blank one restores it
 #[tauri::command]
-pub async fn get_project(id: String) -> Result<ProjectState, String> {
-    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
-    storage::load_project_state(&uuid).map_err(|e| e.to_string())
+pub async fn update_task_review_gate(
+    project_id: String,
+    enabled: bool,
+    rubric: Option<String>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.review_gate = enabled;
+    task.review_rubric = rubric
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
 }
 
-/// Set whether to skip git repo check for a project
+/// Review the loop every `interval` iterations and let the supervisor revise
+/// its prompt, or turn the supervisor off with `None`
 #[tauri::command]
-pub async fn set_project_skip_git_repo_check(
+pub async fn update_task_supervisor(
     project_id: String,
-    skip: bool,
+    interval: Option<u32>,
 ) -> Result<ProjectState, String> {
+    if interval == Some(0) {
+        return Err("Supervisor interval must be at least 1".to_string());
+    }
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
-    state.skip_git_repo_check = skip;
+    let task = state
+        .task
+        .as_mut()
+        .ok_or("No task configured for this project")?;
+    task.supervisor_interval = interval;
     state.updated_at = Utc::now();
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
     Ok(state)
 }
 
-/// Update max iterations for a project's task
+/// Set the script run before every iteration, or remove it with `None`;
+/// `abort_on_failure` skips the iteration when the script fails
 #[tauri::command]
-pub async fn update_task_max_iterations(
+pub async fn update_task_pre_iteration_hook(
     project_id: String,
-    max_iterations: u32,
+    command: Option<String>,
+    abort_on_failure: bool,
 ) -> Result<ProjectState, String> {
+    let command = command
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
     let task = state
         .task
         .as_mut()
         .ok_or("No task configured for this project")?;
-    task.max_iterations = max_iterations;
+    task.pre_iteration_command = command;
+    task.pre_iteration_abort_on_failure = abort_on_failure;
     state.updated_at = Utc::now();
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
     Ok(state)
 }
 
-/// Update auto-commit setting for a project's task
+/// Set the wall-clock budget of the task's runs in minutes, or remove it with `None`
 #[tauri::command]
-pub async fn update_task_auto_commit(
+pub async fn update_task_max_run_duration(
     project_id: String,
-    auto_commit: bool,
+    max_run_duration_mins: Option<u32>,
 ) -> Result<ProjectState, String> {
+    if max_run_duration_mins == Some(0) {
+        return Err("Time budget must be at least one minute".to_string());
+    }
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
     let task = state
         .task
         .as_mut()
         .ok_or("No task configured for this project")?;
-    task.auto_commit = auto_commit;
+    task.max_run_duration_mins = max_run_duration_mins;
     state.updated_at = Utc::now();
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
     Ok(state)
 }
 
-/// Update auto-init git setting for a project's task
+/// Turn the project's scheduled dependency-update loop on or off; a new schedule
+/// checks at the next opportunity and then every `interval_days`
 #[tauri::command]
-pub async fn update_task_auto_init(
+pub async fn set_dependency_updates(
     project_id: String,
-    auto_init_git: bool,
+    enabled: bool,
+    interval_days: Option<u32>,
 ) -> Result<ProjectState, String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    if !enabled {
+        state.dependency_updates = None;
+    } else {
+        if state.task.is_none() {
+            return Err("No task configured for this project".to_string());
+        }
+        if state.remote.is_some() {
+            return Err("Dependency updates are not available for remote projects".to_string());
+        }
+        let interval_days = interval_days.unwrap_or(dependency_updates::DEFAULT_INTERVAL_DAYS);
+        if interval_days == 0 {
+            return Err("Interval must be at least one day".to_string());
+        }
+        let last_run_at = state
+            .dependency_updates
+            .as_ref()
+            .and_then(|s| s.last_run_at);
+        state.dependency_updates = Some(DependencyUpdateSchedule {
+            interval_days,
+            last_run_at,
+        });
+    }
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Copy an image into the project's attachments and reference it from the task
+#[tauri::command]
+pub async fn add_task_attachment(project_id: String, path: String) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let project_path = PathBuf::from(&state.path);
     let task = state
         .task
         .as_mut()
         .ok_or("No task configured for this project")?;
-    task.auto_init_git = auto_init_git;
+    let name = attachments::copy_attachment(&project_path, &PathBuf::from(path))?;
+    task.attachments.push(name);
     state.updated_at = Utc::now();
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
     Ok(state)
 }
 
-/// Update prompt content for a project's task
+/// Remove an attachment from the task and delete its stored copy
 #[tauri::command]
-pub async fn update_task_prompt(
+pub async fn remove_task_attachment(
     project_id: String,
-    prompt: String,
+    name: String,
 ) -> Result<ProjectState, String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let project_path = PathBuf::from(&state.path);
     let task = state
         .task
         .as_mut()
         .ok_or("No task configured for this project")?;
-    task.prompt = prompt;
+    attachments::remove_attachment(&project_path, &name)?;
+    task.attachments.retain(|a| a != &name);
     state.updated_at = Utc::now();
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
     Ok(state)
@@ -210,9 +943,8 @@ pub async fn delete_project(id: String) -> Result<(), String> {
     let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
 
     // Remove from index
-    let mut index = storage::load_project_index().map_err(|e| e.to_string())?;
-    index.projects.retain(|p| p.id != uuid);
-    storage::save_project_index(&index).map_err(|e| e.to_string())?;
+    storage::update_project_index(|index| index.projects.retain(|p| p.id != uuid))
+        .map_err(|e| e.to_string())?;
 
     // Delete project data
     storage::delete_project_data(&uuid).map_err(|e| e.to_string())?;
@@ -235,16 +967,181 @@ pub async fn get_config() -> Result<GlobalConfig, String> {
 /// Save global config
 #[tauri::command]
 pub async fn save_config(config: GlobalConfig) -> Result<(), String> {
+    validate_patterns(&config.output_filters)?;
+    validate_rules(&config.highlight_rules)?;
+    validate_answers(&config.prompt_answers)?;
+    validate_profiles(&config.env_profiles)?;
+    if let Some(key) = config.env.keys().find(|k| !is_valid_env_name(k)) {
+        return Err(format!("Invalid environment variable name '{}'", key));
+    }
+    commit_hygiene::validate(&config.commit_hygiene)?;
+    authorship::validate(&config.commit_identity)?;
+    crate::notifications::validate(&config.quiet_hours)?;
     storage::save_config(&config).map_err(|e| e.to_string())
 }
 
+/// Turn encryption at rest on or off, converting already stored states and
+/// finished logs; returns how many files were rewritten
+#[tauri::command]
+pub async fn set_encryption_at_rest(enabled: bool) -> Result<usize, String> {
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    // Convert first so a keychain failure leaves the setting unchanged
+    let converted = storage::convert_stored_data(enabled).map_err(|e| e.to_string())?;
+    config.encrypt_at_rest = enabled;
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    Ok(converted)
+}
+
 /// Confirm permissions
 #[tauri::command]
 pub async fn confirm_permissions() -> Result<(), String> {
     let mut config = storage::load_config().map_err(|e| e.to_string())?;
     config.permissions_confirmed = true;
     config.permissions_confirmed_at = Some(Utc::now());
-    storage::save_config(&config).map_err(|e| e.to_string())
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+
+    let mut grants = storage::load_consents().map_err(|e| e.to_string())?;
+    crate::security::consent::grant(
+        &mut grants,
+        PendingConsent {
+            capability: Capability::FullDiskAccess,
+            project_id: None,
+            scope: None,
+        },
+        Utc::now(),
+    );
+    storage::save_consents(&grants).map_err(|e| e.to_string())
+}
+
+/// Get the brainstorm system prompt template (saved or built-in)
+#[tauri::command]
+pub async fn get_brainstorm_prompt() -> Result<String, String> {
+    let saved = storage::load_brainstorm_prompt().map_err(|e| e.to_string())?;
+    Ok(saved.unwrap_or_else(|| BRAINSTORM_SYSTEM_PROMPT.to_string()))
+}
+
+/// Save a custom brainstorm system prompt template
+#[tauri::command]
+pub async fn set_brainstorm_prompt(template: String) -> Result<(), String> {
+    validate_brainstorm_prompt(&template)?;
+    storage::save_brainstorm_prompt(&template).map_err(|e| e.to_string())
+}
+
+/// Drop the custom brainstorm prompt and return the built-in one
+#[tauri::command]
+pub async fn reset_brainstorm_prompt() -> Result<String, String> {
+    storage::delete_brainstorm_prompt().map_err(|e| e.to_string())?;
+    Ok(BRAINSTORM_SYSTEM_PROMPT.to_string())
+}
+
+/// List built-in and user-defined brainstorm personas
+#[tauri::command]
+pub async fn list_brainstorm_personas() -> Result<Vec<BrainstormPersona>, String> {
+    Ok(personas::list_personas())
+}
+
+/// Create or update a user-defined brainstorm persona
+#[tauri::command]
+pub async fn save_brainstorm_persona(
+    persona: BrainstormPersona,
+) -> Result<Vec<BrainstormPersona>, String> {
+    if persona.id.trim().is_empty() || persona.name.trim().is_empty() {
+        return Err("Persona id and name are required".to_string());
+    }
+    if personas::builtin_personas()
+        .iter()
+        .any(|p| p.id == persona.id)
+    {
+        return Err(format!("Cannot overwrite built-in persona: {}", persona.id));
+    }
+    let mut custom = storage::load_custom_personas().map_err(|e| e.to_string())?;
+    let persona = BrainstormPersona {
+        built_in: false,
+        ..persona
+    };
+    match custom.iter_mut().find(|p| p.id == persona.id) {
+        Some(existing) => *existing = persona,
+        None => custom.push(persona),
+    }
+    storage::save_custom_personas(&custom).map_err(|e| e.to_string())?;
+    Ok(personas::list_personas())
+}
+
+/// Delete a user-defined brainstorm persona
+#[tauri::command]
+pub async fn delete_brainstorm_persona(id: String) -> Result<Vec<BrainstormPersona>, String> {
+    let mut custom = storage::load_custom_personas().map_err(|e| e.to_string())?;
+    custom.retain(|p| p.id != id);
+    storage::save_custom_personas(&custom).map_err(|e| e.to_string())?;
+    Ok(personas::list_personas())
+}
+
+/// Select the persona used for a project's brainstorm conversation
+#[tauri::command]
+pub async fn set_brainstorm_persona(
+    project_id: String,
+    persona: Option<String>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    if let Some(id) = &persona {
+        if !personas::list_personas().iter().any(|p| &p.id == id) {
+            return Err(format!("Unknown persona: {}", id));
+        }
+    }
+    let brainstorm = state.brainstorm.get_or_insert_with(|| BrainstormState {
+        answers: vec![],
+        completed_at: None,
+        persona: None,
+        draft_prompt: None,
+    });
+    brainstorm.persona = persona;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+/// Re-link a project to its directory after it moved or its drive was remounted
+#[tauri::command]
+pub async fn update_project_path(
+    state: State<'_, AppState>,
+    project_id: String,
+    path: String,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    if state.running_loops.read().await.contains_key(&uuid) {
+        return Err("Stop the loop before changing the project directory".to_string());
+    }
+    relink_project(&uuid, path)
+}
+
+/// Point the project's index entry and state at `path`
+pub(crate) fn relink_project(uuid: &Uuid, path: String) -> Result<ProjectState, String> {
+    if !std::path::Path::new(&path).is_dir() {
+        return Err(format!("Directory not found: {}", path));
+    }
+
+    storage::update_project_index(|index| {
+        if let Some(meta) = index.projects.iter_mut().find(|p| p.id == *uuid) {
+            meta.path = path.clone();
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    let mut project_state = storage::load_project_state(uuid).map_err(|e| e.to_string())?;
+    project_state.path = path;
+    if let Some(ref mut exec) = project_state.execution {
+        if exec
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with(PROJECT_PATH_MISSING))
+        {
+            exec.last_error = None;
+        }
+    }
+    project_state.updated_at = Utc::now();
+    storage::save_project_state(&project_state).map_err(|e| e.to_string())?;
+    Ok(project_state)
 }
 
 /// Update project status
@@ -268,21 +1165,115 @@ pub async fn update_project_status(
 #[tauri::command]
 pub async fn ai_brainstorm_chat(
     project_id: String,
-    conversation: Vec<ConversationMessage>,
+    mut conversation: Vec<ConversationMessage>,
 ) -> Result<AiBrainstormResponse, String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
-    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let config =
+        storage::load_workspace_config(state.workspace_id.as_ref()).map_err(|e| e.to_string())?;
+    for message in conversation.iter_mut().filter(|m| m.role == "user") {
+        message.content = sanitize_input(&message.content, config.max_prompt_bytes as usize)
+            .map_err(|e| e.to_string())?;
+    }
 
     let working_dir = PathBuf::from(&state.path);
-    run_ai_brainstorm(
+    let repo_context = if config.brainstorm_repo_context {
+        let query: Vec<&str> = conversation
+            .iter()
+            .filter(|m| m.role == "user")
+            .map(|m| m.content.as_str())
+            .collect();
+        Some(
+            build_repo_context(
+                &working_dir,
+                &query.join("\n"),
+                config.context_token_budget as usize,
+            )
+            .await,
+        )
+    } else {
+        None
+    };
+    let persona =
+        personas::persona_prompt(state.brainstorm.as_ref().and_then(|b| b.persona.as_deref()));
+    let response = run_ai_brainstorm(
         &working_dir,
         &conversation,
         config.default_cli,
         state.skip_git_repo_check,
+        repo_context.as_deref(),
+        Some(config.agent_language.as_str()),
+        persona.as_deref(),
     )
     .await
-    .map_err(|e| security::sanitize_log(&e))
+    .map_err(|e| security::sanitize_log(&e))?;
+
+    // Keep the generated prompt as a draft until the user confirms it
+    if let (true, Some(draft)) = (response.is_complete, &response.generated_prompt) {
+        let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+        if let Some(ref mut brainstorm) = state.brainstorm {
+            brainstorm.draft_prompt = Some(draft.clone());
+            state.updated_at = Utc::now();
+            storage::save_project_state(&state).map_err(|e| e.to_string())?;
+        }
+    }
+
+    Ok(response)
+}
+
+/// Transcribe a voice note and use it as the first brainstorm message
+#[tauri::command]
+pub async fn transcribe_and_brainstorm(
+    project_id: String,
+    audio_path: String,
+) -> Result<VoiceBrainstormResponse, String> {
+    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let transcript = transcribe_audio(
+        &PathBuf::from(&audio_path),
+        config.transcription_command.as_deref(),
+    )
+    .await?;
+    let conversation = vec![ConversationMessage {
+        role: "user".to_string(),
+        content: transcript.clone(),
+    }];
+    let response = ai_brainstorm_chat(project_id, conversation).await?;
+    Ok(VoiceBrainstormResponse {
+        transcript,
+        response,
+    })
+}
+
+/// Store an (optionally edited) draft prompt and report lint warnings and size
+#[tauri::command]
+pub async fn preview_generated_prompt(
+    project_id: String,
+    edited_prompt: Option<String>,
+) -> Result<PromptPreview, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let brainstorm = state
+        .brainstorm
+        .as_mut()
+        .ok_or("No brainstorm for this project")?;
+
+    if let Some(edited) = edited_prompt {
+        brainstorm.draft_prompt = Some(clean_prompt_input(&edited)?);
+        state.updated_at = Utc::now();
+        storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    }
+    let prompt = state
+        .brainstorm
+        .as_ref()
+        .and_then(|b| b.draft_prompt.clone())
+        .ok_or("No generated prompt to preview")?;
+
+    let completion_signal = TaskConfig::default().completion_signal;
+    Ok(PromptPreview {
+        token_estimate: estimate_tokens(&prompt) as u32,
+        warnings: lint_prompt(&prompt, &completion_signal),
+        prompt,
+    })
 }
 
 /// Complete AI brainstorming with the generated prompt
@@ -293,15 +1284,39 @@ pub async fn complete_ai_brainstorm(
     cli: CliType,
     max_iterations: u32,
 ) -> Result<ProjectState, String> {
+    let generated_prompt = clean_prompt_input(&generated_prompt)?;
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
 
-    // Update brainstorm state
-    if let Some(ref mut brainstorm) = state.brainstorm {
-        brainstorm.completed_at = Some(Utc::now());
+    // Only a previewed draft can be finalized
+    let brainstorm = state
+        .brainstorm
+        .as_mut()
+        .ok_or("No brainstorm for this project")?;
+    if brainstorm.draft_prompt.is_none() {
+        return Err("Preview the generated prompt before completing the brainstorm".to_string());
     }
+    if generated_prompt.trim().is_empty() {
+        return Err("Confirmed prompt cannot be empty".to_string());
+    }
+    brainstorm.draft_prompt = None;
+    brainstorm.completed_at = Some(Utc::now());
 
-    // Set task config with generated prompt
+    // Set task config with generated prompt, keeping attachments, the working
+    // directory, monorepo targets, artifact globs and priority from an earlier task
+    let (attachments, working_subdir, targets, artifacts, priority) = state
+        .task
+        .as_ref()
+        .map(|t| {
+            (
+                t.attachments.clone(),
+                t.working_subdir.clone(),
+                t.targets.clone(),
+                t.artifacts.clone(),
+                t.priority,
+            )
+        })
+        .unwrap_or_default();
     state.task = Some(TaskConfig {
         prompt: generated_prompt,
         design_doc_path: None,
@@ -309,7 +1324,12 @@ pub async fn complete_ai_brainstorm(
         max_iterations,
         auto_commit: true,
         auto_init_git: true,
-        completion_signal: "<done>COMPLETE</done>".to_string(),
+        attachments,
+        working_subdir,
+        targets,
+        artifacts,
+        priority,
+        ..TaskConfig::default()
     });
 
     state.status = ProjectStatus::Ready;
@@ -330,7 +1350,8 @@ pub async fn generate_project_title_cmd(
 ) -> Result<String, String> {
     let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
     let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
-    let config = storage::load_config().map_err(|e| e.to_string())?;
+    let config =
+        storage::load_workspace_config(state.workspace_id.as_ref()).map_err(|e| e.to_string())?;
 
     let working_dir = PathBuf::from(&state.path);
 
@@ -353,11 +1374,12 @@ pub async fn generate_project_title_cmd(
     storage::save_project_state(&state).map_err(|e| e.to_string())?;
 
     // Persist: update project index entry
-    let mut index = storage::load_project_index().map_err(|e| e.to_string())?;
-    if let Some(meta) = index.projects.iter_mut().find(|p| p.id == uuid) {
-        meta.name = title.clone();
-    }
-    storage::save_project_index(&index).map_err(|e| e.to_string())?;
+    storage::update_project_index(|index| {
+        if let Some(meta) = index.projects.iter_mut().find(|p| p.id == uuid) {
+            meta.name = title.clone();
+        }
+    })
+    .map_err(|e| e.to_string())?;
 
     Ok(title)
 }
@@ -400,9 +1422,157 @@ mod tests {
         }
     }
 
+    #[cfg(unix)]
+    fn write_executable(path: &std::path::Path, contents: &str) {
+        std::fs::write(path, contents).unwrap();
+        let mut perms = std::fs::metadata(path).unwrap().permissions();
+        std::os::unix::fs::PermissionsExt::set_mode(&mut perms, 0o755);
+        std::fs::set_permissions(path, perms).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn brainstorm_uses_configured_cli() {
+        let _env_lock = crate::test_support::lock_env_async().await;
+        let temp_dir = tempdir().unwrap();
+        let home_dir = temp_dir.path();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir);
+        let _shell_guard = EnvVarGuard::set("SHELL", "/bin/bash");
+
+        let bin_dir = home_dir.join("bin");
+        std::fs::create_dir_all(&bin_dir).unwrap();
+
+        let codex_script = r#"#!/usr/bin/env bash
+echo '{"question":"Hi","description":null,"options":[],"multiSelect":false,"allowOther":false,"isComplete":false}'
+"#;
+        write_executable(&bin_dir.join("codex"), codex_script);
+
+        let claude_script = r#"#!/usr/bin/env bash
+echo 'not-json'
+"#;
+        write_executable(&bin_dir.join("claude"), claude_script);
+
+        let current_path = env::var_os("PATH").unwrap_or_default();
+        let new_path = format!("{}:{}", bin_dir.display(), current_path.to_string_lossy());
+        let _path_guard = EnvVarGuard::set("PATH", new_path);
+
+        let project_path = home_dir.join("project");
+        std::fs::create_dir_all(&project_path).unwrap();
+
+        let mut config = GlobalConfig::default();
+        config.default_cli = CliType::Codex;
+        storage::save_config(&config).unwrap();
+
+        let now = Utc::now();
+        let project_id = Uuid::new_v4();
+        let project_state = ProjectState {
+            id: project_id,
+            name: "Test".to_string(),
+            path: project_path.to_string_lossy().to_string(),
+            status: ProjectStatus::Brainstorming,
+            skip_git_repo_check: false,
+            screenshot: None,
+            dependency_updates: None,
+            remote: None,
+            container: None,
+            output_filters: Vec::new(),
+            debug_raw_output: false,
+            network_blocked: false,
+            workspace_id: None,
+            brainstorm: Some(BrainstormState {
+                answers: vec![],
+                completed_at: None,
+                persona: None,
+                draft_prompt: None,
+            }),
+            task: None,
+            execution: None,
+            created_at: now,
+            updated_at: now,
+        };
+        storage::save_project_state(&project_state).unwrap();
+
+        let response = ai_brainstorm_chat(
+            project_id.to_string(),
+            vec![ConversationMessage {
+                role: "user".to_string(),
+                content: "Test".to_string(),
+            }],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(response.question, "Hi");
+    }
+
+    #[tokio::test]
+    async fn complete_ai_brainstorm_requires_previewed_draft() {
+        let _env_lock = crate::test_support::lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let now = Utc::now();
+        let id = Uuid::new_v4();
+        let project_dir = tempdir().unwrap();
+        let state = ProjectState {
+            id,
+            name: "Test".to_string(),
+            path: project_dir.path().to_string_lossy().to_string(),
+            status: ProjectStatus::Brainstorming,
+            skip_git_repo_check: false,
+            screenshot: None,
+            dependency_updates: None,
+            remote: None,
+            container: None,
+            output_filters: Vec::new(),
+            debug_raw_output: false,
+            network_blocked: false,
+            workspace_id: None,
+            brainstorm: Some(BrainstormState {
+                answers: vec![],
+                completed_at: None,
+                persona: None,
+                draft_prompt: None,
+            }),
+            task: None,
+            execution: None,
+            created_at: now,
+            updated_at: now,
+        };
+        storage::save_project_state(&state).unwrap();
+
+        let err = complete_ai_brainstorm(id.to_string(), "Build it".into(), CliType::Codex, 3)
+            .await
+            .unwrap_err();
+        assert!(err.contains("Preview"));
+        assert!(preview_generated_prompt(id.to_string(), None)
+            .await
+            .is_err());
+
+        let preview = preview_generated_prompt(id.to_string(), Some("Build it".into()))
+            .await
+            .expect("preview draft");
+        assert_eq!(preview.prompt, "Build it");
+        assert!(preview.token_estimate > 0);
+        assert!(!preview.warnings.is_empty());
+
+        let completed =
+            complete_ai_brainstorm(id.to_string(), "Build it well".into(), CliType::Codex, 3)
+                .await
+                .expect("complete brainstorm");
+        assert_eq!(completed.status, ProjectStatus::Ready);
+        assert_eq!(
+            completed.task.as_ref().map(|t| t.prompt.as_str()),
+            Some("Build it well")
+        );
+        assert!(completed.brainstorm.unwrap().draft_prompt.is_none());
+
+        let _ = storage::delete_project_data(&id);
+    }
+
     #[tokio::test]
     async fn update_task_prompt_persists_prompt() {
-        let _env_lock = crate::test_support::lock_env();
+        let _env_lock = crate::test_support::lock_env_async().await;
         let home_dir = tempdir().unwrap();
         let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
 
@@ -417,6 +1587,14 @@ mod tests {
             path: project_dir.path().to_string_lossy().to_string(),
             status: ProjectStatus::Ready,
             skip_git_repo_check: false,
+            screenshot: None,
+            dependency_updates: None,
+            remote: None,
+            container: None,
+            output_filters: Vec::new(),
+            debug_raw_output: false,
+            network_blocked: false,
+            workspace_id: None,
             brainstorm: None,
             task: Some(TaskConfig {
                 prompt: initial_prompt,
@@ -425,7 +1603,7 @@ mod tests {
                 max_iterations: 3,
                 auto_commit: false,
                 auto_init_git: false,
-                completion_signal: "<done>COMPLETE</done>".to_string(),
+                ..TaskConfig::default()
             }),
             execution: None,
             created_at: now,
@@ -455,7 +1633,7 @@ mod tests {
 
     #[tokio::test]
     async fn generate_project_title_cmd_fallback_when_cli_missing() {
-        let _env_lock = crate::test_support::lock_env();
+        let _env_lock = crate::test_support::lock_env_async().await;
         // Point HOME to a temp dir so storage is isolated
         let home_dir = tempdir().unwrap();
         let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
@@ -477,6 +1655,14 @@ mod tests {
             path: project_dir.path().to_string_lossy().to_string(),
             status: ProjectStatus::Brainstorming,
             skip_git_repo_check: true,
+            screenshot: None,
+            dependency_updates: None,
+            remote: None,
+            container: None,
+            output_filters: Vec::new(),
+            debug_raw_output: false,
+            network_blocked: false,
+            workspace_id: None,
             brainstorm: None,
             task: None,
             execution: None,
@@ -494,7 +1680,9 @@ mod tests {
                 status: ProjectStatus::Brainstorming,
                 created_at: now,
                 last_opened_at: now,
+                workspace_id: None,
             }],
+            statuses_cached: true,
         };
         storage::save_project_index(&meta).unwrap();
 
@@ -504,7 +1692,11 @@ mod tests {
             .expect("should not error even when AI fails");
 
         // Fallback title must be ≤ 16 chars (15 + ellipsis) and not the original path
-        assert!(title.chars().count() <= 16, "fallback title too long: {}", title);
+        assert!(
+            title.chars().count() <= 16,
+            "fallback title too long: {}",
+            title
+        );
         assert!(
             !title.contains('/') && !title.contains('\\'),
             "fallback title must not be a path: {}",
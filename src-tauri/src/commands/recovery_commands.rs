@@ -1,6 +1,12 @@
-use crate::engine::logs::cleanup_all_logs;
-use crate::storage::{self, models::ProjectStatus};
+use super::AppState;
+use crate::engine::logs::{cleanup_all_logs, LogManager};
+use crate::engine::processes;
+use crate::storage::{
+    self,
+    models::{OrphanReport, ProcessRecord, ProjectStatus},
+};
 use serde::{Deserialize, Serialize};
+use tauri::State;
 use uuid::Uuid;
 
 /// Recovery action for interrupted tasks
@@ -22,7 +28,8 @@ pub async fn check_interrupted_tasks() -> Result<Vec<RecoveryInfo>, String> {
     for project_meta in &index.projects {
         if let Ok(state) = storage::load_project_state(&project_meta.id) {
             match state.status {
-                ProjectStatus::Running | ProjectStatus::Pausing => {
+                // The queue is not kept across restarts
+                ProjectStatus::Running | ProjectStatus::Pausing | ProjectStatus::Queued => {
                     let iteration = state
                         .execution
                         .as_ref()
@@ -64,3 +71,38 @@ pub async fn cleanup_logs() -> Result<u32, String> {
     let config = storage::load_config().map_err(|e| e.to_string())?;
     cleanup_all_logs(config.log_retention_days)
 }
+
+/// Get the stable log path that follows the running session (for `tail -f`)
+#[tauri::command]
+pub async fn get_current_log_path(project_id: String) -> Result<String, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let path = LogManager::current_log_path(&uuid)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Find CLI processes left running by earlier Ralph sessions
+#[tauri::command]
+pub async fn scan_orphan_processes() -> Result<Vec<ProcessRecord>, String> {
+    Ok(processes::scan_orphans().await)
+}
+
+/// Cleanup of orphaned processes done at startup; returned once so it is shown once
+#[tauri::command]
+pub async fn take_orphan_report(
+    state: State<'_, AppState>,
+) -> Result<Option<OrphanReport>, String> {
+    Ok(state.orphan_report.write().await.take())
+}
+
+/// Terminate orphaned CLI processes; PIDs not found by a fresh scan are ignored
+#[tauri::command]
+pub async fn terminate_orphan_processes(pids: Vec<u32>) -> Result<u32, String> {
+    let mut terminated = 0;
+    for record in processes::scan_orphans().await {
+        if pids.contains(&record.pid) {
+            processes::terminate(&record).await?;
+            terminated += 1;
+        }
+    }
+    Ok(terminated)
+}
@@ -0,0 +1,53 @@
+use super::*;
+use crate::engine::{health, report};
+
+/// Render the local report for a `YYYY-MM` month (the current one by default)
+/// from the recorded sessions and save it next to the app data
+#[tauri::command]
+pub async fn generate_usage_report(month: Option<String>) -> Result<UsageReport, String> {
+    let month = month.unwrap_or_else(report::current_month);
+    let first = report::parse_month(&month)?;
+    let sessions = storage::load_session_metrics().map_err(|e| e.to_string())?;
+    let project_names: HashMap<Uuid, String> = storage::load_project_index()
+        .map_err(|e| e.to_string())?
+        .projects
+        .into_iter()
+        .map(|p| (p.id, p.name))
+        .collect();
+    let markdown = report::render(first, &sessions, &project_names);
+    let month = first.format("%Y-%m").to_string();
+    let path = storage::save_usage_report(&month, &markdown).map_err(|e| e.to_string())?;
+    Ok(UsageReport {
+        month,
+        markdown,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// Opt in or out of recording finished sessions for the report
+#[tauri::command]
+pub async fn set_local_analytics(enabled: bool) -> Result<GlobalConfig, String> {
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    config.local_analytics = enabled;
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+    Ok(config)
+}
+
+/// Score the project's recent sessions and suggest changes to its task
+#[tauri::command]
+pub async fn get_project_health(project_id: String) -> Result<ProjectHealth, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let mut sessions = Vec::new();
+    for session_id in storage::list_session_ids(&uuid).map_err(|e| e.to_string())? {
+        let records =
+            storage::load_session_iterations(&uuid, &session_id).map_err(|e| e.to_string())?;
+        if !records.is_empty() {
+            sessions.push(records);
+        }
+        if sessions.len() == health::RECENT_SESSIONS {
+            break;
+        }
+    }
+    Ok(health::assess(state.task.as_ref(), &sessions))
+}
@@ -0,0 +1,470 @@
+use super::*;
+use crate::engine::logs::{render_transcript, LogManager};
+use crate::engine::{authorship, changelog, postmortem, snapshot, time_travel};
+use std::path::Path;
+
+/// Resolve a session id, defaulting to the project's latest session
+fn resolve_session_id(uuid: &Uuid, session_id: Option<String>) -> Result<Option<String>, String> {
+    if let Some(id) = session_id {
+        return Ok(Some(id));
+    }
+    let ids = storage::list_session_ids(uuid).map_err(|e| e.to_string())?;
+    Ok(ids.into_iter().next())
+}
+
+/// Commit a session's changes end at: where the next session started, or HEAD
+/// for the latest session
+fn session_end_commit(uuid: &Uuid, session_id: &str) -> Result<String, String> {
+    let ids = storage::list_session_ids(uuid).map_err(|e| e.to_string())?;
+    let next_session = ids
+        .iter()
+        .position(|id| id == session_id)
+        .and_then(|i| i.checked_sub(1))
+        .map(|i| ids[i].clone());
+    let end = match next_session {
+        Some(next) => storage::load_session_snapshot(uuid, &next)
+            .map_err(|e| e.to_string())?
+            .and_then(|s| s.git_head),
+        None => None,
+    };
+    Ok(end.unwrap_or_else(|| "HEAD".to_string()))
+}
+
+/// List loop sessions for a project with their notes, newest first
+#[tauri::command]
+pub async fn list_sessions(project_id: String) -> Result<Vec<SessionSummary>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let ids = storage::list_session_ids(&uuid).map_err(|e| e.to_string())?;
+    ids.into_iter()
+        .map(|id| {
+            let notes = storage::load_session_notes(&uuid, &id).map_err(|e| e.to_string())?;
+            let pins = storage::load_session_pins(&uuid, &id).map_err(|e| e.to_string())?;
+            let target = storage::load_session_target(&uuid, &id).map_err(|e| e.to_string())?;
+            Ok(SessionSummary {
+                id,
+                notes,
+                pins,
+                target,
+            })
+        })
+        .collect()
+}
+
+/// Pin or unpin a whole session
+#[tauri::command]
+pub async fn pin_session(
+    project_id: String,
+    session_id: String,
+    pinned: bool,
+) -> Result<SessionPins, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut pins = storage::load_session_pins(&uuid, &session_id).map_err(|e| e.to_string())?;
+    pins.pinned = pinned;
+    storage::save_session_pins(&uuid, &session_id, &pins).map_err(|e| e.to_string())?;
+    Ok(pins)
+}
+
+/// Pin or unpin one iteration of a session
+#[tauri::command]
+pub async fn pin_iteration(
+    project_id: String,
+    session_id: String,
+    iteration: u32,
+    pinned: bool,
+) -> Result<SessionPins, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let mut pins = storage::load_session_pins(&uuid, &session_id).map_err(|e| e.to_string())?;
+    pins.iterations.retain(|i| *i != iteration);
+    if pinned {
+        pins.iterations.push(iteration);
+        pins.iterations.sort_unstable();
+    }
+    storage::save_session_pins(&uuid, &session_id, &pins).map_err(|e| e.to_string())?;
+    Ok(pins)
+}
+
+/// List pinned sessions and iterations, for one project or all of them
+#[tauri::command]
+pub async fn list_pinned(project_id: Option<String>) -> Result<Vec<PinnedSession>, String> {
+    let project_ids = match project_id {
+        Some(id) => vec![Uuid::parse_str(&id).map_err(|e| e.to_string())?],
+        None => storage::load_project_index()
+            .map_err(|e| e.to_string())?
+            .projects
+            .into_iter()
+            .map(|p| p.id)
+            .collect(),
+    };
+    let mut pinned = Vec::new();
+    for project_id in project_ids {
+        for session_id in storage::list_session_ids(&project_id).map_err(|e| e.to_string())? {
+            let pins =
+                storage::load_session_pins(&project_id, &session_id).map_err(|e| e.to_string())?;
+            if pins.is_pinned() {
+                pinned.push(PinnedSession {
+                    project_id,
+                    session_id,
+                    pins,
+                });
+            }
+        }
+    }
+    Ok(pinned)
+}
+
+/// Attach a note to a session, optionally to one iteration of it
+#[tauri::command]
+pub async fn add_session_note(
+    project_id: String,
+    session_id: String,
+    iteration: Option<u32>,
+    text: String,
+) -> Result<Vec<SessionNote>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    if text.trim().is_empty() {
+        return Err("Note text cannot be empty".to_string());
+    }
+    let mut notes = storage::load_session_notes(&uuid, &session_id).map_err(|e| e.to_string())?;
+    notes.push(SessionNote {
+        iteration,
+        text: text.trim().to_string(),
+        created_at: Utc::now(),
+    });
+    storage::save_session_notes(&uuid, &session_id, &notes).map_err(|e| e.to_string())?;
+    Ok(notes)
+}
+
+/// Export a session's log with its notes to a file
+#[tauri::command]
+pub async fn export_session_transcript(
+    project_id: String,
+    session_id: String,
+    destination: String,
+) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let log_path = LogManager::session_log_path(&uuid, &session_id)?;
+    // A session may have notes but no log; a log that can't be read is an error
+    let log = if log_path.exists() {
+        storage::crypto::read_text(&log_path).map_err(|e| e.to_string())?
+    } else {
+        String::new()
+    };
+    let log_lines: Vec<String> = log.lines().map(|l| l.to_string()).collect();
+    let notes = storage::load_session_notes(&uuid, &session_id).map_err(|e| e.to_string())?;
+    if log_lines.is_empty() && notes.is_empty() {
+        return Err(format!("No log or notes for session {}", session_id));
+    }
+    let mut transcript = storage::load_session_snapshot(&uuid, &session_id)
+        .map_err(|e| e.to_string())?
+        .map(|snapshot| snapshot::render(&snapshot))
+        .unwrap_or_default();
+    transcript.push_str(&render_transcript(&log_lines, &notes));
+    std::fs::write(destination, transcript).map_err(|e| e.to_string())
+}
+
+/// Get files most frequently modified by the agent during a session
+#[tauri::command]
+pub async fn get_change_hotspots(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<FileHotspot>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(Vec::new());
+    };
+    storage::load_session_hotspots(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// Output lines hidden by filters during a session (latest session if omitted)
+#[tauri::command]
+pub async fn get_suppressed_counts(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<SuppressedCount>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(Vec::new());
+    };
+    storage::load_suppressed_counts(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// Files collected after each iteration of a session (latest session if omitted),
+/// optionally limited to one iteration
+#[tauri::command]
+pub async fn list_iteration_artifacts(
+    project_id: String,
+    session_id: Option<String>,
+    iteration: Option<u32>,
+) -> Result<Vec<IterationArtifact>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(Vec::new());
+    };
+    let artifacts =
+        storage::load_session_artifacts(&uuid, &session_id).map_err(|e| e.to_string())?;
+    Ok(artifacts
+        .into_iter()
+        .filter(|a| iteration.is_none_or(|i| a.iteration == i))
+        .collect())
+}
+
+/// Structured payload a session completed with (latest session if omitted);
+/// `None` when the agent finished with the plain signal or did not finish
+#[tauri::command]
+pub async fn get_session_completion(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Option<CompletionPayload>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(None);
+    };
+    storage::load_session_completion(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// Versions, commit, prompt hash and settings a session started with (latest session if omitted)
+#[tauri::command]
+pub async fn get_session_snapshot(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Option<SessionSnapshot>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(None);
+    };
+    storage::load_session_snapshot(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// Start, end, exit code, commits, tokens and outcome of each iteration of a
+/// session (latest session if omitted)
+#[tauri::command]
+pub async fn list_session_iterations(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<IterationRecord>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(Vec::new());
+    };
+    storage::load_session_iterations(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// The supervisor's revisions of a session's prompt (latest session if omitted),
+/// oldest first
+#[tauri::command]
+pub async fn list_prompt_revisions(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<PromptRevision>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(Vec::new());
+    };
+    storage::load_prompt_revisions(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// Highlighted output lines of a session (latest session if omitted), in output order
+#[tauri::command]
+pub async fn list_highlights(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<SessionHighlight>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(Vec::new());
+    };
+    storage::load_session_highlights(&uuid, &session_id).map_err(|e| e.to_string())
+}
+
+/// Add a CHANGELOG entry for a session from its commits and completion
+/// summary, optionally committing it
+#[tauri::command]
+pub async fn generate_session_changelog(
+    project_id: String,
+    session_id: String,
+    commit: bool,
+) -> Result<SessionChangelog, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let snapshot = storage::load_session_snapshot(&uuid, &session_id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Session {} did not record where it started", session_id))?;
+    let end = session_end_commit(&uuid, &session_id)?;
+
+    let project_path = Path::new(&state.path);
+    let subjects =
+        changelog::commit_subjects(project_path, snapshot.git_head.as_deref(), &end).await?;
+    let summary = storage::load_session_completion(&uuid, &session_id)
+        .map_err(|e| e.to_string())?
+        .and_then(|c| c.summary);
+    if subjects.is_empty() && summary.is_none() {
+        return Err(format!(
+            "Session {} made no commits and left no summary",
+            session_id
+        ));
+    }
+    let entry = changelog::render_entry(
+        snapshot.captured_at.date_naive(),
+        summary.as_deref(),
+        &subjects,
+    );
+
+    let path = project_path.join(changelog::CHANGELOG_FILE);
+    let existing = std::fs::read_to_string(&path).ok();
+    std::fs::write(&path, changelog::insert_entry(existing.as_deref(), &entry))
+        .map_err(|e| format!("Failed to write {}: {}", changelog::CHANGELOG_FILE, e))?;
+
+    let commit = if commit {
+        let config = storage::load_workspace_config(state.workspace_id.as_ref())
+            .map_err(|e| e.to_string())?;
+        let args = authorship::commit_args(
+            &config.commit_identity,
+            snapshot.cli,
+            adapters::get_adapter(snapshot.cli).name(),
+            "ralph: update CHANGELOG",
+        );
+        Some(changelog::commit(project_path, args).await?)
+    } else {
+        None
+    };
+    Ok(SessionChangelog {
+        entry,
+        path: path.to_string_lossy().to_string(),
+        commit,
+    })
+}
+
+/// Have a readonly CLI call explain why a failed or exhausted session did not
+/// complete and suggest prompt and settings changes; saved with the session
+#[tauri::command]
+pub async fn generate_postmortem(
+    project_id: String,
+    session_id: String,
+) -> Result<SessionPostmortem, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    let log_path = LogManager::session_log_path(&uuid, &session_id)?;
+    let log = storage::crypto::read_text(&log_path)
+        .map_err(|_| format!("No log for session {}", session_id))?;
+    let status = postmortem::session_status(&log);
+    postmortem::check_status(status)?;
+    let status = status.unwrap_or_default().to_string();
+
+    let session_snapshot =
+        storage::load_session_snapshot(&uuid, &session_id).map_err(|e| e.to_string())?;
+    let project_path = Path::new(&state.path);
+    let diff = match &session_snapshot {
+        Some(snapshot) => {
+            let end = session_end_commit(&uuid, &session_id)?;
+            postmortem::session_diff(project_path, snapshot.git_head.as_deref(), &end).await
+        }
+        None => String::new(),
+    };
+    let settings = session_snapshot
+        .as_ref()
+        .map(snapshot::render)
+        .unwrap_or_default();
+    let task = state.task.as_ref();
+    let cli = session_snapshot
+        .as_ref()
+        .map(|s| s.cli)
+        .or(task.map(|t| t.cli))
+        .ok_or("No task configured for this project")?;
+    let prompt = postmortem::postmortem_prompt(
+        task.map(|t| t.prompt.as_str()).unwrap_or_default(),
+        &status,
+        &settings,
+        &log,
+        &diff,
+    );
+    let markdown =
+        postmortem::generate(cli, project_path, &prompt, state.skip_git_repo_check).await?;
+    let path = storage::save_session_postmortem(&uuid, &session_id, &markdown)
+        .map_err(|e| e.to_string())?;
+    Ok(SessionPostmortem {
+        session_id,
+        status,
+        markdown,
+        path: path.to_string_lossy().to_string(),
+    })
+}
+
+/// The postmortem generated for a session (latest session if omitted); `None`
+/// before one was generated
+#[tauri::command]
+pub async fn get_session_postmortem(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Option<SessionPostmortem>, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let Some(session_id) = resolve_session_id(&uuid, session_id)? else {
+        return Ok(None);
+    };
+    let Some((markdown, path)) =
+        storage::load_session_postmortem(&uuid, &session_id).map_err(|e| e.to_string())?
+    else {
+        return Ok(None);
+    };
+    let log_path = LogManager::session_log_path(&uuid, &session_id)?;
+    let log = storage::crypto::read_text(&log_path).unwrap_or_default();
+    Ok(Some(SessionPostmortem {
+        status: postmortem::session_status(&log)
+            .unwrap_or_default()
+            .to_string(),
+        session_id,
+        markdown,
+        path: path.to_string_lossy().to_string(),
+    }))
+}
+
+/// Check out the repository as of an iteration's last commit into a detached
+/// worktree at `dest_dir` (a new temporary directory if omitted) to run or
+/// inspect it; stale snapshot worktrees are removed first
+#[tauri::command]
+pub async fn checkout_iteration_snapshot(
+    project_id: String,
+    session_id: String,
+    iteration: u32,
+    dest_dir: Option<String>,
+) -> Result<IterationSnapshot, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    if state.remote.is_some() {
+        return Err("Iteration snapshots need a local project checkout".to_string());
+    }
+    let records =
+        storage::load_session_iterations(&uuid, &session_id).map_err(|e| e.to_string())?;
+    let session_start = storage::load_session_snapshot(&uuid, &session_id)
+        .map_err(|e| e.to_string())?
+        .and_then(|s| s.git_head);
+    let commit = time_travel::commit_for_iteration(&records, iteration, session_start.as_deref())?;
+
+    time_travel::cleanup_stale().await;
+    let path = match dest_dir {
+        Some(dir) => dir,
+        None => std::env::temp_dir()
+            .join(format!(
+                "ralph-snapshot-{}-{}",
+                iteration,
+                Utc::now().format("%Y%m%d%H%M%S")
+            ))
+            .to_string_lossy()
+            .to_string(),
+    };
+    time_travel::checkout(IterationSnapshot {
+        project_id: uuid,
+        session_id,
+        iteration,
+        commit,
+        project_path: state.path,
+        path,
+        created_at: Utc::now(),
+    })
+    .await
+}
+
+/// Remove a snapshot worktree checked out by `checkout_iteration_snapshot`
+#[tauri::command]
+pub async fn remove_iteration_snapshot(path: String) -> Result<(), String> {
+    time_travel::remove(&path).await
+}
@@ -0,0 +1,23 @@
+use super::*;
+
+/// Save global shortcut bindings and re-register them, returning any another app already owns
+#[tauri::command]
+pub async fn update_shortcut_bindings(
+    app_handle: AppHandle,
+    bindings: ShortcutBindings,
+) -> Result<Vec<String>, String> {
+    #[cfg(desktop)]
+    crate::shortcuts::parse_bindings(&bindings)?;
+
+    let mut config = storage::load_config().map_err(|e| e.to_string())?;
+    config.shortcuts = bindings;
+    storage::save_config(&config).map_err(|e| e.to_string())?;
+
+    #[cfg(desktop)]
+    return crate::shortcuts::register(&app_handle, &config.shortcuts);
+    #[cfg(not(desktop))]
+    {
+        let _ = app_handle;
+        Ok(Vec::new())
+    }
+}
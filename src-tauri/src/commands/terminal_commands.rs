@@ -0,0 +1,74 @@
+use super::*;
+use crate::engine::terminal::{self, TERMINAL_NOT_RUNNING};
+use serde::Serialize;
+use tauri::Emitter;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Sent on `terminal-output`; `data` is raw terminal bytes
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalOutput {
+    pub project_id: String,
+    pub data: Vec<u8>,
+}
+
+fn running_session(project_id: &str) -> Result<Arc<terminal::TerminalSession>, String> {
+    terminal::session(project_id).ok_or_else(|| TERMINAL_NOT_RUNNING.to_string())
+}
+
+/// Attach to the project's running interactive iteration. Returns its recent
+/// output; the rest follows on `terminal-output` until the view detaches or
+/// another attaches, and `terminal-closed` is sent when the iteration ends.
+#[tauri::command]
+pub async fn attach_terminal(app_handle: AppHandle, project_id: String) -> Result<Vec<u8>, String> {
+    let session = running_session(&project_id)?;
+    let (scrollback, mut output) = session.subscribe();
+    let attachment = session.attach();
+    // Held weakly so the channel closes when the iteration ends
+    let session = Arc::downgrade(&session);
+    tokio::spawn(async move {
+        loop {
+            match output.recv().await {
+                Ok(data) => {
+                    let attached = session.upgrade().is_some_and(|s| s.is_attached(attachment));
+                    if !attached {
+                        break;
+                    }
+                    let _ = app_handle.emit(
+                        "terminal-output",
+                        TerminalOutput {
+                            project_id: project_id.clone(),
+                            data,
+                        },
+                    );
+                }
+                Err(RecvError::Lagged(_)) => continue,
+                Err(RecvError::Closed) => {
+                    let _ = app_handle.emit("terminal-closed", &project_id);
+                    break;
+                }
+            }
+        }
+    });
+    Ok(scrollback)
+}
+
+/// Type into the attached terminal
+#[tauri::command]
+pub async fn send_terminal_input(project_id: String, data: String) -> Result<(), String> {
+    running_session(&project_id)?.write_input(data.as_bytes())
+}
+
+#[tauri::command]
+pub async fn resize_terminal(project_id: String, cols: u16, rows: u16) -> Result<(), String> {
+    running_session(&project_id)?.resize(cols, rows)
+}
+
+/// Stop streaming output; the iteration keeps running
+#[tauri::command]
+pub async fn detach_terminal(project_id: String) -> Result<(), String> {
+    if let Some(session) = terminal::session(&project_id) {
+        session.detach();
+    }
+    Ok(())
+}
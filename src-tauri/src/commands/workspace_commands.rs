@@ -0,0 +1,174 @@
+use super::*;
+use crate::engine::env_profiles::is_valid_env_name;
+use crate::notifications;
+
+fn validate_workspace(workspace: &Workspace) -> Result<(), String> {
+    if workspace.name.trim().is_empty() {
+        return Err("Workspace name cannot be empty".to_string());
+    }
+    if let Some(quiet_hours) = &workspace.quiet_hours {
+        notifications::validate(quiet_hours)?;
+    }
+    if let Some(key) = workspace.env.keys().find(|k| !is_valid_env_name(k)) {
+        return Err(format!("Invalid environment variable name '{}'", key));
+    }
+    Ok(())
+}
+
+/// List workspaces
+#[tauri::command]
+pub async fn list_workspaces() -> Result<Vec<Workspace>, String> {
+    storage::load_workspaces().map_err(|e| e.to_string())
+}
+
+/// Create an empty workspace; every default is inherited until it is overridden
+#[tauri::command]
+pub async fn create_workspace(name: String) -> Result<Workspace, String> {
+    let workspace = Workspace {
+        id: Uuid::new_v4(),
+        name: name.trim().to_string(),
+        default_cli: None,
+        iteration_timeout_ms: None,
+        idle_timeout_ms: None,
+        env: Default::default(),
+        quiet_hours: None,
+        created_at: Utc::now(),
+    };
+    validate_workspace(&workspace)?;
+    let mut workspaces = storage::load_workspaces().map_err(|e| e.to_string())?;
+    workspaces.push(workspace.clone());
+    storage::save_workspaces(&workspaces).map_err(|e| e.to_string())?;
+    Ok(workspace)
+}
+
+/// Replace a workspace's name and overrides
+#[tauri::command]
+pub async fn update_workspace(workspace: Workspace) -> Result<Workspace, String> {
+    validate_workspace(&workspace)?;
+    let mut workspaces = storage::load_workspaces().map_err(|e| e.to_string())?;
+    let existing = workspaces
+        .iter_mut()
+        .find(|w| w.id == workspace.id)
+        .ok_or("Workspace not found")?;
+    *existing = Workspace {
+        created_at: existing.created_at,
+        ..workspace
+    };
+    let updated = existing.clone();
+    storage::save_workspaces(&workspaces).map_err(|e| e.to_string())?;
+    Ok(updated)
+}
+
+/// Delete a workspace; its projects fall back to the global config
+#[tauri::command]
+pub async fn delete_workspace(id: String) -> Result<(), String> {
+    let uuid = Uuid::parse_str(&id).map_err(|e| e.to_string())?;
+    let mut workspaces = storage::load_workspaces().map_err(|e| e.to_string())?;
+    workspaces.retain(|w| w.id != uuid);
+    storage::save_workspaces(&workspaces).map_err(|e| e.to_string())?;
+
+    let moved: Vec<Uuid> = storage::update_project_index(|index| {
+        index
+            .projects
+            .iter_mut()
+            .filter(|p| p.workspace_id == Some(uuid))
+            .map(|meta| {
+                meta.workspace_id = None;
+                meta.id
+            })
+            .collect()
+    })
+    .map_err(|e| e.to_string())?;
+    for id in moved {
+        if let Ok(mut state) = storage::load_project_state(&id) {
+            state.workspace_id = None;
+            state.updated_at = Utc::now();
+            let _ = storage::save_project_state(&state);
+        }
+    }
+    Ok(())
+}
+
+/// Move a project into a workspace, or out of any with `None`
+#[tauri::command]
+pub async fn set_project_workspace(
+    project_id: String,
+    workspace_id: Option<String>,
+) -> Result<ProjectState, String> {
+    let uuid = Uuid::parse_str(&project_id).map_err(|e| e.to_string())?;
+    let workspace_id = workspace_id
+        .map(|id| Uuid::parse_str(&id).map_err(|e| e.to_string()))
+        .transpose()?;
+    if let Some(workspace_id) = workspace_id {
+        let workspaces = storage::load_workspaces().map_err(|e| e.to_string())?;
+        if !workspaces.iter().any(|w| w.id == workspace_id) {
+            return Err("Workspace not found".to_string());
+        }
+    }
+
+    storage::update_project_index(|index| {
+        if let Some(meta) = index.projects.iter_mut().find(|p| p.id == uuid) {
+            meta.workspace_id = workspace_id;
+        }
+    })
+    .map_err(|e| e.to_string())?;
+
+    let mut state = storage::load_project_state(&uuid).map_err(|e| e.to_string())?;
+    state.workspace_id = workspace_id;
+    state.updated_at = Utc::now();
+    storage::save_project_state(&state).map_err(|e| e.to_string())?;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace() -> Workspace {
+        Workspace {
+            id: Uuid::new_v4(),
+            name: "Client A".to_string(),
+            default_cli: Some(CliType::Codex),
+            iteration_timeout_ms: None,
+            idle_timeout_ms: Some(60_000),
+            env: [("API_URL".to_string(), "https://a.example".to_string())].into(),
+            quiet_hours: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn workspace_overrides_only_what_it_sets() {
+        let global = GlobalConfig {
+            iteration_timeout_ms: 900_000,
+            env: [
+                ("API_URL".to_string(), "https://global.example".to_string()),
+                ("LOG_LEVEL".to_string(), "info".to_string()),
+            ]
+            .into(),
+            ..GlobalConfig::default()
+        };
+        let config = workspace().apply(global);
+        assert_eq!(config.default_cli, CliType::Codex);
+        assert_eq!(config.iteration_timeout_ms, 900_000);
+        assert_eq!(config.idle_timeout_ms, 60_000);
+        assert_eq!(config.env["API_URL"], "https://a.example");
+        assert_eq!(config.env["LOG_LEVEL"], "info");
+    }
+
+    #[test]
+    fn rejects_unnamed_workspaces_and_bad_env_names() {
+        assert!(validate_workspace(&workspace()).is_ok());
+        let mut unnamed = workspace();
+        unnamed.name = " ".to_string();
+        assert!(validate_workspace(&unnamed).is_err());
+        let mut bad_env = workspace();
+        bad_env.env.insert("A=B".to_string(), "c".to_string());
+        assert!(validate_workspace(&bad_env).is_err());
+        let mut unsafe_env = workspace();
+        unsafe_env
+            .env
+            .insert("X;rm -rf ~".to_string(), "c".to_string());
+        assert!(validate_workspace(&unsafe_env).is_err());
+    }
+}
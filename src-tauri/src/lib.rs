@@ -1,14 +1,21 @@
-mod adapters;
 mod auto_update;
 mod commands;
-mod engine;
-mod security;
-mod storage;
+mod notifications;
+mod observer;
+#[cfg(desktop)]
+mod shortcuts;
 #[cfg(test)]
-mod test_support;
+use ralph_core::test_support;
+
+use ralph_core::{adapters, api_agent, engine, security, storage};
 
 use commands::AppState;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
+
+/// Run as the headless API agent when launched by the `api` adapter; returns the exit code
+pub fn run_api_agent() -> Option<i32> {
+    api_agent::run_from_args()
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -19,45 +26,204 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .manage(AppState::default())
+        .on_window_event(|_window, event| {
+            // Loops only stream full output while someone is looking
+            if let tauri::WindowEvent::Focused(focused) = event {
+                engine::verbosity::set_window_focused(*focused);
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             // Project commands
             commands::list_projects,
+            commands::refresh_project_status,
             commands::create_project,
             commands::get_project,
+            commands::get_project_bundle,
             commands::set_project_skip_git_repo_check,
+            commands::set_project_debug_raw_output,
+            commands::set_project_network_blocked,
+            commands::set_project_output_filters,
+            commands::set_project_container,
+            commands::set_project_remote,
+            commands::set_project_screenshot,
             commands::update_task_max_iterations,
             commands::update_task_auto_commit,
+            commands::update_task_write_status_file,
+            commands::update_task_interactive_terminal,
+            commands::update_task_generate_readme,
             commands::update_task_auto_init,
+            commands::update_task_cli_path_override,
+            commands::update_task_env_profile,
+            commands::update_task_auto_decide,
+            commands::update_task_warmup_context,
+            commands::update_task_repo_context,
             commands::update_task_prompt,
+            commands::update_task_iteration_prompts,
+            commands::update_task_working_subdir,
+            commands::update_task_targets,
+            commands::update_task_artifacts,
+            commands::update_task_priority,
+            commands::update_task_memory_limit,
+            commands::update_task_max_run_duration,
+            commands::update_task_completion_signal,
+            commands::update_task_completion_strategy,
+            commands::update_task_validation_command,
+            commands::update_task_pre_iteration_hook,
+            commands::update_task_carry_over_progress,
+            commands::update_task_supervisor,
+            commands::update_task_review_gate,
+            commands::update_task_compare_cli,
+            commands::set_dependency_updates,
+            commands::add_task_attachment,
+            commands::remove_task_attachment,
             commands::init_project_git_repo,
             commands::check_project_git_repo,
             commands::delete_project,
             commands::detect_installed_clis,
             commands::get_config,
             commands::save_config,
+            commands::set_encryption_at_rest,
+            commands::get_pending_consents,
+            commands::grant_consent,
+            commands::revoke_consent,
+            commands::list_consents,
             commands::confirm_permissions,
             commands::update_project_status,
+            commands::update_project_path,
             commands::ai_brainstorm_chat,
+            commands::transcribe_and_brainstorm,
+            commands::preview_generated_prompt,
             commands::complete_ai_brainstorm,
+            commands::get_brainstorm_prompt,
+            commands::set_brainstorm_prompt,
+            commands::reset_brainstorm_prompt,
+            commands::list_brainstorm_personas,
+            commands::save_brainstorm_persona,
+            commands::delete_brainstorm_persona,
+            commands::set_brainstorm_persona,
             commands::generate_project_title_cmd,
             // Loop commands
             commands::start_loop,
+            commands::queue_loop,
+            commands::list_queue,
+            commands::run_dependency_update,
+            commands::run_issue_triage,
+            commands::start_followup,
+            commands::resolve_codex_git_check,
             commands::pause_loop,
             commands::resume_loop,
             commands::stop_loop,
+            commands::estimate_loop_cost,
             commands::get_loop_status,
+            commands::stop_all_loops,
+            commands::pause_all_loops,
+            // Observer commands
+            commands::get_observer_status,
+            commands::set_observer_enabled,
+            commands::regenerate_observer_token,
+            commands::observe_remote_projects,
+            // Shortcut commands
+            commands::update_shortcut_bindings,
             // Recovery commands
             commands::check_interrupted_tasks,
             commands::cancel_interrupted_task,
             commands::cleanup_logs,
             commands::get_project_logs,
+            commands::get_current_log_path,
+            commands::scan_orphan_processes,
+            commands::terminate_orphan_processes,
+            commands::take_orphan_report,
+            // Session commands
+            commands::list_sessions,
+            commands::get_change_hotspots,
+            commands::add_session_note,
+            commands::export_session_transcript,
+            commands::get_suppressed_counts,
+            commands::list_highlights,
+            commands::get_session_completion,
+            commands::get_session_snapshot,
+            commands::list_session_iterations,
+            commands::list_prompt_revisions,
+            commands::generate_session_changelog,
+            commands::generate_postmortem,
+            commands::get_session_postmortem,
+            commands::checkout_iteration_snapshot,
+            commands::remove_iteration_snapshot,
+            commands::list_iteration_artifacts,
+            commands::run_benchmark,
+            commands::run_cli_comparison,
+            commands::stop_benchmark,
+            commands::list_benchmarks,
+            commands::delete_benchmark,
+            commands::run_prompt_experiment,
+            commands::list_prompt_experiments,
+            commands::delete_prompt_experiment,
+            commands::pin_session,
+            commands::pin_iteration,
+            commands::list_pinned,
+            // Diagnostic commands
+            commands::list_parse_anomalies,
+            commands::export_parse_anomalies,
             // Update commands
             commands::get_update_state,
             commands::check_for_updates,
             commands::load_update_state_cmd,
+            // Capture commands
+            commands::quick_task_from_clipboard,
+            // Notification commands
+            commands::update_quiet_hours,
+            commands::route_notification,
+            // Workspace commands
+            commands::list_workspaces,
+            commands::create_workspace,
+            commands::update_workspace,
+            commands::delete_workspace,
+            commands::set_project_workspace,
+            // Report commands
+            commands::generate_usage_report,
+            commands::set_local_analytics,
+            commands::get_project_health,
+            commands::set_knowledge_base,
+            commands::suggest_learnings,
+            commands::update_task_learnings,
+            // Plugin commands
+            commands::list_plugins,
+            commands::set_plugin_enabled,
+            // Terminal commands
+            commands::attach_terminal,
+            commands::send_terminal_input,
+            commands::resize_terminal,
+            commands::detach_terminal,
         ])
         .setup(|app| {
+            #[cfg(desktop)]
+            {
+                use tauri_plugin_global_shortcut::ShortcutState;
+                app.handle().plugin(
+                    tauri_plugin_global_shortcut::Builder::new()
+                        .with_handler(|app, shortcut, event| {
+                            if event.state() == ShortcutState::Pressed {
+                                shortcuts::handle(app, shortcut);
+                            }
+                        })
+                        .build(),
+                )?;
+                // Another app may own a shortcut; every binding is optional
+                let bindings = storage::load_config()
+                    .map(|c| c.shortcuts)
+                    .unwrap_or_default();
+                match shortcuts::register(app.handle(), &bindings) {
+                    Ok(unavailable) => {
+                        for binding in unavailable {
+                            eprintln!("[Shortcuts] Failed to register {}", binding);
+                        }
+                    }
+                    Err(e) => eprintln!("[Shortcuts] {}", e),
+                }
+            }
+
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
                 let _ = auto_update::apply_pending_update().await;
@@ -67,6 +233,42 @@ pub fn run() {
                 let mut update_state = state.update_state.write().await;
                 *update_state = loaded;
             });
+
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app_handle.state::<AppState>();
+                if let Err(e) = commands::sync_observer(&state).await {
+                    eprintln!("[Observer] {}", e);
+                }
+            });
+
+            tauri::async_runtime::spawn(notifications::watch_digest(app.handle().clone()));
+            tauri::async_runtime::spawn(commands::watch_dependency_updates(app.handle().clone()));
+
+            tauri::async_runtime::spawn(async {
+                engine::time_travel::cleanup_stale().await;
+            });
+
+            // Clean up, or offer to, CLI processes a previous crash left running
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                let reap = storage::load_config()
+                    .map(|config| config.reap_orphans_on_startup)
+                    .unwrap_or(true);
+                if !reap {
+                    let orphans = engine::processes::scan_orphans().await;
+                    if !orphans.is_empty() {
+                        let _ = app_handle.emit("orphan-processes", &orphans);
+                    }
+                    return;
+                }
+                let report = engine::processes::reap_orphans().await;
+                if !report.is_empty() {
+                    let state = app_handle.state::<AppState>();
+                    *state.orphan_report.write().await = Some(report.clone());
+                    let _ = app_handle.emit("orphan-report", &report);
+                }
+            });
             Ok(())
         })
         .run(tauri::generate_context!())
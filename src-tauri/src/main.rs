@@ -2,5 +2,8 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    if let Some(code) = ralph_desktop_lib::run_api_agent() {
+        std::process::exit(code);
+    }
     ralph_desktop_lib::run()
 }
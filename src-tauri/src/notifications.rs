@@ -0,0 +1,158 @@
+//! Quiet hours for notifications: during the configured window only break-through
+//! severities are delivered, the rest are queued and sent as one digest when it ends.
+
+use crate::commands::AppState;
+use crate::storage;
+use crate::storage::models::{NotificationSeverity, QueuedNotification, QuietHours};
+use chrono::{Local, Timelike};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const DIGEST_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Minutes after midnight of an `HH:MM` time
+pub fn parse_clock(value: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid time '{}', expected HH:MM", value);
+    let (hours, minutes) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+pub fn validate(quiet_hours: &QuietHours) -> Result<(), String> {
+    let start = parse_clock(&quiet_hours.start)?;
+    let end = parse_clock(&quiet_hours.end)?;
+    if quiet_hours.enabled && start == end {
+        return Err("Quiet hours must start and end at different times".to_string());
+    }
+    Ok(())
+}
+
+/// Whether `minute` (after midnight) falls in the window; the end is exclusive
+pub fn is_quiet_at(quiet_hours: &QuietHours, minute: u32) -> bool {
+    if !quiet_hours.enabled {
+        return false;
+    }
+    let (Ok(start), Ok(end)) = (
+        parse_clock(&quiet_hours.start),
+        parse_clock(&quiet_hours.end),
+    ) else {
+        return false;
+    };
+    if start <= end {
+        (start..end).contains(&minute)
+    } else {
+        minute >= start || minute < end
+    }
+}
+
+fn local_minute() -> u32 {
+    let now = Local::now();
+    now.hour() * 60 + now.minute()
+}
+
+/// Whether a notification of `severity` may be shown at `minute`
+pub fn delivers_at(quiet_hours: &QuietHours, severity: NotificationSeverity, minute: u32) -> bool {
+    quiet_hours.break_through.contains(&severity) || !is_quiet_at(quiet_hours, minute)
+}
+
+pub fn delivers_now(quiet_hours: &QuietHours, severity: NotificationSeverity) -> bool {
+    delivers_at(quiet_hours, severity, local_minute())
+}
+
+/// Emit `notification-digest` with everything queued once quiet hours are over
+pub async fn watch_digest(app_handle: AppHandle) {
+    loop {
+        tokio::time::sleep(DIGEST_CHECK_INTERVAL).await;
+        let state = app_handle.state::<AppState>();
+        if state.notification_digest.read().await.is_empty() {
+            continue;
+        }
+        let config = storage::load_config().unwrap_or_default();
+        let workspaces = storage::load_workspaces().unwrap_or_default();
+        let minute = local_minute();
+        let still_quiet = |item: &QueuedNotification| {
+            let quiet_hours = item
+                .workspace_id
+                .and_then(|id| workspaces.iter().find(|w| w.id == id))
+                .and_then(|w| w.quiet_hours.as_ref())
+                .unwrap_or(&config.quiet_hours);
+            is_quiet_at(quiet_hours, minute)
+        };
+        let digest: Vec<QueuedNotification> = {
+            let mut queued = state.notification_digest.write().await;
+            let (held, ready): (Vec<_>, Vec<_>) = std::mem::take(&mut *queued)
+                .into_iter()
+                .partition(still_quiet);
+            *queued = held;
+            ready
+        };
+        if !digest.is_empty() {
+            let _ = app_handle.emit("notification-digest", &digest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn overnight() -> QuietHours {
+        QuietHours {
+            enabled: true,
+            ..QuietHours::default()
+        }
+    }
+
+    #[test]
+    fn parses_clock_times() {
+        assert_eq!(parse_clock("07:30"), Ok(450));
+        assert_eq!(parse_clock("0:05"), Ok(5));
+        assert!(parse_clock("24:00").is_err());
+        assert!(parse_clock("7pm").is_err());
+    }
+
+    #[test]
+    fn windows_wrap_past_midnight() {
+        let quiet = overnight();
+        assert!(is_quiet_at(&quiet, 23 * 60));
+        assert!(is_quiet_at(&quiet, 3 * 60));
+        assert!(!is_quiet_at(&quiet, 7 * 60));
+        assert!(!is_quiet_at(&quiet, 12 * 60));
+
+        let lunch = QuietHours {
+            start: "12:00".to_string(),
+            end: "13:00".to_string(),
+            ..overnight()
+        };
+        assert!(is_quiet_at(&lunch, 12 * 60 + 30));
+        assert!(!is_quiet_at(&lunch, 3 * 60));
+        assert!(!is_quiet_at(&QuietHours::default(), 3 * 60));
+    }
+
+    #[test]
+    fn break_through_severities_are_delivered() {
+        let quiet = overnight();
+        let three_am = 3 * 60;
+        assert!(delivers_at(&quiet, NotificationSeverity::Error, three_am));
+        assert!(!delivers_at(
+            &quiet,
+            NotificationSeverity::Success,
+            three_am
+        ));
+        assert!(delivers_at(&quiet, NotificationSeverity::Success, 9 * 60));
+    }
+
+    #[test]
+    fn rejects_empty_windows() {
+        let empty = QuietHours {
+            end: "22:00".to_string(),
+            ..overnight()
+        };
+        assert!(validate(&empty).is_err());
+        assert!(validate(&overnight()).is_ok());
+    }
+}
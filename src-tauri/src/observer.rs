@@ -0,0 +1,463 @@
+//! Read-only HTTP status feed, so runs can be watched from another machine.
+//!
+//! Only `GET` requests are served and every request must carry the observer
+//! token, either as `Authorization: Bearer <token>` or a `?token=` query
+//! parameter (for browsers and `EventSource`). Routes:
+//!
+//! - `/api/projects` — project summaries
+//! - `/api/projects/{id}` — full project state
+//! - `/api/projects/{id}/bundle` — state, active session usage, recent
+//!   sessions and the last completion summary in one document
+//! - `/api/projects/{id}/logs` — server-sent events tailing the current log
+
+use crate::engine::logs::LogManager;
+use crate::storage;
+use crate::storage::models::ObservedProject;
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::watch;
+
+const MAX_REQUEST_HEAD: usize = 8 * 1024;
+/// How long a client may take to send its request head
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+const LOG_BACKLOG_LINES: usize = 200;
+const LOG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, PartialEq)]
+struct ObserverRequest {
+    method: String,
+    path: String,
+    token: Option<String>,
+}
+
+/// Random token for authenticating observers
+pub fn generate_token() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+/// Bind the feed on all interfaces and serve it until `true` is sent on the returned channel
+pub async fn start(port: u16, token: String) -> Result<watch::Sender<bool>, String> {
+    let listener = TcpListener::bind(("0.0.0.0", port))
+        .await
+        .map_err(|e| format!("Failed to start observer feed on port {}: {}", port, e))?;
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    tokio::spawn(serve(listener, token, shutdown_rx));
+    Ok(shutdown_tx)
+}
+
+async fn serve(listener: TcpListener, token: String, mut shutdown: watch::Receiver<bool>) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                if let Ok((stream, _)) = accepted {
+                    let token = token.clone();
+                    let shutdown = shutdown.clone();
+                    tokio::spawn(async move {
+                        let _ = handle_connection(stream, &token, shutdown).await;
+                    });
+                }
+            }
+            _ = shutdown.changed() => break,
+        }
+    }
+}
+
+fn parse_request(head: &str) -> Option<ObserverRequest> {
+    let mut lines = head.lines();
+    let mut parts = lines.next()?.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let mut token = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("token="))
+        .map(str::to_string);
+    for line in lines {
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("authorization") {
+                if let Some(bearer) = value.trim().strip_prefix("Bearer ") {
+                    token = Some(bearer.trim().to_string());
+                }
+            }
+        }
+    }
+
+    Some(ObserverRequest {
+        method,
+        path: path.to_string(),
+        token,
+    })
+}
+
+/// Compare tokens without returning early on the first differing byte
+fn tokens_match(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Read up to a blank line, never more than `MAX_REQUEST_HEAD` bytes in total
+async fn read_request_head(stream: &mut TcpStream) -> std::io::Result<String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD as u64));
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        head.push_str(&line);
+    }
+    Ok(head)
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    token: &str,
+    shutdown: watch::Receiver<bool>,
+) -> std::io::Result<()> {
+    let Ok(head) = tokio::time::timeout(REQUEST_HEAD_TIMEOUT, read_request_head(&mut stream)).await
+    else {
+        return respond(
+            &mut stream,
+            "408 Request Timeout",
+            "text/plain",
+            "Request timeout",
+        )
+        .await;
+    };
+    let head = head?;
+
+    let Some(request) = parse_request(&head) else {
+        return respond(&mut stream, "400 Bad Request", "text/plain", "Bad request").await;
+    };
+    if request.method != "GET" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "Read-only",
+        )
+        .await;
+    }
+    if !request
+        .token
+        .as_deref()
+        .is_some_and(|given| tokens_match(given, token))
+    {
+        return respond(
+            &mut stream,
+            "401 Unauthorized",
+            "text/plain",
+            "Unauthorized",
+        )
+        .await;
+    }
+
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["api", "projects"] => match list_observed_projects() {
+            Ok(projects) => respond_json(&mut stream, &projects).await,
+            Err(e) => respond(&mut stream, "500 Internal Server Error", "text/plain", &e).await,
+        },
+        ["api", "projects", id] => match uuid::Uuid::parse_str(id)
+            .ok()
+            .and_then(|uuid| storage::load_project_state(&uuid).ok())
+        {
+            Some(state) => respond_json(&mut stream, &state).await,
+            None => {
+                respond(
+                    &mut stream,
+                    "404 Not Found",
+                    "text/plain",
+                    "Project not found",
+                )
+                .await
+            }
+        },
+        ["api", "projects", id, "bundle"] => match uuid::Uuid::parse_str(id)
+            .ok()
+            .and_then(|uuid| storage::load_project_bundle(&uuid).ok())
+        {
+            Some(bundle) => respond_json(&mut stream, &bundle).await,
+            None => {
+                respond(
+                    &mut stream,
+                    "404 Not Found",
+                    "text/plain",
+                    "Project not found",
+                )
+                .await
+            }
+        },
+        ["api", "projects", id, "logs"] => match uuid::Uuid::parse_str(id) {
+            Ok(uuid) => stream_logs(&mut stream, &uuid, shutdown).await,
+            Err(_) => {
+                respond(
+                    &mut stream,
+                    "404 Not Found",
+                    "text/plain",
+                    "Project not found",
+                )
+                .await
+            }
+        },
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "Not found").await,
+    }
+}
+
+fn list_observed_projects() -> Result<Vec<ObservedProject>, String> {
+    let index = storage::load_project_index().map_err(|e| e.to_string())?;
+    Ok(index
+        .projects
+        .iter()
+        .filter_map(|meta| storage::load_project_state(&meta.id).ok())
+        .map(|state| ObservedProject {
+            id: state.id,
+            name: state.name,
+            status: state.status,
+            current_iteration: state.execution.as_ref().map(|e| e.current_iteration),
+            session_id: state.execution.as_ref().and_then(|e| e.session_id.clone()),
+            target: state.execution.as_ref().and_then(|e| e.target.clone()),
+            updated_at: state.updated_at,
+        })
+        .collect())
+}
+
+async fn respond(
+    stream: &mut TcpStream,
+    status: &str,
+    content_type: &str,
+    body: &str,
+) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}; charset=utf-8\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+async fn respond_json<T: serde::Serialize>(
+    stream: &mut TcpStream,
+    value: &T,
+) -> std::io::Result<()> {
+    match serde_json::to_string(value) {
+        Ok(body) => respond(stream, "200 OK", "application/json", &body).await,
+        Err(e) => {
+            respond(
+                stream,
+                "500 Internal Server Error",
+                "text/plain",
+                &e.to_string(),
+            )
+            .await
+        }
+    }
+}
+
+/// Tail the project's current log as server-sent events, following new sessions
+async fn stream_logs(
+    stream: &mut TcpStream,
+    project_id: &uuid::Uuid,
+    mut shutdown: watch::Receiver<bool>,
+) -> std::io::Result<()> {
+    stream
+        .write_all(
+            b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        )
+        .await?;
+
+    let log_path = LogManager::current_log_path(project_id)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
+    let mut tail = LogTail::new(log_path);
+    for line in tail.backlog(LOG_BACKLOG_LINES) {
+        send_event(stream, &line).await?;
+    }
+
+    loop {
+        tokio::select! {
+            _ = tokio::time::sleep(LOG_POLL_INTERVAL) => {}
+            _ = shutdown.changed() => return stream.shutdown().await,
+        }
+        for line in tail.poll() {
+            send_event(stream, &line).await?;
+        }
+    }
+}
+
+async fn send_event(stream: &mut TcpStream, line: &str) -> std::io::Result<()> {
+    stream
+        .write_all(format!("data: {}\n\n", line.trim_end_matches('\r')).as_bytes())
+        .await
+}
+
+/// Incremental reader over `current.log`, which is re-pointed at each new session
+struct LogTail {
+    path: PathBuf,
+    resolved: Option<PathBuf>,
+    position: u64,
+    partial: String,
+}
+
+impl LogTail {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            resolved: None,
+            position: 0,
+            partial: String::new(),
+        }
+    }
+
+    /// Last `limit` complete lines, leaving the tail positioned at the end of the file
+    fn backlog(&mut self, limit: usize) -> Vec<String> {
+        let lines = self.poll();
+        lines[lines.len().saturating_sub(limit)..].to_vec()
+    }
+
+    /// Complete lines appended since the last poll
+    fn poll(&mut self) -> Vec<String> {
+        let resolved = fs::canonicalize(&self.path).ok();
+        if resolved != self.resolved {
+            self.resolved = resolved;
+            self.position = 0;
+            self.partial.clear();
+        }
+        let Ok(mut file) = fs::File::open(&self.path) else {
+            return Vec::new();
+        };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.position {
+            self.position = 0;
+            self.partial.clear();
+        }
+        let mut buf = Vec::new();
+        if file.seek(SeekFrom::Start(self.position)).is_err() || file.read_to_end(&mut buf).is_err()
+        {
+            return Vec::new();
+        }
+        self.position += buf.len() as u64;
+        self.partial.push_str(&String::from_utf8_lossy(&buf));
+
+        let mut lines: Vec<String> = self.partial.split('\n').map(str::to_string).collect();
+        self.partial = lines.pop().unwrap_or_default();
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{lock_env_async, EnvVarGuard};
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_token_from_header_or_query() {
+        let request = parse_request("GET /api/projects?token=abc HTTP/1.1\r\nHost: x\r\n").unwrap();
+        assert_eq!(request.method, "GET");
+        assert_eq!(request.path, "/api/projects");
+        assert_eq!(request.token.as_deref(), Some("abc"));
+
+        let request =
+            parse_request("GET /api/projects HTTP/1.1\r\nauthorization: Bearer xyz\r\n").unwrap();
+        assert_eq!(request.token.as_deref(), Some("xyz"));
+
+        assert!(parse_request("").is_none());
+    }
+
+    #[test]
+    fn tokens_must_match_exactly() {
+        assert!(tokens_match("secret", "secret"));
+        assert!(!tokens_match("secreT", "secret"));
+        assert!(!tokens_match("secret2", "secret"));
+        assert!(!tokens_match("", "secret"));
+    }
+
+    #[test]
+    fn log_tail_returns_only_new_complete_lines() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("current.log");
+        fs::write(&path, "one\ntwo\nthr").unwrap();
+
+        let mut tail = LogTail::new(path.clone());
+        assert_eq!(tail.backlog(1), vec!["two".to_string()]);
+
+        let mut file = fs::OpenOptions::new().append(true).open(&path).unwrap();
+        std::io::Write::write_all(&mut file, b"ee\nfour\n").unwrap();
+        assert_eq!(tail.poll(), vec!["three".to_string(), "four".to_string()]);
+        assert!(tail.poll().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_head_is_capped() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let client = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+            let padding = format!("X-Pad: {}\r\n", "a".repeat(MAX_REQUEST_HEAD * 4));
+            let _ = stream.write_all(padding.as_bytes()).await;
+        });
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let head = read_request_head(&mut stream).await.unwrap();
+        assert_eq!(head.len(), MAX_REQUEST_HEAD);
+        drop(stream);
+        let _ = client.await;
+    }
+
+    async fn get(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn serves_projects_only_to_authorized_get_requests() {
+        let _env_lock = lock_env_async().await;
+        let home_dir = tempdir().unwrap();
+        let _home_guard = EnvVarGuard::set("HOME", home_dir.path());
+
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        tokio::spawn(serve(listener, "secret".to_string(), shutdown_rx));
+
+        let response = get(port, "GET /api/projects HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 401"));
+
+        let response = get(
+            port,
+            "POST /api/projects HTTP/1.1\r\nAuthorization: Bearer secret\r\n\r\n",
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 405"));
+
+        let response = get(port, "GET /api/projects?token=secret HTTP/1.1\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.ends_with("[]"));
+
+        let response = get(
+            port,
+            &format!(
+                "GET /api/projects/{}/bundle?token=secret HTTP/1.1\r\n\r\n",
+                uuid::Uuid::new_v4()
+            ),
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.1 404"));
+
+        let _ = shutdown_tx.send(true);
+    }
+}
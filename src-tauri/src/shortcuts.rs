@@ -0,0 +1,163 @@
+//! Global keyboard shortcuts with user-configurable bindings
+
+use crate::commands::{self, AppState};
+use crate::storage;
+use crate::storage::models::ShortcutBindings;
+use std::str::FromStr;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    StopAll,
+    PauseActive,
+    QuickCapture,
+    ToggleWindow,
+}
+
+impl ShortcutAction {
+    const ALL: [ShortcutAction; 4] = [
+        ShortcutAction::StopAll,
+        ShortcutAction::PauseActive,
+        ShortcutAction::QuickCapture,
+        ShortcutAction::ToggleWindow,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            ShortcutAction::StopAll => "stop all",
+            ShortcutAction::PauseActive => "pause active",
+            ShortcutAction::QuickCapture => "quick capture",
+            ShortcutAction::ToggleWindow => "show/hide window",
+        }
+    }
+
+    fn binding(self, bindings: &ShortcutBindings) -> Option<&str> {
+        match self {
+            ShortcutAction::StopAll => bindings.stop_all.as_deref(),
+            ShortcutAction::PauseActive => bindings.pause_active.as_deref(),
+            ShortcutAction::QuickCapture => bindings.quick_capture.as_deref(),
+            ShortcutAction::ToggleWindow => bindings.toggle_window.as_deref(),
+        }
+    }
+}
+
+/// Parse every bound action, rejecting invalid accelerators and keys bound twice
+pub fn parse_bindings(
+    bindings: &ShortcutBindings,
+) -> Result<Vec<(ShortcutAction, Shortcut)>, String> {
+    let mut parsed: Vec<(ShortcutAction, Shortcut)> = Vec::new();
+    for action in ShortcutAction::ALL {
+        let Some(accelerator) = action
+            .binding(bindings)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+        else {
+            continue;
+        };
+        let shortcut = Shortcut::from_str(accelerator)
+            .map_err(|e| format!("Invalid {} shortcut {}: {}", action.label(), accelerator, e))?;
+        if let Some((other, _)) = parsed.iter().find(|(_, s)| *s == shortcut) {
+            return Err(format!(
+                "{} is bound to both {} and {}",
+                accelerator,
+                other.label(),
+                action.label()
+            ));
+        }
+        parsed.push((action, shortcut));
+    }
+    Ok(parsed)
+}
+
+/// Replace registered shortcuts, returning the ones another app already owns
+pub fn register(app: &AppHandle, bindings: &ShortcutBindings) -> Result<Vec<String>, String> {
+    let parsed = parse_bindings(bindings)?;
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| e.to_string())?;
+
+    let mut unavailable = Vec::new();
+    for (action, shortcut) in parsed {
+        if let Err(e) = global_shortcut.register(shortcut) {
+            unavailable.push(format!("{} ({}): {}", action.label(), shortcut, e));
+        }
+    }
+    Ok(unavailable)
+}
+
+/// Run the action bound to a pressed shortcut
+pub fn handle(app: &AppHandle, shortcut: &Shortcut) {
+    let Ok(config) = storage::load_config() else {
+        return;
+    };
+    let Some(action) = parse_bindings(&config.shortcuts)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|(_, s)| s == shortcut)
+        .map(|(action, _)| action)
+    else {
+        return;
+    };
+
+    match action {
+        ShortcutAction::QuickCapture => commands::request_quick_capture(app),
+        ShortcutAction::ToggleWindow => toggle_main_window(app),
+        ShortcutAction::StopAll => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::stop_all_loops(app.clone(), app.state::<AppState>()).await;
+            });
+        }
+        ShortcutAction::PauseActive => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let _ = commands::pause_all_loops(app.state::<AppState>()).await;
+            });
+        }
+    }
+}
+
+fn toggle_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) && window.is_focused().unwrap_or(false) {
+        let _ = window.hide();
+    } else {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_bindings_parse() {
+        let parsed = parse_bindings(&ShortcutBindings::default()).unwrap();
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].0, ShortcutAction::QuickCapture);
+    }
+
+    #[test]
+    fn rejects_invalid_and_conflicting_bindings() {
+        let invalid = ShortcutBindings {
+            stop_all: Some("Ctrl+Nope".to_string()),
+            ..ShortcutBindings::default()
+        };
+        assert!(parse_bindings(&invalid).is_err());
+
+        // Equivalent accelerators conflict even when written differently
+        let conflicting = ShortcutBindings {
+            stop_all: Some("Ctrl+Shift+S".to_string()),
+            pause_active: Some("shift+control+s".to_string()),
+            ..ShortcutBindings::default()
+        };
+        let err = parse_bindings(&conflicting).unwrap_err();
+        assert!(err.contains("stop all") && err.contains("pause active"));
+    }
+}
@@ -1,13 +1,26 @@
 import * as api from '$lib/services/tauri';
+import { requestConsent } from '$lib/stores/consent';
 import { requestGitRepoCheck } from '$lib/stores/gitRepoCheck';
 
 export const CODEX_GIT_REPO_CHECK_REQUIRED = 'codex_git_repo_check_required';
+export const LOOP_ALREADY_RUNNING = 'loop_already_running';
+export const CONSENT_REQUIRED = 'consent_required';
 
 export function isGitRepoCheckError(error: unknown): boolean {
   const message = error instanceof Error ? error.message : String(error);
   return message.includes(CODEX_GIT_REPO_CHECK_REQUIRED);
 }
 
+export function isAlreadyRunningError(error: unknown): boolean {
+  const message = error instanceof Error ? error.message : String(error);
+  return message.includes(LOOP_ALREADY_RUNNING);
+}
+
+export function isConsentRequiredError(error: unknown): boolean {
+  const message = error instanceof Error ? error.message : String(error);
+  return message.includes(CONSENT_REQUIRED);
+}
+
 export async function startLoopWithGuard(projectId: string): Promise<boolean> {
   try {
     await api.startLoop(projectId);
@@ -17,6 +30,14 @@ export async function startLoopWithGuard(projectId: string): Promise<boolean> {
       requestGitRepoCheck(projectId, 'precheck');
       return false;
     }
+    if (isConsentRequiredError(error)) {
+      requestConsent(projectId, await api.getPendingConsents(projectId));
+      return false;
+    }
+    // A repeated Start while the loop is already starting or running is a no-op
+    if (isAlreadyRunningError(error)) {
+      return false;
+    }
     throw error;
   }
 }
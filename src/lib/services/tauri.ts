@@ -8,7 +8,57 @@ import type {
   CliType,
   ProjectStatus,
   LoopEvent,
-  UpdateState
+  UpdateState,
+  FileHotspot,
+  SessionNote,
+  SessionSummary,
+  LoopTarget,
+  SessionPins,
+  SuppressedCount,
+  SessionHighlight,
+  CompletionPayload,
+  CompletionMode,
+  CompletionStrategy,
+  IterationArtifact,
+  IterationRecord,
+  PromptRevision,
+  BenchmarkReport,
+  NotificationSeverity,
+  QuietHours,
+  QueuedNotification,
+  CostEstimate,
+  PromptExperiment,
+  ProcessRecord,
+  PinnedSession,
+  ParseAnomaly,
+  ObservedProject,
+  ProjectBundle,
+  QueuedLoop,
+  SessionChangelog,
+  SessionPostmortem,
+  IterationSnapshot,
+  SessionSnapshot,
+  ObserverStatus,
+  Capability,
+  GitCheckResolution,
+  PendingConsent,
+  ConsentGrant,
+  ContainerConfig,
+  RemoteTarget,
+  ScreenshotConfig,
+  ShortcutBindings,
+  BrainstormPersona,
+  PromptPreview,
+  QuickCaptureRequest,
+  OrphanReport,
+  RawOutputEvent,
+  TerminalOutput,
+  ProcessPriority,
+  UsageReport,
+  ProjectHealth,
+  KnowledgeMatch,
+  PluginInfo,
+  Workspace
 } from '../types';
 
 const isE2E = import.meta.env.VITE_E2E === '1';
@@ -39,12 +89,20 @@ const e2eState = (() => {
     maxConcurrentProjects: 3,
     iterationTimeoutMs: 0,
     idleTimeoutMs: 0,
+    firstOutputTimeoutMs: 120000,
+    maxConsecutiveFailures: 3,
     theme: 'system',
     language: 'en',
+    agentLanguage: 'auto',
     logRetentionDays: 7,
     permissionsConfirmed: true
   };
 
+  let brainstormPrompt = '{language_rule}';
+  let personas: BrainstormPersona[] = [
+    { id: 'thought_partner', name: 'Thought Partner', prompt: '', builtIn: true }
+  ];
+
   function now() {
     return new Date().toISOString();
   }
@@ -106,6 +164,12 @@ const e2eState = (() => {
       const line = `iteration ${loop.iteration}: generating web build (HTML5 Canvas / JavaScript)`;
       project.logs.push(line);
       emit({ type: 'output', projectId, iteration: loop.iteration, content: line, isStderr: false });
+      emit({
+        type: 'iterationEnd',
+        projectId,
+        iteration: loop.iteration,
+        stats: { durationMs: 700, stdoutLines: 1, stderrLines: 0, suppressedLines: 0, exitCode: 0 }
+      });
 
       if (loop.iteration >= loop.maxIterations) {
         const doneLine = completionSignal;
@@ -179,6 +243,17 @@ const e2eState = (() => {
         lastOpenedAt: state.updatedAt
       }));
     },
+    refreshProjectStatus(projectId: string): ProjectMeta {
+      const { state } = ensureProject(projectId);
+      return {
+        id: state.id,
+        name: state.name,
+        path: state.path,
+        status: state.status,
+        createdAt: state.createdAt,
+        lastOpenedAt: state.updatedAt
+      };
+    },
     createProject(path: string, name: string) {
       const id = createId();
       const ts = now();
@@ -214,6 +289,42 @@ const e2eState = (() => {
       project.state.updatedAt = now();
       return project.state;
     },
+    setProjectDebugRawOutput(projectId: string, enabled: boolean) {
+      const project = ensureProject(projectId);
+      project.state.debugRawOutput = enabled;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    setProjectNetworkBlocked(projectId: string, blocked: boolean) {
+      const project = ensureProject(projectId);
+      project.state.networkBlocked = blocked;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    setProjectOutputFilters(projectId: string, filters: string[]) {
+      const project = ensureProject(projectId);
+      project.state.outputFilters = filters;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    setProjectContainer(projectId: string, container: ContainerConfig | null) {
+      const project = ensureProject(projectId);
+      project.state.container = container;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    setProjectRemote(projectId: string, remote: RemoteTarget | null) {
+      const project = ensureProject(projectId);
+      project.state.remote = remote;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    setProjectScreenshot(projectId: string, screenshot: ScreenshotConfig | null) {
+      const project = ensureProject(projectId);
+      project.state.screenshot = screenshot;
+      project.state.updatedAt = now();
+      return project.state;
+    },
     updateTaskMaxIterations(projectId: string, maxIterations: number) {
       const project = ensureProject(projectId);
       if (!project.state.task) {
@@ -241,6 +352,74 @@ const e2eState = (() => {
       project.state.updatedAt = now();
       return project.state;
     },
+    updateTaskAutoDecide(projectId: string, autoDecide: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.autoDecide = autoDecide;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskWriteStatusFile(projectId: string, writeStatusFile: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.writeStatusFile = writeStatusFile;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskGenerateReadme(projectId: string, generateReadme: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.generateReadme = generateReadme;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    setDependencyUpdates(projectId: string, enabled: boolean, intervalDays?: number) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.dependencyUpdates = enabled
+        ? {
+            intervalDays: intervalDays ?? project.state.dependencyUpdates?.intervalDays ?? 7,
+            lastRunAt: project.state.dependencyUpdates?.lastRunAt ?? null
+          }
+        : null;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskInteractiveTerminal(projectId: string, interactiveTerminal: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.interactiveTerminal = interactiveTerminal;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskWarmupContext(projectId: string, warmupContext: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.warmupContext = warmupContext;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskRepoContext(projectId: string, repoContext: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.repoContext = repoContext;
+      project.state.updatedAt = now();
+      return project.state;
+    },
     updateTaskPrompt(projectId: string, prompt: string) {
       const project = ensureProject(projectId);
       if (!project.state.task) {
@@ -250,6 +429,195 @@ const e2eState = (() => {
       project.state.updatedAt = now();
       return project.state;
     },
+    updateTaskIterationPrompts(
+      projectId: string,
+      firstIterationPrompt: string | null,
+      subsequentIterationPrompt: string | null
+    ) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.firstIterationPrompt = firstIterationPrompt?.trim() ? firstIterationPrompt : null;
+      project.state.task.subsequentIterationPrompt = subsequentIterationPrompt?.trim()
+        ? subsequentIterationPrompt
+        : null;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskWorkingSubdir(projectId: string, workingSubdir: string | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.workingSubdir = workingSubdir?.trim() ? workingSubdir.trim() : null;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskTargets(projectId: string, targets: LoopTarget[]) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.targets = targets;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskArtifacts(projectId: string, artifacts: string[]) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.artifacts = artifacts.map((pattern) => pattern.trim());
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskPriority(projectId: string, priority: ProcessPriority) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.priority = priority;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskMemoryLimit(projectId: string, memoryLimitMb: number | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.memoryLimitMb = memoryLimitMb;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskMaxRunDuration(projectId: string, maxRunDurationMins: number | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.maxRunDurationMins = maxRunDurationMins;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskCompletionSignal(
+      projectId: string,
+      completionSignal: string,
+      extraCompletionSignals: string[],
+      isRegex: boolean,
+      mode: CompletionMode
+    ) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      if (!completionSignal.trim()) {
+        throw new Error('Completion signal cannot be empty');
+      }
+      project.state.task.completionSignal = completionSignal;
+      project.state.task.completionSignalRegex = isRegex;
+      project.state.task.extraCompletionSignals = extraCompletionSignals.filter((signal) => signal.trim());
+      project.state.task.completionMode = mode;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskCompletionStrategy(projectId: string, strategy: CompletionStrategy) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      if (strategy.type === 'noOpStreak' && strategy.iterations < 1) {
+        throw new Error('A no-op streak needs at least one iteration');
+      }
+      project.state.task.completionStrategy = strategy;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskValidationCommand(projectId: string, command: string | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.validationCommand = command?.trim() ? command.trim() : null;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskCarryOverProgress(projectId: string, enabled: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.carryOverProgress = enabled;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskCompareCli(projectId: string, cli: CliType | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.compareCli = cli;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskReviewGate(projectId: string, enabled: boolean, rubric: string | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.reviewGate = enabled;
+      project.state.task.reviewRubric = rubric?.trim() ? rubric.trim() : null;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskSupervisor(projectId: string, interval: number | null) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.supervisorInterval = interval;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskPreIterationHook(projectId: string, command: string | null, abortOnFailure: boolean) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.preIterationCommand = command?.trim() ? command.trim() : null;
+      project.state.task.preIterationAbortOnFailure = abortOnFailure;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    updateTaskLearnings(projectId: string, learningIds: string[]) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.learningIds = learningIds;
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    addTaskAttachment(projectId: string, path: string) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      const name = path.split(/[\\/]/).pop() || path;
+      project.state.task.attachments = [...(project.state.task.attachments ?? []), name];
+      project.state.updatedAt = now();
+      return project.state;
+    },
+    removeTaskAttachment(projectId: string, name: string) {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      project.state.task.attachments = (project.state.task.attachments ?? []).filter(
+        (a) => a !== name
+      );
+      project.state.updatedAt = now();
+      return project.state;
+    },
     deleteProject(id: string) {
       projects.delete(id);
     },
@@ -269,6 +637,12 @@ const e2eState = (() => {
       project.state.updatedAt = now();
       return project.state;
     },
+    updateProjectPath(projectId: string, path: string) {
+      const project = ensureProject(projectId);
+      project.state.path = path;
+      project.state.updatedAt = now();
+      return project.state;
+    },
     startLoop(projectId: string) {
       startLoop(projectId);
     },
@@ -281,6 +655,23 @@ const e2eState = (() => {
     stopLoop(projectId: string) {
       stopLoop(projectId);
     },
+    estimateLoopCost(projectId: string): CostEstimate {
+      const project = ensureProject(projectId);
+      if (!project.state.task) {
+        throw new Error('No task configured for this project');
+      }
+      return {
+        cli: project.state.task.cli,
+        maxIterations: project.state.task.maxIterations,
+        promptTokens: Math.ceil(project.state.task.prompt.length / 4),
+        sampledIterations: 0,
+        typicalIterations: null,
+        inputTokensPerIteration: 0,
+        outputTokensPerIteration: 0,
+        lowUsd: null,
+        highUsd: null
+      };
+    },
     getLoopStatus(projectId: string) {
       const project = ensureProject(projectId);
       return Boolean(project.loop && !project.loop.stopped);
@@ -298,6 +689,41 @@ const e2eState = (() => {
       const project = ensureProject(projectId);
       return project.logs;
     },
+    getCurrentLogPath(projectId: string) {
+      return `/tmp/ralph-e2e-logs/${projectId}/current.log`;
+    },
+    listSessions(_projectId: string) {
+      return [] as SessionSummary[];
+    },
+    addSessionNote(
+      _projectId: string,
+      _sessionId: string,
+      iteration: number | null,
+      text: string
+    ) {
+      return [{ iteration, text, createdAt: now() }] as SessionNote[];
+    },
+    pinSession(_projectId: string, _sessionId: string, pinned: boolean) {
+      return { pinned, iterations: [] } as SessionPins;
+    },
+    pinIteration(_projectId: string, _sessionId: string, iteration: number, pinned: boolean) {
+      return { pinned: false, iterations: pinned ? [iteration] : [] } as SessionPins;
+    },
+    listPinned(_projectId?: string) {
+      return [] as PinnedSession[];
+    },
+    getChangeHotspots(_projectId: string, _sessionId?: string) {
+      return [] as FileHotspot[];
+    },
+    listParseAnomalies(_cli?: CliType) {
+      return [] as ParseAnomaly[];
+    },
+    exportParseAnomalies(_destination: string) {
+      return 0;
+    },
+    setObserverEnabled(enabled: boolean) {
+      return { enabled, running: enabled, port: 7421, token: enabled ? 'e2e-token' : null } as ObserverStatus;
+    },
     aiBrainstormChat(projectId: string, conversation: ConversationMessage[]) {
       const topic = conversation[conversation.length - 1]?.content || 'task';
       const prompt = [
@@ -320,6 +746,16 @@ const e2eState = (() => {
         generatedPrompt: prompt
       } as AiBrainstormResponse;
     },
+    previewGeneratedPrompt(projectId: string, editedPrompt?: string) {
+      const project = ensureProject(projectId);
+      const prompt = editedPrompt ?? project.state.brainstorm?.draftPrompt ?? '';
+      project.state.brainstorm = { answers: [], ...project.state.brainstorm, draftPrompt: prompt };
+      return {
+        prompt,
+        tokenEstimate: Math.ceil(prompt.length / 4),
+        warnings: []
+      } as PromptPreview;
+    },
     completeAiBrainstorm(projectId: string, generatedPrompt: string, cli: CliType, maxIterations: number) {
       const project = ensureProject(projectId);
       project.state.task = {
@@ -334,6 +770,33 @@ const e2eState = (() => {
       project.state.updatedAt = now();
       return project.state;
     },
+    getBrainstormPrompt() {
+      return brainstormPrompt;
+    },
+    setBrainstormPrompt(template: string) {
+      brainstormPrompt = template;
+    },
+    resetBrainstormPrompt() {
+      brainstormPrompt = '{language_rule}';
+      return brainstormPrompt;
+    },
+    listBrainstormPersonas() {
+      return personas;
+    },
+    saveBrainstormPersona(persona: BrainstormPersona) {
+      personas = [...personas.filter((p) => p.id !== persona.id), { ...persona, builtIn: false }];
+      return personas;
+    },
+    deleteBrainstormPersona(id: string) {
+      personas = personas.filter((p) => p.builtIn || p.id !== id);
+      return personas;
+    },
+    setBrainstormPersona(projectId: string, persona: string | null) {
+      const project = ensureProject(projectId);
+      project.state.brainstorm = { answers: [], ...project.state.brainstorm, persona };
+      project.state.updatedAt = now();
+      return project.state;
+    },
     listenToLoopEvents(callback: LoopListener) {
       listeners.add(callback);
       return () => listeners.delete(callback);
@@ -367,6 +830,11 @@ export async function listProjects(): Promise<ProjectMeta[]> {
   return invoke('list_projects');
 }
 
+export async function refreshProjectStatus(projectId: string): Promise<ProjectMeta> {
+  if (isE2E) return e2eState.refreshProjectStatus(projectId);
+  return invoke('refresh_project_status', { projectId });
+}
+
 export async function createProject(path: string, name: string): Promise<ProjectState> {
   if (isE2E) return e2eState.createProject(path, name);
   return invoke('create_project', { path, name });
@@ -377,6 +845,21 @@ export async function getProject(id: string): Promise<ProjectState> {
   return invoke('get_project', { id });
 }
 
+export async function getProjectBundle(projectId: string): Promise<ProjectBundle> {
+  if (isE2E) {
+    const state = await e2eState.getProject(projectId);
+    return {
+      state,
+      sessionId: null,
+      usage: [],
+      recentSessions: [],
+      lastCompletion: null,
+      snapshot: null
+    };
+  }
+  return invoke('get_project_bundle', { projectId });
+}
+
 export async function setProjectSkipGitRepoCheck(
   projectId: string,
   skip: boolean
@@ -385,11 +868,59 @@ export async function setProjectSkipGitRepoCheck(
   return invoke('set_project_skip_git_repo_check', { projectId, skip });
 }
 
-export async function updateTaskMaxIterations(
+export async function setProjectDebugRawOutput(
   projectId: string,
-  maxIterations: number
+  enabled: boolean
 ): Promise<ProjectState> {
-  if (isE2E) return e2eState.updateTaskMaxIterations(projectId, maxIterations);
+  if (isE2E) return e2eState.setProjectDebugRawOutput(projectId, enabled);
+  return invoke('set_project_debug_raw_output', { projectId, enabled });
+}
+
+export async function setProjectNetworkBlocked(
+  projectId: string,
+  blocked: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setProjectNetworkBlocked(projectId, blocked);
+  return invoke('set_project_network_blocked', { projectId, blocked });
+}
+
+export async function setProjectOutputFilters(
+  projectId: string,
+  filters: string[]
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setProjectOutputFilters(projectId, filters);
+  return invoke('set_project_output_filters', { projectId, filters });
+}
+
+export async function setProjectContainer(
+  projectId: string,
+  container: ContainerConfig | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setProjectContainer(projectId, container);
+  return invoke('set_project_container', { projectId, container });
+}
+
+export async function setProjectRemote(
+  projectId: string,
+  remote: RemoteTarget | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setProjectRemote(projectId, remote);
+  return invoke('set_project_remote', { projectId, remote });
+}
+
+export async function setProjectScreenshot(
+  projectId: string,
+  screenshot: ScreenshotConfig | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setProjectScreenshot(projectId, screenshot);
+  return invoke('set_project_screenshot', { projectId, screenshot });
+}
+
+export async function updateTaskMaxIterations(
+  projectId: string,
+  maxIterations: number
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskMaxIterations(projectId, maxIterations);
   return invoke('update_task_max_iterations', { projectId, maxIterations });
 }
 
@@ -401,6 +932,49 @@ export async function updateTaskAutoCommit(
   return invoke('update_task_auto_commit', { projectId, autoCommit });
 }
 
+export async function updateTaskWriteStatusFile(
+  projectId: string,
+  writeStatusFile: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskWriteStatusFile(projectId, writeStatusFile);
+  return invoke('update_task_write_status_file', { projectId, writeStatusFile });
+}
+
+export async function updateTaskGenerateReadme(
+  projectId: string,
+  generateReadme: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskGenerateReadme(projectId, generateReadme);
+  return invoke('update_task_generate_readme', { projectId, generateReadme });
+}
+
+export async function setDependencyUpdates(
+  projectId: string,
+  enabled: boolean,
+  intervalDays?: number
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setDependencyUpdates(projectId, enabled, intervalDays);
+  return invoke('set_dependency_updates', { projectId, enabled, intervalDays });
+}
+
+export async function runDependencyUpdate(projectId: string): Promise<boolean> {
+  if (isE2E) throw new Error('Dependency updates are not available in E2E mode');
+  return invoke('run_dependency_update', { projectId });
+}
+
+export async function runIssueTriage(projectId: string): Promise<number> {
+  if (isE2E) throw new Error('Issue triage is not available in E2E mode');
+  return invoke('run_issue_triage', { projectId });
+}
+
+export async function updateTaskInteractiveTerminal(
+  projectId: string,
+  interactiveTerminal: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskInteractiveTerminal(projectId, interactiveTerminal);
+  return invoke('update_task_interactive_terminal', { projectId, interactiveTerminal });
+}
+
 export async function updateTaskAutoInit(
   projectId: string,
   autoInitGit: boolean
@@ -409,6 +983,46 @@ export async function updateTaskAutoInit(
   return invoke('update_task_auto_init', { projectId, autoInitGit });
 }
 
+export async function updateTaskCliPathOverride(
+  projectId: string,
+  path: string | null
+): Promise<ProjectState> {
+  if (isE2E) throw new Error('CLI path override is not available in E2E mode');
+  return invoke('update_task_cli_path_override', { projectId, path });
+}
+
+export async function updateTaskEnvProfile(
+  projectId: string,
+  profile: string | null
+): Promise<ProjectState> {
+  if (isE2E) throw new Error('Environment profiles are not available in E2E mode');
+  return invoke('update_task_env_profile', { projectId, profile });
+}
+
+export async function updateTaskAutoDecide(
+  projectId: string,
+  autoDecide: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskAutoDecide(projectId, autoDecide);
+  return invoke('update_task_auto_decide', { projectId, autoDecide });
+}
+
+export async function updateTaskWarmupContext(
+  projectId: string,
+  warmupContext: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskWarmupContext(projectId, warmupContext);
+  return invoke('update_task_warmup_context', { projectId, warmupContext });
+}
+
+export async function updateTaskRepoContext(
+  projectId: string,
+  repoContext: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskRepoContext(projectId, repoContext);
+  return invoke('update_task_repo_context', { projectId, repoContext });
+}
+
 export async function updateTaskPrompt(
   projectId: string,
   prompt: string
@@ -417,6 +1031,166 @@ export async function updateTaskPrompt(
   return invoke('update_task_prompt', { projectId, prompt });
 }
 
+export async function updateTaskIterationPrompts(
+  projectId: string,
+  firstIterationPrompt: string | null,
+  subsequentIterationPrompt: string | null
+): Promise<ProjectState> {
+  if (isE2E) {
+    return e2eState.updateTaskIterationPrompts(
+      projectId,
+      firstIterationPrompt,
+      subsequentIterationPrompt
+    );
+  }
+  return invoke('update_task_iteration_prompts', {
+    projectId,
+    firstIterationPrompt,
+    subsequentIterationPrompt
+  });
+}
+
+export async function updateTaskWorkingSubdir(
+  projectId: string,
+  workingSubdir: string | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskWorkingSubdir(projectId, workingSubdir);
+  return invoke('update_task_working_subdir', { projectId, workingSubdir });
+}
+
+export async function updateTaskTargets(
+  projectId: string,
+  targets: LoopTarget[]
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskTargets(projectId, targets);
+  return invoke('update_task_targets', { projectId, targets });
+}
+
+export async function updateTaskArtifacts(
+  projectId: string,
+  artifacts: string[]
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskArtifacts(projectId, artifacts);
+  return invoke('update_task_artifacts', { projectId, artifacts });
+}
+
+export async function updateTaskPriority(
+  projectId: string,
+  priority: ProcessPriority
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskPriority(projectId, priority);
+  return invoke('update_task_priority', { projectId, priority });
+}
+
+export async function updateTaskMemoryLimit(
+  projectId: string,
+  memoryLimitMb: number | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskMemoryLimit(projectId, memoryLimitMb);
+  return invoke('update_task_memory_limit', { projectId, memoryLimitMb });
+}
+
+export async function updateTaskMaxRunDuration(
+  projectId: string,
+  maxRunDurationMins: number | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskMaxRunDuration(projectId, maxRunDurationMins);
+  return invoke('update_task_max_run_duration', { projectId, maxRunDurationMins });
+}
+
+export async function updateTaskCompletionSignal(
+  projectId: string,
+  completionSignal: string,
+  extraCompletionSignals: string[],
+  isRegex: boolean,
+  mode: CompletionMode
+): Promise<ProjectState> {
+  if (isE2E) {
+    return e2eState.updateTaskCompletionSignal(
+      projectId,
+      completionSignal,
+      extraCompletionSignals,
+      isRegex,
+      mode
+    );
+  }
+  return invoke('update_task_completion_signal', {
+    projectId,
+    completionSignal,
+    extraCompletionSignals,
+    isRegex,
+    mode
+  });
+}
+
+export async function updateTaskCompletionStrategy(
+  projectId: string,
+  strategy: CompletionStrategy
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskCompletionStrategy(projectId, strategy);
+  return invoke('update_task_completion_strategy', { projectId, strategy });
+}
+
+export async function updateTaskValidationCommand(
+  projectId: string,
+  command: string | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskValidationCommand(projectId, command);
+  return invoke('update_task_validation_command', { projectId, command });
+}
+
+export async function updateTaskCarryOverProgress(
+  projectId: string,
+  enabled: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskCarryOverProgress(projectId, enabled);
+  return invoke('update_task_carry_over_progress', { projectId, enabled });
+}
+
+export async function updateTaskCompareCli(
+  projectId: string,
+  cli: CliType | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskCompareCli(projectId, cli);
+  return invoke('update_task_compare_cli', { projectId, cli });
+}
+
+export async function updateTaskReviewGate(
+  projectId: string,
+  enabled: boolean,
+  rubric: string | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskReviewGate(projectId, enabled, rubric);
+  return invoke('update_task_review_gate', { projectId, enabled, rubric });
+}
+
+export async function updateTaskSupervisor(
+  projectId: string,
+  interval: number | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskSupervisor(projectId, interval);
+  return invoke('update_task_supervisor', { projectId, interval });
+}
+
+export async function updateTaskPreIterationHook(
+  projectId: string,
+  command: string | null,
+  abortOnFailure: boolean
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskPreIterationHook(projectId, command, abortOnFailure);
+  return invoke('update_task_pre_iteration_hook', { projectId, command, abortOnFailure });
+}
+
+export async function addTaskAttachment(projectId: string, path: string): Promise<ProjectState> {
+  if (isE2E) return e2eState.addTaskAttachment(projectId, path);
+  return invoke('add_task_attachment', { projectId, path });
+}
+
+export async function removeTaskAttachment(projectId: string, name: string): Promise<ProjectState> {
+  if (isE2E) return e2eState.removeTaskAttachment(projectId, name);
+  return invoke('remove_task_attachment', { projectId, name });
+}
+
 export async function initProjectGitRepo(projectId: string): Promise<void> {
   if (isE2E) return e2eState.initProjectGitRepo(projectId);
   return invoke('init_project_git_repo', { projectId });
@@ -449,6 +1223,12 @@ export async function saveConfig(config: GlobalConfig): Promise<void> {
   return invoke('save_config', { config });
 }
 
+// Returns how many stored files were converted
+export async function setEncryptionAtRest(enabled: boolean): Promise<number> {
+  if (isE2E) return 0;
+  return invoke('set_encryption_at_rest', { enabled });
+}
+
 export async function confirmPermissions(): Promise<void> {
   if (isE2E) return e2eState.confirmPermissions();
   return invoke('confirm_permissions');
@@ -462,12 +1242,51 @@ export async function updateProjectStatus(
   return invoke('update_project_status', { projectId, status });
 }
 
+// Apply a Git repository check resolution; `restart` starts the loop again
+export async function resolveCodexGitCheck(
+  projectId: string,
+  action: GitCheckResolution,
+  restart = false
+): Promise<ProjectState> {
+  if (isE2E) {
+    if (action.type === 'skipCheck') return e2eState.setProjectSkipGitRepoCheck(projectId, true);
+    if (action.type === 'changeDirectory') return e2eState.updateProjectPath(projectId, action.path);
+    await e2eState.initProjectGitRepo(projectId);
+    return e2eState.getProject(projectId);
+  }
+  return invoke('resolve_codex_git_check', { projectId, action, restart });
+}
+
+export async function updateProjectPath(projectId: string, path: string): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateProjectPath(projectId, path);
+  return invoke('update_project_path', { projectId, path });
+}
+
 // Loop Commands
 export async function startLoop(projectId: string): Promise<void> {
   if (isE2E) return e2eState.startLoop(projectId);
   return invoke('start_loop', { projectId });
 }
 
+// Position the loop was queued at, 0 when it started right away
+export async function queueLoop(projectId: string, priority?: number): Promise<number> {
+  if (isE2E) {
+    e2eState.startLoop(projectId);
+    return 0;
+  }
+  return invoke('queue_loop', { projectId, priority });
+}
+
+export async function listQueue(): Promise<QueuedLoop[]> {
+  if (isE2E) return [];
+  return invoke('list_queue');
+}
+
+export async function startFollowup(projectId: string, suggestionIndex: number): Promise<void> {
+  if (isE2E) return e2eState.startLoop(projectId);
+  return invoke('start_followup', { projectId, suggestionIndex });
+}
+
 export async function pauseLoop(projectId: string): Promise<void> {
   if (isE2E) return e2eState.pauseLoop(projectId);
   return invoke('pause_loop', { projectId });
@@ -478,6 +1297,11 @@ export async function resumeLoop(projectId: string): Promise<void> {
   return invoke('resume_loop', { projectId });
 }
 
+export async function estimateLoopCost(projectId: string): Promise<CostEstimate> {
+  if (isE2E) return e2eState.estimateLoopCost(projectId);
+  return invoke('estimate_loop_cost', { projectId });
+}
+
 export async function stopLoop(projectId: string): Promise<void> {
   if (isE2E) return e2eState.stopLoop(projectId);
   return invoke('stop_loop', { projectId });
@@ -488,6 +1312,147 @@ export async function getLoopStatus(projectId: string): Promise<boolean> {
   return invoke('get_loop_status', { projectId });
 }
 
+export async function stopAllLoops(): Promise<number> {
+  if (isE2E) return 0;
+  return invoke('stop_all_loops');
+}
+
+export async function pauseAllLoops(): Promise<number> {
+  if (isE2E) return 0;
+  return invoke('pause_all_loops');
+}
+
+// Shortcut Commands
+export async function updateShortcutBindings(bindings: ShortcutBindings): Promise<string[]> {
+  if (isE2E) return [];
+  return invoke('update_shortcut_bindings', { bindings });
+}
+
+// Notification Commands
+export async function updateQuietHours(quietHours: QuietHours): Promise<GlobalConfig> {
+  if (isE2E) {
+    const next = { ...e2eState.getConfig(), quietHours };
+    e2eState.saveConfig(next);
+    return next;
+  }
+  return invoke('update_quiet_hours', { quietHours });
+}
+
+export async function routeNotification(
+  severity: NotificationSeverity,
+  title: string,
+  message?: string,
+  projectId?: string
+): Promise<boolean> {
+  if (isE2E) return true;
+  return invoke('route_notification', { severity, title, message, projectId });
+}
+
+export async function listenToNotificationDigest(
+  callback: (digest: QueuedNotification[]) => void
+): Promise<UnlistenFn> {
+  if (isE2E) {
+    return Promise.resolve(() => {});
+  }
+  return listen<QueuedNotification[]>('notification-digest', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Workspace Commands
+export async function listWorkspaces(): Promise<Workspace[]> {
+  if (isE2E) return [];
+  return invoke('list_workspaces');
+}
+
+export async function createWorkspace(name: string): Promise<Workspace> {
+  if (isE2E) {
+    return { id: crypto.randomUUID(), name, env: {}, createdAt: new Date().toISOString() };
+  }
+  return invoke('create_workspace', { name });
+}
+
+export async function updateWorkspace(workspace: Workspace): Promise<Workspace> {
+  if (isE2E) return workspace;
+  return invoke('update_workspace', { workspace });
+}
+
+export async function deleteWorkspace(id: string): Promise<void> {
+  if (isE2E) return;
+  return invoke('delete_workspace', { id });
+}
+
+export async function setProjectWorkspace(
+  projectId: string,
+  workspaceId: string | null
+): Promise<ProjectState> {
+  if (isE2E) {
+    const project = await getProject(projectId);
+    return { ...project, workspaceId };
+  }
+  return invoke('set_project_workspace', { projectId, workspaceId });
+}
+
+// Report Commands
+export async function generateUsageReport(month?: string): Promise<UsageReport> {
+  if (isE2E) {
+    const current = month ?? new Date().toISOString().slice(0, 7);
+    return {
+      month: current,
+      markdown: `# Ralph report: ${current}\n\nNo loops ran this month.\n`,
+      path: ''
+    };
+  }
+  return invoke('generate_usage_report', { month });
+}
+
+export async function getProjectHealth(projectId: string): Promise<ProjectHealth> {
+  if (isE2E) return { sessions: 0, score: null, recommendations: [] };
+  return invoke('get_project_health', { projectId });
+}
+
+export async function setLocalAnalytics(enabled: boolean): Promise<GlobalConfig> {
+  if (isE2E) {
+    const next = { ...e2eState.getConfig(), localAnalytics: enabled };
+    e2eState.saveConfig(next);
+    return next;
+  }
+  return invoke('set_local_analytics', { enabled });
+}
+
+export async function setKnowledgeBase(enabled: boolean): Promise<GlobalConfig> {
+  if (isE2E) {
+    const next = { ...e2eState.getConfig(), knowledgeBase: enabled };
+    e2eState.saveConfig(next);
+    return next;
+  }
+  return invoke('set_knowledge_base', { enabled });
+}
+
+export async function suggestLearnings(projectId: string): Promise<KnowledgeMatch[]> {
+  if (isE2E) return [];
+  return invoke('suggest_learnings', { projectId });
+}
+
+export async function updateTaskLearnings(
+  projectId: string,
+  learningIds: string[]
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.updateTaskLearnings(projectId, learningIds);
+  return invoke('update_task_learnings', { projectId, learningIds });
+}
+
+// Plugin Commands
+export async function listPlugins(): Promise<PluginInfo[]> {
+  if (isE2E) return [];
+  return invoke('list_plugins');
+}
+
+export async function setPluginEnabled(pluginId: string, enabled: boolean): Promise<PluginInfo[]> {
+  if (isE2E) return [];
+  return invoke('set_plugin_enabled', { pluginId, enabled });
+}
+
 // Recovery Commands
 export interface RecoveryInfo {
   projectId: string;
@@ -516,6 +1481,375 @@ export async function getProjectLogs(projectId: string): Promise<string[]> {
   return invoke('get_project_logs', { projectId });
 }
 
+export async function getCurrentLogPath(projectId: string): Promise<string> {
+  if (isE2E) return e2eState.getCurrentLogPath(projectId);
+  return invoke('get_current_log_path', { projectId });
+}
+
+export async function scanOrphanProcesses(): Promise<ProcessRecord[]> {
+  if (isE2E) return [];
+  return invoke('scan_orphan_processes');
+}
+
+export async function terminateOrphanProcesses(pids: number[]): Promise<number> {
+  if (isE2E) return 0;
+  return invoke('terminate_orphan_processes', { pids });
+}
+
+// Startup cleanup of orphaned processes, returned once
+export async function takeOrphanReport(): Promise<OrphanReport | null> {
+  if (isE2E) return null;
+  return invoke('take_orphan_report');
+}
+
+export async function listenToOrphanReport(
+  callback: (report: OrphanReport) => void
+): Promise<UnlistenFn> {
+  if (isE2E) {
+    return Promise.resolve(() => {});
+  }
+  return listen<OrphanReport>('orphan-report', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Session Commands
+export async function listSessions(projectId: string): Promise<SessionSummary[]> {
+  if (isE2E) return e2eState.listSessions(projectId);
+  return invoke('list_sessions', { projectId });
+}
+
+export async function getChangeHotspots(
+  projectId: string,
+  sessionId?: string
+): Promise<FileHotspot[]> {
+  if (isE2E) return e2eState.getChangeHotspots(projectId, sessionId);
+  return invoke('get_change_hotspots', { projectId, sessionId });
+}
+
+export async function listIterationArtifacts(
+  projectId: string,
+  sessionId?: string,
+  iteration?: number
+): Promise<IterationArtifact[]> {
+  if (isE2E) return [];
+  return invoke('list_iteration_artifacts', { projectId, sessionId, iteration });
+}
+
+export async function listHighlights(
+  projectId: string,
+  sessionId?: string
+): Promise<SessionHighlight[]> {
+  if (isE2E) return [];
+  return invoke('list_highlights', { projectId, sessionId });
+}
+
+export async function getSessionCompletion(
+  projectId: string,
+  sessionId?: string
+): Promise<CompletionPayload | null> {
+  if (isE2E) return null;
+  return invoke('get_session_completion', { projectId, sessionId });
+}
+
+export async function listSessionIterations(
+  projectId: string,
+  sessionId?: string
+): Promise<IterationRecord[]> {
+  if (isE2E) return [];
+  return invoke('list_session_iterations', { projectId, sessionId });
+}
+
+export async function listPromptRevisions(
+  projectId: string,
+  sessionId?: string
+): Promise<PromptRevision[]> {
+  if (isE2E) return [];
+  return invoke('list_prompt_revisions', { projectId, sessionId });
+}
+
+export async function getSessionSnapshot(
+  projectId: string,
+  sessionId?: string
+): Promise<SessionSnapshot | null> {
+  if (isE2E) return null;
+  return invoke('get_session_snapshot', { projectId, sessionId });
+}
+
+export async function getSuppressedCounts(
+  projectId: string,
+  sessionId?: string
+): Promise<SuppressedCount[]> {
+  if (isE2E) return [];
+  return invoke('get_suppressed_counts', { projectId, sessionId });
+}
+
+export async function addSessionNote(
+  projectId: string,
+  sessionId: string,
+  iteration: number | null,
+  text: string
+): Promise<SessionNote[]> {
+  if (isE2E) return e2eState.addSessionNote(projectId, sessionId, iteration, text);
+  return invoke('add_session_note', { projectId, sessionId, iteration, text });
+}
+
+export async function exportSessionTranscript(
+  projectId: string,
+  sessionId: string,
+  destination: string
+): Promise<void> {
+  if (isE2E) return;
+  return invoke('export_session_transcript', { projectId, sessionId, destination });
+}
+
+export async function generateSessionChangelog(
+  projectId: string,
+  sessionId: string,
+  commit: boolean
+): Promise<SessionChangelog> {
+  if (isE2E) throw new Error('Changelog generation is not available in E2E mode');
+  return invoke('generate_session_changelog', { projectId, sessionId, commit });
+}
+
+export async function generatePostmortem(
+  projectId: string,
+  sessionId: string
+): Promise<SessionPostmortem> {
+  if (isE2E) throw new Error('Postmortem generation is not available in E2E mode');
+  return invoke('generate_postmortem', { projectId, sessionId });
+}
+
+export async function getSessionPostmortem(
+  projectId: string,
+  sessionId?: string
+): Promise<SessionPostmortem | null> {
+  if (isE2E) return null;
+  return invoke('get_session_postmortem', { projectId, sessionId });
+}
+
+export async function checkoutIterationSnapshot(
+  projectId: string,
+  sessionId: string,
+  iteration: number,
+  destDir?: string
+): Promise<IterationSnapshot> {
+  if (isE2E)
+    throw new Error('Iteration snapshots are not available in E2E mode');
+  return invoke('checkout_iteration_snapshot', {
+    projectId,
+    sessionId,
+    iteration,
+    destDir
+  });
+}
+
+export async function removeIterationSnapshot(path: string): Promise<void> {
+  if (isE2E) return;
+  return invoke('remove_iteration_snapshot', { path });
+}
+
+export async function pinSession(
+  projectId: string,
+  sessionId: string,
+  pinned: boolean
+): Promise<SessionPins> {
+  if (isE2E) return e2eState.pinSession(projectId, sessionId, pinned);
+  return invoke('pin_session', { projectId, sessionId, pinned });
+}
+
+export async function pinIteration(
+  projectId: string,
+  sessionId: string,
+  iteration: number,
+  pinned: boolean
+): Promise<SessionPins> {
+  if (isE2E) return e2eState.pinIteration(projectId, sessionId, iteration, pinned);
+  return invoke('pin_iteration', { projectId, sessionId, iteration, pinned });
+}
+
+export async function listPinned(projectId?: string): Promise<PinnedSession[]> {
+  if (isE2E) return e2eState.listPinned(projectId);
+  return invoke('list_pinned', { projectId });
+}
+
+// Diagnostic Commands
+export async function listParseAnomalies(cli?: CliType): Promise<ParseAnomaly[]> {
+  if (isE2E) return e2eState.listParseAnomalies(cli);
+  return invoke('list_parse_anomalies', { cli });
+}
+
+export async function exportParseAnomalies(destination: string): Promise<number> {
+  if (isE2E) return e2eState.exportParseAnomalies(destination);
+  return invoke('export_parse_anomalies', { destination });
+}
+
+// Benchmark Commands
+export async function runBenchmark(
+  projectId: string,
+  clis: CliType[],
+  iterations: number,
+  testCommand?: string
+): Promise<BenchmarkReport> {
+  if (isE2E) throw new Error('Benchmarks are not available in E2E mode');
+  return invoke('run_benchmark', { projectId, clis, iterations, testCommand });
+}
+
+export async function runCliComparison(projectId: string): Promise<BenchmarkReport> {
+  if (isE2E) throw new Error('Benchmarks are not available in E2E mode');
+  return invoke('run_cli_comparison', { projectId });
+}
+
+export async function stopBenchmark(projectId: string): Promise<void> {
+  if (isE2E) return;
+  return invoke('stop_benchmark', { projectId });
+}
+
+export async function listBenchmarks(projectId: string): Promise<BenchmarkReport[]> {
+  if (isE2E) return [];
+  return invoke('list_benchmarks', { projectId });
+}
+
+export async function deleteBenchmark(projectId: string, benchmarkId: string): Promise<void> {
+  if (isE2E) return;
+  return invoke('delete_benchmark', { projectId, benchmarkId });
+}
+
+export async function runPromptExperiment(
+  projectId: string,
+  promptA: string,
+  promptB: string,
+  iterations: number,
+  testCommand?: string
+): Promise<PromptExperiment> {
+  if (isE2E) throw new Error('Prompt experiments are not available in E2E mode');
+  return invoke('run_prompt_experiment', { projectId, promptA, promptB, iterations, testCommand });
+}
+
+export async function listPromptExperiments(projectId: string): Promise<PromptExperiment[]> {
+  if (isE2E) return [];
+  return invoke('list_prompt_experiments', { projectId });
+}
+
+export async function deletePromptExperiment(
+  projectId: string,
+  experimentId: string
+): Promise<void> {
+  if (isE2E) return;
+  return invoke('delete_prompt_experiment', { projectId, experimentId });
+}
+
+// Consent Commands
+export async function getPendingConsents(projectId?: string): Promise<PendingConsent[]> {
+  if (isE2E) return [];
+  return invoke('get_pending_consents', { projectId: projectId ?? null });
+}
+
+export async function grantConsent(consent: PendingConsent): Promise<void> {
+  if (isE2E) return;
+  return invoke('grant_consent', { consent });
+}
+
+export async function revokeConsent(
+  capability: Capability,
+  projectId?: string
+): Promise<boolean> {
+  if (isE2E) return false;
+  return invoke('revoke_consent', { capability, projectId: projectId ?? null });
+}
+
+export async function listConsents(): Promise<ConsentGrant[]> {
+  if (isE2E) return [];
+  return invoke('list_consents');
+}
+
+// Observer Commands
+export async function getObserverStatus(): Promise<ObserverStatus> {
+  if (isE2E) return e2eState.setObserverEnabled(false);
+  return invoke('get_observer_status');
+}
+
+export async function setObserverEnabled(enabled: boolean): Promise<ObserverStatus> {
+  if (isE2E) return e2eState.setObserverEnabled(enabled);
+  return invoke('set_observer_enabled', { enabled });
+}
+
+export async function regenerateObserverToken(): Promise<ObserverStatus> {
+  if (isE2E) return e2eState.setObserverEnabled(true);
+  return invoke('regenerate_observer_token');
+}
+
+export async function observeRemoteProjects(url: string, token: string): Promise<ObservedProject[]> {
+  if (isE2E) return [];
+  return invoke('observe_remote_projects', { url, token });
+}
+
+// Capture Commands
+export async function quickTaskFromClipboard(path?: string): Promise<ProjectState> {
+  if (isE2E) throw new Error('Clipboard capture is not available in E2E mode');
+  return invoke('quick_task_from_clipboard', { path });
+}
+
+export async function listenToQuickCapture(
+  callback: (request: QuickCaptureRequest) => void
+): Promise<UnlistenFn> {
+  if (isE2E) {
+    return Promise.resolve(() => {});
+  }
+  return listen<QuickCaptureRequest>('quick-capture', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Terminal Commands
+// Returns the output so far; the rest arrives through listenToTerminalOutput
+export async function attachTerminal(projectId: string): Promise<number[]> {
+  if (isE2E) throw new Error('Interactive terminals are not available in E2E mode');
+  return invoke('attach_terminal', { projectId });
+}
+
+export async function sendTerminalInput(projectId: string, data: string): Promise<void> {
+  if (isE2E) return;
+  return invoke('send_terminal_input', { projectId, data });
+}
+
+export async function resizeTerminal(
+  projectId: string,
+  cols: number,
+  rows: number
+): Promise<void> {
+  if (isE2E) return;
+  return invoke('resize_terminal', { projectId, cols, rows });
+}
+
+export async function detachTerminal(projectId: string): Promise<void> {
+  if (isE2E) return;
+  return invoke('detach_terminal', { projectId });
+}
+
+export async function listenToTerminalOutput(
+  callback: (output: TerminalOutput) => void
+): Promise<UnlistenFn> {
+  if (isE2E) {
+    return Promise.resolve(() => {});
+  }
+  return listen<TerminalOutput>('terminal-output', (event) => {
+    callback(event.payload);
+  });
+}
+
+// Called with the project id when its attached iteration ends
+export async function listenToTerminalClosed(
+  callback: (projectId: string) => void
+): Promise<UnlistenFn> {
+  if (isE2E) {
+    return Promise.resolve(() => {});
+  }
+  return listen<string>('terminal-closed', (event) => {
+    callback(event.payload);
+  });
+}
+
 // Update Commands
 export async function getUpdateState(): Promise<UpdateState> {
   if (isE2E) return e2eState.getUpdateState();
@@ -554,6 +1888,11 @@ export interface AiBrainstormResponse {
   generatedPrompt?: string;
 }
 
+export interface VoiceBrainstormResponse {
+  transcript: string;
+  response: AiBrainstormResponse;
+}
+
 // AI Title Generation
 export async function generateProjectTitle(
   projectId: string,
@@ -572,6 +1911,28 @@ export async function aiBrainstormChat(
   return invoke('ai_brainstorm_chat', { projectId, conversation });
 }
 
+export async function transcribeAndBrainstorm(
+  projectId: string,
+  audioPath: string
+): Promise<VoiceBrainstormResponse> {
+  if (isE2E) {
+    const transcript = `Voice note: ${audioPath}`;
+    const response = await e2eState.aiBrainstormChat(projectId, [
+      { role: 'user', content: transcript }
+    ]);
+    return { transcript, response };
+  }
+  return invoke('transcribe_and_brainstorm', { projectId, audioPath });
+}
+
+export async function previewGeneratedPrompt(
+  projectId: string,
+  editedPrompt?: string
+): Promise<PromptPreview> {
+  if (isE2E) return e2eState.previewGeneratedPrompt(projectId, editedPrompt);
+  return invoke('preview_generated_prompt', { projectId, editedPrompt });
+}
+
 export async function completeAiBrainstorm(
   projectId: string,
   generatedPrompt: string,
@@ -582,6 +1943,46 @@ export async function completeAiBrainstorm(
   return invoke('complete_ai_brainstorm', { projectId, generatedPrompt, cli, maxIterations });
 }
 
+export async function getBrainstormPrompt(): Promise<string> {
+  if (isE2E) return e2eState.getBrainstormPrompt();
+  return invoke('get_brainstorm_prompt');
+}
+
+export async function setBrainstormPrompt(template: string): Promise<void> {
+  if (isE2E) return e2eState.setBrainstormPrompt(template);
+  return invoke('set_brainstorm_prompt', { template });
+}
+
+export async function resetBrainstormPrompt(): Promise<string> {
+  if (isE2E) return e2eState.resetBrainstormPrompt();
+  return invoke('reset_brainstorm_prompt');
+}
+
+export async function listBrainstormPersonas(): Promise<BrainstormPersona[]> {
+  if (isE2E) return e2eState.listBrainstormPersonas();
+  return invoke('list_brainstorm_personas');
+}
+
+export async function saveBrainstormPersona(
+  persona: BrainstormPersona
+): Promise<BrainstormPersona[]> {
+  if (isE2E) return e2eState.saveBrainstormPersona(persona);
+  return invoke('save_brainstorm_persona', { persona });
+}
+
+export async function deleteBrainstormPersona(id: string): Promise<BrainstormPersona[]> {
+  if (isE2E) return e2eState.deleteBrainstormPersona(id);
+  return invoke('delete_brainstorm_persona', { id });
+}
+
+export async function setBrainstormPersona(
+  projectId: string,
+  persona: string | null
+): Promise<ProjectState> {
+  if (isE2E) return e2eState.setBrainstormPersona(projectId, persona);
+  return invoke('set_brainstorm_persona', { projectId, persona });
+}
+
 // Event Listeners
 export async function listenToLoopEvents(
   callback: (event: LoopEvent) => void
@@ -593,3 +1994,14 @@ export async function listenToLoopEvents(
     callback(event.payload);
   });
 }
+
+export async function listenToRawLoopEvents(
+  callback: (event: RawOutputEvent) => void
+): Promise<UnlistenFn> {
+  if (isE2E) {
+    return Promise.resolve(() => {});
+  }
+  return listen<RawOutputEvent>('loop-event-raw', (event) => {
+    callback(event.payload);
+  });
+}
@@ -0,0 +1,17 @@
+import { writable } from 'svelte/store';
+import type { PendingConsent } from '$lib/types';
+
+export interface ConsentRequest {
+  projectId: string;
+  pending: PendingConsent[];
+}
+
+export const consentRequest = writable<ConsentRequest | null>(null);
+
+export function requestConsent(projectId: string, pending: PendingConsent[]) {
+  consentRequest.set({ projectId, pending });
+}
+
+export function clearConsentRequest() {
+  consentRequest.set(null);
+}
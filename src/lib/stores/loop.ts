@@ -1,6 +1,14 @@
 import { writable } from 'svelte/store';
 import type { LogEntry, ProjectStatus } from '../types';
 
+// Tokens the CLI reported for one iteration
+export interface IterationTokens {
+  iteration: number;
+  inputTokens: number;
+  outputTokens: number;
+  cacheReadTokens: number;
+}
+
 export interface LoopStoreState {
   status: ProjectStatus;
   currentIteration: number;
@@ -12,6 +20,12 @@ export interface LoopStoreState {
   elapsedMs: number | null;
   summary: string | null;
   summaryUpdatedAt: Date | null;
+  // Output was summarized while unfocused; the full lines are in the session log
+  logsStale: boolean;
+  // Latest `\r` redraw (e.g. a progress bar), replaced in place until the next line
+  progress: string | null;
+  // Per-iteration token usage of the current run, oldest first
+  tokens: IterationTokens[];
 }
 
 const createInitialState = (): LoopStoreState => ({
@@ -24,7 +38,10 @@ const createInitialState = (): LoopStoreState => ({
   endedAt: null,
   elapsedMs: null,
   summary: null,
-  summaryUpdatedAt: null
+  summaryUpdatedAt: null,
+  logsStale: false,
+  progress: null,
+  tokens: []
 });
 
 export const loopStates = writable<Record<string, LoopStoreState>>({});
@@ -62,10 +79,65 @@ export function setMaxIterations(projectId: string, max: number) {
 export function addLog(projectId: string, entry: LogEntry) {
   updateProjectState(projectId, state => ({
     ...state,
-    logs: [...state.logs.slice(-999), entry] // Keep last 1000 logs
+    logs: [...state.logs.slice(-999), entry], // Keep last 1000 logs
+    progress: null
+  }));
+}
+
+export function setProgress(projectId: string, progress: string | null) {
+  updateProjectState(projectId, state => ({ ...state, progress }));
+}
+
+// Replace the running totals of an iteration with newer ones
+export function setIterationTokens(projectId: string, usage: IterationTokens) {
+  updateProjectState(projectId, state => ({
+    ...state,
+    tokens: [...state.tokens.filter(t => t.iteration !== usage.iteration), usage]
+  }));
+}
+
+export function replaceLogs(projectId: string, logs: LogEntry[]) {
+  updateProjectState(projectId, state => ({
+    ...state,
+    logs: logs.slice(-1000),
+    logsStale: false
   }));
 }
 
+export function markLogsStale(projectId: string) {
+  updateProjectState(projectId, state => ({ ...state, logsStale: true }));
+}
+
+// Parse a session log line (`[#iteration] HH:MM:SS [OUT|ERR] content`)
+export function parseLogLine(line: string): LogEntry {
+  const match = line.match(/^\[#(\d+)\] (\d{2}:\d{2}:\d{2}) \[(OUT|ERR)\] (.*)$/);
+  if (match) {
+    const now = new Date();
+    const [hours, minutes, seconds] = match[2].split(':').map(Number);
+    const timestamp = new Date(
+      now.getFullYear(),
+      now.getMonth(),
+      now.getDate(),
+      hours,
+      minutes,
+      seconds
+    );
+
+    return {
+      iteration: parseInt(match[1]),
+      timestamp: timestamp,
+      isStderr: match[3] === 'ERR',
+      content: match[4]
+    };
+  }
+  return {
+    iteration: 0,
+    timestamp: new Date(),
+    isStderr: false,
+    content: line
+  };
+}
+
 export function setStatus(projectId: string, status: ProjectStatus) {
   updateProjectState(projectId, state => ({ ...state, status }));
 }
@@ -86,7 +158,8 @@ export function markStarted(projectId: string, startedAt: Date = new Date()) {
     elapsedMs: null,
     summary: null,
     summaryUpdatedAt: null,
-    lastError: null
+    lastError: null,
+    tokens: []
   }));
 }
 
@@ -1,4 +1,5 @@
 import { writable } from 'svelte/store';
+import { routeNotification } from '$lib/services/tauri';
 
 export interface Notification {
   id: string;
@@ -49,3 +50,22 @@ export function notifyWarning(title: string, message?: string) {
 export function notifyInfo(title: string, message?: string) {
   return addNotification({ type: 'info', title, message });
 }
+
+// Loop notifications respect quiet hours: held ones are queued by the backend
+// and arrive later through `notification-digest`
+export async function notifyRouted(
+  type: Notification['type'],
+  title: string,
+  message?: string,
+  projectId?: string
+): Promise<void> {
+  let deliver = true;
+  try {
+    deliver = await routeNotification(type, title, message, projectId);
+  } catch (error) {
+    console.error('Failed to route notification:', error);
+  }
+  if (deliver) {
+    addNotification({ type, title, message, duration: type === 'error' ? 10000 : undefined });
+  }
+}
@@ -1,6 +1,7 @@
 import { writable, derived, get } from 'svelte/store';
 import type { ProjectMeta } from '../types';
 import { startLoopWithGuard } from '$lib/services/loopStart';
+import * as api from '$lib/services/tauri';
 
 // Queue of projects waiting to run
 export const projectQueue = writable<string[]>([]);
@@ -33,6 +34,16 @@ export function enqueueProject(projectId: string) {
   processQueue();
 }
 
+// Sync with the loops the backend scheduler holds back
+export async function refreshQueue() {
+  try {
+    const queue = await api.listQueue();
+    projectQueue.set(queue.map(entry => entry.projectId));
+  } catch (error) {
+    console.error('Failed to load loop queue:', error);
+  }
+}
+
 // Remove from queue
 export function dequeueProject(projectId: string) {
   projectQueue.update(queue => queue.filter(id => id !== projectId));
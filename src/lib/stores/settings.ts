@@ -8,8 +8,11 @@ const defaultConfig: GlobalConfig = {
   maxConcurrentProjects: 3,
   iterationTimeoutMs: 0,
   idleTimeoutMs: 0,
+  firstOutputTimeoutMs: 120000,
+  maxConsecutiveFailures: 3,
   theme: 'system',
   language: 'system',
+  agentLanguage: 'auto',
   logRetentionDays: 7,
   permissionsConfirmed: false
 };
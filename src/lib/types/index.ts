@@ -1,6 +1,7 @@
 // CLI Types
-export type CliType = 'claude' | 'codex' | 'opencode';
+export type CliType = 'claude' | 'codex' | 'opencode' | 'amp' | 'qwencode' | 'iflow' | 'gemini' | 'api' | 'custom';
 export type Theme = 'light' | 'dark' | 'system';
+export type AgentLanguage = 'auto' | 'zh' | 'en' | 'ja' | 'ko';
 
 export type UpdateStatus =
   | 'idle'
@@ -46,11 +47,171 @@ export interface GlobalConfig {
   maxConcurrentProjects: number;
   iterationTimeoutMs: number;
   idleTimeoutMs: number;
+  // Retry an iteration whose CLI prints nothing this long after starting; 0 disables it
+  firstOutputTimeoutMs?: number;
+  maxConsecutiveFailures?: number;
   theme: Theme;
   language: string;
+  agentLanguage?: AgentLanguage;
   logRetentionDays: number;
   permissionsConfirmed: boolean;
   permissionsConfirmedAt?: string;
+  autoDecidePolicy?: string;
+  contextTokenBudget?: number;
+  maxPromptBytes?: number;
+  brainstormRepoContext?: boolean;
+  transcriptionCommand?: string | null;
+  observerEnabled?: boolean;
+  observerPort?: number;
+  observerToken?: string | null;
+  shortcuts?: ShortcutBindings;
+  outputFilters?: string[];
+  // Show runs of identical output lines once, followed by a repeat count
+  collapseRepeatedLines?: boolean;
+  // Set through setEncryptionAtRest so existing files are converted too
+  encryptAtRest?: boolean;
+  highlightRules?: HighlightRule[];
+  apiBackend?: ApiBackendConfig;
+  customCli?: CustomCliConfig;
+  quietHours?: QuietHours;
+  localAnalytics?: boolean;
+  // Keep what sessions learned and offer relevant learnings before a loop starts
+  knowledgeBase?: boolean;
+  // Directory names of the plugins new loops load
+  enabledPlugins?: string[];
+  env?: Record<string, string>;
+  envProfiles?: EnvProfile[];
+  commitHygiene?: CommitHygiene;
+  commitIdentity?: CommitIdentity;
+  // Reply to questions CLIs stop on, using promptAnswers
+  autoAnswerPrompts?: boolean;
+  promptAnswers?: PromptAnswer[];
+  // Terminate CLI processes a crash left running when the app starts
+  reapOrphansOnStartup?: boolean;
+}
+
+// Regex for a question a CLI asks and the reply typed in for it; Enter is added
+export interface PromptAnswer {
+  pattern: string;
+  response: string;
+}
+
+// Author of auto-commits; unset fields use the repository's git config
+export interface CommitIdentity {
+  name?: string | null;
+  email?: string | null;
+  // Add a Co-authored-by trailer naming the CLI that made the change
+  coAuthorTrailer?: boolean;
+}
+
+// Files auto-commit leaves out; deny globs also match at any depth
+export interface CommitHygiene {
+  // 0 disables the size limit
+  maxFileBytes: number;
+  denyGlobs: string[];
+  // Block commits whose added lines look like keys or tokens
+  scanSecrets: boolean;
+}
+
+// Variables applied on top of the global env for tasks selecting the profile
+export interface EnvProfile {
+  name: string;
+  env: Record<string, string>;
+}
+
+// Projects grouped under shared defaults; unset fields fall back to the global config
+export interface Workspace {
+  id: string;
+  name: string;
+  defaultCli?: CliType | null;
+  iterationTimeoutMs?: number | null;
+  idleTimeoutMs?: number | null;
+  env: Record<string, string>;
+  quietHours?: QuietHours | null;
+  createdAt: string;
+}
+
+export type NotificationSeverity = 'info' | 'success' | 'warning' | 'error';
+
+// Daily do-not-disturb window in local time; `start`/`end` are HH:MM and may wrap past midnight
+export interface QuietHours {
+  enabled: boolean;
+  start: string;
+  end: string;
+  breakThrough: NotificationSeverity[];
+}
+
+// Notification held back during quiet hours, delivered on `notification-digest`
+export interface QueuedNotification {
+  severity: NotificationSeverity;
+  title: string;
+  message?: string | null;
+  queuedAt: string;
+}
+
+// Container sandbox for loop iterations; the project is mounted at /workspace
+export interface ContainerConfig {
+  image: string;
+  network?: 'bridge' | 'none' | 'host';
+  extraArgs?: string[];
+}
+
+// SSH host a project's loops run on; git also runs there
+export interface RemoteTarget {
+  host: string;
+  user?: string | null;
+  port?: number | null;
+  identityFile?: string | null;
+  directory: string;
+}
+
+// Screenshot of a web project's running app after each iteration;
+// `captureCommand` replaces headless Chromium and gets {url} and {output}
+export interface ScreenshotConfig {
+  devCommand?: string | null;
+  url: string;
+  readyTimeoutSecs?: number;
+  captureCommand?: string | null;
+  width?: number;
+  height?: number;
+  feedBack?: boolean;
+}
+
+// Periodic loop that upgrades outdated dependencies until the tests pass
+export interface DependencyUpdateSchedule {
+  intervalDays: number;
+  lastRunAt?: string | null;
+}
+
+// Model endpoint for the `api` backend, which runs without an installed CLI
+export interface ApiBackendConfig {
+  provider: 'anthropic' | 'openAiCompatible';
+  baseUrl?: string | null;
+  model: string;
+  apiKey?: string | null;
+  maxTurns?: number;
+  maxTokens?: number;
+}
+
+// User-defined agent CLI for the `custom` backend; `{prompt}` and `{cwd}` in args are filled in
+export interface CustomCliConfig {
+  name: string;
+  executable: string;
+  args: string[];
+  readonlyArgs?: string[] | null;
+  promptViaStdin: boolean;
+  outputFormat: 'text' | 'jsonl';
+  // JSON pointers tried in order for `jsonl` output, e.g. `/message/content`
+  textPointers: string[];
+  versionArgs: string[];
+}
+
+// Global shortcut accelerators, e.g. `CommandOrControl+Alt+R`; null leaves an action unbound
+export interface ShortcutBindings {
+  stopAll?: string | null;
+  pauseActive?: string | null;
+  quickCapture?: string | null;
+  toggleWindow?: string | null;
 }
 
 // Project Types
@@ -61,6 +222,7 @@ export interface ProjectMeta {
   status: ProjectStatus;
   createdAt: string;
   lastOpenedAt: string;
+  workspaceId?: string | null;
 }
 
 export interface ProjectState {
@@ -68,7 +230,15 @@ export interface ProjectState {
   name: string;
   path: string;
   status: ProjectStatus;
+  workspaceId?: string | null;
   skipGitRepoCheck?: boolean;
+  debugRawOutput?: boolean;
+  networkBlocked?: boolean;
+  outputFilters?: string[];
+  container?: ContainerConfig | null;
+  remote?: RemoteTarget | null;
+  screenshot?: ScreenshotConfig | null;
+  dependencyUpdates?: DependencyUpdateSchedule | null;
   brainstorm?: BrainstormState;
   task?: TaskConfig;
   execution?: ExecutionState;
@@ -79,6 +249,21 @@ export interface ProjectState {
 export interface BrainstormState {
   answers: BrainstormAnswer[];
   completedAt?: string;
+  persona?: string | null;
+  draftPrompt?: string | null;
+}
+
+export interface PromptPreview {
+  prompt: string;
+  tokenEstimate: number;
+  warnings: string[];
+}
+
+export interface BrainstormPersona {
+  id: string;
+  name: string;
+  prompt: string;
+  builtIn?: boolean;
 }
 
 export interface BrainstormAnswer {
@@ -95,7 +280,70 @@ export interface TaskConfig {
   maxIterations: number;
   autoCommit?: boolean;
   autoInitGit?: boolean;
+  autoDecide?: boolean;
+  firstIterationPrompt?: string | null;
+  subsequentIterationPrompt?: string | null;
+  warmupContext?: boolean;
+  repoContext?: boolean;
   completionSignal: string;
+  completionSignalRegex?: boolean;
+  extraCompletionSignals?: string[];
+  completionMode?: CompletionMode;
+  completionStrategy?: CompletionStrategy;
+  // Run after every iteration; its failure output goes into the next prompt
+  validationCommand?: string | null;
+  // Summarize each iteration's output into the next prompt
+  carryOverProgress?: boolean;
+  // Review each auto-commit's diff against reviewRubric; blockers keep it uncommitted
+  reviewGate?: boolean;
+  reviewRubric?: string | null;
+  // Second CLI runCliComparison runs the task with
+  compareCli?: CliType | null;
+  // Review the loop every this many iterations and let a supervisor revise the prompt
+  supervisorInterval?: number | null;
+  // Run before every iteration; a failure skips the iteration when preIterationAbortOnFailure is set
+  preIterationCommand?: string | null;
+  preIterationAbortOnFailure?: boolean;
+  attachments?: string[];
+  workingSubdir?: string | null;
+  artifacts?: string[];
+  targets?: LoopTarget[];
+  priority?: ProcessPriority;
+  // Memory the CLI may use, in MB; unlimited when unset
+  memoryLimitMb?: number | null;
+  // Wall-clock minutes a run may take; it stops after the iteration in progress
+  maxRunDurationMins?: number | null;
+  // Knowledge base entries listed in the prompt as learnings from past sessions
+  learningIds?: string[];
+  // Executable run for `cli` instead of the one found on PATH
+  cliPathOverride?: string | null;
+  // Name of a GlobalConfig.envProfiles entry applied to the CLI
+  envProfile?: string | null;
+  // Write each session's outcome to .ralph/status.json in the project
+  writeStatusFile?: boolean;
+  // Run iterations in a terminal the app can attach to
+  interactiveTerminal?: boolean;
+  // Write or update the README after a completed loop, committed on its own
+  generateReadme?: boolean;
+}
+
+// When a loop counts as done; the completion signal is honored under every strategy
+export type CompletionStrategy =
+  | { type: 'signal' }
+  | { type: 'cleanExit' }
+  | { type: 'noOpStreak'; iterations: number };
+
+// Whether any completion signal ends a task, or only all of them in one iteration
+export type CompletionMode = 'any' | 'all';
+
+// Scheduling priority of the spawned CLI
+export type ProcessPriority = 'normal' | 'low' | 'background';
+
+// Monorepo package a task runs as its own sequential session
+export interface LoopTarget {
+  name: string;
+  subdir: string;
+  promptSuffix?: string | null;
 }
 
 export interface ExecutionState {
@@ -106,6 +354,352 @@ export interface ExecutionState {
   lastOutput: string;
   lastError?: string;
   lastExitCode?: number;
+  sessionId?: string;
+  target?: string | null;
+}
+
+export interface SessionNote {
+  iteration?: number | null;
+  text: string;
+  createdAt: string;
+}
+
+export interface SessionPins {
+  pinned: boolean;
+  iterations: number[];
+}
+
+export interface SessionSummary {
+  id: string;
+  notes: SessionNote[];
+  pins: SessionPins;
+  target?: string | null;
+}
+
+export interface PinnedSession {
+  projectId: string;
+  sessionId: string;
+  pins: SessionPins;
+}
+
+export type HighlightSeverity = 'info' | 'warning' | 'error';
+
+// Regex flagging matching output lines with a label such as `error` or `todo`
+export interface HighlightRule {
+  pattern: string;
+  label: string;
+  severity: HighlightSeverity;
+}
+
+export interface Highlight {
+  label: string;
+  severity: HighlightSeverity;
+}
+
+// File copied into the session after an iteration; `path` is relative to the working directory
+export interface IterationArtifact {
+  iteration: number;
+  path: string;
+  storedPath: string;
+  sizeBytes: number;
+}
+
+export type BenchmarkOutcome = 'completed' | 'maxIterationsReached' | 'failed' | 'stopped' | 'error';
+
+// One CLI's run within a benchmark
+export interface BenchmarkResult {
+  cli: CliType;
+  outcome: BenchmarkOutcome;
+  iterations: number;
+  durationMs: number;
+  testsPassed?: boolean | null;
+  costUsd?: number | null;
+  sessionId?: string | null;
+  worktreePath: string;
+  // What the run changed from the base commit
+  diff?: DiffStats | null;
+  error?: string | null;
+}
+
+export interface DiffStats {
+  insertions: number;
+  deletions: number;
+  files: string[];
+}
+
+// Where the runs of a benchmark went different ways
+export interface Divergence {
+  sharedFiles: string[];
+  uniqueFiles: { cli: CliType; files: string[] }[];
+  // null unless every run was tested
+  testsDiffer?: boolean | null;
+}
+
+// Same task run across several CLIs; re-sent on `benchmark-progress` as CLIs finish
+export interface BenchmarkReport {
+  id: string;
+  projectId: string;
+  startedAt: string;
+  finishedAt?: string | null;
+  clis: CliType[];
+  maxIterations: number;
+  testCommand?: string | null;
+  results: BenchmarkResult[];
+  // Set once the benchmark finished with two runs that changed code
+  divergence?: Divergence | null;
+}
+
+export interface PromptVariant {
+  label: string;
+  prompt: string;
+}
+
+// Prompt variants run from one commit; re-sent on `prompt-experiment-progress`
+export interface PromptExperiment {
+  id: string;
+  projectId: string;
+  startedAt: string;
+  finishedAt?: string | null;
+  cli: CliType;
+  baseCommit: string;
+  maxIterations: number;
+  testCommand?: string | null;
+  variants: PromptVariant[];
+  // In variant order
+  results: BenchmarkResult[];
+}
+
+// What the agent reported when it finished with `<done>{json}</done>`; `confidence` is 0-1
+export interface CompletionPayload {
+  summary?: string | null;
+  files: string[];
+  followUps: string[];
+  confidence?: number | null;
+}
+
+// Highlighted output line; `line` counts output lines within the iteration
+export interface SessionHighlight {
+  iteration: number;
+  line: number;
+  label: string;
+  severity: HighlightSeverity;
+  content: string;
+}
+
+// Output lines hidden by one filter pattern during a session
+export interface SuppressedCount {
+  pattern: string;
+  count: number;
+}
+
+export interface FileHotspot {
+  path: string;
+  changes: number;
+}
+
+// Project summary served by the read-only observer feed
+export interface ObservedProject {
+  id: string;
+  name: string;
+  status: ProjectStatus;
+  currentIteration?: number | null;
+  sessionId?: string | null;
+  target?: string | null;
+  updatedAt: string;
+}
+
+export interface IterationUsage {
+  iteration: number;
+  cli: CliType;
+  inputTokens: number;
+  outputTokens: number;
+  cacheReadTokens?: number;
+  costUsd?: number | null;
+}
+
+export type IterationOutcome =
+  | 'running'
+  | 'continued'
+  | 'completed'
+  | 'failed'
+  | 'skipped'
+  | 'stopped';
+
+// One iteration of a session; endedAt is null while it runs
+// A change the supervisor made to a session's working prompt
+export interface PromptRevision {
+  iteration: number;
+  revisedAt: string;
+  previous: string;
+  prompt: string;
+  reason: string;
+}
+
+export interface IterationRecord {
+  number: number;
+  startedAt: string;
+  endedAt?: string | null;
+  exitCode?: number | null;
+  commitShas: string[];
+  tokens?: IterationUsage | null;
+  // Whether the validation command passed after it; null when none ran
+  validationPassed?: boolean | null;
+  outcome: IterationOutcome;
+}
+
+// Something a past session learned: its completion summary or a fix it committed
+export interface KnowledgeEntry {
+  id: string;
+  projectId: string;
+  sessionId: string;
+  kind: 'summary' | 'fix';
+  text: string;
+  createdAt: string;
+}
+
+export interface KnowledgeMatch {
+  entry: KnowledgeEntry;
+  score: number;
+  projectName?: string | null;
+}
+
+export type PluginHook = 'beforePrompt' | 'beforeCommit';
+
+// plugin.json in a plugin's directory
+export interface PluginManifest {
+  name: string;
+  description: string;
+  command: string;
+  args: string[];
+  // Loop event types forwarded to the plugin; '*' for all
+  events: string[];
+  hooks: PluginHook[];
+}
+
+export interface PluginInfo {
+  id: string;
+  dir: string;
+  enabled: boolean;
+  manifest?: PluginManifest | null;
+  error?: string | null;
+}
+
+export interface SessionMetrics {
+  projectId: string;
+  sessionId: string;
+  cli: CliType;
+  startedAt: string;
+  endedAt: string;
+  status: string;
+  iterations: number;
+  commits: number;
+  // Iterations that ended with a question for the user
+  questionsAsked?: number;
+  inputTokens: number;
+  outputTokens: number;
+  costUsd?: number | null;
+}
+
+// What a session started with; `settings` holds the loop config values in effect
+export interface SessionSnapshot {
+  capturedAt: string;
+  appVersion: string;
+  cli: CliType;
+  cliVersion?: string | null;
+  model?: string | null;
+  gitHead?: string | null;
+  promptSha256: string;
+  settings: Record<string, string>;
+  previousCliVersion?: string | null;
+}
+
+// Loop waiting for a free slot; higher priorities start first
+export interface QueuedLoop {
+  projectId: string;
+  priority: number;
+  prompt?: string | null;
+  completionCheck?: string | null;
+  readonly?: boolean;
+  artifacts?: string[];
+  queuedAt: string;
+}
+
+// CHANGELOG entry written for a session; `commit` is set when it was committed
+export interface SessionChangelog {
+  entry: string;
+  path: string;
+  commit?: string | null;
+}
+
+// Why a failed or exhausted session did not complete, with suggested changes
+export interface SessionPostmortem {
+  sessionId: string;
+  status: string;
+  markdown: string;
+  path: string;
+}
+
+// The repository as of one iteration, checked out into a detached worktree
+export interface IterationSnapshot {
+  projectId: string;
+  sessionId: string;
+  iteration: number;
+  commit: string;
+  projectPath: string;
+  path: string;
+  createdAt: string;
+}
+
+// Read-only snapshot of a project, also served at `/api/projects/{id}/bundle`
+export interface ProjectBundle {
+  state: ProjectState;
+  sessionId?: string | null;
+  usage: IterationUsage[];
+  recentSessions: SessionMetrics[];
+  lastCompletion?: CompletionPayload | null;
+  snapshot?: SessionSnapshot | null;
+}
+
+// How to get past Codex refusing to run outside a Git repository
+export type GitCheckResolution =
+  | { type: 'initRepo' }
+  | { type: 'skipCheck' }
+  | { type: 'changeDirectory'; path: string };
+
+// Risky capability acknowledged once where it is used
+export type Capability =
+  | 'fullDiskAccess'
+  | 'remoteExecution'
+  | 'observerFeed'
+  | 'pluginExecution';
+
+// Capability in use without a matching acknowledgment
+export interface PendingConsent {
+  capability: Capability;
+  projectId: string | null;
+  // What was acknowledged, e.g. the remote host; a new value asks again
+  scope: string | null;
+}
+
+export interface ConsentGrant extends PendingConsent {
+  grantedAt: string;
+}
+
+export interface ObserverStatus {
+  enabled: boolean;
+  running: boolean;
+  port: number;
+  token?: string | null;
+}
+
+// Output line a CLI adapter could not fully parse, aggregated by kind
+export interface ParseAnomaly {
+  cli: CliType;
+  kind: string;
+  count: number;
+  sample: string;
+  firstSeen: string;
+  lastSeen: string;
 }
 
 // CLI Info
@@ -120,14 +714,24 @@ export interface CliInfo {
 // Loop Events
 export type LoopEventType =
   | 'iterationStart'
+  | 'iterationEnd'
   | 'output'
   | 'pausing'
   | 'paused'
   | 'resumed'
   | 'completed'
   | 'maxIterationsReached'
+  | 'timeBudgetReached'
   | 'error'
-  | 'stopped';
+  | 'stopped'
+  | 'queued'
+  | 'portConflict'
+  | 'outputSummary'
+  | 'pathMissing'
+  | 'cliVersionChanged'
+  | 'promptRevised'
+  | 'progress'
+  | 'usage';
 
 export interface LoopEvent {
   type: LoopEventType;
@@ -135,7 +739,133 @@ export interface LoopEvent {
   iteration?: number;
   content?: string;
   isStderr?: boolean;
+  highlight?: Highlight;
   error?: string;
+  stats?: IterationStats;
+  // portConflict: the taken port and, when found, the process holding it
+  port?: number;
+  pid?: number | null;
+  program?: string | null;
+  message?: string;
+  // outputSummary: lines held back while the window was unfocused
+  lines?: number;
+  lastLine?: string;
+  // pathMissing: the project directory that disappeared
+  path?: string;
+  // queued: 1-based place in the loop queue
+  position?: number;
+  // completed: the structured payload, when the agent sent one
+  payload?: CompletionPayload;
+  // cliVersionChanged: versions of the last successful session and now
+  cli?: CliType;
+  previous?: string;
+  current?: string;
+  // promptRevised: the new working prompt (previous holds the old one) and why
+  prompt?: string;
+  reason?: string;
+  // usage: running token totals of the iteration; input includes cache reads
+  inputTokens?: number;
+  outputTokens?: number;
+  cacheReadTokens?: number;
+}
+
+// Summary sent with `iterationEnd`
+export interface IterationStats {
+  durationMs: number;
+  stdoutLines: number;
+  stderrLines: number;
+  suppressedLines: number;
+  exitCode?: number | null;
+  commitSha?: string | null;
+  costUsd?: number | null;
+  inputTokens?: number | null;
+  outputTokens?: number | null;
+}
+
+// Expected spend of the configured loop; `lowUsd`/`highUsd` are null when nothing prices the CLI
+export interface CostEstimate {
+  cli: CliType;
+  maxIterations: number;
+  promptTokens: number;
+  sampledIterations: number;
+  typicalIterations?: number | null;
+  inputTokensPerIteration: number;
+  outputTokensPerIteration: number;
+  lowUsd?: number | null;
+  highUsd?: number | null;
+}
+
+// Local monthly report rendered from the sessions recorded on this machine; `month` is YYYY-MM
+export type RecommendationKind =
+  | 'shortenPrompt'
+  | 'enableTestGate'
+  | 'reviewValidation'
+  | 'carryOverProgress'
+  | 'reduceIterations';
+
+export interface HealthRecommendation {
+  kind: RecommendationKind;
+  message: string;
+}
+
+// Ratios run from 0 to 1 and are null without the data to compute them
+export interface ProjectHealth {
+  sessions: number;
+  // 0-100; null before the first session finished
+  score?: number | null;
+  successRatio?: number | null;
+  stallFrequency?: number | null;
+  testPassRate?: number | null;
+  testPassTrend?: number | null;
+  costEfficiency?: number | null;
+  recommendations: HealthRecommendation[];
+}
+
+export interface UsageReport {
+  month: string;
+  markdown: string;
+  path: string;
+}
+
+// Raw (unparsed) CLI output, emitted on `loop-event-raw`
+export interface RawOutputEvent {
+  projectId: string;
+  iteration: number;
+  line: string;
+  isStderr: boolean;
+}
+
+// CLI process left running by an earlier session, emitted on `orphan-processes`
+export interface ProcessRecord {
+  pid: number;
+  program: string;
+  projectId: string;
+  appPid: number;
+  startedAt: string;
+  processStarted?: string | null;
+  commandLine?: string | null;
+}
+
+// Orphaned processes cleaned up at startup, emitted on `orphan-report`
+export interface OrphanReport {
+  terminated: ProcessRecord[];
+  failed: OrphanFailure[];
+}
+
+export interface OrphanFailure {
+  process: ProcessRecord;
+  error: string;
+}
+
+// Clipboard capture awaiting confirmation, emitted on `quick-capture`
+export interface QuickCaptureRequest {
+  preview: string;
+}
+
+// Raw bytes from an attached terminal, emitted on `terminal-output`
+export interface TerminalOutput {
+  projectId: string;
+  data: number[];
 }
 
 // Log Entry